@@ -4,6 +4,7 @@ import {
   editorInteractionStateUuidAtom,
 } from '@/app/atoms/editorInteractionStateAtom';
 import { events } from '@/app/events/events';
+import { sheets } from '@/app/grid/controller/Sheets';
 import { pixiApp } from '@/app/gridGL/pixiApp/PixiApp';
 import { QuadraticLoading } from '@/app/ui/loading/QuadraticLoading';
 import QuadraticUIContext from '@/app/ui/QuadraticUIContext';
@@ -11,12 +12,17 @@ import { javascriptWebWorker } from '@/app/web-workers/javascriptWebWorker/javas
 import { multiplayer } from '@/app/web-workers/multiplayerWebWorker/multiplayer';
 import { MultiplayerState } from '@/app/web-workers/multiplayerWebWorker/multiplayerClientMessages';
 import { pythonWebWorker } from '@/app/web-workers/pythonWebWorker/pythonWebWorker';
+import { quadraticCore } from '@/app/web-workers/quadraticCore/quadraticCore';
 import { useRootRouteLoaderData } from '@/routes/_root';
+import { useInterval } from '@/shared/hooks/useInterval';
 import { useEffect, useRef, useState } from 'react';
 import { isMobile } from 'react-device-detect';
 import { useRecoilValue } from 'recoil';
 import { v4 } from 'uuid';
 
+// how often to check for code cells that refresh on a schedule
+const REFRESH_CODE_CELLS_INTERVAL_MS = 60 * 1000;
+
 export function QuadraticApp() {
   const didMount = useRef<boolean>(false);
   const permissions = useRecoilValue(editorInteractionStatePermissionsAtom);
@@ -95,8 +101,26 @@ export function QuadraticApp() {
     }
   }, [multiplayerLoading]);
 
+  // rerun code cells that refresh when the file is opened, then the ones that
+  // refresh on a schedule; only one client in the room refreshes them
+  const loading = offlineLoading || multiplayerLoading;
+  const canEdit = hasPermissionToEditFile(permissions);
+  useEffect(() => {
+    if (!loading && canEdit && multiplayer.isRefreshLeader()) {
+      quadraticCore.refreshCodeCells(true, sheets.getCursorPosition());
+    }
+  }, [loading, canEdit]);
+  useInterval(
+    () => {
+      if (multiplayer.isRefreshLeader()) {
+        quadraticCore.refreshCodeCells(false, sheets.getCursorPosition());
+      }
+    },
+    !loading && canEdit ? REFRESH_CODE_CELLS_INTERVAL_MS : null
+  );
+
   // Show loading screen until everything is loaded
-  if (offlineLoading || multiplayerLoading) {
+  if (loading) {
     return <QuadraticLoading />;
   }
   return <QuadraticUIContext />;
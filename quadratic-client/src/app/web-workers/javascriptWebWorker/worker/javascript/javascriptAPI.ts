@@ -3,10 +3,10 @@
 // (x,y) position with the code, so `pos()` and `relCell()` can be calculated
 // within the worker using getCells.
 
-import { JsGetCellResponse } from '@/app/quadratic-core-types';
 import { javascriptClient } from '../javascriptClient';
 import { javascriptCore } from '../javascriptCore';
 import { Javascript } from './javascript';
+import { javascriptArrowToCells } from './javascriptArrow';
 
 export type CellType = number | string | boolean | Date | undefined;
 export type CellPos = { x: number; y: number };
@@ -18,12 +18,10 @@ export class JavascriptAPI {
     this.javascript = javascript;
   }
 
-  private convertType(entry: JsGetCellResponse): CellType | undefined {
-    if (entry.type_name === 'blank') return undefined;
-    if (entry.type_name === 'date time' || entry.type_name === 'date')
-      return `___date___${new Date(entry.value).getTime()}`;
-
-    return entry.type_name === 'number' ? parseFloat(entry.value) : entry.value;
+  // Dates are sent to the runner as JSON, so they're marked to be converted
+  // back to Dates there.
+  private convertType(value: CellType): CellType {
+    return value instanceof Date ? `___date___${value.getTime()}` : value;
   }
 
   getCellsA1 = async (
@@ -34,30 +32,16 @@ export class JavascriptAPI {
       throw new Error('No transactionId in getCellsA1');
     }
 
-    const results = await javascriptCore.sendGetCellsA1(this.javascript.transactionId, a1, lineNumber);
+    const bytes = await javascriptCore.sendGetCellsA1(this.javascript.transactionId, a1, lineNumber);
 
     // error was thrown while getting cells
-    if (!results) {
+    if (!bytes) {
       javascriptClient.sendState('ready');
       return undefined;
     }
 
-    const cells: CellType[][] = [];
-    const height = results.y + results.h;
-    const width = results.x + results.w;
-
-    for (let y = results.y; y < height; y++) {
-      const row: CellType[] = [];
-
-      for (let x = results.x; x < width; x++) {
-        const entry = results.cells?.find((r) => Number(r.x) === x && Number(r.y) === y);
-        const typed = entry ? this.convertType(entry) : undefined;
-        row.push(typed);
-      }
-
-      cells.push(row);
-    }
-
+    const results = javascriptArrowToCells(bytes);
+    const cells = results.cells.map((row) => row.map((value) => this.convertType(value)));
     return { cells, two_dimensional: results.two_dimensional };
   };
 }
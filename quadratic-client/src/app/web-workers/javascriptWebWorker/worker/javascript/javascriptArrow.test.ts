@@ -0,0 +1,28 @@
+import { describe, expect, it } from 'vitest';
+import { javascriptArrowToCells, javascriptOutputToArrow } from './javascriptArrow';
+
+describe('javascriptArrow', () => {
+  it('should keep the types of the output', () => {
+    const date = new Date('2024-03-10T08:30:15.250Z');
+    const bytes = javascriptOutputToArrow([
+      [
+        ['1.5', 'number'],
+        ['hello', 'text'],
+      ],
+      [
+        ['2', 'number'],
+        ['true', 'logical'],
+      ],
+      [
+        ['', 'blank'],
+        [date.toISOString(), 'date time'],
+      ],
+    ]);
+    const { cells } = javascriptArrowToCells(bytes);
+    expect(cells).toEqual([
+      [1.5, 'hello'],
+      [2, true],
+      [undefined, date],
+    ]);
+  });
+});
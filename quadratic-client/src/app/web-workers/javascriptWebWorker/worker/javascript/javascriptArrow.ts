@@ -0,0 +1,203 @@
+// Converts cells to and from the Arrow IPC streams that core uses to pass
+// typed values to code cells (see quadratic-core/src/values/arrow.rs). A column
+// of one type uses that Arrow type; mixed columns are dense unions whose
+// children are named after the cell type.
+
+import {
+  Bool,
+  DataType,
+  DenseUnion,
+  Field,
+  Float64,
+  Null,
+  Table,
+  TimestampMillisecond,
+  Utf8,
+  Vector,
+  makeData,
+  makeVector,
+  tableFromIPC,
+  tableToIPC,
+  vectorFromArray,
+} from 'apache-arrow';
+import type { CellType } from './javascriptAPI';
+
+export interface ArrowCells {
+  cells: CellType[][];
+  x: number;
+  y: number;
+  w: number;
+  h: number;
+  two_dimensional: boolean;
+}
+
+// Converts a Decimal128 (four little-endian 32-bit words) to a number.
+function decimalToNumber(words: Uint32Array, scale: number): number {
+  let value = 0n;
+  for (let i = 3; i >= 0; i--) {
+    value = (value << 32n) | BigInt(words[i]);
+  }
+  if (words[3] & 0x80000000) {
+    value -= 1n << 128n;
+  }
+  const negative = value < 0n;
+  let digits = (negative ? -value : value).toString();
+  if (scale > 0) {
+    digits = digits.padStart(scale + 1, '0');
+    digits = `${digits.slice(0, -scale)}.${digits.slice(-scale)}`;
+  } else if (scale < 0) {
+    digits += '0'.repeat(-scale);
+  }
+  return Number(negative ? `-${digits}` : digits);
+}
+
+// Converts a time of day to HH:MM:SS.sss.
+function timeToString(value: number | bigint, unit: number): string {
+  const milliseconds = (Number(value) * 1000) / 1000 ** unit;
+  return new Date(milliseconds).toISOString().slice(11, 23);
+}
+
+// Converts a month-day-nanosecond interval to a duration string.
+function intervalToString(value: any): string {
+  let months: number, days: number, nanoseconds: number;
+  if (typeof value?.months === 'number') {
+    ({ months, days } = value);
+    nanoseconds = Number(value.nanoseconds);
+  } else {
+    // [months, days, nanoseconds (low word), nanoseconds (high word)]
+    months = value[0];
+    days = value[1];
+    nanoseconds = (value[2] >>> 0) + value[3] * 2 ** 32;
+  }
+  return `${months}mo ${days}d ${nanoseconds / 1e9}s`;
+}
+
+// Converts an Arrow value to a cell. `name` is the name of the union child the
+// value is in, which tells errors, HTML and images apart from text.
+function cellValue(type: DataType, name: string, value: any): CellType {
+  if (value === null || value === undefined) return undefined;
+  if (name === 'error') return '[error]';
+  if (name === 'html' || name === 'image') return '';
+  if (DataType.isDecimal(type)) return decimalToNumber(value, type.scale);
+  if (DataType.isInt(type) || DataType.isFloat(type)) return Number(value);
+  if (DataType.isBool(type)) return Boolean(value);
+  if (DataType.isDate(type) || DataType.isTimestamp(type)) {
+    return value instanceof Date ? value : new Date(Number(value));
+  }
+  if (DataType.isTime(type)) return timeToString(value, type.unit);
+  if (DataType.isInterval(type)) return intervalToString(value);
+  return String(value);
+}
+
+function columnValues(vector: Vector): CellType[] {
+  const values: CellType[] = [];
+  let row = 0;
+  for (const data of vector.data) {
+    for (let i = 0; i < data.length; i++, row++) {
+      let type: DataType = data.type;
+      let name = '';
+      if (DataType.isUnion(type)) {
+        const field = type.children[type.typeIdToChildIndex[data.typeIds[i]]];
+        type = field.type;
+        name = field.name;
+      }
+      values.push(cellValue(type, name, vector.get(row)));
+    }
+  }
+  return values;
+}
+
+// Reads the cells returned by core's calculationGetCellsA1Arrow.
+export function javascriptArrowToCells(bytes: Uint8Array): ArrowCells {
+  const table = tableFromIPC(bytes);
+  const metadata = table.schema.metadata;
+  const columns = table.schema.fields.map((_, i) => columnValues(table.getChildAt(i) as Vector));
+  const cells: CellType[][] = [];
+  for (let y = 0; y < table.numRows; y++) {
+    cells.push(columns.map((column) => column[y]));
+  }
+  return {
+    cells,
+    x: Number(metadata.get('x')),
+    y: Number(metadata.get('y')),
+    w: Number(metadata.get('w')),
+    h: Number(metadata.get('h')),
+    two_dimensional: metadata.get('two_dimensional') === 'true',
+  };
+}
+
+// Arrow types of the output types of javascriptConvertOutputType.
+function outputArrowType(kind: string): DataType {
+  switch (kind) {
+    case 'number':
+      return new Float64();
+    case 'logical':
+      return new Bool();
+    case 'date time':
+      return new TimestampMillisecond();
+    case 'text':
+    case 'image':
+      return new Utf8();
+    default:
+      return new Null();
+  }
+}
+
+function outputValue([value, kind]: [string, string]): number | string | boolean | null {
+  switch (kind) {
+    case 'number':
+      return parseFloat(value);
+    case 'logical':
+      return value === 'true';
+    case 'date time':
+      return new Date(value).getTime();
+    case 'text':
+    case 'image':
+      return value;
+    default:
+      return null;
+  }
+}
+
+function outputKind([, kind]: [string, string]): string {
+  return ['number', 'logical', 'date time', 'text', 'image'].includes(kind) ? kind : 'blank';
+}
+
+function outputColumn(column: [string, string][]): Vector {
+  const kinds = [...new Set(column.map(outputKind))];
+  const present = kinds.filter((kind) => kind !== 'blank');
+  if (present.length === 0 || (present.length === 1 && present[0] !== 'image')) {
+    return vectorFromArray(column.map(outputValue), outputArrowType(present[0] ?? 'blank'));
+  }
+
+  const children = kinds.map((kind) =>
+    vectorFromArray(column.filter((value) => outputKind(value) === kind).map(outputValue), outputArrowType(kind))
+  );
+  const typeIds = Int8Array.from(column, (value) => kinds.indexOf(outputKind(value)));
+  const lengths = kinds.map(() => 0);
+  const valueOffsets = Int32Array.from(typeIds, (typeId) => lengths[typeId]++);
+  const type = new DenseUnion(
+    kinds.map((_, i) => i),
+    kinds.map((kind, i) => new Field(kind, children[i].type, true))
+  );
+  return makeVector(
+    makeData({
+      type,
+      length: column.length,
+      typeIds,
+      valueOffsets,
+      children: children.map((child) => child.data[0]),
+    })
+  );
+}
+
+// Writes the output of javascriptConvertOutputArray (rows of values and their
+// types) as an Arrow IPC stream for core's calculationCompleteArrow.
+export function javascriptOutputToArrow(output: [string, string][][]): Uint8Array {
+  const width = Math.max(0, ...output.map((row) => row.length));
+  const columns: Record<string, Vector> = {};
+  for (let x = 0; x < width; x++) {
+    columns[String(x)] = outputColumn(output.map((row) => row[x] ?? ['', 'blank']));
+  }
+  return tableToIPC(new Table(columns), 'stream');
+}
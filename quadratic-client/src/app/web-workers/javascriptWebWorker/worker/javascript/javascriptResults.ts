@@ -3,6 +3,7 @@
 import { JsCodeResult } from '@/app/quadratic-core-types';
 import { javascriptClient } from '../javascriptClient';
 import { javascriptCore } from '../javascriptCore';
+import { javascriptOutputToArrow } from './javascriptArrow';
 import { javascriptConvertOutputArray, javascriptConvertOutputType } from './javascriptOutput';
 
 export function javascriptErrorResult(transactionId: string, message: string, lineNumber?: number) {
@@ -32,13 +33,16 @@ export function javascriptResults(
   const message: string[] = [];
   const outputType = javascriptConvertOutputType(message, result, x, y);
   const outputArray = javascriptConvertOutputArray(message, result, x, y);
+
+  // arrays are sent to core as Arrow so their values keep their types
+  const outputArrow = outputArray ? javascriptOutputToArrow(outputArray.output) : undefined;
   const codeResult: JsCodeResult = {
     transaction_id: transactionId,
     success: true,
     output_value: outputType?.output ? outputType.output : null,
     std_out: (consoleOutput ? consoleOutput : '') + (message.length ? message.join('\n') : ''),
     std_err: null,
-    output_array: outputArray ? outputArray.output : null,
+    output_array: null,
 
     // lineNumber is tricky because of the hacky way we count it. A return on line 0
     // will never increment the line number, which is why we have to increment it.
@@ -47,6 +51,6 @@ export function javascriptResults(
     output_display_type: outputType?.displayType || outputArray?.displayType || null,
     cancel_compute: false,
   };
-  javascriptCore.sendJavascriptResults(transactionId, codeResult);
+  javascriptCore.sendJavascriptResults(transactionId, codeResult, outputArrow);
   javascriptClient.sendState('ready', { current: undefined });
 }
@@ -1,5 +1,5 @@
 import { debugWebWorkers, debugWebWorkersMessages } from '@/app/debugFlags';
-import { JsCodeResult, JsGetCellResponse } from '@/app/quadratic-core-types';
+import { JsCodeResult } from '@/app/quadratic-core-types';
 import {
   CoreJavascriptGetCellsA1,
   CoreJavascriptMessage,
@@ -51,14 +51,15 @@ class JavascriptCore {
     console.warn("[javascriptCore] didn't handle message", e.data);
   };
 
-  sendJavascriptResults(transactionId: string, results: JsCodeResult, transfer?: Transferable) {
+  sendJavascriptResults(transactionId: string, results: JsCodeResult, outputArrow?: Uint8Array) {
     this.send(
       {
         type: 'javascriptCoreResults',
         transactionId,
         results,
+        outputArrow,
       },
-      transfer
+      outputArrow?.buffer
     );
   }
 
@@ -66,30 +67,11 @@ class JavascriptCore {
     transactionId: string,
     a1: string,
     lineNumber?: number
-  ): Promise<
-    { cells: JsGetCellResponse[]; x: number; y: number; w: number; h: number; two_dimensional: boolean } | undefined
-  > {
+  ): Promise<Uint8Array | undefined> {
     return new Promise((resolve) => {
       const id = this.id++;
       this.waitingForResponse[id] = (message: CoreJavascriptGetCellsA1) => {
-        if (
-          message.cells !== undefined &&
-          message.x !== undefined &&
-          message.y !== undefined &&
-          message.w !== undefined &&
-          message.h !== undefined
-        ) {
-          resolve({
-            cells: message.cells,
-            x: message.x,
-            y: message.y,
-            w: message.w,
-            h: message.h,
-            two_dimensional: message.two_dimensional ?? false,
-          });
-        } else {
-          resolve(undefined);
-        }
+        resolve(message.cells);
       };
       this.send({ type: 'javascriptCoreGetCellsA1', transactionId, id, a1, lineNumber });
     });
@@ -199,6 +199,19 @@ export class Multiplayer {
     return Array.from(this.users.values());
   }
 
+  // Only one client refreshes code cells (see quadraticCore.refreshCodeCells)
+  // so that they don't run once per user: the editor who has been in the room
+  // the longest. Without a connection, this client refreshes them.
+  isRefreshLeader(): boolean {
+    if (this.index === undefined) return true;
+    for (const player of this.users.values()) {
+      if (player.index < this.index && player.permissions?.includes('FILE_EDIT')) {
+        return false;
+      }
+    }
+    return true;
+  }
+
   // whether a multiplayer user is already editing a cell
   cellIsBeingEdited(x: number, y: number, sheetId: string): { codeEditor: boolean; user: string } | undefined {
     for (const player of this.users.values()) {
@@ -409,6 +422,7 @@ export class Multiplayer {
             code_running: user.code_running,
             parsedCodeRunning: user.code_running ? JSON.parse(user.code_running) : [],
             follow: user.follow,
+            permissions: user.permissions,
           };
           this.users.set(user.session_id, player);
           if (debugShowMultiplayer) console.log(`[Multiplayer] Player ${user.first_name} entered room.`);
@@ -9,6 +9,7 @@ export interface CoreMultiplayerTransaction {
   type: 'coreMultiplayerTransaction';
   operations: ArrayBuffer;
   transaction_id: string;
+  comment?: boolean;
 }
 
 export interface MultiplayerCoreReceiveTransactions {
@@ -21,6 +22,17 @@ export interface MultiplayerCoreReceiveTransaction {
   transaction: ReceiveTransaction;
 }
 
+export interface MultiplayerCoreReceiveCatchUp {
+  type: 'multiplayerCoreReceiveCatchUp';
+  checkpointSequenceNum: number;
+  transactions: ReceiveTransaction[];
+}
+
+export interface MultiplayerCoreReceiveTransactionRejected {
+  type: 'multiplayerCoreReceiveTransactionRejected';
+  id: string;
+}
+
 export interface MultiplayerCoreReceiveCurrentTransaction {
   type: 'multiplayerCoreReceiveCurrentTransaction';
   sequenceNum: number;
@@ -31,10 +43,19 @@ export interface CoreMultiplayerRequestTransactions {
   sequenceNum: number;
 }
 
+export interface CoreMultiplayerReload {
+  type: 'coreMultiplayerReload';
+}
+
 export type MultiplayerCoreMessage =
   | MultiplayerCoreSequenceNum
   | MultiplayerCoreReceiveTransactions
   | MultiplayerCoreReceiveTransaction
+  | MultiplayerCoreReceiveCatchUp
+  | MultiplayerCoreReceiveTransactionRejected
   | MultiplayerCoreReceiveCurrentTransaction;
 
-export type CoreMultiplayerMessage = CoreMultiplayerTransaction | CoreMultiplayerRequestTransactions;
+export type CoreMultiplayerMessage =
+  | CoreMultiplayerTransaction
+  | CoreMultiplayerRequestTransactions
+  | CoreMultiplayerReload;
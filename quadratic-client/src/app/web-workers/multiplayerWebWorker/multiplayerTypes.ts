@@ -30,6 +30,7 @@ export interface MultiplayerUserServer {
   viewport: string;
   code_running: string;
   follow?: string;
+  permissions?: string[];
 }
 
 // extended by the client
@@ -73,12 +74,19 @@ export interface MessageUserUpdate {
 
 export interface SendEnterRoom extends MultiplayerUserServer {
   type: 'EnterRoom';
+
+  // highest protocol version the client supports
+  protocol_version: number;
 }
 
 export interface ReceiveEnterRoom {
   type: 'EnterRoom';
   file_id: string;
   sequence_num: number;
+  min_version: Version;
+
+  // protocol version negotiated with the server
+  protocol_version: number;
 }
 
 export interface Transaction {
@@ -104,6 +112,23 @@ export interface SendTransaction {
   operations: string;
 }
 
+// transactions that only change comments (viewers can send these)
+export interface SendComment {
+  type: 'Comment';
+  id: string;
+  session_id: string;
+  file_id: string;
+  operations: string;
+}
+
+export interface ReceiveComment {
+  type: 'Comment';
+  id: string;
+  file_id: string;
+  operations: string | Buffer;
+  sequence_num: number;
+}
+
 export interface SendGetTransactions {
   type: 'GetTransactions';
   session_id: string;
@@ -116,6 +141,13 @@ export interface ReceiveTransactions {
   transactions: ReceiveTransaction[];
 }
 
+export interface ReceiveCatchUp {
+  type: 'CatchUp';
+  file_id: string;
+  checkpoint_sequence_num: number;
+  transactions: ReceiveTransaction[];
+}
+
 export interface Heartbeat {
   type: 'Heartbeat';
   session_id: string;
@@ -131,6 +163,14 @@ export interface ReceiveCurrentTransaction {
   sequence_num: number;
 }
 
+// the server will never sequence the transaction, so it's rolled back
+export interface ReceiveTransactionRejected {
+  type: 'TransactionRejected';
+  id: string;
+  file_id: string;
+  error: string | Record<string, unknown>;
+}
+
 export interface ReceiveError {
   type: 'Error';
   error: string | Record<string, string[]>;
@@ -141,10 +181,13 @@ export type ReceiveMessages =
   | ReceiveRoom
   | MessageUserUpdate
   | ReceiveTransaction
+  | ReceiveComment
   | ReceiveEmpty
   | ReceiveTransactions
+  | ReceiveCatchUp
   | ReceiveEnterRoom
   | ReceiveError
+  | ReceiveTransactionRejected
   | ReceiveCurrentTransaction;
 
-export type MultiplayerServerMessage = SendTransaction | SendEnterRoom | SendGetTransactions;
+export type MultiplayerServerMessage = SendTransaction | SendComment | SendEnterRoom | SendGetTransactions;
@@ -0,0 +1,177 @@
+/**
+ * Binary frames for the multiplayer protocol (version 2).
+ *
+ * Transactions are sent and received as bincode serialized frames instead of
+ * JSON with base64 encoded operations. The operations are already compressed,
+ * so the frames are not compressed again. Every other message is JSON.
+ *
+ * NOTE: the layout needs to be kept in sync with
+ * quadratic-multiplayer/src/message/frame.rs
+ */
+
+import { Buffer } from 'buffer';
+import { ReceiveMessages, ReceiveTransaction } from '../multiplayerTypes';
+
+// highest protocol version the client supports
+export const PROTOCOL_VERSION = 2;
+
+// first protocol version that sends transactions in binary frames
+export const BINARY_PROTOCOL_VERSION = 2;
+
+// variant indices of BinaryRequest and BinaryResponse
+enum RequestVariant {
+  Transaction = 0,
+  Comment = 1,
+}
+
+enum ResponseVariant {
+  Transaction = 0,
+  Comment = 1,
+  Transactions = 2,
+  CatchUp = 3,
+}
+
+const UUID_LENGTH = 16;
+
+export interface BinaryTransactionRequest {
+  type: 'Transaction' | 'Comment';
+  id: string;
+  session_id: string;
+  file_id: string;
+  operations: Uint8Array;
+}
+
+const uuidToBytes = (uuid: string): Uint8Array => {
+  const hex = uuid.replace(/-/g, '');
+  if (hex.length !== UUID_LENGTH * 2) throw new Error(`Invalid uuid ${uuid}`);
+  const bytes = new Uint8Array(UUID_LENGTH);
+  for (let i = 0; i < UUID_LENGTH; i++) {
+    bytes[i] = parseInt(hex.slice(i * 2, i * 2 + 2), 16);
+  }
+  return bytes;
+};
+
+const bytesToUuid = (bytes: Uint8Array): string => {
+  const hex = Array.from(bytes, (byte) => byte.toString(16).padStart(2, '0')).join('');
+  return `${hex.slice(0, 8)}-${hex.slice(8, 12)}-${hex.slice(12, 16)}-${hex.slice(16, 20)}-${hex.slice(20)}`;
+};
+
+// bincode writes variant indices as u32 and lengths as u64, little endian
+class BincodeWriter {
+  private chunks: Uint8Array[] = [];
+
+  u32(value: number) {
+    const chunk = new Uint8Array(4);
+    new DataView(chunk.buffer).setUint32(0, value, true);
+    this.chunks.push(chunk);
+  }
+
+  u64(value: number) {
+    const chunk = new Uint8Array(8);
+    new DataView(chunk.buffer).setBigUint64(0, BigInt(value), true);
+    this.chunks.push(chunk);
+  }
+
+  bytes(value: Uint8Array) {
+    this.u64(value.length);
+    this.chunks.push(value);
+  }
+
+  uuid(value: string) {
+    this.bytes(uuidToBytes(value));
+  }
+
+  finish(): Uint8Array {
+    const length = this.chunks.reduce((total, chunk) => total + chunk.length, 0);
+    const output = new Uint8Array(length);
+    let offset = 0;
+    for (const chunk of this.chunks) {
+      output.set(chunk, offset);
+      offset += chunk.length;
+    }
+    return output;
+  }
+}
+
+class BincodeReader {
+  private view: DataView;
+  private offset = 0;
+
+  constructor(private data: Uint8Array) {
+    this.view = new DataView(data.buffer, data.byteOffset, data.byteLength);
+  }
+
+  u32(): number {
+    const value = this.view.getUint32(this.offset, true);
+    this.offset += 4;
+    return value;
+  }
+
+  u64(): number {
+    const value = this.view.getBigUint64(this.offset, true);
+    this.offset += 8;
+    return Number(value);
+  }
+
+  bytes(): Uint8Array {
+    const length = this.u64();
+    const value = this.data.subarray(this.offset, this.offset + length);
+    this.offset += length;
+    return value;
+  }
+
+  uuid(): string {
+    return bytesToUuid(this.bytes());
+  }
+
+  transaction(): ReceiveTransaction {
+    return {
+      type: 'Transaction',
+      id: this.uuid(),
+      file_id: this.uuid(),
+      sequence_num: this.u64(),
+      operations: Buffer.from(this.bytes()),
+    };
+  }
+
+  transactions(): ReceiveTransaction[] {
+    const length = this.u64();
+    return Array.from({ length }, () => this.transaction());
+  }
+}
+
+export const encodeRequest = (request: BinaryTransactionRequest): Uint8Array => {
+  const writer = new BincodeWriter();
+  writer.u32(request.type === 'Comment' ? RequestVariant.Comment : RequestVariant.Transaction);
+  writer.uuid(request.id);
+  writer.uuid(request.session_id);
+  writer.uuid(request.file_id);
+  writer.bytes(request.operations);
+  return writer.finish();
+};
+
+export const decodeResponse = (frame: ArrayBuffer): ReceiveMessages => {
+  const reader = new BincodeReader(new Uint8Array(frame));
+  const variant = reader.u32();
+  switch (variant) {
+    case ResponseVariant.Transaction:
+      return reader.transaction();
+
+    case ResponseVariant.Comment:
+      return { ...reader.transaction(), type: 'Comment' };
+
+    case ResponseVariant.Transactions:
+      return { type: 'Transactions', transactions: reader.transactions() };
+
+    case ResponseVariant.CatchUp:
+      return {
+        type: 'CatchUp',
+        file_id: reader.uuid(),
+        checkpoint_sequence_num: reader.u64(),
+        transactions: reader.transactions(),
+      };
+
+    default:
+      throw new Error(`Unknown binary message variant ${variant}`);
+  }
+};
@@ -4,7 +4,8 @@
 
 import { debugWebWorkersMessages } from '@/app/debugFlags';
 import { CoreMultiplayerMessage, MultiplayerCoreMessage } from '../multiplayerCoreMessages';
-import { ReceiveTransaction, ReceiveTransactions } from '../multiplayerTypes';
+import { ReceiveCatchUp, ReceiveTransaction, ReceiveTransactions } from '../multiplayerTypes';
+import { multiplayerClient } from './multiplayerClient';
 import { multiplayerServer } from './multiplayerServer';
 
 class MultiplayerCore {
@@ -32,6 +33,10 @@ class MultiplayerCore {
         multiplayerServer.requestTransactions(e.data.sequenceNum);
         break;
 
+      case 'coreMultiplayerReload':
+        multiplayerClient.reload();
+        break;
+
       default:
         console.warn('[multiplayerCore] Unhandled message type', e.data);
     }
@@ -57,6 +62,21 @@ class MultiplayerCore {
       transactions: receive_transactions.transactions,
     });
   }
+
+  receiveTransactionRejected(id: string) {
+    this.send({
+      type: 'multiplayerCoreReceiveTransactionRejected',
+      id,
+    });
+  }
+
+  receiveCatchUp(catch_up: ReceiveCatchUp) {
+    this.send({
+      type: 'multiplayerCoreReceiveCatchUp',
+      checkpointSequenceNum: catch_up.checkpoint_sequence_num,
+      transactions: catch_up.transactions,
+    });
+  }
 }
 
 export const multiplayerCore = new MultiplayerCore();
@@ -16,18 +16,21 @@ import {
   MultiplayerServerMessage,
   ReceiveMessages,
   ReceiveRoom,
+  SendComment,
   SendEnterRoom,
   SendGetTransactions,
   SendTransaction,
   UserUpdate,
   Version,
 } from '../multiplayerTypes';
+import { BINARY_PROTOCOL_VERSION, PROTOCOL_VERSION, decodeResponse, encodeRequest } from './multiplayerBinary';
 import { multiplayerClient } from './multiplayerClient';
 import { multiplayerCore } from './multiplayerCore';
 
 const UPDATE_TIME_MS = 1000 / 60;
 const HEARTBEAT_TIME = 1000 * 10;
 const RECONNECT_AFTER_ERROR_TIMEOUT = 1000 * 5;
+const JSON_PROTOCOL_VERSION = 1;
 
 interface UserData {
   sheetId: string;
@@ -67,6 +70,9 @@ export class MultiplayerServer {
 
   private updateAlertVersion: Version = sharedConstants;
 
+  // protocol version negotiated in EnterRoom
+  private protocolVersion = JSON_PROTOCOL_VERSION;
+
   // queue of items waiting to be sent to the server on the next tick
   userUpdate: UserUpdate = {};
 
@@ -124,8 +130,10 @@ export class MultiplayerServer {
       await multiplayerClient.sendRefreshJwt();
     }
 
+    this.protocolVersion = JSON_PROTOCOL_VERSION;
     this.websocket = new WebSocket(import.meta.env.VITE_QUADRATIC_MULTIPLAYER_URL);
-    this.websocket.addEventListener('message', this.handleMessage);
+    this.websocket.binaryType = 'arraybuffer';
+    this.websocket.addEventListener('message', this.receiveMessage);
 
     this.websocket.addEventListener('close', () => {
       if (debugShowMultiplayer) console.log('[Multiplayer] websocket closed unexpectedly.');
@@ -178,6 +186,7 @@ export class MultiplayerServer {
       viewport: this.userData.viewport,
       code_running: this.userData.codeRunning,
       follow: this.userData.follow,
+      protocol_version: PROTOCOL_VERSION,
     };
     this.send(enterRoom);
     if (debugShowMultiplayer) console.log(`[Multiplayer] Joined room ${this.fileId}.`);
@@ -232,8 +241,12 @@ export class MultiplayerServer {
    * Receive Messages from Multiplayer Server *
    ********************************************/
 
-  private handleMessage = (e: MessageEvent<string>) => {
-    const data: ReceiveMessages = JSON.parse(e.data);
+  private receiveMessage = (e: MessageEvent<string | ArrayBuffer>) => {
+    const data: ReceiveMessages = typeof e.data === 'string' ? JSON.parse(e.data) : decodeResponse(e.data);
+    this.handleMessage(data);
+  };
+
+  private handleMessage = (data: ReceiveMessages) => {
     switch (data.type) {
       case 'UsersInRoom':
         this.receiveUsersInRoom(data);
@@ -247,12 +260,28 @@ export class MultiplayerServer {
         multiplayerCore.receiveTransaction(data);
         break;
 
+      case 'Comment':
+        // comments are sequenced and applied like any other transaction
+        multiplayerCore.receiveTransaction({ ...data, type: 'Transaction' });
+        break;
+
       case 'Transactions':
         multiplayerCore.receiveTransactions(data);
         break;
 
+      case 'CatchUp':
+        // the transactions before the checkpoint are no longer available, so
+        // core loads the checkpoint before applying the remaining ones
+        if (debugShowMultiplayer)
+          console.log(
+            `[Multiplayer] Catching up from checkpoint ${data.checkpoint_sequence_num} with ${data.transactions.length} transaction(s)`
+          );
+        multiplayerCore.receiveCatchUp(data);
+        break;
+
       case 'EnterRoom':
         if (data.file_id !== this.fileId) throw new Error('Expected file_id to match in EnterRoom');
+        this.protocolVersion = data.protocol_version ?? JSON_PROTOCOL_VERSION;
         multiplayerCore.receiveCurrentTransaction(data.sequence_num);
         break;
 
@@ -260,6 +289,11 @@ export class MultiplayerServer {
         multiplayerCore.receiveCurrentTransaction(data.sequence_num);
         break;
 
+      case 'TransactionRejected':
+        console.warn(`[Multiplayer] Transaction ${data.id} was rejected`, data.error);
+        multiplayerCore.receiveTransactionRejected(data.id);
+        break;
+
       case 'Error':
         if (data.error_level === 'Error') {
           // If the server is missing transactions, reload the page
@@ -297,6 +331,11 @@ export class MultiplayerServer {
     this.websocket.send(JSON.stringify(message));
   }
 
+  private sendBinary(frame: Uint8Array) {
+    if (!this.websocket) throw new Error('Expected websocket to be defined in sendBinary');
+    this.websocket.send(frame);
+  }
+
   sendTransaction(transactionMessage: CoreMultiplayerTransaction) {
     if (this.state === 'connecting' || this.state === 'waiting to reconnect') {
       this.waitingForConnection.push(this.sendTransaction.bind(this, transactionMessage));
@@ -304,8 +343,21 @@ export class MultiplayerServer {
     }
 
     multiplayerClient.sendState('syncing');
-    const message: SendTransaction = {
-      type: 'Transaction',
+    if (this.protocolVersion >= BINARY_PROTOCOL_VERSION) {
+      this.sendBinary(
+        encodeRequest({
+          type: transactionMessage.comment ? 'Comment' : 'Transaction',
+          id: transactionMessage.transaction_id,
+          session_id: this.sessionId!,
+          file_id: this.fileId!,
+          operations: new Uint8Array(transactionMessage.operations),
+        })
+      );
+      return;
+    }
+
+    const message: SendTransaction | SendComment = {
+      type: transactionMessage.comment ? 'Comment' : 'Transaction',
       id: transactionMessage.transaction_id,
       session_id: this.sessionId!,
       file_id: this.fileId!,
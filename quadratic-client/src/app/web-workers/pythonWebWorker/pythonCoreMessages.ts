@@ -21,6 +21,9 @@ export interface PythonCoreGetCellsA1Length {
   transactionId: string;
   a1: string;
   lineNumber?: number;
+
+  // return the cells as an Arrow IPC stream instead of JSON
+  arrow?: boolean;
 }
 
 export interface PythonCoreGetCellsA1Data {
@@ -26,6 +26,10 @@ export type outputType = 'blank' | 'number' | 'text' | 'logical' | 'instant';
 export interface PythonSuccess {
   array_output: string[][];
   typed_array_output: [string, outputType][];
+
+  // array output as an Arrow IPC stream (when pyarrow is available)
+  arrow_output?: Uint8Array;
+
   code: string;
   input_python_stack_trace: string;
   output?: [string, outputType];
@@ -50,6 +54,7 @@ export interface PythonError {
   output_size: undefined;
   array_output: undefined;
   typed_array_output?: [string, outputType][];
+  arrow_output?: undefined;
 
   success: false;
   input_python_stack_trace: string;
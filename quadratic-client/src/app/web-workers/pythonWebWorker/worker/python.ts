@@ -40,6 +40,26 @@ class Python {
     return pythonCore.sendGetCellsA1(this.transactionId, a1, lineNumber);
   };
 
+  private getCellsA1Arrow = (a1: string, lineNumber?: number): Uint8Array | undefined => {
+    if (!this.transactionId) {
+      throw new Error('No transactionId in getCellsA1Arrow');
+    }
+    return pythonCore.sendGetCellsA1Arrow(this.transactionId, a1, lineNumber);
+  };
+
+  // Loads pyarrow, which lets Python cells exchange typed values with core as
+  // Arrow. Without it, they use the JSON cells.
+  private loadArrow = async (): Promise<boolean> => {
+    if (!this.pyodide) return false;
+    try {
+      await this.pyodide.loadPackage('pyarrow', { messageCallback: () => 0, errorCallback: () => 0 });
+      await this.pyodide.runPythonAsync('import pyarrow');
+      return true;
+    } catch (_e) {
+      return false;
+    }
+  };
+
   private init = async () => {
     const jwt = await pythonClient.getJwt();
 
@@ -114,6 +134,9 @@ class Python {
     });
 
     this.pyodide.registerJsModule('getCellsA1', this.getCellsA1);
+    if (await this.loadArrow()) {
+      this.pyodide.registerJsModule('getCellsA1Arrow', this.getCellsA1Arrow);
+    }
 
     // patch requests https://github.com/koenvo/pyodide-http
     await this.pyodide.runPythonAsync('import pyodide_http; pyodide_http.patch_all();');
@@ -231,6 +254,7 @@ class Python {
       if (nothingReturned) {
         output.array_output = undefined;
         output.typed_array_output = undefined;
+        output.arrow_output = undefined;
         output.output = ['', 'blank'];
       } else {
         if (output.array_output && output.array_output.length) {
@@ -259,6 +283,7 @@ class Python {
         ...pythonRun,
         array_output: [],
         typed_array_output: [],
+        arrow_output: undefined,
         success: false,
         std_err: String(e),
         input_python_stack_trace: String(e),
@@ -41,6 +41,31 @@ export class PythonCore {
     a1: string,
     lineNumber?: number
   ): { cells: JsGetCellResponse[]; x: number; y: number; w: number; h: number } | undefined {
+    const bytes = this.getCells(transactionId, a1, lineNumber, false);
+    if (!bytes) return undefined;
+    try {
+      const decoder = new TextDecoder();
+      const cellsStringified = decoder.decode(bytes);
+      const cells = JSON.parse(cellsStringified) as CellA1Response;
+      return { cells: cells.cells, x: Number(cells.x), y: Number(cells.y), w: Number(cells.w), h: Number(cells.h) };
+    } catch (e) {
+      console.warn('[pythonCore] getCellsA1 error', e);
+    }
+    return undefined;
+  }
+
+  // Returns the cells as an Arrow IPC stream with typed values.
+  sendGetCellsA1Arrow(transactionId: string, a1: string, lineNumber?: number): Uint8Array | undefined {
+    return this.getCells(transactionId, a1, lineNumber, true);
+  }
+
+  // Gets the cells from core as JSON or Arrow bytes.
+  private getCells(
+    transactionId: string,
+    a1: string,
+    lineNumber: number | undefined,
+    arrow: boolean
+  ): Uint8Array | undefined {
     try {
       // This is a shared buffer that will be used to communicate with core
       // The first 4 bytes are used to signal the python core that the data is ready
@@ -51,14 +76,14 @@ export class PythonCore {
       let int32View: Int32Array | undefined = new Int32Array(sharedBuffer, 0, 3);
       Atomics.store(int32View, 0, 0);
 
-      this.send({ type: 'pythonCoreGetCellsA1Length', sharedBuffer, transactionId, a1, lineNumber });
+      this.send({ type: 'pythonCoreGetCellsA1Length', sharedBuffer, transactionId, a1, lineNumber, arrow });
       let result = Atomics.wait(int32View, 0, 0);
       const length = int32View[1];
       if (result !== 'ok' || length === 0) return undefined;
 
       const id = int32View[2];
 
-      // New shared buffer, which is sized to hold the cells string
+      // New shared buffer, which is sized to hold the cells
       sharedBuffer = new SharedArrayBuffer(4 + length);
       int32View = new Int32Array(sharedBuffer, 0, 1);
       Atomics.store(int32View, 0, 0);
@@ -77,10 +102,7 @@ export class PythonCore {
       int32View = undefined;
       uint8View = undefined;
 
-      const decoder = new TextDecoder();
-      const cellsStringified = decoder.decode(nonSharedView);
-      const cells = JSON.parse(cellsStringified) as CellA1Response;
-      return { cells: cells.cells, x: Number(cells.x), y: Number(cells.y), w: Number(cells.w), h: Number(cells.h) };
+      return nonSharedView;
     } catch (e) {
       console.warn('[pythonCore] getCellsA1 error', e);
     }
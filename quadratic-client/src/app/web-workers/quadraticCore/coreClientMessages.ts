@@ -21,6 +21,7 @@ import {
   JsSummarizeSelectionResult,
   JsValidationWarning,
   JumpDirection,
+  ProtectionRole,
   SearchOptions,
   SheetBounds,
   SheetInfo,
@@ -235,6 +236,20 @@ export interface ClientCoreGetJwt {
   jwt: string;
 }
 
+export interface CoreClientGetLastCheckpoint {
+  type: 'coreClientGetLastCheckpoint';
+  id: number;
+
+  // the oldest checkpoint that can be used
+  sequenceNumber: number;
+}
+
+export interface ClientCoreGetLastCheckpoint {
+  type: 'clientCoreGetLastCheckpoint';
+  id: number;
+  checkpoint?: { url: string; sequenceNumber: number };
+}
+
 //#endregion
 
 //#region Render
@@ -463,6 +478,12 @@ export interface ClientCoreRerunCodeCells {
   cursor: string;
 }
 
+export interface ClientCoreRefreshCodeCells {
+  type: 'clientCoreRefreshCodeCells';
+  opened: boolean;
+  cursor: string;
+}
+
 export interface ClientCoreSetBorders {
   type: 'clientCoreSetBorders';
   selection: string;
@@ -585,6 +606,12 @@ export interface ClientCoreSetSheetColor {
   cursor: string;
 }
 
+export interface ClientCoreSetProtectionEditor {
+  type: 'clientCoreSetProtectionEditor';
+  userId: string;
+  roles: ProtectionRole[];
+}
+
 export interface ClientCoreDuplicateSheet {
   type: 'clientCoreDuplicateSheet';
   sheetId: string;
@@ -1148,6 +1175,7 @@ export type ClientCoreMessage =
   | ClientCoreMoveSheet
   | ClientCoreSetSheetName
   | ClientCoreSetSheetColor
+  | ClientCoreSetProtectionEditor
   | ClientCoreDuplicateSheet
   | ClientCoreUndo
   | ClientCoreRedo
@@ -1155,6 +1183,7 @@ export type ClientCoreMessage =
   | ClientCoreExport
   | ClientCoreSearch
   | ClientCoreRerunCodeCells
+  | ClientCoreRefreshCodeCells
   | ClientCoreHasRenderCells
   | ClientCoreCopyToClipboard
   | ClientCoreCutToClipboard
@@ -1175,6 +1204,7 @@ export type ClientCoreMessage =
   | ClientCoreInitJavascript
   | ClientCoreCancelExecution
   | ClientCoreGetJwt
+  | ClientCoreGetLastCheckpoint
   | ClientCoreMoveCells
   | ClientCoreGetFormatCell
   | ClientCoreSetDateTimeFormat
@@ -1245,6 +1275,7 @@ export type CoreClientMessage =
   | CoreClientOfflineTransactions
   | CoreClientUndoRedo
   | CoreClientGetJwt
+  | CoreClientGetLastCheckpoint
   | CoreClientImage
   | CoreClientGetFormatCell
   | CoreClientSheetMetaFills
@@ -26,6 +26,7 @@ import {
   JsSummarizeSelectionResult,
   JumpDirection,
   PasteSpecial,
+  ProtectionRole,
   SearchOptions,
   SheetPos,
   SheetRect,
@@ -38,6 +39,7 @@ import {
   ClientCoreGetCodeCell,
   ClientCoreGetDisplayCell,
   ClientCoreGetEditCell,
+  ClientCoreGetLastCheckpoint,
   ClientCoreGetRenderCell,
   ClientCoreHasRenderCells,
   ClientCoreImportFile,
@@ -54,6 +56,7 @@ import {
   CoreClientGetDisplayCell,
   CoreClientGetEditCell,
   CoreClientGetJwt,
+  CoreClientGetLastCheckpoint,
   CoreClientGetRenderCell,
   CoreClientGetRowsBounds,
   CoreClientGetValidationList,
@@ -70,6 +73,7 @@ import {
 } from '@/app/web-workers/quadraticCore/coreClientMessages';
 import { renderWebWorker } from '@/app/web-workers/renderWebWorker/renderWebWorker';
 import { authClient } from '@/auth/auth';
+import { apiClient } from '@/shared/api/apiClient';
 import { Rectangle } from 'pixi.js';
 
 class QuadraticCore {
@@ -77,6 +81,9 @@ class QuadraticCore {
   private id = 0;
   private waitingForResponse: Record<number, Function> = {};
 
+  // the file loaded in core, used to find its last checkpoint
+  private fileId?: string;
+
   // This is a hack to get import files to properly show negative offsets dialog
   // after importing from dashboard. This can be removed in the future.
   receivedClientMessage = false;
@@ -175,6 +182,21 @@ class QuadraticCore {
       const data = e.data as CoreClientGetJwt;
       this.send({ type: 'clientCoreGetJwt', id: data.id, jwt });
       return;
+    } else if (e.data.type === 'coreClientGetLastCheckpoint') {
+      const data = e.data as CoreClientGetLastCheckpoint;
+      let checkpoint: ClientCoreGetLastCheckpoint['checkpoint'];
+      try {
+        if (!this.fileId) throw new Error('Expected fileId to be defined in coreClientGetLastCheckpoint');
+        const { file } = await apiClient.files.get(this.fileId);
+        if (file.lastCheckpointSequenceNumber >= data.sequenceNumber) {
+          checkpoint = { url: file.lastCheckpointDataUrl, sequenceNumber: file.lastCheckpointSequenceNumber };
+        }
+      } catch (error) {
+        console.error('[quadraticCore] unable to get the last checkpoint', error);
+      }
+      if (!checkpoint) events.emit('needRefresh', 'force');
+      this.send({ type: 'clientCoreGetLastCheckpoint', id: data.id, checkpoint });
+      return;
     } else if (e.data.type === 'coreClientImage') {
       events.emit('updateImage', e.data);
       return;
@@ -246,6 +268,8 @@ class QuadraticCore {
     sequenceNumber: number;
   }): Promise<{ version?: string; error?: string }> {
     // this is the channel between the core worker and the render worker
+    this.fileId = fileId;
+
     const port = new MessageChannel();
     renderWebWorker.init(port.port2);
 
@@ -764,6 +788,16 @@ class QuadraticCore {
     });
   }
 
+  // Reruns the code cells that are due for refresh. `opened` is true for the
+  // first refresh after the file is opened.
+  refreshCodeCells(opened: boolean, cursor: string) {
+    this.send({
+      type: 'clientCoreRefreshCodeCells',
+      opened,
+      cursor,
+    });
+  }
+
   //#region Sheet Operations
 
   addSheet(cursor?: string) {
@@ -790,6 +824,11 @@ class QuadraticCore {
     this.send({ type: 'clientCoreDuplicateSheet', sheetId, cursor });
   }
 
+  // Sets the user that is checked against protected ranges
+  setProtectionEditor(userId: string, roles: ProtectionRole[]) {
+    this.send({ type: 'clientCoreSetProtectionEditor', userId, roles });
+  }
+
   //#endregion
 
   //#region Undo/redo
@@ -25,6 +25,7 @@ import {
   JsRenderCell,
   JsSummarizeSelectionResult,
   JumpDirection,
+  ProtectionRole,
   SearchOptions,
   SheetPos,
   Validation,
@@ -32,9 +33,11 @@ import {
 import initCore, { GridController, MinMax, Pos } from '@/app/quadratic-core/quadratic_core';
 import { Rect } from '@/app/quadratic-rust-client/quadratic_rust_client';
 import {
+  MultiplayerCoreReceiveCatchUp,
   MultiplayerCoreReceiveTransaction,
   MultiplayerCoreReceiveTransactions,
 } from '@/app/web-workers/multiplayerWebWorker/multiplayerCoreMessages';
+import { ReceiveTransaction } from '@/app/web-workers/multiplayerWebWorker/multiplayerTypes';
 import {
   ClientCoreFindNextColumn,
   ClientCoreFindNextColumnForRect,
@@ -48,6 +51,7 @@ import {
   ClientCoreSummarizeSelection,
 } from '@/app/web-workers/quadraticCore/coreClientMessages';
 import { coreClient } from '@/app/web-workers/quadraticCore/worker/coreClient';
+import { coreMultiplayer } from '@/app/web-workers/quadraticCore/worker/coreMultiplayer';
 import { coreRender } from '@/app/web-workers/quadraticCore/worker/coreRender';
 import { offline } from '@/app/web-workers/quadraticCore/worker/offline';
 import {
@@ -300,15 +304,7 @@ class Core {
       this.clientQueue.push(async () => {
         if (!this.gridController) throw new Error('Expected gridController to be defined');
 
-        const formattedTransactions = receive_transactions.transactions.map((transaction) => ({
-          id: transaction.id,
-          file_id: transaction.file_id,
-          sequence_num: transaction.sequence_num,
-          operations:
-            typeof transaction.operations === 'string'
-              ? Array.from(Buffer.from(transaction.operations, 'base64'))
-              : Array.from(transaction.operations),
-        }));
+        const formattedTransactions = this.formatTransactions(receive_transactions.transactions);
         receive_transactions.transactions = [];
 
         this.gridController.receiveMultiplayerTransactions(formattedTransactions);
@@ -326,6 +322,79 @@ class Core {
     });
   }
 
+  // Rolls back a transaction that the server rejected, and removes it from the
+  // unsent transactions so it's not sent again.
+  receiveTransactionRejected(id: string) {
+    return new Promise((resolve) => {
+      this.clientQueue.push(async () => {
+        if (!this.gridController) throw new Error('Expected gridController to be defined');
+        this.gridController.receiveRejectedTransaction(id);
+        offline.markTransactionSent(id);
+        if (await offline.unsentTransactionsCount()) {
+          coreClient.sendMultiplayerState('syncing');
+        } else {
+          coreClient.sendMultiplayerState('connected');
+        }
+        resolve(undefined);
+      });
+    });
+  }
+
+  // Replaces the grid with the file's last checkpoint and applies the
+  // transactions after it. The server sends a catch up when it no longer has
+  // every transaction since our last sequence_num.
+  async receiveCatchUp(catch_up: MultiplayerCoreReceiveCatchUp) {
+    // reload the file if the client cannot provide the checkpoint
+    const checkpoint = await coreClient.getLastCheckpoint(catch_up.checkpointSequenceNum);
+    if (!checkpoint) {
+      coreMultiplayer.reload();
+      return;
+    }
+
+    const addToken = coreClient.env.VITE_STORAGE_TYPE === 'file-system';
+    const file = await this.loadGridFile(checkpoint.url, addToken);
+
+    return new Promise((resolve) => {
+      this.clientQueue.push(async () => {
+        if (!this.gridController) throw new Error('Expected gridController to be defined');
+
+        const formattedTransactions = this.formatTransactions(catch_up.transactions);
+        catch_up.transactions = [];
+
+        try {
+          this.gridController.receiveCatchUp(file, checkpoint.sequenceNumber, formattedTransactions);
+        } catch (e) {
+          console.error('Error catching up from checkpoint:', e);
+          Sentry.captureException(e);
+          coreMultiplayer.reload();
+          resolve(undefined);
+          return;
+        }
+
+        coreClient.sendMultiplayerSynced();
+
+        if (await offline.unsentTransactionsCount()) {
+          coreClient.sendMultiplayerState('syncing');
+        } else {
+          coreClient.sendMultiplayerState('connected');
+        }
+        resolve(undefined);
+      });
+    });
+  }
+
+  private formatTransactions(transactions: ReceiveTransaction[]) {
+    return transactions.map((transaction) => ({
+      id: transaction.id,
+      file_id: transaction.file_id,
+      sequence_num: transaction.sequence_num,
+      operations:
+        typeof transaction.operations === 'string'
+          ? Array.from(Buffer.from(transaction.operations, 'base64'))
+          : Array.from(transaction.operations),
+    }));
+  }
+
   summarizeSelection(message: ClientCoreSummarizeSelection): Promise<JsSummarizeSelectionResult | undefined> {
     return new Promise((resolve) => {
       this.clientQueue.push(() => {
@@ -605,6 +674,16 @@ class Core {
     });
   }
 
+  setProtectionEditor(userId: string, roles: ProtectionRole[]) {
+    return new Promise((resolve) => {
+      this.clientQueue.push(() => {
+        if (!this.gridController) throw new Error('Expected gridController to be defined');
+        this.gridController.setProtectionEditor(userId, JSON.stringify(roles));
+        resolve(undefined);
+      });
+    });
+  }
+
   duplicateSheet(sheetId: string, cursor: string) {
     return new Promise((resolve) => {
       this.clientQueue.push(() => {
@@ -918,6 +997,12 @@ class Core {
     this.gridController.calculationComplete(JSON.stringify(results));
   }
 
+  // Completes a calculation whose output is an Arrow IPC stream.
+  calculationCompleteArrow(results: JsCodeResult, output: Uint8Array) {
+    if (!this.gridController) throw new Error('Expected gridController to be defined');
+    this.gridController.calculationCompleteArrow(JSON.stringify(results), output);
+  }
+
   connectionComplete(transactionId: string, data: ArrayBuffer, std_out?: string, std_err?: string, extra?: string) {
     if (!this.gridController) throw new Error('Expected gridController to be defined');
     this.gridController.connectionComplete(transactionId, new Uint8Array(data), std_out, std_err, extra);
@@ -953,6 +1038,14 @@ class Core {
     }
   }
 
+  refreshCodeCells(opened: boolean, cursor?: string) {
+    if (!this.gridController) throw new Error('Expected gridController to be defined');
+    const codeCells = this.gridController.getCodeCellsDueForRefresh(opened);
+    if (codeCells !== '[]') {
+      this.gridController.refreshCodeCells(codeCells, cursor);
+    }
+  }
+
   cancelExecution(transactionId: string) {
     if (!this.gridController) throw new Error('Expected gridController to be defined');
     const codeResult: JsCodeResult = {
@@ -1162,6 +1255,12 @@ class Core {
     return this.gridController.calculationGetCellsA1(transactionId, a1, lineNumber);
   }
 
+  // Returns the cells as an Arrow IPC stream with typed values.
+  getCellsA1Arrow(transactionId: string, a1: string, lineNumber?: number): Uint8Array {
+    if (!this.gridController) throw new Error('Expected gridController to be defined');
+    return this.gridController.calculationGetCellsA1Arrow(transactionId, a1, lineNumber);
+  }
+
   finiteRectFromSelection(selection: string): Rectangle | undefined {
     if (!this.gridController) throw new Error('Expected gridController to be defined');
     const rect: Rect | undefined = this.gridController.finiteRectFromSelection(selection);
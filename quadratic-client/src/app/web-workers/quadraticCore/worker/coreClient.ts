@@ -24,7 +24,12 @@ import {
 } from '@/app/quadratic-core-types';
 import { coreConnection } from '@/app/web-workers/quadraticCore/worker/coreConnection';
 import { MultiplayerState } from '../../multiplayerWebWorker/multiplayerClientMessages';
-import { ClientCoreGetJwt, ClientCoreMessage, CoreClientMessage } from '../coreClientMessages';
+import {
+  ClientCoreGetJwt,
+  ClientCoreGetLastCheckpoint,
+  ClientCoreMessage,
+  CoreClientMessage,
+} from '../coreClientMessages';
 import { core } from './core';
 import { coreJavascript } from './coreJavascript';
 import { coreMultiplayer } from './coreMultiplayer';
@@ -297,6 +302,10 @@ class CoreClient {
         await core.setSheetColor(e.data.sheetId, e.data.color, e.data.cursor);
         return;
 
+      case 'clientCoreSetProtectionEditor':
+        await core.setProtectionEditor(e.data.userId, e.data.roles);
+        return;
+
       case 'clientCoreDuplicateSheet':
         await core.duplicateSheet(e.data.sheetId, e.data.cursor);
         return;
@@ -484,6 +493,10 @@ class CoreClient {
         core.rerunCodeCells(e.data.sheetId, e.data.x, e.data.y, e.data.cursor);
         return;
 
+      case 'clientCoreRefreshCodeCells':
+        core.refreshCodeCells(e.data.opened, e.data.cursor);
+        return;
+
       case 'clientCoreCancelExecution':
         const language = getLanguage(e.data.language);
         if (language === 'Python') {
@@ -783,6 +796,14 @@ class CoreClient {
       this.send({ type: 'coreClientGetJwt', id });
     });
   }
+
+  getLastCheckpoint(sequenceNumber: number): Promise<ClientCoreGetLastCheckpoint['checkpoint']> {
+    return new Promise((resolve) => {
+      const id = this.id++;
+      this.waitingForResponse[id] = (message: ClientCoreGetLastCheckpoint) => resolve(message.checkpoint);
+      this.send({ type: 'coreClientGetLastCheckpoint', id, sequenceNumber });
+    });
+  }
   sendImage = (sheetId: string, x: number, y: number, image?: string, w?: string, h?: string) => {
     this.send({ type: 'coreClientImage', sheetId, x, y, image, w, h });
   };
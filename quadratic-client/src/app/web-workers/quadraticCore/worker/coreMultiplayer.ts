@@ -5,6 +5,7 @@ import { core } from './core';
 declare var self: WorkerGlobalScope &
   typeof globalThis & {
     sendTransaction: (transactionId: string, operations: Uint8Array) => void;
+    sendComment: (transactionId: string, operations: Uint8Array) => void;
     requestTransactions: (sequenceNum: number) => void;
   };
 
@@ -43,6 +44,14 @@ class CoreMultiplayer {
         core.receiveTransactions(e.data);
         break;
 
+      case 'multiplayerCoreReceiveCatchUp':
+        core.receiveCatchUp(e.data);
+        break;
+
+      case 'multiplayerCoreReceiveTransactionRejected':
+        core.receiveTransactionRejected(e.data.id);
+        break;
+
       default:
         console.warn('[coreMultiplayer] Unhandled message type', e.data);
     }
@@ -59,15 +68,33 @@ class CoreMultiplayer {
     );
   };
 
+  sendComment = (transactionId: string, operations: ArrayBuffer) => {
+    this.send(
+      {
+        type: 'coreMultiplayerTransaction',
+        operations,
+        transaction_id: transactionId,
+        comment: true,
+      },
+      [operations]
+    );
+  };
+
   requestTransactions = (sequenceNum: number) => {
     this.send({
       type: 'coreMultiplayerRequestTransactions',
       sequenceNum,
     });
   };
+
+  // reloads the file when core can't catch up with the server
+  reload = () => {
+    this.send({ type: 'coreMultiplayerReload' });
+  };
 }
 
 export const coreMultiplayer = new CoreMultiplayer();
 
 self.sendTransaction = coreMultiplayer.sendTransaction;
+self.sendComment = coreMultiplayer.sendComment;
 self.requestTransactions = coreMultiplayer.requestTransactions;
@@ -11,7 +11,7 @@ declare var self: WorkerGlobalScope &
 class CorePython {
   private corePythonPort?: MessagePort;
   private id = 0;
-  private getCellsResponses: Record<number, string> = {};
+  private getCellsResponses: Record<number, string | Uint8Array> = {};
 
   // last running transaction (used to cancel execution)
   lastTransactionId?: string;
@@ -50,17 +50,27 @@ class CorePython {
           std_err: results.std_err,
           std_out: results.std_out,
           output_value: results.output ? (results.output as any as string[]) : null,
-          output_array,
+          output_array: results.arrow_output ? null : output_array,
           line_number: results.lineno ?? null,
           output_display_type: results.output_type ?? null,
           cancel_compute: false,
         };
 
-        core.calculationComplete(codeResult);
+        if (results.arrow_output) {
+          core.calculationCompleteArrow(codeResult, results.arrow_output);
+        } else {
+          core.calculationComplete(codeResult);
+        }
         break;
 
       case 'pythonCoreGetCellsA1Length':
-        this.sendGetCellsA1Length(e.data.sharedBuffer, e.data.transactionId, e.data.a1, e.data.lineNumber);
+        this.sendGetCellsA1Length(
+          e.data.sharedBuffer,
+          e.data.transactionId,
+          e.data.a1,
+          e.data.lineNumber,
+          e.data.arrow
+        );
         break;
 
       case 'pythonCoreGetCellsA1Data':
@@ -84,19 +94,22 @@ class CorePython {
     sharedBuffer: SharedArrayBuffer,
     transactionId: string,
     a1: string,
-    lineNumber?: number
+    lineNumber?: number,
+    arrow?: boolean
   ) {
     const int32View = new Int32Array(sharedBuffer, 0, 3);
     try {
-      const cellsString = core.getCellsA1(transactionId, a1, lineNumber);
+      const cells = arrow
+        ? core.getCellsA1Arrow(transactionId, a1, lineNumber)
+        : core.getCellsA1(transactionId, a1, lineNumber);
 
       // need to get the bytes of the string (which covers unicode characters)
-      const length = new Blob([cellsString]).size;
+      const length = typeof cells === 'string' ? new Blob([cells]).size : cells.length;
 
       Atomics.store(int32View, 1, length);
       if (length !== 0) {
         const id = this.id++;
-        this.getCellsResponses[id] = cellsString;
+        this.getCellsResponses[id] = cells;
         Atomics.store(int32View, 2, id);
       }
       Atomics.store(int32View, 0, 1);
@@ -107,14 +120,13 @@ class CorePython {
   }
 
   private sendGetCellsA1Data(id: number, sharedBuffer: SharedArrayBuffer) {
-    const cellsString = this.getCellsResponses[id];
+    const cells = this.getCellsResponses[id];
     delete this.getCellsResponses[id];
     const int32View = new Int32Array(sharedBuffer, 0, 1);
-    if (cellsString === undefined) {
+    if (cells === undefined) {
       console.warn('[corePython] No cells found for id:', id);
     } else {
-      const encoder = new TextEncoder();
-      const encodedCells = encoder.encode(cellsString);
+      const encodedCells = typeof cells === 'string' ? new TextEncoder().encode(cells) : cells;
       const uint8View = new Uint8Array(sharedBuffer, 4, encodedCells.length);
       uint8View.set(encodedCells);
     }
@@ -21,6 +21,7 @@ declare var self: WorkerGlobalScope &
   typeof globalThis & {
     addUnsentTransaction: (transactionId: string, transaction: string, operations: number) => void;
     sendTransaction: (transactionId: string, operations: ArrayBuffer) => void;
+    sendComment: (transactionId: string, operations: ArrayBuffer) => void;
     sendImportProgress: (
       filename: string,
       current: number,
@@ -98,6 +99,10 @@ export const jsSendTransaction = (transactionId: string, operations: Uint8Array)
   return self.sendTransaction(transactionId, operations.buffer);
 };
 
+export const jsSendComment = (transactionId: string, operations: Uint8Array) => {
+  return self.sendComment(transactionId, operations.buffer);
+};
+
 export const jsTime = (name: string) => console.time(name);
 export const jsTimeEnd = (name: string) => console.timeEnd(name);
 
@@ -84,6 +84,15 @@ export const loader = async ({ request, params }: LoaderFunctionArgs): Promise<F
     if (!data.file.thumbnail && data.userMakingRequest.filePermissions.includes('FILE_EDIT')) {
       thumbnail.generateThumbnail();
     }
+
+    // protected ranges are checked against the user making changes
+    const user = await authClient.user();
+    if (user?.sub) {
+      quadraticCore.setProtectionEditor(
+        user.sub,
+        data.userMakingRequest.filePermissions.includes('FILE_DELETE') ? ['Owner'] : []
+      );
+    }
   } else {
     throw new Error('Expected quadraticCore.load to return either a version or an error');
   }
@@ -1,9 +1,11 @@
 use criterion::{criterion_group, criterion_main, Bencher, Criterion};
+use quadratic_core::controller::active_transactions::transaction_name::TransactionName;
 use quadratic_core::controller::operations::clipboard::PasteSpecial;
 use quadratic_core::controller::GridController;
+use quadratic_core::formulas::{parse_formula, FormulaCache};
 use quadratic_core::grid::js_types::JsClipboard;
-use quadratic_core::grid::{CellAlign, Grid};
-use quadratic_core::{A1Selection, Pos, Rect, SheetRect};
+use quadratic_core::grid::{CellAlign, CodeCellLanguage, Grid};
+use quadratic_core::{A1Selection, Pos, Rect, SheetPos, SheetRect};
 use std::time::Duration;
 
 criterion_group!(benches, criterion_benchmark);
@@ -346,6 +348,106 @@ fn criterion_benchmark(c: &mut Criterion) {
             criterion::BatchSize::SmallInput,
         )
     });
+
+    let formula_inputs = vec![
+        ("1000_formulas", formula_grid(1000)),
+        ("10000_formulas", formula_grid(10000)),
+    ];
+
+    benchmark_grids(c, &formula_inputs, "get_dependent_code_cells", |b, grid| {
+        let gc = GridController::from_grid(grid.clone(), 0);
+        let sheet_id = gc.sheet_ids()[0];
+        let sheet_rect = SheetRect::single_pos(Pos { x: 1, y: 500 }, sheet_id);
+        b.iter(|| gc.get_dependent_code_cells(&sheet_rect));
+    });
+
+    benchmark_grids(c, &formula_inputs, "set_value_with_dependent", |b, grid| {
+        let mut gc = GridController::from_grid(grid.clone(), 0);
+        let sheet_id = gc.sheet_ids()[0];
+        let sheet_pos = SheetPos {
+            x: 1,
+            y: 500,
+            sheet_id,
+        };
+        // a new value each time so the dependent formula is recomputed
+        let mut value = 0;
+        b.iter(|| {
+            value += 1;
+            gc.set_cell_value(sheet_pos, value.to_string(), None)
+        });
+    });
+
+    benchmark_grids(c, &formula_inputs, "recalculate_formulas", |b, grid| {
+        b.iter_batched(
+            || {
+                // Setup
+                let gc = GridController::from_grid(grid.clone(), 0);
+                let sheet = &gc.grid().sheets()[0];
+                let formulas = sheet
+                    .code_runs
+                    .keys()
+                    .map(|pos| pos.to_sheet_pos(sheet.id))
+                    .collect::<Vec<_>>();
+                (gc, formulas)
+            },
+            |(mut gc, formulas)| {
+                // Test
+                gc.refresh_code_cells(&formulas, None);
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+
+    // parsing a filled-down column of formulas, which have the same code
+    let mut group = c.benchmark_group("parse_10000_formulas");
+    group.measurement_time(Duration::new(5, 0));
+    group.sample_size(10);
+    let code = "IF(R[0]C[-1] > 0, SUM(R[-1]C[-2]:R[0]C[-1]) * 2, \"none\")";
+    group.bench_function("uncached", |b| {
+        b.iter(|| {
+            for y in 1..=10000 {
+                parse_formula(code, Pos { x: 3, y }).unwrap();
+            }
+        });
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            let mut cache = FormulaCache::default();
+            for y in 1..=10000 {
+                cache.parse(code, Pos { x: 3, y }).unwrap();
+            }
+        });
+    });
+    group.finish();
+}
+
+/// A grid where each of the first `count` cells in column B is a formula
+/// reading the cell next to it in column A.
+fn formula_grid(count: i64) -> Grid {
+    let mut gc = GridController::from_grid(Grid::new(), 0);
+    let sheet_id = gc.sheet_ids()[0];
+    let values = (1..=count).map(|y| y.to_string()).collect::<Vec<_>>();
+    gc.set_cell_values(
+        SheetPos {
+            x: 1,
+            y: 1,
+            sheet_id,
+        },
+        values.iter().map(|value| vec![value.as_str()]).collect(),
+        None,
+    );
+
+    let ops = (1..=count)
+        .flat_map(|y| {
+            gc.set_code_cell_operations(
+                SheetPos { x: 2, y, sheet_id },
+                CodeCellLanguage::Formula,
+                format!("A{y} * 2"),
+            )
+        })
+        .collect();
+    gc.start_user_transaction(ops, None, TransactionName::SetCode);
+    gc.into_grid()
 }
 
 fn benchmark_grids(
@@ -1,6 +1,8 @@
 //! Mutation methods that insert or delete columns and rows from a selection.
 
 use super::A1Selection;
+use crate::grid::sheet::merge_cells::MergeCells;
+use crate::CellRefRange;
 
 impl A1Selection {
     /// Potentially shrinks a selection after the removal of a column.
@@ -85,6 +87,30 @@ impl A1Selection {
         selection.translate_in_place(x, y);
         selection
     }
+
+    /// Expands the finite ranges of the selection so they fully contain any
+    /// merged regions they intersect. Returns whether the selection changed.
+    pub fn expand_to_merges(&mut self, merge_cells: &MergeCells) -> bool {
+        if merge_cells.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+        self.ranges.iter_mut().for_each(|range| {
+            if let Some(rect) = range.to_rect() {
+                let expanded = merge_cells.expand_rect(rect);
+                if expanded != rect {
+                    *range = CellRefRange::new_relative_rect(expanded);
+                    changed = true;
+                }
+            }
+        });
+
+        if changed {
+            self.update_cursor();
+        }
+        changed
+    }
 }
 
 #[cfg(test)]
@@ -426,4 +452,22 @@ mod tests {
         assert_eq!(translated, A1Selection::test_a1("A1"));
         assert_eq!(selection, A1Selection::test_a1("A1"));
     }
+
+    #[test]
+    fn test_expand_to_merges() {
+        let mut merge_cells = MergeCells::default();
+        merge_cells.merge(crate::Rect::test_a1("B2:C3"));
+
+        let mut selection = A1Selection::test_a1("C3");
+        assert!(selection.expand_to_merges(&merge_cells));
+        assert_eq!(selection, A1Selection::test_a1("B2:C3"));
+        assert_eq!(selection.cursor, pos![B2]);
+
+        let mut selection = A1Selection::test_a1("A1:B2,E");
+        assert!(selection.expand_to_merges(&merge_cells));
+        assert_eq!(selection, A1Selection::test_a1("A1:C3,E"));
+
+        let mut selection = A1Selection::test_a1("D4");
+        assert!(!selection.expand_to_merges(&merge_cells));
+    }
 }
@@ -3,7 +3,10 @@ use std::str::FromStr;
 use ts_rs::TS;
 use wasm_bindgen::prelude::*;
 
-use crate::{grid::SheetId, Pos, Rect, SheetRect};
+use crate::{
+    grid::{sheet::merge_cells::MergeCells, SheetId},
+    Pos, Rect, SheetRect,
+};
 
 use super::{A1Selection, SheetNameIdMap};
 
@@ -303,6 +306,15 @@ impl JsSelection {
             Some(Pos::new(x1 as i64, y1 as i64)),
         );
     }
+
+    /// Expands the selection to include any merged regions it intersects.
+    /// `merge_cells` is the JSON sent in SheetInfo.
+    #[wasm_bindgen(js_name = "expandToMerges")]
+    pub fn expand_to_merges(&mut self, merge_cells: String) -> Result<bool, String> {
+        let merge_cells =
+            serde_json::from_str::<MergeCells>(&merge_cells).map_err(|e| e.to_string())?;
+        Ok(self.selection.expand_to_merges(&merge_cells))
+    }
 }
 
 #[wasm_bindgen(js_name = "stringToSelection")]
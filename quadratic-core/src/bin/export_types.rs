@@ -5,13 +5,14 @@ use quadratic_core::controller::active_transactions::transaction_name::Transacti
 use quadratic_core::controller::execution::run_code::get_cells::CellA1Response;
 use quadratic_core::controller::execution::run_code::get_cells::JsGetCellResponse;
 use quadratic_core::controller::operations::clipboard::PasteSpecial;
+use quadratic_core::controller::operations::what_if::{DataTableInput, GoalSeekResult};
 use quadratic_core::controller::transaction_types::JsCodeResult;
 use quadratic_core::grid::formats::Format;
 use quadratic_core::grid::js_types::{
     CellFormatSummary, JsCellValue, JsCellValuePos, JsCellValuePosAIContext, JsClipboard,
     JsCodeCell, JsHtmlOutput, JsNumber, JsOffset, JsRenderCell, JsRenderCellSpecial,
-    JsRenderCodeCell, JsRenderCodeCellState, JsRenderFill, JsReturnInfo, JsRowHeight, JsSheetFill,
-    JsSummarizeSelectionResult, JsValidationWarning,
+    JsRenderCodeCell, JsRenderCodeCellState, JsRenderDataBar, JsRenderFill, JsReturnInfo,
+    JsRowHeight, JsSheetFill, JsSummarizeSelectionResult, JsValidationWarning,
 };
 use quadratic_core::grid::sheet::borders::BorderSelection;
 use quadratic_core::grid::sheet::borders::BorderSide;
@@ -22,7 +23,13 @@ use quadratic_core::grid::sheet::borders::CellBorderLine;
 use quadratic_core::grid::sheet::borders::JsBorderHorizontal;
 use quadratic_core::grid::sheet::borders::JsBorderVertical;
 use quadratic_core::grid::sheet::borders::JsBordersSheet;
+use quadratic_core::grid::sheet::comments::{Comment, CommentThread};
+use quadratic_core::grid::sheet::conditional_formats::conditional_format::ConditionalFormat;
+use quadratic_core::grid::sheet::conditional_formats::conditional_format_rule::{
+    ColorScale, ConditionalFormatRule, DataBar, NumberComparison,
+};
 use quadratic_core::grid::sheet::jump_cursor::JumpDirection;
+use quadratic_core::grid::sheet::protections::{ProtectedRange, ProtectionEditor, ProtectionRole};
 use quadratic_core::grid::sheet::search::SearchOptions;
 use quadratic_core::grid::sheet::validations::validation::{
     Validation, ValidationError, ValidationMessage, ValidationStyle,
@@ -44,7 +51,7 @@ use quadratic_core::grid::sheet::validations::validation_rules::ValidationRule;
 use quadratic_core::grid::{
     CellAlign, CellVerticalAlign, CellWrap, GridBounds, NumericFormat, NumericFormatKind, SheetId,
 };
-use quadratic_core::grid::{CodeCellLanguage, ConnectionKind};
+use quadratic_core::grid::{CodeCellLanguage, CodeCellRefresh, ConnectionKind};
 use quadratic_core::grid::{JsCellsAccessed, RenderSize};
 use quadratic_core::sheet_offsets::resize_transient::TransientResize;
 use quadratic_core::sheet_offsets::sheet_offsets_wasm::ColumnRow;
@@ -93,10 +100,19 @@ fn main() {
         CellVerticalAlign,
         CellWrap,
         CodeCellLanguage,
+        CodeCellRefresh,
+        ColorScale,
         ColumnRow,
+        Comment,
+        CommentThread,
+        ConditionalFormat,
+        ConditionalFormatRule,
         ConnectionKind,
+        DataBar,
+        DataTableInput,
         DateTimeRange,
         Format,
+        GoalSeekResult,
         GridBounds,
         JsBordersSheet,
         JsBorderHorizontal,
@@ -117,6 +133,7 @@ fn main() {
         JsRenderCellSpecial,
         JsRenderCodeCell,
         JsRenderCodeCellState,
+        JsRenderDataBar,
         JsRenderFill,
         JsReturnInfo,
         JsRowHeight,
@@ -124,11 +141,15 @@ fn main() {
         JsSummarizeSelectionResult,
         JsValidationWarning,
         JumpDirection,
+        NumberComparison,
         NumberRange,
         NumericFormat,
         NumericFormatKind,
         PasteSpecial,
         Pos,
+        ProtectedRange,
+        ProtectionEditor,
+        ProtectionRole,
         RefRangeBounds,
         Rect,
         RenderSize,
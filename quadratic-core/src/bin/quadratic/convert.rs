@@ -0,0 +1,348 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use bigdecimal::ToPrimitive;
+use parquet::arrow::ArrowWriter;
+use quadratic_core::controller::host::NativeHost;
+use quadratic_core::controller::GridController;
+use quadratic_core::grid::file::{export, import};
+use quadratic_core::grid::{Grid, GridBounds, Sheet};
+use quadratic_core::{A1Selection, CellValue, Pos, Rect};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Grid,
+    Csv,
+    Json,
+    Xlsx,
+    Parquet,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        Ok(match extension.as_str() {
+            "grid" => Format::Grid,
+            "csv" => Format::Csv,
+            "json" => Format::Json,
+            "xlsx" => Format::Xlsx,
+            "parquet" => Format::Parquet,
+            _ => bail!("unsupported file type: {}", path.display()),
+        })
+    }
+}
+
+/// Reads a file into a grid controller that computes formulas natively.
+pub fn read(path: &Path) -> Result<GridController> {
+    let format = Format::from_path(path)?;
+    let file = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+
+    if format == Format::Grid {
+        return Ok(GridController::from_grid_with_host(
+            import(file)?,
+            0,
+            NativeHost,
+        ));
+    }
+
+    let grid = match format {
+        Format::Xlsx => Grid::new_blank(),
+        _ => Grid::new(),
+    };
+    let mut gc = GridController::from_grid_with_host(grid, 0, NativeHost);
+    let sheet_id = gc.sheet_ids().first().copied();
+    let insert_at = Pos { x: 1, y: 1 };
+    match (format, sheet_id) {
+        (Format::Xlsx, _) => gc.import_excel(file, file_name, None)?,
+        (Format::Csv, Some(sheet_id)) => {
+            gc.import_csv(sheet_id, file, file_name, insert_at, None)?;
+        }
+        (Format::Parquet, Some(sheet_id)) => {
+            gc.import_parquet(sheet_id, file, file_name, insert_at, None)?;
+        }
+        _ => bail!("can't read {}", path.display()),
+    }
+    Ok(gc)
+}
+
+/// Writes the grid, or a single sheet of it for formats with one table.
+pub fn write(gc: &GridController, path: &Path, sheet: Option<&Sheet>) -> Result<()> {
+    let sheet = match sheet {
+        Some(sheet) => sheet,
+        None => gc.grid().sheets().first().context("file has no sheets")?,
+    };
+    let file = match Format::from_path(path)? {
+        Format::Grid => export(gc.grid().clone())?,
+        Format::Xlsx => super::xlsx::write(gc.grid())?,
+        Format::Csv => match sheet.bounds(true) {
+            GridBounds::Empty => vec![],
+            GridBounds::NonEmpty(rect) => gc
+                .export_csv_selection(&A1Selection::from_rect(rect.to_sheet_rect(sheet.id)))?
+                .into_bytes(),
+        },
+        Format::Json => {
+            let rows = match sheet.bounds(true) {
+                GridBounds::Empty => vec![],
+                GridBounds::NonEmpty(rect) => json_rows(sheet, rect),
+            };
+            serde_json::to_vec_pretty(&rows)?
+        }
+        Format::Parquet => parquet(sheet)?,
+    };
+    fs::write(path, file).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Converts a displayed value to JSON, keeping numbers and booleans typed.
+fn json_value(value: Option<CellValue>) -> serde_json::Value {
+    match value {
+        None | Some(CellValue::Blank) => serde_json::Value::Null,
+        Some(CellValue::Number(n)) if n.is_integer() && n.to_i64().is_some() => n.to_i64().into(),
+        Some(CellValue::Number(n)) => n
+            .to_f64()
+            .and_then(serde_json::Number::from_f64)
+            .map_or_else(|| n.to_string().into(), serde_json::Value::Number),
+        Some(CellValue::Logical(b)) => b.into(),
+        Some(value) => value.to_string().into(),
+    }
+}
+
+/// Returns the displayed values of `rect` as rows of JSON values.
+pub fn json_rows(sheet: &Sheet, rect: Rect) -> Vec<Vec<serde_json::Value>> {
+    rect.y_range()
+        .map(|y| {
+            rect.x_range()
+                .map(|x| json_value(sheet.display_value(Pos { x, y })))
+                .collect()
+        })
+        .collect()
+}
+
+/// Writes a sheet as a Parquet table. The first row has the column names.
+fn parquet(sheet: &Sheet) -> Result<Vec<u8>> {
+    let GridBounds::NonEmpty(rect) = sheet.bounds(true) else {
+        bail!("sheet `{}` is empty", sheet.name);
+    };
+
+    let mut fields = vec![];
+    let mut columns: Vec<ArrayRef> = vec![];
+    for x in rect.x_range() {
+        let name = sheet
+            .display_value(Pos { x, y: rect.min.y })
+            .map(|value| value.to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("column{}", x - rect.min.x + 1));
+        let values = (rect.min.y + 1..=rect.max.y)
+            .map(|y| sheet.display_value(Pos { x, y }))
+            .map(|value| value.filter(|value| !value.is_blank_or_empty_string()))
+            .collect::<Vec<_>>();
+
+        let (data_type, column): (DataType, ArrayRef) = if values
+            .iter()
+            .flatten()
+            .all(|value| matches!(value, CellValue::Number(_)))
+        {
+            let values = values.iter().map(|value| match value {
+                Some(CellValue::Number(n)) => n.to_f64(),
+                _ => None,
+            });
+            (DataType::Float64, Arc::new(Float64Array::from_iter(values)))
+        } else if values
+            .iter()
+            .flatten()
+            .all(|value| matches!(value, CellValue::Logical(_)))
+        {
+            let values = values.iter().map(|value| match value {
+                Some(CellValue::Logical(b)) => Some(*b),
+                _ => None,
+            });
+            (DataType::Boolean, Arc::new(BooleanArray::from_iter(values)))
+        } else {
+            let values = values
+                .iter()
+                .map(|value| value.as_ref().map(|value| value.to_string()));
+            (DataType::Utf8, Arc::new(StringArray::from_iter(values)))
+        };
+        fields.push(Field::new(name, data_type, true));
+        columns.push(column);
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    let mut file = vec![];
+    let mut writer = ArrowWriter::try_new(&mut file, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(file)
+}
+
+/// A cell whose displayed value differs between two files. Sheets are
+/// matched by name; a sheet in only one file has `cell` set to `None`.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ValueChange {
+    pub sheet: String,
+    pub cell: Option<String>,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl fmt::Display for ValueChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.cell {
+            None if self.old.is_none() => write!(f, "+ sheet {}", self.sheet),
+            None => write!(f, "- sheet {}", self.sheet),
+            Some(cell) => write!(
+                f,
+                "{}!{cell}: {} -> {}",
+                self.sheet,
+                self.old.as_deref().unwrap_or("(blank)"),
+                self.new.as_deref().unwrap_or("(blank)")
+            ),
+        }
+    }
+}
+
+/// Compares the displayed values of two grids, including code outputs.
+pub fn diff_values<'a>(old: &'a GridController, new: &'a GridController) -> Vec<ValueChange> {
+    let mut changes = vec![];
+    let find = |gc: &'a GridController, name: &str| {
+        gc.grid().sheets().iter().find(|sheet| sheet.name == name)
+    };
+    let display = |sheet: &Sheet, pos: Pos| {
+        sheet
+            .display_value(pos)
+            .filter(|value| !value.is_blank_or_empty_string())
+            .map(|value| value.to_string())
+    };
+
+    for old_sheet in old.grid().sheets() {
+        let Some(new_sheet) = find(new, &old_sheet.name) else {
+            changes.push(ValueChange {
+                sheet: old_sheet.name.clone(),
+                cell: None,
+                old: Some(old_sheet.name.clone()),
+                new: None,
+            });
+            continue;
+        };
+        let mut cells = BTreeSet::new();
+        for sheet in [old_sheet, new_sheet] {
+            if let GridBounds::NonEmpty(rect) = sheet.bounds(true) {
+                cells.extend(rect.iter().map(|pos| (pos.y, pos.x)));
+            }
+        }
+        for (y, x) in cells {
+            let pos = Pos { x, y };
+            let (old_value, new_value) = (display(old_sheet, pos), display(new_sheet, pos));
+            if old_value != new_value {
+                changes.push(ValueChange {
+                    sheet: old_sheet.name.clone(),
+                    cell: Some(pos.a1_string()),
+                    old: old_value,
+                    new: new_value,
+                });
+            }
+        }
+    }
+    for new_sheet in new.grid().sheets() {
+        if find(old, &new_sheet.name).is_none() {
+            changes.push(ValueChange {
+                sheet: new_sheet.name.clone(),
+                cell: None,
+                old: None,
+                new: Some(new_sheet.name.clone()),
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use quadratic_core::SheetPos;
+    use serde_json::json;
+
+    use super::*;
+
+    fn grid_controller() -> GridController {
+        GridController::from_grid_with_host(Grid::new(), 0, NativeHost)
+    }
+
+    #[test]
+    fn test_json_value() {
+        assert_eq!(json_value(None), serde_json::Value::Null);
+        assert_eq!(json_value(Some(CellValue::Blank)), serde_json::Value::Null);
+        assert_eq!(json_value(Some(CellValue::Number(3.into()))), json!(3));
+        assert_eq!(
+            json_value(Some(CellValue::Number("1.5".parse().unwrap()))),
+            json!(1.5)
+        );
+        assert_eq!(json_value(Some(CellValue::Logical(true))), json!(true));
+        assert_eq!(
+            json_value(Some(CellValue::Text("hello".into()))),
+            json!("hello")
+        );
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let mut old = grid_controller();
+        let sheet_id = old.sheet_ids()[0];
+        old.set_cell_value(SheetPos::new(sheet_id, 1, 1), "1".to_string(), None);
+        old.set_cell_value(SheetPos::new(sheet_id, 2, 1), "same".to_string(), None);
+        assert!(diff_values(&old, &old).is_empty());
+
+        let mut new = old.clone();
+        let sheet_id = new.sheet_ids()[0];
+        new.set_cell_value(SheetPos::new(sheet_id, 1, 1), "2".to_string(), None);
+        new.set_cell_value(SheetPos::new(sheet_id, 1, 2), "added".to_string(), None);
+        new.add_sheet(None);
+        let added_sheet = new.grid().sheets()[1].name.clone();
+
+        let diff = diff_values(&old, &new);
+        assert_eq!(
+            diff,
+            vec![
+                ValueChange {
+                    sheet: "Sheet 1".to_string(),
+                    cell: Some("A1".to_string()),
+                    old: Some("1".to_string()),
+                    new: Some("2".to_string()),
+                },
+                ValueChange {
+                    sheet: "Sheet 1".to_string(),
+                    cell: Some("A2".to_string()),
+                    old: None,
+                    new: Some("added".to_string()),
+                },
+                ValueChange {
+                    sheet: added_sheet.clone(),
+                    cell: None,
+                    old: None,
+                    new: Some(added_sheet.clone()),
+                },
+            ]
+        );
+        assert_eq!(diff[0].to_string(), "Sheet 1!A1: 1 -> 2");
+        assert_eq!(diff[1].to_string(), "Sheet 1!A2: (blank) -> added");
+        assert_eq!(diff[2].to_string(), format!("+ sheet {added_sheet}"));
+
+        // a removed sheet
+        let diff = diff_values(&new, &old);
+        assert_eq!(diff[2].to_string(), format!("- sheet {added_sheet}"));
+    }
+}
@@ -0,0 +1,442 @@
+//! `quadratic`: inspect, evaluate, convert and diff grid files without a
+//! browser.
+//!
+//! Files are read and written by extension: `.grid`, `.csv`, `.xlsx` and
+//! `.parquet` (plus `.json` for output). Formulas are computed natively;
+//! Python, Javascript and connection cells keep their last output since
+//! there's no client to run them.
+
+mod convert;
+mod xlsx;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::ExitCode;
+
+use anyhow::{anyhow, bail, Context, Result};
+use quadratic_core::controller::GridController;
+use quadratic_core::grid::file::CURRENT_VERSION;
+use quadratic_core::grid::{CodeCellLanguage, GridBounds, Sheet};
+use quadratic_core::{A1Selection, CellValue, Pos, SheetPos};
+
+const USAGE: &str = "\
+Usage: quadratic <command> [options]
+
+Commands:
+  info <file>                         list sheets and their bounds
+  dump <file>                         print cells as CSV or JSON
+      [--sheet <name>] [--range <a1>] [--format csv|json]
+  set <file> <cell>=<value>...        set cells (values starting with = are formulas)
+      --output <file> [--sheet <name>]
+  recalc <file> --output <file>       recompute all formulas
+  upgrade <file> --output <file>      save a .grid file in the current version
+  convert <input> <output>            convert between .grid, .csv, .xlsx and .parquet
+      [--sheet <name>]
+  diff <old> <new> [--format text|json]
+                                      compare displayed values (exits with 1 if different)
+";
+
+/// Command-line arguments: positional arguments and `--name value` options.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut positional = vec![];
+        let mut options = HashMap::new();
+        while let Some(arg) = args.next() {
+            let name = match arg.as_str() {
+                "-o" => "output",
+                "-s" => "sheet",
+                _ => match arg.strip_prefix("--") {
+                    Some(name) => name,
+                    None => {
+                        positional.push(arg);
+                        continue;
+                    }
+                },
+            };
+            let value = args
+                .next()
+                .with_context(|| format!("missing value for {arg}"))?;
+            options.insert(name.to_string(), value);
+        }
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn file(&self, index: usize) -> Result<&str> {
+        self.positional
+            .get(index)
+            .map(|file| file.as_str())
+            .ok_or_else(|| anyhow!("missing file argument\n\n{USAGE}"))
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(|value| value.as_str())
+    }
+
+    /// The file to write. It's required so the input is never overwritten
+    /// by accident.
+    fn output(&self) -> Result<&str> {
+        self.option("output")
+            .ok_or_else(|| anyhow!("missing --output <file>\n\n{USAGE}"))
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else {
+        eprint!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let result = Args::parse(args).and_then(|args| match command.as_str() {
+        "info" => info(&args),
+        "dump" => dump(&args),
+        "set" => set(&args),
+        "recalc" => recalc(&args),
+        "upgrade" => upgrade(&args),
+        "convert" => convert(&args),
+        "diff" => diff(&args),
+        "help" | "--help" | "-h" => {
+            print!("{USAGE}");
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => bail!("unknown command `{command}`\n\n{USAGE}"),
+    });
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Returns the sheet named `name`, or the first sheet.
+fn find_sheet<'a>(gc: &'a GridController, name: Option<&str>) -> Result<&'a Sheet> {
+    let sheets = gc.grid().sheets();
+    match name {
+        Some(name) => sheets
+            .iter()
+            .find(|sheet| sheet.name.eq_ignore_ascii_case(name))
+            .with_context(|| format!("sheet `{name}` not found")),
+        None => sheets.first().context("file has no sheets"),
+    }
+}
+
+fn info(args: &Args) -> Result<ExitCode> {
+    let file = args.file(0)?;
+    let gc = convert::read(Path::new(file))?;
+    println!("{file}");
+    for sheet in gc.grid().sheets() {
+        let bounds = match sheet.bounds(true) {
+            GridBounds::Empty => "empty".to_string(),
+            GridBounds::NonEmpty(rect) => format!(
+                "{}:{} ({} x {})",
+                rect.min.a1_string(),
+                rect.max.a1_string(),
+                rect.width(),
+                rect.height()
+            ),
+        };
+        println!(
+            "  {}: {bounds}, {} code cells",
+            sheet.name,
+            sheet.code_runs.len()
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn dump(args: &Args) -> Result<ExitCode> {
+    print!("{}", dump_text(args)?);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Returns the cells of `dump` as CSV or JSON.
+fn dump_text(args: &Args) -> Result<String> {
+    let gc = convert::read(Path::new(args.file(0)?))?;
+    let sheet = find_sheet(&gc, args.option("sheet"))?;
+    let selection = match args.option("range") {
+        Some(range) => A1Selection::from_str(range, &sheet.id, &gc.grid().sheet_name_id_map())
+            .map_err(|e| anyhow!("invalid range `{range}`: {e}"))?,
+        None => match sheet.bounds(true) {
+            GridBounds::Empty => return Ok(String::new()),
+            GridBounds::NonEmpty(rect) => A1Selection::from_rect(rect.to_sheet_rect(sheet.id)),
+        },
+    };
+    match args.option("format").unwrap_or("csv") {
+        "csv" => Ok(gc.export_csv_selection(&selection).unwrap_or_default()),
+        "json" => {
+            let sheet = gc
+                .try_sheet(selection.sheet_id)
+                .context("sheet not found")?;
+            let rows = match sheet.selection_bounds(&selection) {
+                Some(rect) => convert::json_rows(sheet, rect),
+                None => vec![],
+            };
+            Ok(format!("{}\n", serde_json::to_string_pretty(&rows)?))
+        }
+        format => bail!("unknown format `{format}`"),
+    }
+}
+
+fn set(args: &Args) -> Result<ExitCode> {
+    let file = args.file(0)?;
+    let output = args.output()?;
+    let mut gc = convert::read(Path::new(file))?;
+    let sheet_id = find_sheet(&gc, args.option("sheet"))?.id;
+    for assignment in &args.positional[1..] {
+        let (cell, value) = assignment
+            .split_once('=')
+            .with_context(|| format!("expected <cell>=<value>, found `{assignment}`"))?;
+        let pos =
+            Pos::try_a1_string(cell.trim()).with_context(|| format!("invalid cell `{cell}`"))?;
+        let sheet_pos = SheetPos::new(sheet_id, pos.x, pos.y);
+        match value.strip_prefix('=') {
+            Some(formula) => {
+                gc.set_code_cell(
+                    sheet_pos,
+                    CodeCellLanguage::Formula,
+                    formula.to_string(),
+                    None,
+                );
+            }
+            None => gc.set_cell_value(sheet_pos, value.to_string(), None),
+        }
+    }
+    convert::write(&gc, Path::new(output), None)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn recalc(args: &Args) -> Result<ExitCode> {
+    let file = args.file(0)?;
+    let output = args.output()?;
+    let mut gc = convert::read(Path::new(file))?;
+    let formulas = gc
+        .grid()
+        .sheets()
+        .iter()
+        .flat_map(|sheet| {
+            sheet
+                .code_runs
+                .keys()
+                .filter_map(|&pos| match sheet.cell_value_ref(pos) {
+                    Some(CellValue::Code(code)) if code.language == CodeCellLanguage::Formula => {
+                        Some(pos.to_sheet_pos(sheet.id))
+                    }
+                    _ => None,
+                })
+        })
+        .collect::<Vec<_>>();
+    gc.refresh_code_cells(&formulas, None);
+    convert::write(&gc, Path::new(output), None)?;
+    println!("recomputed {} formulas", formulas.len());
+    Ok(ExitCode::SUCCESS)
+}
+
+fn upgrade(args: &Args) -> Result<ExitCode> {
+    let file = args.file(0)?;
+    let output = Path::new(args.output()?);
+    if convert::Format::from_path(output)? != convert::Format::Grid {
+        bail!("upgrade writes a .grid file");
+    }
+    let gc = convert::read(Path::new(file))?;
+    convert::write(&gc, output, None)?;
+    println!("saved {} as version {CURRENT_VERSION}", output.display());
+    Ok(ExitCode::SUCCESS)
+}
+
+fn convert(args: &Args) -> Result<ExitCode> {
+    let input = args.file(0)?;
+    let output = args.file(1)?;
+    let gc = convert::read(Path::new(input))?;
+    let sheet = args
+        .option("sheet")
+        .map(|name| find_sheet(&gc, Some(name)))
+        .transpose()?;
+    convert::write(&gc, Path::new(output), sheet)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(args: &Args) -> Result<ExitCode> {
+    let old = convert::read(Path::new(args.file(0)?))?;
+    let new = convert::read(Path::new(args.file(1)?))?;
+    let diff = convert::diff_values(&old, &new);
+    match args.option("format").unwrap_or("text") {
+        "text" => {
+            for change in &diff {
+                println!("{change}");
+            }
+        }
+        "json" => println!("{}", serde_json::to_string_pretty(&diff)?),
+        format => bail!("unknown format `{format}`"),
+    }
+    Ok(if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::Utc;
+    use quadratic_core::controller::host::NativeHost;
+    use quadratic_core::grid::{CellsAccessed, CodeCellValue, CodeRun, CodeRunResult, Grid};
+    use quadratic_core::Value;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Args {
+        Args::parse(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    /// A path in the temp directory that is removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(extension: &str) -> Self {
+            Self(std::env::temp_dir().join(format!("{}.{extension}", Uuid::new_v4())))
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn write_grid(gc: &GridController) -> TempFile {
+        let file = TempFile::new("grid");
+        convert::write(gc, Path::new(file.path()), None).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = args(&["in.grid", "A1=1", "-o", "out.grid", "--sheet", "Sheet 2"]);
+        assert_eq!(args.positional, vec!["in.grid", "A1=1"]);
+        assert_eq!(args.file(0).unwrap(), "in.grid");
+        assert!(args.file(2).is_err());
+        assert_eq!(args.output().unwrap(), "out.grid");
+        assert_eq!(args.option("sheet"), Some("Sheet 2"));
+        assert_eq!(args.option("format"), None);
+
+        let error = Args::parse(["in.grid".to_string(), "--format".to_string()].into_iter());
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn test_output_is_required() {
+        let input = write_grid(&GridController::from_grid_with_host(
+            Grid::new(),
+            0,
+            NativeHost,
+        ));
+        for command in [set, recalc, upgrade] {
+            assert!(command(&args(&[input.path(), "A1=1"])).is_err());
+        }
+        let gc = convert::read(Path::new(input.path())).unwrap();
+        assert_eq!(
+            gc.try_sheet(gc.sheet_ids()[0])
+                .unwrap()
+                .display_value(Pos { x: 1, y: 1 }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_and_dump() {
+        let input = write_grid(&GridController::from_grid_with_host(
+            Grid::new(),
+            0,
+            NativeHost,
+        ));
+        let output = TempFile::new("grid");
+        set(&args(&[
+            input.path(),
+            "A1=2",
+            "B1==A1 * 3",
+            "C1=hello",
+            "--output",
+            output.path(),
+        ]))
+        .unwrap();
+
+        assert_eq!(dump_text(&args(&[output.path()])).unwrap(), "2,6,hello\n");
+        assert_eq!(
+            dump_text(&args(&[
+                output.path(),
+                "--range",
+                "A1:B1",
+                "--format",
+                "json"
+            ]))
+            .unwrap(),
+            "[\n  [\n    2,\n    6\n  ]\n]\n"
+        );
+
+        // the input is unchanged
+        assert_eq!(dump_text(&args(&[input.path()])).unwrap(), "");
+    }
+
+    #[test]
+    fn test_recalc_keeps_code_output() {
+        let mut gc = GridController::from_grid_with_host(Grid::new(), 0, NativeHost);
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 1), "1".to_string(), None);
+        gc.set_code_cell(
+            SheetPos::new(sheet_id, 2, 1),
+            CodeCellLanguage::Formula,
+            "A1 + 1".to_string(),
+            None,
+        );
+
+        // a Python cell that read B1 when it last ran in the browser
+        let mut cells_accessed = CellsAccessed::default();
+        cells_accessed.add_sheet_pos(SheetPos::new(sheet_id, 2, 1));
+        let sheet = gc.try_sheet_mut(sheet_id).unwrap();
+        sheet.set_cell_value(
+            Pos { x: 3, y: 1 },
+            CellValue::Code(CodeCellValue {
+                language: CodeCellLanguage::Python,
+                code: "q.cells('B1') * 21".to_string(),
+            }),
+        );
+        sheet.set_code_run(
+            Pos { x: 3, y: 1 },
+            Some(CodeRun {
+                std_out: None,
+                std_err: None,
+                formatted_code_string: None,
+                last_modified: Utc::now(),
+                cells_accessed,
+                result: CodeRunResult::Ok(Value::Single(CellValue::Number(42.into()))),
+                return_type: Some("number".into()),
+                spill_error: false,
+                line_number: None,
+                output_type: None,
+                refresh: Default::default(),
+                volatile: false,
+            }),
+        );
+        let input = write_grid(&gc);
+
+        let output = TempFile::new("grid");
+        recalc(&args(&[input.path(), "--output", output.path()])).unwrap();
+        assert_eq!(dump_text(&args(&[output.path()])).unwrap(), "1,2,42\n");
+    }
+}
@@ -0,0 +1,170 @@
+//! A minimal XLSX writer: one worksheet per sheet with the displayed values.
+//! Formulas with a single cell output are kept as formulas.
+
+use std::io::{Cursor, Write};
+
+use anyhow::Result;
+use htmlescape::{encode_attribute, encode_minimal};
+use quadratic_core::formulas::replace_internal_cell_references;
+use quadratic_core::grid::{CodeCellLanguage, Grid, GridBounds, Sheet};
+use quadratic_core::{ArraySize, CellValue, Pos};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const CONTENT_TYPES_START: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const WORKBOOK_START: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#;
+
+const RELS_START: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#;
+
+const WORKSHEET_START: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#;
+
+pub fn write(grid: &Grid) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(vec![]));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut content_types = CONTENT_TYPES_START.to_string();
+    let mut workbook = WORKBOOK_START.to_string();
+    let mut rels = RELS_START.to_string();
+    for (index, sheet) in grid.sheets().iter().enumerate() {
+        let n = index + 1;
+        content_types.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{n}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#
+        ));
+        workbook.push_str(&format!(
+            r#"<sheet name="{}" sheetId="{n}" r:id="rId{n}"/>"#,
+            encode_attribute(&sheet.name)
+        ));
+        rels.push_str(&format!(
+            r#"<Relationship Id="rId{n}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{n}.xml"/>"#
+        ));
+
+        zip.start_file(format!("xl/worksheets/sheet{n}.xml"), options)?;
+        zip.write_all(worksheet(sheet).as_bytes())?;
+    }
+    content_types.push_str("</Types>");
+    workbook.push_str("</sheets></workbook>");
+    rels.push_str("</Relationships>");
+
+    zip.start_file("[Content_Types].xml", options)?;
+    zip.write_all(content_types.as_bytes())?;
+    zip.start_file("_rels/.rels", options)?;
+    zip.write_all(ROOT_RELS.as_bytes())?;
+    zip.start_file("xl/workbook.xml", options)?;
+    zip.write_all(workbook.as_bytes())?;
+    zip.start_file("xl/_rels/workbook.xml.rels", options)?;
+    zip.write_all(rels.as_bytes())?;
+
+    Ok(zip.finish()?.into_inner())
+}
+
+fn worksheet(sheet: &Sheet) -> String {
+    let mut xml = WORKSHEET_START.to_string();
+    if let GridBounds::NonEmpty(rect) = sheet.bounds(true) {
+        for y in rect.y_range() {
+            xml.push_str(&format!(r#"<row r="{y}">"#));
+            for x in rect.x_range() {
+                let pos = Pos { x, y };
+                if let Some(value) = sheet.display_value(pos) {
+                    xml.push_str(&cell(sheet, pos, &value));
+                }
+            }
+            xml.push_str("</row>");
+        }
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn cell(sheet: &Sheet, pos: Pos, value: &CellValue) -> String {
+    let r = pos.a1_string();
+    let formula = match sheet.cell_value_ref(pos) {
+        Some(CellValue::Code(code))
+            if code.language == CodeCellLanguage::Formula
+                && sheet
+                    .code_run(pos)
+                    .is_some_and(|run| run.output_size() == ArraySize::_1X1) =>
+        {
+            let formula = replace_internal_cell_references(&code.code, pos);
+            format!("<f>{}</f>", encode_minimal(&formula))
+        }
+        _ => String::new(),
+    };
+    match value {
+        CellValue::Blank => String::new(),
+        CellValue::Number(n) => format!(r#"<c r="{r}">{formula}<v>{n}</v></c>"#),
+        CellValue::Logical(b) => {
+            format!(r#"<c r="{r}" t="b">{formula}<v>{}</v></c>"#, u8::from(*b))
+        }
+        CellValue::Error(_) if !formula.is_empty() => {
+            format!(r##"<c r="{r}" t="e">{formula}<v>#VALUE!</v></c>"##)
+        }
+        value if !formula.is_empty() => format!(
+            r#"<c r="{r}" t="str">{formula}<v>{}</v></c>"#,
+            encode_minimal(&value.to_string())
+        ),
+        value => format!(
+            r#"<c r="{r}" t="inlineStr"><is><t>{}</t></is></c>"#,
+            encode_minimal(&value.to_string())
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quadratic_core::controller::host::NativeHost;
+    use quadratic_core::controller::GridController;
+    use quadratic_core::SheetPos;
+
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let mut gc = GridController::from_grid_with_host(Grid::new(), 0, NativeHost);
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 1), "2".to_string(), None);
+        gc.set_cell_value(SheetPos::new(sheet_id, 2, 1), "a < b".to_string(), None);
+        gc.set_cell_value(SheetPos::new(sheet_id, 3, 1), "true".to_string(), None);
+        gc.set_code_cell(
+            SheetPos::new(sheet_id, 1, 2),
+            CodeCellLanguage::Formula,
+            "A1 * 3".to_string(),
+            None,
+        );
+        let file = write(gc.grid()).unwrap();
+
+        let mut imported = GridController::from_grid_with_host(Grid::new_blank(), 0, NativeHost);
+        imported.import_excel(file, "test.xlsx", None).unwrap();
+        let sheet = &imported.grid().sheets()[0];
+        assert_eq!(sheet.name, "Sheet 1");
+        assert_eq!(
+            sheet.display_value(Pos { x: 1, y: 1 }),
+            Some(CellValue::Number(2.into()))
+        );
+        assert_eq!(
+            sheet.display_value(Pos { x: 2, y: 1 }),
+            Some(CellValue::Text("a < b".into()))
+        );
+        assert_eq!(
+            sheet.display_value(Pos { x: 3, y: 1 }),
+            Some(CellValue::Logical(true))
+        );
+
+        // the formula is kept and computed again
+        let Some(CellValue::Code(code)) = sheet.cell_value_ref(Pos { x: 1, y: 2 }) else {
+            panic!("expected a formula");
+        };
+        assert_eq!(code.language, CodeCellLanguage::Formula);
+        assert_eq!(
+            sheet.display_value(Pos { x: 1, y: 2 }),
+            Some(CellValue::Number(6.into()))
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use flate2::{
+    read::ZlibDecoder as ZlibReadDecoder,
     write::{ZlibDecoder, ZlibEncoder},
     Compression,
 };
@@ -42,6 +43,21 @@ where
     deserialize::<T>(serialization_format, &decompressed)
 }
 
+/// Same as `decompress_and_deserialize`, but fails if the decompressed data is
+/// larger than `max_bytes`.
+pub fn decompress_and_deserialize_bounded<T>(
+    serialization_format: &SerializationFormat,
+    compression_format: &CompressionFormat,
+    data: &[u8],
+    max_bytes: usize,
+) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let decompressed = decompress_bounded(compression_format, data, max_bytes)?;
+    deserialize::<T>(serialization_format, &decompressed)
+}
+
 // SERIALIZATION
 
 pub fn serialize<T>(serialization_format: &SerializationFormat, data: T) -> Result<Vec<u8>>
@@ -94,6 +110,31 @@ pub fn decompress_zlib(data: &[u8]) -> Result<Vec<u8>> {
     Ok(decoder.finish()?)
 }
 
+/// Decompresses data, failing if the decompressed data is larger than
+/// `max_bytes`.  Only `max_bytes + 1` bytes are ever decompressed.
+pub fn decompress_bounded(
+    compression_format: &CompressionFormat,
+    data: &[u8],
+    max_bytes: usize,
+) -> Result<Vec<u8>> {
+    let decompressed = match compression_format {
+        CompressionFormat::None => data.to_vec(),
+        CompressionFormat::Zlib => {
+            let mut decompressed = Vec::new();
+            ZlibReadDecoder::new(data)
+                .take(max_bytes as u64 + 1)
+                .read_to_end(&mut decompressed)?;
+            decompressed
+        }
+    };
+
+    if decompressed.len() > max_bytes {
+        bail!("Decompressed data exceeds the limit of {max_bytes} bytes");
+    }
+
+    Ok(decompressed)
+}
+
 // HEADER
 
 pub fn add_header(header: Vec<u8>, data: Vec<u8>) -> Result<Vec<u8>> {
@@ -153,4 +194,16 @@ mod test {
 
         assert_roundtrip_compression(&serialization_format, &compression_format);
     }
+
+    #[test]
+    fn decompress_bounded_limit() {
+        let data = vec![0u8; 1000];
+        let compressed = compress(&CompressionFormat::Zlib, data.clone()).unwrap();
+
+        let decompressed = decompress_bounded(&CompressionFormat::Zlib, &compressed, 1000).unwrap();
+        assert_eq!(data, decompressed);
+
+        assert!(decompress_bounded(&CompressionFormat::Zlib, &compressed, 999).is_err());
+        assert!(decompress_bounded(&CompressionFormat::None, &data, 999).is_err());
+    }
 }
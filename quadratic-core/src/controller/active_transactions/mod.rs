@@ -75,7 +75,7 @@ impl ActiveTransactions {
     pub fn add_async_transaction(&mut self, pending: &mut PendingTransaction) {
         // Unsaved_operations hold async operations that are not complete. In that case, we need to replace the
         // unsaved operation with the new version.
-        self.unsaved_transactions.insert_or_replace(pending, false);
+        self.unsaved_transactions.insert_or_replace(pending, None);
         pending.has_async += 1;
         let transaction = pending.clone();
         if let Ok(index) = self.get_async_transaction_index(pending.id) {
@@ -88,7 +88,7 @@ impl ActiveTransactions {
     pub fn update_async_transaction(&mut self, pending: &PendingTransaction) {
         // Unsaved_operations hold async operations that are not complete. In that case, we need to replace the
         // unsaved operation with the new version.
-        self.unsaved_transactions.insert_or_replace(pending, false);
+        self.unsaved_transactions.insert_or_replace(pending, None);
         let transaction = pending.clone();
         if let Ok(index) = self.get_async_transaction_index(pending.id) {
             self.async_transactions[index] = transaction;
@@ -10,7 +10,8 @@ use uuid::Uuid;
 
 use crate::{
     controller::{
-        execution::TransactionSource, operations::operation::Operation, transaction::Transaction,
+        execution::TransactionSource, host::Host, operations::operation::Operation,
+        transaction::Transaction,
     },
     grid::{
         js_types::JsValidationWarning, sheet::validations::validation::Validation, CellsAccessed,
@@ -44,6 +45,18 @@ pub struct PendingTransaction {
     /// pending operations
     pub operations: VecDeque<Operation>,
 
+    /// code cells with a ComputeCode operation waiting in operations
+    pub pending_compute_code: HashSet<SheetPos>,
+
+    /// code cells computed in this transaction (each runs at most once)
+    pub computed_code: HashSet<SheetPos>,
+
+    /// code cells set to a circular reference error in this transaction
+    pub circular_code: HashSet<SheetPos>,
+
+    /// pending code cells with new code (their cells_accessed are out of date)
+    pub changed_code: HashSet<SheetPos>,
+
     /// undo operations
     pub reverse_operations: Vec<Operation>,
 
@@ -103,6 +116,10 @@ pub struct PendingTransaction {
 
     // offsets modified (sheet_id -> SheetOffsets)
     pub offsets_modified: HashMap<SheetId, SheetOffsets>,
+
+    /// conditional formats whose cached results need to be recalculated once
+    /// the transaction completes (sheet_id -> conditional format ids)
+    pub conditional_formats_stale: HashMap<SheetId, HashSet<Uuid>>,
 }
 
 impl Default for PendingTransaction {
@@ -113,6 +130,10 @@ impl Default for PendingTransaction {
             cursor: None,
             source: TransactionSource::User,
             operations: VecDeque::new(),
+            pending_compute_code: HashSet::new(),
+            computed_code: HashSet::new(),
+            circular_code: HashSet::new(),
+            changed_code: HashSet::new(),
             reverse_operations: Vec::new(),
             forward_operations: Vec::new(),
             has_async: 0,
@@ -133,6 +154,7 @@ impl Default for PendingTransaction {
             fill_cells: HashSet::new(),
             sheet_info: HashSet::new(),
             offsets_modified: HashMap::new(),
+            conditional_formats_stale: HashMap::new(),
         }
     }
 }
@@ -170,18 +192,18 @@ impl PendingTransaction {
         }
     }
 
-    /// Sends the transaction to the multiplayer server (if needed)
-    pub fn send_transaction(&self) {
-        if self.complete
-            && self.is_user_undo_redo()
-            && (cfg!(target_family = "wasm") || cfg!(test))
-            && !self.is_server()
-        {
+    /// Sends the transaction to the multiplayer server through `host` (if
+    /// needed)
+    pub fn send_transaction(&self, host: &dyn Host) {
+        if self.complete && self.is_user_undo_redo() && host.renders() && !self.is_server() {
             let transaction_id = self.id.to_string();
 
             match Transaction::serialize_and_compress(&self.forward_operations) {
+                Ok(ops) if Operation::all_comments(&self.forward_operations) => {
+                    host.send_comment(transaction_id, ops);
+                }
                 Ok(ops) => {
-                    crate::wasm_bindings::js::jsSendTransaction(transaction_id, ops);
+                    host.send_transaction(transaction_id, ops);
                 }
                 Err(e) => {
                     dbgjs!(&self.forward_operations);
@@ -191,12 +213,12 @@ impl PendingTransaction {
 
             if self.is_undo_redo() {
                 if let Some(cursor) = &self.cursor_undo_redo {
-                    crate::wasm_bindings::js::jsSetCursor(cursor.clone());
+                    host.set_cursor(cursor.clone());
                 }
             }
 
             if self.generate_thumbnail {
-                crate::wasm_bindings::js::jsGenerateThumbnail();
+                host.generate_thumbnail();
             }
         }
     }
@@ -589,6 +611,8 @@ mod tests {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         transaction.add_from_code_run(sheet_id, pos, &Some(code_run));
         assert_eq!(transaction.code_cells.len(), 1);
@@ -606,6 +630,8 @@ mod tests {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         transaction.add_from_code_run(sheet_id, pos, &Some(code_run));
         assert_eq!(transaction.code_cells.len(), 1);
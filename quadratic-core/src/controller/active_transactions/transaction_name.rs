@@ -22,4 +22,10 @@ pub enum TransactionName {
     MoveCells,
     Validation,
     ManipulateColumnRow,
+    MergeCells,
+    ConditionalFormat,
+    Comment,
+    ProtectedRange,
+    GoalSeek,
+    DataTable,
 }
@@ -1,5 +1,5 @@
 use super::pending_transaction::PendingTransaction;
-use crate::controller::transaction::Transaction;
+use crate::controller::{host::Host, transaction::Transaction};
 use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
 use uuid::Uuid;
@@ -46,8 +46,9 @@ impl UnsavedTransactions {
             .position(|unsaved_transaction| unsaved_transaction.id() == transaction_id)
     }
 
-    /// Inserts or replaces a `PendingTransaction``.
-    pub fn insert_or_replace(&mut self, pending: &PendingTransaction, send: bool) {
+    /// Inserts or replaces a `PendingTransaction``. The transaction is sent to
+    /// `host`, if provided, so the client can store it until it is saved.
+    pub fn insert_or_replace(&mut self, pending: &PendingTransaction, host: Option<&dyn Host>) {
         let forward = pending.to_forward_transaction();
         let reverse = pending.to_undo_transaction();
         match self
@@ -60,9 +61,9 @@ impl UnsavedTransactions {
                     reverse,
                     sent_to_server: false,
                 };
-                if cfg!(target_family = "wasm") && send {
+                if let Some(host) = host.filter(|host| host.renders()) {
                     if let Ok(stringified) = serde_json::to_string(&transaction) {
-                        crate::wasm_bindings::js::addUnsentTransaction(
+                        host.add_unsent_transaction(
                             transaction.forward.id.to_string(),
                             stringified,
                             transaction.forward.operations.len() as u32,
@@ -74,9 +75,9 @@ impl UnsavedTransactions {
             Some(unsaved_transaction) => {
                 unsaved_transaction.forward = forward;
                 unsaved_transaction.reverse = reverse;
-                if cfg!(target_family = "wasm") && send {
+                if let Some(host) = host.filter(|host| host.renders()) {
                     if let Ok(stringified) = serde_json::to_string(&unsaved_transaction) {
-                        crate::wasm_bindings::js::addUnsentTransaction(
+                        host.add_unsent_transaction(
                             unsaved_transaction.forward.id.to_string(),
                             stringified,
                             unsaved_transaction.forward.operations.len() as u32,
@@ -129,7 +130,7 @@ mod test {
             id,
             ..Default::default()
         };
-        unsaved_transactions.insert_or_replace(&pending, false);
+        unsaved_transactions.insert_or_replace(&pending, None);
         assert_eq!(
             unsaved_transactions.find_forward(id),
             Some((0, &transaction))
@@ -146,7 +147,7 @@ mod test {
             new_size: 0.0,
             client_resized: true,
         });
-        unsaved_transactions.insert_or_replace(&pending_2, false);
+        unsaved_transactions.insert_or_replace(&pending_2, None);
         assert_eq!(unsaved_transactions.len(), 1);
         assert_eq!(
             unsaved_transactions.find_forward(id),
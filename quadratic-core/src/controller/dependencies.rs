@@ -2,22 +2,41 @@ use std::{self};
 
 use std::collections::HashSet;
 
-use crate::{SheetPos, SheetRect};
+use crate::{grid::SheetId, Pos, SheetPos, SheetRect};
 
 use super::GridController;
 
 impl GridController {
-    /// Searches all code_runs in all sheets for cells that are dependent on the given sheet_rect.
+    /// Finds the code cells in all sheets that are dependent on the given
+    /// sheet_rect. Uses the dependency index, falling back to scanning the
+    /// code_runs of sheets that are not fully indexed.
     pub fn get_dependent_code_cells(&self, sheet_rect: &SheetRect) -> Option<HashSet<SheetPos>> {
         let mut dependent_cells = HashSet::new();
 
-        self.grid.sheets().iter().for_each(|sheet| {
-            sheet.code_runs.iter().for_each(|(pos, code_run)| {
-                if code_run.cells_accessed.intersects(sheet_rect) {
-                    dependent_cells.insert(pos.to_sheet_pos(sheet.id));
-                }
+        for sheet_pos in self.dependencies.candidates(sheet_rect) {
+            let Some(sheet) = self.try_sheet(sheet_pos.sheet_id) else {
+                continue;
+            };
+            if sheet
+                .code_runs
+                .get(&Pos::from(sheet_pos))
+                .is_some_and(|code_run| code_run.cells_accessed.intersects(sheet_rect))
+            {
+                dependent_cells.insert(sheet_pos);
+            }
+        }
+
+        self.grid
+            .sheets()
+            .iter()
+            .filter(|sheet| !self.dependencies.is_indexed(sheet))
+            .for_each(|sheet| {
+                sheet.code_runs.iter().for_each(|(pos, code_run)| {
+                    if code_run.cells_accessed.intersects(sheet_rect) {
+                        dependent_cells.insert(pos.to_sheet_pos(sheet.id));
+                    }
+                });
             });
-        });
 
         if dependent_cells.is_empty() {
             None
@@ -25,6 +44,38 @@ impl GridController {
             Some(dependent_cells)
         }
     }
+
+    /// Updates the dependency index for a code cell after its code run
+    /// changes. `was_indexed` is whether the sheet was indexed before the
+    /// change; otherwise the whole sheet is reindexed.
+    pub(crate) fn update_dependencies(&mut self, sheet_pos: SheetPos, was_indexed: bool) {
+        let Some(sheet) = self.grid.try_sheet(sheet_pos.sheet_id) else {
+            self.dependencies.remove_sheet(sheet_pos.sheet_id);
+            return;
+        };
+
+        // code runs changed outside of finalize_code_run are picked up here
+        if !was_indexed {
+            self.dependencies.index_sheet(sheet);
+            return;
+        }
+
+        let cells_accessed = sheet
+            .code_runs
+            .get(&Pos::from(sheet_pos))
+            .map(|code_run| &code_run.cells_accessed);
+        self.dependencies.set(sheet_pos, cells_accessed);
+        self.dependencies.mark_indexed(sheet);
+    }
+
+    /// Reindexes the dependencies of a sheet's code cells after they move
+    /// (or the sheet is added or deleted).
+    pub(crate) fn update_sheet_dependencies(&mut self, sheet_id: SheetId) {
+        match self.grid.try_sheet(sheet_id) {
+            Some(sheet) => self.dependencies.index_sheet(sheet),
+            None => self.dependencies.remove_sheet(sheet_id),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -75,6 +126,8 @@ mod test {
                 line_number: None,
                 output_type: None,
                 cells_accessed: cells_accessed.clone(),
+                refresh: Default::default(),
+                volatile: false,
             }),
         );
         let sheet_pos_02 = SheetPos {
@@ -146,4 +199,29 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn test_dependencies_after_moving_code_run() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 1), "1".to_string(), None);
+        gc.set_code_cell(
+            SheetPos::new(sheet_id, 2, 1),
+            CodeCellLanguage::Formula,
+            "A1 + 5".to_string(),
+            None,
+        );
+
+        // moving the code run outside of a transaction keeps the number of
+        // code runs, but the index is no longer up to date
+        let sheet = gc.sheet_mut(sheet_id);
+        let code_run = sheet.set_code_run(Pos { x: 2, y: 1 }, None);
+        sheet.set_code_run(Pos { x: 3, y: 1 }, code_run);
+        assert!(!gc.dependencies.is_indexed(gc.sheet(sheet_id)));
+
+        assert_eq!(
+            gc.get_dependent_code_cells(&SheetPos::new(sheet_id, 1, 1).into()),
+            Some([SheetPos::new(sheet_id, 3, 1)].into())
+        );
+    }
 }
@@ -0,0 +1,294 @@
+//! Reverse index from the cells read by code runs to the code cells that read
+//! them, so the dependents of a change can be found without scanning every
+//! code run in the grid.
+//!
+//! Ranges are bucketed into blocks of BLOCK_SIZE x BLOCK_SIZE cells. Ranges
+//! that cover too many blocks (including unbounded column and row ranges) are
+//! kept in a per-sheet list that is checked for every lookup. Lookups return
+//! candidates; callers check the candidates against the code runs'
+//! cells_accessed.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    a1::{CellRefRange, UNBOUNDED},
+    grid::{CellsAccessed, Grid, Sheet, SheetId},
+    SheetPos, SheetRect,
+};
+
+/// Width and height of a block of cells
+const BLOCK_SIZE: i64 = 64;
+
+/// Ranges covering more blocks than this are not indexed by block
+const MAX_RANGE_BLOCKS: i64 = 256;
+
+/// Lookups covering more blocks than this return every code cell that reads
+/// the sheet
+const MAX_LOOKUP_BLOCKS: i64 = 1024;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DependencyIndex {
+    /// cells read by each indexed code cell
+    accessed: HashMap<SheetPos, CellsAccessed>,
+
+    /// number of indexed code cells in each sheet
+    counts: HashMap<SheetId, usize>,
+
+    /// code_runs_generation of each sheet when it was last known to be indexed
+    generations: HashMap<SheetId, u64>,
+
+    /// code cells that read each block (sheet_id, block x, block y)
+    blocks: HashMap<(SheetId, i64, i64), HashSet<SheetPos>>,
+
+    /// code cells that read ranges covering too many blocks to index
+    large: HashMap<SheetId, HashSet<SheetPos>>,
+
+    /// code cells that read anything in each sheet
+    readers: HashMap<SheetId, HashSet<SheetPos>>,
+}
+
+/// Bounds of a range in blocks (min x, min y, max x, max y), or None if the
+/// range covers too many blocks.
+fn range_blocks(range: &CellRefRange) -> Option<(i64, i64, i64, i64)> {
+    let CellRefRange::Sheet { range } = range;
+
+    let (x0, x1) = (range.start.col.coord, range.end.col.coord);
+    let (y0, y1) = (range.start.row.coord, range.end.row.coord);
+    if [x0, x1, y0, y1].contains(&UNBOUNDED) {
+        return None;
+    }
+
+    let blocks = (
+        x0.min(x1).div_euclid(BLOCK_SIZE),
+        y0.min(y1).div_euclid(BLOCK_SIZE),
+        x0.max(x1).div_euclid(BLOCK_SIZE),
+        y0.max(y1).div_euclid(BLOCK_SIZE),
+    );
+    (block_count(blocks) <= MAX_RANGE_BLOCKS).then_some(blocks)
+}
+
+fn block_count((x0, y0, x1, y1): (i64, i64, i64, i64)) -> i64 {
+    (x1.saturating_sub(x0).saturating_add(1))
+        .saturating_mul(y1.saturating_sub(y0).saturating_add(1))
+}
+
+impl DependencyIndex {
+    /// Indexes every code run in the grid.
+    pub fn new(grid: &Grid) -> Self {
+        let mut index = DependencyIndex::default();
+        grid.sheets()
+            .iter()
+            .for_each(|sheet| index.index_sheet(sheet));
+        index
+    }
+
+    /// Returns whether every code run in the sheet is indexed, i.e. its code
+    /// runs haven't changed since the index last caught up with them.
+    pub fn is_indexed(&self, sheet: &Sheet) -> bool {
+        self.generations.get(&sheet.id) == Some(&sheet.code_runs_generation())
+            && self.counts.get(&sheet.id).copied().unwrap_or_default() == sheet.code_runs.len()
+    }
+
+    /// Records that the sheet's current code runs are indexed, after its only
+    /// change since it was indexed was passed to [`Self::set`].
+    pub fn mark_indexed(&mut self, sheet: &Sheet) {
+        self.generations
+            .insert(sheet.id, sheet.code_runs_generation());
+    }
+
+    /// Sets the cells read by a code cell. None removes the code cell.
+    pub fn set(&mut self, sheet_pos: SheetPos, cells_accessed: Option<&CellsAccessed>) {
+        self.remove(sheet_pos);
+
+        let Some(cells_accessed) = cells_accessed else {
+            return;
+        };
+
+        for (sheet_id, ranges) in cells_accessed.cells.iter() {
+            self.readers.entry(*sheet_id).or_default().insert(sheet_pos);
+
+            for range in ranges.iter() {
+                match range_blocks(range) {
+                    Some((x0, y0, x1, y1)) => {
+                        for x in x0..=x1 {
+                            for y in y0..=y1 {
+                                self.blocks
+                                    .entry((*sheet_id, x, y))
+                                    .or_default()
+                                    .insert(sheet_pos);
+                            }
+                        }
+                    }
+                    None => {
+                        self.large.entry(*sheet_id).or_default().insert(sheet_pos);
+                    }
+                }
+            }
+        }
+
+        self.accessed.insert(sheet_pos, cells_accessed.clone());
+        *self.counts.entry(sheet_pos.sheet_id).or_default() += 1;
+    }
+
+    fn remove(&mut self, sheet_pos: SheetPos) {
+        let Some(cells_accessed) = self.accessed.remove(&sheet_pos) else {
+            return;
+        };
+
+        for (sheet_id, ranges) in cells_accessed.cells.iter() {
+            if let Some(readers) = self.readers.get_mut(sheet_id) {
+                readers.remove(&sheet_pos);
+            }
+
+            for range in ranges.iter() {
+                match range_blocks(range) {
+                    Some((x0, y0, x1, y1)) => {
+                        for x in x0..=x1 {
+                            for y in y0..=y1 {
+                                if let Some(block) = self.blocks.get_mut(&(*sheet_id, x, y)) {
+                                    block.remove(&sheet_pos);
+                                    if block.is_empty() {
+                                        self.blocks.remove(&(*sheet_id, x, y));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    None => {
+                        if let Some(large) = self.large.get_mut(sheet_id) {
+                            large.remove(&sheet_pos);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(count) = self.counts.get_mut(&sheet_pos.sheet_id) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// Removes every code cell in the sheet from the index.
+    pub fn remove_sheet(&mut self, sheet_id: SheetId) {
+        let code_cells = self
+            .accessed
+            .keys()
+            .filter(|sheet_pos| sheet_pos.sheet_id == sheet_id)
+            .copied()
+            .collect::<Vec<_>>();
+        code_cells
+            .into_iter()
+            .for_each(|sheet_pos| self.remove(sheet_pos));
+        self.counts.remove(&sheet_id);
+        self.generations.remove(&sheet_id);
+    }
+
+    /// Replaces the index of the sheet's code cells.
+    pub fn index_sheet(&mut self, sheet: &Sheet) {
+        self.remove_sheet(sheet.id);
+        sheet.code_runs.iter().for_each(|(pos, code_run)| {
+            self.set(pos.to_sheet_pos(sheet.id), Some(&code_run.cells_accessed));
+        });
+        self.mark_indexed(sheet);
+    }
+
+    /// Code cells that may read cells in sheet_rect.
+    pub fn candidates(&self, sheet_rect: &SheetRect) -> HashSet<SheetPos> {
+        let sheet_id = sheet_rect.sheet_id;
+        let blocks = (
+            sheet_rect.min.x.div_euclid(BLOCK_SIZE),
+            sheet_rect.min.y.div_euclid(BLOCK_SIZE),
+            sheet_rect.max.x.div_euclid(BLOCK_SIZE),
+            sheet_rect.max.y.div_euclid(BLOCK_SIZE),
+        );
+
+        if block_count(blocks) > MAX_LOOKUP_BLOCKS {
+            return self.readers.get(&sheet_id).cloned().unwrap_or_default();
+        }
+
+        let mut candidates = self.large.get(&sheet_id).cloned().unwrap_or_default();
+        let (x0, y0, x1, y1) = blocks;
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                if let Some(block) = self.blocks.get(&(sheet_id, x, y)) {
+                    candidates.extend(block.iter().copied());
+                }
+            }
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{a1::RefRangeBounds, Pos, Rect};
+
+    fn accessed(sheet_id: SheetId, ranges: &[RefRangeBounds]) -> CellsAccessed {
+        let mut cells_accessed = CellsAccessed::default();
+        for range in ranges {
+            cells_accessed.add(sheet_id, CellRefRange::Sheet { range: *range });
+        }
+        cells_accessed
+    }
+
+    #[test]
+    fn test_candidates() {
+        let sheet_id = SheetId::new();
+        let other_sheet_id = SheetId::new();
+        let mut index = DependencyIndex::default();
+
+        let a = Pos { x: 1, y: 1 }.to_sheet_pos(sheet_id);
+        let b = Pos { x: 2, y: 1 }.to_sheet_pos(sheet_id);
+        let c = Pos { x: 3, y: 1 }.to_sheet_pos(other_sheet_id);
+        index.set(
+            a,
+            Some(&accessed(sheet_id, &[RefRangeBounds::test_a1("A5:B6")])),
+        );
+        index.set(
+            b,
+            Some(&accessed(sheet_id, &[RefRangeBounds::test_a1("CZ1000")])),
+        );
+        index.set(
+            c,
+            Some(&accessed(sheet_id, &[RefRangeBounds::test_a1("C:C")])),
+        );
+        assert_eq!(index.counts.get(&sheet_id), Some(&2));
+        assert_eq!(index.counts.get(&other_sheet_id), Some(&1));
+
+        let lookup = |index: &DependencyIndex, rect: Rect| {
+            index.candidates(&SheetRect::new_pos_span(rect.min, rect.max, sheet_id))
+        };
+
+        // the column range is always a candidate
+        assert_eq!(lookup(&index, Rect::test_a1("A1")), HashSet::from([a, c]));
+        assert_eq!(
+            lookup(&index, Rect::test_a1("CZ1000")),
+            HashSet::from([b, c])
+        );
+
+        // large lookups return every code cell reading the sheet
+        assert_eq!(
+            lookup(&index, Rect::new(1, 1, 10000, 10000)),
+            HashSet::from([a, b, c])
+        );
+
+        // updating and removing code cells
+        index.set(
+            a,
+            Some(&accessed(sheet_id, &[RefRangeBounds::test_a1("CZ999")])),
+        );
+        assert_eq!(lookup(&index, Rect::test_a1("A1")), HashSet::from([c]));
+        index.set(c, None);
+        assert_eq!(lookup(&index, Rect::test_a1("A1")), HashSet::new());
+        assert_eq!(
+            lookup(&index, Rect::test_a1("CZ1000")),
+            HashSet::from([a, b])
+        );
+
+        index.remove_sheet(sheet_id);
+        assert_eq!(lookup(&index, Rect::test_a1("CZ1000")), HashSet::new());
+        assert!(index.blocks.is_empty());
+    }
+}
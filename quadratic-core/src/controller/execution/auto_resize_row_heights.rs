@@ -14,10 +14,7 @@ impl GridController {
         sheet_id: SheetId,
         rows: Vec<i64>,
     ) -> bool {
-        if (!cfg!(target_family = "wasm") && !cfg!(test))
-            || !transaction.is_user()
-            || rows.is_empty()
-        {
+        if !self.host.renders() || !transaction.is_user() || rows.is_empty() {
             return false;
         }
 
@@ -28,7 +25,7 @@ impl GridController {
             }
             auto_resize_rows.sort();
             if let Ok(rows_string) = serde_json::to_string(&auto_resize_rows) {
-                crate::wasm_bindings::js::jsRequestRowHeights(
+                self.host.request_row_heights(
                     transaction.id.to_string(),
                     sheet_id.to_string(),
                     rows_string,
@@ -406,6 +403,92 @@ mod tests {
         assert!(async_transaction.is_err());
     }
 
+    #[test]
+    #[serial]
+    fn test_auto_resize_row_heights_on_font_format() {
+        clear_js_calls();
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_values(
+            SheetPos {
+                x: 1,
+                y: 1,
+                sheet_id,
+            },
+            vec![vec!["1"], vec!["2"]],
+            None,
+        );
+
+        // should trigger auto resize row heights for font size without wrap
+        let ops = vec![Operation::SetCellFormatsA1 {
+            sheet_id,
+            formats: SheetFormatUpdates::from_selection(
+                &A1Selection::test_a1("A1"),
+                FormatUpdate {
+                    font_size: Some(Some(24)),
+                    ..FormatUpdate::default()
+                },
+            ),
+        }];
+        let row_heights = vec![JsRowHeight {
+            row: 1,
+            height: 36f64,
+        }];
+        mock_auto_resize_row_heights(&mut gc, sheet_id, ops, row_heights.clone());
+        let transaction_id = gc.last_transaction().unwrap().id;
+        expect_js_call(
+            "jsRequestRowHeights",
+            format!("{},{},{}", transaction_id, sheet_id, "[1]"),
+            false,
+        );
+        assert_eq!(gc.sheet(sheet_id).offsets.row_height(1), 36f64);
+        expect_js_request_row_heights(sheet_id, row_heights);
+
+        // should trigger auto resize row heights for text rotation
+        let ops = vec![Operation::SetCellFormatsA1 {
+            sheet_id,
+            formats: SheetFormatUpdates::from_selection(
+                &A1Selection::test_a1("A2"),
+                FormatUpdate {
+                    text_rotation: Some(Some(90)),
+                    ..FormatUpdate::default()
+                },
+            ),
+        }];
+        let row_heights = vec![JsRowHeight {
+            row: 2,
+            height: 60f64,
+        }];
+        mock_auto_resize_row_heights(&mut gc, sheet_id, ops, row_heights.clone());
+        let transaction_id = gc.last_transaction().unwrap().id;
+        expect_js_call(
+            "jsRequestRowHeights",
+            format!("{},{},{}", transaction_id, sheet_id, "[2]"),
+            false,
+        );
+        assert_eq!(gc.sheet(sheet_id).offsets.row_height(2), 60f64);
+        expect_js_request_row_heights(sheet_id, row_heights);
+
+        // should trigger auto resize row heights when a value is set in a
+        // cell with a font size
+        gc.set_cell_value(
+            SheetPos {
+                x: 1,
+                y: 1,
+                sheet_id,
+            },
+            "larger".to_string(),
+            None,
+        );
+        let transaction_id = gc.last_transaction().unwrap().id;
+        expect_js_call(
+            "jsRequestRowHeights",
+            format!("{},{},{}", transaction_id, sheet_id, "[1]"),
+            true,
+        );
+    }
+
     #[test]
     #[serial]
     fn test_auto_resize_row_heights_on_compute_code_formula() {
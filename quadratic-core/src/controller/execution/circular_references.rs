@@ -0,0 +1,450 @@
+//! Code cells that read each other, directly or through other code cells,
+//! form a cycle. Cycles are found in the dependency graph before the code
+//! cells are computed (and after a computation adds a new dependency), and
+//! every code cell in a cycle is set to a circular reference error that names
+//! the cycle instead of being computed.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use chrono::Utc;
+
+use crate::a1::quote_sheet_name;
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+use crate::grid::{CodeRun, CodeRunResult};
+use crate::{CellValue, Pos, RunError, RunErrorMsg, SheetPos};
+
+use super::compute_order::sort_sheet_positions;
+
+/// Returns the shortest path of cells from `start` back to `start`, following
+/// `successors` through the cells allowed by `allowed`. The path starts with
+/// `start` and does not repeat it. A cell reading its own output is not a
+/// cycle (self references are errors when the code cell runs).
+fn cycle_path(
+    start: SheetPos,
+    successors: impl Fn(SheetPos) -> Vec<SheetPos>,
+    allowed: impl Fn(&SheetPos) -> bool,
+) -> Option<Vec<SheetPos>> {
+    let mut parents = HashMap::<SheetPos, SheetPos>::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(sheet_pos) = queue.pop_front() {
+        for next in successors(sheet_pos) {
+            if next == start && sheet_pos != start {
+                let mut path = vec![sheet_pos];
+                while let Some(parent) = parents.get(path.last()?) {
+                    path.push(*parent);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if next != start && allowed(&next) && !parents.contains_key(&next) {
+                parents.insert(next, sheet_pos);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the path for each cell in a cycle, starting with the cell.
+fn cycle_rotations(path: &[SheetPos]) -> Vec<Vec<SheetPos>> {
+    (0..path.len())
+        .map(|start| {
+            path[start..]
+                .iter()
+                .chain(path[..start].iter())
+                .copied()
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the cycles in a dependents graph, as one path for each cell in a
+/// cycle that starts with the cell.
+pub(crate) fn cycle_paths(
+    graph: &HashMap<SheetPos, Vec<SheetPos>>,
+    cells: &HashSet<SheetPos>,
+) -> Vec<Vec<SheetPos>> {
+    let successors = |sheet_pos: SheetPos| {
+        graph
+            .get(&sheet_pos)
+            .into_iter()
+            .flatten()
+            .filter(|dependent| cells.contains(*dependent))
+            .copied()
+            .collect::<Vec<_>>()
+    };
+
+    // strongly connected components (Tarjan's algorithm, without recursion)
+    let mut index = HashMap::<SheetPos, usize>::new();
+    let mut low = HashMap::<SheetPos, usize>::new();
+    let mut stack = vec![];
+    let mut on_stack = HashSet::new();
+    let mut components = vec![];
+
+    let mut roots = cells.iter().copied().collect::<Vec<_>>();
+    sort_sheet_positions(&mut roots);
+    for root in roots {
+        if index.contains_key(&root) {
+            continue;
+        }
+
+        // (cell, index of the next successor to visit)
+        let mut calls = vec![(root, 0)];
+        while let Some((sheet_pos, next)) = calls.pop() {
+            let next_cells = successors(sheet_pos);
+            if next == 0 {
+                index.insert(sheet_pos, index.len());
+                low.insert(sheet_pos, index[&sheet_pos]);
+                stack.push(sheet_pos);
+                on_stack.insert(sheet_pos);
+            } else {
+                let visited = next_cells[next - 1];
+                if on_stack.contains(&visited) {
+                    low.insert(sheet_pos, low[&sheet_pos].min(low[&visited]));
+                }
+            }
+
+            if let Some(visit) = next_cells.get(next) {
+                calls.push((sheet_pos, next + 1));
+                if !index.contains_key(visit) {
+                    calls.push((*visit, 0));
+                }
+                continue;
+            }
+
+            if low[&sheet_pos] == index[&sheet_pos] {
+                let mut component = HashSet::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(&member);
+                    component.insert(member);
+                    if member == sheet_pos {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    let mut paths = vec![];
+    for component in components {
+        let mut members = component.iter().copied().collect::<Vec<_>>();
+        sort_sheet_positions(&mut members);
+        for member in members {
+            if let Some(path) = cycle_path(member, successors, |sheet_pos| {
+                component.contains(sheet_pos)
+            }) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+impl GridController {
+    /// Returns a path of code cells from `sheet_pos` back to itself, if its
+    /// output is read by a code cell that it reads.
+    pub(crate) fn find_code_cell_cycle(&self, sheet_pos: SheetPos) -> Option<Vec<SheetPos>> {
+        cycle_path(
+            sheet_pos,
+            |sheet_pos| {
+                let output = self.code_output_sheet_rect(sheet_pos);
+                let mut dependents = self
+                    .get_dependent_code_cells(&output)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                sort_sheet_positions(&mut dependents);
+                dependents
+            },
+            |_| true,
+        )
+    }
+
+    /// Sets the code cells in a cycle through `sheet_pos` to circular
+    /// reference errors. Returns whether a cycle was found.
+    pub(crate) fn check_code_cell_cycle(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        sheet_pos: SheetPos,
+    ) -> bool {
+        if transaction.circular_code.contains(&sheet_pos) {
+            return false;
+        }
+        match self.find_code_cell_cycle(sheet_pos) {
+            Some(path) => {
+                self.set_circular_references(transaction, cycle_rotations(&path));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Describes a cycle, eg, "A1 -> B1 -> A1". Cells in other sheets than
+    /// the first cell include the sheet name.
+    fn cycle_description(&self, path: &[SheetPos]) -> String {
+        let Some(first) = path.first() else {
+            return String::new();
+        };
+        path.iter()
+            .chain(std::iter::once(first))
+            .map(|sheet_pos| {
+                let a1 = Pos::from(*sheet_pos).a1_string();
+                match self.try_sheet(sheet_pos.sheet_id) {
+                    Some(sheet) if sheet_pos.sheet_id != first.sheet_id => {
+                        format!("{}!{a1}", quote_sheet_name(&sheet.name))
+                    }
+                    _ => a1,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+
+    /// Sets the first code cell of each path to a circular reference error
+    /// naming the path. The code cells are not computed again in the
+    /// transaction.
+    pub(crate) fn set_circular_references(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        paths: Vec<Vec<SheetPos>>,
+    ) {
+        let cells = paths
+            .iter()
+            .filter_map(|path| path.first().copied())
+            .collect::<HashSet<_>>();
+        if cells.is_empty() {
+            return;
+        }
+
+        // mark the cells before finalizing any of them, so finalizing one
+        // doesn't find the cycle again
+        transaction.circular_code.extend(cells.iter().copied());
+        transaction.computed_code.extend(cells.iter().copied());
+        transaction
+            .pending_compute_code
+            .retain(|sheet_pos| !cells.contains(sheet_pos));
+        transaction.operations.retain(
+            |op| !matches!(op, Operation::ComputeCode { sheet_pos } if cells.contains(sheet_pos)),
+        );
+
+        for path in paths {
+            let Some(sheet_pos) = path.first().copied() else {
+                continue;
+            };
+            let Some(sheet) = self.try_sheet(sheet_pos.sheet_id) else {
+                continue;
+            };
+            if !matches!(sheet.cell_value(sheet_pos.into()), Some(CellValue::Code(_))) {
+                continue;
+            }
+
+            let std_err = format!(
+                "{}: {}",
+                RunErrorMsg::CircularReference,
+                self.cycle_description(&path)
+            );
+            let new_code_run = match sheet.code_run(sheet_pos.into()) {
+                Some(old_code_run) => {
+                    // keep the location of a circular reference found by a
+                    // formula
+                    let result = match &old_code_run.result {
+                        CodeRunResult::Err(error)
+                            if error.msg == RunErrorMsg::CircularReference =>
+                        {
+                            old_code_run.result.clone()
+                        }
+                        _ => CodeRunResult::Err(RunError {
+                            span: None,
+                            msg: RunErrorMsg::CircularReference,
+                        }),
+                    };
+                    CodeRun {
+                        formatted_code_string: old_code_run.formatted_code_string.clone(),
+                        result,
+                        return_type: None,
+                        line_number: old_code_run.line_number,
+                        output_type: old_code_run.output_type.clone(),
+                        std_out: None,
+                        std_err: Some(std_err),
+                        spill_error: false,
+                        last_modified: Utc::now(),
+                        refresh: old_code_run.refresh,
+                        volatile: old_code_run.volatile,
+
+                        // keep the cells_accessed so the cycle is found again
+                        // until it's broken
+                        cells_accessed: old_code_run.cells_accessed.clone(),
+                    }
+                }
+                // a code cell without a code run doesn't read any cells, so it
+                // can't be in a cycle
+                None => continue,
+            };
+            self.finalize_code_run(transaction, sheet_pos, Some(new_code_run), None);
+        }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::controller::transaction_types::JsCodeResult;
+    use crate::grid::CodeCellLanguage;
+
+    fn assert_circular_reference(gc: &GridController, sheet_pos: SheetPos, description: &str) {
+        let code_run = gc
+            .sheet(sheet_pos.sheet_id)
+            .code_run(sheet_pos.into())
+            .unwrap();
+        match &code_run.result {
+            CodeRunResult::Err(error) => assert_eq!(error.msg, RunErrorMsg::CircularReference),
+            result => panic!("expected a circular reference, got {result:?}"),
+        }
+        assert_eq!(
+            code_run.std_err,
+            Some(format!("Circular reference: {description}"))
+        );
+    }
+
+    #[test]
+    fn finds_cycle_paths() {
+        let sheet_id = crate::grid::SheetId::new();
+        let [a, b, c, d] = [1, 2, 3, 4].map(|x| Pos { x, y: 1 }.to_sheet_pos(sheet_id));
+
+        // a -> b -> c -> a, c -> d, d -> d (a self reference isn't a cycle)
+        let graph = HashMap::from([(a, vec![b]), (b, vec![c]), (c, vec![a, d]), (d, vec![d])]);
+        let cells = HashSet::from([a, b, c, d]);
+        let paths = cycle_paths(&graph, &cells);
+        assert_eq!(
+            paths.into_iter().collect::<HashSet<_>>(),
+            HashSet::from([vec![a, b, c], vec![b, c, a], vec![c, a, b]])
+        );
+
+        let graph = HashMap::from([(a, vec![b]), (b, vec![c])]);
+        assert!(cycle_paths(&graph, &cells).is_empty());
+    }
+
+    #[test]
+    fn marks_formula_cycles() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let sheet_pos = |pos: Pos| pos.to_sheet_pos(sheet_id);
+
+        gc.set_cell_value(sheet_pos(pos![A1]), "1".into(), None);
+        gc.set_code_cell(
+            sheet_pos(pos![B1]),
+            CodeCellLanguage::Formula,
+            "A1 + C1".into(),
+            None,
+        );
+        gc.set_code_cell(
+            sheet_pos(pos![D1]),
+            CodeCellLanguage::Formula,
+            "C1 * 2".into(),
+            None,
+        );
+
+        // C1 closes the cycle B1 -> C1 -> B1
+        gc.set_code_cell(
+            sheet_pos(pos![C1]),
+            CodeCellLanguage::Formula,
+            "B1 + 1".into(),
+            None,
+        );
+        assert_circular_reference(&gc, sheet_pos(pos![B1]), "B1 -> C1 -> B1");
+        assert_circular_reference(&gc, sheet_pos(pos![C1]), "C1 -> B1 -> C1");
+
+        // D1 reads the cycle, but isn't part of it
+        let sheet = gc.sheet(sheet_id);
+        assert!(!sheet
+            .code_run(pos![D1])
+            .unwrap()
+            .std_err
+            .as_ref()
+            .is_some_and(|std_err| std_err.contains("->")));
+
+        // changing a cell the cycle reads finds the cycle before computing
+        gc.set_cell_value(sheet_pos(pos![A1]), "2".into(), None);
+        assert_circular_reference(&gc, sheet_pos(pos![B1]), "B1 -> C1 -> B1");
+        assert_circular_reference(&gc, sheet_pos(pos![C1]), "C1 -> B1 -> C1");
+
+        // breaking the cycle computes the cells again
+        gc.set_code_cell(
+            sheet_pos(pos![C1]),
+            CodeCellLanguage::Formula,
+            "A1 + 1".into(),
+            None,
+        );
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(pos![C1]),
+            Some(CellValue::Number(3.into()))
+        );
+        assert_eq!(
+            sheet.display_value(pos![B1]),
+            Some(CellValue::Number(5.into()))
+        );
+        assert_eq!(
+            sheet.display_value(pos![D1]),
+            Some(CellValue::Number(6.into()))
+        );
+    }
+
+    #[test]
+    fn marks_cycles_across_languages_and_sheets() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.add_sheet(None);
+        let sheet_2_id = gc.sheet_ids()[1];
+        let sheet_name = gc.sheet(sheet_2_id).name.clone();
+
+        // Python at A1 reads the formula at A1 in the second sheet
+        gc.set_code_cell(
+            pos![A1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Python,
+            format!("q.cells(\"'{sheet_name}'!A1\")"),
+            None,
+        );
+        let transaction_id = gc.async_transactions()[0].id.to_string();
+        gc.calculation_get_cells_a1(transaction_id.clone(), format!("'{sheet_name}'!A1"), None)
+            .unwrap();
+        gc.calculation_complete(JsCodeResult::new(
+            transaction_id,
+            true,
+            None,
+            None,
+            Some(vec!["1".into(), "number".into()]),
+            None,
+            None,
+            None,
+            None,
+        ))
+        .unwrap();
+
+        // the formula reads the Python cell, closing the cycle without
+        // running the Python cell again
+        gc.set_code_cell(
+            pos![A1].to_sheet_pos(sheet_2_id),
+            CodeCellLanguage::Formula,
+            "'Sheet 1'!A1 + 1".into(),
+            None,
+        );
+        assert!(gc.async_transactions().is_empty());
+        assert_circular_reference(
+            &gc,
+            pos![A1].to_sheet_pos(sheet_2_id),
+            "A1 -> 'Sheet 1'!A1 -> A1",
+        );
+        assert_circular_reference(
+            &gc,
+            pos![A1].to_sheet_pos(sheet_id),
+            &format!("A1 -> '{sheet_name}'!A1 -> A1"),
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Code cells that depend on changed cells are computed in dependency order,
+//! so each code cell runs after the code cells it reads and only once per
+//! transaction.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::execution::circular_references::cycle_paths;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+use crate::{SheetPos, SheetRect};
+
+/// Sorts positions by sheet, row and column so results are deterministic.
+pub(crate) fn sort_sheet_positions(cells: &mut [SheetPos]) {
+    cells.sort_by_key(|sheet_pos| (sheet_pos.sheet_id.to_string(), sheet_pos.y, sheet_pos.x));
+}
+
+/// Orders the cells of a dependents graph so that each comes after the cells
+/// it reads. Cells in a cycle are ordered last.
+pub(crate) fn topological_order(
+    graph: &HashMap<SheetPos, Vec<SheetPos>>,
+    cells: &HashSet<SheetPos>,
+) -> Vec<SheetPos> {
+    let mut dependencies = cells
+        .iter()
+        .map(|sheet_pos| (*sheet_pos, 0))
+        .collect::<HashMap<_, usize>>();
+    let dependents = |sheet_pos: SheetPos| {
+        graph
+            .get(&sheet_pos)
+            .into_iter()
+            .flatten()
+            .filter(move |dependent| **dependent != sheet_pos && cells.contains(*dependent))
+    };
+
+    for sheet_pos in cells.iter() {
+        for dependent in dependents(*sheet_pos) {
+            *dependencies.entry(*dependent).or_default() += 1;
+        }
+    }
+
+    let mut ready = dependencies
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(sheet_pos, _)| *sheet_pos)
+        .collect::<Vec<_>>();
+    sort_sheet_positions(&mut ready);
+    let mut ready = ready.into_iter().collect::<VecDeque<_>>();
+
+    let mut order = vec![];
+    while let Some(sheet_pos) = ready.pop_front() {
+        order.push(sheet_pos);
+        for dependent in dependents(sheet_pos) {
+            if let Some(count) = dependencies.get_mut(dependent) {
+                *count -= 1;
+                if *count == 0 {
+                    ready.push_back(*dependent);
+                }
+            }
+        }
+    }
+
+    let mut cycles = dependencies
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(sheet_pos, _)| sheet_pos)
+        .collect::<Vec<_>>();
+    sort_sheet_positions(&mut cycles);
+    order.extend(cycles);
+    order
+}
+
+impl GridController {
+    /// Returns the output of a code cell, or just the cell if it has not run.
+    pub(crate) fn code_output_sheet_rect(&self, sheet_pos: SheetPos) -> SheetRect {
+        self.try_sheet(sheet_pos.sheet_id)
+            .and_then(|sheet| sheet.code_run(sheet_pos.into()))
+            .map_or(sheet_pos.into(), |code_run| {
+                code_run.output_sheet_rect(sheet_pos, false)
+            })
+    }
+
+    /// Returns the code cells that depend on `rect`, followed through the
+    /// outputs of those code cells. Traversal stops at code cells that are
+    /// skipped.
+    pub(crate) fn dependent_code_cells_closure(
+        &self,
+        rect: &SheetRect,
+        skip: impl Fn(&SheetPos) -> bool,
+    ) -> HashSet<SheetPos> {
+        let mut cells = HashSet::new();
+        let mut queue = VecDeque::from([*rect]);
+
+        while let Some(rect) = queue.pop_front() {
+            for sheet_pos in self.get_dependent_code_cells(&rect).unwrap_or_default() {
+                if !skip(&sheet_pos) && cells.insert(sheet_pos) {
+                    queue.push_back(self.code_output_sheet_rect(sheet_pos));
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Returns each code cell and the code cells in `cells` that read its
+    /// output (including itself if it reads its own output).
+    pub(crate) fn dependents_graph(
+        &self,
+        cells: &HashSet<SheetPos>,
+    ) -> HashMap<SheetPos, Vec<SheetPos>> {
+        cells
+            .iter()
+            .map(|sheet_pos| {
+                let output = self.code_output_sheet_rect(*sheet_pos);
+                let mut dependents = self
+                    .get_dependent_code_cells(&output)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|dependent| cells.contains(dependent))
+                    .collect::<Vec<_>>();
+                sort_sheet_positions(&mut dependents);
+                (*sheet_pos, dependents)
+            })
+            .collect()
+    }
+
+    /// Returns the dependents graph of pending code cells, without the
+    /// out-of-date reads of code cells with new code.
+    pub(crate) fn pending_dependents_graph(
+        &self,
+        transaction: &PendingTransaction,
+        cells: &HashSet<SheetPos>,
+    ) -> HashMap<SheetPos, Vec<SheetPos>> {
+        let mut graph = self.dependents_graph(cells);
+        graph.values_mut().for_each(|dependents| {
+            dependents.retain(|dependent| !transaction.changed_code.contains(dependent));
+        });
+        graph
+    }
+
+    /// Orders code cells so that each runs after the code cells it reads.
+    /// Code cells in a cycle are run last.
+    pub(crate) fn compute_order(&self, cells: &HashSet<SheetPos>) -> Vec<SheetPos> {
+        topological_order(&self.dependents_graph(cells), cells)
+    }
+
+    /// Tracks the transaction's ComputeCode operations and orders them so that
+    /// each code cell runs after the code cells it reads. Other operations keep
+    /// their places.
+    pub(crate) fn order_compute_operations(&mut self, transaction: &mut PendingTransaction) {
+        let mut cells = transaction
+            .operations
+            .iter()
+            .filter_map(|op| match op {
+                Operation::ComputeCode { sheet_pos } => Some(*sheet_pos),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+
+        // already tracked (eg, when resuming after an async computation)
+        if cells.is_subset(&transaction.pending_compute_code) {
+            return;
+        }
+        transaction
+            .pending_compute_code
+            .extend(cells.iter().copied());
+
+        // code cells with new code are ordered by the cells they read after
+        // they run
+        transaction.changed_code.extend(
+            transaction
+                .operations
+                .iter()
+                .filter_map(|op| match op {
+                    Operation::SetCellValues { sheet_pos, .. } => Some(*sheet_pos),
+                    _ => None,
+                })
+                .filter(|sheet_pos| cells.contains(sheet_pos)),
+        );
+
+        // code cells in a cycle are set to errors instead of computed
+        let graph = self.pending_dependents_graph(transaction, &cells);
+        let cycles = cycle_paths(&graph, &cells);
+        for path in cycles.iter() {
+            cells.remove(&path[0]);
+        }
+        transaction.operations.retain(|op| match op {
+            Operation::ComputeCode { sheet_pos } => cells.contains(sheet_pos),
+            _ => true,
+        });
+
+        let mut order = topological_order(&graph, &cells).into_iter();
+        for op in transaction.operations.iter_mut() {
+            if let Operation::ComputeCode { sheet_pos } = op {
+                match order.next() {
+                    Some(next) => *sheet_pos = next,
+                    None => break,
+                }
+            }
+        }
+
+        self.set_circular_references(transaction, cycles);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::controller::transaction_types::JsCodeResult;
+    use crate::grid::CodeCellLanguage;
+    use crate::{CellValue, Pos};
+
+    /// Number of times the code cell's result changed in the last transaction.
+    fn code_run_updates(gc: &GridController, sheet_pos: SheetPos) -> usize {
+        gc.last_transaction()
+            .unwrap()
+            .operations
+            .iter()
+            .filter(|op| {
+                matches!(op, Operation::SetCodeRunVersion { sheet_pos: pos, .. } if *pos == sheet_pos)
+            })
+            .count()
+    }
+
+    #[test]
+    fn computes_dependents_once_in_order() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let sheet_pos = |pos: Pos| pos.to_sheet_pos(sheet_id);
+
+        // D1 reads A1 directly and through B1 -> C1
+        gc.set_cell_value(sheet_pos(pos![A1]), "1".into(), None);
+        for (pos, code) in [
+            (pos![B1], "A1 + 1"),
+            (pos![C1], "B1 + 1"),
+            (pos![D1], "A1 + C1"),
+        ] {
+            gc.set_code_cell(sheet_pos(pos), CodeCellLanguage::Formula, code.into(), None);
+        }
+
+        gc.set_cell_value(sheet_pos(pos![A1]), "10".into(), None);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(pos![D1]),
+            Some(CellValue::Number(22.into()))
+        );
+        assert_eq!(code_run_updates(&gc, sheet_pos(pos![B1])), 1);
+        assert_eq!(code_run_updates(&gc, sheet_pos(pos![C1])), 1);
+        assert_eq!(code_run_updates(&gc, sheet_pos(pos![D1])), 1);
+    }
+
+    #[test]
+    fn compute_order_puts_cycles_last() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let sheet_pos = |pos: Pos| pos.to_sheet_pos(sheet_id);
+
+        gc.set_cell_value(sheet_pos(pos![A1]), "1".into(), None);
+        for (pos, code) in [
+            (pos![C1], "B1 + 1"),
+            (pos![B1], "A1 + 1"),
+            (pos![A2], "B2 + 1"),
+            (pos![B2], "A2 + 1"),
+        ] {
+            gc.set_code_cell(sheet_pos(pos), CodeCellLanguage::Formula, code.into(), None);
+        }
+
+        let cells = [pos![C1], pos![B1], pos![A2], pos![B2]]
+            .into_iter()
+            .map(sheet_pos)
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            gc.compute_order(&cells),
+            vec![
+                sheet_pos(pos![B1]),
+                sheet_pos(pos![C1]),
+                sheet_pos(pos![A2]),
+                sheet_pos(pos![B2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn pauses_ordered_computation_for_async_code() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let sheet_pos = |pos: Pos| pos.to_sheet_pos(sheet_id);
+        let complete = |gc: &mut GridController, value: &str| {
+            let transaction_id = gc.async_transactions()[0].id.to_string();
+            gc.calculation_get_cells_a1(transaction_id.clone(), "A1".into(), None)
+                .unwrap();
+            gc.calculation_complete(JsCodeResult::new(
+                transaction_id,
+                true,
+                None,
+                None,
+                Some(vec![value.into(), "number".into()]),
+                None,
+                None,
+                None,
+                None,
+            ))
+            .unwrap();
+        };
+
+        // C1 reads A1 directly and through the Python cell at B1
+        gc.set_cell_value(sheet_pos(pos![A1]), "1".into(), None);
+        gc.set_code_cell(
+            sheet_pos(pos![B1]),
+            CodeCellLanguage::Python,
+            "q.cells('A1') + 1".into(),
+            None,
+        );
+        complete(&mut gc, "2");
+        gc.set_code_cell(
+            sheet_pos(pos![C1]),
+            CodeCellLanguage::Formula,
+            "A1 + B1".into(),
+            None,
+        );
+        assert_eq!(
+            gc.sheet(sheet_id).display_value(pos![C1]),
+            Some(CellValue::Number(3.into()))
+        );
+
+        // C1 waits for B1
+        gc.set_cell_value(sheet_pos(pos![A1]), "10".into(), None);
+        assert_eq!(gc.async_transactions().len(), 1);
+        assert_eq!(
+            gc.sheet(sheet_id).display_value(pos![C1]),
+            Some(CellValue::Number(3.into()))
+        );
+
+        complete(&mut gc, "11");
+        assert!(gc.async_transactions().is_empty());
+        assert_eq!(
+            gc.sheet(sheet_id).display_value(pos![C1]),
+            Some(CellValue::Number(21.into()))
+        );
+        assert_eq!(code_run_updates(&gc, sheet_pos(pos![C1])), 1);
+    }
+}
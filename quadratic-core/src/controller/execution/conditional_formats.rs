@@ -0,0 +1,404 @@
+//! Keeps conditional formats up to date as cells change.
+
+use std::collections::{HashMap, HashSet};
+
+use uuid::Uuid;
+
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::GridController;
+use crate::formulas::{parse_formula, Ctx};
+use crate::grid::sheet::conditional_formats::conditional_format::ConditionalFormat;
+use crate::grid::sheet::conditional_formats::conditional_format_rule::ConditionalFormatRule;
+use crate::grid::sheet::conditional_formats::ConditionalFormats;
+use crate::grid::{CellsAccessed, Sheet, SheetId};
+use crate::renderer_constants::{CELL_SHEET_HEIGHT, CELL_SHEET_WIDTH};
+use crate::{A1Selection, Pos, Rect};
+
+/// Returns whether any of the cells are in a dirty hash.
+fn cells_accessed_dirty(
+    cells_accessed: &CellsAccessed,
+    dirty_hashes: &HashMap<SheetId, HashSet<Pos>>,
+) -> bool {
+    dirty_hashes.iter().any(|(sheet_id, hashes)| {
+        hashes.iter().any(|hash| {
+            let (width, height) = (CELL_SHEET_WIDTH as i64, CELL_SHEET_HEIGHT as i64);
+            let rect = Rect::new(
+                hash.x * width,
+                hash.y * height,
+                (hash.x + 1) * width - 1,
+                (hash.y + 1) * height - 1,
+            );
+            cells_accessed.intersects(&rect.to_sheet_rect(*sheet_id))
+        })
+    })
+}
+
+impl GridController {
+    /// Returns the cells in the selection where the formula evaluates to
+    /// true, and the cells that the formula referenced.
+    fn conditional_format_formula_matches(
+        &self,
+        sheet: &Sheet,
+        selection: &A1Selection,
+        formula: &str,
+    ) -> (HashSet<Pos>, CellsAccessed) {
+        let mut matches = HashSet::new();
+        let mut cells_accessed = CellsAccessed::default();
+        let rects = sheet.selection_to_rects(selection);
+        let Some(anchor) = rects.first().map(|rect| rect.min) else {
+            return (matches, cells_accessed);
+        };
+        let Ok(parsed) = parse_formula(formula.trim_start_matches('='), anchor) else {
+            return (matches, cells_accessed);
+        };
+        let bounds = sheet.bounds(true);
+        for pos in rects.iter().flat_map(|rect| rect.iter()) {
+            let mut ctx = Ctx::new(self.grid(), pos.to_sheet_pos(sheet.id));
+            let value = parsed.eval(&mut ctx, Some(bounds)).into_non_tuple();
+            if value
+                .into_cell_value()
+                .is_ok_and(|value| value.inner.coerce_nonblank::<bool>() == Some(true))
+            {
+                matches.insert(pos);
+            }
+            cells_accessed.extend(ctx.cells_accessed);
+        }
+        (matches, cells_accessed)
+    }
+
+    /// Recalculates the cached results of Formula rules and the values of
+    /// range rules for the conditional formats where `is_stale` returns true.
+    /// Returns the cells whose Formula results changed, and whether the
+    /// conditional format changes their fill.
+    pub(crate) fn calculate_conditional_formats(
+        &mut self,
+        is_stale: impl Fn(&Sheet, &ConditionalFormat) -> bool,
+    ) -> Vec<(SheetId, HashSet<Pos>, bool)> {
+        let mut formula_results = vec![];
+        let mut range_results = vec![];
+        for sheet in self.grid.sheets() {
+            for cf in sheet.conditional_formats.conditional_formats.iter() {
+                if !is_stale(sheet, cf) {
+                    continue;
+                }
+                if let ConditionalFormatRule::Formula(formula) = &cf.rule {
+                    let (matches, cells_accessed) =
+                        self.conditional_format_formula_matches(sheet, &cf.selection, formula);
+                    formula_results.push((sheet.id, cf.id, cf.has_fill(), matches, cells_accessed));
+                } else if cf.rule.is_range_rule() {
+                    let range = ConditionalFormats::calculate_range_values(sheet, cf);
+                    range_results.push((sheet.id, cf.id, range));
+                }
+            }
+        }
+
+        for (sheet_id, id, range) in range_results {
+            if let Some(sheet) = self.try_sheet_mut(sheet_id) {
+                sheet.conditional_formats.range_values.insert(id, range);
+            }
+        }
+
+        let mut changed = vec![];
+        for (sheet_id, id, has_fill, matches, cells_accessed) in formula_results {
+            if let Some(sheet) = self.try_sheet_mut(sheet_id) {
+                sheet
+                    .conditional_formats
+                    .formula_cells_accessed
+                    .insert(id, cells_accessed);
+                let old = sheet
+                    .conditional_formats
+                    .formula_matches
+                    .insert(id, matches.clone())
+                    .unwrap_or_default();
+                let positions = old
+                    .symmetric_difference(&matches)
+                    .copied()
+                    .collect::<HashSet<_>>();
+                if !positions.is_empty() {
+                    changed.push((sheet_id, positions, has_fill));
+                }
+            }
+        }
+        changed
+    }
+
+    /// Recalculates the conditional formats whose results may have changed
+    /// during the transaction and re-renders the cells whose Formula results
+    /// changed. This is called once the transaction's operations are
+    /// complete.
+    pub(crate) fn update_conditional_format_formulas(
+        &mut self,
+        transaction: &mut PendingTransaction,
+    ) {
+        if transaction.is_server() || transaction.forward_operations.is_empty() {
+            return;
+        }
+
+        // Formula rules are also recalculated if they have no cached results
+        // or if they referenced a sheet that no longer exists
+        let mut stale = std::mem::take(&mut transaction.conditional_formats_stale)
+            .into_iter()
+            .flat_map(|(sheet_id, ids)| ids.into_iter().map(move |id| (sheet_id, id)))
+            .collect::<HashSet<(SheetId, Uuid)>>();
+        for sheet in self.grid.sheets() {
+            let conditional_formats = &sheet.conditional_formats;
+            for cf in conditional_formats.conditional_formats.iter() {
+                let uncached = match &cf.rule {
+                    ConditionalFormatRule::Formula(_) => conditional_formats
+                        .formula_cells_accessed
+                        .get(&cf.id)
+                        .is_none_or(|cells_accessed| {
+                            cells_accessed
+                                .cells
+                                .keys()
+                                .any(|sheet_id| self.try_sheet(*sheet_id).is_none())
+                        }),
+                    rule => {
+                        rule.is_range_rule()
+                            && !conditional_formats.range_values.contains_key(&cf.id)
+                    }
+                };
+                if uncached {
+                    stale.insert((sheet.id, cf.id));
+                }
+            }
+        }
+        if stale.is_empty() {
+            return;
+        }
+
+        let changed =
+            self.calculate_conditional_formats(|sheet, cf| stale.contains(&(sheet.id, cf.id)));
+        for (sheet_id, positions, has_fill) in changed {
+            transaction.add_dirty_hashes_from_sheet_cell_positions(sheet_id, positions);
+            if has_fill {
+                transaction.fill_cells.insert(sheet_id);
+            }
+        }
+    }
+
+    /// Re-renders conditional formats whose results may have changed after
+    /// an operation. Rules that depend on the other values in their selection
+    /// are re-rendered when any cell in the selection is dirty. Rules whose
+    /// selection (or, for Formula rules, referenced cells) is dirty are
+    /// marked to be recalculated when the transaction completes.
+    pub(crate) fn update_conditional_formats(&mut self, transaction: &mut PendingTransaction) {
+        if transaction.is_server() || transaction.dirty_hashes.is_empty() {
+            return;
+        }
+
+        let mut stale = vec![];
+        for sheet in self.grid.sheets() {
+            let dirty_hashes = transaction.dirty_hashes.get(&sheet.id);
+            let mut selections = vec![];
+            let mut fills = false;
+            for cf in sheet.conditional_formats.conditional_formats.iter() {
+                let is_formula = matches!(cf.rule, ConditionalFormatRule::Formula(_));
+                if !is_formula && !cf.rule.is_range_rule() && !cf.has_fill() {
+                    continue;
+                }
+                let selection_dirty = dirty_hashes.is_some_and(|dirty_hashes| {
+                    !cf.selection
+                        .rects_to_hashes(sheet)
+                        .is_disjoint(dirty_hashes)
+                });
+                if is_formula
+                    && (selection_dirty
+                        || sheet
+                            .conditional_formats
+                            .formula_cells_accessed
+                            .get(&cf.id)
+                            .is_some_and(|cells_accessed| {
+                                cells_accessed_dirty(cells_accessed, &transaction.dirty_hashes)
+                            }))
+                {
+                    stale.push((sheet.id, cf.id));
+                }
+                if !selection_dirty {
+                    continue;
+                }
+                fills |= cf.has_fill();
+                if cf.rule.is_range_rule() {
+                    selections.push(cf.selection.clone());
+                    stale.push((sheet.id, cf.id));
+                }
+            }
+            transaction.add_dirty_hashes_from_selections(sheet, selections);
+            if fills {
+                transaction.fill_cells.insert(sheet.id);
+            }
+        }
+
+        // cached range values are out of date until they are recalculated,
+        // so renders during the transaction read the values directly
+        for (sheet_id, id) in stale {
+            if let Some(sheet) = self.try_sheet_mut(sheet_id) {
+                sheet.conditional_formats.range_values.remove(&id);
+            }
+            transaction
+                .conditional_formats_stale
+                .entry(sheet_id)
+                .or_default()
+                .insert(id);
+        }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::controller::active_transactions::transaction_name::TransactionName;
+    use crate::controller::operations::operation::Operation;
+    use crate::grid::formats::Format;
+    use crate::grid::sheet::conditional_formats::conditional_format::ConditionalFormat;
+
+    #[test]
+    fn formula_conditional_format() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".into(), None);
+        gc.set_cell_value(pos![A2].to_sheet_pos(sheet_id), "10".into(), None);
+
+        // relative references are relative to the top-left cell of the selection
+        let conditional_format = ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1_sheet_id("B1:B2", &sheet_id),
+            rule: ConditionalFormatRule::Formula("=A1>5".into()),
+            format: Format {
+                bold: Some(true),
+                ..Default::default()
+            },
+        };
+        gc.start_user_transaction(
+            vec![Operation::SetConditionalFormat {
+                conditional_format: conditional_format.clone(),
+            }],
+            None,
+            TransactionName::ConditionalFormat,
+        );
+        let matches = |gc: &GridController| {
+            gc.sheet(sheet_id).conditional_formats.formula_matches[&conditional_format.id].clone()
+        };
+        assert_eq!(matches(&gc), HashSet::from([pos![B2]]));
+
+        // changing a dependent cell recalculates the formula
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "6".into(), None);
+        assert_eq!(matches(&gc), HashSet::from([pos![B1], pos![B2]]));
+    }
+
+    #[test]
+    fn range_conditional_format_dirty() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.sheet_mut(sheet_id)
+            .conditional_formats
+            .set(ConditionalFormat {
+                id: Uuid::new_v4(),
+                selection: A1Selection::test_a1_sheet_id("A1:A100", &sheet_id),
+                rule: ConditionalFormatRule::AboveAverage { below: false },
+                format: Format::default(),
+            });
+
+        // a change in the selection re-renders the entire selection
+        let mut transaction = PendingTransaction::default();
+        transaction.add_dirty_hashes_from_sheet_cell_positions(sheet_id, HashSet::from([pos![A1]]));
+        gc.update_conditional_formats(&mut transaction);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            transaction.dirty_hashes[&sheet_id],
+            A1Selection::test_a1("A1:A100").rects_to_hashes(sheet)
+        );
+
+        // a change outside the selection does not
+        let mut transaction = PendingTransaction::default();
+        transaction.add_dirty_hashes_from_sheet_cell_positions(sheet_id, HashSet::from([pos![Z1]]));
+        gc.update_conditional_formats(&mut transaction);
+        assert_eq!(transaction.dirty_hashes[&sheet_id].len(), 1);
+    }
+
+    #[test]
+    fn formula_conditional_format_stale() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let conditional_format = ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1_sheet_id("B1:B2", &sheet_id),
+            rule: ConditionalFormatRule::Formula("=A1>5".into()),
+            format: Format::default(),
+        };
+        gc.start_user_transaction(
+            vec![Operation::SetConditionalFormat {
+                conditional_format: conditional_format.clone(),
+            }],
+            None,
+            TransactionName::ConditionalFormat,
+        );
+        assert!(gc
+            .sheet(sheet_id)
+            .conditional_formats
+            .formula_cells_accessed
+            .contains_key(&conditional_format.id));
+
+        // a change to a referenced cell marks the rule as stale
+        let mut transaction = PendingTransaction::default();
+        transaction.add_dirty_hashes_from_sheet_cell_positions(sheet_id, HashSet::from([pos![A2]]));
+        gc.update_conditional_formats(&mut transaction);
+        assert_eq!(
+            transaction.conditional_formats_stale[&sheet_id],
+            HashSet::from([conditional_format.id])
+        );
+
+        // a change elsewhere does not
+        let mut transaction = PendingTransaction::default();
+        transaction
+            .add_dirty_hashes_from_sheet_cell_positions(sheet_id, HashSet::from([pos![Z1000]]));
+        gc.update_conditional_formats(&mut transaction);
+        assert!(transaction.conditional_formats_stale.is_empty());
+    }
+
+    #[test]
+    fn range_conditional_format_values() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let conditional_format = ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1_sheet_id("A1:A3", &sheet_id),
+            rule: ConditionalFormatRule::AboveAverage { below: false },
+            format: Format {
+                bold: Some(true),
+                ..Default::default()
+            },
+        };
+        gc.start_user_transaction(
+            vec![Operation::SetConditionalFormat {
+                conditional_format: conditional_format.clone(),
+            }],
+            None,
+            TransactionName::ConditionalFormat,
+        );
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".into(), None);
+        gc.set_cell_value(pos![A2].to_sheet_pos(sheet_id), "2".into(), None);
+
+        // the values are cached once the transaction completes
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.conditional_formats.range_values[&conditional_format.id],
+            ConditionalFormats::calculate_range_values(sheet, &conditional_format)
+        );
+        let results = sheet
+            .conditional_formats
+            .evaluate(sheet, Rect::test_a1("A1:A3"));
+        assert!(results.contains_key(&pos![A2]));
+
+        gc.set_cell_value(pos![A3].to_sheet_pos(sheet_id), "10".into(), None);
+        let sheet = gc.sheet(sheet_id);
+        let results = sheet
+            .conditional_formats
+            .evaluate(sheet, Rect::test_a1("A1:A3"));
+        assert!(!results.contains_key(&pos![A2]));
+        assert!(results.contains_key(&pos![A3]));
+    }
+}
@@ -17,23 +17,25 @@ use crate::{Pos, RunError, RunErrorMsg, Value};
 impl GridController {
     // loop compute cycle until complete or an async call is made
     pub(super) fn start_transaction(&mut self, transaction: &mut PendingTransaction) {
-        if cfg!(target_family = "wasm") {
-            let transaction_name = serde_json::to_string(&transaction.transaction_name)
-                .unwrap_or("Unknown".to_string());
-            crate::wasm_bindings::js::jsTransactionStart(
-                transaction.id.to_string(),
-                transaction_name,
-            );
-        }
+        self.host
+            .transaction_start(transaction.id, &transaction.transaction_name);
+
+        self.order_compute_operations(transaction);
 
         loop {
             if transaction.operations.is_empty() && transaction.resize_rows.is_empty() {
                 transaction.complete = true;
+                self.update_conditional_format_formulas(transaction);
                 break;
             }
 
             if let Some(op) = transaction.operations.pop_front() {
+                if let Operation::ComputeCode { sheet_pos } = &op {
+                    transaction.pending_compute_code.remove(sheet_pos);
+                    transaction.changed_code.remove(sheet_pos);
+                }
                 self.execute_operation(transaction, op);
+                self.update_conditional_formats(transaction);
                 self.send_transaction_progress(transaction);
                 self.process_visible_dirty_hashes(transaction);
             }
@@ -93,7 +95,7 @@ impl GridController {
                     self.redo_stack.clear();
                     self.transactions
                         .unsaved_transactions
-                        .insert_or_replace(&transaction, true);
+                        .insert_or_replace(&transaction, Some(&*self.host));
                 }
                 TransactionSource::Unsaved => {
                     let undo = transaction.to_undo_transaction();
@@ -105,14 +107,14 @@ impl GridController {
                     self.redo_stack.push(undo);
                     self.transactions
                         .unsaved_transactions
-                        .insert_or_replace(&transaction, true);
+                        .insert_or_replace(&transaction, Some(&*self.host));
                 }
                 TransactionSource::Redo => {
                     let undo = transaction.to_undo_transaction();
                     self.undo_stack.push(undo);
                     self.transactions
                         .unsaved_transactions
-                        .insert_or_replace(&transaction, true);
+                        .insert_or_replace(&transaction, Some(&*self.host));
                 }
                 TransactionSource::Multiplayer => (),
                 TransactionSource::Server => (),
@@ -120,14 +122,12 @@ impl GridController {
             }
         }
 
-        transaction.send_transaction();
+        transaction.send_transaction(&*self.host);
 
         // TODO(ayush): consolidate these calls, when viewport buffer PR is merged
-        if (cfg!(target_family = "wasm") || cfg!(test)) && !transaction.is_server() {
-            crate::wasm_bindings::js::jsUndoRedo(
-                !self.undo_stack.is_empty(),
-                !self.redo_stack.is_empty(),
-            );
+        if self.host.renders() && !transaction.is_server() {
+            self.host
+                .undo_redo(!self.undo_stack.is_empty(), !self.redo_stack.is_empty());
 
             transaction.sheet_info.iter().for_each(|sheet_id| {
                 self.send_sheet_info(*sheet_id);
@@ -145,7 +145,7 @@ impl GridController {
 
             transaction.validations.iter().for_each(|sheet_id| {
                 if let Some(sheet) = self.try_sheet(*sheet_id) {
-                    sheet.send_all_validations();
+                    sheet.send_all_validations(&*self.host);
                 }
             });
 
@@ -155,13 +155,15 @@ impl GridController {
                 .for_each(|(sheet_id, warnings)| {
                     let warnings = warnings.values().cloned().collect::<Vec<_>>();
                     if let Some(sheet) = self.try_sheet(*sheet_id) {
-                        sheet.send_validation_warnings(warnings);
+                        sheet.send_validation_warnings(&*self.host, warnings);
                     }
                 });
 
             transaction.sheet_borders.iter().for_each(|sheet_id| {
                 if let Some(sheet) = self.try_sheet(*sheet_id) {
-                    sheet.borders.send_sheet_borders(*sheet_id);
+                    sheet
+                        .borders
+                        .send_sheet_borders(&*self.host, *sheet_id, &sheet.merge_cells);
                 }
             });
 
@@ -172,7 +174,7 @@ impl GridController {
                 .for_each(|(sheet_id, positions)| {
                     if let Some(sheet) = self.try_sheet(*sheet_id) {
                         positions.iter().for_each(|pos| {
-                            sheet.send_code_cell(*pos);
+                            sheet.send_code_cell(&*self.host, *pos);
                         });
                     }
                 });
@@ -193,7 +195,7 @@ impl GridController {
                                 h: None,
                             });
                             if let Ok(html) = serde_json::to_string(&html) {
-                                crate::wasm_bindings::js::jsUpdateHtml(html);
+                                self.host.update_html(html);
                             } else {
                                 dbgjs!(format!(
                                     "Error serializing html in finalize_transaction for {:?}",
@@ -216,7 +218,7 @@ impl GridController {
 
             transaction.fill_cells.iter().for_each(|sheet_id| {
                 if let Some(sheet) = self.try_sheet(*sheet_id) {
-                    sheet.resend_fills();
+                    sheet.resend_fills(&*self.host);
                 }
             });
         }
@@ -228,6 +230,9 @@ impl GridController {
         cursor: Option<String>,
         transaction_name: TransactionName,
     ) {
+        if !self.protections_allow(&operations) {
+            return;
+        }
         let mut transaction = PendingTransaction {
             source: TransactionSource::User,
             operations: operations.into(),
@@ -245,6 +250,15 @@ impl GridController {
         transaction_type: TransactionSource,
         cursor: Option<String>,
     ) {
+        if !self.protections_allow(&transaction.operations) {
+            // keep the transaction so it can be tried again
+            match transaction_type {
+                TransactionSource::Undo => self.undo_stack.push(transaction),
+                TransactionSource::Redo => self.redo_stack.push(transaction),
+                _ => (),
+            }
+            return;
+        }
         let mut pending = transaction.to_undo_transaction(transaction_type, cursor);
         pending.id = Uuid::new_v4();
         self.start_transaction(&mut pending);
@@ -308,6 +322,8 @@ impl GridController {
                 spill_error: false,
                 last_modified: Utc::now(),
                 cells_accessed: transaction.cells_accessed.clone(),
+                refresh: Default::default(),
+                volatile: false,
             };
 
             transaction.cells_accessed.clear();
@@ -1,41 +1,80 @@
 use crate::{
     controller::{
         active_transactions::pending_transaction::PendingTransaction,
-        operations::operation::Operation, GridController,
+        execution::{circular_references::cycle_paths, compute_order::topological_order},
+        operations::operation::Operation,
+        GridController,
     },
     grid::CodeCellLanguage,
     CellValue, Pos, Rect, SheetPos, SheetRect,
 };
 
 impl GridController {
-    /// Adds operations to compute cells that are dependents within a SheetRect
+    /// Adds operations to compute cells that are dependents within a
+    /// SheetRect. All code cells affected by the change are found up front and
+    /// the pending compute operations are reordered so that each code cell
+    /// runs once, after the code cells it reads.
     pub fn add_compute_operations(
         &mut self,
         transaction: &mut PendingTransaction,
         output: &SheetRect,
         skip_compute: Option<SheetPos>,
     ) {
-        self.get_dependent_code_cells(output)
-            .iter()
-            .for_each(|sheet_positions| {
-                sheet_positions.iter().for_each(|code_cell_sheet_pos| {
-                    if !skip_compute
-                        .is_some_and(|skip_compute| skip_compute == *code_cell_sheet_pos)
-                    {
-                        // only add a compute operation if there isn't already one pending
-                        if !transaction.operations.iter().any(|op| match op {
-                            Operation::ComputeCode { sheet_pos } => {
-                                code_cell_sheet_pos == sheet_pos
-                            }
-                            _ => false,
-                        }) {
-                            transaction.operations.push_back(Operation::ComputeCode {
-                                sheet_pos: *code_cell_sheet_pos,
-                            });
-                        }
-                    }
-                });
-            });
+        // a code cell that already ran reads the changed output, so the
+        // change may have closed a cycle. Otherwise it read the output before
+        // it changed and runs again.
+        if let Some(changed) = skip_compute {
+            let stale = self
+                .get_dependent_code_cells(output)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|sheet_pos| {
+                    *sheet_pos != changed
+                        && transaction.computed_code.contains(sheet_pos)
+                        && !transaction.circular_code.contains(sheet_pos)
+                })
+                .collect::<Vec<_>>();
+            if !stale.is_empty() {
+                if self.check_code_cell_cycle(transaction, changed) {
+                    return;
+                }
+                for sheet_pos in stale {
+                    transaction.computed_code.remove(&sheet_pos);
+                }
+            }
+        }
+
+        // code cells that are already pending were added with their
+        // dependents; code cells that already ran are not run again
+        let added = self.dependent_code_cells_closure(output, |sheet_pos| {
+            skip_compute == Some(*sheet_pos)
+                || transaction.pending_compute_code.contains(sheet_pos)
+                || transaction.computed_code.contains(sheet_pos)
+        });
+        if added.is_empty() {
+            return;
+        }
+        transaction
+            .pending_compute_code
+            .extend(added.iter().copied());
+
+        // code cells in a cycle are set to errors instead of computed
+        let graph = self.pending_dependents_graph(transaction, &transaction.pending_compute_code);
+        let cycles = cycle_paths(&graph, &transaction.pending_compute_code);
+        for path in cycles.iter() {
+            transaction.pending_compute_code.remove(&path[0]);
+        }
+
+        transaction
+            .operations
+            .retain(|op| !matches!(op, Operation::ComputeCode { .. }));
+        for sheet_pos in topological_order(&graph, &transaction.pending_compute_code) {
+            transaction
+                .operations
+                .push_back(Operation::ComputeCode { sheet_pos });
+        }
+
+        self.set_circular_references(transaction, cycles);
     }
 
     // delete any code runs within the sheet_rect.
@@ -127,6 +166,8 @@ impl GridController {
                 dbgjs!("Only user / undo / redo / server transaction should have a ComputeCode");
                 return;
             }
+            transaction.computed_code.insert(sheet_pos);
+
             let sheet_id = sheet_pos.sheet_id;
             let Some(sheet) = self.try_sheet(sheet_id) else {
                 // sheet may have been deleted in a multiplayer operation
@@ -158,6 +199,40 @@ impl GridController {
             }
         }
     }
+
+    pub(super) fn execute_set_code_run_refresh(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetCodeRunRefresh { sheet_pos, refresh } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_pos.sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+        let pos = Pos::from(sheet_pos);
+        let Some(code_run) = sheet.code_runs.get_mut(&pos) else {
+            // code cell may have been deleted
+            return;
+        };
+        if code_run.refresh == refresh {
+            return;
+        }
+        let old_refresh = std::mem::replace(&mut code_run.refresh, refresh);
+
+        transaction
+            .forward_operations
+            .push(Operation::SetCodeRunRefresh { sheet_pos, refresh });
+        transaction
+            .reverse_operations
+            .push(Operation::SetCodeRunRefresh {
+                sheet_pos,
+                refresh: old_refresh,
+            });
+
+        transaction.add_code_cell(sheet_pos.sheet_id, pos);
+    }
 }
 
 #[cfg(test)]
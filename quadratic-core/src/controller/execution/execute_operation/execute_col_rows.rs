@@ -157,6 +157,9 @@ impl GridController {
                 return;
             }
 
+            // code cells to the right of (or below) the change have moved
+            self.update_sheet_dependencies(sheet_id);
+
             if transaction.is_user() {
                 // adjust formulas to account for deleted column (needs to be
                 // here since it's across sheets)
@@ -192,6 +195,9 @@ impl GridController {
                 return;
             }
 
+            // code cells to the right of (or below) the change have moved
+            self.update_sheet_dependencies(sheet_id);
+
             if transaction.is_user() {
                 // adjust formulas to account for deleted column (needs to be
                 // here since it's across sheets)
@@ -232,6 +238,9 @@ impl GridController {
                 return;
             }
 
+            // code cells to the right of (or below) the change have moved
+            self.update_sheet_dependencies(sheet_id);
+
             if transaction.is_user() {
                 // adjust formulas to account for inserted column (needs to be
                 // here since it's across sheets)
@@ -272,6 +281,9 @@ impl GridController {
                 return;
             }
 
+            // code cells to the right of (or below) the change have moved
+            self.update_sheet_dependencies(sheet_id);
+
             if transaction.is_user() {
                 // adjust formulas to account for deleted column (needs to be
                 // here since it's across sheets)
@@ -451,6 +463,8 @@ mod tests {
             last_modified: Utc::now(),
             cells_accessed,
             formatted_code_string: None,
+            refresh: Default::default(),
+            volatile: false,
         };
         let transaction = &mut PendingTransaction::default();
         gc.finalize_code_run(transaction, sheet_pos, Some(code_run), None);
@@ -527,6 +541,8 @@ mod tests {
             last_modified: Utc::now(),
             cells_accessed,
             formatted_code_string: None,
+            refresh: Default::default(),
+            volatile: false,
         };
         let transaction = &mut PendingTransaction::default();
         gc.finalize_code_run(transaction, sheet_pos, Some(code_run), None);
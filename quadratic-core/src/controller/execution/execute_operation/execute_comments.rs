@@ -0,0 +1,191 @@
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+
+impl GridController {
+    pub(crate) fn execute_set_comment_thread(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetCommentThread { sheet_id, thread } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        transaction
+            .forward_operations
+            .push(Operation::SetCommentThread {
+                sheet_id,
+                thread: thread.clone(),
+            });
+        transaction
+            .reverse_operations
+            .extend(sheet.comments.set(sheet_id, thread));
+
+        transaction.sheet_info.insert(sheet_id);
+    }
+
+    pub(crate) fn execute_remove_comment_thread(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let RemoveCommentThread { sheet_id, thread_id } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let reverse = sheet.comments.remove(sheet_id, thread_id);
+        if reverse.is_empty() {
+            return;
+        }
+
+        transaction
+            .forward_operations
+            .push(Operation::RemoveCommentThread {
+                sheet_id,
+                thread_id,
+            });
+        transaction.reverse_operations.extend(reverse);
+
+        transaction.sheet_info.insert(sheet_id);
+    }
+
+    pub(crate) fn execute_add_comment_reply(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let AddCommentReply { sheet_id, thread_id, reply } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        // the thread may have been removed by another user
+        let reverse = sheet.comments.add_reply(sheet_id, thread_id, reply.clone());
+        if reverse.is_empty() {
+            return;
+        }
+
+        transaction
+            .forward_operations
+            .push(Operation::AddCommentReply {
+                sheet_id,
+                thread_id,
+                reply,
+            });
+        transaction.reverse_operations.extend(reverse);
+
+        transaction.sheet_info.insert(sheet_id);
+    }
+
+    pub(crate) fn execute_remove_comment_reply(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let RemoveCommentReply { sheet_id, thread_id, reply_id } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let reverse = sheet.comments.remove_reply(sheet_id, thread_id, reply_id);
+        if reverse.is_empty() {
+            return;
+        }
+
+        transaction
+            .forward_operations
+            .push(Operation::RemoveCommentReply {
+                sheet_id,
+                thread_id,
+                reply_id,
+            });
+        transaction.reverse_operations.extend(reverse);
+
+        transaction.sheet_info.insert(sheet_id);
+    }
+
+    pub(crate) fn execute_set_comment_thread_resolved(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetCommentThreadResolved { sheet_id, thread_id, resolved } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let reverse = sheet.comments.set_resolved(sheet_id, thread_id, resolved);
+        if reverse.is_empty() {
+            return;
+        }
+
+        transaction
+            .forward_operations
+            .push(Operation::SetCommentThreadResolved {
+                sheet_id,
+                thread_id,
+                resolved,
+            });
+        transaction.reverse_operations.extend(reverse);
+
+        transaction.sheet_info.insert(sheet_id);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::controller::active_transactions::transaction_name::TransactionName;
+    use crate::controller::operations::operation::Operation;
+    use crate::controller::GridController;
+    use crate::grid::sheet::comments::{Comment, CommentThread};
+
+    #[test]
+    fn execute_set_remove_comment_thread() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let thread = CommentThread::new(
+            pos![A1],
+            Comment::new("user@test.com".into(), "hello".into()),
+        );
+
+        gc.start_user_transaction(
+            vec![Operation::SetCommentThread {
+                sheet_id,
+                thread: thread.clone(),
+            }],
+            None,
+            TransactionName::Comment,
+        );
+        assert_eq!(gc.sheet(sheet_id).comments.thread(thread.id), Some(&thread));
+
+        gc.start_user_transaction(
+            vec![Operation::RemoveCommentThread {
+                sheet_id,
+                thread_id: thread.id,
+            }],
+            None,
+            TransactionName::Comment,
+        );
+        assert!(gc.sheet(sheet_id).comments.is_empty());
+
+        gc.undo(None);
+        assert_eq!(gc.sheet(sheet_id).comments.thread(thread.id), Some(&thread));
+
+        gc.undo(None);
+        assert!(gc.sheet(sheet_id).comments.is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+
+impl GridController {
+    pub(crate) fn execute_set_conditional_format(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetConditionalFormat { conditional_format } = op);
+
+        let sheet_id = conditional_format.selection.sheet_id;
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        // both the old and new selections need to be rendered
+        let mut selections = vec![conditional_format.selection.clone()];
+        if let Some(old) = sheet
+            .conditional_formats
+            .conditional_format(conditional_format.id)
+        {
+            selections.push(old.selection.clone());
+        }
+
+        transaction
+            .forward_operations
+            .push(Operation::SetConditionalFormat {
+                conditional_format: conditional_format.clone(),
+            });
+        transaction
+            .reverse_operations
+            .extend(sheet.conditional_formats.set(conditional_format));
+
+        transaction.sheet_info.insert(sheet_id);
+        if !transaction.is_server() {
+            transaction.add_dirty_hashes_from_selections(sheet, selections);
+            transaction.fill_cells.insert(sheet_id);
+        }
+    }
+
+    pub(crate) fn execute_remove_conditional_format(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let RemoveConditionalFormat { sheet_id, conditional_format_id } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+        let Some(selection) = sheet
+            .conditional_formats
+            .conditional_format(conditional_format_id)
+            .map(|cf| cf.selection.clone())
+        else {
+            return;
+        };
+
+        transaction
+            .forward_operations
+            .push(Operation::RemoveConditionalFormat {
+                sheet_id,
+                conditional_format_id,
+            });
+        transaction
+            .reverse_operations
+            .extend(sheet.conditional_formats.remove(conditional_format_id));
+
+        transaction.sheet_info.insert(sheet_id);
+        if !transaction.is_server() {
+            transaction.add_dirty_hashes_from_selections(sheet, vec![selection]);
+            transaction.fill_cells.insert(sheet_id);
+        }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::controller::active_transactions::transaction_name::TransactionName;
+    use crate::controller::operations::operation::Operation;
+    use crate::controller::GridController;
+    use crate::grid::formats::Format;
+    use crate::grid::sheet::conditional_formats::conditional_format::ConditionalFormat;
+    use crate::grid::sheet::conditional_formats::conditional_format_rule::ConditionalFormatRule;
+    use crate::A1Selection;
+
+    #[test]
+    fn execute_set_remove_conditional_format() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let conditional_format = ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1_sheet_id("A1:B2", &sheet_id),
+            rule: ConditionalFormatRule::Duplicates { unique: false },
+            format: Format {
+                italic: Some(true),
+                ..Default::default()
+            },
+        };
+
+        gc.start_user_transaction(
+            vec![Operation::SetConditionalFormat {
+                conditional_format: conditional_format.clone(),
+            }],
+            None,
+            TransactionName::ConditionalFormat,
+        );
+        assert_eq!(
+            gc.sheet(sheet_id).conditional_formats.conditional_formats,
+            vec![conditional_format.clone()]
+        );
+
+        gc.start_user_transaction(
+            vec![Operation::RemoveConditionalFormat {
+                sheet_id,
+                conditional_format_id: conditional_format.id,
+            }],
+            None,
+            TransactionName::ConditionalFormat,
+        );
+        assert!(gc.sheet(sheet_id).conditional_formats.is_empty());
+
+        gc.undo(None);
+        assert_eq!(
+            gc.sheet(sheet_id).conditional_formats.conditional_formats,
+            vec![conditional_format]
+        );
+
+        gc.undo(None);
+        assert!(gc.sheet(sheet_id).conditional_formats.is_empty());
+    }
+}
@@ -43,12 +43,13 @@ impl GridController {
             return;
         }
 
-        if cfg!(target_family = "wasm") && !transaction.is_server() {
+        if self.host.renders() {
             if let Ok(json) = serde_json::to_string(&selection) {
-                crate::wasm_bindings::js::jsSetCursorSelection(json);
+                if cfg!(test) {
+                    transaction.cursor = Some(json.clone());
+                }
+                self.host.set_cursor_selection(json);
             }
-        } else if cfg!(test) {
-            transaction.cursor = Some(serde_json::to_string(&selection).unwrap());
         }
     }
 }
@@ -10,6 +10,11 @@ impl GridController {
     ) {
         unwrap_op!(let SetCellFormatsA1 { sheet_id, formats } = op);
 
+        if !formats.is_valid() {
+            dbgjs!("Invalid format update in execute_set_cell_formats_a1");
+            return;
+        }
+
         transaction.generate_thumbnail |= self.thumbnail_dirty_formats(sheet_id, &formats);
 
         let Some(sheet) = self.try_sheet_mut(sheet_id) else {
@@ -98,6 +103,8 @@ mod test {
                 return_type: None,
                 line_number: None,
                 last_modified: Utc::now(),
+                refresh: Default::default(),
+                volatile: false,
             }),
         );
 
@@ -0,0 +1,159 @@
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+use crate::{Rect, SheetRect};
+
+impl GridController {
+    /// Marks the merged (or unmerged) regions for re-rendering.
+    fn merge_cells_changed(
+        transaction: &mut PendingTransaction,
+        sheet_rect: SheetRect,
+        removed: &[Rect],
+    ) {
+        transaction.sheet_info.insert(sheet_rect.sheet_id);
+        if transaction.is_server() {
+            return;
+        }
+        transaction.add_dirty_hashes_from_sheet_rect(sheet_rect);
+        removed.iter().for_each(|rect| {
+            transaction.add_dirty_hashes_from_sheet_rect(rect.to_sheet_rect(sheet_rect.sheet_id));
+        });
+        transaction.sheet_borders.insert(sheet_rect.sheet_id);
+        transaction.generate_thumbnail = true;
+    }
+
+    pub(crate) fn execute_merge_cells(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let MergeCells { sheet_rect } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_rect.sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let removed = sheet.merge_cells.merge(sheet_rect.into());
+        Self::merge_cells_changed(transaction, sheet_rect, &removed);
+
+        transaction
+            .forward_operations
+            .push(Operation::MergeCells { sheet_rect });
+
+        // the reverse operations are applied in reverse order: first unmerge
+        // the new region, then restore the regions it replaced
+        transaction
+            .reverse_operations
+            .extend(removed.into_iter().map(|rect| Operation::MergeCells {
+                sheet_rect: rect.to_sheet_rect(sheet_rect.sheet_id),
+            }));
+        transaction
+            .reverse_operations
+            .push(Operation::UnmergeCells { sheet_rect });
+    }
+
+    pub(crate) fn execute_unmerge_cells(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let UnmergeCells { sheet_rect } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_rect.sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let removed = sheet.merge_cells.unmerge(sheet_rect.into());
+        if removed.is_empty() {
+            return;
+        }
+        Self::merge_cells_changed(transaction, sheet_rect, &removed);
+
+        transaction
+            .forward_operations
+            .push(Operation::UnmergeCells { sheet_rect });
+        transaction
+            .reverse_operations
+            .extend(removed.into_iter().map(|rect| Operation::MergeCells {
+                sheet_rect: rect.to_sheet_rect(sheet_rect.sheet_id),
+            }));
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::controller::active_transactions::transaction_name::TransactionName;
+    use crate::controller::operations::operation::Operation;
+    use crate::controller::GridController;
+    use crate::Rect;
+
+    #[test]
+    fn execute_merge_cells() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.start_user_transaction(
+            vec![Operation::MergeCells {
+                sheet_rect: Rect::test_a1("A1:B2").to_sheet_rect(sheet_id),
+            }],
+            None,
+            TransactionName::MergeCells,
+        );
+        gc.start_user_transaction(
+            vec![Operation::MergeCells {
+                sheet_rect: Rect::test_a1("B2:C3").to_sheet_rect(sheet_id),
+            }],
+            None,
+            TransactionName::MergeCells,
+        );
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.merge_cells.get(pos![A1]), None);
+        assert_eq!(
+            sheet.merge_cells.get(pos![C3]),
+            Some(Rect::test_a1("B2:C3"))
+        );
+
+        gc.undo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.merge_cells.get(pos![A1]),
+            Some(Rect::test_a1("A1:B2"))
+        );
+        assert_eq!(sheet.merge_cells.get(pos![C3]), None);
+
+        gc.redo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.merge_cells.get(pos![C3]),
+            Some(Rect::test_a1("B2:C3"))
+        );
+        assert_eq!(sheet.merge_cells.iter().count(), 1);
+    }
+
+    #[test]
+    fn execute_unmerge_cells() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.sheet_mut(sheet_id)
+            .merge_cells
+            .merge(Rect::test_a1("A1:B2"));
+
+        gc.start_user_transaction(
+            vec![Operation::UnmergeCells {
+                sheet_rect: Rect::test_a1("B2").to_sheet_rect(sheet_id),
+            }],
+            None,
+            TransactionName::MergeCells,
+        );
+        assert!(gc.sheet(sheet_id).merge_cells.is_empty());
+
+        gc.undo(None);
+        assert_eq!(
+            gc.sheet(sheet_id).merge_cells.get(pos![B1]),
+            Some(Rect::test_a1("A1:B2"))
+        );
+    }
+}
@@ -16,6 +16,7 @@ impl GridController {
             client_resized,
         } = op
         {
+            let renders = self.host.renders();
             let Some(sheet) = self.try_sheet_mut(sheet_id) else {
                 // sheet may have been deleted
                 return;
@@ -44,7 +45,7 @@ impl GridController {
                     client_resized: false,
                 });
 
-            if (cfg!(target_family = "wasm") || cfg!(test))
+            if renders
                 && (transaction.is_undo_redo()
                     || transaction.is_multiplayer()
                     || (!client_resized && transaction.is_user()))
@@ -107,7 +108,7 @@ impl GridController {
                 client_resized: old_client_resize,
             });
 
-            if (cfg!(target_family = "wasm") || cfg!(test))
+            if self.host.renders()
                 && (transaction.is_undo_redo()
                     || transaction.is_multiplayer()
                     || (!client_resized && transaction.is_user()))
@@ -168,7 +169,7 @@ impl GridController {
                 row_heights: old_row_heights,
             });
 
-            if (cfg!(target_family = "wasm") || cfg!(test)) && !transaction.is_server() {
+            if self.host.renders() && !transaction.is_server() {
                 row_heights.iter().for_each(|&JsRowHeight { row, height }| {
                     transaction.offsets_modified(sheet_id, None, Some(row), Some(height));
                 });
@@ -0,0 +1,239 @@
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+
+impl GridController {
+    pub(crate) fn execute_set_columns_hidden(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetColumnsHidden { sheet_id, columns, hidden } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let changed = columns
+            .into_iter()
+            .filter(|&column| sheet.outline.columns.set_hidden(column, hidden) != hidden)
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            return;
+        }
+
+        sheet.outline_changed(transaction);
+
+        transaction
+            .forward_operations
+            .push(Operation::SetColumnsHidden {
+                sheet_id,
+                columns: changed.clone(),
+                hidden,
+            });
+        transaction
+            .reverse_operations
+            .push(Operation::SetColumnsHidden {
+                sheet_id,
+                columns: changed,
+                hidden: !hidden,
+            });
+
+        if !transaction.is_server() {
+            transaction.generate_thumbnail = true;
+        }
+    }
+
+    pub(crate) fn execute_set_rows_hidden(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetRowsHidden { sheet_id, rows, hidden } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let changed = rows
+            .into_iter()
+            .filter(|&row| sheet.outline.rows.set_hidden(row, hidden) != hidden)
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            return;
+        }
+
+        sheet.outline_changed(transaction);
+
+        transaction
+            .forward_operations
+            .push(Operation::SetRowsHidden {
+                sheet_id,
+                rows: changed.clone(),
+                hidden,
+            });
+        transaction
+            .reverse_operations
+            .push(Operation::SetRowsHidden {
+                sheet_id,
+                rows: changed,
+                hidden: !hidden,
+            });
+
+        if !transaction.is_server() {
+            transaction.generate_thumbnail = true;
+        }
+    }
+
+    pub(crate) fn execute_set_column_groups(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetColumnGroups { sheet_id, groups } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        if sheet.outline.columns.groups == groups {
+            return;
+        }
+        let old_groups = std::mem::replace(&mut sheet.outline.columns.groups, groups.clone());
+
+        sheet.outline_changed(transaction);
+
+        transaction
+            .forward_operations
+            .push(Operation::SetColumnGroups { sheet_id, groups });
+        transaction
+            .reverse_operations
+            .push(Operation::SetColumnGroups {
+                sheet_id,
+                groups: old_groups,
+            });
+
+        if !transaction.is_server() {
+            transaction.generate_thumbnail = true;
+        }
+    }
+
+    pub(crate) fn execute_set_row_groups(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetRowGroups { sheet_id, groups } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        if sheet.outline.rows.groups == groups {
+            return;
+        }
+        let old_groups = std::mem::replace(&mut sheet.outline.rows.groups, groups.clone());
+
+        sheet.outline_changed(transaction);
+
+        transaction
+            .forward_operations
+            .push(Operation::SetRowGroups { sheet_id, groups });
+        transaction
+            .reverse_operations
+            .push(Operation::SetRowGroups {
+                sheet_id,
+                groups: old_groups,
+            });
+
+        if !transaction.is_server() {
+            transaction.generate_thumbnail = true;
+        }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::{
+        controller::{
+            active_transactions::transaction_name::TransactionName,
+            operations::operation::Operation, GridController,
+        },
+        grid::sheet::outline::OutlineGroup,
+    };
+
+    #[test]
+    fn execute_set_columns_hidden() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.start_user_transaction(
+            vec![Operation::SetColumnsHidden {
+                sheet_id,
+                columns: vec![2, 3],
+                hidden: true,
+            }],
+            None,
+            TransactionName::ManipulateColumnRow,
+        );
+        let sheet = gc.sheet(sheet_id);
+        assert!(sheet.is_column_hidden(2));
+        assert!(sheet.is_column_hidden(3));
+        assert_eq!(sheet.offsets.column_width(3), 0.0);
+
+        gc.undo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert!(!sheet.is_column_hidden(2));
+        assert!(!sheet.is_column_hidden(3));
+
+        gc.redo(None);
+        assert!(gc.sheet(sheet_id).is_column_hidden(2));
+    }
+
+    #[test]
+    fn execute_set_row_groups() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let groups = vec![OutlineGroup {
+            start: 2,
+            end: 4,
+            collapsed: true,
+        }];
+        gc.start_user_transaction(
+            vec![Operation::SetRowGroups {
+                sheet_id,
+                groups: groups.clone(),
+            }],
+            None,
+            TransactionName::ManipulateColumnRow,
+        );
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.outline.rows.groups, groups);
+        assert!(sheet.is_row_hidden(3));
+        assert!(!sheet.is_row_hidden(5));
+
+        gc.undo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert!(sheet.outline.rows.groups.is_empty());
+        assert!(!sheet.is_row_hidden(3));
+    }
+
+    #[test]
+    fn execute_outline_multiplayer() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let mut gc2 = GridController::test();
+        gc2.grid_mut().sheets_mut()[0].id = sheet_id;
+
+        gc.hide_rows(sheet_id, vec![5], None);
+        let transaction = gc.last_transaction().unwrap().clone();
+        gc2.received_transaction(transaction.id, 1, transaction.operations);
+        assert!(gc2.sheet(sheet_id).is_row_hidden(5));
+    }
+}
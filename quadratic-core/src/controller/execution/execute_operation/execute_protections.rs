@@ -0,0 +1,98 @@
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+
+impl GridController {
+    pub(crate) fn execute_set_protected_range(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let SetProtectedRange { sheet_id, range } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        transaction
+            .forward_operations
+            .push(Operation::SetProtectedRange {
+                sheet_id,
+                range: range.clone(),
+            });
+        transaction
+            .reverse_operations
+            .extend(sheet.protections.set(sheet_id, range));
+
+        transaction.sheet_info.insert(sheet_id);
+    }
+
+    pub(crate) fn execute_remove_protected_range(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        op: Operation,
+    ) {
+        unwrap_op!(let RemoveProtectedRange { sheet_id, range_id } = op);
+
+        let Some(sheet) = self.try_sheet_mut(sheet_id) else {
+            // sheet may have been deleted
+            return;
+        };
+
+        let reverse = sheet.protections.remove(sheet_id, range_id);
+        if reverse.is_empty() {
+            return;
+        }
+
+        transaction
+            .forward_operations
+            .push(Operation::RemoveProtectedRange { sheet_id, range_id });
+        transaction.reverse_operations.extend(reverse);
+
+        transaction.sheet_info.insert(sheet_id);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::controller::active_transactions::transaction_name::TransactionName;
+    use crate::controller::operations::operation::Operation;
+    use crate::controller::GridController;
+    use crate::grid::sheet::protections::ProtectedRange;
+    use crate::Rect;
+
+    #[test]
+    fn execute_set_remove_protected_range() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let range = ProtectedRange::new(Some(Rect::test_a1("A1:B2")), vec![], vec![]);
+
+        gc.start_user_transaction(
+            vec![Operation::SetProtectedRange {
+                sheet_id,
+                range: range.clone(),
+            }],
+            None,
+            TransactionName::ProtectedRange,
+        );
+        assert_eq!(gc.sheet(sheet_id).protections.get(range.id), Some(&range));
+
+        gc.start_user_transaction(
+            vec![Operation::RemoveProtectedRange {
+                sheet_id,
+                range_id: range.id,
+            }],
+            None,
+            TransactionName::ProtectedRange,
+        );
+        assert!(gc.sheet(sheet_id).protections.is_empty());
+
+        gc.undo(None);
+        assert_eq!(gc.sheet(sheet_id).protections.get(range.id), Some(&range));
+
+        gc.undo(None);
+        assert!(gc.sheet(sheet_id).protections.is_empty());
+    }
+}
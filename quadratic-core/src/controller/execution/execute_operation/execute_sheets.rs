@@ -20,6 +20,7 @@ impl GridController {
                 return;
             }
             let sheet_id = self.grid.add_sheet(Some((*sheet).clone()));
+            self.update_sheet_dependencies(sheet_id);
 
             self.send_add_sheet(sheet_id, transaction);
 
@@ -47,6 +48,7 @@ impl GridController {
                 }
                 let sheet_id = sheet.id;
                 self.grid.add_sheet(Some(sheet));
+                self.update_sheet_dependencies(sheet_id);
 
                 self.send_add_sheet(sheet_id, transaction);
                 self.send_all_fills(sheet_id);
@@ -76,6 +78,7 @@ impl GridController {
                 // sheet was already deleted
                 return;
             };
+            self.update_sheet_dependencies(sheet_id);
 
             transaction
                 .forward_operations
@@ -228,6 +231,7 @@ impl GridController {
                 new_sheet.name = crate::util::unused_name(&name, &self.sheet_names());
             }
             self.grid.add_sheet(Some(new_sheet));
+            self.update_sheet_dependencies(new_sheet_id);
 
             self.send_add_sheet(new_sheet_id, transaction);
 
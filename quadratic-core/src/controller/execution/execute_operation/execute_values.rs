@@ -18,19 +18,19 @@ impl GridController {
                         transaction,
                         sheet_pos.into(),
                         &values,
-                        !transaction.is_server(),
+                        (!transaction.is_server()).then_some(&*self.host),
                     );
                     if old_values == values {
                         return;
                     }
 
-                    if cfg!(target_family = "wasm")
+                    if self.host.renders()
                         && !transaction.is_server()
                         && values.into_iter().any(|(_, _, value)| value.is_html())
                     {
                         if let Some(html) = sheet.get_single_html_output(sheet_pos.into()) {
                             if let Ok(html) = serde_json::to_string(&html) {
-                                crate::wasm_bindings::js::jsUpdateHtml(html);
+                                self.host.update_html(html);
                             }
                         }
                     };
@@ -28,11 +28,16 @@ mod execute_borders;
 mod execute_borders_old;
 mod execute_code;
 mod execute_col_rows;
+mod execute_comments;
+mod execute_conditional_format;
 mod execute_cursor;
 mod execute_formats;
 mod execute_formats_old;
+mod execute_merge_cells;
 mod execute_move_cells;
 mod execute_offsets;
+mod execute_outline;
+mod execute_protections;
 mod execute_sheets;
 mod execute_validation;
 mod execute_values;
@@ -96,6 +101,48 @@ impl GridController {
             Operation::DeleteRow { .. } => self.execute_delete_row(transaction, op),
             Operation::InsertColumn { .. } => self.execute_insert_column(transaction, op),
             Operation::InsertRow { .. } => self.execute_insert_row(transaction, op),
+
+            Operation::SetColumnsHidden { .. } => {
+                self.execute_set_columns_hidden(transaction, op);
+            }
+            Operation::SetRowsHidden { .. } => self.execute_set_rows_hidden(transaction, op),
+            Operation::SetColumnGroups { .. } => {
+                self.execute_set_column_groups(transaction, op);
+            }
+            Operation::SetRowGroups { .. } => self.execute_set_row_groups(transaction, op),
+
+            Operation::MergeCells { .. } => self.execute_merge_cells(transaction, op),
+            Operation::UnmergeCells { .. } => self.execute_unmerge_cells(transaction, op),
+
+            Operation::SetConditionalFormat { .. } => {
+                self.execute_set_conditional_format(transaction, op);
+            }
+            Operation::RemoveConditionalFormat { .. } => {
+                self.execute_remove_conditional_format(transaction, op);
+            }
+
+            Operation::SetCommentThread { .. } => self.execute_set_comment_thread(transaction, op),
+            Operation::RemoveCommentThread { .. } => {
+                self.execute_remove_comment_thread(transaction, op);
+            }
+            Operation::AddCommentReply { .. } => self.execute_add_comment_reply(transaction, op),
+            Operation::RemoveCommentReply { .. } => {
+                self.execute_remove_comment_reply(transaction, op);
+            }
+            Operation::SetCommentThreadResolved { .. } => {
+                self.execute_set_comment_thread_resolved(transaction, op);
+            }
+
+            Operation::SetProtectedRange { .. } => {
+                self.execute_set_protected_range(transaction, op);
+            }
+            Operation::RemoveProtectedRange { .. } => {
+                self.execute_remove_protected_range(transaction, op);
+            }
+
+            Operation::SetCodeRunRefresh { .. } => {
+                self.execute_set_code_run_refresh(transaction, op);
+            }
         }
     }
 }
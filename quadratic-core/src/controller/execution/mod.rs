@@ -1,6 +1,10 @@
 pub mod auto_resize_row_heights;
+pub mod circular_references;
+pub mod compute_order;
+pub mod conditional_formats;
 pub mod control_transaction;
 pub mod execute_operation;
+pub mod recalculate;
 pub mod receive_multiplayer;
 pub mod run_code;
 pub mod spills;
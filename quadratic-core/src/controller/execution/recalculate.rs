@@ -0,0 +1,426 @@
+//! Server-side recalculation
+//!
+//! Clients run code and send the results with their transactions, so the
+//! server usually only replays operations. A client that disconnects in the
+//! middle of a calculation can leave stale results behind, so the server can
+//! also recalculate the formulas that depend on the changed cells. Python,
+//! Javascript and connection cells can only be run by a client; they are
+//! returned as stale instead.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::a1::UNBOUNDED;
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::active_transactions::transaction_name::TransactionName;
+use crate::controller::execution::compute_order::sort_sheet_positions;
+use crate::controller::execution::TransactionSource;
+use crate::controller::operations::operation::Operation;
+use crate::controller::GridController;
+use crate::grid::CodeCellLanguage;
+use crate::{CellValue, Pos, SheetPos, SheetRect};
+
+/// Formulas whose outputs grow can change more formulas, which are
+/// recalculated in another round.
+const MAX_RECALCULATION_ROUNDS: usize = 10;
+
+/// Cells whose values are changed by a batch of operations.
+#[derive(Default)]
+struct ValueChanges {
+    rects: Vec<SheetRect>,
+
+    /// Code cells that were set by the operations.
+    code_cells: Vec<SheetPos>,
+
+    /// Sheets were added, removed or renamed, which can change any formula.
+    all_formulas: bool,
+}
+
+impl GridController {
+    /// Used by the server to apply transactions and then recalculate the
+    /// formulas that depend on the changed cells. Returns the code cells that
+    /// depend on the changed cells but cannot be run by the server.
+    pub fn server_apply_transaction_and_recalculate(
+        &mut self,
+        operations: Vec<Operation>,
+        transaction_name: Option<TransactionName>,
+    ) -> Vec<SheetPos> {
+        // code runs may be replaced by ones with smaller outputs, so their
+        // outputs are read before and after the operations are applied
+        let mut changes = self.value_changes(&operations);
+        self.server_apply_transaction(operations, transaction_name);
+        let after = self.value_changes_code_runs(&changes.code_cells);
+        changes.rects.extend(after);
+
+        self.server_recalculate(changes)
+    }
+
+    /// Returns the cells changed by the operations, using the current grid
+    /// for the outputs of code runs.
+    fn value_changes(&self, operations: &[Operation]) -> ValueChanges {
+        let mut changes = ValueChanges::default();
+
+        for operation in operations {
+            match operation {
+                Operation::SetCellValues { sheet_pos, values } => {
+                    changes.rects.push(SheetRect {
+                        min: (*sheet_pos).into(),
+                        max: Pos {
+                            x: sheet_pos.x + (values.w as i64).max(1) - 1,
+                            y: sheet_pos.y + (values.h as i64).max(1) - 1,
+                        },
+                        sheet_id: sheet_pos.sheet_id,
+                    });
+                    changes.code_cells.extend(
+                        values
+                            .into_iter()
+                            .filter(|(_, _, value)| matches!(value, CellValue::Code(_)))
+                            .map(|(x, y, _)| SheetPos {
+                                x: sheet_pos.x + x as i64,
+                                y: sheet_pos.y + y as i64,
+                                sheet_id: sheet_pos.sheet_id,
+                            }),
+                    );
+                }
+                Operation::SetCodeRun { sheet_pos, .. }
+                | Operation::SetCodeRunVersion { sheet_pos, .. } => {
+                    changes.rects.push(self.code_output_sheet_rect(*sheet_pos));
+                    changes.code_cells.push(*sheet_pos);
+                }
+                Operation::MoveCells { source, dest } => {
+                    changes.rects.push(*source);
+                    changes.rects.push(SheetRect {
+                        min: (*dest).into(),
+                        max: Pos {
+                            x: dest.x + source.max.x - source.min.x,
+                            y: dest.y + source.max.y - source.min.y,
+                        },
+                        sheet_id: dest.sheet_id,
+                    });
+                }
+                // cells after an inserted or deleted column or row move
+                Operation::InsertColumn {
+                    sheet_id, column, ..
+                }
+                | Operation::DeleteColumn { sheet_id, column } => {
+                    changes
+                        .rects
+                        .push(SheetRect::new(*column, 1, UNBOUNDED, UNBOUNDED, *sheet_id));
+                }
+                Operation::InsertRow { sheet_id, row, .. }
+                | Operation::DeleteRow { sheet_id, row } => {
+                    changes
+                        .rects
+                        .push(SheetRect::new(1, *row, UNBOUNDED, UNBOUNDED, *sheet_id));
+                }
+                Operation::AddSheet { .. }
+                | Operation::AddSheetSchema { .. }
+                | Operation::DuplicateSheet { .. }
+                | Operation::DeleteSheet { .. }
+                | Operation::SetSheetName { .. } => changes.all_formulas = true,
+                _ => (),
+            }
+        }
+
+        changes
+    }
+
+    /// Returns the outputs of the code runs at the positions.
+    fn value_changes_code_runs(&self, code_cells: &[SheetPos]) -> Vec<SheetRect> {
+        code_cells
+            .iter()
+            .map(|sheet_pos| self.code_output_sheet_rect(*sheet_pos))
+            .collect()
+    }
+
+    /// Returns the code of a cell if it's a formula, or None if it's another
+    /// kind of code cell. Cells that are no longer code are ignored.
+    fn formula_code(&self, sheet_pos: SheetPos) -> Option<Option<String>> {
+        let sheet = self.try_sheet(sheet_pos.sheet_id)?;
+        match sheet.cell_value(sheet_pos.into()) {
+            Some(CellValue::Code(code)) => {
+                Some((code.language == CodeCellLanguage::Formula).then_some(code.code))
+            }
+            _ => None,
+        }
+    }
+
+    /// Recalculates the formulas affected by the changes. Returns the other
+    /// code cells that were affected.
+    fn server_recalculate(&mut self, changes: ValueChanges) -> Vec<SheetPos> {
+        let mut transaction = PendingTransaction {
+            source: TransactionSource::Server,
+            ..Default::default()
+        };
+        let mut stale = HashSet::new();
+        let mut sheets = HashSet::new();
+
+        let mut cells = changes.code_cells;
+        if changes.all_formulas {
+            cells.extend(
+                self.grid
+                    .sheets()
+                    .iter()
+                    .flat_map(|sheet| sheet.code_runs.keys().map(|pos| pos.to_sheet_pos(sheet.id))),
+            );
+        }
+        let mut rects = changes.rects;
+
+        for _ in 0..MAX_RECALCULATION_ROUNDS {
+            let formulas = self.dirty_formulas(&cells, &rects, &mut stale);
+            if formulas.is_empty() {
+                break;
+            }
+
+            rects = vec![];
+            for sheet_pos in self.compute_order(&formulas) {
+                let Some(Some(code)) = self.formula_code(sheet_pos) else {
+                    continue;
+                };
+                let old = self.code_output_sheet_rect(sheet_pos);
+                self.run_formula(&mut transaction, sheet_pos, code);
+                let new = self.code_output_sheet_rect(sheet_pos);
+
+                // formulas that read the new part of the output run next round
+                if new.max.x > old.max.x || new.max.y > old.max.y {
+                    rects.push(new);
+                }
+                sheets.insert(sheet_pos.sheet_id);
+            }
+            cells = vec![];
+        }
+
+        for sheet_id in sheets {
+            self.check_all_spills(&mut transaction, sheet_id, false);
+        }
+
+        let mut stale = stale.into_iter().collect::<Vec<_>>();
+        sort_sheet_positions(&mut stale);
+        stale
+    }
+
+    /// Returns the formulas in `cells` and those that depend on `rects`,
+    /// followed through the outputs of those formulas. Other code cells are
+    /// added to `stale`.
+    fn dirty_formulas(
+        &self,
+        cells: &[SheetPos],
+        rects: &[SheetRect],
+        stale: &mut HashSet<SheetPos>,
+    ) -> HashSet<SheetPos> {
+        let mut formulas = HashSet::new();
+        let mut queue = rects.iter().copied().collect::<VecDeque<_>>();
+        let mut add = |sheet_pos: SheetPos, queue: &mut VecDeque<SheetRect>| match self
+            .formula_code(sheet_pos)
+        {
+            Some(Some(_)) if formulas.insert(sheet_pos) => {
+                queue.push_back(self.code_output_sheet_rect(sheet_pos));
+            }
+            Some(None) => {
+                stale.insert(sheet_pos);
+            }
+            _ => (),
+        };
+
+        for sheet_pos in cells {
+            add(*sheet_pos, &mut queue);
+        }
+        while let Some(rect) = queue.pop_front() {
+            for sheet_pos in self.get_dependent_code_cells(&rect).unwrap_or_default() {
+                add(sheet_pos, &mut queue);
+            }
+        }
+
+        formulas
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::cell_values::CellValues;
+    use crate::grid::SheetId;
+    use crate::wasm_bindings::js::{clear_js_calls, expect_js_call_count};
+
+    fn set_values(sheet_id: SheetId, pos: Pos, values: Vec<Vec<&str>>) -> Operation {
+        Operation::SetCellValues {
+            sheet_pos: pos.to_sheet_pos(sheet_id),
+            values: CellValues::from(values),
+        }
+    }
+
+    fn code(language: CodeCellLanguage, code: &str) -> CellValue {
+        CellValue::Code(crate::grid::CodeCellValue {
+            language,
+            code: code.into(),
+        })
+    }
+
+    #[test]
+    fn recalculates_dependent_formulas() {
+        let mut client = GridController::test();
+        let sheet_id = client.sheet_ids()[0];
+        client.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".into(), None);
+        client.set_code_cell(
+            pos![B1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "A1 * 2".into(),
+            None,
+        );
+        client.set_code_cell(
+            pos![C1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "B1 + A1".into(),
+            None,
+        );
+        let mut server = GridController::from_grid(client.grid().clone(), 0);
+
+        // the client changed A1 but did not send the recalculated formulas
+        let stale = server.server_apply_transaction_and_recalculate(
+            vec![set_values(sheet_id, pos![A1], vec![vec!["5"]])],
+            None,
+        );
+        assert!(stale.is_empty());
+
+        let sheet = server.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(pos![B1]),
+            Some(CellValue::Number(10.into()))
+        );
+        assert_eq!(
+            sheet.display_value(pos![C1]),
+            Some(CellValue::Number(15.into()))
+        );
+    }
+
+    #[test]
+    fn recalculates_new_formulas_without_results() {
+        let mut server = GridController::test();
+        let sheet_id = server.sheet_ids()[0];
+
+        let mut values = CellValues::new(2, 1);
+        values.set(0, 0, CellValue::Number(3.into()));
+        values.set(1, 0, code(CodeCellLanguage::Formula, "A1 + 1"));
+        let stale = server.server_apply_transaction_and_recalculate(
+            vec![Operation::SetCellValues {
+                sheet_pos: pos![A1].to_sheet_pos(sheet_id),
+                values,
+            }],
+            None,
+        );
+        assert!(stale.is_empty());
+        assert_eq!(
+            server.sheet(sheet_id).display_value(pos![B1]),
+            Some(CellValue::Number(4.into()))
+        );
+    }
+
+    #[test]
+    fn returns_stale_code_cells() {
+        let mut server = GridController::test();
+        let sheet_id = server.sheet_ids()[0];
+
+        let mut values = CellValues::new(3, 1);
+        values.set(0, 0, CellValue::Number(3.into()));
+        values.set(1, 0, code(CodeCellLanguage::Python, "q.cells('A1')"));
+        values.set(2, 0, code(CodeCellLanguage::Formula, "A1 * 3"));
+        clear_js_calls();
+        let stale = server.server_apply_transaction_and_recalculate(
+            vec![Operation::SetCellValues {
+                sheet_pos: pos![A1].to_sheet_pos(sheet_id),
+                values,
+            }],
+            None,
+        );
+
+        // python is not run on the server
+        assert_eq!(stale, vec![pos![B1].to_sheet_pos(sheet_id)]);
+        expect_js_call_count("jsRunPython", 0, true);
+        assert_eq!(server.sheet(sheet_id).code_run(pos![B1]), None);
+        assert_eq!(
+            server.sheet(sheet_id).display_value(pos![C1]),
+            Some(CellValue::Number(9.into()))
+        );
+    }
+
+    #[test]
+    fn orders_formulas_by_dependency() {
+        let mut client = GridController::test();
+        let sheet_id = client.sheet_ids()[0];
+        client.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".into(), None);
+
+        // C1 reads B1, which is later in the order they were added
+        client.set_code_cell(
+            pos![D1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "C1 + 1".into(),
+            None,
+        );
+        client.set_code_cell(
+            pos![C1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "B1 + 1".into(),
+            None,
+        );
+        client.set_code_cell(
+            pos![B1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "A1 + 1".into(),
+            None,
+        );
+        let mut server = GridController::from_grid(client.grid().clone(), 0);
+
+        let formulas = [pos![B1], pos![C1], pos![D1]]
+            .into_iter()
+            .map(|pos| pos.to_sheet_pos(sheet_id))
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            server.compute_order(&formulas),
+            vec![
+                pos![B1].to_sheet_pos(sheet_id),
+                pos![C1].to_sheet_pos(sheet_id),
+                pos![D1].to_sheet_pos(sheet_id),
+            ]
+        );
+
+        server.server_apply_transaction_and_recalculate(
+            vec![set_values(sheet_id, pos![A1], vec![vec!["10"]])],
+            None,
+        );
+        let sheet = server.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(pos![D1]),
+            Some(CellValue::Number(13.into()))
+        );
+    }
+
+    #[test]
+    fn recalculates_formulas_after_structural_changes() {
+        let mut client = GridController::test();
+        let sheet_id = client.sheet_ids()[0];
+        client.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "2".into(), None);
+        client.set_code_cell(
+            pos![C1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "A1 + B1".into(),
+            None,
+        );
+        let mut server = GridController::from_grid(client.grid().clone(), 0);
+
+        // a value moves into B1
+        server.server_apply_transaction_and_recalculate(
+            vec![
+                set_values(sheet_id, pos![E1], vec![vec!["3"]]),
+                Operation::MoveCells {
+                    source: SheetRect::single_pos(pos![E1], sheet_id),
+                    dest: pos![B1].to_sheet_pos(sheet_id),
+                },
+            ],
+            None,
+        );
+        assert_eq!(
+            server.sheet(sheet_id).display_value(pos![C1]),
+            Some(CellValue::Number(5.into()))
+        );
+    }
+}
@@ -7,9 +7,11 @@ use super::TransactionSource;
 use crate::controller::active_transactions::pending_transaction::PendingTransaction;
 use crate::controller::active_transactions::transaction_name::TransactionName;
 use crate::controller::active_transactions::unsaved_transactions::UnsavedTransaction;
+use crate::controller::dependency_index::DependencyIndex;
 use crate::controller::operations::operation::Operation;
 use crate::controller::transaction::{Transaction, TransactionServer};
 use crate::controller::GridController;
+use crate::grid::Grid;
 
 // seconds to wait before requesting wait_for_transactions
 const SECONDS_TO_WAIT_FOR_GET_TRANSACTIONS: i64 = 5;
@@ -49,7 +51,7 @@ impl GridController {
             ..Default::default()
         };
         self.start_transaction(&mut rollback);
-        rollback.send_transaction();
+        rollback.send_transaction(&*self.host);
         transaction.add_updates_from_transaction(rollback);
     }
 
@@ -74,7 +76,7 @@ impl GridController {
             ..Default::default()
         };
         self.start_transaction(&mut reapply);
-        reapply.send_transaction();
+        reapply.send_transaction(&*self.host);
         transaction.add_updates_from_transaction(reapply);
     }
 
@@ -112,14 +114,13 @@ impl GridController {
             } {
                 self.transactions.last_get_transactions_time = None;
 
-                if cfg!(target_family = "wasm") {
-                    crate::wasm_bindings::js::jsRequestTransactions(
-                        self.transactions.last_sequence_num + 1,
-                    );
+                if self.host.renders() {
+                    self.host
+                        .request_transactions(self.transactions.last_sequence_num + 1);
                 }
             }
-        } else if cfg!(target_family = "wasm") || cfg!(test) {
-            crate::wasm_bindings::js::jsMultiplayerSynced();
+        } else if self.host.renders() {
+            self.host.multiplayer_synced();
         }
     }
 
@@ -159,7 +160,7 @@ impl GridController {
             ..Default::default()
         };
         self.start_transaction(&mut out_of_order_transaction);
-        out_of_order_transaction.send_transaction();
+        out_of_order_transaction.send_transaction(&*self.host);
         transaction.add_updates_from_transaction(out_of_order_transaction);
         self.transactions.last_sequence_num = sequence_num;
     }
@@ -262,6 +263,118 @@ impl GridController {
         self.finalize_transaction(results);
     }
 
+    /// Replaces the grid with a checkpoint from the server and applies the
+    /// transactions after it. The server sends a catch up when it no longer
+    /// has every transaction since our last_sequence_num. Unsaved transactions
+    /// are reapplied on top of the checkpoint.
+    pub fn received_catch_up(
+        &mut self,
+        grid: Grid,
+        checkpoint_sequence_num: u64,
+        transactions: Vec<TransactionServer>,
+    ) {
+        if checkpoint_sequence_num > self.transactions.last_sequence_num {
+            let mut transaction = PendingTransaction {
+                source: TransactionSource::Multiplayer,
+                ..Default::default()
+            };
+            let old_grid = std::mem::replace(&mut self.grid, grid);
+            self.dependencies = DependencyIndex::new(&self.grid);
+            self.calculate_conditional_formats(|_, _| true);
+            self.transactions.last_sequence_num = checkpoint_sequence_num;
+            self.transactions
+                .out_of_order_transactions
+                .retain(|t| t.sequence_num.is_some_and(|s| s > checkpoint_sequence_num));
+
+            // the undo and redo stacks were built against the replaced grid
+            self.undo_stack.clear();
+            self.redo_stack.clear();
+
+            self.add_replaced_sheets(&old_grid, &mut transaction);
+            self.rebase_unsaved_transactions(&mut transaction);
+            self.finalize_transaction(transaction);
+        }
+        self.received_transactions(transactions);
+    }
+
+    /// The server rejected one of our transactions, e.g. because its
+    /// operations are invalid. Its changes are rolled back, and it's removed
+    /// from the unsaved transactions and the undo and redo stacks.
+    pub fn received_rejected_transaction(&mut self, transaction_id: Uuid) {
+        let Some(index) = self
+            .transactions
+            .unsaved_transactions
+            .find_index(transaction_id)
+        else {
+            return;
+        };
+        let mut transaction = PendingTransaction {
+            source: TransactionSource::Multiplayer,
+            ..Default::default()
+        };
+        self.rollback_unsaved_transactions(&mut transaction);
+        self.transactions.unsaved_transactions.remove(index);
+        self.reapply_unsaved_transactions(&mut transaction);
+        self.undo_stack.retain(|undo| undo.id != transaction_id);
+        self.redo_stack.retain(|redo| redo.id != transaction_id);
+        self.finalize_transaction(transaction);
+    }
+
+    /// Sends the client everything that may have changed when `old_grid` was
+    /// replaced by a checkpoint.
+    fn add_replaced_sheets(&self, old_grid: &Grid, transaction: &mut PendingTransaction) {
+        for old_sheet in old_grid.sheets() {
+            if self.try_sheet(old_sheet.id).is_none() {
+                self.send_delete_sheet(old_sheet.id, transaction);
+            }
+        }
+        for sheet in self.grid.sheets() {
+            let sheet_id = sheet.id;
+            let old_sheet = old_grid.try_sheet(sheet_id);
+            if old_sheet.is_none() {
+                self.send_add_sheet(sheet_id, transaction);
+            } else {
+                transaction.sheet_info.insert(sheet_id);
+            }
+            for s in old_sheet.into_iter().chain(std::iter::once(sheet)) {
+                if let Some(bounds) = Option::<crate::Rect>::from(s.bounds(false)) {
+                    transaction.add_dirty_hashes_from_sheet_rect(bounds.to_sheet_rect(sheet_id));
+                }
+                for (pos, code_run) in s.code_runs.iter() {
+                    transaction.add_code_cell(sheet_id, *pos);
+                    if code_run.is_html() {
+                        transaction.add_html_cell(sheet_id, *pos);
+                    }
+                    if code_run.is_image() {
+                        transaction.add_image_cell(sheet_id, *pos);
+                    }
+                }
+            }
+            transaction.fill_cells.insert(sheet_id);
+            transaction.sheet_borders.insert(sheet_id);
+            transaction.validations.insert(sheet_id);
+        }
+    }
+
+    /// Reapplies the unsaved transactions after the grid was replaced, so
+    /// their reverse operations roll back against the new grid.
+    fn rebase_unsaved_transactions(&mut self, transaction: &mut PendingTransaction) {
+        for index in 0..self.transactions.unsaved_transactions.len() {
+            let forward = &self.transactions.unsaved_transactions[index].forward;
+            // replayed like a redo: operations are applied as recorded and
+            // their reverse operations are collected
+            let mut rebase = PendingTransaction {
+                id: forward.id,
+                source: TransactionSource::Redo,
+                operations: forward.operations.clone().into(),
+                ..Default::default()
+            };
+            self.start_transaction(&mut rebase);
+            self.transactions.unsaved_transactions[index].reverse = rebase.to_undo_transaction();
+            transaction.add_updates_from_transaction(rebase);
+        }
+    }
+
     /// Called by TS for each offline transaction it has in its offline queue.
     pub fn apply_offline_unsaved_transaction(
         &mut self,
@@ -271,15 +384,18 @@ impl GridController {
         // first check if we've already applied this transaction
         if let Some(transaction) = self.transactions.unsaved_transactions.find(transaction_id) {
             // send it to the server if we've not successfully sent it to the server
-            if cfg!(target_family = "wasm") && !transaction.sent_to_server {
+            if self.host.renders() && !transaction.sent_to_server {
                 let compressed_ops =
                     Transaction::serialize_and_compress(&unsaved_transaction.forward.operations);
 
                 if let Ok(compressed_ops) = compressed_ops {
-                    crate::wasm_bindings::js::jsSendTransaction(
-                        transaction_id.to_string(),
-                        compressed_ops,
-                    );
+                    if Operation::all_comments(&unsaved_transaction.forward.operations) {
+                        self.host
+                            .send_comment(transaction_id.to_string(), compressed_ops);
+                    } else {
+                        self.host
+                            .send_transaction(transaction_id.to_string(), compressed_ops);
+                    }
                 } else {
                     dbgjs!("Unable to serialize and compress operations in apply_offline_unsaved_transaction()");
                 }
@@ -410,6 +526,38 @@ mod tests {
         );
     }
 
+    #[test]
+    #[parallel]
+    fn test_received_rejected_transaction() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 1), "first".to_string(), None);
+        let rejected_id = gc.last_transaction().unwrap().id;
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 2), "second".to_string(), None);
+        assert_eq!(gc.transactions.unsaved_transactions.len(), 2);
+
+        // the rejected transaction is rolled back, and later ones are kept
+        gc.received_rejected_transaction(rejected_id);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.display_value(Pos { x: 1, y: 1 }), None);
+        assert_eq!(
+            sheet.display_value(Pos { x: 1, y: 2 }),
+            Some(CellValue::Text("second".to_string()))
+        );
+        assert_eq!(gc.transactions.unsaved_transactions.len(), 1);
+        assert_eq!(gc.undo_stack.len(), 1);
+
+        // undo only reverts the remaining transaction
+        gc.undo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.display_value(Pos { x: 1, y: 1 }), None);
+        assert_eq!(sheet.display_value(Pos { x: 1, y: 2 }), None);
+
+        // unknown transactions are ignored
+        gc.received_rejected_transaction(Uuid::new_v4());
+        assert_eq!(gc.redo_stack.len(), 1);
+    }
+
     #[test]
     #[parallel]
     fn test_server_apply_transaction() {
@@ -1172,4 +1320,73 @@ mod tests {
             Some(CellValue::Number(BigDecimal::from(3)))
         );
     }
+
+    #[test]
+    #[parallel]
+    fn test_received_catch_up() {
+        let mut client = GridController::test();
+        let sheet_id = client.sheet_ids()[0];
+        let mut other = client.clone();
+
+        // an unsaved change the server has not seen yet
+        client.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "unsaved".to_string(), None);
+        let unsaved = client.transactions.unsaved_transactions[0].forward.clone();
+
+        // the checkpoint includes the other user's first change
+        other.set_cell_value(
+            pos![A1].to_sheet_pos(sheet_id),
+            "checkpoint".to_string(),
+            None,
+        );
+        let checkpoint = other.grid().clone();
+
+        // the second change comes after the checkpoint
+        other.set_cell_value(pos![A2].to_sheet_pos(sheet_id), "after".to_string(), None);
+        let operations = other.last_transaction().unwrap().operations.clone();
+
+        client.received_catch_up(
+            checkpoint,
+            5,
+            vec![TransactionServer {
+                file_id: Uuid::new_v4(),
+                id: Uuid::new_v4(),
+                sequence_num: 6,
+                operations: Transaction::serialize_and_compress(&operations).unwrap(),
+            }],
+        );
+        assert_eq!(client.transactions.last_sequence_num, 6);
+        assert_eq!(client.transactions.unsaved_transactions.len(), 1);
+        assert!(client.undo_stack.is_empty());
+        let sheet = client.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(pos![A1]),
+            Some(CellValue::Text("unsaved".to_string()))
+        );
+        assert_eq!(
+            sheet.display_value(pos![A2]),
+            Some(CellValue::Text("after".to_string()))
+        );
+
+        // the unsaved transaction now rolls back to the checkpoint
+        let mut transaction = PendingTransaction::default();
+        client.rollback_unsaved_transactions(&mut transaction);
+        assert_eq!(
+            client.sheet(sheet_id).display_value(pos![A1]),
+            Some(CellValue::Text("checkpoint".to_string()))
+        );
+        client.reapply_unsaved_transactions(&mut transaction);
+
+        // the server acknowledges the unsaved transaction
+        client.received_transaction(unsaved.id, 7, unsaved.operations);
+        assert_eq!(client.transactions.last_sequence_num, 7);
+        assert!(client.transactions.unsaved_transactions.is_empty());
+
+        // an older checkpoint is ignored
+        client.received_catch_up(Grid::new(), 3, vec![]);
+        assert_eq!(client.transactions.last_sequence_num, 7);
+        assert_eq!(
+            client.sheet(sheet_id).display_value(pos![A1]),
+            Some(CellValue::Text("unsaved".to_string()))
+        );
+    }
 }
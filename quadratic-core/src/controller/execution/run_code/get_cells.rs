@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::{
-    controller::GridController, error_core::CoreError, CellRefRange, RunError, RunErrorMsg,
+    controller::GridController, error_core::CoreError, grid::Sheet, CellRefRange, Rect, RunError,
+    RunErrorMsg,
 };
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +35,73 @@ impl GridController {
         a1: String,
         line_number: Option<u32>,
     ) -> Result<CellA1Response, CoreError> {
+        self.calculation_get_cells(
+            transaction_id,
+            a1,
+            line_number,
+            |sheet, range| match range {
+                Some((rect, two_dimensional)) => CellA1Response {
+                    cells: sheet.get_cells_response(rect),
+                    x: rect.min.x,
+                    y: rect.min.y,
+                    w: rect.width() as i64,
+                    h: rect.height() as i64,
+                    two_dimensional,
+                },
+                None => CellA1Response {
+                    cells: vec![],
+                    x: 1,
+                    y: 1,
+                    w: 0,
+                    h: 0,
+                    two_dimensional: false,
+                },
+            },
+        )
+    }
+
+    /// Gets cells during an async calculation as an Arrow IPC stream with
+    /// typed values (see [`crate::arrow`]), one column per sheet column. The
+    /// schema metadata has the `x`, `y`, `w`, `h` and `two_dimensional` of
+    /// [`CellA1Response`].
+    pub fn calculation_get_cells_a1_arrow(
+        &mut self,
+        transaction_id: String,
+        a1: String,
+        line_number: Option<u32>,
+    ) -> Result<Vec<u8>, CoreError> {
+        self.calculation_get_cells(transaction_id, a1, line_number, |sheet, range| {
+            let (x, y, w, h, two_dimensional) = match range {
+                Some((rect, two_dimensional)) => (
+                    rect.min.x,
+                    rect.min.y,
+                    rect.width(),
+                    rect.height(),
+                    two_dimensional,
+                ),
+                None => (1, 1, 0, 0, false),
+            };
+            let metadata = HashMap::from([
+                ("x".to_string(), x.to_string()),
+                ("y".to_string(), y.to_string()),
+                ("w".to_string(), w.to_string()),
+                ("h".to_string(), h.to_string()),
+                ("two_dimensional".to_string(), two_dimensional.to_string()),
+            ]);
+            sheet.get_cells_arrow(range.map(|(rect, _)| rect), metadata)
+        })?
+        .map_err(|e| CoreError::Serialization(e.to_string()))
+    }
+
+    /// Finds the cells of `a1` for an async calculation and returns
+    /// `response` for them, with whether the range is two-dimensional.
+    fn calculation_get_cells<T>(
+        &mut self,
+        transaction_id: String,
+        a1: String,
+        line_number: Option<u32>,
+        response: impl FnOnce(&Sheet, Option<(Rect, bool)>) -> T,
+    ) -> Result<T, CoreError> {
         let transaction_id = Uuid::parse_str(&transaction_id)
             .map_err(|_| CoreError::TransactionNotFound("Transaction Id is invalid".into()))?;
 
@@ -131,7 +201,7 @@ impl GridController {
             transaction.cells_accessed.add(sheet.id, *range);
         });
 
-        let response = if let Some(rect) = rects.first() {
+        let range = rects.first().map(|rect| {
             // Tracks whether to force the get_cells call to return a 2D array.
             // The use case is where the rect is currently one-dimensional, but
             // the selection may change to two-dimensional based on data bounds.
@@ -147,26 +217,9 @@ impl GridController {
             } else {
                 false
             };
-
-            let cells = sheet.get_cells_response(*rect);
-            CellA1Response {
-                cells,
-                x: rect.min.x,
-                y: rect.min.y,
-                w: rect.width() as i64,
-                h: rect.height() as i64,
-                two_dimensional,
-            }
-        } else {
-            CellA1Response {
-                cells: vec![],
-                x: 1,
-                y: 1,
-                w: 0,
-                h: 0,
-                two_dimensional: false,
-            }
-        };
+            (*rect, two_dimensional)
+        });
+        let response = response(sheet, range);
 
         self.transactions.add_async_transaction(&mut transaction);
 
@@ -178,7 +231,7 @@ impl GridController {
 #[serial_test::parallel]
 mod test {
     use super::*;
-    use crate::{grid::CodeCellLanguage, Pos, Rect, SheetPos};
+    use crate::{grid::CodeCellLanguage, CellValue, Pos, Rect, SheetPos};
 
     #[test]
     fn test_calculation_get_cells_bad_transaction_id() {
@@ -541,4 +594,55 @@ mod test {
             .unwrap();
         assert!(result.two_dimensional);
     }
+
+    #[test]
+    fn test_calculation_get_cells_a1_arrow() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 1), "name".to_string(), None);
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 2), "0.1".to_string(), None);
+        gc.set_cell_value(
+            SheetPos::new(sheet_id, 2, 1),
+            "2024-01-15".to_string(),
+            None,
+        );
+        gc.set_cell_value(SheetPos::new(sheet_id, 2, 2), "true".to_string(), None);
+        gc.set_code_cell(
+            SheetPos::new(sheet_id, 3, 1),
+            CodeCellLanguage::Python,
+            "".to_string(),
+            None,
+        );
+        let transaction_id = gc.last_transaction().unwrap().id;
+
+        let bytes = gc
+            .calculation_get_cells_a1_arrow(transaction_id.to_string(), "A1:B2".to_string(), None)
+            .unwrap();
+        let (columns, metadata) = crate::arrow::arrow_ipc_to_cell_values(&bytes).unwrap();
+        assert_eq!(metadata["x"], "1");
+        assert_eq!(metadata["w"], "2");
+        assert_eq!(metadata["two_dimensional"], "true");
+        assert_eq!(
+            columns,
+            vec![
+                (
+                    "A".to_string(),
+                    vec![CellValue::Text("name".into()), CellValue::from(0.1)]
+                ),
+                (
+                    "B".to_string(),
+                    vec![
+                        CellValue::Date(chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()),
+                        CellValue::Logical(true)
+                    ]
+                ),
+            ]
+        );
+
+        // the transaction is still waiting for the result
+        assert!(gc
+            .calculation_get_cells_a1_arrow(transaction_id.to_string(), "A1".to_string(), None)
+            .is_ok());
+    }
 }
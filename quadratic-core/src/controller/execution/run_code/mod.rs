@@ -20,10 +20,13 @@ impl GridController {
         &mut self,
         transaction: &mut PendingTransaction,
         sheet_pos: SheetPos,
-        new_code_run: Option<CodeRun>,
+        mut new_code_run: Option<CodeRun>,
         index: Option<usize>,
     ) {
         let sheet_id = sheet_pos.sheet_id;
+        let was_indexed = self
+            .try_sheet(sheet_id)
+            .is_some_and(|sheet| self.dependencies.is_indexed(sheet));
         let Some(sheet) = self.try_sheet_mut(sheet_id) else {
             // sheet may have been deleted
             return;
@@ -39,6 +42,14 @@ impl GridController {
                 .unwrap_or(sheet.code_runs.len()),
         );
 
+        // the refresh policy belongs to the code cell, so it's kept when the
+        // code cell runs again
+        if let (Some(new_code_run), Some(old_code_run)) =
+            (new_code_run.as_mut(), sheet.code_runs.get(&pos))
+        {
+            new_code_run.refresh = old_code_run.refresh;
+        }
+
         let old_code_run = if let Some(new_code_run) = &new_code_run {
             let (old_index, old_code_run) = sheet.code_runs.insert_full(pos, new_code_run.clone());
             // keep the orderings of the code runs consistent, particularly when undoing/redoing
@@ -52,6 +63,9 @@ impl GridController {
         } else {
             sheet.code_runs.shift_remove(&pos)
         };
+        sheet.code_runs_changed();
+
+        self.update_dependencies(sheet_pos, was_indexed);
 
         if old_code_run == new_code_run {
             return;
@@ -77,7 +91,7 @@ impl GridController {
             }
         };
 
-        if (cfg!(target_family = "wasm") || cfg!(test)) && !transaction.is_server() {
+        if self.host.renders() && !transaction.is_server() {
             transaction.add_from_code_run(sheet_id, pos, &old_code_run);
             transaction.add_from_code_run(sheet_id, pos, &new_code_run);
 
@@ -94,6 +108,13 @@ impl GridController {
             }
         }
 
+        let cells_accessed_changed = old_code_run
+            .as_ref()
+            .map(|code_run| &code_run.cells_accessed)
+            != new_code_run
+                .as_ref()
+                .map(|code_run| &code_run.cells_accessed);
+
         if transaction.is_user_undo_redo() {
             transaction
                 .forward_operations
@@ -114,7 +135,11 @@ impl GridController {
                 });
 
             if transaction.is_user() {
-                self.add_compute_operations(transaction, &sheet_rect, Some(sheet_pos));
+                // a code cell that reads different cells may have closed a
+                // cycle
+                if !(cells_accessed_changed && self.check_code_cell_cycle(transaction, sheet_pos)) {
+                    self.add_compute_operations(transaction, &sheet_rect, Some(sheet_pos));
+                }
                 self.check_all_spills(transaction, sheet_pos.sheet_id, true);
             }
         }
@@ -168,6 +193,29 @@ impl GridController {
         Ok(())
     }
 
+    /// Handles a code cell the host can't run. A cell that already ran keeps
+    /// its last output (eg, a Python cell that depends on a recomputed
+    /// formula in a native host); a new cell finishes with an error.
+    pub(super) fn code_cell_unavailable(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        sheet_pos: SheetPos,
+        language: &str,
+    ) {
+        if self
+            .try_sheet(sheet_pos.sheet_id)
+            .is_some_and(|sheet| sheet.code_run(sheet_pos.into()).is_some())
+        {
+            return;
+        }
+        let error = RunError {
+            span: None,
+            msg: RunErrorMsg::CodeRunError(format!("{language} is not available").into()),
+        };
+        transaction.current_sheet_pos = Some(sheet_pos);
+        let _ = self.code_cell_sheet_error(transaction, &error);
+    }
+
     pub(super) fn code_cell_sheet_error(
         &mut self,
         transaction: &mut PendingTransaction,
@@ -213,6 +261,8 @@ impl GridController {
                     std_err: Some(error.msg.to_string()),
                     spill_error: false,
                     last_modified: Utc::now(),
+                    refresh: old_code_run.refresh,
+                    volatile: old_code_run.volatile,
 
                     // keep the old cells_accessed to better rerun after an error
                     cells_accessed: old_code_run.cells_accessed.clone(),
@@ -231,6 +281,8 @@ impl GridController {
                 spill_error: false,
                 last_modified: Utc::now(),
                 cells_accessed: transaction.cells_accessed.clone(),
+                refresh: Default::default(),
+                volatile: false,
             },
         };
         transaction.cells_accessed.clear();
@@ -266,9 +318,21 @@ impl GridController {
                 spill_error: false,
                 last_modified: Utc::now(),
                 cells_accessed: transaction.cells_accessed.clone(),
+                refresh: Default::default(),
+                volatile: false,
             };
         };
-        let result = if js_code_result.success {
+        let success = js_code_result.success;
+        let result = if let Some(arrow) = js_code_result.output_arrow.filter(|_| success) {
+            match Array::from_arrow_ipc(&arrow) {
+                Ok(Some(array)) => CodeRunResult::Ok(Value::Array(array)),
+                Ok(None) => CodeRunResult::Ok(Value::Single(CellValue::Blank)),
+                Err(e) => CodeRunResult::Err(RunError {
+                    span: None,
+                    msg: RunErrorMsg::CodeRunError(format!("Invalid output: {e}").into()),
+                }),
+            }
+        } else if success {
             let result = if let Some(array_output) = js_code_result.output_array {
                 let (array, ops) = Array::from_string_list(start.into(), sheet, array_output);
                 transaction.reverse_operations.extend(ops);
@@ -321,6 +385,8 @@ impl GridController {
             spill_error: false,
             last_modified: Utc::now(),
             cells_accessed: transaction.cells_accessed.clone(),
+            refresh: Default::default(),
+            volatile: false,
         };
         transaction.cells_accessed.clear();
         code_run
@@ -372,6 +438,8 @@ mod test {
             last_modified: Utc::now(),
             cells_accessed: Default::default(),
             spill_error: false,
+            refresh: Default::default(),
+            volatile: false,
         };
         gc.finalize_code_run(transaction, sheet_pos, Some(new_code_run.clone()), None);
         assert_eq!(transaction.forward_operations.len(), 1);
@@ -401,6 +469,8 @@ mod test {
             last_modified: Utc::now(),
             cells_accessed: Default::default(),
             spill_error: false,
+            refresh: Default::default(),
+            volatile: false,
         };
         gc.finalize_code_run(transaction, sheet_pos, Some(new_code_run.clone()), None);
         assert_eq!(transaction.forward_operations.len(), 1);
@@ -456,8 +526,97 @@ mod test {
             output_array: None,
             output_display_type: None,
             cancel_compute: None,
+            output_arrow: None,
         };
         gc.calculation_complete(result).unwrap();
         expect_js_call_count("jsSendImage", 1, true);
     }
+
+    #[test]
+    #[parallel]
+    fn code_run_arrow_output() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let sheet_pos = SheetPos::new(sheet_id, 1, 1);
+        gc.set_code_cell(
+            sheet_pos,
+            CodeCellLanguage::Python,
+            "code".to_string(),
+            None,
+        );
+
+        let columns = vec![
+            (
+                "price".to_string(),
+                vec![CellValue::from(0.1), CellValue::Blank],
+            ),
+            (
+                "error".to_string(),
+                vec![
+                    CellValue::Text("ok".into()),
+                    CellValue::Error(Box::new(RunError::from(RunErrorMsg::DivideByZero))),
+                ],
+            ),
+        ];
+        let metadata = [(
+            crate::arrow::ARROW_HEADERS_METADATA.to_string(),
+            "true".to_string(),
+        )];
+        let output_arrow =
+            crate::arrow::cell_values_to_arrow_ipc(&columns, metadata.into()).unwrap();
+
+        let transaction = gc.last_transaction().unwrap();
+        let result = JsCodeResult {
+            transaction_id: transaction.id.to_string(),
+            success: true,
+            std_out: None,
+            std_err: None,
+            line_number: None,
+            output_value: None,
+            output_array: None,
+            output_display_type: Some("DataFrame".into()),
+            cancel_compute: None,
+            output_arrow: Some(output_arrow),
+        };
+        gc.calculation_complete(result).unwrap();
+
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value((1, 1).into()),
+            Some(CellValue::Text("price".into()))
+        );
+        assert_eq!(
+            sheet.display_value((1, 2).into()),
+            Some(CellValue::from(0.1))
+        );
+        assert_eq!(sheet.display_value((1, 3).into()), Some(CellValue::Blank));
+        assert!(matches!(
+            sheet.display_value((2, 3).into()),
+            Some(CellValue::Error(_))
+        ));
+
+        // invalid output is an error
+        gc.set_code_cell(
+            sheet_pos,
+            CodeCellLanguage::Python,
+            "code".to_string(),
+            None,
+        );
+        let transaction = gc.last_transaction().unwrap();
+        let result = JsCodeResult {
+            transaction_id: transaction.id.to_string(),
+            success: true,
+            std_out: None,
+            std_err: None,
+            line_number: None,
+            output_value: None,
+            output_array: None,
+            output_display_type: None,
+            cancel_compute: None,
+            output_arrow: Some(vec![1, 2, 3]),
+        };
+        gc.calculation_complete(result).unwrap();
+        let code_run = gc.sheet(sheet_id).code_run((1, 1).into()).unwrap();
+        assert!(matches!(code_run.result, CodeRunResult::Err(_)));
+    }
 }
@@ -78,18 +78,19 @@ impl GridController {
         id: String,
     ) {
         // send the request to get the sql data via the connector to the host
-        if (cfg!(target_family = "wasm") || cfg!(test)) && !transaction.is_server() {
+        if !transaction.is_server() {
             match self.replace_handlebars(transaction, sheet_pos, &code, sheet_pos.sheet_id) {
                 Ok(replaced_code) => {
-                    crate::wasm_bindings::js::jsConnection(
-                        transaction.id.to_string(),
-                        sheet_pos.x as i32,
-                        sheet_pos.y as i32,
-                        sheet_pos.sheet_id.to_string(),
+                    if !self.host.run_connection(
+                        transaction.id,
+                        sheet_pos,
                         replaced_code,
                         kind,
                         id.to_owned(),
-                    );
+                    ) {
+                        self.code_cell_unavailable(transaction, sheet_pos, "Connections");
+                        return;
+                    }
                 }
                 Err(msg) => {
                     let error = RunError {
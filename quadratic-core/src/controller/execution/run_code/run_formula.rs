@@ -3,7 +3,7 @@ use itertools::Itertools;
 
 use crate::{
     controller::{active_transactions::pending_transaction::PendingTransaction, GridController},
-    formulas::{parse_formula, Ctx},
+    formulas::Ctx,
     grid::{CodeRun, CodeRunResult},
     SheetPos,
 };
@@ -15,13 +15,14 @@ impl GridController {
         sheet_pos: SheetPos,
         code: String,
     ) {
+        let parsed = self.formula_cache.parse(&code, sheet_pos.into());
         let mut ctx = Ctx::new(self.grid(), sheet_pos);
         transaction.current_sheet_pos = Some(sheet_pos);
 
         if let Some(sheet) = self.grid().try_sheet(sheet_pos.sheet_id) {
             let bounds = sheet.bounds(true);
 
-            match parse_formula(&code, sheet_pos.into()) {
+            match parsed {
                 Ok(parsed) => {
                     let output = parsed.eval(&mut ctx, Some(bounds)).into_non_tuple();
                     let errors = output.inner.errors();
@@ -39,6 +40,8 @@ impl GridController {
                         return_type: None,
                         line_number: None,
                         output_type: None,
+                        refresh: Default::default(),
+                        volatile: parsed.is_volatile(),
                     };
                     transaction.cells_accessed.clear();
                     self.finalize_code_run(transaction, sheet_pos, Some(new_code_run), None);
@@ -262,6 +265,8 @@ mod test {
                 output_type: None,
                 cells_accessed: Default::default(),
                 spill_error: false,
+                refresh: Default::default(),
+                volatile: false,
             },
         );
     }
@@ -326,6 +331,8 @@ mod test {
                 cells_accessed: Default::default(),
                 spill_error: false,
                 last_modified: result.last_modified,
+                refresh: Default::default(),
+                volatile: false,
             }
         );
     }
@@ -448,4 +455,49 @@ mod test {
         assert!(!result.spill_error);
         assert!(result.std_err.is_some());
     }
+
+    #[test]
+    fn test_formula_cache() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_values(
+            SheetPos::new(sheet_id, 1, 1),
+            vec![vec!["1"], vec!["2"]],
+            None,
+        );
+
+        // filled-down formulas share a parsed formula
+        for y in 1..=2 {
+            gc.set_code_cell(
+                SheetPos::new(sheet_id, 2, y),
+                CodeCellLanguage::Formula,
+                format!("A{y} * 2"),
+                None,
+            );
+        }
+        assert_eq!(gc.formula_cache.len(), 1);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(Pos { x: 2, y: 2 }),
+            Some(CellValue::from(4))
+        );
+
+        // changing one cell's code keeps the formula the other cell uses
+        gc.set_code_cell(
+            SheetPos::new(sheet_id, 2, 1),
+            CodeCellLanguage::Formula,
+            "A1 * 3".into(),
+            None,
+        );
+        assert_eq!(gc.formula_cache.len(), 2);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.display_value(Pos { x: 2, y: 1 }),
+            Some(CellValue::from(3))
+        );
+        assert_eq!(
+            sheet.display_value(Pos { x: 2, y: 2 }),
+            Some(CellValue::from(4))
+        );
+    }
 }
@@ -11,14 +11,9 @@ impl GridController {
         sheet_pos: SheetPos,
         code: String,
     ) {
-        if (cfg!(target_family = "wasm") || cfg!(test)) && !transaction.is_server() {
-            crate::wasm_bindings::js::jsRunPython(
-                transaction.id.to_string(),
-                sheet_pos.x as i32,
-                sheet_pos.y as i32,
-                sheet_pos.sheet_id.to_string(),
-                code,
-            );
+        if !transaction.is_server() && !self.host.run_python(transaction.id, sheet_pos, code) {
+            self.code_cell_unavailable(transaction, sheet_pos, "Python");
+            return;
         }
         // stop the computation cycle until async returns
         transaction.current_sheet_pos = Some(sheet_pos);
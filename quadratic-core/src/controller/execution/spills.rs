@@ -38,10 +38,7 @@ impl GridController {
                         version: 1,
                     });
 
-                if (cfg!(target_family = "wasm") || cfg!(test))
-                    && !transaction.is_server()
-                    && send_client
-                {
+                if self.host.renders() && !transaction.is_server() && send_client {
                     transaction.add_from_code_run(sheet_id, *pos, &Some(run.to_owned()));
                     let sheet_rect = run.output_sheet_rect(sheet_pos, false);
                     transaction.add_dirty_hashes_from_sheet_rect(sheet_rect);
@@ -437,6 +434,8 @@ mod tests {
             last_modified: Utc::now(),
             cells_accessed: Default::default(),
             formatted_code_string: None,
+            refresh: Default::default(),
+            volatile: false,
         };
         let pos = Pos { x: 0, y: 0 };
         let sheet = gc.sheet_mut(sheet_id);
@@ -22,8 +22,13 @@ impl GridController {
             for x in bounds.min.x..=bounds.max.x {
                 // we need to ignore unselected columns or rows
                 if selection.might_contain_pos(Pos { x, y }) {
-                    if let Some((_, value)) = iter.peeking_next(|(pos, _)| pos.x == x && pos.y == y)
-                    {
+                    let value = iter.peeking_next(|(pos, _)| pos.x == x && pos.y == y);
+
+                    // hidden columns and rows are not exported
+                    if sheet.is_pos_hidden(Pos { x, y }) {
+                        continue;
+                    }
+                    if let Some((_, value)) = value {
                         line.push(value.to_string());
                     } else {
                         line.push("".to_string());
@@ -68,4 +73,25 @@ mod tests {
 
         assert_eq!(&result, expected);
     }
+
+    #[test]
+    fn exports_a_csv_hidden() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let vals = vec![
+            vec!["1", "2", "3"],
+            vec!["4", "5", "6"],
+            vec!["7", "8", "9"],
+        ];
+        let sheet = gc.sheet_mut(sheet_id);
+        sheet.set_cell_values(crate::Rect::new(1, 1, 3, 3), &Array::from(vals));
+        gc.hide_columns(sheet_id, vec![2], None);
+        gc.hide_rows(sheet_id, vec![2], None);
+
+        let result = gc
+            .export_csv_selection(&A1Selection::test_a1("A1:C3"))
+            .unwrap();
+        assert_eq!(&result, "1,3\n7,9\n");
+    }
 }
@@ -0,0 +1,666 @@
+//! The effects core has on the program that embeds it: render notifications,
+//! async code execution, connections and progress.
+//!
+//! In the browser the host is the core web worker, reached through the
+//! callbacks in `wasm_bindings::js`. A native embedding (e.g. a backend
+//! service that loads a `.grid`, sets inputs and reads outputs) uses
+//! [`NativeHost`] or its own [`Host`].
+
+use std::{fmt, sync::Arc};
+
+use uuid::Uuid;
+
+use super::active_transactions::transaction_name::TransactionName;
+use crate::{grid::ConnectionKind, viewport::ViewportBuffer, wasm_bindings::js, SheetPos};
+
+pub trait Host: Send + Sync {
+    /// Whether the host renders the grid. Render notifications are skipped
+    /// when false.
+    fn renders(&self) -> bool;
+
+    /// Starts running a Python code cell. Returns false if the host can't run
+    /// Python. Otherwise the transaction waits until the result is passed to
+    /// [`super::GridController::calculation_complete`].
+    fn run_python(&self, transaction_id: Uuid, sheet_pos: SheetPos, code: String) -> bool;
+
+    /// Starts running a Javascript code cell. Returns false if the host can't
+    /// run Javascript. Otherwise the transaction waits until the result is
+    /// passed to [`super::GridController::calculation_complete`].
+    fn run_javascript(&self, transaction_id: Uuid, sheet_pos: SheetPos, code: String) -> bool;
+
+    /// Starts running a connection query. Returns false if the host has no
+    /// connections. Otherwise the transaction waits until the result is passed
+    /// to [`super::GridController::connection_complete`].
+    fn run_connection(
+        &self,
+        transaction_id: Uuid,
+        sheet_pos: SheetPos,
+        query: String,
+        kind: ConnectionKind,
+        connection_id: String,
+    ) -> bool;
+
+    /// Called when a transaction starts running.
+    fn transaction_start(&self, _transaction_id: Uuid, _name: &TransactionName) {}
+
+    /// Called after each operation of a user transaction.
+    fn transaction_progress(&self, _transaction_id: Uuid, _remaining_operations: usize) {}
+
+    /// Called while a file is imported.
+    #[allow(clippy::too_many_arguments)]
+    fn import_progress(
+        &self,
+        _file_name: &str,
+        _current: u32,
+        _total: u32,
+        _x: i64,
+        _y: i64,
+        _w: u32,
+        _h: u32,
+    ) {
+    }
+
+    // Render notifications. Callers check [`Host::renders`] before building
+    // them, so the default implementations do nothing.
+
+    /// Shares the viewport buffer with the renderer.
+    fn send_viewport_buffer(&self, _viewport_buffer: &ViewportBuffer) {}
+
+    /// Render cells of a hash, as a JSON `Vec<JsRenderCell>`.
+    fn render_cells(&self, _sheet_id: String, _hash_x: i64, _hash_y: i64, _cells: String) {}
+
+    /// Info of all sheets when a file is loaded, as a JSON `Vec<SheetInfo>`.
+    fn sheet_info(&self, _sheets_info: String) {}
+
+    /// Html cells of all sheets when a file is loaded, as a JSON
+    /// `Vec<JsHtmlOutput>`.
+    fn html_output(&self, _html: String) {}
+
+    /// Code cells of a sheet when a file is loaded, as a JSON
+    /// `Vec<JsRenderCodeCell>`.
+    fn sheet_code_cells(&self, _sheet_id: String, _code_cells: String) {}
+
+    /// Hashes that changed outside of the viewport, as a JSON `Vec<Pos>`.
+    fn hashes_dirty(&self, _sheet_id: String, _hashes: String) {}
+
+    /// Fills of a sheet, as a JSON `Vec<JsRenderFill>`.
+    fn sheet_fills(&self, _sheet_id: String, _fills: String) {}
+
+    /// Column, row and sheet fills, as a JSON `Vec<JsSheetFill>`.
+    fn sheet_meta_fills(&self, _sheet_id: String, _fills: String) {}
+
+    /// Borders of a sheet, as a JSON `JsBordersSheet`.
+    fn borders_sheet(&self, _sheet_id: String, _borders: String) {}
+
+    /// A code cell changed, as a JSON `JsCodeCell` and `JsRenderCodeCell`
+    /// (None if it was removed).
+    fn update_code_cell(
+        &self,
+        _sheet_id: String,
+        _x: i64,
+        _y: i64,
+        _code_cell: Option<String>,
+        _render_code_cell: Option<String>,
+    ) {
+    }
+
+    /// An html cell changed, as a JSON `JsHtmlOutput`.
+    fn update_html(&self, _html: String) {}
+
+    /// An image cell changed (image is None if it was removed).
+    fn send_image(
+        &self,
+        _sheet_id: String,
+        _x: i32,
+        _y: i32,
+        _image: Option<String>,
+        _w: Option<String>,
+        _h: Option<String>,
+    ) {
+    }
+
+    /// Validations of a sheet, as a JSON `Vec<Validation>`.
+    fn sheet_validations(&self, _sheet_id: String, _validations: String) {}
+
+    /// Validation warnings that changed, as a JSON `Vec<JsValidationWarning>`.
+    fn validation_warning(&self, _sheet_id: String, _warnings: String) {}
+
+    /// Validation warnings of a hash, as a JSON `Vec<JsValidationWarning>`.
+    fn render_validation_warnings(
+        &self,
+        _sheet_id: String,
+        _hash_x: i64,
+        _hash_y: i64,
+        _warnings: String,
+    ) {
+    }
+
+    /// Bounds of a sheet changed, as a JSON `SheetBounds`.
+    fn sheet_bounds_update(&self, _bounds: String) {}
+
+    /// A sheet was added, as a JSON `SheetInfo`.
+    fn add_sheet(&self, _sheet_info: String, _user: bool) {}
+
+    /// A sheet was deleted.
+    fn delete_sheet(&self, _sheet_id: String, _user: bool) {}
+
+    /// A sheet's name, color or order changed, as a JSON `SheetInfo`.
+    fn sheet_info_update(&self, _sheet_info: String) {}
+
+    /// Column widths or row heights changed, as a JSON `Vec<JsOffset>`.
+    fn offsets_modified(&self, _sheet_id: String, _offsets: String) {}
+
+    /// Asks the renderer for the heights of rows with wrapped text, as a JSON
+    /// `Vec<i64>`. The transaction waits for the answer.
+    fn request_row_heights(&self, _transaction_id: String, _sheet_id: String, _rows: String) {}
+
+    // Client notifications
+
+    /// Whether undo and redo are available.
+    fn undo_redo(&self, _undo: bool, _redo: bool) {}
+
+    /// Moves the cursor after undo or redo, as a JSON `A1Selection`.
+    fn set_cursor(&self, _cursor: String) {}
+
+    /// Sets the selection from a SetCursorSelection operation, as a JSON
+    /// `A1Selection`.
+    fn set_cursor_selection(&self, _selection: String) {}
+
+    /// The thumbnail should be generated again.
+    fn generate_thumbnail(&self) {}
+
+    /// Shows a message to the user.
+    fn client_message(&self, _message: String, _error: bool) {}
+
+    // Multiplayer
+
+    /// Stores a transaction that hasn't been acknowledged by the server, as a
+    /// JSON `UnsavedTransaction`.
+    fn add_unsent_transaction(
+        &self,
+        _transaction_id: String,
+        _transaction: String,
+        _operations: u32,
+    ) {
+    }
+
+    /// Sends a transaction's compressed operations to the server.
+    fn send_transaction(&self, _transaction_id: String, _transaction: Vec<u8>) {}
+
+    /// Sends a transaction that only changes comments to the server.
+    fn send_comment(&self, _transaction_id: String, _transaction: Vec<u8>) {}
+
+    /// Asks the server for the transactions from `sequence_num`.
+    fn request_transactions(&self, _sequence_num: u64) {}
+
+    /// All transactions from the server have been applied.
+    fn multiplayer_synced(&self) {}
+}
+
+/// The browser host: effects are sent to the client through the
+/// `wasm_bindings::js` callbacks. Only used where [`Host::renders`] is true
+/// (in the browser and in tests, where the callbacks are recorded).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsHost;
+
+impl Host for JsHost {
+    fn renders(&self) -> bool {
+        cfg!(target_family = "wasm") || cfg!(test)
+    }
+
+    fn run_python(&self, transaction_id: Uuid, sheet_pos: SheetPos, code: String) -> bool {
+        if self.renders() {
+            crate::wasm_bindings::js::jsRunPython(
+                transaction_id.to_string(),
+                sheet_pos.x as i32,
+                sheet_pos.y as i32,
+                sheet_pos.sheet_id.to_string(),
+                code,
+            );
+        }
+        true
+    }
+
+    fn run_javascript(&self, transaction_id: Uuid, sheet_pos: SheetPos, code: String) -> bool {
+        if self.renders() {
+            crate::wasm_bindings::js::jsRunJavascript(
+                transaction_id.to_string(),
+                sheet_pos.x as i32,
+                sheet_pos.y as i32,
+                sheet_pos.sheet_id.to_string(),
+                code,
+            );
+        }
+        true
+    }
+
+    fn run_connection(
+        &self,
+        transaction_id: Uuid,
+        sheet_pos: SheetPos,
+        query: String,
+        kind: ConnectionKind,
+        connection_id: String,
+    ) -> bool {
+        if self.renders() {
+            crate::wasm_bindings::js::jsConnection(
+                transaction_id.to_string(),
+                sheet_pos.x as i32,
+                sheet_pos.y as i32,
+                sheet_pos.sheet_id.to_string(),
+                query,
+                kind,
+                connection_id,
+            );
+        }
+        true
+    }
+
+    fn transaction_start(&self, transaction_id: Uuid, name: &TransactionName) {
+        if cfg!(target_family = "wasm") {
+            let name = serde_json::to_string(name).unwrap_or("Unknown".to_string());
+            crate::wasm_bindings::js::jsTransactionStart(transaction_id.to_string(), name);
+        }
+    }
+
+    fn transaction_progress(&self, transaction_id: Uuid, remaining_operations: usize) {
+        if self.renders() {
+            crate::wasm_bindings::js::jsTransactionProgress(
+                transaction_id.to_string(),
+                remaining_operations as i32,
+            );
+        }
+    }
+
+    fn import_progress(
+        &self,
+        file_name: &str,
+        current: u32,
+        total: u32,
+        x: i64,
+        y: i64,
+        w: u32,
+        h: u32,
+    ) {
+        if self.renders() {
+            crate::wasm_bindings::js::jsImportProgress(file_name, current, total, x, y, w, h);
+        }
+    }
+
+    fn send_viewport_buffer(&self, viewport_buffer: &ViewportBuffer) {
+        js::jsSendViewportBuffer(viewport_buffer.get_buffer());
+    }
+
+    fn render_cells(&self, sheet_id: String, hash_x: i64, hash_y: i64, cells: String) {
+        js::jsRenderCellSheets(sheet_id, hash_x, hash_y, cells);
+    }
+
+    fn sheet_info(&self, sheets_info: String) {
+        js::jsSheetInfo(sheets_info);
+    }
+
+    fn html_output(&self, html: String) {
+        js::jsHtmlOutput(html);
+    }
+
+    fn sheet_code_cells(&self, sheet_id: String, code_cells: String) {
+        js::jsSheetCodeCell(sheet_id, code_cells);
+    }
+
+    fn hashes_dirty(&self, sheet_id: String, hashes: String) {
+        js::jsHashesDirty(sheet_id, hashes);
+    }
+
+    fn sheet_fills(&self, sheet_id: String, fills: String) {
+        js::jsSheetFills(sheet_id, fills);
+    }
+
+    fn sheet_meta_fills(&self, sheet_id: String, fills: String) {
+        js::jsSheetMetaFills(sheet_id, fills);
+    }
+
+    fn borders_sheet(&self, sheet_id: String, borders: String) {
+        js::jsBordersSheet(sheet_id, borders);
+    }
+
+    fn update_code_cell(
+        &self,
+        sheet_id: String,
+        x: i64,
+        y: i64,
+        code_cell: Option<String>,
+        render_code_cell: Option<String>,
+    ) {
+        js::jsUpdateCodeCell(sheet_id, x, y, code_cell, render_code_cell);
+    }
+
+    fn update_html(&self, html: String) {
+        js::jsUpdateHtml(html);
+    }
+
+    fn send_image(
+        &self,
+        sheet_id: String,
+        x: i32,
+        y: i32,
+        image: Option<String>,
+        w: Option<String>,
+        h: Option<String>,
+    ) {
+        js::jsSendImage(sheet_id, x, y, image, w, h);
+    }
+
+    fn sheet_validations(&self, sheet_id: String, validations: String) {
+        js::jsSheetValidations(sheet_id, validations);
+    }
+
+    fn validation_warning(&self, sheet_id: String, warnings: String) {
+        js::jsValidationWarning(sheet_id, warnings);
+    }
+
+    fn render_validation_warnings(
+        &self,
+        sheet_id: String,
+        hash_x: i64,
+        hash_y: i64,
+        warnings: String,
+    ) {
+        js::jsRenderValidationWarnings(sheet_id, hash_x, hash_y, warnings);
+    }
+
+    fn sheet_bounds_update(&self, bounds: String) {
+        js::jsSheetBoundsUpdate(bounds);
+    }
+
+    fn add_sheet(&self, sheet_info: String, user: bool) {
+        js::jsAddSheet(sheet_info, user);
+    }
+
+    fn delete_sheet(&self, sheet_id: String, user: bool) {
+        js::jsDeleteSheet(sheet_id, user);
+    }
+
+    fn sheet_info_update(&self, sheet_info: String) {
+        js::jsSheetInfoUpdate(sheet_info);
+    }
+
+    fn offsets_modified(&self, sheet_id: String, offsets: String) {
+        js::jsOffsetsModified(sheet_id, offsets);
+    }
+
+    fn request_row_heights(&self, transaction_id: String, sheet_id: String, rows: String) {
+        js::jsRequestRowHeights(transaction_id, sheet_id, rows);
+    }
+
+    fn undo_redo(&self, undo: bool, redo: bool) {
+        js::jsUndoRedo(undo, redo);
+    }
+
+    fn set_cursor(&self, cursor: String) {
+        js::jsSetCursor(cursor);
+    }
+
+    fn set_cursor_selection(&self, selection: String) {
+        js::jsSetCursorSelection(selection);
+    }
+
+    fn generate_thumbnail(&self) {
+        js::jsGenerateThumbnail();
+    }
+
+    fn client_message(&self, message: String, error: bool) {
+        js::jsClientMessage(message, error);
+    }
+
+    fn add_unsent_transaction(&self, transaction_id: String, transaction: String, operations: u32) {
+        js::addUnsentTransaction(transaction_id, transaction, operations);
+    }
+
+    fn send_transaction(&self, transaction_id: String, transaction: Vec<u8>) {
+        js::jsSendTransaction(transaction_id, transaction);
+    }
+
+    fn send_comment(&self, transaction_id: String, transaction: Vec<u8>) {
+        js::jsSendComment(transaction_id, transaction);
+    }
+
+    fn request_transactions(&self, sequence_num: u64) {
+        js::jsRequestTransactions(sequence_num);
+    }
+
+    fn multiplayer_synced(&self) {
+        js::jsMultiplayerSynced();
+    }
+}
+
+/// A host without a client: nothing is rendered, and Python, Javascript and
+/// connection cells keep their last output (new ones finish with an error).
+/// Formulas run as usual.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NativeHost;
+
+impl Host for NativeHost {
+    fn renders(&self) -> bool {
+        false
+    }
+
+    fn run_python(&self, _transaction_id: Uuid, _sheet_pos: SheetPos, _code: String) -> bool {
+        false
+    }
+
+    fn run_javascript(&self, _transaction_id: Uuid, _sheet_pos: SheetPos, _code: String) -> bool {
+        false
+    }
+
+    fn run_connection(
+        &self,
+        _transaction_id: Uuid,
+        _sheet_pos: SheetPos,
+        _query: String,
+        _kind: ConnectionKind,
+        _connection_id: String,
+    ) -> bool {
+        false
+    }
+}
+
+/// The host held by a [`super::GridController`]. The host isn't part of the
+/// grid, so it's ignored when controllers are compared.
+#[derive(Clone)]
+pub struct SharedHost(Arc<dyn Host>);
+
+impl SharedHost {
+    pub fn new(host: impl Host + 'static) -> Self {
+        Self(Arc::new(host))
+    }
+}
+
+impl Default for SharedHost {
+    fn default() -> Self {
+        Self::new(JsHost)
+    }
+}
+
+impl std::ops::Deref for SharedHost {
+    type Target = dyn Host;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for SharedHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedHost")
+    }
+}
+
+impl PartialEq for SharedHost {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{
+        controller::{transaction_types::JsCodeResult, GridController},
+        grid::{CellsAccessed, CodeCellLanguage, CodeCellValue, CodeRun, CodeRunResult, Grid},
+        CellValue, Pos, Value,
+    };
+    use chrono::Utc;
+
+    #[test]
+    fn test_native_host() {
+        let mut gc = GridController::from_grid_with_host(Grid::test(), 0, NativeHost);
+        let sheet_id = gc.sheet_ids()[0];
+        assert!(!gc.host().renders());
+
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "2".to_string(), None);
+        gc.set_code_cell(
+            pos![B1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "A1 * 10".to_string(),
+            None,
+        );
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "3".to_string(), None);
+        assert_eq!(
+            gc.sheet(sheet_id).display_value(pos![B1]),
+            Some(CellValue::Number(30.into()))
+        );
+
+        // the transaction doesn't wait for code the host can't run
+        gc.set_code_cell(
+            pos![C1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Python,
+            "1 + 1".to_string(),
+            None,
+        );
+        assert!(gc.async_transactions().is_empty());
+        let code_run = gc.sheet(sheet_id).code_run(pos![C1]).unwrap();
+        let CodeRunResult::Err(error) = &code_run.result else {
+            panic!("expected an error");
+        };
+        assert_eq!(error.msg.to_string(), "Python is not available");
+    }
+
+    #[test]
+    fn test_native_host_keeps_code_output() {
+        let mut gc = GridController::from_grid_with_host(Grid::test(), 0, NativeHost);
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".to_string(), None);
+
+        // a Python cell that read A1 when it last ran in the browser
+        let mut cells_accessed = CellsAccessed::default();
+        cells_accessed.add_sheet_pos(pos![A1].to_sheet_pos(sheet_id));
+        let sheet = gc.sheet_mut(sheet_id);
+        sheet.set_cell_value(
+            pos![B1],
+            CellValue::Code(CodeCellValue {
+                language: CodeCellLanguage::Python,
+                code: "q.cells('A1') * 2".to_string(),
+            }),
+        );
+        sheet.set_code_run(
+            pos![B1],
+            Some(CodeRun {
+                std_out: None,
+                std_err: None,
+                formatted_code_string: None,
+                last_modified: Utc::now(),
+                cells_accessed,
+                result: CodeRunResult::Ok(Value::Single(CellValue::Number(2.into()))),
+                return_type: Some("number".into()),
+                spill_error: false,
+                line_number: None,
+                output_type: None,
+                refresh: Default::default(),
+                volatile: false,
+            }),
+        );
+
+        // changing A1 doesn't replace the output with an error
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "5".to_string(), None);
+        let code_run = gc.sheet(sheet_id).code_run(pos![B1]).unwrap();
+        assert!(code_run.result.as_std_ref().is_ok());
+        assert_eq!(
+            gc.sheet(sheet_id).display_value(pos![B1]),
+            Some(CellValue::Number(2.into()))
+        );
+    }
+
+    #[derive(Default)]
+    struct TestHost {
+        python: Arc<Mutex<Vec<(Uuid, SheetPos)>>>,
+    }
+
+    impl Host for TestHost {
+        fn renders(&self) -> bool {
+            false
+        }
+
+        fn run_python(&self, transaction_id: Uuid, sheet_pos: SheetPos, _code: String) -> bool {
+            self.python
+                .lock()
+                .unwrap()
+                .push((transaction_id, sheet_pos));
+            true
+        }
+
+        fn run_javascript(&self, _: Uuid, _: SheetPos, _: String) -> bool {
+            false
+        }
+
+        fn run_connection(
+            &self,
+            _: Uuid,
+            _: SheetPos,
+            _: String,
+            _: ConnectionKind,
+            _: String,
+        ) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_host_runs_code() {
+        let host = TestHost::default();
+        let python = host.python.clone();
+        let mut gc = GridController::from_grid_with_host(Grid::test(), 0, host);
+        let sheet_id = gc.sheet_ids()[0];
+
+        let sheet_pos = pos![A1].to_sheet_pos(sheet_id);
+        gc.set_code_cell(
+            sheet_pos,
+            CodeCellLanguage::Python,
+            "1 + 1".to_string(),
+            None,
+        );
+        let (transaction_id, requested) = python.lock().unwrap()[0];
+        assert_eq!(requested, sheet_pos);
+        assert_eq!(gc.async_transactions().len(), 1);
+
+        gc.calculation_complete(JsCodeResult::new(
+            transaction_id.to_string(),
+            true,
+            None,
+            None,
+            Some(vec!["2".into(), "number".into()]),
+            None,
+            None,
+            None,
+            None,
+        ))
+        .unwrap();
+        assert!(gc.async_transactions().is_empty());
+        assert_eq!(
+            gc.sheet(sheet_id).display_value(Pos::from(sheet_pos)),
+            Some(CellValue::Number(2.into()))
+        );
+    }
+}
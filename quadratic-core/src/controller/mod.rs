@@ -1,11 +1,22 @@
-use self::{active_transactions::ActiveTransactions, transaction::Transaction};
-use crate::{grid::Grid, viewport::ViewportBuffer};
+use self::{
+    active_transactions::ActiveTransactions,
+    dependency_index::DependencyIndex,
+    host::{Host, SharedHost},
+    transaction::Transaction,
+};
+use crate::{
+    formulas::FormulaCache,
+    grid::{sheet::protections::ProtectionEditor, Grid},
+    viewport::ViewportBuffer,
+};
 use wasm_bindgen::prelude::*;
 pub mod active_transactions;
 pub mod dependencies;
+pub mod dependency_index;
 pub mod execution;
 pub mod export;
 pub mod formula;
+pub mod host;
 pub mod operations;
 pub mod send_render;
 pub mod sheet_offsets;
@@ -15,7 +26,7 @@ pub mod transaction;
 pub mod transaction_types;
 pub mod user_actions;
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone)]
 #[cfg_attr(feature = "js", wasm_bindgen)]
 pub struct GridController {
     grid: Grid,
@@ -28,23 +39,74 @@ pub struct GridController {
     // the viewport buffer is a shared array buffer that is accessed by the render web worker and the controller
     // contains current viewport position and sheet id, updated by render web worker on viewport change
     viewport_buffer: Option<ViewportBuffer>,
+
+    // the user making local changes, used to refuse changes to protected
+    // ranges; None skips the check
+    protection_editor: Option<ProtectionEditor>,
+
+    // reverse index from the cells read by code runs to their code cells
+    dependencies: DependencyIndex,
+
+    // the program embedding core: receives render notifications and runs
+    // async code cells
+    host: SharedHost,
+
+    // parsed formulas, shared by cells with the same relative formula
+    formula_cache: FormulaCache,
+}
+
+/// Compares everything except the formula cache, which doesn't change what
+/// formulas compute.
+impl PartialEq for GridController {
+    fn eq(&self, other: &Self) -> bool {
+        self.grid == other.grid
+            && self.undo_stack == other.undo_stack
+            && self.redo_stack == other.redo_stack
+            && self.transactions == other.transactions
+            && self.viewport_buffer == other.viewport_buffer
+            && self.protection_editor == other.protection_editor
+            && self.dependencies == other.dependencies
+            && self.host == other.host
+    }
 }
 
 impl GridController {
     pub fn from_grid(grid: Grid, last_sequence_num: u64) -> Self {
-        GridController {
+        let mut gc = GridController {
             grid,
             transactions: ActiveTransactions::new(last_sequence_num),
             ..Default::default()
-        }
+        };
+        gc.dependencies = DependencyIndex::new(&gc.grid);
+        gc.calculate_conditional_formats(|_, _| true);
+        gc
     }
 
     pub fn upgrade_grid(grid: Grid, last_sequence_num: u64) -> Self {
-        GridController {
+        let mut gc = GridController {
             grid,
             transactions: ActiveTransactions::new(last_sequence_num),
             ..Default::default()
-        }
+        };
+        gc.dependencies = DependencyIndex::new(&gc.grid);
+        gc.calculate_conditional_formats(|_, _| true);
+        gc
+    }
+
+    /// Creates a grid controller that sends its effects to `host` instead of
+    /// the browser client.
+    pub fn from_grid_with_host(
+        grid: Grid,
+        last_sequence_num: u64,
+        host: impl Host + 'static,
+    ) -> Self {
+        let mut gc = Self::from_grid(grid, last_sequence_num);
+        gc.host = SharedHost::new(host);
+        gc
+    }
+
+    pub fn host(&self) -> &dyn Host {
+        &*self.host
     }
 
     pub fn grid(&self) -> &Grid {
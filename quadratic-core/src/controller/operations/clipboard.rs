@@ -15,7 +15,7 @@ use crate::grid::js_types::JsClipboard;
 use crate::grid::sheet::borders::BordersUpdates;
 use crate::grid::sheet::validations::validation::Validation;
 use crate::grid::CodeCellLanguage;
-use crate::{A1Selection, CellValue, Pos, SheetPos, SheetRect};
+use crate::{A1Selection, CellValue, Pos, Rect, SheetPos, SheetRect};
 
 // todo: break up this file so tests are easier to write
 
@@ -75,6 +75,9 @@ pub struct Clipboard {
 
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub validations: Option<ClipboardValidations>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub merge_cells: Option<Vec<Rect>>,
 }
 
 impl GridController {
@@ -253,6 +256,15 @@ impl GridController {
                 clipboard.validations,
                 start_pos.to_sheet_pos(selection.sheet_id),
             ));
+
+            if let Some(merge_cells) = clipboard.merge_cells {
+                ops.extend(merge_cells.into_iter().map(|mut rect| {
+                    rect.translate(contiguous_2d_translate_x, contiguous_2d_translate_y);
+                    Operation::MergeCells {
+                        sheet_rect: rect.to_sheet_rect(selection.sheet_id),
+                    }
+                }));
+            }
         }
 
         ops
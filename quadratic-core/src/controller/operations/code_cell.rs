@@ -1,3 +1,5 @@
+use chrono::{DateTime, Utc};
+
 use super::operation::Operation;
 use crate::{
     cell_values::CellValues,
@@ -129,6 +131,34 @@ impl GridController {
     pub fn rerun_code_cell_operations(&self, sheet_pos: SheetPos) -> Vec<Operation> {
         vec![Operation::ComputeCode { sheet_pos }]
     }
+
+    /// Returns the code cells in all Sheets that should run again. `opened` is
+    /// true when the file was just opened. Only one client per file should
+    /// refresh code cells, otherwise each client reruns them.
+    pub fn code_cells_due_for_refresh(&self, now: DateTime<Utc>, opened: bool) -> Vec<SheetPos> {
+        self.grid()
+            .sheets()
+            .iter()
+            .flat_map(|sheet| {
+                sheet
+                    .code_runs
+                    .iter()
+                    .filter(|(_, code_run)| code_run.is_refresh_due(now, opened))
+                    .map(|(pos, _)| pos.to_sheet_pos(sheet.id))
+            })
+            .collect()
+    }
+
+    /// Reruns code cells (eg, the code cells due for refresh). The code cells
+    /// are ordered when the transaction starts.
+    pub fn refresh_code_cells_operations(&self, code_cells: &[SheetPos]) -> Vec<Operation> {
+        code_cells
+            .iter()
+            .map(|sheet_pos| Operation::ComputeCode {
+                sheet_pos: *sheet_pos,
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,313 @@
+//! Checks comment operations against the authors of the comments.
+//!
+//! Users may only write comments as themselves, and may only change or remove
+//! comments that they wrote unless they can edit the file. Like protected
+//! ranges, the multiplayer server checks this without loading the file, so
+//! only the author of each comment is kept.
+//!
+//! Authors are kept after their comment is removed so that undoing the removal
+//! is checked against the original author.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::operation::Operation;
+use crate::grid::{sheet::comments::Comment, Grid};
+
+/// Author of each comment thread and reply by id.
+pub type CommentAuthors = HashMap<Uuid, String>;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CommentError {
+    #[error("Operation {0} writes comment {1} as another user")]
+    Author(usize, Uuid),
+
+    #[error("Operation {0} changes comment {1} written by another user")]
+    NotAuthor(usize, Uuid),
+}
+
+/// Collects the authors of the comments in a grid.
+pub fn comment_authors(grid: &Grid) -> CommentAuthors {
+    let mut authors = CommentAuthors::new();
+    grid.sheets()
+        .iter()
+        .flat_map(|sheet| sheet.comments.iter())
+        .for_each(|thread| {
+            authors.insert(thread.id, thread.comment.author.clone());
+            add_authors(&thread.replies, &mut authors);
+        });
+    authors
+}
+
+fn add_authors(comments: &[Comment], authors: &mut CommentAuthors) {
+    comments.iter().for_each(|comment| {
+        authors.insert(comment.id, comment.author.clone());
+    });
+}
+
+/// Updates `authors` for an operation that has already been checked.
+fn apply_operation(operation: &Operation, authors: &mut CommentAuthors) {
+    match operation {
+        Operation::SetCommentThread { thread, .. } => {
+            authors.insert(thread.id, thread.comment.author.clone());
+            add_authors(&thread.replies, authors);
+        }
+        Operation::AddCommentReply { reply, .. } => {
+            authors.insert(reply.id, reply.author.clone());
+        }
+        _ => (),
+    }
+}
+
+/// Updates `authors` for a batch of operations without checking them.
+pub fn apply_comment_operations(operations: &[Operation], authors: &mut CommentAuthors) {
+    operations
+        .iter()
+        .for_each(|operation| apply_operation(operation, authors));
+}
+
+/// Checks that `user` may write comment `id` as `author` (or remove it if
+/// `author` is None).
+fn check_comment(
+    index: usize,
+    id: Uuid,
+    author: Option<&str>,
+    authors: &CommentAuthors,
+    user: &str,
+    can_edit: bool,
+) -> Result<(), CommentError> {
+    match authors.get(&id) {
+        Some(existing) => {
+            if existing != user && !can_edit {
+                return Err(CommentError::NotAuthor(index, id));
+            }
+            if author.is_some_and(|author| author != existing) {
+                return Err(CommentError::Author(index, id));
+            }
+        }
+        None => {
+            if author.is_some_and(|author| author != user) {
+                return Err(CommentError::Author(index, id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Validates the comment operations in a batch made by `user` (the author
+/// name used in comments). Users who can edit the file may change or remove
+/// any comment.
+///
+/// `authors` is updated as the batch adds comments, so callers should pass a
+/// copy and only keep it if validation succeeds.
+pub fn validate_comments(
+    operations: &[Operation],
+    authors: &mut CommentAuthors,
+    user: &str,
+    can_edit: bool,
+) -> Result<(), CommentError> {
+    for (index, operation) in operations.iter().enumerate() {
+        let check = |id: Uuid, author: Option<&str>, authors: &CommentAuthors| {
+            check_comment(index, id, author, authors, user, can_edit)
+        };
+
+        match operation {
+            Operation::SetCommentThread { thread, .. } => {
+                check(thread.id, Some(&thread.comment.author), authors)?;
+                for reply in thread.replies.iter() {
+                    // replies that are kept don't need to be written by the user
+                    if authors.get(&reply.id) != Some(&reply.author) {
+                        check(reply.id, Some(&reply.author), authors)?;
+                    }
+                }
+            }
+            Operation::AddCommentReply { reply, .. } => {
+                check(reply.id, Some(&reply.author), authors)?;
+            }
+            Operation::RemoveCommentThread { thread_id, .. } => {
+                check(*thread_id, None, authors)?;
+            }
+            Operation::RemoveCommentReply { reply_id, .. } => {
+                check(*reply_id, None, authors)?;
+            }
+            _ => (),
+        }
+
+        apply_operation(operation, authors);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::{sheet::comments::CommentThread, SheetId},
+        Pos,
+    };
+
+    fn thread(author: &str) -> CommentThread {
+        CommentThread::new(Pos { x: 1, y: 1 }, Comment::new(author.into(), "hi".into()))
+    }
+
+    #[test]
+    fn new_comments_are_written_by_the_user() {
+        let sheet_id = SheetId::TEST;
+        let mut authors = CommentAuthors::new();
+
+        let alice = thread("alice");
+        let set = Operation::SetCommentThread {
+            sheet_id,
+            thread: alice.clone(),
+        };
+        assert_eq!(
+            validate_comments(std::slice::from_ref(&set), &mut authors, "bob", true),
+            Err(CommentError::Author(0, alice.id))
+        );
+        assert_eq!(
+            validate_comments(&[set], &mut authors, "alice", false),
+            Ok(())
+        );
+
+        let reply = Comment::new("alice".into(), "reply".into());
+        let add = Operation::AddCommentReply {
+            sheet_id,
+            thread_id: alice.id,
+            reply: reply.clone(),
+        };
+        assert_eq!(
+            validate_comments(std::slice::from_ref(&add), &mut authors, "bob", false),
+            Err(CommentError::Author(0, reply.id))
+        );
+
+        // the author of an existing thread can't be changed
+        let mut forged = alice.clone();
+        forged.comment.author = "bob".into();
+        assert_eq!(
+            validate_comments(
+                &[Operation::SetCommentThread {
+                    sheet_id,
+                    thread: forged
+                }],
+                &mut authors,
+                "bob",
+                true
+            ),
+            Err(CommentError::Author(0, alice.id))
+        );
+    }
+
+    #[test]
+    fn only_authors_and_editors_change_comments() {
+        let sheet_id = SheetId::TEST;
+        let mut alice = thread("alice");
+        let reply = Comment::new("bob".into(), "reply".into());
+        alice.replies.push(reply.clone());
+        let mut authors = CommentAuthors::new();
+        apply_comment_operations(
+            &[Operation::SetCommentThread {
+                sheet_id,
+                thread: alice.clone(),
+            }],
+            &mut authors,
+        );
+
+        let remove_thread = Operation::RemoveCommentThread {
+            sheet_id,
+            thread_id: alice.id,
+        };
+        let remove_reply = Operation::RemoveCommentReply {
+            sheet_id,
+            thread_id: alice.id,
+            reply_id: reply.id,
+        };
+        let mut resolved = alice.clone();
+        resolved.resolved = true;
+        let set_thread = Operation::SetCommentThread {
+            sheet_id,
+            thread: resolved,
+        };
+
+        for operation in [&remove_thread, &set_thread] {
+            assert_eq!(
+                validate_comments(
+                    std::slice::from_ref(operation),
+                    &mut authors.clone(),
+                    "bob",
+                    false
+                ),
+                Err(CommentError::NotAuthor(0, alice.id))
+            );
+        }
+        assert_eq!(
+            validate_comments(
+                std::slice::from_ref(&remove_reply),
+                &mut authors.clone(),
+                "alice",
+                false
+            ),
+            Err(CommentError::NotAuthor(0, reply.id))
+        );
+
+        // authors and editors
+        assert_eq!(
+            validate_comments(
+                std::slice::from_ref(&set_thread),
+                &mut authors.clone(),
+                "alice",
+                false
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_comments(
+                std::slice::from_ref(&remove_reply),
+                &mut authors.clone(),
+                "bob",
+                false
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_comments(
+                &[remove_reply, remove_thread.clone()],
+                &mut authors.clone(),
+                "carol",
+                true
+            ),
+            Ok(())
+        );
+
+        // undoing a removal is checked against the original author
+        assert_eq!(
+            validate_comments(&[remove_thread], &mut authors, "alice", false),
+            Ok(())
+        );
+        assert_eq!(
+            validate_comments(&[set_thread], &mut authors, "bob", false),
+            Err(CommentError::NotAuthor(0, alice.id))
+        );
+    }
+
+    #[test]
+    fn collects_comment_authors() {
+        let mut grid = Grid::test();
+        let mut alice = thread("alice");
+        let reply = Comment::new("bob".into(), "reply".into());
+        alice.replies.push(reply.clone());
+        let sheet_id = grid.sheets()[0].id;
+        grid.try_sheet_mut(sheet_id)
+            .unwrap()
+            .comments
+            .set(sheet_id, alice.clone());
+
+        let authors = comment_authors(&grid);
+        assert_eq!(authors.len(), 2);
+        assert_eq!(authors[&alice.id], "alice");
+        assert_eq!(authors[&reply.id], "bob");
+    }
+}
@@ -1,4 +1,6 @@
-use std::io::Cursor;
+use std::collections::HashMap;
+use std::io::{BufReader, Cursor};
+use std::str::FromStr;
 
 use anyhow::{anyhow, bail, Result};
 use chrono::{NaiveDate, NaiveTime};
@@ -6,13 +8,19 @@ use chrono::{NaiveDate, NaiveTime};
 use crate::{
     cell_values::CellValues,
     controller::GridController,
-    grid::{file::sheet_schema::export_sheet, CodeCellLanguage, CodeCellValue, Sheet, SheetId},
-    CellValue, Pos, SheetPos,
+    grid::{
+        file::sheet_schema::export_sheet, CodeCellLanguage, CodeCellValue, NumericFormat,
+        NumericFormatKind, Sheet, SheetId,
+    },
+    number_format::{builtin_number_format_code, NumberFormat},
+    CellRefRange, CellValue, Pos, Rect, SheetPos,
 };
 use bytes::Bytes;
 use calamine::{Data as ExcelData, Reader as ExcelReader, Xlsx, XlsxError};
 use lexicon_fractional_index::key_between;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use quick_xml::events::Event;
+use zip::ZipArchive;
 
 use super::operation::Operation;
 
@@ -98,17 +106,15 @@ impl GridController {
                 cell_values = CellValues::new(width, h);
 
                 // update the progress bar every time there's a new operation
-                if cfg!(target_family = "wasm") || cfg!(test) {
-                    crate::wasm_bindings::js::jsImportProgress(
-                        file_name,
-                        current_y,
-                        height,
-                        insert_at.x,
-                        insert_at.y,
-                        width,
-                        height,
-                    );
-                }
+                self.host.import_progress(
+                    file_name,
+                    current_y,
+                    height,
+                    insert_at.x,
+                    insert_at.y,
+                    width,
+                    height,
+                );
             }
         }
 
@@ -133,9 +139,14 @@ impl GridController {
         let mut ops = vec![] as Vec<Operation>;
         let error = |e: XlsxError| anyhow!("Error parsing Excel file {file_name}: {e}");
 
+        // merged cells and number formats are optional, so a file that we
+        // can't read them from is still imported
+        let mut sheet_formats = xlsx_sheet_formats(&file).unwrap_or_default();
+
         let cursor = Cursor::new(file);
         let mut workbook: Xlsx<_> = ExcelReader::new(cursor).map_err(error)?;
         let sheets = workbook.sheet_names().to_owned();
+        let host = self.host.clone();
 
         let existing_sheet_names = self.sheet_names();
         for sheet_name in sheets.iter() {
@@ -224,11 +235,9 @@ impl GridController {
                 }
 
                 // send progress to the client, every IMPORT_LINES_PER_OPERATION
-                if (cfg!(target_family = "wasm") || cfg!(test))
-                    && current_y_values % IMPORT_LINES_PER_OPERATION == 0
-                {
+                if current_y_values % IMPORT_LINES_PER_OPERATION == 0 {
                     let width = row.len() as u32;
-                    crate::wasm_bindings::js::jsImportProgress(
+                    host.import_progress(
                         file_name,
                         current_y_values + current_y_formula,
                         total_rows as u32,
@@ -265,11 +274,9 @@ impl GridController {
                 }
 
                 // send progress to the client, every IMPORT_LINES_PER_OPERATION
-                if (cfg!(target_family = "wasm") || cfg!(test))
-                    && current_y_formula % IMPORT_LINES_PER_OPERATION == 0
-                {
+                if current_y_formula % IMPORT_LINES_PER_OPERATION == 0 {
                     let width = row.len() as u32;
-                    crate::wasm_bindings::js::jsImportProgress(
+                    host.import_progress(
                         file_name,
                         current_y_values + current_y_formula,
                         total_rows as u32,
@@ -281,6 +288,21 @@ impl GridController {
                 }
                 current_y_formula += 1;
             }
+            // merged cells and number formats
+            let formats = sheet_formats.remove(&sheet_name).unwrap_or_default();
+            for rect in formats.merge_cells {
+                sheet.merge_cells.merge(rect);
+            }
+            for (pos, code) in formats.number_formats {
+                sheet.formats.numeric_format.set(
+                    pos,
+                    Some(NumericFormat {
+                        kind: NumericFormatKind::Custom,
+                        symbol: Some(code),
+                    }),
+                );
+            }
+
             // add new sheets
             ops.push(Operation::AddSheetSchema {
                 schema: Box::new(export_sheet(sheet)),
@@ -347,17 +369,15 @@ impl GridController {
                 ops.push(operations);
 
                 // update the progress bar every time there's a new operation
-                if cfg!(target_family = "wasm") || cfg!(test) {
-                    crate::wasm_bindings::js::jsImportProgress(
-                        file_name,
-                        current_size as u32,
-                        total_size,
-                        insert_at.x,
-                        insert_at.y,
-                        width,
-                        height,
-                    );
-                }
+                self.host.import_progress(
+                    file_name,
+                    current_size as u32,
+                    total_size,
+                    insert_at.x,
+                    insert_at.y,
+                    width,
+                    height,
+                );
             }
         }
 
@@ -365,6 +385,145 @@ impl GridController {
     }
 }
 
+/// Formatting of an xlsx worksheet that calamine does not expose.
+#[derive(Debug, Default)]
+struct XlsxSheetFormats {
+    merge_cells: Vec<Rect>,
+
+    /// Cells with a (non-date) number format code.
+    number_formats: Vec<(Pos, String)>,
+}
+
+/// Reads the merged cells and number formats of each worksheet in an xlsx
+/// file (keyed by sheet name) directly from the xml.
+fn xlsx_sheet_formats(file: &[u8]) -> Result<HashMap<String, XlsxSheetFormats>> {
+    let mut zip = ZipArchive::new(Cursor::new(file))?;
+
+    // relationship id -> worksheet path (relative to xl/)
+    let relationships = xml_attributes(
+        &mut zip,
+        "xl/_rels/workbook.xml.rels",
+        b"Relationship",
+        None,
+        &[b"Id", b"Target"],
+    )?
+    .into_iter()
+    .map(|[id, target]| (id, target))
+    .collect::<HashMap<_, _>>();
+
+    // cell style index -> number format code; dates are already imported as
+    // dates, so their format codes are skipped
+    let custom_codes = xml_attributes(
+        &mut zip,
+        "xl/styles.xml",
+        b"numFmt",
+        None,
+        &[b"numFmtId", b"formatCode"],
+    )?
+    .into_iter()
+    .filter_map(|[id, code]| Some((id.parse::<u32>().ok()?, code)))
+    .collect::<HashMap<_, _>>();
+    let style_codes = xml_attributes(
+        &mut zip,
+        "xl/styles.xml",
+        b"xf",
+        Some(b"cellXfs"),
+        &[b"numFmtId"],
+    )?
+    .into_iter()
+    .map(|[id]| {
+        let id = id.parse::<u32>().ok()?;
+        let code = custom_codes
+            .get(&id)
+            .cloned()
+            .or_else(|| builtin_number_format_code(id).map(String::from))?;
+        (!code.eq_ignore_ascii_case("general") && !NumberFormat::is_date_time(&code))
+            .then_some(code)
+    })
+    .collect::<Vec<_>>();
+
+    let mut sheet_formats = HashMap::new();
+    let sheets = xml_attributes(
+        &mut zip,
+        "xl/workbook.xml",
+        b"sheet",
+        None,
+        &[b"name", b"id"],
+    )?;
+    for [name, id] in sheets {
+        let Some(target) = relationships.get(&id) else {
+            continue;
+        };
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{target}"),
+        };
+        let merge_cells = xml_attributes(&mut zip, &path, b"mergeCell", None, &[b"ref"])?
+            .into_iter()
+            .filter_map(|[reference]| CellRefRange::from_str(&reference).ok()?.to_rect())
+            .collect::<Vec<_>>();
+        let number_formats = xml_attributes(&mut zip, &path, b"c", None, &[b"r", b"s"])?
+            .into_iter()
+            .filter_map(|[reference, style]| {
+                let code = style_codes.get(style.parse::<usize>().ok()?)?.clone()?;
+                Some((Pos::try_a1_string(&reference)?, code))
+            })
+            .collect::<Vec<_>>();
+        if !merge_cells.is_empty() || !number_formats.is_empty() {
+            sheet_formats.insert(
+                name,
+                XlsxSheetFormats {
+                    merge_cells,
+                    number_formats,
+                },
+            );
+        }
+    }
+    Ok(sheet_formats)
+}
+
+/// Returns the values of the requested attributes (matched by local name)
+/// for every element with the given name in an xml file within the zip. If
+/// `parent` is set, only elements within that parent element are returned.
+/// Missing attributes are returned as empty strings.
+fn xml_attributes<const N: usize>(
+    zip: &mut ZipArchive<Cursor<&[u8]>>,
+    path: &str,
+    element: &[u8],
+    parent: Option<&[u8]>,
+    attributes: &[&[u8]; N],
+) -> Result<Vec<[String; N]>> {
+    let Ok(file) = zip.by_name(path) else {
+        return Ok(vec![]);
+    };
+    let mut reader = quick_xml::Reader::from_reader(BufReader::new(file));
+    let mut buf = vec![];
+    let mut found = vec![];
+    let mut in_parent = parent.is_none();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) if Some(e.local_name().as_ref()) == parent => in_parent = true,
+            Event::End(e) if Some(e.local_name().as_ref()) == parent => in_parent = false,
+            Event::Start(e) | Event::Empty(e)
+                if in_parent && e.local_name().as_ref() == element =>
+            {
+                let mut values = std::array::from_fn(|_| String::new());
+                for attribute in e.attributes().flatten() {
+                    let key = attribute.key.local_name();
+                    if let Some(index) = attributes.iter().position(|a| *a == key.as_ref()) {
+                        values[index] = attribute.decode_and_unescape_value(&reader)?.into_owned();
+                    }
+                }
+                found.push(values);
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(found)
+}
+
 fn read_utf16(bytes: &[u8]) -> Option<String> {
     if bytes.is_empty() && bytes.len() % 2 == 0 {
         return None;
@@ -549,6 +708,58 @@ mod test {
         assert_eq!(sheet.cell_value((4, 1).into()), None);
     }
 
+    #[test]
+    #[parallel]
+    fn import_excel_merge_cells() {
+        let mut gc = GridController::new_blank();
+        let file = include_bytes!("../../../test-files/merge_cells.xlsx");
+        gc.import_excel(file.to_vec(), "merge_cells.xlsx", None)
+            .unwrap();
+
+        let sheet = gc.sheet(gc.grid.sheets()[0].id);
+        assert_eq!(
+            sheet.merge_cells.get(pos![B1]),
+            Some(Rect::test_a1("A1:C1"))
+        );
+        assert_eq!(
+            sheet.merge_cells.get(pos![B9]),
+            Some(Rect::test_a1("A8:B9"))
+        );
+        assert_eq!(sheet.merge_cells.iter().count(), 2);
+    }
+
+    #[test]
+    #[parallel]
+    fn import_excel_number_formats() {
+        let mut gc = GridController::new_blank();
+        let file = include_bytes!("../../../test-files/number_formats.xlsx");
+        gc.import_excel(file.to_vec(), "number_formats.xlsx", None)
+            .unwrap();
+
+        let sheet = gc.sheet(gc.grid.sheets()[0].id);
+        let code = |pos| {
+            sheet
+                .formats
+                .numeric_format
+                .get(pos)
+                .and_then(|format| format.symbol)
+        };
+        assert_eq!(
+            code(pos![A1]),
+            Some("#,##0.00;[Red]\\(#,##0.00\\)".to_string())
+        );
+        assert_eq!(
+            sheet.rendered_value(pos![A1]),
+            Some("(1,234.50)".to_string())
+        );
+        assert_eq!(code(pos![A2]), Some("0.00%".to_string()));
+        assert_eq!(sheet.rendered_value(pos![A2]), Some("25.00%".to_string()));
+
+        // dates and General are not number formats
+        assert_eq!(code(pos![A3]), None);
+        assert_eq!(code(pos![A4]), None);
+    }
+
     #[test]
     #[parallel]
     fn import_excel_invalid() {
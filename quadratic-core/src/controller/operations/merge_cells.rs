@@ -0,0 +1,78 @@
+use super::operation::Operation;
+use crate::cell_values::CellValues;
+use crate::controller::GridController;
+use crate::{A1Selection, Rect};
+
+impl GridController {
+    /// Returns the operations to merge each finite range in the selection.
+    /// Like Excel, only the value in the top-left cell of each range is kept.
+    pub fn merge_cells_operations(&self, selection: &A1Selection) -> Vec<Operation> {
+        let Some(sheet) = self.try_sheet(selection.sheet_id) else {
+            return vec![];
+        };
+
+        let mut ops = vec![];
+        for rect in selection.ranges.iter().filter_map(|range| range.to_rect()) {
+            if rect.len() <= 1 {
+                continue;
+            }
+            if sheet.has_cell_value_in_rect(&rect, Some(rect.min)) {
+                for covered in rect.subtract(Rect::single_pos(rect.min)) {
+                    ops.push(Operation::SetCellValues {
+                        sheet_pos: covered.min.to_sheet_pos(selection.sheet_id),
+                        values: CellValues::new(covered.width(), covered.height()),
+                    });
+                }
+            }
+            ops.push(Operation::MergeCells {
+                sheet_rect: rect.to_sheet_rect(selection.sheet_id),
+            });
+        }
+        ops
+    }
+
+    /// Returns the operations to unmerge all merged regions that intersect
+    /// the selection.
+    pub fn unmerge_cells_operations(&self, selection: &A1Selection) -> Vec<Operation> {
+        let Some(sheet) = self.try_sheet(selection.sheet_id) else {
+            return vec![];
+        };
+
+        sheet
+            .selection_to_rects(selection)
+            .into_iter()
+            .filter(|rect| !sheet.merge_cells.in_rect(*rect).is_empty())
+            .map(|rect| Operation::UnmergeCells {
+                sheet_rect: rect.to_sheet_rect(selection.sheet_id),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_cells_operations() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let ops = gc.merge_cells_operations(&A1Selection::test_a1("A1:B2"));
+        assert_eq!(ops.len(), 1);
+
+        gc.set_cell_value(pos![B2].to_sheet_pos(sheet_id), "x".to_string(), None);
+        let ops = gc.merge_cells_operations(&A1Selection::test_a1("A1:B2"));
+        assert!(matches!(ops[0], Operation::SetCellValues { .. }));
+        assert!(matches!(ops.last(), Some(Operation::MergeCells { .. })));
+
+        // single cells and infinite ranges are not merged
+        assert!(gc
+            .merge_cells_operations(&A1Selection::test_a1("C3"))
+            .is_empty());
+        assert!(gc
+            .merge_cells_operations(&A1Selection::test_a1("C"))
+            .is_empty());
+    }
+}
@@ -7,8 +7,14 @@ pub mod borders;
 pub mod cell_value;
 pub mod clipboard;
 pub mod code_cell;
+pub mod comments;
 pub mod formats;
 pub mod formatting;
 pub mod import;
+pub mod merge_cells;
 pub mod operation;
+pub mod protection;
 pub mod sheets;
+#[cfg(feature = "multiplayer")]
+pub mod validation;
+pub mod what_if;
@@ -14,9 +14,13 @@ use crate::{
                 borders_old::{BorderStyleCellUpdates, SheetBorders},
                 BordersUpdates,
             },
+            comments::{Comment, CommentThread},
+            conditional_formats::conditional_format::ConditionalFormat,
+            outline::OutlineGroup,
+            protections::ProtectedRange,
             validations::validation::Validation,
         },
-        CodeRun, CodeRunOld, Sheet, SheetId,
+        CodeCellRefresh, CodeRun, CodeRunOld, Sheet, SheetId,
     },
     selection::OldSelection,
     A1Selection, CopyFormats, SheetPos, SheetRect,
@@ -180,6 +184,108 @@ pub enum Operation {
         row: i64,
         copy_formats: CopyFormats,
     },
+
+    /// Hides or unhides columns.
+    SetColumnsHidden {
+        sheet_id: SheetId,
+        columns: Vec<i64>,
+        hidden: bool,
+    },
+    /// Hides or unhides rows.
+    SetRowsHidden {
+        sheet_id: SheetId,
+        rows: Vec<i64>,
+        hidden: bool,
+    },
+    /// Replaces all column groups (including their collapsed state).
+    SetColumnGroups {
+        sheet_id: SheetId,
+        groups: Vec<OutlineGroup>,
+    },
+    /// Replaces all row groups (including their collapsed state).
+    SetRowGroups {
+        sheet_id: SheetId,
+        groups: Vec<OutlineGroup>,
+    },
+
+    /// Merges a rect of cells. Existing merged regions that intersect the
+    /// rect are unmerged.
+    MergeCells { sheet_rect: SheetRect },
+    /// Unmerges all merged regions that intersect the rect.
+    UnmergeCells { sheet_rect: SheetRect },
+
+    /// Creates or updates a conditional format.
+    SetConditionalFormat {
+        conditional_format: ConditionalFormat,
+    },
+    /// Deletes a conditional format.
+    RemoveConditionalFormat {
+        sheet_id: SheetId,
+        conditional_format_id: Uuid,
+    },
+
+    /// Creates or updates a comment thread (including its replies and
+    /// resolved flag).
+    SetCommentThread {
+        sheet_id: SheetId,
+        thread: CommentThread,
+    },
+    /// Deletes a comment thread.
+    RemoveCommentThread { sheet_id: SheetId, thread_id: Uuid },
+    /// Adds a reply to a comment thread. Replies are separate operations so
+    /// that concurrent replies from different users are not lost.
+    AddCommentReply {
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        reply: Comment,
+    },
+    /// Deletes a reply from a comment thread.
+    RemoveCommentReply {
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        reply_id: Uuid,
+    },
+    /// Resolves (or reopens) a comment thread.
+    SetCommentThreadResolved {
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        resolved: bool,
+    },
+
+    /// Creates or updates a protected range.
+    SetProtectedRange {
+        sheet_id: SheetId,
+        range: ProtectedRange,
+    },
+    /// Deletes a protected range.
+    RemoveProtectedRange { sheet_id: SheetId, range_id: Uuid },
+
+    /// Sets when a code cell runs again without a change to the cells it
+    /// reads.
+    SetCodeRunRefresh {
+        sheet_pos: SheetPos,
+        refresh: CodeCellRefresh,
+    },
+}
+
+impl Operation {
+    /// Whether this operation only changes comments. Viewers are allowed to
+    /// send these.
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            self,
+            Operation::SetCommentThread { .. }
+                | Operation::RemoveCommentThread { .. }
+                | Operation::AddCommentReply { .. }
+                | Operation::RemoveCommentReply { .. }
+                | Operation::SetCommentThreadResolved { .. }
+        )
+    }
+
+    /// Whether a non-empty batch of operations only changes comments.
+    pub fn all_comments(operations: &[Operation]) -> bool {
+        !operations.is_empty() && operations.iter().all(Operation::is_comment)
+    }
 }
 
 // TODO: either remove this or add a comment explaining why it's better than the
@@ -365,6 +471,132 @@ impl fmt::Display for Operation {
                     "InsertRow {{ sheet_id: {sheet_id}, row: {row}, copy_formats: {copy_formats:?} }}"
                 )
             }
+            Operation::SetColumnsHidden {
+                sheet_id,
+                columns,
+                hidden,
+            } => {
+                write!(
+                    fmt,
+                    "SetColumnsHidden {{ sheet_id: {sheet_id}, columns: {columns:?}, hidden: {hidden} }}"
+                )
+            }
+            Operation::SetRowsHidden {
+                sheet_id,
+                rows,
+                hidden,
+            } => {
+                write!(
+                    fmt,
+                    "SetRowsHidden {{ sheet_id: {sheet_id}, rows: {rows:?}, hidden: {hidden} }}"
+                )
+            }
+            Operation::SetColumnGroups { sheet_id, groups } => {
+                write!(
+                    fmt,
+                    "SetColumnGroups {{ sheet_id: {sheet_id}, groups: {groups:?} }}"
+                )
+            }
+            Operation::SetRowGroups { sheet_id, groups } => {
+                write!(
+                    fmt,
+                    "SetRowGroups {{ sheet_id: {sheet_id}, groups: {groups:?} }}"
+                )
+            }
+            Operation::MergeCells { sheet_rect } => {
+                write!(fmt, "MergeCells {{ sheet_rect: {sheet_rect} }}")
+            }
+            Operation::UnmergeCells { sheet_rect } => {
+                write!(fmt, "UnmergeCells {{ sheet_rect: {sheet_rect} }}")
+            }
+            Operation::SetConditionalFormat { conditional_format } => {
+                write!(
+                    fmt,
+                    "SetConditionalFormat {{ conditional_format: {:?} }}",
+                    conditional_format
+                )
+            }
+            Operation::RemoveConditionalFormat {
+                sheet_id,
+                conditional_format_id,
+            } => {
+                write!(
+                    fmt,
+                    "RemoveConditionalFormat {{ sheet_id: {}, conditional_format_id: {} }}",
+                    sheet_id, conditional_format_id
+                )
+            }
+            Operation::SetCommentThread { sheet_id, thread } => {
+                write!(
+                    fmt,
+                    "SetCommentThread {{ sheet_id: {}, thread: {:?} }}",
+                    sheet_id, thread
+                )
+            }
+            Operation::RemoveCommentThread {
+                sheet_id,
+                thread_id,
+            } => {
+                write!(
+                    fmt,
+                    "RemoveCommentThread {{ sheet_id: {}, thread_id: {} }}",
+                    sheet_id, thread_id
+                )
+            }
+            Operation::AddCommentReply {
+                sheet_id,
+                thread_id,
+                reply,
+            } => {
+                write!(
+                    fmt,
+                    "AddCommentReply {{ sheet_id: {}, thread_id: {}, reply: {:?} }}",
+                    sheet_id, thread_id, reply
+                )
+            }
+            Operation::RemoveCommentReply {
+                sheet_id,
+                thread_id,
+                reply_id,
+            } => {
+                write!(
+                    fmt,
+                    "RemoveCommentReply {{ sheet_id: {}, thread_id: {}, reply_id: {} }}",
+                    sheet_id, thread_id, reply_id
+                )
+            }
+            Operation::SetCommentThreadResolved {
+                sheet_id,
+                thread_id,
+                resolved,
+            } => {
+                write!(
+                    fmt,
+                    "SetCommentThreadResolved {{ sheet_id: {}, thread_id: {}, resolved: {} }}",
+                    sheet_id, thread_id, resolved
+                )
+            }
+            Operation::SetProtectedRange { sheet_id, range } => {
+                write!(
+                    fmt,
+                    "SetProtectedRange {{ sheet_id: {}, range: {:?} }}",
+                    sheet_id, range
+                )
+            }
+            Operation::RemoveProtectedRange { sheet_id, range_id } => {
+                write!(
+                    fmt,
+                    "RemoveProtectedRange {{ sheet_id: {}, range_id: {} }}",
+                    sheet_id, range_id
+                )
+            }
+            Operation::SetCodeRunRefresh { sheet_pos, refresh } => {
+                write!(
+                    fmt,
+                    "SetCodeRunRefresh {{ sheet_pos: {}, refresh: {:?} }}",
+                    sheet_pos, refresh
+                )
+            }
         }
     }
 }
@@ -0,0 +1,670 @@
+//! Checks operations against protected ranges.
+//!
+//! Core uses this to refuse local changes to protected cells, and the
+//! multiplayer server uses it to reject transactions from other clients. The
+//! server does not load the file, so the check only needs the protected
+//! ranges, which are kept up to date by applying each batch of operations.
+//!
+//! Code results (code runs and their spills) are not checked since they are
+//! produced by recalculating, which is how a protected formula updates when
+//! its unprotected inputs change. The legacy `SetCodeRun` is not produced by
+//! recalculating, so its output is checked like any other value.
+//!
+//! Protected ranges only change through protection operations. Operations that
+//! would replace an existing sheet (and its protected ranges) are rejected.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::operation::Operation;
+use crate::{
+    a1::UNBOUNDED,
+    grid::{
+        formats::SheetFormatUpdates,
+        sheet::{
+            borders::BordersUpdates,
+            protections::{ProtectedRange, ProtectionEditor, Protections},
+        },
+        CodeRunResult, Grid, SheetId,
+    },
+    A1Selection, ArraySize, Rect, Value,
+};
+
+/// Protected ranges of each sheet.
+pub type SheetProtections = HashMap<SheetId, Protections>;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ProtectionError {
+    #[error("Operation {0} changes cells protected by range {1}")]
+    ProtectedCells(usize, Uuid),
+
+    #[error("Operation {0} changes protected range {1}")]
+    ProtectedRange(usize, Uuid),
+
+    #[error("Operation {0} replaces sheet {1}, which has protected ranges")]
+    ReplacedSheet(usize, SheetId),
+}
+
+/// Cells that an operation changes.
+enum ChangedCells<'a> {
+    Rects(Vec<Rect>),
+    Selection(&'a A1Selection),
+    Formats(&'a SheetFormatUpdates),
+    Borders(&'a BordersUpdates),
+    Sheet,
+}
+
+impl ChangedCells<'_> {
+    fn intersects(&self, range: &ProtectedRange) -> bool {
+        let Some(rect) = range.rect else {
+            // the whole sheet is protected
+            return true;
+        };
+        match self {
+            ChangedCells::Rects(rects) => rects.iter().any(|r| r.intersects(rect)),
+            ChangedCells::Selection(selection) => selection
+                .ranges
+                .iter()
+                .any(|range| range.might_intersect_rect(rect)),
+            ChangedCells::Formats(formats) => formats.intersects(rect),
+            ChangedCells::Borders(borders) => borders.intersects(rect),
+            ChangedCells::Sheet => true,
+        }
+    }
+}
+
+impl Operation {
+    /// Returns the id of the sheet that this operation adds.
+    fn added_sheet_id(&self) -> Option<SheetId> {
+        match self {
+            Operation::AddSheet { sheet } => Some(sheet.id),
+            Operation::AddSheetSchema { schema } => schema.sheet_id().ok(),
+            Operation::DuplicateSheet { new_sheet_id, .. } => Some(*new_sheet_id),
+            _ => None,
+        }
+    }
+
+    /// Returns the cells that this operation changes for each sheet.
+    /// Operations that are allowed in protected ranges return nothing.
+    fn changed_cells(&self) -> Vec<(SheetId, ChangedCells<'_>)> {
+        match self {
+            Operation::SetCellValues { sheet_pos, values } => {
+                let rect = Rect::new(
+                    sheet_pos.x,
+                    sheet_pos.y,
+                    sheet_pos.x.saturating_add((values.w as i64).max(1) - 1),
+                    sheet_pos.y.saturating_add((values.h as i64).max(1) - 1),
+                );
+                vec![(sheet_pos.sheet_id, ChangedCells::Rects(vec![rect]))]
+            }
+            Operation::SetCodeRun {
+                sheet_pos,
+                code_run,
+                ..
+            } => {
+                // the output of the code run being replaced is not part of the
+                // operation
+                let size = match code_run.as_ref().map(|run| &run.result) {
+                    Some(CodeRunResult::Ok(Value::Array(array))) => array.size(),
+                    _ => ArraySize::_1X1,
+                };
+                vec![(
+                    sheet_pos.sheet_id,
+                    ChangedCells::Rects(vec![Rect::from_pos_and_size((*sheet_pos).into(), size)]),
+                )]
+            }
+            Operation::SetCodeRunRefresh { sheet_pos, .. } => vec![(
+                sheet_pos.sheet_id,
+                ChangedCells::Rects(vec![Rect::single_pos((*sheet_pos).into())]),
+            )],
+            Operation::SetCellFormats { sheet_rect, .. }
+            | Operation::SetBorders { sheet_rect, .. }
+            | Operation::MergeCells { sheet_rect }
+            | Operation::UnmergeCells { sheet_rect } => vec![(
+                sheet_rect.sheet_id,
+                ChangedCells::Rects(vec![Rect::from(*sheet_rect)]),
+            )],
+            Operation::SetCellFormatsA1 { sheet_id, formats } => {
+                vec![(*sheet_id, ChangedCells::Formats(formats))]
+            }
+            Operation::SetBordersA1 { sheet_id, borders } => {
+                vec![(*sheet_id, ChangedCells::Borders(borders))]
+            }
+            Operation::SetValidation { validation } => vec![(
+                validation.selection.sheet_id,
+                ChangedCells::Selection(&validation.selection),
+            )],
+            Operation::SetConditionalFormat { conditional_format } => vec![(
+                conditional_format.selection.sheet_id,
+                ChangedCells::Selection(&conditional_format.selection),
+            )],
+            Operation::MoveCells { source, dest } => {
+                let width = source.max.x.saturating_sub(source.min.x);
+                let height = source.max.y.saturating_sub(source.min.y);
+                let dest_rect = Rect::new(
+                    dest.x,
+                    dest.y,
+                    dest.x.saturating_add(width),
+                    dest.y.saturating_add(height),
+                );
+                vec![
+                    (
+                        source.sheet_id,
+                        ChangedCells::Rects(vec![Rect::from(*source)]),
+                    ),
+                    (dest.sheet_id, ChangedCells::Rects(vec![dest_rect])),
+                ]
+            }
+            Operation::InsertColumn {
+                sheet_id, column, ..
+            }
+            | Operation::DeleteColumn { sheet_id, column } => vec![(
+                *sheet_id,
+                ChangedCells::Rects(vec![Rect::new(*column, 1, *column, UNBOUNDED)]),
+            )],
+            Operation::InsertRow { sheet_id, row, .. } | Operation::DeleteRow { sheet_id, row } => {
+                vec![(
+                    *sheet_id,
+                    ChangedCells::Rects(vec![Rect::new(1, *row, UNBOUNDED, *row)]),
+                )]
+            }
+
+            // the removed item's cells are not part of the operation
+            Operation::RemoveValidation { sheet_id, .. }
+            | Operation::RemoveConditionalFormat { sheet_id, .. }
+            | Operation::DeleteSheet { sheet_id }
+            | Operation::SetSheetName { sheet_id, .. } => vec![(*sheet_id, ChangedCells::Sheet)],
+            Operation::SetCellFormatsSelection { selection, .. }
+            | Operation::SetBordersSelection { selection, .. } => {
+                vec![(selection.sheet_id, ChangedCells::Sheet)]
+            }
+
+            Operation::SetCodeRunVersion { .. }
+            | Operation::ComputeCode { .. }
+            | Operation::SetValidationWarning { .. }
+            | Operation::AddSheet { .. }
+            | Operation::AddSheetSchema { .. }
+            | Operation::DuplicateSheet { .. }
+            | Operation::SetSheetColor { .. }
+            | Operation::ReorderSheet { .. }
+            | Operation::ResizeColumn { .. }
+            | Operation::ResizeRow { .. }
+            | Operation::ResizeRows { .. }
+            | Operation::SetCursor { .. }
+            | Operation::SetCursorSelection { .. }
+            | Operation::SetCursorA1 { .. }
+            | Operation::SetColumnsHidden { .. }
+            | Operation::SetRowsHidden { .. }
+            | Operation::SetColumnGroups { .. }
+            | Operation::SetRowGroups { .. }
+            | Operation::SetCommentThread { .. }
+            | Operation::RemoveCommentThread { .. }
+            | Operation::AddCommentReply { .. }
+            | Operation::RemoveCommentReply { .. }
+            | Operation::SetCommentThreadResolved { .. }
+            | Operation::SetProtectedRange { .. }
+            | Operation::RemoveProtectedRange { .. } => vec![],
+        }
+    }
+}
+
+/// Collects the protected ranges of a grid.
+pub fn sheet_protections(grid: &Grid) -> SheetProtections {
+    grid.sheets()
+        .iter()
+        .filter(|sheet| !sheet.protections.is_empty())
+        .map(|sheet| (sheet.id, sheet.protections.clone()))
+        .collect()
+}
+
+/// Updates `protections` for an operation that has already been checked.
+fn apply_operation(operation: &Operation, protections: &mut SheetProtections) {
+    match operation {
+        Operation::SetProtectedRange { sheet_id, range } => {
+            protections
+                .entry(*sheet_id)
+                .or_default()
+                .set(*sheet_id, range.clone());
+        }
+        Operation::RemoveProtectedRange { sheet_id, range_id } => {
+            if let Some(sheet) = protections.get_mut(sheet_id) {
+                sheet.remove(*sheet_id, *range_id);
+            }
+        }
+        Operation::InsertColumn {
+            sheet_id, column, ..
+        } => {
+            if let Some(sheet) = protections.get_mut(sheet_id) {
+                sheet.insert(true, *column);
+            }
+        }
+        Operation::DeleteColumn { sheet_id, column } => {
+            if let Some(sheet) = protections.get_mut(sheet_id) {
+                sheet.delete(true, *column);
+            }
+        }
+        Operation::InsertRow { sheet_id, row, .. } => {
+            if let Some(sheet) = protections.get_mut(sheet_id) {
+                sheet.insert(false, *row);
+            }
+        }
+        Operation::DeleteRow { sheet_id, row } => {
+            if let Some(sheet) = protections.get_mut(sheet_id) {
+                sheet.delete(false, *row);
+            }
+        }
+        Operation::DeleteSheet { sheet_id } => {
+            protections.remove(sheet_id);
+        }
+        // undoing a delete restores the sheet with its protections, but the
+        // protections of an existing sheet are never replaced
+        Operation::AddSheet { sheet } => {
+            protections
+                .entry(sheet.id)
+                .or_insert_with(|| sheet.protections.clone());
+        }
+        Operation::AddSheetSchema { schema } => {
+            if let Ok(sheet_id) = schema.sheet_id() {
+                protections
+                    .entry(sheet_id)
+                    .or_insert_with(|| schema.protections());
+            }
+        }
+        Operation::DuplicateSheet {
+            sheet_id,
+            new_sheet_id,
+        } => {
+            if let Some(sheet) = protections.get(sheet_id).cloned() {
+                protections.entry(*new_sheet_id).or_insert(sheet);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Updates `protections` for a batch of operations without checking them.
+pub fn apply_protection_operations(operations: &[Operation], protections: &mut SheetProtections) {
+    operations
+        .iter()
+        .for_each(|operation| apply_operation(operation, protections));
+}
+
+/// Validates a batch of operations made by `editor` against the protected
+/// ranges.
+///
+/// `protections` is updated as the batch changes the protected ranges, so
+/// callers should pass a copy and only keep it if validation succeeds.
+pub fn validate_protections(
+    operations: &[Operation],
+    protections: &mut SheetProtections,
+    editor: &ProtectionEditor,
+) -> Result<(), ProtectionError> {
+    for (index, operation) in operations.iter().enumerate() {
+        if let Some(sheet_id) = operation.added_sheet_id() {
+            if protections.contains_key(&sheet_id) {
+                return Err(ProtectionError::ReplacedSheet(index, sheet_id));
+            }
+        }
+
+        match operation {
+            // only editors of an existing range may change it
+            Operation::SetProtectedRange {
+                sheet_id,
+                range: ProtectedRange { id: range_id, .. },
+            }
+            | Operation::RemoveProtectedRange { sheet_id, range_id } => {
+                if let Some(range) = protections
+                    .get(sheet_id)
+                    .and_then(|sheet| sheet.get(*range_id))
+                {
+                    if !range.can_edit(editor) {
+                        return Err(ProtectionError::ProtectedRange(index, range.id));
+                    }
+                }
+            }
+            _ => {
+                for (sheet_id, cells) in operation.changed_cells() {
+                    let Some(sheet) = protections.get(&sheet_id) else {
+                        continue;
+                    };
+                    if let Some(range) = sheet
+                        .iter()
+                        .find(|range| !range.can_edit(editor) && cells.intersects(range))
+                    {
+                        return Err(ProtectionError::ProtectedCells(index, range.id));
+                    }
+                }
+            }
+        }
+
+        apply_operation(operation, protections);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{
+        cell_values::CellValues,
+        grid::{
+            sheet::{
+                protections::ProtectionRole,
+                validations::{
+                    validation::Validation,
+                    validation_rules::{validation_logical::ValidationLogical, ValidationRule},
+                },
+            },
+            CodeRunOld, Sheet,
+        },
+        Array, CopyFormats, Pos, SheetRect,
+    };
+
+    fn editor(user_id: &str) -> ProtectionEditor {
+        ProtectionEditor {
+            user_id: user_id.into(),
+            roles: vec![],
+        }
+    }
+
+    fn set_values(sheet_id: SheetId, a1: &str) -> Operation {
+        let rect = Rect::test_a1(a1);
+        Operation::SetCellValues {
+            sheet_pos: rect.min.to_sheet_pos(sheet_id),
+            values: CellValues::new(rect.width(), rect.height()),
+        }
+    }
+
+    fn protections(range: ProtectedRange) -> SheetProtections {
+        HashMap::from([(SheetId::TEST, Protections::from_iter([range]))])
+    }
+
+    #[test]
+    fn protected_cells() {
+        let sheet_id = SheetId::TEST;
+        let range = ProtectedRange::new(
+            Some(Rect::test_a1("B2:C3")),
+            vec!["alice".into()],
+            vec![ProtectionRole::Owner],
+        );
+        let mut protections = protections(range.clone());
+
+        let inside = set_values(sheet_id, "C3:D4");
+        let outside = set_values(sheet_id, "D1:E10");
+
+        assert_eq!(
+            validate_protections(
+                &[outside.clone(), inside.clone()],
+                &mut protections,
+                &editor("bob")
+            ),
+            Err(ProtectionError::ProtectedCells(1, range.id))
+        );
+        assert_eq!(
+            validate_protections(
+                std::slice::from_ref(&outside),
+                &mut protections,
+                &editor("bob")
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_protections(
+                std::slice::from_ref(&inside),
+                &mut protections,
+                &editor("alice")
+            ),
+            Ok(())
+        );
+        let owner = ProtectionEditor {
+            user_id: "carol".into(),
+            roles: vec![ProtectionRole::Owner],
+        };
+        assert_eq!(
+            validate_protections(&[inside], &mut protections, &owner),
+            Ok(())
+        );
+
+        // deleting a column through the range
+        assert_eq!(
+            validate_protections(
+                &[Operation::DeleteColumn {
+                    sheet_id,
+                    column: 2
+                }],
+                &mut protections,
+                &editor("bob")
+            ),
+            Err(ProtectionError::ProtectedCells(0, range.id))
+        );
+
+        // validations that cover the range
+        let validation = |a1: &str| Validation {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1(a1),
+            rule: ValidationRule::Logical(ValidationLogical::default()),
+            message: Default::default(),
+            error: Default::default(),
+        };
+        assert_eq!(
+            validate_protections(
+                &[Operation::SetValidation {
+                    validation: validation("A:A")
+                }],
+                &mut protections,
+                &editor("bob")
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate_protections(
+                &[Operation::SetValidation {
+                    validation: validation("2:2")
+                }],
+                &mut protections,
+                &editor("bob")
+            ),
+            Err(ProtectionError::ProtectedCells(0, range.id))
+        );
+
+        // moving cells into the range
+        assert_eq!(
+            validate_protections(
+                &[Operation::MoveCells {
+                    source: SheetRect::new(5, 1, 6, 2, sheet_id),
+                    dest: pos![A1].to_sheet_pos(sheet_id),
+                }],
+                &mut protections,
+                &editor("bob")
+            ),
+            Err(ProtectionError::ProtectedCells(0, range.id))
+        );
+    }
+
+    #[test]
+    fn shifted_ranges() {
+        let sheet_id = SheetId::TEST;
+        let range = ProtectedRange::new(Some(Rect::test_a1("B2:C3")), vec![], vec![]);
+        let mut protections = protections(range.clone());
+
+        // inserting a column before the range moves it, so a later change to
+        // its new position is refused
+        let ops = vec![
+            Operation::InsertColumn {
+                sheet_id,
+                column: 1,
+                copy_formats: CopyFormats::None,
+            },
+            set_values(sheet_id, "D2"),
+        ];
+        assert_eq!(
+            validate_protections(&ops, &mut protections.clone(), &editor("bob")),
+            Err(ProtectionError::ProtectedCells(1, range.id))
+        );
+
+        apply_protection_operations(&ops[..1], &mut protections);
+        assert_eq!(
+            protections[&sheet_id].get(range.id).unwrap().rect,
+            Some(Rect::test_a1("C2:D3"))
+        );
+    }
+
+    #[test]
+    fn protected_ranges() {
+        let sheet_id = SheetId::TEST;
+        let range = ProtectedRange::new(None, vec!["alice".into()], vec![]);
+        let mut protections = protections(range.clone());
+
+        // the whole sheet is protected
+        assert_eq!(
+            validate_protections(
+                &[set_values(sheet_id, "Z100")],
+                &mut protections,
+                &editor("bob")
+            ),
+            Err(ProtectionError::ProtectedCells(0, range.id))
+        );
+
+        // only editors may change or remove a range
+        let remove = Operation::RemoveProtectedRange {
+            sheet_id,
+            range_id: range.id,
+        };
+        assert_eq!(
+            validate_protections(
+                std::slice::from_ref(&remove),
+                &mut protections,
+                &editor("bob")
+            ),
+            Err(ProtectionError::ProtectedRange(0, range.id))
+        );
+        assert_eq!(
+            validate_protections(
+                &[remove, set_values(sheet_id, "Z100")],
+                &mut protections,
+                &editor("alice")
+            ),
+            Ok(())
+        );
+        assert!(protections[&sheet_id].is_empty());
+
+        // anyone may add a new range
+        let new_range = ProtectedRange::new(Some(Rect::test_a1("A1")), vec![], vec![]);
+        assert_eq!(
+            validate_protections(
+                &[Operation::SetProtectedRange {
+                    sheet_id,
+                    range: new_range.clone()
+                }],
+                &mut protections,
+                &editor("bob")
+            ),
+            Ok(())
+        );
+        assert_eq!(protections[&sheet_id].get(new_range.id), Some(&new_range));
+    }
+
+    #[test]
+    fn replaced_sheets() {
+        let sheet_id = SheetId::TEST;
+        let range = ProtectedRange::new(Some(Rect::test_a1("B2:C3")), vec![], vec![]);
+        let mut protections = protections(range.clone());
+
+        // a sheet with protected ranges can't be replaced
+        let mut sheet = Sheet::test();
+        sheet.id = sheet_id;
+        assert_eq!(
+            validate_protections(
+                &[Operation::AddSheet {
+                    sheet: Box::new(sheet.clone())
+                }],
+                &mut protections.clone(),
+                &editor("bob")
+            ),
+            Err(ProtectionError::ReplacedSheet(0, sheet_id))
+        );
+        assert_eq!(
+            validate_protections(
+                &[Operation::DuplicateSheet {
+                    sheet_id: SheetId::new(),
+                    new_sheet_id: sheet_id
+                }],
+                &mut protections.clone(),
+                &editor("bob")
+            ),
+            Err(ProtectionError::ReplacedSheet(0, sheet_id))
+        );
+
+        // sequenced operations don't replace them either
+        apply_protection_operations(
+            &[Operation::AddSheet {
+                sheet: Box::new(sheet),
+            }],
+            &mut protections,
+        );
+        assert_eq!(protections[&sheet_id].get(range.id), Some(&range));
+
+        // but new sheets are added with their protected ranges
+        let mut new_sheet = Sheet::test();
+        new_sheet.id = SheetId::new();
+        new_sheet.protections.set(new_sheet.id, range.clone());
+        assert_eq!(
+            validate_protections(
+                &[Operation::AddSheet {
+                    sheet: Box::new(new_sheet.clone())
+                }],
+                &mut protections,
+                &editor("bob")
+            ),
+            Ok(())
+        );
+        assert_eq!(protections[&new_sheet.id].get(range.id), Some(&range));
+    }
+
+    #[test]
+    fn code_run_output() {
+        let sheet_id = SheetId::TEST;
+        let range = ProtectedRange::new(Some(Rect::test_a1("B2:C3")), vec![], vec![]);
+        let mut protections = protections(range.clone());
+
+        let set_code_run = |a1: &str, size: ArraySize| Operation::SetCodeRun {
+            sheet_pos: Pos::try_a1_string(a1).unwrap().to_sheet_pos(sheet_id),
+            code_run: Some(CodeRunOld {
+                formatted_code_string: None,
+                std_out: None,
+                std_err: None,
+                cells_accessed: vec![],
+                result: CodeRunResult::Ok(Value::Array(Array::new_empty(size))),
+                return_type: None,
+                spill_error: false,
+                line_number: None,
+                output_type: None,
+                last_modified: Default::default(),
+            }),
+            index: 0,
+        };
+
+        // the output spills into the range
+        let size = ArraySize::new(2, 2).unwrap();
+        assert_eq!(
+            validate_protections(
+                &[set_code_run("A1", size)],
+                &mut protections,
+                &editor("bob")
+            ),
+            Err(ProtectionError::ProtectedCells(0, range.id))
+        );
+        assert_eq!(
+            validate_protections(
+                &[set_code_run("D1", size)],
+                &mut protections,
+                &editor("bob")
+            ),
+            Ok(())
+        );
+    }
+}
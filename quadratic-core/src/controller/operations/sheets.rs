@@ -3,7 +3,7 @@ use lexicon_fractional_index::key_between;
 
 use crate::{
     controller::GridController,
-    grid::{file::sheet_schema::export_sheet, Sheet, SheetId},
+    grid::{file::sheet_schema::export_sheet, Grid, Sheet, SheetId},
     util,
 };
 
@@ -112,11 +112,43 @@ impl GridController {
         ops.extend(code_run_ops);
         ops
     }
+
+    /// Returns the operations that revert the grid to the sheets of `grid`, a
+    /// past version of this file. Sheets are replaced as a whole so the
+    /// restore is applied as a regular transaction on top of the history.
+    pub fn restore_grid_operations(&self, grid: &Grid) -> Vec<Operation> {
+        // a grid can't be empty, so keep a placeholder sheet while the sheets
+        // are replaced (its name is its id so it can't clash with a sheet)
+        let placeholder_id = SheetId::new();
+        let placeholder = Sheet::new(
+            placeholder_id,
+            placeholder_id.to_string(),
+            self.grid.end_order(),
+        );
+
+        let mut ops = vec![Operation::AddSheet {
+            sheet: Box::new(placeholder),
+        }];
+        ops.extend(
+            self.sheet_ids()
+                .into_iter()
+                .map(|sheet_id| Operation::DeleteSheet { sheet_id }),
+        );
+        ops.extend(grid.sheets().iter().map(|sheet| Operation::AddSheetSchema {
+            schema: Box::new(export_sheet(sheet.clone())),
+        }));
+        ops.push(Operation::DeleteSheet {
+            sheet_id: placeholder_id,
+        });
+
+        ops
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{controller::active_transactions::transaction_name::TransactionName, CellValue};
     use serial_test::parallel;
 
     #[test]
@@ -186,6 +218,37 @@ mod test {
         assert_eq!(gc.sheet_index(2).name, "Sheet 3");
     }
 
+    #[test]
+    #[parallel]
+    fn test_restore_grid_operations() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value((1, 1, sheet_id).into(), "old".to_string(), None);
+        let past = gc.grid().clone();
+
+        gc.set_cell_value((1, 1, sheet_id).into(), "new".to_string(), None);
+        gc.set_sheet_name(sheet_id, "Renamed".to_string(), None);
+        gc.add_sheet(None);
+
+        // applying as a user transaction must not leave an extra sheet
+        let ops = gc.restore_grid_operations(&past);
+        gc.start_user_transaction(ops, None, TransactionName::Unknown);
+        assert_eq!(gc.sheet_ids(), vec![sheet_id]);
+        assert_eq!(gc.sheet_names(), vec!["Sheet 1"]);
+        assert_eq!(
+            gc.sheet(sheet_id).display_value((1, 1).into()),
+            Some(CellValue::Text("old".to_string()))
+        );
+
+        // the restore can be undone
+        gc.undo(None);
+        assert_eq!(gc.sheet_names(), vec!["Renamed", "Sheet 1"]);
+        assert_eq!(
+            gc.sheet(sheet_id).display_value((1, 1).into()),
+            Some(CellValue::Text("new".to_string()))
+        );
+    }
+
     #[test]
     #[parallel]
     fn sheet_names() {
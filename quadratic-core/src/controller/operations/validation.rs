@@ -0,0 +1,487 @@
+//! Structural validation of operations received from other clients.
+//!
+//! The multiplayer server uses this to reject transactions that cannot be
+//! safely applied by other clients or the files service. The server does not
+//! load the file, so sheet ids are only checked against the sheets it has seen
+//! deleted (and not restored).
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use super::operation::Operation;
+use crate::{grid::SheetId, SheetRect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationLimits {
+    /// Maximum number of operations in a single transaction.
+    pub max_operations: usize,
+
+    /// Maximum number of cells in a single operation's rect.
+    pub max_rect_cells: u64,
+}
+
+impl Default for OperationLimits {
+    fn default() -> Self {
+        OperationLimits {
+            max_operations: 10_000,
+            max_rect_cells: 10_000_000,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum OperationValidationError {
+    #[error("Transaction has {0} operations, which exceeds the limit of {1}")]
+    TooManyOperations(usize, usize),
+
+    #[error("Invalid rect in operation {0}")]
+    InvalidRect(usize),
+
+    #[error("Rect in operation {0} has {1} cells, which exceeds the limit of {2}")]
+    RectTooLarge(usize, u64, u64),
+
+    #[error("Operation {0} references deleted sheet {1}")]
+    DeletedSheet(usize, SheetId),
+
+    #[error("Operation {0} has an invalid sheet id")]
+    InvalidSheetId(usize),
+}
+
+impl Operation {
+    /// Returns the ids of the existing sheets that this operation modifies.
+    /// Sheets added by the operation are not included.
+    pub fn sheet_ids(&self) -> Vec<SheetId> {
+        match self {
+            Operation::SetCellValues { sheet_pos, .. }
+            | Operation::SetCodeRun { sheet_pos, .. }
+            | Operation::SetCodeRunVersion { sheet_pos, .. }
+            | Operation::ComputeCode { sheet_pos }
+            | Operation::SetValidationWarning { sheet_pos, .. }
+            | Operation::SetCodeRunRefresh { sheet_pos, .. } => vec![sheet_pos.sheet_id],
+            Operation::SetCellFormats { sheet_rect, .. }
+            | Operation::SetBorders { sheet_rect, .. }
+            | Operation::SetCursor { sheet_rect }
+            | Operation::MergeCells { sheet_rect }
+            | Operation::UnmergeCells { sheet_rect } => vec![sheet_rect.sheet_id],
+            Operation::SetCellFormatsSelection { selection, .. }
+            | Operation::SetBordersSelection { selection, .. }
+            | Operation::SetCursorSelection { selection } => vec![selection.sheet_id],
+            Operation::SetCursorA1 { selection } => vec![selection.sheet_id],
+            Operation::SetValidation { validation } => vec![validation.selection.sheet_id],
+            Operation::SetConditionalFormat { conditional_format } => {
+                vec![conditional_format.selection.sheet_id]
+            }
+            Operation::SetCellFormatsA1 { sheet_id, .. }
+            | Operation::SetBordersA1 { sheet_id, .. }
+            | Operation::DuplicateSheet { sheet_id, .. }
+            | Operation::DeleteSheet { sheet_id }
+            | Operation::SetSheetName { sheet_id, .. }
+            | Operation::SetSheetColor { sheet_id, .. }
+            | Operation::ResizeColumn { sheet_id, .. }
+            | Operation::ResizeRow { sheet_id, .. }
+            | Operation::ResizeRows { sheet_id, .. }
+            | Operation::RemoveValidation { sheet_id, .. }
+            | Operation::DeleteColumn { sheet_id, .. }
+            | Operation::DeleteRow { sheet_id, .. }
+            | Operation::InsertColumn { sheet_id, .. }
+            | Operation::InsertRow { sheet_id, .. }
+            | Operation::SetColumnsHidden { sheet_id, .. }
+            | Operation::SetRowsHidden { sheet_id, .. }
+            | Operation::SetColumnGroups { sheet_id, .. }
+            | Operation::SetRowGroups { sheet_id, .. }
+            | Operation::RemoveConditionalFormat { sheet_id, .. }
+            | Operation::SetCommentThread { sheet_id, .. }
+            | Operation::RemoveCommentThread { sheet_id, .. }
+            | Operation::AddCommentReply { sheet_id, .. }
+            | Operation::RemoveCommentReply { sheet_id, .. }
+            | Operation::SetCommentThreadResolved { sheet_id, .. }
+            | Operation::SetProtectedRange { sheet_id, .. }
+            | Operation::RemoveProtectedRange { sheet_id, .. } => vec![*sheet_id],
+            Operation::ReorderSheet { target, .. } => vec![*target],
+            Operation::MoveCells { source, dest } => vec![source.sheet_id, dest.sheet_id],
+            Operation::AddSheet { .. } | Operation::AddSheetSchema { .. } => vec![],
+        }
+    }
+
+    /// Returns the finite rects that this operation covers, or None if a rect
+    /// overflows the grid or a resize is invalid. Cursor operations are not
+    /// included since they do not change the grid. Column, row, and sheet
+    /// formats and borders are not included since they are stored as a single
+    /// range.
+    pub fn sheet_rects(&self) -> Option<Vec<SheetRect>> {
+        let valid_size = |size: f64| size.is_finite() && size >= 0.0;
+        let rects = match self {
+            Operation::SetCellValues { sheet_pos, values } => {
+                if values.w == 0 || values.h == 0 {
+                    return Some(vec![]);
+                }
+                let max_x = sheet_pos.x.checked_add(values.w as i64 - 1)?;
+                let max_y = sheet_pos.y.checked_add(values.h as i64 - 1)?;
+                vec![SheetRect::new(
+                    sheet_pos.x,
+                    sheet_pos.y,
+                    max_x,
+                    max_y,
+                    sheet_pos.sheet_id,
+                )]
+            }
+            Operation::SetCellFormats { sheet_rect, .. }
+            | Operation::SetBorders { sheet_rect, .. }
+            | Operation::MergeCells { sheet_rect }
+            | Operation::UnmergeCells { sheet_rect } => vec![*sheet_rect],
+            Operation::MoveCells { source, .. } => vec![*source],
+            Operation::SetCellFormatsA1 { sheet_id, formats } => formats
+                .finite_rects()
+                .into_iter()
+                .map(|rect| rect.to_sheet_rect(*sheet_id))
+                .collect(),
+            Operation::SetBordersA1 { sheet_id, borders } => borders
+                .finite_rects()
+                .into_iter()
+                .map(|rect| rect.to_sheet_rect(*sheet_id))
+                .collect(),
+            Operation::ResizeColumn {
+                sheet_id,
+                column,
+                new_size,
+                ..
+            } => {
+                if *column < 1 || !valid_size(*new_size) {
+                    return None;
+                }
+                vec![SheetRect::new(*column, 1, *column, 1, *sheet_id)]
+            }
+            Operation::ResizeRow {
+                sheet_id,
+                row,
+                new_size,
+                ..
+            } => {
+                if *row < 1 || !valid_size(*new_size) {
+                    return None;
+                }
+                vec![SheetRect::new(1, *row, 1, *row, *sheet_id)]
+            }
+            Operation::ResizeRows {
+                sheet_id,
+                row_heights,
+            } => row_heights
+                .iter()
+                .map(|row_height| {
+                    (row_height.row >= 1 && valid_size(row_height.height))
+                        .then(|| SheetRect::new(1, row_height.row, 1, row_height.row, *sheet_id))
+                })
+                .collect::<Option<Vec<_>>>()?,
+            _ => vec![],
+        };
+        Some(rects)
+    }
+}
+
+/// Number of cells in a rect, or None if the rect is inverted.
+fn rect_cells(sheet_rect: &SheetRect) -> Option<u64> {
+    let width = sheet_rect.max.x as i128 - sheet_rect.min.x as i128 + 1;
+    let height = sheet_rect.max.y as i128 - sheet_rect.min.y as i128 + 1;
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+    Some(u64::try_from(width * height).unwrap_or(u64::MAX))
+}
+
+/// Validates a batch of operations against `limits`.
+///
+/// `deleted_sheets` contains the sheets that were deleted by earlier
+/// transactions. It is updated with the sheets deleted and restored by this
+/// batch, so callers should pass a copy and only keep it if validation
+/// succeeds.
+pub fn validate_operations(
+    operations: &[Operation],
+    limits: &OperationLimits,
+    deleted_sheets: &mut HashSet<SheetId>,
+) -> Result<(), OperationValidationError> {
+    if operations.len() > limits.max_operations {
+        return Err(OperationValidationError::TooManyOperations(
+            operations.len(),
+            limits.max_operations,
+        ));
+    }
+
+    for (index, operation) in operations.iter().enumerate() {
+        if let Some(sheet_id) = operation
+            .sheet_ids()
+            .into_iter()
+            .find(|sheet_id| deleted_sheets.contains(sheet_id))
+        {
+            return Err(OperationValidationError::DeletedSheet(index, sheet_id));
+        }
+
+        let sheet_rects = operation
+            .sheet_rects()
+            .ok_or(OperationValidationError::InvalidRect(index))?;
+        for sheet_rect in sheet_rects {
+            let cells =
+                rect_cells(&sheet_rect).ok_or(OperationValidationError::InvalidRect(index))?;
+            if cells > limits.max_rect_cells {
+                return Err(OperationValidationError::RectTooLarge(
+                    index,
+                    cells,
+                    limits.max_rect_cells,
+                ));
+            }
+        }
+
+        match operation {
+            Operation::DeleteSheet { sheet_id } => {
+                deleted_sheets.insert(*sheet_id);
+            }
+            // undoing a delete restores the sheet with the same id
+            Operation::AddSheet { sheet } => {
+                deleted_sheets.remove(&sheet.id);
+            }
+            Operation::AddSheetSchema { schema } => {
+                let sheet_id = schema
+                    .sheet_id()
+                    .map_err(|_| OperationValidationError::InvalidSheetId(index))?;
+                deleted_sheets.remove(&sheet_id);
+            }
+            Operation::DuplicateSheet { new_sheet_id, .. } => {
+                deleted_sheets.remove(new_sheet_id);
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{
+        cell_values::CellValues,
+        grid::{
+            formats::SheetFormatUpdates, js_types::JsRowHeight, sheet::borders::BordersUpdates,
+            Contiguous2D, Sheet,
+        },
+        A1Selection, ClearOption, SheetPos,
+    };
+
+    fn set_values(sheet_id: SheetId, x: i64, w: u32) -> Operation {
+        Operation::SetCellValues {
+            sheet_pos: SheetPos { x, y: 1, sheet_id },
+            values: CellValues::new(w, 1),
+        }
+    }
+
+    #[test]
+    fn test_validate_operations() {
+        let sheet_id = SheetId::new();
+        let mut deleted = HashSet::new();
+        let limits = OperationLimits::default();
+
+        let operations = vec![
+            set_values(sheet_id, 1, 10),
+            Operation::MergeCells {
+                sheet_rect: SheetRect::new(1, 1, 2, 2, sheet_id),
+            },
+        ];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_validate_operations_limits() {
+        let sheet_id = SheetId::new();
+        let mut deleted = HashSet::new();
+        let limits = OperationLimits {
+            max_operations: 2,
+            max_rect_cells: 100,
+        };
+
+        let operations = vec![set_values(sheet_id, 1, 1); 3];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::TooManyOperations(3, 2))
+        );
+
+        let operations = vec![Operation::MoveCells {
+            source: SheetRect::new(1, 1, 100, 100, sheet_id),
+            dest: SheetPos::new(sheet_id, 1, 200),
+        }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::RectTooLarge(0, 10_000, 100))
+        );
+
+        let operations = vec![
+            set_values(sheet_id, 1, 1),
+            Operation::MergeCells {
+                sheet_rect: SheetRect::new(5, 5, 1, 1, sheet_id),
+            },
+        ];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::InvalidRect(1))
+        );
+
+        // overflowing values
+        let operations = vec![set_values(sheet_id, i64::MAX, 2)];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::InvalidRect(0))
+        );
+
+        // unbounded rects are too large
+        let operations = vec![Operation::UnmergeCells {
+            sheet_rect: SheetRect::new(1, 1, i64::MAX, i64::MAX, sheet_id),
+        }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::RectTooLarge(0, u64::MAX, 100))
+        );
+    }
+
+    #[test]
+    fn test_validate_operations_a1_and_resize() {
+        let sheet_id = SheetId::new();
+        let mut deleted = HashSet::new();
+        let limits = OperationLimits {
+            max_operations: 10,
+            max_rect_cells: 100,
+        };
+
+        let formats = SheetFormatUpdates {
+            bold: Contiguous2D::new_from_opt_selection(
+                &A1Selection::test_a1("A1:Z100"),
+                Some(ClearOption::Some(true)),
+            ),
+            ..Default::default()
+        };
+        let operations = vec![Operation::SetCellFormatsA1 { sheet_id, formats }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::RectTooLarge(0, 2600, 100))
+        );
+
+        // column formats are stored as a single range
+        let formats = SheetFormatUpdates {
+            bold: Contiguous2D::new_from_opt_selection(
+                &A1Selection::test_a1("A:Z"),
+                Some(ClearOption::Some(true)),
+            ),
+            ..Default::default()
+        };
+        let operations = vec![Operation::SetCellFormatsA1 { sheet_id, formats }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Ok(())
+        );
+
+        let borders = BordersUpdates {
+            top: Contiguous2D::new_from_opt_selection(
+                &A1Selection::test_a1("A1:J20"),
+                Some(ClearOption::Clear),
+            ),
+            ..Default::default()
+        };
+        let operations = vec![Operation::SetBordersA1 { sheet_id, borders }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::RectTooLarge(0, 200, 100))
+        );
+
+        let operations = vec![
+            Operation::ResizeColumn {
+                sheet_id,
+                column: 2,
+                new_size: 120.0,
+                client_resized: false,
+            },
+            Operation::ResizeRow {
+                sheet_id,
+                row: 0,
+                new_size: 20.0,
+                client_resized: false,
+            },
+        ];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::InvalidRect(1))
+        );
+
+        let operations = vec![Operation::ResizeRows {
+            sheet_id,
+            row_heights: vec![
+                JsRowHeight {
+                    row: 1,
+                    height: 20.0,
+                },
+                JsRowHeight {
+                    row: 2,
+                    height: f64::NAN,
+                },
+            ],
+        }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::InvalidRect(0))
+        );
+
+        // operations on deleted sheets are rejected
+        deleted.insert(sheet_id);
+        let operations = vec![Operation::ResizeColumn {
+            sheet_id,
+            column: 2,
+            new_size: 120.0,
+            client_resized: false,
+        }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted),
+            Err(OperationValidationError::DeletedSheet(0, sheet_id))
+        );
+    }
+
+    #[test]
+    fn test_validate_operations_deleted_sheets() {
+        let sheet = Sheet::test();
+        let sheet_id = sheet.id;
+        let limits = OperationLimits::default();
+        let mut deleted = HashSet::new();
+
+        let operations = vec![
+            Operation::DeleteSheet { sheet_id },
+            set_values(sheet_id, 1, 1),
+        ];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted.clone()),
+            Err(OperationValidationError::DeletedSheet(1, sheet_id))
+        );
+
+        let operations = vec![Operation::DeleteSheet { sheet_id }];
+        validate_operations(&operations, &limits, &mut deleted).unwrap();
+        assert!(deleted.contains(&sheet_id));
+
+        let operations = vec![Operation::SetSheetName {
+            sheet_id,
+            name: "Sheet 2".to_string(),
+        }];
+        assert_eq!(
+            validate_operations(&operations, &limits, &mut deleted.clone()),
+            Err(OperationValidationError::DeletedSheet(0, sheet_id))
+        );
+
+        // undo restores the sheet
+        let operations = vec![
+            Operation::AddSheet {
+                sheet: Box::new(sheet),
+            },
+            set_values(sheet_id, 1, 1),
+        ];
+        validate_operations(&operations, &limits, &mut deleted).unwrap();
+        assert!(deleted.is_empty());
+    }
+}
@@ -0,0 +1,395 @@
+//! What-if analysis: Goal Seek and data tables.
+//!
+//! Both try input values in a scratch copy of the sheets the formula reads,
+//! so the grid is only changed by the returned operations, which write the
+//! results as values. The formula may only depend on values and other
+//! formulas, since other code cells can't run in the copy.
+
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use bigdecimal::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use super::operation::Operation;
+use crate::{
+    cell_values::CellValues,
+    controller::{
+        active_transactions::{transaction_name::TransactionName, ActiveTransactions},
+        host::{NativeHost, SharedHost},
+        GridController,
+    },
+    grid::{CodeCellLanguage, Grid, SheetId},
+    CellValue, Pos, Rect, SheetPos, SheetRect,
+};
+
+/// Maximum number of input values Goal Seek tries.
+const GOAL_SEEK_MAX_ITERATIONS: u32 = 100;
+
+/// Goal Seek stops when the formula is this close to the target, relative to
+/// the target (or absolute for targets smaller than 1).
+const GOAL_SEEK_TOLERANCE: f64 = 1e-9;
+
+/// Maximum number of results in a data table.
+const DATA_TABLE_MAX_CELLS: usize = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub struct GoalSeekResult {
+    /// Whether the formula reached the target. The input cell is only
+    /// changed when it did.
+    pub found: bool,
+
+    /// The last input value tried.
+    pub input_value: f64,
+
+    /// The formula's value for `input_value`.
+    pub formula_value: f64,
+
+    pub iterations: u32,
+}
+
+/// An input of a data table: `input` is set to each of the values in the
+/// `values` cells.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub struct DataTableInput {
+    pub input: SheetPos,
+    pub values: SheetRect,
+}
+
+/// A copy of the sheets a formula reads that formulas are recalculated in.
+struct Scratch(GridController);
+
+impl Scratch {
+    /// Copies `sheets`. The copies have the same code runs, so the
+    /// dependency index and parsed formulas are reused instead of rebuilt.
+    fn new(gc: &GridController, sheets: &HashSet<SheetId>) -> Self {
+        let mut grid = Grid::new_blank();
+        for sheet in gc.grid.sheets() {
+            if sheets.contains(&sheet.id) {
+                grid.add_sheet(Some(sheet.clone()));
+            }
+        }
+        Scratch(GridController {
+            grid,
+            transactions: ActiveTransactions::new(0),
+            dependencies: gc.dependencies.clone(),
+            host: SharedHost::new(NativeHost),
+            formula_cache: gc.formula_cache.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Sets the inputs and returns the formula's value.
+    fn evaluate(&mut self, inputs: &[(SheetPos, CellValue)], formula: SheetPos) -> CellValue {
+        let ops = inputs
+            .iter()
+            .map(|(sheet_pos, value)| Operation::SetCellValues {
+                sheet_pos: *sheet_pos,
+                values: CellValues::from(value.clone()),
+            })
+            .collect();
+        self.0
+            .start_user_transaction(ops, None, TransactionName::Unknown);
+        self.0.undo_stack.clear();
+
+        self.0
+            .try_sheet(formula.sheet_id)
+            .and_then(|sheet| sheet.display_value(formula.into()))
+            .unwrap_or(CellValue::Blank)
+    }
+
+    /// Sets the input and returns the formula's value if it's a number.
+    fn evaluate_number(&mut self, input: SheetPos, value: f64, formula: SheetPos) -> Option<f64> {
+        match self.evaluate(&[(input, CellValue::from(value))], formula) {
+            CellValue::Number(n) => n.to_f64(),
+            _ => None,
+        }
+    }
+}
+
+/// Finds `x` where `f(x)` is 0 with the secant method, starting at `x0`.
+/// Once two values with different signs are found, steps are kept between
+/// them (falling back to bisection), so the search can't diverge.
+///
+/// Returns the last value tried, its result, and whether it's a root.
+fn solve(mut f: impl FnMut(f64) -> Option<f64>, x0: f64, tolerance: f64) -> (f64, f64, u32, bool) {
+    let Some(f0) = f(x0) else {
+        return (x0, f64::NAN, 1, false);
+    };
+    if f0.abs() <= tolerance {
+        return (x0, f0, 1, true);
+    }
+
+    let (mut a, mut fa) = (x0, f0);
+    let mut b = if x0 == 0.0 { 1.0 } else { x0 * 1.01 };
+    let Some(mut fb) = f(b) else {
+        return (x0, f0, 2, false);
+    };
+    let mut bracket = None;
+
+    for iterations in 2..GOAL_SEEK_MAX_ITERATIONS {
+        if fb.abs() <= tolerance {
+            return (b, fb, iterations, true);
+        }
+        if fa.signum() != fb.signum() {
+            bracket = Some(((a, fa), (b, fb)));
+        }
+
+        let mut next = b - fb * (b - a) / (fb - fa);
+        if let Some(((lo, _), (hi, _))) = bracket {
+            if !next.is_finite() || next <= lo.min(hi) || next >= lo.max(hi) {
+                next = (lo + hi) / 2.0;
+            }
+        }
+        if !next.is_finite() {
+            // the formula doesn't change with the input
+            return (b, fb, iterations, false);
+        }
+
+        let Some(f_next) = f(next) else {
+            return (b, fb, iterations + 1, false);
+        };
+        if let Some(((lo, f_lo), (hi, f_hi))) = bracket {
+            bracket = Some(if f_next.signum() == f_lo.signum() {
+                ((next, f_next), (hi, f_hi))
+            } else {
+                ((lo, f_lo), (next, f_next))
+            });
+        }
+        (a, fa) = (b, fb);
+        (b, fb) = (next, f_next);
+    }
+    (b, fb, GOAL_SEEK_MAX_ITERATIONS, fb.abs() <= tolerance)
+}
+
+impl GridController {
+    /// Returns an error if `sheet_pos` is not a formula, or if it depends on
+    /// code cells that aren't formulas. Returns the sheets it reads,
+    /// directly or through other formulas.
+    fn what_if_formula(&self, sheet_pos: SheetPos) -> Result<HashSet<SheetId>> {
+        let sheet = self
+            .try_sheet(sheet_pos.sheet_id)
+            .context("Sheet not found")?;
+        match sheet.cell_value_ref(sheet_pos.into()) {
+            Some(CellValue::Code(code)) if code.language == CodeCellLanguage::Formula => (),
+            _ => bail!(
+                "{} must contain a formula",
+                Pos::from(sheet_pos).a1_string()
+            ),
+        }
+
+        let mut sheets = HashSet::from([sheet_pos.sheet_id]);
+        let mut visited = HashSet::from([sheet_pos]);
+        let mut pending = vec![sheet_pos];
+        while let Some(current) = pending.pop() {
+            let Some(code_run) = self
+                .try_sheet(current.sheet_id)
+                .and_then(|sheet| sheet.code_run(current.into()))
+            else {
+                continue;
+            };
+            for sheet in self.grid.sheets() {
+                if code_run.cells_accessed.len(sheet.id).is_none() {
+                    continue;
+                }
+                sheets.insert(sheet.id);
+                for (pos, run) in sheet.code_runs.iter() {
+                    let code_pos = pos.to_sheet_pos(sheet.id);
+                    if visited.contains(&code_pos)
+                        || !code_run
+                            .cells_accessed
+                            .intersects(&run.output_sheet_rect(code_pos, false))
+                    {
+                        continue;
+                    }
+                    if let Some(CellValue::Code(code)) = sheet.cell_value_ref(*pos) {
+                        if code.language != CodeCellLanguage::Formula {
+                            bail!(
+                                "{} depends on {}, which is not a formula",
+                                Pos::from(sheet_pos).a1_string(),
+                                pos.a1_string()
+                            );
+                        }
+                    }
+                    visited.insert(code_pos);
+                    pending.push(code_pos);
+                }
+            }
+        }
+        Ok(sheets)
+    }
+
+    /// Returns an error if `sheet_pos` is a code cell.
+    fn what_if_input(&self, sheet_pos: SheetPos) -> Result<()> {
+        let sheet = self
+            .try_sheet(sheet_pos.sheet_id)
+            .context("Sheet not found")?;
+        match sheet.cell_value_ref(sheet_pos.into()) {
+            Some(CellValue::Code(_)) => {
+                bail!("{} must contain a value", Pos::from(sheet_pos).a1_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Finds the value of `input` that makes `formula` equal `target`.
+    /// Returns the operation that writes it to `input`, if one was found.
+    pub fn goal_seek_operations(
+        &self,
+        formula: SheetPos,
+        target: f64,
+        input: SheetPos,
+    ) -> Result<(Vec<Operation>, GoalSeekResult)> {
+        let mut sheets = self.what_if_formula(formula)?;
+        self.what_if_input(input)?;
+        sheets.insert(input.sheet_id);
+
+        let start = match self
+            .try_sheet(input.sheet_id)
+            .and_then(|sheet| sheet.display_value(input.into()))
+        {
+            Some(CellValue::Number(n)) => n.to_f64().unwrap_or_default(),
+            Some(CellValue::Blank) | None => 0.0,
+            _ => bail!("{} must contain a number", Pos::from(input).a1_string()),
+        };
+
+        let mut scratch = Scratch::new(self, &sheets);
+        let tolerance = GOAL_SEEK_TOLERANCE * target.abs().max(1.0);
+        let (input_value, difference, iterations, found) = solve(
+            |x| Some(scratch.evaluate_number(input, x, formula)? - target),
+            start,
+            tolerance,
+        );
+        if difference.is_nan() {
+            bail!(
+                "{} must evaluate to a number",
+                Pos::from(formula).a1_string()
+            );
+        }
+
+        let ops = if found {
+            vec![Operation::SetCellValues {
+                sheet_pos: input,
+                values: CellValues::from(CellValue::from(input_value)),
+            }]
+        } else {
+            vec![]
+        };
+        let result = GoalSeekResult {
+            found,
+            input_value,
+            formula_value: difference + target,
+            iterations,
+        };
+        Ok((ops, result))
+    }
+
+    /// Evaluates `formula` for each of the values of `column_input` (one row
+    /// each) and `row_input` (one column each). Returns the operation that
+    /// writes the results at `output`.
+    pub fn data_table_operations(
+        &self,
+        formula: SheetPos,
+        column_input: Option<DataTableInput>,
+        row_input: Option<DataTableInput>,
+        output: SheetPos,
+    ) -> Result<Vec<Operation>> {
+        let mut sheets = self.what_if_formula(formula)?;
+        if column_input.is_none() && row_input.is_none() {
+            bail!("A data table needs a row or column input");
+        }
+        sheets.extend(
+            column_input
+                .iter()
+                .chain(row_input.iter())
+                .map(|input| input.input.sheet_id),
+        );
+
+        let values =
+            |input: &Option<DataTableInput>| -> Result<Vec<Option<(SheetPos, CellValue)>>> {
+                let Some(input) = input else {
+                    return Ok(vec![None]);
+                };
+                self.what_if_input(input.input)?;
+                let sheet = self
+                    .try_sheet(input.values.sheet_id)
+                    .context("Sheet not found")?;
+                Ok(Rect::from(input.values)
+                    .iter()
+                    .map(|pos| {
+                        let value = sheet.display_value(pos).unwrap_or(CellValue::Blank);
+                        Some((input.input, value))
+                    })
+                    .collect())
+            };
+        let rows = values(&column_input)?;
+        let columns = values(&row_input)?;
+        if rows.len() * columns.len() > DATA_TABLE_MAX_CELLS {
+            bail!("A data table can have at most {DATA_TABLE_MAX_CELLS} results");
+        }
+
+        // the results can't replace the cells the table is computed from
+        let output_rect = SheetRect::from_numbers(
+            output.x,
+            output.y,
+            columns.len() as i64,
+            rows.len() as i64,
+            output.sheet_id,
+        );
+        let overlaps = output_rect.contains(formula)
+            || column_input.iter().chain(row_input.iter()).any(|input| {
+                output_rect.contains(input.input) || output_rect.intersects(input.values)
+            });
+        if overlaps {
+            bail!("The data table can't overlap its formula, inputs or values");
+        }
+
+        let mut scratch = Scratch::new(self, &sheets);
+        let mut results = CellValues::new(columns.len() as u32, rows.len() as u32);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, column) in columns.iter().enumerate() {
+                let inputs = row.iter().chain(column.iter()).cloned().collect::<Vec<_>>();
+                let value = scratch.evaluate(&inputs, formula);
+                results.set(x as u32, y as u32, value);
+            }
+        }
+
+        Ok(vec![Operation::SetCellValues {
+            sheet_pos: output,
+            values: results,
+        }])
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        // linear and non-linear functions
+        let (x, _, _, found) = solve(|x| Some(3.0 * x - 12.0), 0.0, 1e-9);
+        assert!(found);
+        assert!((x - 4.0).abs() < 1e-9);
+
+        let (x, _, _, found) = solve(|x| Some(x * x - 2.0), 1.0, 1e-9);
+        assert!(found);
+        assert!((x - 2f64.sqrt()).abs() < 1e-9);
+
+        // no solution
+        let (_, _, _, found) = solve(|x| Some(x * x + 1.0), 3.0, 1e-9);
+        assert!(!found);
+
+        // constant function
+        let (_, _, _, found) = solve(|_| Some(5.0), 3.0, 1e-9);
+        assert!(!found);
+
+        // not a number
+        let (_, difference, _, found) = solve(|_| None, 3.0, 1e-9);
+        assert!(!found);
+        assert!(difference.is_nan());
+    }
+}
@@ -14,20 +14,17 @@ use super::{active_transactions::pending_transaction::PendingTransaction, GridCo
 
 impl GridController {
     pub fn send_viewport_buffer(&mut self) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+        if !self.host.renders() {
             return;
         }
 
         let viewport_buffer = ViewportBuffer::default();
-        crate::wasm_bindings::js::jsSendViewportBuffer(viewport_buffer.get_buffer());
+        self.host.send_viewport_buffer(&viewport_buffer);
         self.viewport_buffer = Some(viewport_buffer);
     }
 
     pub fn process_visible_dirty_hashes(&self, transaction: &mut PendingTransaction) {
-        if (!cfg!(target_family = "wasm") && !cfg!(test))
-            || transaction.is_server()
-            || transaction.dirty_hashes.is_empty()
-        {
+        if !self.host.renders() || transaction.is_server() || transaction.dirty_hashes.is_empty() {
             return;
         }
 
@@ -69,7 +66,7 @@ impl GridController {
         sheet_id: SheetId,
         dirty_hashes: Vec<Pos>,
     ) -> HashSet<Pos> {
-        if (!cfg!(target_family = "wasm") && !cfg!(test)) || dirty_hashes.is_empty() {
+        if !self.host.renders() || dirty_hashes.is_empty() {
             return HashSet::new();
         }
 
@@ -86,7 +83,7 @@ impl GridController {
                             && pos.y >= top_left.y
                             && pos.y <= bottom_right.y
                         {
-                            sheet.send_render_cells_in_hash(pos);
+                            sheet.send_render_cells_in_hash(&*self.host, pos);
                         } else {
                             remaining_hashes.insert(pos);
                         }
@@ -98,10 +95,7 @@ impl GridController {
     }
 
     pub fn process_remaining_dirty_hashes(&self, transaction: &mut PendingTransaction) {
-        if (!cfg!(target_family = "wasm") && !cfg!(test))
-            || transaction.is_server()
-            || transaction.dirty_hashes.is_empty()
-        {
+        if !self.host.renders() || transaction.is_server() || transaction.dirty_hashes.is_empty() {
             return;
         }
 
@@ -113,13 +107,13 @@ impl GridController {
     }
 
     pub fn flag_hashes_dirty(&self, sheet_id: SheetId, dirty_hashes: &HashSet<Pos>) {
-        if (!cfg!(target_family = "wasm") && !cfg!(test)) || dirty_hashes.is_empty() {
+        if !self.host.renders() || dirty_hashes.is_empty() {
             return;
         }
 
         let hashes = dirty_hashes.iter().cloned().collect::<Vec<Pos>>();
         if let Ok(hashes_string) = serde_json::to_string(&hashes) {
-            crate::wasm_bindings::js::jsHashesDirty(sheet_id.to_string(), hashes_string);
+            self.host.hashes_dirty(sheet_id.to_string(), hashes_string);
         }
     }
 
@@ -127,14 +121,14 @@ impl GridController {
         // send the modified cells to the render web worker
         modified.iter().for_each(|hash| {
             if let Some(sheet) = self.try_sheet(sheet_id) {
-                sheet.send_render_cells_in_hash(*hash);
+                sheet.send_render_cells_in_hash(&*self.host, *hash);
             }
         });
     }
 
     /// Sends the modified cell sheets to the render web worker
     pub fn send_render_cells(&self, sheet_rect: &SheetRect) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+        if !self.host.renders() {
             return;
         }
 
@@ -154,18 +148,19 @@ impl GridController {
     }
 
     pub fn send_all_fills(&self, sheet_id: SheetId) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+        if !self.host.renders() {
             return;
         }
 
         if let Some(sheet) = self.try_sheet(sheet_id) {
             let fills = sheet.get_all_render_fills();
             if let Ok(fills) = serde_json::to_string(&fills) {
-                crate::wasm_bindings::js::jsSheetFills(sheet_id.to_string(), fills);
+                self.host.sheet_fills(sheet_id.to_string(), fills);
             }
             let sheet_fills = sheet.get_all_sheet_fills();
             if let Ok(sheet_fills) = serde_json::to_string(&sheet_fills) {
-                crate::wasm_bindings::js::jsSheetMetaFills(sheet_id.to_string(), sheet_fills);
+                self.host
+                    .sheet_meta_fills(sheet_id.to_string(), sheet_fills);
             }
         }
     }
@@ -178,10 +173,10 @@ impl GridController {
             false
         };
 
-        if cfg!(target_family = "wasm") && recalculated {
+        if self.host.renders() && recalculated {
             if let Some(sheet) = self.try_sheet(sheet_id) {
                 if let Ok(sheet_info) = serde_json::to_string(&SheetBounds::from(sheet)) {
-                    crate::wasm_bindings::js::jsSheetBoundsUpdate(sheet_info);
+                    self.host.sheet_bounds_update(sheet_info);
                 }
             }
         };
@@ -189,7 +184,7 @@ impl GridController {
 
     /// Sends html output to the client within a sheetRect
     pub fn send_html_output_rect(&self, sheet_rect: &SheetRect) {
-        if cfg!(target_family = "wasm") | cfg!(test) {
+        if self.host.renders() {
             if let Some(sheet) = self.try_sheet(sheet_rect.sheet_id) {
                 sheet
                     .get_html_output()
@@ -203,7 +198,7 @@ impl GridController {
                     })
                     .for_each(|html_output| {
                         if let Ok(html) = serde_json::to_string(&html_output) {
-                            crate::wasm_bindings::js::jsUpdateHtml(html);
+                            self.host.update_html(html);
                         }
                     });
             }
@@ -212,14 +207,12 @@ impl GridController {
 
     /// Sends add sheet to the client
     pub fn send_add_sheet(&self, sheet_id: SheetId, transaction: &PendingTransaction) {
-        if (cfg!(target_family = "wasm") || cfg!(test)) && !transaction.is_server() {
+        if self.host.renders() && !transaction.is_server() {
             if let Some(sheet) = self.try_sheet(sheet_id) {
                 let sheet_info = SheetInfo::from(sheet);
                 if let Ok(sheet_info) = serde_json::to_string(&sheet_info) {
-                    crate::wasm_bindings::js::jsAddSheet(
-                        sheet_info,
-                        transaction.is_user_undo_redo(),
-                    );
+                    self.host
+                        .add_sheet(sheet_info, transaction.is_user_undo_redo());
                 }
             }
         }
@@ -227,21 +220,19 @@ impl GridController {
 
     /// Sends delete sheet to the client
     pub fn send_delete_sheet(&self, sheet_id: SheetId, transaction: &PendingTransaction) {
-        if (cfg!(target_family = "wasm") || cfg!(test)) && !transaction.is_server() {
-            crate::wasm_bindings::js::jsDeleteSheet(
-                sheet_id.to_string(),
-                transaction.is_user_undo_redo(),
-            );
+        if self.host.renders() && !transaction.is_server() {
+            self.host
+                .delete_sheet(sheet_id.to_string(), transaction.is_user_undo_redo());
         }
     }
 
     /// Sends sheet info to the client
     pub fn send_sheet_info(&self, sheet_id: SheetId) {
-        if cfg!(target_family = "wasm") || cfg!(test) {
+        if self.host.renders() {
             if let Some(sheet) = self.try_sheet(sheet_id) {
                 let sheet_info = SheetInfo::from(sheet);
                 if let Ok(sheet_info) = serde_json::to_string(&sheet_info) {
-                    crate::wasm_bindings::js::jsSheetInfoUpdate(sheet_info);
+                    self.host.sheet_info_update(sheet_info);
                 }
             }
         }
@@ -253,7 +244,7 @@ impl GridController {
         sheet_id: SheetId,
         offsets: &HashMap<(Option<i64>, Option<i64>), f64>,
     ) {
-        if cfg!(target_family = "wasm") || cfg!(test) {
+        if self.host.renders() {
             let mut offsets = offsets
                 .iter()
                 .map(|(&(column, row), &size)| JsOffset {
@@ -264,12 +255,12 @@ impl GridController {
                 .collect::<Vec<JsOffset>>();
             offsets.sort_by(|a, b| a.row.cmp(&b.row).then(a.column.cmp(&b.column)));
             let offsets = serde_json::to_string(&offsets).unwrap();
-            crate::wasm_bindings::js::jsOffsetsModified(sheet_id.to_string(), offsets);
+            self.host.offsets_modified(sheet_id.to_string(), offsets);
         }
     }
 
     pub fn send_image(&self, sheet_pos: SheetPos) {
-        if cfg!(target_family = "wasm") || cfg!(test) {
+        if self.host.renders() {
             if let Some(sheet) = self.try_sheet(sheet_pos.sheet_id) {
                 let image = sheet.code_run(sheet_pos.into()).and_then(|code_run| {
                     code_run
@@ -285,7 +276,7 @@ impl GridController {
                     (None, None)
                 };
 
-                crate::wasm_bindings::js::jsSendImage(
+                self.host.send_image(
                     sheet_pos.sheet_id.to_string(),
                     sheet_pos.x as i32,
                     sheet_pos.y as i32,
@@ -299,14 +290,12 @@ impl GridController {
 
     /// Send transaction progress to client
     pub fn send_transaction_progress(&self, transaction: &PendingTransaction) {
-        if (!cfg!(target_family = "wasm") && !cfg!(test)) || transaction.is_server() {
+        if transaction.is_server() {
             return;
         }
 
-        crate::wasm_bindings::js::jsTransactionProgress(
-            transaction.id.to_string(),
-            transaction.operations.len() as i32,
-        );
+        self.host
+            .transaction_progress(transaction.id, transaction.operations.len());
     }
 }
 
@@ -465,6 +454,7 @@ mod test {
             line_number: None,
             output_display_type: None,
             cancel_compute: None,
+            output_arrow: None,
         });
 
         expect_js_call(
@@ -505,6 +495,7 @@ mod test {
             line_number: None,
             output_display_type: None,
             cancel_compute: None,
+            output_arrow: None,
         })
         .unwrap();
 
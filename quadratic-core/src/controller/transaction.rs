@@ -8,8 +8,8 @@ use super::execution::TransactionSource;
 use super::operations::operation::Operation;
 use super::GridController;
 use crate::compression::{
-    add_header, decompress_and_deserialize, deserialize, remove_header, serialize,
-    serialize_and_compress, CompressionFormat, SerializationFormat,
+    add_header, decompress_and_deserialize, decompress_and_deserialize_bounded, deserialize,
+    remove_header, serialize, serialize_and_compress, CompressionFormat, SerializationFormat,
 };
 
 pub static SERIALIZATION_FORMAT: SerializationFormat = SerializationFormat::Json;
@@ -74,6 +74,24 @@ impl Transaction {
 
         decompress_and_deserialize::<T>(&SERIALIZATION_FORMAT, &COMPRESSION_FORMAT, data)
     }
+
+    /// Same as `decompress_and_deserialize`, but fails if the decompressed
+    /// operations are larger than `max_bytes`.  Used for operations received
+    /// from untrusted clients.
+    pub fn decompress_and_deserialize_bounded<T: DeserializeOwned>(
+        operations: &[u8],
+        max_bytes: usize,
+    ) -> Result<T> {
+        let (header, data) = remove_header(operations)?;
+        let _version = deserialize::<TransactionVersion>(&HEADER_SERIALIZATION_FORMAT, header)?;
+
+        decompress_and_deserialize_bounded::<T>(
+            &SERIALIZATION_FORMAT,
+            &COMPRESSION_FORMAT,
+            data,
+            max_bytes,
+        )
+    }
 }
 
 // Transaction received from Server
@@ -13,6 +13,13 @@ pub struct JsCodeResult {
     pub output_array: Option<Vec<Vec<Vec<String>>>>,
     pub output_display_type: Option<String>,
     pub cancel_compute: Option<bool>,
+
+    /// Typed output as an Arrow IPC stream, used instead of `output_array`
+    /// (see [`crate::arrow`]). It's passed to `calculationCompleteArrow`
+    /// separately rather than as JSON.
+    #[serde(skip)]
+    #[ts(skip)]
+    pub output_arrow: Option<Vec<u8>>,
 }
 
 impl JsCodeResult {
@@ -39,6 +46,7 @@ impl JsCodeResult {
             line_number,
             output_display_type,
             cancel_compute,
+            output_arrow: None,
         }
     }
 }
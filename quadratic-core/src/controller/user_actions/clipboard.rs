@@ -271,6 +271,9 @@ mod test {
                 cell_type: None,
                 underline: None,
                 strike_through: None,
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
         assert_eq!(
@@ -291,7 +294,10 @@ mod test {
                 date_time: None,
                 cell_type: None,
                 underline: None,
-                strike_through: None
+                strike_through: None,
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
         assert_eq!(
@@ -312,7 +318,10 @@ mod test {
                 date_time: None,
                 cell_type: None,
                 underline: Some(true),
-                strike_through: None
+                strike_through: None,
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
         assert_eq!(
@@ -334,6 +343,9 @@ mod test {
                 cell_type: None,
                 underline: None,
                 strike_through: Some(true),
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
     }
@@ -789,7 +801,10 @@ mod test {
                 date_time: None,
                 cell_type: None,
                 underline: None,
-                strike_through: None
+                strike_through: None,
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
         assert_eq!(
@@ -806,7 +821,10 @@ mod test {
                 date_time: None,
                 cell_type: None,
                 underline: None,
-                strike_through: None
+                strike_through: None,
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
     }
@@ -936,7 +954,10 @@ mod test {
                 date_time: None,
                 cell_type: None,
                 underline: None,
-                strike_through: None
+                strike_through: None,
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
         assert_eq!(
@@ -953,7 +974,10 @@ mod test {
                 date_time: None,
                 cell_type: None,
                 underline: None,
-                strike_through: None
+                strike_through: None,
+                font_size: None,
+                font_family: None,
+                text_rotation: None,
             }
         );
     }
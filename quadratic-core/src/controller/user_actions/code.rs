@@ -1,6 +1,9 @@
 use crate::{
-    controller::{active_transactions::transaction_name::TransactionName, GridController},
-    grid::{CodeCellLanguage, SheetId},
+    controller::{
+        active_transactions::transaction_name::TransactionName, operations::operation::Operation,
+        GridController,
+    },
+    grid::{CodeCellLanguage, CodeCellRefresh, SheetId},
     SheetPos,
 };
 
@@ -34,6 +37,26 @@ impl GridController {
         let ops = self.rerun_code_cell_operations(sheet_pos);
         self.start_user_transaction(ops, cursor, TransactionName::RunCode);
     }
+
+    /// Reruns code cells returned by `code_cells_due_for_refresh`.
+    pub fn refresh_code_cells(&mut self, code_cells: &[SheetPos], cursor: Option<String>) {
+        let ops = self.refresh_code_cells_operations(code_cells);
+        if !ops.is_empty() {
+            self.start_user_transaction(ops, cursor, TransactionName::RunCode);
+        }
+    }
+
+    /// Sets when a code cell runs again without a change to the cells it
+    /// reads.
+    pub fn set_code_cell_refresh(
+        &mut self,
+        sheet_pos: SheetPos,
+        refresh: CodeCellRefresh,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::SetCodeRunRefresh { sheet_pos, refresh }];
+        self.start_user_transaction(ops, cursor, TransactionName::SetCode);
+    }
 }
 
 #[cfg(test)]
@@ -78,4 +101,71 @@ mod tests {
         assert!(matches!(get_cell(pos![C1]), crate::CellValue::Text(_)));
         assert!(matches!(get_cell(pos![C2]), crate::CellValue::Blank));
     }
+
+    #[test]
+    fn test_code_cell_refresh() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let now =
+            |gc: &GridController| gc.sheet(sheet_id).code_run(pos![B1]).unwrap().last_modified;
+
+        gc.set_code_cell(
+            pos![A1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "TODAY() + 1".to_owned(),
+            None,
+        );
+        gc.set_code_cell(
+            pos![B1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Formula,
+            "1 + 1".to_owned(),
+            None,
+        );
+        let sheet = gc.sheet(sheet_id);
+        assert!(sheet.code_run(pos![A1]).unwrap().volatile);
+        assert!(!sheet.code_run(pos![B1]).unwrap().volatile);
+
+        // volatile code cells refresh when the file is opened
+        let start = now(&gc);
+        assert!(gc.code_cells_due_for_refresh(start, false).is_empty());
+        assert_eq!(
+            gc.code_cells_due_for_refresh(start, true),
+            vec![pos![A1].to_sheet_pos(sheet_id)]
+        );
+
+        gc.set_code_cell_refresh(
+            pos![B1].to_sheet_pos(sheet_id),
+            CodeCellRefresh::Every { minutes: 5 },
+            None,
+        );
+        assert!(gc.code_cells_due_for_refresh(start, false).is_empty());
+        let later = start + chrono::Duration::minutes(5);
+        let due = gc.code_cells_due_for_refresh(later, false);
+        assert_eq!(due, vec![pos![B1].to_sheet_pos(sheet_id)]);
+
+        // running again keeps the refresh policy and resets the schedule
+        gc.refresh_code_cells(&due, None);
+        let code_run = gc.sheet(sheet_id).code_run(pos![B1]).unwrap();
+        assert_eq!(code_run.refresh, CodeCellRefresh::Every { minutes: 5 });
+        assert!(code_run.last_modified > start);
+        assert!(gc.code_cells_due_for_refresh(now(&gc), false).is_empty());
+
+        // the refresh policy is saved with the file
+        let grid = crate::grid::file::import(crate::grid::file::export(gc.grid().clone()).unwrap())
+            .unwrap();
+        let sheet = &grid.sheets()[0];
+        assert_eq!(
+            sheet.code_run(pos![B1]).unwrap().refresh,
+            CodeCellRefresh::Every { minutes: 5 }
+        );
+        assert!(sheet.code_run(pos![A1]).unwrap().volatile);
+
+        // undo the refresh, then the refresh policy
+        gc.undo(None);
+        gc.undo(None);
+        assert_eq!(
+            gc.sheet(sheet_id).code_run(pos![B1]).unwrap().refresh,
+            CodeCellRefresh::Manual
+        );
+    }
 }
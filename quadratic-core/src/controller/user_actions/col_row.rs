@@ -3,7 +3,7 @@ use crate::{
         active_transactions::transaction_name::TransactionName, operations::operation::Operation,
         GridController,
     },
-    grid::SheetId,
+    grid::{sheet::outline::OutlineAxis, SheetId},
     CopyFormats,
 };
 
@@ -69,6 +69,170 @@ impl GridController {
         }];
         self.start_user_transaction(ops, cursor, TransactionName::ManipulateColumnRow);
     }
+
+    pub fn hide_columns(&mut self, sheet_id: SheetId, columns: Vec<i64>, cursor: Option<String>) {
+        let ops = vec![Operation::SetColumnsHidden {
+            sheet_id,
+            columns,
+            hidden: true,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::ManipulateColumnRow);
+    }
+
+    pub fn unhide_columns(&mut self, sheet_id: SheetId, columns: Vec<i64>, cursor: Option<String>) {
+        let ops = vec![Operation::SetColumnsHidden {
+            sheet_id,
+            columns,
+            hidden: false,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::ManipulateColumnRow);
+    }
+
+    pub fn hide_rows(&mut self, sheet_id: SheetId, rows: Vec<i64>, cursor: Option<String>) {
+        let ops = vec![Operation::SetRowsHidden {
+            sheet_id,
+            rows,
+            hidden: true,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::ManipulateColumnRow);
+    }
+
+    pub fn unhide_rows(&mut self, sheet_id: SheetId, rows: Vec<i64>, cursor: Option<String>) {
+        let ops = vec![Operation::SetRowsHidden {
+            sheet_id,
+            rows,
+            hidden: false,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::ManipulateColumnRow);
+    }
+
+    /// Applies `f` to a copy of the sheet's column or row outline and starts a
+    /// transaction to replace the groups if `f` changed anything.
+    fn update_outline_groups(
+        &mut self,
+        sheet_id: SheetId,
+        columns: bool,
+        cursor: Option<String>,
+        f: impl FnOnce(&mut OutlineAxis) -> bool,
+    ) {
+        let Some(sheet) = self.try_sheet(sheet_id) else {
+            return;
+        };
+        let mut axis = if columns {
+            sheet.outline.columns.clone()
+        } else {
+            sheet.outline.rows.clone()
+        };
+        if !f(&mut axis) {
+            return;
+        }
+        let ops = if columns {
+            vec![Operation::SetColumnGroups {
+                sheet_id,
+                groups: axis.groups,
+            }]
+        } else {
+            vec![Operation::SetRowGroups {
+                sheet_id,
+                groups: axis.groups,
+            }]
+        };
+        self.start_user_transaction(ops, cursor, TransactionName::ManipulateColumnRow);
+    }
+
+    /// Groups columns from start to end (inclusive).
+    pub fn group_columns(
+        &mut self,
+        sheet_id: SheetId,
+        start: i64,
+        end: i64,
+        cursor: Option<String>,
+    ) {
+        self.update_outline_groups(sheet_id, true, cursor, |axis| axis.add_group(start, end));
+    }
+
+    /// Removes one level of grouping from the columns from start to end
+    /// (inclusive).
+    pub fn ungroup_columns(
+        &mut self,
+        sheet_id: SheetId,
+        start: i64,
+        end: i64,
+        cursor: Option<String>,
+    ) {
+        self.update_outline_groups(sheet_id, true, cursor, |axis| axis.remove_group(start, end));
+    }
+
+    /// Collapses or expands the inner-most column group containing a column.
+    pub fn set_column_group_collapsed(
+        &mut self,
+        sheet_id: SheetId,
+        column: i64,
+        collapsed: bool,
+        cursor: Option<String>,
+    ) {
+        self.update_outline_groups(sheet_id, true, cursor, |axis| {
+            axis.set_collapsed(column, collapsed)
+        });
+    }
+
+    /// Shows column groups up to an outline level (collapsing deeper groups).
+    pub fn show_column_outline_level(
+        &mut self,
+        sheet_id: SheetId,
+        level: usize,
+        cursor: Option<String>,
+    ) {
+        self.update_outline_groups(sheet_id, true, cursor, |axis| {
+            axis.show_level(level);
+            true
+        });
+    }
+
+    /// Groups rows from start to end (inclusive).
+    pub fn group_rows(&mut self, sheet_id: SheetId, start: i64, end: i64, cursor: Option<String>) {
+        self.update_outline_groups(sheet_id, false, cursor, |axis| axis.add_group(start, end));
+    }
+
+    /// Removes one level of grouping from the rows from start to end
+    /// (inclusive).
+    pub fn ungroup_rows(
+        &mut self,
+        sheet_id: SheetId,
+        start: i64,
+        end: i64,
+        cursor: Option<String>,
+    ) {
+        self.update_outline_groups(sheet_id, false, cursor, |axis| {
+            axis.remove_group(start, end)
+        });
+    }
+
+    /// Collapses or expands the inner-most row group containing a row.
+    pub fn set_row_group_collapsed(
+        &mut self,
+        sheet_id: SheetId,
+        row: i64,
+        collapsed: bool,
+        cursor: Option<String>,
+    ) {
+        self.update_outline_groups(sheet_id, false, cursor, |axis| {
+            axis.set_collapsed(row, collapsed)
+        });
+    }
+
+    /// Shows row groups up to an outline level (collapsing deeper groups).
+    pub fn show_row_outline_level(
+        &mut self,
+        sheet_id: SheetId,
+        level: usize,
+        cursor: Option<String>,
+    ) {
+        self.update_outline_groups(sheet_id, false, cursor, |axis| {
+            axis.show_level(level);
+            true
+        });
+    }
 }
 
 #[cfg(test)]
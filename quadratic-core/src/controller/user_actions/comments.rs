@@ -0,0 +1,169 @@
+use uuid::Uuid;
+
+use crate::{
+    controller::{
+        active_transactions::transaction_name::TransactionName, operations::operation::Operation,
+        GridController,
+    },
+    grid::{
+        sheet::comments::{Comment, CommentThread},
+        SheetId,
+    },
+    SheetPos,
+};
+
+impl GridController {
+    /// Gets the comment threads for a sheet.
+    pub fn comment_threads(&self, sheet_id: SheetId) -> Vec<&CommentThread> {
+        self.try_sheet(sheet_id)
+            .map(|sheet| sheet.comments.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Starts a new comment thread on a cell. Returns the id of the thread.
+    pub fn add_comment_thread(
+        &mut self,
+        sheet_pos: SheetPos,
+        author: String,
+        text: String,
+        cursor: Option<String>,
+    ) -> Uuid {
+        let thread = CommentThread::new(sheet_pos.into(), Comment::new(author, text));
+        let thread_id = thread.id;
+        let ops = vec![Operation::SetCommentThread {
+            sheet_id: sheet_pos.sheet_id,
+            thread,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::Comment);
+        thread_id
+    }
+
+    /// Replies to a comment thread.
+    pub fn reply_to_comment_thread(
+        &mut self,
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        author: String,
+        text: String,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::AddCommentReply {
+            sheet_id,
+            thread_id,
+            reply: Comment::new(author, text),
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::Comment);
+    }
+
+    /// Resolves (or reopens) a comment thread.
+    pub fn resolve_comment_thread(
+        &mut self,
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        resolved: bool,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::SetCommentThreadResolved {
+            sheet_id,
+            thread_id,
+            resolved,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::Comment);
+    }
+
+    /// Deletes a reply from a comment thread.
+    pub fn remove_comment_reply(
+        &mut self,
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        reply_id: Uuid,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::RemoveCommentReply {
+            sheet_id,
+            thread_id,
+            reply_id,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::Comment);
+    }
+
+    /// Deletes a comment thread and its replies.
+    pub fn remove_comment_thread(
+        &mut self,
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::RemoveCommentThread {
+            sheet_id,
+            thread_id,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::Comment);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comment_threads() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let thread_id = gc.add_comment_thread(
+            pos![B2].to_sheet_pos(sheet_id),
+            "a@test.com".into(),
+            "hello".into(),
+            None,
+        );
+        gc.reply_to_comment_thread(sheet_id, thread_id, "b@test.com".into(), "hi".into(), None);
+        gc.resolve_comment_thread(sheet_id, thread_id, true, None);
+
+        let threads = gc.comment_threads(sheet_id);
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].pos, pos![B2]);
+        assert_eq!(threads[0].comment.text, "hello");
+        assert_eq!(threads[0].replies[0].author, "b@test.com");
+        assert!(threads[0].resolved);
+
+        let reply_id = threads[0].replies[0].id;
+        gc.remove_comment_reply(sheet_id, thread_id, reply_id, None);
+        assert!(gc.comment_threads(sheet_id)[0].replies.is_empty());
+
+        gc.undo(None);
+        gc.undo(None);
+        let threads = gc.comment_threads(sheet_id);
+        assert_eq!(threads[0].replies.len(), 1);
+        assert!(!threads[0].resolved);
+
+        gc.remove_comment_thread(sheet_id, thread_id, None);
+        assert!(gc.comment_threads(sheet_id).is_empty());
+    }
+
+    #[test]
+    fn comment_threads_anchored_to_cells() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let thread_id = gc.add_comment_thread(
+            pos![B2].to_sheet_pos(sheet_id),
+            "a@test.com".into(),
+            "hello".into(),
+            None,
+        );
+
+        gc.insert_column(sheet_id, 1, true, None);
+        gc.insert_row(sheet_id, 1, true, None);
+        assert_eq!(gc.comment_threads(sheet_id)[0].pos, pos![C3]);
+
+        gc.delete_rows(sheet_id, vec![3], None);
+        assert!(gc.comment_threads(sheet_id).is_empty());
+
+        // undo restores the thread on its cell
+        gc.undo(None);
+        let threads = gc.comment_threads(sheet_id);
+        assert_eq!(threads[0].id, thread_id);
+        assert_eq!(threads[0].pos, pos![C3]);
+    }
+}
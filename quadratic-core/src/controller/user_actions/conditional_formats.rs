@@ -0,0 +1,91 @@
+use uuid::Uuid;
+
+use crate::{
+    controller::{
+        active_transactions::transaction_name::TransactionName, operations::operation::Operation,
+        GridController,
+    },
+    grid::{sheet::conditional_formats::conditional_format::ConditionalFormat, SheetId},
+};
+
+impl GridController {
+    /// Gets the conditional formats for a sheet.
+    pub fn conditional_formats(&self, sheet_id: SheetId) -> Option<&Vec<ConditionalFormat>> {
+        let sheet = self.try_sheet(sheet_id)?;
+        if sheet.conditional_formats.is_empty() {
+            None
+        } else {
+            Some(&sheet.conditional_formats.conditional_formats)
+        }
+    }
+
+    /// Creates or updates a conditional format.
+    pub fn update_conditional_format(
+        &mut self,
+        conditional_format: ConditionalFormat,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::SetConditionalFormat { conditional_format }];
+        self.start_user_transaction(ops, cursor, TransactionName::ConditionalFormat);
+    }
+
+    pub fn remove_conditional_format(
+        &mut self,
+        sheet_id: SheetId,
+        conditional_format_id: Uuid,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::RemoveConditionalFormat {
+            sheet_id,
+            conditional_format_id,
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::ConditionalFormat);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{
+        grid::{
+            formats::Format,
+            sheet::conditional_formats::conditional_format_rule::{
+                ConditionalFormatRule, NumberComparison,
+            },
+        },
+        A1Selection, Rect,
+    };
+
+    #[test]
+    fn update_remove_conditional_format() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "5".into(), None);
+
+        let conditional_format = ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1_sheet_id("A1:A2", &sheet_id),
+            rule: ConditionalFormatRule::Number(NumberComparison::Equal(5.0)),
+            format: Format {
+                italic: Some(true),
+                ..Default::default()
+            },
+        };
+        gc.update_conditional_format(conditional_format.clone(), None);
+        assert_eq!(
+            gc.conditional_formats(sheet_id),
+            Some(&vec![conditional_format.clone()])
+        );
+        let render = gc.sheet(sheet_id).get_render_cells(Rect::test_a1("A1"));
+        assert_eq!(render[0].italic, Some(true));
+
+        // re-evaluated when the cell changes
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "6".into(), None);
+        let render = gc.sheet(sheet_id).get_render_cells(Rect::test_a1("A1"));
+        assert_eq!(render[0].italic, None);
+
+        gc.remove_conditional_format(sheet_id, conditional_format.id, None);
+        assert_eq!(gc.conditional_formats(sheet_id), None);
+    }
+}
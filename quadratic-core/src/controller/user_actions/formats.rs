@@ -320,12 +320,69 @@ impl GridController {
         self.start_user_transaction(ops, cursor, TransactionName::SetFormats);
         Ok(())
     }
+
+    pub(crate) fn set_font_size(
+        &mut self,
+        selection: &A1Selection,
+        font_size: Option<i16>,
+        cursor: Option<String>,
+    ) -> Result<(), JsValue> {
+        let format_update = FormatUpdate {
+            font_size: Some(font_size),
+            ..Default::default()
+        };
+        let ops = vec![Operation::SetCellFormatsA1 {
+            sheet_id: selection.sheet_id,
+            formats: SheetFormatUpdates::from_selection(selection, format_update),
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::SetFormats);
+        Ok(())
+    }
+
+    pub(crate) fn set_font_family(
+        &mut self,
+        selection: &A1Selection,
+        font_family: Option<String>,
+        cursor: Option<String>,
+    ) -> Result<(), JsValue> {
+        let format_update = FormatUpdate {
+            font_family: Some(font_family),
+            ..Default::default()
+        };
+        let ops = vec![Operation::SetCellFormatsA1 {
+            sheet_id: selection.sheet_id,
+            formats: SheetFormatUpdates::from_selection(selection, format_update),
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::SetFormats);
+        Ok(())
+    }
+
+    pub(crate) fn set_text_rotation(
+        &mut self,
+        selection: &A1Selection,
+        text_rotation: Option<i16>,
+        cursor: Option<String>,
+    ) -> Result<(), JsValue> {
+        let format_update = FormatUpdate {
+            text_rotation: Some(text_rotation),
+            ..Default::default()
+        };
+        let ops = vec![Operation::SetCellFormatsA1 {
+            sheet_id: selection.sheet_id,
+            formats: SheetFormatUpdates::from_selection(selection, format_update),
+        }];
+        self.start_user_transaction(ops, cursor, TransactionName::SetFormats);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 #[serial_test::parallel]
 mod test {
+    use crate::controller::active_transactions::transaction_name::TransactionName;
+    use crate::controller::operations::operation::Operation;
     use crate::controller::GridController;
+    use crate::grid::formats::{FormatUpdate, SheetFormatUpdates};
     use crate::grid::{CellWrap, RenderSize};
     use crate::{A1Selection, Pos};
 
@@ -600,6 +657,54 @@ mod test {
         assert_eq!(sheet.formats.strike_through.get(pos![A2]), Some(true));
     }
 
+    #[test]
+    fn test_set_font_selection() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let selection = A1Selection::test_a1("A1:B2");
+        gc.set_font_size(&selection, Some(18), None).unwrap();
+        gc.set_font_family(&selection, Some("Georgia".to_string()), None)
+            .unwrap();
+        gc.set_text_rotation(&selection, Some(45), None).unwrap();
+
+        let sheet = gc.sheet(sheet_id);
+        let format = sheet.formats.format(pos![B2]);
+        assert_eq!(format.font_size, Some(18));
+        assert_eq!(format.font_family, Some("Georgia".to_string()));
+        assert_eq!(format.text_rotation, Some(45));
+
+        gc.set_font_size(&selection, None, None).unwrap();
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.formats.font_size.get(pos![B2]), None);
+        assert_eq!(sheet.formats.text_rotation.get(pos![B2]), Some(45));
+
+        gc.undo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(sheet.formats.font_size.get(pos![B2]), Some(18));
+    }
+
+    #[test]
+    fn test_set_text_rotation_out_of_range() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let selection = A1Selection::test_a1("B2");
+
+        // operations with an out of range rotation are ignored
+        let format_update = FormatUpdate {
+            text_rotation: Some(Some(360)),
+            ..Default::default()
+        };
+        gc.start_user_transaction(
+            vec![Operation::SetCellFormatsA1 {
+                sheet_id,
+                formats: SheetFormatUpdates::from_selection(&selection, format_update),
+            }],
+            None,
+            TransactionName::SetFormats,
+        );
+        assert_eq!(gc.sheet(sheet_id).formats.text_rotation.get(pos![B2]), None);
+    }
+
     #[test]
     fn test_clear_format() {
         let mut gc = GridController::test();
@@ -0,0 +1,61 @@
+use crate::{
+    controller::{active_transactions::transaction_name::TransactionName, GridController},
+    A1Selection,
+};
+
+impl GridController {
+    /// Merges each finite range in the selection into a single cell.
+    pub fn merge_cells(&mut self, selection: &A1Selection, cursor: Option<String>) {
+        let ops = self.merge_cells_operations(selection);
+        if !ops.is_empty() {
+            self.start_user_transaction(ops, cursor, TransactionName::MergeCells);
+        }
+    }
+
+    /// Unmerges all merged regions that intersect the selection.
+    pub fn unmerge_cells(&mut self, selection: &A1Selection, cursor: Option<String>) {
+        let ops = self.unmerge_cells_operations(selection);
+        if !ops.is_empty() {
+            self.start_user_transaction(ops, cursor, TransactionName::MergeCells);
+        }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{CellValue, Rect};
+
+    #[test]
+    fn merge_unmerge_cells() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "a".to_string(), None);
+        gc.set_cell_value(pos![B1].to_sheet_pos(sheet_id), "b".to_string(), None);
+
+        gc.merge_cells(&A1Selection::test_a1("A1:C1"), None);
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.merge_cells.get(pos![C1]),
+            Some(Rect::test_a1("A1:C1"))
+        );
+        assert_eq!(
+            sheet.cell_value(pos![A1]),
+            Some(CellValue::Text("a".to_string()))
+        );
+        assert_eq!(sheet.cell_value(pos![B1]), None);
+
+        gc.unmerge_cells(&A1Selection::test_a1("B1"), None);
+        assert!(gc.sheet(sheet_id).merge_cells.is_empty());
+
+        gc.undo(None);
+        gc.undo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert!(sheet.merge_cells.is_empty());
+        assert_eq!(
+            sheet.cell_value(pos![B1]),
+            Some(CellValue::Text("b".to_string()))
+        );
+    }
+}
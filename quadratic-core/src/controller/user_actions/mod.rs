@@ -6,8 +6,13 @@ pub mod cells;
 pub mod clipboard;
 pub mod code;
 pub mod col_row;
+pub mod comments;
+pub mod conditional_formats;
 pub mod formats;
 pub mod import;
+pub mod merge_cells;
+pub mod protections;
 pub mod sheets;
 pub mod undo;
 pub mod validations;
+pub mod what_if;
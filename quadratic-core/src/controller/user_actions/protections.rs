@@ -0,0 +1,134 @@
+use uuid::Uuid;
+
+use crate::{
+    controller::{
+        active_transactions::transaction_name::TransactionName,
+        operations::{
+            operation::Operation,
+            protection::{sheet_protections, validate_protections},
+        },
+        GridController,
+    },
+    grid::{
+        sheet::protections::{ProtectedRange, ProtectionEditor},
+        SheetId,
+    },
+};
+
+impl GridController {
+    /// Gets the protected ranges for a sheet.
+    pub fn protected_ranges(&self, sheet_id: SheetId) -> Vec<&ProtectedRange> {
+        self.try_sheet(sheet_id)
+            .map(|sheet| sheet.protections.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Creates or updates a protected range.
+    pub fn set_protected_range(
+        &mut self,
+        sheet_id: SheetId,
+        range: ProtectedRange,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::SetProtectedRange { sheet_id, range }];
+        self.start_user_transaction(ops, cursor, TransactionName::ProtectedRange);
+    }
+
+    /// Deletes a protected range.
+    pub fn remove_protected_range(
+        &mut self,
+        sheet_id: SheetId,
+        range_id: Uuid,
+        cursor: Option<String>,
+    ) {
+        let ops = vec![Operation::RemoveProtectedRange { sheet_id, range_id }];
+        self.start_user_transaction(ops, cursor, TransactionName::ProtectedRange);
+    }
+
+    /// Sets the user making local changes. When set, changes to protected
+    /// ranges that the user cannot edit are refused.
+    pub fn set_protection_editor(&mut self, editor: Option<ProtectionEditor>) {
+        self.protection_editor = editor;
+    }
+
+    /// Returns whether the local user may make these changes. Tells the
+    /// client when they are refused.
+    pub(crate) fn protections_allow(&self, operations: &[Operation]) -> bool {
+        let Some(editor) = &self.protection_editor else {
+            return true;
+        };
+        let mut protections = sheet_protections(&self.grid);
+        if protections.is_empty() {
+            return true;
+        }
+        match validate_protections(operations, &mut protections, editor) {
+            Ok(()) => true,
+            Err(_) => {
+                self.host.client_message(
+                    "These cells are protected. Ask someone who can edit them for access.".into(),
+                    true,
+                );
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{grid::sheet::protections::ProtectionRole, CellValue, Rect};
+
+    #[test]
+    fn protected_ranges() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let range = ProtectedRange::new(
+            Some(Rect::test_a1("A1:B2")),
+            vec!["alice".into()],
+            vec![ProtectionRole::Owner],
+        );
+        gc.set_protected_range(sheet_id, range.clone(), None);
+        assert_eq!(gc.protected_ranges(sheet_id), vec![&range]);
+
+        // protected cells are refused for other users
+        gc.set_protection_editor(Some(ProtectionEditor {
+            user_id: "bob".into(),
+            roles: vec![],
+        }));
+        gc.set_cell_value(pos![B2].to_sheet_pos(sheet_id), "1".into(), None);
+        gc.set_cell_value(pos![C3].to_sheet_pos(sheet_id), "2".into(), None);
+        assert_eq!(gc.sheet(sheet_id).cell_value(pos![B2]), None);
+        assert_eq!(
+            gc.sheet(sheet_id).cell_value(pos![C3]),
+            Some(CellValue::Number(2.into()))
+        );
+        gc.remove_protected_range(sheet_id, range.id, None);
+        assert_eq!(gc.protected_ranges(sheet_id).len(), 1);
+
+        // but allowed for its editors
+        gc.set_protection_editor(Some(ProtectionEditor {
+            user_id: "carol".into(),
+            roles: vec![ProtectionRole::Owner],
+        }));
+        gc.set_cell_value(pos![B2].to_sheet_pos(sheet_id), "1".into(), None);
+        assert_eq!(
+            gc.sheet(sheet_id).cell_value(pos![B2]),
+            Some(CellValue::Number(1.into()))
+        );
+
+        // undo is refused once the user can no longer edit the range
+        gc.set_protection_editor(Some(ProtectionEditor {
+            user_id: "bob".into(),
+            roles: vec![],
+        }));
+        gc.undo(None);
+        assert_eq!(
+            gc.sheet(sheet_id).cell_value(pos![B2]),
+            Some(CellValue::Number(1.into()))
+        );
+        assert!(gc.has_undo());
+    }
+}
@@ -0,0 +1,347 @@
+use anyhow::Result;
+
+use crate::{
+    controller::{
+        active_transactions::transaction_name::TransactionName,
+        operations::what_if::{DataTableInput, GoalSeekResult},
+        GridController,
+    },
+    SheetPos,
+};
+
+impl GridController {
+    /// Changes `input` so `formula` equals `target`. The input is only
+    /// changed when a solution is found.
+    pub fn goal_seek(
+        &mut self,
+        formula: SheetPos,
+        target: f64,
+        input: SheetPos,
+        cursor: Option<String>,
+    ) -> Result<GoalSeekResult> {
+        let (ops, result) = self.goal_seek_operations(formula, target, input)?;
+        if !ops.is_empty() {
+            self.start_user_transaction(ops, cursor, TransactionName::GoalSeek);
+        }
+        Ok(result)
+    }
+
+    /// Writes a data table at `output`: the values of `formula` for each value
+    /// of `column_input` (rows) and `row_input` (columns).
+    pub fn data_table(
+        &mut self,
+        formula: SheetPos,
+        column_input: Option<DataTableInput>,
+        row_input: Option<DataTableInput>,
+        output: SheetPos,
+        cursor: Option<String>,
+    ) -> Result<()> {
+        let ops = self.data_table_operations(formula, column_input, row_input, output)?;
+        self.start_user_transaction(ops, cursor, TransactionName::DataTable);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::{
+        controller::transaction_types::JsCodeResult,
+        grid::{CodeCellLanguage, SheetId},
+        CellValue, Pos, SheetRect,
+    };
+
+    fn set_formula(gc: &mut GridController, sheet_pos: SheetPos, code: &str) {
+        gc.set_code_cell(sheet_pos, CodeCellLanguage::Formula, code.to_string(), None);
+    }
+
+    fn number(gc: &GridController, sheet_id: SheetId, pos: Pos) -> f64 {
+        match gc.sheet(sheet_id).display_value(pos) {
+            Some(CellValue::Number(n)) => n.to_string().parse().unwrap(),
+            value => panic!("expected a number, found {value:?}"),
+        }
+    }
+
+    #[test]
+    fn test_goal_seek() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        // price in A1, cost in A2, margin in A3 (through an intermediate
+        // formula in A4)
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "10".to_string(), None);
+        gc.set_cell_value(pos![A2].to_sheet_pos(sheet_id), "7".to_string(), None);
+        set_formula(&mut gc, pos![A4].to_sheet_pos(sheet_id), "A1 - A2");
+        set_formula(&mut gc, pos![A3].to_sheet_pos(sheet_id), "A4 / A1");
+
+        let result = gc
+            .goal_seek(
+                pos![A3].to_sheet_pos(sheet_id),
+                0.3,
+                pos![A1].to_sheet_pos(sheet_id),
+                None,
+            )
+            .unwrap();
+        assert!(result.found);
+        assert!((result.input_value - 10.0).abs() < 1e-6);
+
+        let result = gc
+            .goal_seek(
+                pos![A3].to_sheet_pos(sheet_id),
+                0.5,
+                pos![A1].to_sheet_pos(sheet_id),
+                None,
+            )
+            .unwrap();
+        assert!(result.found);
+        assert!((number(&gc, sheet_id, pos![A1]) - 14.0).abs() < 1e-6);
+        assert!((number(&gc, sheet_id, pos![A3]) - 0.5).abs() < 1e-9);
+
+        // the solution is undoable
+        gc.undo(None);
+        assert_eq!(number(&gc, sheet_id, pos![A1]), 10.0);
+
+        let result = gc
+            .goal_seek(
+                pos![A3].to_sheet_pos(sheet_id),
+                2.0,
+                pos![A2].to_sheet_pos(sheet_id),
+                None,
+            )
+            .unwrap();
+        assert!(result.found);
+        assert!((number(&gc, sheet_id, pos![A2]) + 10.0).abs() < 1e-6);
+
+        // no solution leaves the input unchanged
+        let result = gc
+            .goal_seek(
+                pos![A3].to_sheet_pos(sheet_id),
+                5.0,
+                pos![B1].to_sheet_pos(sheet_id),
+                None,
+            )
+            .unwrap();
+        assert!(!result.found);
+        assert_eq!(gc.sheet(sheet_id).display_value(pos![B1]), None);
+
+        // the formula and input cells are checked
+        assert!(gc
+            .goal_seek(
+                pos![A1].to_sheet_pos(sheet_id),
+                1.0,
+                pos![A2].to_sheet_pos(sheet_id),
+                None
+            )
+            .is_err());
+        assert!(gc
+            .goal_seek(
+                pos![A3].to_sheet_pos(sheet_id),
+                1.0,
+                pos![A4].to_sheet_pos(sheet_id),
+                None
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_data_table() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        // A1 * B1, with values for A1 in D2:D4 and for B1 in E1:F1
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".to_string(), None);
+        gc.set_cell_value(pos![B1].to_sheet_pos(sheet_id), "1".to_string(), None);
+        set_formula(&mut gc, pos![C1].to_sheet_pos(sheet_id), "A1 * B1");
+        for (pos, value) in [
+            (pos![D2], "1"),
+            (pos![D3], "2"),
+            (pos![D4], "3"),
+            (pos![E1], "10"),
+            (pos![F1], "20"),
+        ] {
+            gc.set_cell_value(pos.to_sheet_pos(sheet_id), value.to_string(), None);
+        }
+
+        let column_input = DataTableInput {
+            input: pos![A1].to_sheet_pos(sheet_id),
+            values: SheetRect::new(4, 2, 4, 4, sheet_id),
+        };
+        let row_input = DataTableInput {
+            input: pos![B1].to_sheet_pos(sheet_id),
+            values: SheetRect::new(5, 1, 6, 1, sheet_id),
+        };
+
+        // one variable
+        gc.data_table(
+            pos![C1].to_sheet_pos(sheet_id),
+            Some(column_input.clone()),
+            None,
+            pos![H2].to_sheet_pos(sheet_id),
+            None,
+        )
+        .unwrap();
+        assert_eq!(number(&gc, sheet_id, pos![H2]), 1.0);
+        assert_eq!(number(&gc, sheet_id, pos![H3]), 2.0);
+        assert_eq!(number(&gc, sheet_id, pos![H4]), 3.0);
+
+        // two variables
+        gc.data_table(
+            pos![C1].to_sheet_pos(sheet_id),
+            Some(column_input),
+            Some(row_input),
+            pos![E2].to_sheet_pos(sheet_id),
+            None,
+        )
+        .unwrap();
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.cell_value(pos![E2]),
+            Some(CellValue::Number(10.into()))
+        );
+        assert_eq!(number(&gc, sheet_id, pos![F2]), 20.0);
+        assert_eq!(number(&gc, sheet_id, pos![E4]), 30.0);
+        assert_eq!(number(&gc, sheet_id, pos![F4]), 60.0);
+
+        // the inputs are unchanged, and the table is undoable
+        assert_eq!(number(&gc, sheet_id, pos![A1]), 1.0);
+        assert_eq!(number(&gc, sheet_id, pos![C1]), 1.0);
+        gc.undo(None);
+        assert_eq!(gc.sheet(sheet_id).display_value(pos![F4]), None);
+
+        assert!(gc
+            .data_table(
+                pos![C1].to_sheet_pos(sheet_id),
+                None,
+                None,
+                pos![H2].to_sheet_pos(sheet_id),
+                None,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_what_if_requires_formulas() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".to_string(), None);
+
+        // a Python cell
+        gc.set_code_cell(
+            pos![B1].to_sheet_pos(sheet_id),
+            CodeCellLanguage::Python,
+            "2".to_string(),
+            None,
+        );
+        let transaction_id = gc.async_transactions()[0].id;
+        gc.calculation_complete(JsCodeResult::new(
+            transaction_id.to_string(),
+            true,
+            None,
+            None,
+            Some(vec!["2".into(), "number".into()]),
+            None,
+            None,
+            None,
+            None,
+        ))
+        .unwrap();
+        set_formula(&mut gc, pos![C1].to_sheet_pos(sheet_id), "A1 * B1");
+
+        // the formula cell must be a formula
+        let error = gc
+            .goal_seek(
+                pos![B1].to_sheet_pos(sheet_id),
+                4.0,
+                pos![A1].to_sheet_pos(sheet_id),
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(error.to_string(), "B1 must contain a formula");
+
+        // and may not depend on other code cells
+        let error = gc
+            .goal_seek(
+                pos![C1].to_sheet_pos(sheet_id),
+                4.0,
+                pos![A1].to_sheet_pos(sheet_id),
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "C1 depends on B1, which is not a formula"
+        );
+        assert_eq!(number(&gc, sheet_id, pos![A1]), 1.0);
+    }
+
+    #[test]
+    fn test_data_table_output_overlap() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".to_string(), None);
+        set_formula(&mut gc, pos![B1].to_sheet_pos(sheet_id), "A1 * 2");
+        for (pos, value) in [(pos![D1], "1"), (pos![D2], "2"), (pos![D3], "3")] {
+            gc.set_cell_value(pos.to_sheet_pos(sheet_id), value.to_string(), None);
+        }
+        let column_input = DataTableInput {
+            input: pos![A1].to_sheet_pos(sheet_id),
+            values: SheetRect::new(4, 1, 4, 3, sheet_id),
+        };
+
+        // the 1x3 results can't cover the formula, the input or the values
+        for output in [pos![B1], pos![A1], pos![D2]] {
+            assert!(
+                gc.data_table(
+                    pos![B1].to_sheet_pos(sheet_id),
+                    Some(column_input.clone()),
+                    None,
+                    output.to_sheet_pos(sheet_id),
+                    None,
+                )
+                .is_err(),
+                "{output} overlaps"
+            );
+        }
+
+        gc.data_table(
+            pos![B1].to_sheet_pos(sheet_id),
+            Some(column_input),
+            None,
+            pos![E1].to_sheet_pos(sheet_id),
+            None,
+        )
+        .unwrap();
+        assert_eq!(number(&gc, sheet_id, pos![E3]), 6.0);
+    }
+
+    #[test]
+    fn test_goal_seek_other_sheet() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.add_sheet(None);
+        let other_id = gc.sheet_ids()[1];
+        let other_name = gc.sheet(other_id).name.clone();
+
+        // the formula reads the input through a formula on another sheet
+        gc.set_cell_value(pos![A1].to_sheet_pos(sheet_id), "1".to_string(), None);
+        set_formula(&mut gc, pos![A1].to_sheet_pos(other_id), "'Sheet 1'!A1 + 1");
+        set_formula(
+            &mut gc,
+            pos![B1].to_sheet_pos(sheet_id),
+            &format!("'{other_name}'!A1 * 10"),
+        );
+        assert_eq!(number(&gc, sheet_id, pos![B1]), 20.0);
+
+        let result = gc
+            .goal_seek(
+                pos![B1].to_sheet_pos(sheet_id),
+                50.0,
+                pos![A1].to_sheet_pos(sheet_id),
+                None,
+            )
+            .unwrap();
+        assert!(result.found);
+        assert!((number(&gc, sheet_id, pos![A1]) - 4.0).abs() < 1e-6);
+    }
+}
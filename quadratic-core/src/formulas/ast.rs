@@ -61,9 +61,31 @@ impl Formula {
             inner: e.into(),
         })
     }
+
+    /// Returns whether the formula calls a volatile function (eg, `NOW()`),
+    /// so its result may change without a change to the cells it reads.
+    pub fn is_volatile(&self) -> bool {
+        self.ast.is_volatile()
+    }
 }
 
 impl AstNode {
+    fn is_volatile(&self) -> bool {
+        match &self.inner {
+            AstNodeContents::FunctionCall { func, args } => {
+                functions::is_volatile_function(&func.inner)
+                    || args.iter().any(|arg| arg.is_volatile())
+            }
+            AstNodeContents::Paren(contents) => contents.iter().any(|arg| arg.is_volatile()),
+            AstNodeContents::Array(rows) => rows.iter().flatten().any(|arg| arg.is_volatile()),
+            AstNodeContents::Empty
+            | AstNodeContents::CellRef(_)
+            | AstNodeContents::String(_)
+            | AstNodeContents::Number(_)
+            | AstNodeContents::Bool(_) => false,
+        }
+    }
+
     fn eval<'expr, 'ctx: 'expr>(
         &'expr self,
         ctx: &'expr mut Ctx<'ctx>,
@@ -85,9 +107,9 @@ impl AstNode {
                 let func_name = &func.inner;
                 match functions::lookup_function(func_name) {
                     Some(f) => {
-                        let arg_values: Vec<Spanned<Value>> = args
+                        let arg_values: Vec<Spanned<ArgValue<'_>>> = args
                             .iter()
-                            .map(|arg| arg.eval(&mut *ctx, bounds))
+                            .map(|arg| arg.eval_arg(&mut *ctx, bounds))
                             .try_collect()?;
                         let args = FormulaFnArgs::new(arg_values, self.span, f.name);
                         (f.eval)(&mut *ctx, args)?
@@ -158,6 +180,26 @@ impl AstNode {
         })
     }
 
+    /// Evaluates a function argument. Cell ranges are returned without reading
+    /// them, so that functions can skip blank cells.
+    fn eval_arg<'expr, 'ctx: 'expr>(
+        &'expr self,
+        ctx: &'expr mut Ctx<'ctx>,
+        bounds: Option<GridBounds>,
+    ) -> CodeResult<Spanned<ArgValue<'ctx>>> {
+        match &self.inner {
+            AstNodeContents::FunctionCall { func, .. }
+                if func.inner == ":" && !ctx.skip_computation =>
+            {
+                let range = self.to_range_ref(ctx)?;
+                let rect = ctx.resolve_range_ref(&range.inner, self.span)?;
+                let range = ctx.get_cell_range(rect.inner, self.span, bounds);
+                Ok(range.map(ArgValue::Range))
+            }
+            _ => Ok(self.eval(ctx, bounds)?.map(ArgValue::Value)),
+        }
+    }
+
     /// Evaluates the expression to a tuple of range references, or returns an
     /// error if this cannot be done
     fn to_range_ref_tuple<'expr, 'ctx: 'expr>(
@@ -226,10 +268,10 @@ impl AstNode {
                     .try_collect()?;
 
                 // Get other arguments
-                let arg_values: Vec<Spanned<Value>> = args
+                let arg_values: Vec<Spanned<ArgValue<'_>>> = args
                     .iter()
                     .skip(1)
-                    .map(|arg| arg.eval(&mut *ctx, None))
+                    .map(|arg| CodeResult::Ok(arg.eval(&mut *ctx, None)?.map(ArgValue::from)))
                     .try_collect()?;
                 let mut args = FormulaFnArgs::new(arg_values, self.span, "INDEX");
                 let row = args
@@ -0,0 +1,76 @@
+//! Cache of parsed formulas.
+//!
+//! Formula code is stored with internal R1C1-style references (see
+//! [`super::replace_a1_notation`]), so the same relative formula in different
+//! cells -- like a filled-down column -- has the same code and shares one
+//! parsed AST. Code with A1-style references depends on the cell it's in and
+//! isn't cached.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{parser::parse_formula_at, Formula};
+use crate::{CodeResult, Pos};
+
+/// Maximum number of cached formulas. The cache is cleared when it's full.
+const FORMULA_CACHE_MAX_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Default, Clone)]
+pub struct FormulaCache {
+    formulas: HashMap<String, Arc<Formula>>,
+}
+
+impl FormulaCache {
+    /// Returns the parsed formula for `code` in the cell at `pos`, parsing it
+    /// if it's not cached. Errors are not cached.
+    pub fn parse(&mut self, code: &str, pos: Pos) -> CodeResult<Arc<Formula>> {
+        if let Some(formula) = self.formulas.get(code) {
+            return Ok(formula.clone());
+        }
+
+        let (formula, used_pos) = parse_formula_at(code, pos)?;
+        let formula = Arc::new(formula);
+        if !used_pos {
+            if self.formulas.len() >= FORMULA_CACHE_MAX_ENTRIES {
+                self.formulas.clear();
+            }
+            self.formulas.insert(code.to_string(), formula.clone());
+        }
+        Ok(formula)
+    }
+
+    pub fn len(&self) -> usize {
+        self.formulas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.formulas.is_empty()
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formula_cache() {
+        let mut cache = FormulaCache::default();
+
+        // relative formulas share an AST
+        let a = cache.parse("R[0]C[-1] * 2", Pos { x: 2, y: 1 }).unwrap();
+        let b = cache.parse("R[0]C[-1] * 2", Pos { x: 2, y: 2 }).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 1);
+
+        // A1-style references depend on the cell
+        let a = cache.parse("A1 * 2", Pos { x: 2, y: 1 }).unwrap();
+        let b = cache.parse("A1 * 2", Pos { x: 2, y: 2 }).unwrap();
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 1);
+
+        // errors are not cached
+        assert!(cache.parse("SUM(", Pos { x: 1, y: 1 }).is_err());
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -1,11 +1,13 @@
+use std::collections::BTreeSet;
+
 use itertools::Itertools;
 use smallvec::{smallvec, SmallVec};
 
 use super::*;
 use crate::{
     grid::{CellsAccessed, Grid, GridBounds},
-    Array, CellValue, CodeResult, CodeResultExt, Pos, RunErrorMsg, SheetPos, SheetRect, Span,
-    Spanned, Value, UNBOUNDED,
+    Array, ArraySize, Axis, CellValue, CodeResult, CodeResultExt, IsBlank, Pos, Rect, RunErrorMsg,
+    SheetPos, SheetRect, Span, Spanned, Value, UNBOUNDED,
 };
 
 /// Formula execution context.
@@ -127,6 +129,25 @@ impl<'ctx> Ctx<'ctx> {
             return Ok(CellValue::Blank.into()).with_span(span);
         }
 
+        // TODO(ddimaria): removed b/c this should be enforced across all languages
+        // remove this comment and the code below once implemented elsewhere
+        //
+        // if std::cmp::max(array_size.w, array_size.h).get() > crate::limits::CELL_RANGE_LIMIT {
+        //     return Err(RunErrorMsg::ArrayTooBig.with_span(span));
+        // }
+
+        let range = self.get_cell_range(rect, span, bounds);
+        Ok(range.inner.to_array()?).with_span(span)
+    }
+
+    /// Returns the cell range at `rect` without reading its contents, so that
+    /// functions like `SUM` can walk only its non-blank cells.
+    pub fn get_cell_range(
+        &mut self,
+        rect: SheetRect,
+        span: Span,
+        bounds: Option<GridBounds>,
+    ) -> Spanned<CellRange<'ctx>> {
         let mut bounded_rect = rect;
 
         // convert unbounded values to the data bounds of the sheet
@@ -145,30 +166,15 @@ impl<'ctx> Ctx<'ctx> {
             }
         }
 
-        let sheet_id = bounded_rect.sheet_id;
-        let array_size = bounded_rect.size();
-
-        // TODO(ddimaria): removed b/c this should be enforced across all languages
-        // remove this comment and the code below once implemented elsewhere
-        //
-        // if std::cmp::max(array_size.w, array_size.h).get() > crate::limits::CELL_RANGE_LIMIT {
-        //     return Err(RunErrorMsg::ArrayTooBig.with_span(span));
-        // }
-
-        let mut flat_array = smallvec![];
-        // Reuse the same `CellRef` object so that we don't have to
-        // clone `sheet_name.`
-        for y in bounded_rect.y_range() {
-            for x in bounded_rect.x_range() {
-                // TODO: record array dependency instead of many individual cell dependencies
-                flat_array.push(self.get_cell(SheetPos { x, y, sheet_id }, span).inner);
-            }
-        }
-
-        self.cells_accessed
-            .add_sheet_rect(SheetRect::new_pos_span(rect.min, rect.max, sheet_id));
+        self.cells_accessed.add_sheet_rect(rect);
 
-        Ok(Array::new_row_major(array_size, flat_array)?).with_span(span)
+        let range = CellRange {
+            grid: self.grid,
+            formula_pos: self.sheet_pos,
+            rect: bounded_rect,
+            span,
+        };
+        Spanned { span, inner: range }
     }
 
     /// Evaluates a function once for each corresponding set of values from
@@ -231,7 +237,7 @@ impl<'ctx> Ctx<'ctx> {
         &mut self,
         eval_range1: Spanned<Array>,
         criteria1: Spanned<Value>,
-        mut remaining_args: FormulaFnArgs,
+        mut remaining_args: FormulaFnArgs<'_>,
         f: impl for<'b> Fn(
             &'b mut Ctx<'_>,
             Vec<(&'b Spanned<Array>, Criterion)>,
@@ -260,3 +266,187 @@ impl<'ctx> Ctx<'ctx> {
         })
     }
 }
+
+/// Range of cells referenced by a formula, which is read from the grid only
+/// when it's used.
+#[derive(Debug, Copy, Clone)]
+pub struct CellRange<'ctx> {
+    grid: &'ctx Grid,
+    /// Position of the formula, which can't be read from the range.
+    formula_pos: SheetPos,
+    /// Range of cells, with unbounded coordinates replaced by the data bounds
+    /// of the sheet.
+    rect: SheetRect,
+    span: Span,
+}
+impl<'ctx> CellRange<'ctx> {
+    /// Returns the size of the range.
+    pub fn size(&self) -> ArraySize {
+        self.rect.size()
+    }
+
+    /// Returns the column (`Axis::Y`) or row (`Axis::X`) at `index` within
+    /// the range.
+    pub fn line(self, axis: Axis, index: u32) -> Self {
+        let mut rect = self.rect;
+        match axis {
+            Axis::X => {
+                rect.min.y += index as i64;
+                rect.max.y = rect.min.y;
+            }
+            Axis::Y => {
+                rect.min.x += index as i64;
+                rect.max.x = rect.min.x;
+            }
+        }
+        CellRange { rect, ..self }
+    }
+
+    /// Returns the contents of the cell at `pos`, or an error value in the
+    /// case of a circular reference.
+    fn get_pos(&self, pos: Pos) -> CellValue {
+        let error_value = |e: RunErrorMsg| CellValue::Error(Box::new(e.with_span(self.span)));
+
+        let Some(sheet) = self.grid.try_sheet(self.rect.sheet_id) else {
+            return error_value(RunErrorMsg::BadCellReference);
+        };
+        if pos.to_sheet_pos(sheet.id) == self.formula_pos {
+            return error_value(RunErrorMsg::CircularReference);
+        }
+
+        sheet.get_cell_for_formula(pos)
+    }
+
+    /// Returns the contents of the cell at (`x`, `y`) within the range.
+    pub fn get(&self, x: u32, y: u32) -> CodeResult<CellValue> {
+        let size = self.size();
+        if x >= size.w.get() || y >= size.h.get() {
+            return Err(RunErrorMsg::IndexOutOfBounds.with_span(self.span));
+        }
+        let pos = Pos {
+            x: self.rect.min.x + x as i64,
+            y: self.rect.min.y + y as i64,
+        };
+        Ok(self.get_pos(pos))
+    }
+
+    /// Reads the contents of every cell in the range.
+    pub fn to_array(self) -> CodeResult<Array> {
+        let mut flat_array = smallvec![];
+        for y in self.rect.y_range() {
+            for x in self.rect.x_range() {
+                flat_array.push(self.get_pos(Pos { x, y }));
+            }
+        }
+        Array::new_row_major(self.size(), flat_array)
+    }
+
+    /// Iterates over the non-blank cells in the range in row-major order,
+    /// along with their (`x`, `y`) coordinates within the range.
+    ///
+    /// Only cells that have a value in `Column` storage or in the output of
+    /// a code cell are visited, so this is cheap even for large ranges that
+    /// are mostly blank.
+    pub fn iter_non_blank(self) -> impl 'ctx + Iterator<Item = (u32, u32, CellValue)> {
+        let rect = Rect::from(self.rect);
+        let sheet = self.grid.try_sheet(self.rect.sheet_id);
+
+        // Report a missing sheet the same way as reading a cell would.
+        let missing_sheet = sheet.is_none().then(|| (0, 0, self.get_pos(rect.min)));
+
+        let code_outputs = sheet
+            .iter()
+            .flat_map(|sheet| &sheet.code_runs)
+            .filter_map(|(pos, code_run)| code_run.output_rect(*pos, false).intersection(&rect))
+            .collect_vec();
+        let columns: BTreeSet<i64> = sheet
+            .iter()
+            .flat_map(|sheet| sheet.columns.range(rect.x_range()).map(|(&x, _)| x))
+            .chain(code_outputs.iter().flat_map(|output| output.x_range()))
+            .collect();
+
+        // Walk each column in order, then merge the columns into rows.
+        let positions = columns
+            .into_iter()
+            .map(|x| {
+                let values = sheet
+                    .and_then(|sheet| sheet.columns.get(&x))
+                    .into_iter()
+                    .flat_map(move |column| column.values.range(rect.y_range()).map(|(&y, _)| y));
+                let outputs = code_outputs
+                    .iter()
+                    .filter(|output| output.x_range().contains(&x))
+                    .map(|output| output.y_range())
+                    .collect_vec()
+                    .into_iter()
+                    .kmerge();
+                values.merge(outputs).dedup().map(move |y| Pos { x, y })
+            })
+            .kmerge_by(|a, b| (a.y, a.x) < (b.y, b.x));
+
+        let cells = positions.filter_map(move |pos| {
+            let value = self.get_pos(pos);
+            let x = (pos.x - rect.min.x) as u32;
+            let y = (pos.y - rect.min.y) as u32;
+            (!value.is_blank()).then_some((x, y, value))
+        });
+        missing_sheet.into_iter().chain(cells)
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::controller::GridController;
+    use crate::grid::CodeCellLanguage;
+
+    #[test]
+    fn test_cell_range_iter_non_blank() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(SheetPos::new(sheet_id, 2, 1), "hello".into(), None);
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 5), "1".into(), None);
+        gc.set_cell_value(SheetPos::new(sheet_id, 4, 500_000), "2".into(), None);
+        // the output spills into column D, which is outside the range
+        gc.set_code_cell(
+            SheetPos::new(sheet_id, 3, 2),
+            CodeCellLanguage::Formula,
+            "{10, 20; 30, 40}".into(),
+            None,
+        );
+
+        let mut ctx = Ctx::new(gc.grid(), SheetPos::new(sheet_id, 1, 5));
+        let rect = SheetRect::new(1, 1, 3, 1_000_000, sheet_id);
+        let range = ctx.get_cell_range(rect, Span::empty(0), None).inner;
+        assert!(ctx.cells_accessed.intersects(&rect));
+
+        let cells = range.iter_non_blank().collect_vec();
+        let values = cells
+            .iter()
+            .map(|(x, y, value)| (*x, *y, value.to_string()))
+            .collect_vec();
+        assert_eq!(
+            values,
+            vec![
+                (1, 0, "hello".to_string()),
+                (2, 1, "10".to_string()),
+                (2, 2, "30".to_string()),
+                (0, 4, "Circular reference".to_string()),
+            ],
+        );
+        assert_eq!(range.get(2, 2).unwrap(), CellValue::from(30));
+        assert_eq!(
+            range.get(3, 0).unwrap_err().msg,
+            RunErrorMsg::IndexOutOfBounds
+        );
+
+        // a single column
+        let values = range
+            .line(Axis::Y, 2)
+            .iter_non_blank()
+            .map(|(x, y, _)| (x, y))
+            .collect_vec();
+        assert_eq!(values, vec![(0, 1), (0, 2)]);
+    }
+}
@@ -49,24 +49,23 @@ fn get_functions() -> Vec<FormulaFunction> {
             fn VLOOKUP(
                 span: Span,
                 [search_key]: CellValue,
-                search_range: Array,
+                search_range: (Spanned<ArgValue>),
                 [output_col]: u32,
                 [is_sorted]: (Option<bool>),
             ) {
                 let needle = search_key;
-                let haystack = &(0..search_range.height())
-                    .filter_map(|y| search_range.get(0, y).ok())
-                    .collect_vec();
+                let haystack = &search_range.line_values(Axis::Y, 0)?;
+                let len = search_range.size()?[Axis::Y].get();
                 let match_mode = LookupMatchMode::Exact;
                 let search_mode = LookupSearchMode::from_is_sorted(is_sorted);
 
                 let x = output_col
                     .checked_sub(1)
                     .ok_or(RunErrorMsg::IndexOutOfBounds)?;
-                let y = lookup(needle, haystack, match_mode, search_mode)?
+                let y = lookup_line(needle, haystack, len, match_mode, search_mode)?
                     .ok_or_else(|| RunErrorMsg::NoMatch.with_span(span))?;
 
-                search_range.get(x, y as u32)?.clone()
+                search_range.get(x, y)?
             }
         ),
         formula_fn!(
@@ -89,25 +88,23 @@ fn get_functions() -> Vec<FormulaFunction> {
             fn HLOOKUP(
                 span: Span,
                 [search_key]: CellValue,
-                search_range: Array,
+                search_range: (Spanned<ArgValue>),
                 [output_row]: u32,
                 [is_sorted]: (Option<bool>),
             ) {
                 let needle = search_key;
-                let haystack = search_range
-                    .rows()
-                    .next()
-                    .ok_or_else(|| internal_error_value!("missing first row"))?;
+                let haystack = &search_range.line_values(Axis::X, 0)?;
+                let len = search_range.size()?[Axis::X].get();
                 let match_mode = LookupMatchMode::Exact;
                 let search_mode = LookupSearchMode::from_is_sorted(is_sorted);
 
-                let x = lookup(needle, haystack, match_mode, search_mode)?
+                let x = lookup_line(needle, haystack, len, match_mode, search_mode)?
                     .ok_or_else(|| RunErrorMsg::NoMatch.with_span(span))?;
                 let y = output_row
                     .checked_sub(1)
                     .ok_or(RunErrorMsg::IndexOutOfBounds)?;
 
-                search_range.get(x as u32, y)?.clone()
+                search_range.get(x, y)?
             }
         ),
         formula_fn!(
@@ -178,8 +175,8 @@ fn get_functions() -> Vec<FormulaFunction> {
             fn XLOOKUP(
                 span: Span,
                 search_key: (Spanned<Array>),
-                search_range: (Spanned<Array>),
-                output_range: (Spanned<Array>),
+                search_range: (Spanned<ArgValue>),
+                output_range: (Spanned<ArgValue>),
                 fallback: (Option<Spanned<Array>>),
                 match_mode: (Option<Spanned<i64>>),
                 search_mode: (Option<Spanned<i64>>),
@@ -236,21 +233,37 @@ fn get_functions() -> Vec<FormulaFunction> {
 
                 // Find the values for N, Q, and R, and error if there's an
                 // array mismatch.
-                let n = Array::common_len(search_axis, [&haystack, &returns].map(|v| v.as_ref()))?;
+                let haystack_size = Spanned {
+                    span: haystack.span,
+                    inner: haystack.size()?,
+                };
+                let returns_size = Spanned {
+                    span: returns.span,
+                    inner: returns.size()?,
+                };
+                let needle_size = needle.as_ref().map(|a| a.size());
+                let fallback_size = fallback.as_ref().map(|a| a.size());
+                let n = Array::common_len_of_sizes(search_axis, [haystack_size, returns_size])?;
                 returns.check_array_size_on(search_axis, n.get())?;
-                let q = Array::common_len(search_axis, [&needle, &fallback].map(|v| v.as_ref()))?;
-                let r = Array::common_len(
+                let q = Array::common_len_of_sizes(search_axis, [needle_size, fallback_size])?;
+                let r = Array::common_len_of_sizes(
                     non_search_axis,
-                    [&needle, &returns, &fallback].map(|v| v.as_ref()),
+                    [needle_size, returns_size, fallback_size],
                 )?;
 
                 // Perform the lookup for each needle.
-                let haystack_values = haystack.inner.cell_values_slice().iter().collect_vec();
+                let haystack_values = haystack.line_values(search_axis, 0)?;
                 let lookup_indices = (needle.inner.cell_values_slice().iter())
                     .map(|needle_value| {
-                        lookup(needle_value, &haystack_values, match_mode, search_mode)
+                        lookup_line(
+                            needle_value,
+                            &haystack_values,
+                            haystack_size.inner[search_axis].get(),
+                            match_mode,
+                            search_mode,
+                        )
                     })
-                    .collect::<CodeResult<Vec<Option<usize>>>>()?;
+                    .collect::<CodeResult<Vec<Option<u32>>>>()?;
 
                 // Construct the final output array.
                 let needle_size = needle.inner.size();
@@ -267,9 +280,9 @@ fn get_functions() -> Vec<FormulaFunction> {
                     let needle_index = needle_size.flatten_index(x, y)?;
                     match lookup_indices[needle_index] {
                         Some(i) => final_output_array.push({
-                            let x = if search_axis == Axis::X { i as u32 } else { x };
-                            let y = if search_axis == Axis::Y { i as u32 } else { y };
-                            returns.inner.get(x, y)?.clone()
+                            let x = if search_axis == Axis::X { i } else { x };
+                            let y = if search_axis == Axis::Y { i } else { y };
+                            returns.get(x, y)?
                         }),
                         None => final_output_array.push(fallback.inner.get(x, y)?.clone()),
                     }
@@ -309,7 +322,7 @@ fn get_functions() -> Vec<FormulaFunction> {
             #[zip_map]
             fn MATCH(
                 [search_key]: CellValue,
-                search_range: (Spanned<Array>),
+                search_range: (Spanned<ArgValue>),
                 match_mode: (Option<f64>),
             ) {
                 let match_mode = match_mode.unwrap_or(1.0);
@@ -327,8 +340,10 @@ fn get_functions() -> Vec<FormulaFunction> {
                     (LookupMatchMode::Wildcard, LookupSearchMode::LinearForward)
                 };
                 let needle = search_key;
-                let haystack = search_range.try_as_linear_array()?;
-                let index = lookup(needle, haystack, match_mode, search_mode)?
+                let axis = search_range.array_linear_axis()?.unwrap_or(Axis::X);
+                let haystack = &search_range.line_values(axis, 0)?;
+                let len = search_range.size()?[axis].get();
+                let index = lookup_line(needle, haystack, len, match_mode, search_mode)?
                     .ok_or(RunErrorMsg::NoMatch)?;
                 index as i64 + 1 // 1-indexed
             }
@@ -505,6 +520,37 @@ fn lookup<V: ToString + AsRef<CellValue>>(
     }))
 }
 
+/// Performs a `LOOKUP` over values along a row or column, which may skip
+/// blank cells in a range (see `line_values()`), and returns the
+/// index of the best match. `len` is the length of the row or column.
+fn lookup_line(
+    needle: &CellValue,
+    haystack: &[(u32, CellValue)],
+    len: u32,
+    match_mode: LookupMatchMode,
+    search_mode: LookupSearchMode,
+) -> CodeResult<Option<u32>> {
+    // A blank needle matches the first skipped blank cell, if there is one.
+    if matches!(needle, CellValue::Blank) {
+        let is_blank = |i: &u32| {
+            haystack
+                .binary_search_by_key(i, |(index, _)| *index)
+                .is_err()
+        };
+        let blank_index = match search_mode {
+            LookupSearchMode::LinearReverse => (0..len).rev().find(is_blank),
+            _ => (0..len).find(is_blank),
+        };
+        if blank_index.is_some() {
+            return Ok(blank_index);
+        }
+    }
+
+    let values = haystack.iter().map(|(_, value)| value).collect_vec();
+    let index = lookup(needle, &values, match_mode, search_mode)?;
+    Ok(index.map(|i| haystack[i].0))
+}
+
 /// Performs a `LOOKUP` using a wildcard and returns the index of the first
 /// match.
 fn lookup_regex<'a, V: 'a + ToString>(
@@ -1222,6 +1268,66 @@ mod tests {
         }
     }
 
+    #[test]
+    #[parallel]
+    fn test_lookup_large_range() {
+        let mut g = Grid::new();
+        let sheet = &mut g.sheets_mut()[0];
+        let _ = sheet.set_cell_value(pos![A10], "apple");
+        let _ = sheet.set_cell_value(pos![A500000], "banana");
+        let _ = sheet.set_cell_value(pos![B500000], 42);
+        let _ = sheet.set_cell_value(pos![J1], "banana");
+
+        // only the non-blank cells are read
+        assert_eq!(
+            "500000",
+            eval_to_string(&g, "MATCH(\"banana\", A1:A1000000, 0)")
+        );
+        assert_eq!("10", eval_to_string(&g, "MATCH(\"b*\", A1:Z1, 0)"));
+        assert_eq!(
+            "42",
+            eval_to_string(&g, "VLOOKUP(\"banana\", A1:B1000000, 2)"),
+        );
+        assert_eq!(
+            "{42}",
+            eval_to_string(&g, "XLOOKUP(\"banana\", A1:A1000000, B1:B1000000)"),
+        );
+        assert_eq!(
+            "banana",
+            eval_to_string(&g, "HLOOKUP(\"banana\", A1:Z1, 1)"),
+        );
+        assert_eq!(
+            RunErrorMsg::NoMatch,
+            eval_to_err(&g, "MATCH(\"cherry\", A1:A1000000, 0)").msg,
+        );
+    }
+
+    #[test]
+    #[parallel]
+    fn test_lookup_blank_needle() {
+        let mut g = Grid::new();
+        let sheet = &mut g.sheets_mut()[0];
+        let _ = sheet.set_cell_value(pos![A1], "apple");
+        let _ = sheet.set_cell_value(pos![A2], "banana");
+        let _ = sheet.set_cell_value(pos![A4], "cherry");
+        for y in 1..=5 {
+            let _ = sheet.set_cell_value(Pos { x: 2, y }, y * 10);
+        }
+
+        // an empty cell matches the first blank cell in the range
+        assert_eq!("{30}", eval_to_string(&g, "XLOOKUP(C1, A1:A5, B1:B5)"));
+        assert_eq!("3", eval_to_string(&g, "MATCH(C1, A1:A5, 0)"));
+        assert_eq!("30", eval_to_string(&g, "VLOOKUP(C1, A1:B5, 2)"));
+        assert_eq!(
+            "{50}",
+            eval_to_string(&g, "XLOOKUP(C1, A1:A5, B1:B5, , 0, -1)"),
+        );
+        assert_eq!(
+            RunErrorMsg::NoMatch,
+            eval_to_err(&g, "MATCH(C1, A1:A2, 0)").msg,
+        );
+    }
+
     #[test]
     fn test_match() {
         let mut g = Grid::test();
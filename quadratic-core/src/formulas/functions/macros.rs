@@ -102,6 +102,7 @@ macro_rules! see_docs_for_more_about_wildcards {
 /// - `String` - coerce to `String`
 /// - `f64` - coerce to `f64`
 /// - `bool` - coerce to `bool`
+/// - `Spanned<ArgValue>` - keep cell ranges unread (see `CellRange`)
 ///
 /// Generic types:
 /// - `arg: Option< ... >` - optional argument (type is `Option< ... >`)
@@ -177,7 +178,7 @@ macro_rules! formula_fn {
 macro_rules! formula_fn_eval {
     ($($tok:tt)*) => {{
         #[allow(unused_mut)]
-        let ret: FormulaFn = |_ctx: &mut Ctx<'_>, mut _args: FormulaFnArgs| -> CodeResult<Value> {
+        let ret: FormulaFn = |_ctx: &mut Ctx<'_>, mut _args: FormulaFnArgs<'_>| -> CodeResult<Value> {
             formula_fn_eval_inner!(_ctx, _args, $($tok)*)
         };
         ret
@@ -324,11 +325,18 @@ macro_rules! formula_fn_arg {
         // Do not flatten arrays.
         let mut $arg_name = $args.take_rest().map(Array::from).map(CodeResult::Ok);
     };
+    (@assign($ctx:ident, $args:ident); $arg_name:ident: Iter< Spanned< ArgValue $(>>)* $(>)*) => {
+        $args.error_if_no_more_args(stringify!($arg_name))?;
+
+        // Do not read cell ranges.
+        let mut $arg_name = $args.take_rest_args().map(CodeResult::Ok);
+    };
     (@assign($ctx:ident, $args:ident); $arg_name:ident: Iter< Spanned< $($arg_type:tt)*) => {
         $args.error_if_no_more_args(stringify!($arg_name))?;
 
-        // Flatten into iterator over non-array type.
-        let remaining_args = $args.take_rest();
+        // Flatten into iterator over non-array type. Cell ranges are not read
+        // from the grid, so that only their non-blank cells are visited.
+        let remaining_args = $args.take_rest_args();
         let mut $arg_name = remaining_args.flat_map(|arg_value| {
             formula_fn_convert_arg!(arg_value, Value -> Iter< Spanned< $($arg_type)*)
         });
@@ -339,6 +347,14 @@ macro_rules! formula_fn_arg {
         let mut $arg_name = $arg_name.without_spans();
     };
 
+    // Argument that may be an unread cell range
+    (@assign($ctx:ident, $args:ident); $arg_name:ident: Option< Spanned< ArgValue $(>>)* $(>)*) => {
+        let $arg_name = $args.take_next_optional_arg();
+    };
+    (@assign($ctx:ident, $args:ident); $arg_name:ident: Spanned< ArgValue $(>>)* $(>)*) => {
+        let $arg_name = $args.take_next_required_arg(stringify!($arg_name))?;
+    };
+
     // Optional argument
     (@assign($ctx:ident, $args:ident); $arg_name:ident: Option< $($arg_type:tt)*) => {
         let $arg_name = match $args.take_next_optional() {
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
 
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use lazy_static::lazy_static;
 
 #[macro_use]
@@ -18,10 +18,10 @@ mod string;
 mod tests;
 mod trigonometry;
 
-use super::{util, CellRef, Criterion, Ctx, Param, ParamKind};
+use super::{util, CellRange, CellRef, Criterion, Ctx, Param, ParamKind};
 use crate::{
-    Array, Axis, CellValue, CodeResult, CoerceInto, Duration, IsBlank, RunError, RunErrorMsg, Span,
-    Spanned, SpannedIterExt, Value,
+    Array, ArraySize, Axis, CellValue, CodeResult, CoerceInto, Duration, IsBlank, RunError,
+    RunErrorMsg, Span, Spanned, SpannedIterExt, Value,
 };
 
 pub use lookup::IndexFunctionArgs;
@@ -34,6 +34,15 @@ pub fn lookup_function(name: &str) -> Option<&'static FormulaFunction> {
     )
 }
 
+/// Functions whose results change without a change to their arguments.
+const VOLATILE_FUNCTIONS: &[&str] = &["NOW", "TODAY"];
+
+/// Returns whether a function's result changes without a change to its
+/// arguments.
+pub fn is_volatile_function(name: &str) -> bool {
+    lookup_function(name).is_some_and(|function| VOLATILE_FUNCTIONS.contains(&function.name))
+}
+
 pub const CATEGORIES: &[FormulaFunctionCategory] = &[
     operators::CATEGORY,
     mathematics::CATEGORY,
@@ -67,17 +76,163 @@ lazy_static! {
     };
 }
 
+/// Value of an argument passed to a formula function.
+#[derive(Debug, Clone)]
+pub enum ArgValue<'ctx> {
+    Value(Value),
+    /// Cell range that hasn't been read from the grid yet.
+    Range(CellRange<'ctx>),
+}
+impl From<Value> for ArgValue<'_> {
+    fn from(value: Value) -> Self {
+        ArgValue::Value(value)
+    }
+}
+impl<'ctx> Spanned<ArgValue<'ctx>> {
+    /// Reads the argument into a value.
+    pub fn into_value(self) -> Spanned<Value> {
+        self.map(|arg| match arg {
+            ArgValue::Value(value) => value,
+            ArgValue::Range(range) => match range.to_array() {
+                Ok(array) => Value::Array(array),
+                Err(e) => Value::from(e),
+            },
+        })
+    }
+
+    /// Returns the size of the argument. Returns an error for a tuple.
+    pub fn size(&self) -> CodeResult<ArraySize> {
+        match &self.inner {
+            ArgValue::Value(Value::Single(_)) => Ok(ArraySize::_1X1),
+            ArgValue::Value(Value::Array(array)) => Ok(array.size()),
+            ArgValue::Value(Value::Tuple(_)) => Err(RunErrorMsg::Expected {
+                expected: "single value or array".into(),
+                got: Some("tuple".into()),
+            }
+            .with_span(self.span)),
+            ArgValue::Range(range) => Ok(range.size()),
+        }
+    }
+
+    /// Checks that the argument is linear (width=1 or height=1), then returns
+    /// which is the long axis. Returns `None` for a single cell.
+    pub fn array_linear_axis(&self) -> CodeResult<Option<Axis>> {
+        let size = self.size()?;
+        match (size.w.get(), size.h.get()) {
+            (1, 1) => Ok(None),
+            (_, 1) => Ok(Some(Axis::X)), // height = 1
+            (1, _) => Ok(Some(Axis::Y)), // width = 1
+            _ => Err(RunErrorMsg::NonLinearArray.with_span(self.span)),
+        }
+    }
+
+    /// Checks the size of the argument on one axis, returning an error if it
+    /// does not match exactly.
+    pub fn check_array_size_on(&self, axis: Axis, len: u32) -> CodeResult<()> {
+        let expected = len;
+        let got = self.size()?[axis].get();
+        if expected == got {
+            Ok(())
+        } else {
+            Err(RunErrorMsg::ExactArrayAxisMismatch {
+                axis,
+                expected,
+                got,
+            }
+            .with_span(self.span))
+        }
+    }
+
+    /// Returns the value at (`x`, `y`) if this is an array or range, or the
+    /// single value itself otherwise.
+    pub fn get(&self, x: u32, y: u32) -> CodeResult<CellValue> {
+        match &self.inner {
+            ArgValue::Value(value) => value.get(x, y).cloned().map_err(|e| e.with_span(self.span)),
+            ArgValue::Range(range) => range.get(x, y),
+        }
+    }
+
+    /// Returns the values along `axis` at `index` on the other axis, along
+    /// with their indices. Blank cells in a range are skipped.
+    pub fn line_values(&self, axis: Axis, index: u32) -> CodeResult<Vec<(u32, CellValue)>> {
+        let size = self.size()?;
+        if index >= size[axis.other_axis()].get() {
+            return Err(RunErrorMsg::IndexOutOfBounds.with_span(self.span));
+        }
+        let index_on = |x: u32, y: u32| match axis {
+            Axis::X => x,
+            Axis::Y => y,
+        };
+        match &self.inner {
+            ArgValue::Value(_) => (0..size[axis].get())
+                .map(|i| {
+                    let (x, y) = match axis {
+                        Axis::X => (i, index),
+                        Axis::Y => (index, i),
+                    };
+                    Ok((i, self.get(x, y)?))
+                })
+                .collect(),
+            ArgValue::Range(range) => Ok(range
+                .line(axis, index)
+                .iter_non_blank()
+                .map(|(x, y, value)| (index_on(x, y), value))
+                .collect()),
+        }
+    }
+
+    /// Iterates over the values, converting them to a particular type. If a
+    /// value in an array or range cannot be converted, it is ignored. Blank
+    /// cells in a range are skipped without being read.
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T>(self) -> impl 'ctx + Iterator<Item = CodeResult<Spanned<T>>>
+    where
+        CellValue: TryInto<T, Error = RunErrorMsg>,
+        T: 'ctx,
+    {
+        let span = self.span;
+        match self.inner {
+            ArgValue::Value(value) => Either::Left(Spanned { span, inner: value }.into_iter()),
+            ArgValue::Range(range) => Either::Right(
+                range
+                    .iter_non_blank()
+                    .flat_map(move |(_, _, inner)| Spanned { span, inner }.coerce_or_none::<T>()),
+            ),
+        }
+    }
+
+    /// Iterates over cell values. Blank cells in a range are skipped without
+    /// being read.
+    pub fn into_iter_cell_values(
+        self,
+    ) -> impl 'ctx + Iterator<Item = CodeResult<Spanned<CellValue>>> {
+        let span = self.span;
+        match self.inner {
+            ArgValue::Value(value) => {
+                Either::Left(Spanned { span, inner: value }.into_iter_cell_values())
+            }
+            ArgValue::Range(range) => {
+                Either::Right(range.iter_non_blank().map(move |(_, _, value)| {
+                    value
+                        .into_non_error_value()
+                        .map(|inner| Spanned { span, inner })
+                }))
+            }
+        }
+    }
+}
+
 /// Argument values passed to a formula function.
-pub struct FormulaFnArgs {
+pub struct FormulaFnArgs<'ctx> {
     pub span: Span,
-    values: VecDeque<Spanned<Value>>,
+    values: VecDeque<Spanned<ArgValue<'ctx>>>,
     func_name: &'static str,
     args_popped: usize,
 }
-impl FormulaFnArgs {
+impl<'ctx> FormulaFnArgs<'ctx> {
     /// Constructs a list of arguments values.
     pub fn new(
-        values: impl Into<VecDeque<Spanned<Value>>>,
+        values: impl Into<VecDeque<Spanned<ArgValue<'ctx>>>>,
         span: Span,
         func_name: &'static str,
     ) -> Self {
@@ -92,25 +247,43 @@ impl FormulaFnArgs {
     pub fn has_next(&self) -> bool {
         !self.values.is_empty()
     }
-    /// Takes the next argument.
-    fn take_next(&mut self) -> Option<Spanned<Value>> {
+    /// Takes the next argument without reading it.
+    fn take_next_arg(&mut self) -> Option<Spanned<ArgValue<'ctx>>> {
         if !self.values.is_empty() {
             self.args_popped += 1;
         }
         self.values.pop_front()
     }
+    /// Takes the next argument.
+    fn take_next(&mut self) -> Option<Spanned<Value>> {
+        self.take_next_arg().map(|arg| arg.into_value())
+    }
     /// Takes the next argument, or returns `None` if there is none or the
     /// argument is blank˙.
     pub fn take_next_optional(&mut self) -> Option<Spanned<Value>> {
         self.take_next()
             .filter(|v| v.inner != Value::Single(CellValue::Blank))
     }
+    /// Takes the next argument without reading it, or returns `None` if there
+    /// is none or the argument is blank.
+    pub fn take_next_optional_arg(&mut self) -> Option<Spanned<ArgValue<'ctx>>> {
+        self.take_next_arg()
+            .filter(|v| !matches!(v.inner, ArgValue::Value(Value::Single(CellValue::Blank))))
+    }
     /// Takes the next argument, or returns an error if there is none.
     pub fn take_next_required(
         &mut self,
         arg_name: impl Into<Cow<'static, str>>,
     ) -> CodeResult<Spanned<Value>> {
-        self.take_next().ok_or_else(|| {
+        Ok(self.take_next_required_arg(arg_name)?.into_value())
+    }
+    /// Takes the next argument without reading it, or returns an error if
+    /// there is none.
+    pub fn take_next_required_arg(
+        &mut self,
+        arg_name: impl Into<Cow<'static, str>>,
+    ) -> CodeResult<Spanned<ArgValue<'ctx>>> {
+        self.take_next_arg().ok_or_else(|| {
             RunErrorMsg::MissingRequiredArgument {
                 func_name: self.func_name.into(),
                 arg_name: arg_name.into(),
@@ -119,7 +292,12 @@ impl FormulaFnArgs {
         })
     }
     /// Takes the rest of the arguments and iterates over them.
-    pub fn take_rest(&mut self) -> impl Iterator<Item = Spanned<Value>> {
+    pub fn take_rest(&mut self) -> impl Iterator<Item = Spanned<Value>> + 'ctx {
+        self.take_rest_args().map(|arg| arg.into_value())
+    }
+    /// Takes the rest of the arguments and iterates over them without reading
+    /// them.
+    pub fn take_rest_args(&mut self) -> impl Iterator<Item = Spanned<ArgValue<'ctx>>> {
         std::mem::take(&mut self.values).into_iter()
     }
 
@@ -151,7 +329,7 @@ impl FormulaFnArgs {
 }
 
 /// Function pointer that represents the body of a formula function.
-pub type FormulaFn = for<'a> fn(&'a mut Ctx<'_>, FormulaFnArgs) -> CodeResult<Value>;
+pub type FormulaFn = for<'a> fn(&'a mut Ctx<'_>, FormulaFnArgs<'_>) -> CodeResult<Value>;
 
 /// Formula function with associated metadata.
 pub struct FormulaFunction {
@@ -123,12 +123,27 @@ fn get_functions() -> Vec<FormulaFunction> {
             /// - Cells containing zero are not counted.
             /// - Cells with an error are not counted.
             #[examples("COUNTBLANK(A1:A10)")]
-            fn COUNTBLANK(range: (Iter<CellValue>)) {
-                // Ignore error values.
-                range
-                    .filter_map(|v| v.ok())
-                    .filter(|v| v.is_blank_or_empty_string())
-                    .count()
+            fn COUNTBLANK(range: (Iter<Spanned<ArgValue>>)) {
+                let mut count = 0;
+                for arg in range {
+                    let arg = arg?;
+                    // Blank cells in a range are skipped, so count the whole
+                    // range and then subtract the cells that aren't blank.
+                    let is_range = matches!(arg.inner, ArgValue::Range(_));
+                    if let ArgValue::Range(range) = &arg.inner {
+                        count += range.size().len();
+                    }
+                    for value in arg.into_iter_cell_values() {
+                        // Ignore error values.
+                        let is_blank = value.is_ok_and(|v| v.inner.is_blank_or_empty_string());
+                        match (is_range, is_blank) {
+                            (false, true) => count += 1,
+                            (true, false) => count -= 1,
+                            _ => (),
+                        }
+                    }
+                }
+                count
             }
         ),
         formula_fn!(
@@ -402,6 +417,31 @@ mod tests {
         assert_eq!("1", eval_to_string(&g, "COUNTBLANK(B3)"));
         assert_eq!("28", eval_to_string(&g, "COUNTBLANK(B3:C16)"));
         assert_eq!("3", eval_to_string(&g, "COUNTBLANK({B3, \"\", C6, \"0\"})"));
+
+        let mut g = Grid::new();
+        let sheet = &mut g.sheets_mut()[0];
+        let _ = sheet.set_cell_value(pos![B10], "");
+        let _ = sheet.set_cell_value(pos![B500000], 5);
+        assert_eq!("999999", eval_to_string(&g, "COUNTBLANK(B1:B1000000)"));
+    }
+
+    #[test]
+    #[parallel]
+    fn test_aggregate_large_range() {
+        let mut g = Grid::new();
+        let sheet = &mut g.sheets_mut()[0];
+        let _ = sheet.set_cell_value(pos![A10], 1);
+        let _ = sheet.set_cell_value(pos![B20], "text");
+        let _ = sheet.set_cell_value(pos![A500000], 2);
+        let _ = sheet.set_cell_value(pos![C1000000], 3);
+
+        // only the non-blank cells are read
+        assert_eq!("6", eval_to_string(&g, "SUM(A1:C1000000)"));
+        assert_eq!("3", eval_to_string(&g, "COUNT(A1:C1000000)"));
+        assert_eq!("4", eval_to_string(&g, "COUNTA(A1:C1000000)"));
+        assert_eq!("2", eval_to_string(&g, "AVERAGE(A1:C1000000)"));
+        assert_eq!("1", eval_to_string(&g, "MIN(A1:C1000000)"));
+        assert_eq!("3", eval_to_string(&g, "MAX(A1:C1000000)"));
     }
 
     #[test]
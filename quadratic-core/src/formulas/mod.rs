@@ -1,4 +1,5 @@
 pub mod ast;
+mod cache;
 mod cell_ref;
 mod criteria;
 mod ctx;
@@ -16,10 +17,11 @@ pub mod tests;
 
 use ast::AstNode;
 pub use ast::Formula;
+pub use cache::FormulaCache;
 pub use cell_ref::*;
 pub use criteria::Criterion;
-pub use ctx::Ctx;
-use functions::FormulaFnArgs;
+pub use ctx::{CellRange, Ctx};
+use functions::{ArgValue, FormulaFnArgs};
 use params::{Param, ParamKind};
 pub use parser::{
     find_cell_references, parse_and_check_formula, parse_formula, replace_a1_notation,
@@ -15,15 +15,19 @@ use super::*;
 use crate::{grid::Grid, CodeResult, CoerceInto, Pos, RunError, RunErrorMsg, Span, Spanned};
 
 pub fn parse_formula(source: &str, pos: Pos) -> CodeResult<ast::Formula> {
-    Ok(Formula {
-        ast: parse_exactly_one(source, pos, rules::Expression)?,
-    })
+    parse_formula_at(source, pos).map(|(formula, _)| formula)
 }
 
-fn parse_exactly_one<R: SyntaxRule>(source: &str, pos: Pos, rule: R) -> CodeResult<R::Output> {
+/// Parses a formula, also returning whether the result depends on `pos`. It
+/// does when the formula has A1-style references; internal R1C1-style
+/// references are already relative.
+pub(crate) fn parse_formula_at(source: &str, pos: Pos) -> CodeResult<(ast::Formula, bool)> {
     let tokens = lexer::tokenize(source).collect_vec();
     let mut p = Parser::new(source, &tokens, pos);
-    p.parse(rule).and_then(|output| p.ok_if_not_eof(output))
+    let ast = p
+        .parse(rules::Expression)
+        .and_then(|output| p.ok_if_not_eof(output))?;
+    Ok((Formula { ast }, p.used_pos))
 }
 
 pub fn find_cell_references(source: &str, pos: Pos) -> Vec<Spanned<RangeRef>> {
@@ -166,6 +170,8 @@ pub struct Parser<'a> {
 
     /// Coordinates of the cell where this formula was entered.
     pub pos: Pos,
+    /// Whether a reference was parsed relative to `pos`.
+    pub used_pos: bool,
 }
 impl<'a> Parser<'a> {
     /// Constructs a parser for a file.
@@ -176,6 +182,7 @@ impl<'a> Parser<'a> {
             cursor: None,
 
             pos,
+            used_pos: false,
         };
 
         // Skip leading `=`
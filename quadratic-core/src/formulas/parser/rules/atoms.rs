@@ -102,8 +102,9 @@ impl SyntaxRule for CellReference {
 
         p.next();
 
-        let cell_ref = CellRef::parse_a1(p.token_str(), p.pos)
-            .or_else(|| CellRef::from_str(p.token_str()).ok());
+        let a1_ref = CellRef::parse_a1(p.token_str(), p.pos);
+        p.used_pos |= a1_ref.is_some();
+        let cell_ref = a1_ref.or_else(|| CellRef::from_str(p.token_str()).ok());
 
         cell_ref.map_or_else(
             || Err(RunErrorMsg::BadCellReference.with_span(p.span())),
@@ -454,3 +454,14 @@ fn test_syntax_check_ok() {
     assert_check_syntax_succeeds(&g, "XLOOKUP(\"zebra\", A1:Z1, A4:Z6)");
     assert_check_syntax_succeeds(&g, "ABS(({1, 2; 3, 4}, A1:C10))");
 }
+
+#[test]
+fn test_formula_is_volatile() {
+    let is_volatile = |s: &str| parse_formula(s, Pos::ORIGIN).unwrap().is_volatile();
+
+    assert!(is_volatile("NOW()"));
+    assert!(is_volatile("IF(A1, 1, today() + 1)"));
+    assert!(is_volatile("{1, NOW()}"));
+    assert!(!is_volatile("SUM(A1:A5, 1)"));
+    assert!(!is_volatile("DATE(2024, 1, 1)"));
+}
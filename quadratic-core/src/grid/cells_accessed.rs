@@ -102,6 +102,13 @@ impl CellsAccessed {
         self.add(sheet_rect.sheet_id, range);
     }
 
+    /// Adds all ranges from another CellsAccessed.
+    pub fn extend(&mut self, other: CellsAccessed) {
+        for (sheet_id, ranges) in other.cells {
+            self.cells.entry(sheet_id).or_default().extend(ranges);
+        }
+    }
+
     /// Whether the CellsAccessed intersects the SheetRect.
     pub fn intersects(&self, sheet_rect: &SheetRect) -> bool {
         let rect: Rect = (*sheet_rect).into();
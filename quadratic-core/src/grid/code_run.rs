@@ -52,6 +52,8 @@ impl From<CodeRunOld> for CodeRun {
             line_number: old.line_number,
             output_type: old.output_type,
             last_modified: old.last_modified,
+            refresh: CodeCellRefresh::default(),
+            volatile: false,
         }
     }
 }
@@ -83,9 +85,34 @@ pub struct CodeRun {
     pub output_type: Option<String>,
 
     pub last_modified: DateTime<Utc>,
+
+    /// when the code cell runs again without a change to the cells it reads
+    #[serde(default)]
+    pub refresh: CodeCellRefresh,
+
+    /// whether the result changes without a change to the cells it reads (eg,
+    /// a formula that calls NOW()); derived from the formula, so it's not
+    /// saved in the file
+    #[serde(default)]
+    pub volatile: bool,
 }
 
 impl CodeRun {
+    /// Returns whether the code cell should run again. `opened` is true when
+    /// the file was just opened. Volatile code cells without a refresh policy
+    /// run again when the file is opened.
+    pub fn is_refresh_due(&self, now: DateTime<Utc>, opened: bool) -> bool {
+        match self.refresh {
+            CodeCellRefresh::Manual => opened && self.volatile,
+            CodeCellRefresh::OnOpen => opened,
+            CodeCellRefresh::Every { minutes } => {
+                opened
+                    || now.signed_duration_since(self.last_modified)
+                        >= chrono::Duration::minutes(minutes.max(1).into())
+            }
+        }
+    }
+
     /// Returns the output value of a code run at the relative location (ie, (0,0) is the top of the code run result).
     /// A spill or error returns [`CellValue::Blank`]. Note: this assumes a [`CellValue::Code`] exists at the location.
     pub fn cell_value_at(&self, x: u32, y: u32) -> Option<CellValue> {
@@ -192,6 +219,21 @@ pub enum CodeCellLanguage {
     Javascript,
 }
 
+/// When a code cell runs again without a change to the cells it reads.
+#[derive(Serialize, Deserialize, Default, Copy, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "js", derive(ts_rs::TS))]
+pub enum CodeCellRefresh {
+    /// only when the user runs it
+    #[default]
+    Manual,
+
+    /// when the file is opened
+    OnOpen,
+
+    /// every `minutes` minutes while the file is open (and when it's opened)
+    Every { minutes: u32 },
+}
+
 #[derive(Serialize, Deserialize, Display, Copy, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "js", derive(ts_rs::TS))]
 #[serde(rename_all = "UPPERCASE")]
@@ -263,6 +305,8 @@ mod test {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         assert_eq!(code_run.output_size(), ArraySize::_1X1);
         assert_eq!(
@@ -290,6 +334,8 @@ mod test {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         assert_eq!(code_run.output_size().w.get(), 10);
         assert_eq!(code_run.output_size().h.get(), 11);
@@ -323,6 +369,8 @@ mod test {
             output_type: None,
             spill_error: true,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         assert_eq!(code_run.output_size().w.get(), 10);
         assert_eq!(code_run.output_size().h.get(), 11);
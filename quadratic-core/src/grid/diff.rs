@@ -0,0 +1,622 @@
+//! Cell-level diff between two versions of a grid.
+//!
+//! Sheets are matched by id. For sheets in both grids, the diff reports
+//! changed cell values, code, formats, borders and validations. Code outputs
+//! are not compared since they are derived from the code.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::sheet::borders::{BorderSide, BorderStyle, Borders};
+use super::sheet::validations::validation::Validation;
+use super::{CodeCellValue, Contiguous2D, Format, Grid, Sheet, SheetFormatting, SheetId};
+use crate::{CellValue, Pos};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct GridDiff {
+    pub sheets_added: Vec<SheetInfo>,
+    pub sheets_removed: Vec<SheetInfo>,
+
+    /// Sheets in both grids that have changes.
+    pub sheets: Vec<SheetDiff>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SheetInfo {
+    pub id: SheetId,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SheetDiff {
+    pub sheet_id: SheetId,
+    pub name: String,
+
+    /// Previous name, set only if the sheet was renamed.
+    pub old_name: Option<String>,
+
+    pub cells: Vec<CellValueDiff>,
+    pub code: Vec<CodeDiff>,
+    pub formats: Vec<FormatDiff>,
+    pub borders: Vec<BorderDiff>,
+    pub validations: Vec<ValidationDiff>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CellValueDiff {
+    pub pos: Pos,
+    pub old: Option<CellValue>,
+    pub new: Option<CellValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CodeDiff {
+    pub pos: Pos,
+    pub old: Option<CodeCellValue>,
+    pub new: Option<CodeCellValue>,
+}
+
+/// A region with the same change. Coordinates are inclusive; `None` means
+/// the region extends to the end of the sheet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FormatDiff {
+    pub x1: i64,
+    pub y1: i64,
+    pub x2: Option<i64>,
+    pub y2: Option<i64>,
+    pub old: Format,
+    pub new: Format,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct BorderSides {
+    pub top: Option<BorderStyle>,
+    pub bottom: Option<BorderStyle>,
+    pub left: Option<BorderStyle>,
+    pub right: Option<BorderStyle>,
+}
+
+/// A region with the same border change. Coordinates are inclusive; `None`
+/// means the region extends to the end of the sheet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BorderDiff {
+    pub x1: i64,
+    pub y1: i64,
+    pub x2: Option<i64>,
+    pub y2: Option<i64>,
+    pub old: BorderSides,
+    pub new: BorderSides,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ValidationDiff {
+    Added(Validation),
+    Removed(Validation),
+    Changed {
+        old: Box<Validation>,
+        new: Box<Validation>,
+    },
+}
+
+impl GridDiff {
+    /// Compares two versions of a grid.
+    pub fn new(old: &Grid, new: &Grid) -> Self {
+        let mut diff = GridDiff::default();
+
+        for new_sheet in new.sheets() {
+            match old.try_sheet(new_sheet.id) {
+                Some(old_sheet) => {
+                    let sheet_diff = SheetDiff::new(old_sheet, new_sheet);
+                    if !sheet_diff.is_empty() {
+                        diff.sheets.push(sheet_diff);
+                    }
+                }
+                None => diff.sheets_added.push(SheetInfo::from(new_sheet)),
+            }
+        }
+
+        diff.sheets_removed = old
+            .sheets()
+            .iter()
+            .filter(|sheet| new.try_sheet(sheet.id).is_none())
+            .map(SheetInfo::from)
+            .collect();
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sheets_added.is_empty() && self.sheets_removed.is_empty() && self.sheets.is_empty()
+    }
+}
+
+impl From<&Sheet> for SheetInfo {
+    fn from(sheet: &Sheet) -> Self {
+        SheetInfo {
+            id: sheet.id,
+            name: sheet.name.clone(),
+        }
+    }
+}
+
+impl SheetDiff {
+    /// Compares two versions of the same sheet.
+    pub fn new(old: &Sheet, new: &Sheet) -> Self {
+        let (cells, code) = diff_values(old, new);
+
+        SheetDiff {
+            sheet_id: new.id,
+            name: new.name.clone(),
+            old_name: (old.name != new.name).then(|| old.name.clone()),
+            cells,
+            code,
+            formats: diff_formats(&old.formats, &new.formats),
+            borders: diff_borders(&old.borders, &new.borders),
+            validations: diff_validations(
+                &old.validations.validations,
+                &new.validations.validations,
+            ),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.old_name.is_none()
+            && self.cells.is_empty()
+            && self.code.is_empty()
+            && self.formats.is_empty()
+            && self.borders.is_empty()
+            && self.validations.is_empty()
+    }
+}
+
+/// Values of a sheet by position.
+fn values(sheet: &Sheet) -> BTreeMap<Pos, &CellValue> {
+    sheet
+        .columns
+        .iter()
+        .flat_map(|(&x, column)| {
+            column
+                .values
+                .iter()
+                .map(move |(&y, value)| (Pos { x, y }, value))
+        })
+        .collect()
+}
+
+/// Compares cell values, splitting out code cells.
+fn diff_values(old: &Sheet, new: &Sheet) -> (Vec<CellValueDiff>, Vec<CodeDiff>) {
+    let old_values = values(old);
+    let new_values = values(new);
+    let positions = old_values
+        .keys()
+        .chain(new_values.keys())
+        .copied()
+        .collect::<BTreeSet<_>>();
+
+    let mut cells = vec![];
+    let mut code = vec![];
+    for pos in positions {
+        let old_value = old_values.get(&pos).copied();
+        let new_value = new_values.get(&pos).copied();
+        if old_value == new_value {
+            continue;
+        }
+
+        let as_code = |value: Option<&CellValue>| match value {
+            Some(CellValue::Code(code)) => Some(code.clone()),
+            _ => None,
+        };
+        let as_value = |value: Option<&CellValue>| match value {
+            Some(CellValue::Code(_)) | Some(CellValue::Blank) | None => None,
+            Some(value) => Some(value.clone()),
+        };
+
+        let (old_code, new_code) = (as_code(old_value), as_code(new_value));
+        if old_code != new_code {
+            code.push(CodeDiff {
+                pos,
+                old: old_code,
+                new: new_code,
+            });
+        }
+        let (old_value, new_value) = (as_value(old_value), as_value(new_value));
+        if old_value != new_value {
+            cells.push(CellValueDiff {
+                pos,
+                old: old_value,
+                new: new_value,
+            });
+        }
+    }
+
+    (cells, code)
+}
+
+/// The bounds of a rect with values: `(x1, y1, x2, y2)`.
+type Bounds = (i64, i64, Option<i64>, Option<i64>);
+
+/// Adds the bounds of the rects with values to `rects`.
+fn add_rects<T: Clone + PartialEq + std::fmt::Debug>(
+    values: &Contiguous2D<Option<T>>,
+    rects: &mut Vec<Bounds>,
+) {
+    rects.extend(
+        values
+            .to_rects()
+            .map(|(x1, y1, x2, y2, _)| (x1, y1, x2, y2)),
+    );
+}
+
+fn format_rects(formats: &SheetFormatting, rects: &mut Vec<Bounds>) {
+    add_rects(&formats.align, rects);
+    add_rects(&formats.vertical_align, rects);
+    add_rects(&formats.wrap, rects);
+    add_rects(&formats.numeric_format, rects);
+    add_rects(&formats.numeric_decimals, rects);
+    add_rects(&formats.numeric_commas, rects);
+    add_rects(&formats.bold, rects);
+    add_rects(&formats.italic, rects);
+    add_rects(&formats.text_color, rects);
+    add_rects(&formats.fill_color, rects);
+    add_rects(&formats.render_size, rects);
+    add_rects(&formats.date_time, rects);
+    add_rects(&formats.underline, rects);
+    add_rects(&formats.strike_through, rects);
+    add_rects(&formats.font_size, rects);
+    add_rects(&formats.font_family, rects);
+    add_rects(&formats.text_rotation, rects);
+}
+
+/// A changed region: `(x1, y1, x2, y2, old, new)`.
+type Region<T> = (i64, i64, Option<i64>, Option<i64>, T, T);
+
+/// Splits the sheet into column bands at the x edges of `rects`, and each
+/// column band into regions at the y edges of only the rects that cover it.
+/// Values are constant within a region, so `get` is called once per region
+/// and the regions where it returns different values for the old and new
+/// versions are returned. Vertically adjacent regions with the same change
+/// are merged.
+fn changed_regions<T: PartialEq>(
+    rects: Vec<Bounds>,
+    get: impl Fn(Pos) -> (T, T),
+) -> Vec<Region<T>> {
+    // rects that start and end at each x edge
+    let mut starts = BTreeMap::<i64, Vec<&Bounds>>::new();
+    let mut ends = BTreeMap::<i64, Vec<&Bounds>>::new();
+    for rect in &rects {
+        starts.entry(rect.0).or_default().push(rect);
+        if let Some(x2) = rect.2 {
+            ends.entry(x2 + 1).or_default().push(rect);
+        }
+    }
+    let xs = starts
+        .keys()
+        .chain(ends.keys())
+        .copied()
+        .chain([1])
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    // y edges of the rects covering the current column band, counted since
+    // rects may share an edge
+    let mut ys = BTreeMap::from([(1, 1)]);
+    fn update_ys(ys: &mut BTreeMap<i64, usize>, rects: Option<&Vec<&Bounds>>, add: bool) {
+        for &&(_, y1, _, y2) in rects.into_iter().flatten() {
+            for y in std::iter::once(y1).chain(y2.map(|y2| y2 + 1)) {
+                let count = ys.entry(y).or_insert(0);
+                if add {
+                    *count += 1;
+                } else {
+                    *count -= 1;
+                    if *count == 0 {
+                        ys.remove(&y);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut regions: Vec<Region<T>> = vec![];
+    for (i, &x1) in xs.iter().enumerate() {
+        update_ys(&mut ys, ends.get(&x1), false);
+        update_ys(&mut ys, starts.get(&x1), true);
+        if x1 < 1 {
+            continue;
+        }
+        let x2 = xs.get(i + 1).map(|x| x - 1);
+
+        let y_edges = ys.keys().copied().filter(|&y| y >= 1).collect::<Vec<_>>();
+        let y_bands = y_edges
+            .iter()
+            .enumerate()
+            .map(|(j, &y1)| (y1, y_edges.get(j + 1).map(|y| y - 1)));
+        for (y1, y2) in y_bands {
+            let (old, new) = get(Pos { x: x1, y: y1 });
+            if old == new {
+                continue;
+            }
+            if let Some(last) = regions.last_mut() {
+                if last.0 == x1
+                    && last.3.map(|y| y + 1) == Some(y1)
+                    && last.4 == old
+                    && last.5 == new
+                {
+                    last.3 = y2;
+                    continue;
+                }
+            }
+            regions.push((x1, y1, x2, y2, old, new));
+        }
+    }
+    regions
+}
+
+fn diff_formats(old: &SheetFormatting, new: &SheetFormatting) -> Vec<FormatDiff> {
+    let mut rects = vec![];
+    format_rects(old, &mut rects);
+    format_rects(new, &mut rects);
+
+    changed_regions(rects, |pos| (old.format(pos), new.format(pos)))
+        .into_iter()
+        .map(|(x1, y1, x2, y2, old, new)| FormatDiff {
+            x1,
+            y1,
+            x2,
+            y2,
+            old,
+            new,
+        })
+        .collect()
+}
+
+fn border_sides(borders: &Borders, pos: Pos) -> BorderSides {
+    BorderSides {
+        top: borders.get(BorderSide::Top, pos),
+        bottom: borders.get(BorderSide::Bottom, pos),
+        left: borders.get(BorderSide::Left, pos),
+        right: borders.get(BorderSide::Right, pos),
+    }
+}
+
+fn diff_borders(old: &Borders, new: &Borders) -> Vec<BorderDiff> {
+    let mut rects = vec![];
+    for borders in [old, new] {
+        add_rects(&borders.top, &mut rects);
+        add_rects(&borders.bottom, &mut rects);
+        add_rects(&borders.left, &mut rects);
+        add_rects(&borders.right, &mut rects);
+    }
+
+    // timestamps are ignored since they don't change how a border looks
+    changed_regions(rects, |pos| {
+        (border_sides(old, pos), border_sides(new, pos))
+    })
+    .into_iter()
+    .map(|(x1, y1, x2, y2, old, new)| BorderDiff {
+        x1,
+        y1,
+        x2,
+        y2,
+        old,
+        new,
+    })
+    .collect()
+}
+
+fn diff_validations(old: &[Validation], new: &[Validation]) -> Vec<ValidationDiff> {
+    let mut diffs = vec![];
+    for new_validation in new {
+        match old.iter().find(|v| v.id == new_validation.id) {
+            Some(old_validation) if old_validation != new_validation => {
+                diffs.push(ValidationDiff::Changed {
+                    old: Box::new(old_validation.clone()),
+                    new: Box::new(new_validation.clone()),
+                });
+            }
+            Some(_) => (),
+            None => diffs.push(ValidationDiff::Added(new_validation.clone())),
+        }
+    }
+    diffs.extend(
+        old.iter()
+            .filter(|v| !new.iter().any(|new_validation| new_validation.id == v.id))
+            .map(|v| ValidationDiff::Removed(v.clone())),
+    );
+    diffs
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::controller::GridController;
+    use crate::grid::sheet::borders::BorderSelection;
+    use crate::grid::CodeCellLanguage;
+    use crate::A1Selection;
+
+    #[test]
+    fn test_diff_sheets() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.add_sheet(None);
+        let removed_id = gc.sheet_ids()[1];
+        let old = gc.grid().clone();
+
+        assert!(GridDiff::new(&old, gc.grid()).is_empty());
+
+        gc.set_sheet_name(sheet_id, "Renamed".to_string(), None);
+        gc.delete_sheet(removed_id, None);
+        gc.add_sheet(None);
+        let added_id = gc.sheet_ids()[1];
+
+        let diff = GridDiff::new(&old, gc.grid());
+        assert_eq!(
+            diff.sheets_added,
+            vec![SheetInfo {
+                id: added_id,
+                name: "Sheet 1".to_string()
+            }]
+        );
+        assert_eq!(
+            diff.sheets_removed,
+            vec![SheetInfo {
+                id: removed_id,
+                name: "Sheet 2".to_string()
+            }]
+        );
+        assert_eq!(diff.sheets.len(), 1);
+        assert_eq!(diff.sheets[0].name, "Renamed");
+        assert_eq!(diff.sheets[0].old_name, Some("Sheet 1".to_string()));
+    }
+
+    #[test]
+    fn test_diff_values_and_code() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value((1, 1, sheet_id).into(), "same".to_string(), None);
+        gc.set_cell_value((1, 2, sheet_id).into(), "old".to_string(), None);
+        gc.set_cell_value((1, 3, sheet_id).into(), "removed".to_string(), None);
+        gc.set_code_cell(
+            (1, 4, sheet_id).into(),
+            CodeCellLanguage::Formula,
+            "1 + 1".to_string(),
+            None,
+        );
+        let old = gc.grid().clone();
+
+        gc.set_cell_value((1, 2, sheet_id).into(), "new".to_string(), None);
+        gc.set_cell_value((1, 3, sheet_id).into(), "".to_string(), None);
+        gc.set_code_cell(
+            (1, 4, sheet_id).into(),
+            CodeCellLanguage::Formula,
+            "2 + 2".to_string(),
+            None,
+        );
+        gc.set_cell_value((2, 1, sheet_id).into(), "added".to_string(), None);
+
+        let diff = GridDiff::new(&old, gc.grid());
+        let sheet_diff = &diff.sheets[0];
+        assert_eq!(
+            sheet_diff.cells,
+            vec![
+                CellValueDiff {
+                    pos: Pos { x: 1, y: 2 },
+                    old: Some(CellValue::Text("old".to_string())),
+                    new: Some(CellValue::Text("new".to_string())),
+                },
+                CellValueDiff {
+                    pos: Pos { x: 1, y: 3 },
+                    old: Some(CellValue::Text("removed".to_string())),
+                    new: None,
+                },
+                CellValueDiff {
+                    pos: Pos { x: 2, y: 1 },
+                    old: None,
+                    new: Some(CellValue::Text("added".to_string())),
+                },
+            ]
+        );
+        assert_eq!(
+            sheet_diff.code,
+            vec![CodeDiff {
+                pos: Pos { x: 1, y: 4 },
+                old: Some(CodeCellValue {
+                    language: CodeCellLanguage::Formula,
+                    code: "1 + 1".to_string(),
+                }),
+                new: Some(CodeCellValue {
+                    language: CodeCellLanguage::Formula,
+                    code: "2 + 2".to_string(),
+                }),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_formats_and_borders() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_bold(&A1Selection::test_a1("A1:B2"), true, None)
+            .unwrap();
+        let old = gc.grid().clone();
+
+        // column C is bold, B2 is not bold
+        gc.set_bold(&A1Selection::test_a1("C"), true, None).unwrap();
+        gc.set_bold(&A1Selection::test_a1("B2"), false, None)
+            .unwrap();
+        gc.set_borders(
+            A1Selection::test_a1("D5"),
+            BorderSelection::Top,
+            Some(BorderStyle::default()),
+            None,
+        );
+
+        let diff = GridDiff::new(&old, gc.grid());
+        let sheet_diff = &diff.sheets[0];
+        assert_eq!(sheet_diff.sheet_id, sheet_id);
+
+        let bold = Format {
+            bold: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            sheet_diff.formats,
+            vec![
+                FormatDiff {
+                    x1: 2,
+                    y1: 2,
+                    x2: Some(2),
+                    y2: Some(2),
+                    old: bold.clone(),
+                    new: Format {
+                        bold: Some(false),
+                        ..Default::default()
+                    },
+                },
+                FormatDiff {
+                    x1: 3,
+                    y1: 1,
+                    x2: Some(3),
+                    y2: None,
+                    old: Format::default(),
+                    new: bold,
+                },
+            ]
+        );
+
+        assert_eq!(sheet_diff.borders.len(), 1);
+        let border_diff = &sheet_diff.borders[0];
+        assert_eq!(
+            (
+                border_diff.x1,
+                border_diff.y1,
+                border_diff.x2,
+                border_diff.y2
+            ),
+            (4, 5, Some(4), Some(5))
+        );
+        assert_eq!(border_diff.old, BorderSides::default());
+        assert!(border_diff.new.top.is_some());
+    }
+
+    #[test]
+    fn test_changed_regions_probes_covering_rects() {
+        // a diagonal of single cells: each column band is only split by the
+        // cell in it
+        let rects = (1..=100)
+            .map(|i| (i, i, Some(i), Some(i)))
+            .collect::<Vec<_>>();
+        let probes = std::cell::Cell::new(0);
+        let regions = changed_regions(rects, |pos| {
+            probes.set(probes.get() + 1);
+            (false, pos.x == pos.y && pos.x <= 100)
+        });
+
+        assert_eq!(regions.len(), 100);
+        assert_eq!(regions[0], (1, 1, Some(1), Some(1), false, true));
+        assert_eq!(regions[99], (100, 100, Some(100), Some(100), false, true));
+        assert!(probes.get() <= 400);
+    }
+}
@@ -13,7 +13,7 @@ pub use shift_negative_offsets::add_import_offset_to_contiguous_2d_rect;
 use shift_negative_offsets::shift_negative_offsets;
 use std::fmt::Debug;
 use std::str;
-pub use v1_7_1::GridSchema as current;
+pub use v1_8::GridSchema as current;
 
 mod migrate_code_cell_references;
 pub mod serialize;
@@ -25,10 +25,11 @@ mod v1_5;
 mod v1_6;
 mod v1_7;
 pub mod v1_7_1;
+pub mod v1_8;
 
-pub use v1_7_1::{CellsAccessedSchema, CodeRunSchema};
+pub use v1_8::{CellsAccessedSchema, CodeRunSchema};
 
-pub static CURRENT_VERSION: &str = "1.7.1";
+pub static CURRENT_VERSION: &str = "1.8";
 pub static SERIALIZATION_FORMAT: SerializationFormat = SerializationFormat::Json;
 pub static COMPRESSION_FORMAT: CompressionFormat = CompressionFormat::Zlib;
 pub static HEADER_SERIALIZATION_FORMAT: SerializationFormat = SerializationFormat::Bincode;
@@ -41,6 +42,11 @@ pub struct FileVersion {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "version")]
 enum GridFile {
+    #[serde(rename = "1.8")]
+    V1_8 {
+        #[serde(flatten)]
+        grid: v1_8::GridSchema,
+    },
     #[serde(rename = "1.7.1")]
     V1_7_1 {
         #[serde(flatten)]
@@ -74,19 +80,20 @@ enum GridFile {
 }
 
 impl GridFile {
-    fn into_latest(self) -> Result<v1_7_1::GridSchema> {
+    fn into_latest(self) -> Result<v1_8::GridSchema> {
         match self {
-            GridFile::V1_7_1 { grid } => Ok(grid),
-            GridFile::V1_7 { grid } => v1_7::upgrade(grid),
-            GridFile::V1_6 { grid } => v1_7::upgrade(v1_6::file::upgrade(grid)?),
-            GridFile::V1_5 { grid } => {
-                v1_7::upgrade(v1_6::file::upgrade(v1_5::file::upgrade(grid)?)?)
-            }
-            GridFile::V1_4 { grid } => v1_7::upgrade(v1_6::file::upgrade(v1_5::file::upgrade(
-                v1_4::file::upgrade(grid)?,
+            GridFile::V1_8 { grid } => Ok(grid),
+            GridFile::V1_7_1 { grid } => v1_7_1::upgrade(grid),
+            GridFile::V1_7 { grid } => v1_7_1::upgrade(v1_7::upgrade(grid)?),
+            GridFile::V1_6 { grid } => v1_7_1::upgrade(v1_7::upgrade(v1_6::file::upgrade(grid)?)?),
+            GridFile::V1_5 { grid } => v1_7_1::upgrade(v1_7::upgrade(v1_6::file::upgrade(
+                v1_5::file::upgrade(grid)?,
             )?)?),
-            GridFile::V1_3 { grid } => v1_7::upgrade(v1_6::file::upgrade(v1_5::file::upgrade(
-                v1_4::file::upgrade(v1_3::file::upgrade(grid)?)?,
+            GridFile::V1_4 { grid } => v1_7_1::upgrade(v1_7::upgrade(v1_6::file::upgrade(
+                v1_5::file::upgrade(v1_4::file::upgrade(grid)?)?,
+            )?)?),
+            GridFile::V1_3 { grid } => v1_7_1::upgrade(v1_7::upgrade(v1_6::file::upgrade(
+                v1_5::file::upgrade(v1_4::file::upgrade(v1_3::file::upgrade(grid)?)?)?,
             )?)?),
         }
     }
@@ -117,7 +124,7 @@ fn import_binary(file_contents: Vec<u8>) -> Result<Grid> {
                 data,
             )?;
             drop(file_contents);
-            let schema = v1_7::upgrade(v1_6::file::upgrade(schema)?)?;
+            let schema = v1_7_1::upgrade(v1_7::upgrade(v1_6::file::upgrade(schema)?)?)?;
             Ok(serialize::import(schema)?)
         }
         "1.7" => {
@@ -128,9 +135,18 @@ fn import_binary(file_contents: Vec<u8>) -> Result<Grid> {
                 data,
             )?;
             drop(file_contents);
-            Ok(serialize::import(v1_7::upgrade(schema)?)?)
+            Ok(serialize::import(v1_7_1::upgrade(v1_7::upgrade(schema)?)?)?)
         }
         "1.7.1" => {
+            let schema = decompress_and_deserialize::<v1_7_1::GridSchema>(
+                &SERIALIZATION_FORMAT,
+                &COMPRESSION_FORMAT,
+                data,
+            )?;
+            drop(file_contents);
+            Ok(serialize::import(v1_7_1::upgrade(schema)?)?)
+        }
+        "1.8" => {
             let schema = decompress_and_deserialize::<current>(
                 &SERIALIZATION_FORMAT,
                 &COMPRESSION_FORMAT,
@@ -648,4 +664,35 @@ mod tests {
         let imported = import(exported).unwrap();
         assert_eq!(imported, gc.grid().clone());
     }
+
+    #[test]
+    #[parallel]
+    fn process_a_v1_8_font_formats_file() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        gc.set_font_size(&A1Selection::test_a1("A1:B2"), Some(18), None)
+            .unwrap();
+        gc.set_font_family(
+            &A1Selection::test_a1("B"),
+            Some("Georgia".to_string()),
+            None,
+        )
+        .unwrap();
+        gc.set_text_rotation(&A1Selection::test_a1("3"), Some(-45), None)
+            .unwrap();
+
+        let exported = export(gc.grid().clone()).unwrap();
+        let imported = import(exported).unwrap();
+        assert_eq!(imported, gc.grid().clone());
+
+        let format = imported
+            .try_sheet(sheet_id)
+            .unwrap()
+            .formats
+            .format(pos![B3]);
+        assert_eq!(format.font_size, None);
+        assert_eq!(format.font_family, Some("Georgia".to_string()));
+        assert_eq!(format.text_rotation, Some(-45));
+    }
 }
@@ -15,7 +15,7 @@ use crate::{
     small_timestamp::SmallTimestamp,
 };
 
-fn import_rgba(schema: current::RgbaSchema) -> Rgba {
+pub(crate) fn import_rgba(schema: current::RgbaSchema) -> Rgba {
     Rgba {
         red: schema.red,
         green: schema.green,
@@ -59,7 +59,7 @@ pub(crate) fn import_borders(borders: current::BordersSchema) -> Borders {
     }
 }
 
-fn export_rgba(color: Rgba) -> current::RgbaSchema {
+pub(crate) fn export_rgba(color: Rgba) -> current::RgbaSchema {
     current::RgbaSchema {
         red: color.red,
         green: color.green,
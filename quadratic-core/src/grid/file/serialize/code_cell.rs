@@ -6,7 +6,7 @@ use indexmap::IndexMap;
 use itertools::Itertools;
 
 use crate::{
-    grid::{CellsAccessed, CodeRun, CodeRunResult, SheetId},
+    grid::{CellsAccessed, CodeCellRefresh, CodeRun, CodeRunResult, SheetId},
     CellRefCoord, CellRefRange, CellRefRangeEnd, Pos, RefRangeBounds, Value,
 };
 
@@ -94,12 +94,32 @@ pub(crate) fn import_code_cell_builder(
                 return_type: code_run.return_type,
                 line_number: code_run.line_number,
                 output_type: code_run.output_type,
+                refresh: import_code_cell_refresh(code_run.refresh),
+
+                // set from the formula by Sheet::update_volatile_formulas
+                volatile: false,
             },
         );
     }
     Ok(new_code_runs)
 }
 
+fn import_code_cell_refresh(refresh: current::CodeCellRefreshSchema) -> CodeCellRefresh {
+    match refresh {
+        current::CodeCellRefreshSchema::Manual => CodeCellRefresh::Manual,
+        current::CodeCellRefreshSchema::OnOpen => CodeCellRefresh::OnOpen,
+        current::CodeCellRefreshSchema::Every { minutes } => CodeCellRefresh::Every { minutes },
+    }
+}
+
+fn export_code_cell_refresh(refresh: CodeCellRefresh) -> current::CodeCellRefreshSchema {
+    match refresh {
+        CodeCellRefresh::Manual => current::CodeCellRefreshSchema::Manual,
+        CodeCellRefresh::OnOpen => current::CodeCellRefreshSchema::OnOpen,
+        CodeCellRefresh::Every { minutes } => current::CodeCellRefreshSchema::Every { minutes },
+    }
+}
+
 fn export_cell_ref_coord(coord: CellRefCoord) -> current::CellRefCoordSchema {
     current::CellRefCoordSchema {
         coord: coord.coord,
@@ -185,6 +205,7 @@ pub(crate) fn export_rows_code_runs(code_runs: IndexMap<Pos, CodeRun>) -> curren
                     return_type: code_run.return_type,
                     line_number: code_run.line_number,
                     output_type: code_run.output_type,
+                    refresh: export_code_cell_refresh(code_run.refresh),
                 },
             )
         })
@@ -236,6 +257,7 @@ mod tests {
                 return_type: Some("string".to_string()),
                 line_number: Some(1),
                 output_type: Some("text".to_string()),
+                refresh: current::CodeCellRefreshSchema::Manual,
             },
         )];
 
@@ -263,6 +285,7 @@ mod tests {
                     return_type: Some("number".to_string()),
                     line_number: Some(1),
                     output_type: Some("number".to_string()),
+                    refresh: current::CodeCellRefreshSchema::Manual,
                 },
             ),
             (
@@ -280,6 +303,7 @@ mod tests {
                     return_type: Some("string".to_string()),
                     line_number: Some(1),
                     output_type: Some("text".to_string()),
+                    refresh: current::CodeCellRefreshSchema::Manual,
                 },
             ),
         ];
@@ -315,6 +339,8 @@ mod tests {
                 return_type: Some("string".to_string()),
                 line_number: Some(1),
                 output_type: Some("text".to_string()),
+                refresh: CodeCellRefresh::Manual,
+                volatile: false,
             },
         );
 
@@ -341,6 +367,8 @@ mod tests {
                 return_type: Some("number".to_string()),
                 line_number: Some(1),
                 output_type: Some("number".to_string()),
+                refresh: CodeCellRefresh::Manual,
+                volatile: false,
             },
         );
         code_runs.insert(
@@ -358,6 +386,8 @@ mod tests {
                 return_type: Some("string".to_string()),
                 line_number: Some(1),
                 output_type: Some("text".to_string()),
+                refresh: CodeCellRefresh::Manual,
+                volatile: false,
             },
         );
 
@@ -387,6 +417,7 @@ mod tests {
                 return_type: Some("number".to_string()),
                 line_number: Some(1),
                 output_type: Some("number".to_string()),
+                refresh: current::CodeCellRefreshSchema::Every { minutes: 5 },
             },
         )];
 
@@ -396,6 +427,7 @@ mod tests {
         assert_eq!(original_code_runs.len(), exported.len());
         assert_eq!(original_code_runs[0].0.x, exported[0].0.x);
         assert_eq!(original_code_runs[0].0.y, exported[0].0.y);
+        assert_eq!(original_code_runs[0].1.refresh, exported[0].1.refresh);
         // Add more detailed comparisons here for other fields
     }
 }
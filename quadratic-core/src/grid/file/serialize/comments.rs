@@ -0,0 +1,79 @@
+use crate::grid::sheet::comments::{Comment, CommentThread, Comments};
+use crate::Pos;
+
+use super::current;
+
+fn import_comment(comment: current::CommentSchema) -> Comment {
+    Comment {
+        id: comment.id,
+        author: comment.author,
+        text: comment.text,
+        timestamp: comment.timestamp,
+    }
+}
+
+pub(crate) fn import_comments(comments: Vec<current::CommentThreadSchema>) -> Comments {
+    comments
+        .into_iter()
+        .map(|thread| CommentThread {
+            id: thread.id,
+            pos: Pos {
+                x: thread.pos.x,
+                y: thread.pos.y,
+            },
+            comment: import_comment(thread.comment),
+            replies: thread.replies.into_iter().map(import_comment).collect(),
+            resolved: thread.resolved,
+        })
+        .collect()
+}
+
+fn export_comment(comment: &Comment) -> current::CommentSchema {
+    current::CommentSchema {
+        id: comment.id,
+        author: comment.author.clone(),
+        text: comment.text.clone(),
+        timestamp: comment.timestamp,
+    }
+}
+
+pub(crate) fn export_comments(comments: Comments) -> Vec<current::CommentThreadSchema> {
+    comments
+        .iter()
+        .map(|thread| current::CommentThreadSchema {
+            id: thread.id,
+            pos: current::PosSchema::from(thread.pos),
+            comment: export_comment(&thread.comment),
+            replies: thread.replies.iter().map(export_comment).collect(),
+            resolved: thread.resolved,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::{
+        controller::GridController,
+        grid::file::{export, import},
+    };
+
+    #[test]
+    fn import_export_comments() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let thread_id = gc.add_comment_thread(
+            pos![B2].to_sheet_pos(sheet_id),
+            "a@test.com".into(),
+            "hello".into(),
+            None,
+        );
+        gc.reply_to_comment_thread(sheet_id, thread_id, "b@test.com".into(), "hi".into(), None);
+        gc.resolve_comment_thread(sheet_id, thread_id, true, None);
+
+        let grid = import(export(gc.grid().clone()).unwrap()).unwrap();
+        let sheet = grid.sheets()[0].clone();
+        assert_eq!(sheet.comments, gc.sheet(sheet_id).comments);
+        assert!(sheet.comments.thread(thread_id).unwrap().resolved);
+    }
+}
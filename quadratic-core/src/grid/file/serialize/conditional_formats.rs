@@ -0,0 +1,229 @@
+use crate::grid::sheet::conditional_formats::conditional_format::ConditionalFormat;
+use crate::grid::sheet::conditional_formats::conditional_format_rule::{
+    ColorScale, ConditionalFormatRule, DataBar, NumberComparison,
+};
+use crate::grid::sheet::conditional_formats::ConditionalFormats;
+
+use super::borders::{export_rgba, import_rgba};
+use super::current;
+use super::formats::{export_format, import_format};
+use super::selection::{export_selection, import_selection};
+
+fn import_number_comparison(comparison: current::NumberComparisonSchema) -> NumberComparison {
+    match comparison {
+        current::NumberComparisonSchema::GreaterThan(value) => NumberComparison::GreaterThan(value),
+        current::NumberComparisonSchema::GreaterThanOrEqual(value) => {
+            NumberComparison::GreaterThanOrEqual(value)
+        }
+        current::NumberComparisonSchema::LessThan(value) => NumberComparison::LessThan(value),
+        current::NumberComparisonSchema::LessThanOrEqual(value) => {
+            NumberComparison::LessThanOrEqual(value)
+        }
+        current::NumberComparisonSchema::Equal(value) => NumberComparison::Equal(value),
+        current::NumberComparisonSchema::NotEqual(value) => NumberComparison::NotEqual(value),
+        current::NumberComparisonSchema::Between(min, max) => NumberComparison::Between(min, max),
+        current::NumberComparisonSchema::NotBetween(min, max) => {
+            NumberComparison::NotBetween(min, max)
+        }
+    }
+}
+
+fn import_conditional_format_rule(
+    rule: current::ConditionalFormatRuleSchema,
+) -> ConditionalFormatRule {
+    match rule {
+        current::ConditionalFormatRuleSchema::Number(comparison) => {
+            ConditionalFormatRule::Number(import_number_comparison(comparison))
+        }
+        current::ConditionalFormatRuleSchema::TextContains {
+            text,
+            case_sensitive,
+        } => ConditionalFormatRule::TextContains {
+            text,
+            case_sensitive,
+        },
+        current::ConditionalFormatRuleSchema::TopBottom { top, rank, percent } => {
+            ConditionalFormatRule::TopBottom { top, rank, percent }
+        }
+        current::ConditionalFormatRuleSchema::AboveAverage { below } => {
+            ConditionalFormatRule::AboveAverage { below }
+        }
+        current::ConditionalFormatRuleSchema::Duplicates { unique } => {
+            ConditionalFormatRule::Duplicates { unique }
+        }
+        current::ConditionalFormatRuleSchema::Formula(formula) => {
+            ConditionalFormatRule::Formula(formula)
+        }
+        current::ConditionalFormatRuleSchema::ColorScale(color_scale) => {
+            ConditionalFormatRule::ColorScale(ColorScale {
+                min_color: import_rgba(color_scale.min_color),
+                mid_color: color_scale.mid_color.map(import_rgba),
+                max_color: import_rgba(color_scale.max_color),
+            })
+        }
+        current::ConditionalFormatRuleSchema::DataBar(data_bar) => {
+            ConditionalFormatRule::DataBar(DataBar {
+                color: import_rgba(data_bar.color),
+            })
+        }
+    }
+}
+
+pub(crate) fn import_conditional_formats(
+    conditional_formats: Vec<current::ConditionalFormatSchema>,
+) -> ConditionalFormats {
+    ConditionalFormats {
+        conditional_formats: conditional_formats
+            .into_iter()
+            .map(|cf| ConditionalFormat {
+                id: cf.id,
+                selection: import_selection(cf.selection),
+                rule: import_conditional_format_rule(cf.rule),
+                format: import_format(cf.format),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn export_number_comparison(comparison: NumberComparison) -> current::NumberComparisonSchema {
+    match comparison {
+        NumberComparison::GreaterThan(value) => current::NumberComparisonSchema::GreaterThan(value),
+        NumberComparison::GreaterThanOrEqual(value) => {
+            current::NumberComparisonSchema::GreaterThanOrEqual(value)
+        }
+        NumberComparison::LessThan(value) => current::NumberComparisonSchema::LessThan(value),
+        NumberComparison::LessThanOrEqual(value) => {
+            current::NumberComparisonSchema::LessThanOrEqual(value)
+        }
+        NumberComparison::Equal(value) => current::NumberComparisonSchema::Equal(value),
+        NumberComparison::NotEqual(value) => current::NumberComparisonSchema::NotEqual(value),
+        NumberComparison::Between(min, max) => current::NumberComparisonSchema::Between(min, max),
+        NumberComparison::NotBetween(min, max) => {
+            current::NumberComparisonSchema::NotBetween(min, max)
+        }
+    }
+}
+
+fn export_conditional_format_rule(
+    rule: ConditionalFormatRule,
+) -> current::ConditionalFormatRuleSchema {
+    match rule {
+        ConditionalFormatRule::Number(comparison) => {
+            current::ConditionalFormatRuleSchema::Number(export_number_comparison(comparison))
+        }
+        ConditionalFormatRule::TextContains {
+            text,
+            case_sensitive,
+        } => current::ConditionalFormatRuleSchema::TextContains {
+            text,
+            case_sensitive,
+        },
+        ConditionalFormatRule::TopBottom { top, rank, percent } => {
+            current::ConditionalFormatRuleSchema::TopBottom { top, rank, percent }
+        }
+        ConditionalFormatRule::AboveAverage { below } => {
+            current::ConditionalFormatRuleSchema::AboveAverage { below }
+        }
+        ConditionalFormatRule::Duplicates { unique } => {
+            current::ConditionalFormatRuleSchema::Duplicates { unique }
+        }
+        ConditionalFormatRule::Formula(formula) => {
+            current::ConditionalFormatRuleSchema::Formula(formula)
+        }
+        ConditionalFormatRule::ColorScale(color_scale) => {
+            current::ConditionalFormatRuleSchema::ColorScale(current::ColorScaleSchema {
+                min_color: export_rgba(color_scale.min_color),
+                mid_color: color_scale.mid_color.map(export_rgba),
+                max_color: export_rgba(color_scale.max_color),
+            })
+        }
+        ConditionalFormatRule::DataBar(data_bar) => {
+            current::ConditionalFormatRuleSchema::DataBar(current::DataBarSchema {
+                color: export_rgba(data_bar.color),
+            })
+        }
+    }
+}
+
+pub(crate) fn export_conditional_formats(
+    conditional_formats: ConditionalFormats,
+) -> Vec<current::ConditionalFormatSchema> {
+    conditional_formats
+        .conditional_formats
+        .into_iter()
+        .map(|cf| current::ConditionalFormatSchema {
+            id: cf.id,
+            selection: export_selection(cf.selection),
+            rule: export_conditional_format_rule(cf.rule),
+            format: export_format(cf.format),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{
+        color::Rgba,
+        controller::GridController,
+        grid::{
+            file::{export, import},
+            formats::Format,
+        },
+        A1Selection,
+    };
+
+    use super::*;
+
+    #[test]
+    fn import_export_conditional_formats() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        let rules = vec![
+            ConditionalFormatRule::Number(NumberComparison::Between(1.0, 2.5)),
+            ConditionalFormatRule::TextContains {
+                text: "a".into(),
+                case_sensitive: true,
+            },
+            ConditionalFormatRule::TopBottom {
+                top: false,
+                rank: 10,
+                percent: true,
+            },
+            ConditionalFormatRule::AboveAverage { below: true },
+            ConditionalFormatRule::Duplicates { unique: true },
+            ConditionalFormatRule::Formula("A1>B1".into()),
+            ConditionalFormatRule::ColorScale(ColorScale {
+                min_color: Rgba::new(1, 2, 3, 255),
+                mid_color: Some(Rgba::new(4, 5, 6, 255)),
+                max_color: Rgba::new(7, 8, 9, 255),
+            }),
+            ConditionalFormatRule::DataBar(DataBar {
+                color: Rgba::new(1, 2, 3, 255),
+            }),
+        ];
+        for rule in rules {
+            gc.sheet_mut(sheet_id)
+                .conditional_formats
+                .set(ConditionalFormat {
+                    id: Uuid::new_v4(),
+                    selection: A1Selection::test_a1_sheet_id("A1:B2,C", &sheet_id),
+                    rule,
+                    format: Format {
+                        bold: Some(true),
+                        fill_color: Some("red".into()),
+                        ..Default::default()
+                    },
+                });
+        }
+
+        let grid = import(export(gc.grid().clone()).unwrap()).unwrap();
+        assert_eq!(
+            grid.sheets()[0].conditional_formats,
+            gc.sheet(sheet_id).conditional_formats
+        );
+    }
+}
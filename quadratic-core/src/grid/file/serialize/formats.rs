@@ -44,6 +44,7 @@ fn import_numeric_format(numeric_format: current::NumericFormatSchema) -> Numeri
             current::NumericFormatKindSchema::Currency => NumericFormatKind::Currency,
             current::NumericFormatKindSchema::Percentage => NumericFormatKind::Percentage,
             current::NumericFormatKindSchema::Exponential => NumericFormatKind::Exponential,
+            current::NumericFormatKindSchema::Custom => NumericFormatKind::Custom,
         },
         symbol: numeric_format.symbol,
     }
@@ -56,6 +57,28 @@ fn import_render_size(render_size: current::RenderSizeSchema) -> RenderSize {
     }
 }
 
+pub(crate) fn import_format(format: current::FormatSchema) -> Format {
+    Format {
+        align: format.align.map(import_cell_align),
+        vertical_align: format.vertical_align.map(import_cell_vertical_align),
+        wrap: format.wrap.map(import_cell_wrap),
+        numeric_format: format.numeric_format.map(import_numeric_format),
+        numeric_decimals: format.numeric_decimals,
+        numeric_commas: format.numeric_commas,
+        bold: format.bold,
+        italic: format.italic,
+        text_color: format.text_color,
+        fill_color: format.fill_color,
+        render_size: format.render_size.map(import_render_size),
+        date_time: format.date_time,
+        underline: format.underline,
+        strike_through: format.strike_through,
+        font_size: format.font_size,
+        font_family: format.font_family,
+        text_rotation: format.text_rotation,
+    }
+}
+
 pub(crate) fn import_formats(formats: current::SheetFormattingSchema) -> SheetFormatting {
     SheetFormatting {
         align: import_contiguous_2d(formats.align, opt_fn(import_cell_align)),
@@ -75,6 +98,9 @@ pub(crate) fn import_formats(formats: current::SheetFormattingSchema) -> SheetFo
         date_time: import_contiguous_2d(formats.date_time, |x| x),
         underline: import_contiguous_2d(formats.underline, |x| x),
         strike_through: import_contiguous_2d(formats.strike_through, |x| x),
+        font_size: import_contiguous_2d(formats.font_size, |x| x),
+        font_family: import_contiguous_2d(formats.font_family, |x| x),
+        text_rotation: import_contiguous_2d(formats.text_rotation, |x| x),
     }
 }
 
@@ -111,6 +137,7 @@ fn export_numeric_format(numeric_format: NumericFormat) -> current::NumericForma
             NumericFormatKind::Currency => current::NumericFormatKindSchema::Currency,
             NumericFormatKind::Percentage => current::NumericFormatKindSchema::Percentage,
             NumericFormatKind::Exponential => current::NumericFormatKindSchema::Exponential,
+            NumericFormatKind::Custom => current::NumericFormatKindSchema::Custom,
         },
         symbol: numeric_format.symbol,
     }
@@ -123,6 +150,28 @@ fn export_render_size(render_size: RenderSize) -> current::RenderSizeSchema {
     }
 }
 
+pub(crate) fn export_format(format: Format) -> current::FormatSchema {
+    current::FormatSchema {
+        align: format.align.map(export_cell_align),
+        vertical_align: format.vertical_align.map(export_cell_vertical_align),
+        wrap: format.wrap.map(export_cell_wrap),
+        numeric_format: format.numeric_format.map(export_numeric_format),
+        numeric_decimals: format.numeric_decimals,
+        numeric_commas: format.numeric_commas,
+        bold: format.bold,
+        italic: format.italic,
+        text_color: format.text_color,
+        fill_color: format.fill_color,
+        render_size: format.render_size.map(export_render_size),
+        date_time: format.date_time,
+        underline: format.underline,
+        strike_through: format.strike_through,
+        font_size: format.font_size,
+        font_family: format.font_family,
+        text_rotation: format.text_rotation,
+    }
+}
+
 pub(crate) fn export_formats(formats: SheetFormatting) -> current::SheetFormattingSchema {
     current::SheetFormattingSchema {
         align: export_contiguous_2d(formats.align, opt_fn(export_cell_align)),
@@ -142,5 +191,8 @@ pub(crate) fn export_formats(formats: SheetFormatting) -> current::SheetFormatti
         date_time: export_contiguous_2d(formats.date_time, |x| x),
         underline: export_contiguous_2d(formats.underline, |x| x),
         strike_through: export_contiguous_2d(formats.strike_through, |x| x),
+        font_size: export_contiguous_2d(formats.font_size, |x| x),
+        font_family: export_contiguous_2d(formats.font_family, |x| x),
+        text_rotation: export_contiguous_2d(formats.text_rotation, |x| x),
     }
 }
@@ -0,0 +1,41 @@
+use crate::grid::sheet::merge_cells::MergeCells;
+use crate::Rect;
+
+use super::current;
+
+pub(crate) fn import_merge_cells(merge_cells: Vec<current::RectSchema>) -> MergeCells {
+    let mut imported = MergeCells::default();
+    merge_cells.iter().for_each(|rect| {
+        imported.merge(Rect::from(rect));
+    });
+    imported
+}
+
+pub(crate) fn export_merge_cells(merge_cells: MergeCells) -> Vec<current::RectSchema> {
+    merge_cells.iter().map(current::RectSchema::from).collect()
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::{
+        controller::GridController,
+        grid::file::{export, import},
+        A1Selection, Rect,
+    };
+
+    #[test]
+    fn import_export_merge_cells() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.merge_cells(&A1Selection::test_a1("B2:D3"), None);
+
+        let grid = import(export(gc.grid().clone()).unwrap()).unwrap();
+        let sheet = grid.sheets()[0].clone();
+        assert_eq!(sheet.merge_cells, gc.sheet(sheet_id).merge_cells);
+        assert_eq!(
+            sheet.merge_cells.get(pos![C3]),
+            Some(Rect::test_a1("B2:D3"))
+        );
+    }
+}
@@ -3,7 +3,7 @@
 use anyhow::Result;
 use sheets::{export_sheet, import_sheet};
 
-pub use crate::grid::file::v1_7_1 as current;
+pub use crate::grid::file::v1_8 as current;
 use crate::grid::Grid;
 
 use super::CURRENT_VERSION;
@@ -12,8 +12,13 @@ pub(crate) mod borders;
 pub(crate) mod cell_value;
 pub(crate) mod code_cell;
 pub(crate) mod column;
+pub(crate) mod comments;
+pub(crate) mod conditional_formats;
 pub(crate) mod contiguous_2d;
 pub(crate) mod formats;
+pub(crate) mod merge_cells;
+pub(crate) mod outline;
+pub(crate) mod protections;
 pub(crate) mod row_resizes;
 pub(crate) mod selection;
 pub mod sheets;
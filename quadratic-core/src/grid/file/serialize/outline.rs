@@ -0,0 +1,71 @@
+use crate::grid::sheet::outline::{OutlineAxis, OutlineGroup, SheetOutline};
+
+use super::current;
+
+fn import_outline_axis(axis: current::OutlineAxisSchema) -> OutlineAxis {
+    OutlineAxis {
+        hidden: axis.hidden.into_iter().collect(),
+        groups: axis
+            .groups
+            .into_iter()
+            .map(|group| OutlineGroup {
+                start: group.start,
+                end: group.end,
+                collapsed: group.collapsed,
+            })
+            .collect(),
+    }
+}
+
+pub(crate) fn import_outline(outline: current::OutlineSchema) -> SheetOutline {
+    SheetOutline {
+        columns: import_outline_axis(outline.columns),
+        rows: import_outline_axis(outline.rows),
+    }
+}
+
+fn export_outline_axis(axis: OutlineAxis) -> current::OutlineAxisSchema {
+    current::OutlineAxisSchema {
+        hidden: axis.hidden.into_iter().collect(),
+        groups: axis
+            .groups
+            .into_iter()
+            .map(|group| current::OutlineGroupSchema {
+                start: group.start,
+                end: group.end,
+                collapsed: group.collapsed,
+            })
+            .collect(),
+    }
+}
+
+pub(crate) fn export_outline(outline: SheetOutline) -> current::OutlineSchema {
+    current::OutlineSchema {
+        columns: export_outline_axis(outline.columns),
+        rows: export_outline_axis(outline.rows),
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::{
+        controller::GridController,
+        grid::file::{export, import},
+    };
+
+    #[test]
+    fn import_export_outline() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.hide_columns(sheet_id, vec![2], None);
+        gc.group_rows(sheet_id, 3, 5, None);
+        gc.set_row_group_collapsed(sheet_id, 4, true, None);
+
+        let grid = import(export(gc.grid().clone()).unwrap()).unwrap();
+        let sheet = grid.sheets()[0].clone();
+        assert_eq!(sheet.outline, gc.sheet(sheet_id).outline);
+        assert!(sheet.is_column_hidden(2));
+        assert!(sheet.is_row_hidden(5));
+    }
+}
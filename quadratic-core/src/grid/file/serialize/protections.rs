@@ -0,0 +1,74 @@
+use crate::grid::sheet::protections::{ProtectedRange, ProtectionRole, Protections};
+use crate::Rect;
+
+use super::current;
+
+fn import_role(role: current::ProtectionRoleSchema) -> ProtectionRole {
+    match role {
+        current::ProtectionRoleSchema::Owner => ProtectionRole::Owner,
+    }
+}
+
+pub(crate) fn import_protections(protections: Vec<current::ProtectedRangeSchema>) -> Protections {
+    protections
+        .into_iter()
+        .map(|range| ProtectedRange {
+            id: range.id,
+            rect: range.rect.as_ref().map(Rect::from),
+            users: range.users,
+            roles: range.roles.into_iter().map(import_role).collect(),
+        })
+        .collect()
+}
+
+fn export_role(role: &ProtectionRole) -> current::ProtectionRoleSchema {
+    match role {
+        ProtectionRole::Owner => current::ProtectionRoleSchema::Owner,
+    }
+}
+
+pub(crate) fn export_protections(protections: Protections) -> Vec<current::ProtectedRangeSchema> {
+    protections
+        .iter()
+        .map(|range| current::ProtectedRangeSchema {
+            id: range.id,
+            rect: range.rect.as_ref().map(current::RectSchema::from),
+            users: range.users.clone(),
+            roles: range.roles.iter().map(export_role).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use crate::{
+        controller::GridController,
+        grid::{
+            file::{export, import},
+            sheet::protections::{ProtectedRange, ProtectionRole},
+        },
+        Rect,
+    };
+
+    #[test]
+    fn import_export_protections() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_protected_range(
+            sheet_id,
+            ProtectedRange::new(
+                Some(Rect::test_a1("B2:C3")),
+                vec!["user".into()],
+                vec![ProtectionRole::Owner],
+            ),
+            None,
+        );
+        gc.set_protected_range(sheet_id, ProtectedRange::new(None, vec![], vec![]), None);
+
+        let grid = import(export(gc.grid().clone()).unwrap()).unwrap();
+        let sheet = grid.sheets()[0].clone();
+        assert_eq!(sheet.protections, gc.sheet(sheet_id).protections);
+        assert_eq!(sheet.protections.iter().count(), 2);
+    }
+}
@@ -11,8 +11,13 @@ use super::{
     borders::{export_borders, import_borders},
     code_cell::{export_rows_code_runs, import_code_cell_builder},
     column::{export_column_builder, import_column_builder},
+    comments::{export_comments, import_comments},
+    conditional_formats::{export_conditional_formats, import_conditional_formats},
     current,
     formats::{export_formats, import_formats},
+    merge_cells::{export_merge_cells, import_merge_cells},
+    outline::{export_outline, import_outline},
+    protections::{export_protections, import_protections},
     row_resizes::{export_rows_size, import_rows_resize},
     validations::{export_validations, import_validations},
 };
@@ -32,8 +37,16 @@ pub fn import_sheet(sheet: current::SheetSchema) -> Result<Sheet> {
         columns: import_column_builder(sheet.columns)?,
         format_bounds: GridBounds::Empty,
         data_bounds: GridBounds::Empty,
+        outline: import_outline(sheet.outline),
+        merge_cells: import_merge_cells(sheet.merge_cells),
+        conditional_formats: import_conditional_formats(sheet.conditional_formats),
+        comments: import_comments(sheet.comments),
+        protections: import_protections(sheet.protections),
+        code_runs_generation: Default::default(),
     };
     new_sheet.recalculate_bounds();
+    new_sheet.update_hidden_offsets();
+    new_sheet.update_volatile_formulas();
     Ok(new_sheet)
 }
 
@@ -52,5 +65,10 @@ pub(crate) fn export_sheet(sheet: Sheet) -> current::SheetSchema {
         formats: export_formats(sheet.formats),
         code_runs: export_rows_code_runs(sheet.code_runs),
         columns: export_column_builder(sheet.columns),
+        outline: export_outline(sheet.outline),
+        merge_cells: export_merge_cells(sheet.merge_cells),
+        conditional_formats: export_conditional_formats(sheet.conditional_formats),
+        comments: export_comments(sheet.comments),
+        protections: export_protections(sheet.protections),
     }
 }
@@ -1,14 +1,18 @@
 use super::v1_6;
 use super::v1_7;
 use super::v1_7_1;
-use crate::grid::Sheet;
+use super::v1_8;
+use crate::grid::sheet::protections::Protections;
+use crate::grid::{Sheet, SheetId};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// Used to serialize a Sheet for use in Operation::AddSheetSchema.
 #[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum SheetSchema {
+    V1_8(v1_8::SheetSchema),
     V1_7_1(v1_7_1::SheetSchema),
     V1_7(v1_7::schema::SheetSchema),
     V1_6(v1_6::schema::Sheet),
@@ -18,21 +22,48 @@ impl SheetSchema {
     /// Imports a Sheet from the schema.
     pub fn into_latest(self) -> Result<Sheet> {
         match self {
-            SheetSchema::V1_7_1(sheet) => super::serialize::sheets::import_sheet(sheet),
-            SheetSchema::V1_7(sheet) => {
-                super::serialize::sheets::import_sheet(v1_7::upgrade_sheet(sheet))
+            SheetSchema::V1_8(sheet) => super::serialize::sheets::import_sheet(sheet),
+            SheetSchema::V1_7_1(sheet) => {
+                super::serialize::sheets::import_sheet(v1_7_1::upgrade_sheet(sheet))
             }
+            SheetSchema::V1_7(sheet) => super::serialize::sheets::import_sheet(
+                v1_7_1::upgrade_sheet(v1_7::upgrade_sheet(sheet)),
+            ),
             SheetSchema::V1_6(sheet) => super::serialize::sheets::import_sheet(
-                v1_7::upgrade_sheet(v1_6::file::upgrade_sheet(sheet)?),
+                v1_7_1::upgrade_sheet(v1_7::upgrade_sheet(v1_6::file::upgrade_sheet(sheet)?)),
             ),
         }
     }
+
+    /// Returns the id of the sheet without importing it.
+    pub fn sheet_id(&self) -> Result<SheetId> {
+        let id = match self {
+            SheetSchema::V1_8(sheet) => &sheet.id.id,
+            SheetSchema::V1_7_1(sheet) => &sheet.id.id,
+            SheetSchema::V1_7(sheet) => &sheet.id.id,
+            SheetSchema::V1_6(sheet) => &sheet.id.id,
+        };
+        SheetId::from_str(id)
+    }
+
+    /// Returns the sheet's protected ranges without importing it. Versions
+    /// before protected ranges have none.
+    pub fn protections(&self) -> Protections {
+        match self {
+            SheetSchema::V1_8(sheet) => {
+                super::serialize::protections::import_protections(sheet.protections.clone())
+            }
+            SheetSchema::V1_7_1(_) | SheetSchema::V1_7(_) | SheetSchema::V1_6(_) => {
+                Protections::default()
+            }
+        }
+    }
 }
 
 /// Exports a Sheet to the latest schema version.
 pub fn export_sheet(sheet: Sheet) -> SheetSchema {
     let schema = super::serialize::sheets::export_sheet(sheet);
-    SheetSchema::V1_7_1(schema)
+    SheetSchema::V1_8(schema)
 }
 
 #[cfg(test)]
@@ -50,4 +81,12 @@ mod test {
         let imported = schema.into_latest().unwrap();
         assert_eq!(sheet, imported);
     }
+
+    #[test]
+    #[parallel]
+    fn test_sheet_id() {
+        let sheet = Sheet::test();
+        let schema = export_sheet(sheet.clone());
+        assert_eq!(schema.sheet_id().unwrap(), sheet.id);
+    }
 }
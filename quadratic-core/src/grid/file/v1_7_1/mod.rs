@@ -3,6 +3,7 @@ mod borders_a1_schema;
 mod cells_accessed_schema;
 mod contiguous_2d_schema;
 mod sheet_formatting_schema;
+mod upgrade;
 mod validations_schema;
 
 pub use a1_selection_schema::*;
@@ -10,6 +11,7 @@ pub use borders_a1_schema::*;
 pub use cells_accessed_schema::*;
 pub use contiguous_2d_schema::*;
 pub use sheet_formatting_schema::*;
+pub use upgrade::{upgrade, upgrade_sheet};
 pub use validations_schema::*;
 
 use crate::grid::file::v1_7::schema as v1_7;
@@ -0,0 +1,171 @@
+use anyhow::Result;
+
+use crate::grid::file::{v1_7_1 as current, v1_8};
+
+fn upgrade_code_runs(code_runs: current::CodeRunsSchema) -> v1_8::CodeRunsSchema {
+    code_runs
+        .into_iter()
+        .map(|(pos, code_run)| {
+            (
+                pos,
+                v1_8::CodeRunSchema {
+                    formatted_code_string: code_run.formatted_code_string,
+                    std_out: code_run.std_out,
+                    std_err: code_run.std_err,
+                    cells_accessed: code_run.cells_accessed,
+                    result: code_run.result,
+                    return_type: code_run.return_type,
+                    line_number: code_run.line_number,
+                    output_type: code_run.output_type,
+                    spill_error: code_run.spill_error,
+                    last_modified: code_run.last_modified,
+                    refresh: v1_8::CodeCellRefreshSchema::Manual,
+                },
+            )
+        })
+        .collect()
+}
+
+fn upgrade_numeric_format(
+    numeric_format: current::NumericFormatSchema,
+) -> v1_8::NumericFormatSchema {
+    v1_8::NumericFormatSchema {
+        kind: match numeric_format.kind {
+            current::NumericFormatKindSchema::Number => v1_8::NumericFormatKindSchema::Number,
+            current::NumericFormatKindSchema::Currency => v1_8::NumericFormatKindSchema::Currency,
+            current::NumericFormatKindSchema::Percentage => {
+                v1_8::NumericFormatKindSchema::Percentage
+            }
+            current::NumericFormatKindSchema::Exponential => {
+                v1_8::NumericFormatKindSchema::Exponential
+            }
+        },
+        symbol: numeric_format.symbol,
+    }
+}
+
+fn upgrade_numeric_formats(
+    numeric_format: current::Contiguous2DSchema<Option<current::NumericFormatSchema>>,
+) -> v1_8::Contiguous2DSchema<Option<v1_8::NumericFormatSchema>> {
+    numeric_format
+        .into_iter()
+        .map(|x| current::BlockSchema {
+            start: x.start,
+            end: x.end,
+            value: x
+                .value
+                .into_iter()
+                .map(|y| current::BlockSchema {
+                    start: y.start,
+                    end: y.end,
+                    value: y.value.map(upgrade_numeric_format),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn upgrade_formats(formats: current::SheetFormattingSchema) -> v1_8::SheetFormattingSchema {
+    v1_8::SheetFormattingSchema {
+        align: formats.align,
+        vertical_align: formats.vertical_align,
+        wrap: formats.wrap,
+        numeric_format: upgrade_numeric_formats(formats.numeric_format),
+        numeric_decimals: formats.numeric_decimals,
+        numeric_commas: formats.numeric_commas,
+        bold: formats.bold,
+        italic: formats.italic,
+        text_color: formats.text_color,
+        fill_color: formats.fill_color,
+        render_size: formats.render_size,
+        date_time: formats.date_time,
+        underline: formats.underline,
+        strike_through: formats.strike_through,
+        font_size: Default::default(),
+        font_family: Default::default(),
+        text_rotation: Default::default(),
+    }
+}
+
+pub fn upgrade_sheet(sheet: current::SheetSchema) -> v1_8::SheetSchema {
+    let current::SheetSchema {
+        id,
+        name,
+        color,
+        order,
+        offsets,
+        validations,
+        rows_resize,
+        borders,
+        formats,
+        code_runs,
+        columns,
+    } = sheet;
+
+    v1_8::SheetSchema {
+        id,
+        name,
+        color,
+        order,
+        offsets,
+        validations,
+        rows_resize,
+        borders,
+        formats: upgrade_formats(formats),
+        code_runs: upgrade_code_runs(code_runs),
+        columns,
+        outline: Default::default(),
+        merge_cells: vec![],
+        conditional_formats: vec![],
+        comments: vec![],
+        protections: vec![],
+    }
+}
+
+pub fn upgrade(grid: current::GridSchema) -> Result<v1_8::GridSchema> {
+    let new_grid = v1_8::GridSchema {
+        version: "1.8".to_string(),
+        sheets: grid.sheets.into_iter().map(upgrade_sheet).collect(),
+    };
+    Ok(new_grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+
+    use super::*;
+    use crate::controller::GridController;
+    use crate::grid::file::serialize;
+    use crate::grid::CodeCellLanguage;
+    use crate::{A1Selection, SheetPos};
+
+    #[test]
+    #[parallel]
+    fn upgrades_a_v1_7_1_grid() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value(SheetPos::new(sheet_id, 1, 1), "hello".to_string(), None);
+        gc.set_code_cell(
+            SheetPos::new(sheet_id, 2, 1),
+            CodeCellLanguage::Formula,
+            "1 + 1".to_string(),
+            None,
+        );
+        gc.set_currency(&A1Selection::test_a1("A1:B2"), "$".to_string(), None)
+            .unwrap();
+
+        // a v1.8 grid without any v1.8 properties is the same as v1.7.1
+        let schema = serialize::export(gc.grid().clone()).unwrap();
+        let json = serde_json::to_string(&schema).unwrap();
+        let v1_7_1 = serde_json::from_str::<current::GridSchema>(&json).unwrap();
+
+        let upgraded = upgrade(v1_7_1).unwrap();
+        assert_eq!(upgraded.version, "1.8");
+        assert_eq!(
+            upgraded.sheets[0].formats.numeric_format,
+            schema.sheets[0].formats.numeric_format
+        );
+        assert_eq!(&serialize::import(upgraded).unwrap(), gc.grid());
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{CellsAccessedSchema, CodeRunResultSchema};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CodeRunSchema {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted_code_string: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub std_out: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub std_err: Option<String>,
+
+    pub cells_accessed: CellsAccessedSchema,
+
+    pub result: CodeRunResultSchema,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_number: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_type: Option<String>,
+
+    pub spill_error: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<DateTime<Utc>>,
+
+    pub refresh: CodeCellRefreshSchema,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CodeCellRefreshSchema {
+    #[default]
+    Manual,
+    OnOpen,
+    Every {
+        minutes: u32,
+    },
+}
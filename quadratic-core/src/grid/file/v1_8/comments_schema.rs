@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::PosSchema;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommentSchema {
+    pub id: Uuid,
+    pub author: String,
+    pub text: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CommentThreadSchema {
+    pub id: Uuid,
+    pub pos: PosSchema,
+    pub comment: CommentSchema,
+    pub replies: Vec<CommentSchema>,
+    pub resolved: bool,
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{A1SelectionSchema, FormatSchema, RgbaSchema};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum NumberComparisonSchema {
+    GreaterThan(f64),
+    GreaterThanOrEqual(f64),
+    LessThan(f64),
+    LessThanOrEqual(f64),
+    Equal(f64),
+    NotEqual(f64),
+    Between(f64, f64),
+    NotBetween(f64, f64),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ColorScaleSchema {
+    pub min_color: RgbaSchema,
+    pub mid_color: Option<RgbaSchema>,
+    pub max_color: RgbaSchema,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DataBarSchema {
+    pub color: RgbaSchema,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ConditionalFormatRuleSchema {
+    Number(NumberComparisonSchema),
+    TextContains { text: String, case_sensitive: bool },
+    TopBottom { top: bool, rank: u32, percent: bool },
+    AboveAverage { below: bool },
+    Duplicates { unique: bool },
+    Formula(String),
+    ColorScale(ColorScaleSchema),
+    DataBar(DataBarSchema),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConditionalFormatSchema {
+    pub id: Uuid,
+    pub selection: A1SelectionSchema,
+    pub rule: ConditionalFormatRuleSchema,
+    pub format: FormatSchema,
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use super::{CellAlignSchema, CellVerticalAlignSchema, CellWrapSchema, RenderSizeSchema};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NumericFormatKindSchema {
+    #[default]
+    Number,
+    Currency,
+    Percentage,
+    Exponential,
+
+    /// symbol is the Excel-style format code
+    Custom,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericFormatSchema {
+    pub kind: NumericFormatKindSchema,
+    pub symbol: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatSchema {
+    pub align: Option<CellAlignSchema>,
+    pub vertical_align: Option<CellVerticalAlignSchema>,
+    pub wrap: Option<CellWrapSchema>,
+    pub numeric_format: Option<NumericFormatSchema>,
+    pub numeric_decimals: Option<i16>,
+    pub numeric_commas: Option<bool>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub text_color: Option<String>,
+    pub fill_color: Option<String>,
+    pub render_size: Option<RenderSizeSchema>,
+
+    #[serde(default)]
+    pub date_time: Option<String>,
+    #[serde(default)]
+    pub underline: Option<bool>,
+    #[serde(default)]
+    pub strike_through: Option<bool>,
+    #[serde(default)]
+    pub font_size: Option<i16>,
+    #[serde(default)]
+    pub font_family: Option<String>,
+    #[serde(default)]
+    pub text_rotation: Option<i16>,
+}
@@ -0,0 +1,107 @@
+mod code_run_schema;
+mod comments_schema;
+mod conditional_formats_schema;
+mod formats_schema;
+mod outline_schema;
+mod protections_schema;
+mod sheet_formatting_schema;
+
+pub use code_run_schema::*;
+pub use comments_schema::*;
+pub use conditional_formats_schema::*;
+pub use formats_schema::*;
+pub use outline_schema::*;
+pub use protections_schema::*;
+pub use sheet_formatting_schema::*;
+
+use crate::grid::file::v1_7_1;
+
+use serde::{Deserialize, Serialize};
+
+pub type IdSchema = v1_7_1::IdSchema;
+pub type PosSchema = v1_7_1::PosSchema;
+pub type RectSchema = v1_7_1::RectSchema;
+pub type SheetRectSchema = v1_7_1::SheetRectSchema;
+pub type OffsetsSchema = v1_7_1::OffsetsSchema;
+pub type RunErrorSchema = v1_7_1::RunErrorSchema;
+pub type ResizeSchema = v1_7_1::ResizeSchema;
+pub type CodeRunResultSchema = v1_7_1::CodeRunResultSchema;
+pub type OutputValueSchema = v1_7_1::OutputValueSchema;
+pub type OutputArraySchema = v1_7_1::OutputArraySchema;
+pub type OutputSizeSchema = v1_7_1::OutputSizeSchema;
+pub type OutputValueValueSchema = v1_7_1::OutputValueValueSchema;
+pub type CellValueSchema = v1_7_1::CellValueSchema;
+pub type CodeCellLanguageSchema = v1_7_1::CodeCellLanguageSchema;
+pub type ConnectionKindSchema = v1_7_1::ConnectionKindSchema;
+pub type CodeCellSchema = v1_7_1::CodeCellSchema;
+pub type CellAlignSchema = v1_7_1::CellAlignSchema;
+pub type CellVerticalAlignSchema = v1_7_1::CellVerticalAlignSchema;
+pub type CellWrapSchema = v1_7_1::CellWrapSchema;
+pub type CellBorderSchema = v1_7_1::CellBorderSchema;
+pub type ColumnRepeatSchema<T> = v1_7_1::ColumnRepeatSchema<T>;
+pub type RenderSizeSchema = v1_7_1::RenderSizeSchema;
+pub type RunErrorMsgSchema = v1_7_1::RunErrorMsgSchema;
+pub type ValidationStyleSchema = v1_7_1::ValidationStyleSchema;
+pub type ValidationMessageSchema = v1_7_1::ValidationMessageSchema;
+pub type ValidationErrorSchema = v1_7_1::ValidationErrorSchema;
+pub type ValidationDateTimeSchema = v1_7_1::ValidationDateTimeSchema;
+pub type ValidationNumberSchema = v1_7_1::ValidationNumberSchema;
+pub type ValidationTextSchema = v1_7_1::ValidationTextSchema;
+pub type ValidationLogicalSchema = v1_7_1::ValidationLogicalSchema;
+pub type ValidationListSchema = v1_7_1::ValidationListSchema;
+pub type ValidationListSourceSchema = v1_7_1::ValidationListSourceSchema;
+pub type ValidationRuleSchema = v1_7_1::ValidationRuleSchema;
+pub type ValidationSchema = v1_7_1::ValidationSchema;
+pub type ValidationsSchema = v1_7_1::ValidationsSchema;
+pub type TextMatchSchema = v1_7_1::TextMatchSchema;
+pub type TextCaseSchema = v1_7_1::TextCaseSchema;
+pub type DateTimeRangeSchema = v1_7_1::DateTimeRangeSchema;
+pub type NumberRangeSchema = v1_7_1::NumberRangeSchema;
+pub type RgbaSchema = v1_7_1::RgbaSchema;
+pub type CellBorderLineSchema = v1_7_1::CellBorderLineSchema;
+pub type BorderStyleTimestampSchema = v1_7_1::BorderStyleTimestampSchema;
+pub type BorderStyleCellSchema = v1_7_1::BorderStyleCellSchema;
+pub type BordersSideSchema = v1_7_1::BordersSideSchema;
+pub type BordersSchema = v1_7_1::BordersSchema;
+pub type A1SelectionSchema = v1_7_1::A1SelectionSchema;
+pub type CellRefRangeSchema = v1_7_1::CellRefRangeSchema;
+pub type RefRangeBoundsSchema = v1_7_1::RefRangeBoundsSchema;
+pub type CellRefRangeEndSchema = v1_7_1::CellRefRangeEndSchema;
+pub type CellRefCoordSchema = v1_7_1::CellRefCoordSchema;
+pub type CellsAccessedSchema = v1_7_1::CellsAccessedSchema;
+pub type BlockSchema<T> = v1_7_1::BlockSchema<T>;
+pub type Contiguous2DSchema<T> = v1_7_1::Contiguous2DSchema<T>;
+
+pub type RowsResizeSchema = Vec<(i64, ResizeSchema)>;
+
+pub type CodeRunsSchema = Vec<(PosSchema, CodeRunSchema)>;
+
+pub type ColumnSchema = Vec<(i64, CellValueSchema)>;
+
+pub type ColumnsSchema = Vec<(i64, ColumnSchema)>;
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct SheetSchema {
+    pub id: IdSchema,
+    pub name: String,
+    pub color: Option<String>,
+    pub order: String,
+    pub offsets: OffsetsSchema,
+    pub validations: ValidationsSchema,
+    pub rows_resize: RowsResizeSchema,
+    pub borders: BordersSchema,
+    pub formats: SheetFormattingSchema,
+    pub code_runs: CodeRunsSchema,
+    pub columns: ColumnsSchema,
+    pub outline: OutlineSchema,
+    pub merge_cells: Vec<RectSchema>,
+    pub conditional_formats: Vec<ConditionalFormatSchema>,
+    pub comments: Vec<CommentThreadSchema>,
+    pub protections: Vec<ProtectedRangeSchema>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct GridSchema {
+    pub version: String,
+    pub sheets: Vec<SheetSchema>,
+}
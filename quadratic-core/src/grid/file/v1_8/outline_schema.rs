@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OutlineGroupSchema {
+    pub start: i64,
+    pub end: i64,
+    pub collapsed: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct OutlineAxisSchema {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub hidden: Vec<i64>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub groups: Vec<OutlineGroupSchema>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct OutlineSchema {
+    #[serde(default)]
+    pub columns: OutlineAxisSchema,
+
+    #[serde(default)]
+    pub rows: OutlineAxisSchema,
+}
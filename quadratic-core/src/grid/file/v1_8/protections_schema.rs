@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::RectSchema;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ProtectionRoleSchema {
+    Owner,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProtectedRangeSchema {
+    pub id: Uuid,
+    pub rect: Option<RectSchema>,
+    pub users: Vec<String>,
+    pub roles: Vec<ProtectionRoleSchema>,
+}
@@ -26,6 +26,13 @@ pub struct Format {
     pub date_time: Option<String>,
     pub underline: Option<bool>,
     pub strike_through: Option<bool>,
+
+    /// Font size in points.
+    pub font_size: Option<i16>,
+    pub font_family: Option<String>,
+
+    /// Text rotation in degrees counterclockwise (-90 to 90).
+    pub text_rotation: Option<i16>,
 }
 
 impl Format {
@@ -44,6 +51,9 @@ impl Format {
             && self.date_time.is_none()
             && self.underline.is_none()
             && self.strike_through.is_none()
+            && self.font_size.is_none()
+            && self.font_family.is_none()
+            && self.text_rotation.is_none()
     }
 
     /// Clears all formatting.
@@ -62,6 +72,9 @@ impl Format {
         self.date_time = None;
         self.underline = None;
         self.strike_through = None;
+        self.font_size = None;
+        self.font_family = None;
+        self.text_rotation = None;
     }
 
     /// Applies a [`FormatUpdate`] and returns a [`FormatUpdate`] to undo the
@@ -88,6 +101,9 @@ impl Format {
             date_time: replace_opt(&mut self.date_time, &update.date_time),
             underline: replace_opt(&mut self.underline, &update.underline),
             strike_through: replace_opt(&mut self.strike_through, &update.strike_through),
+            font_size: replace_opt(&mut self.font_size, &update.font_size),
+            font_family: replace_opt(&mut self.font_family, &update.font_family),
+            text_rotation: replace_opt(&mut self.text_rotation, &update.text_rotation),
         }
     }
 
@@ -163,6 +179,15 @@ impl Format {
         if self.strike_through.is_some() && update.strike_through.is_some() {
             old.strike_through = Some(None);
         }
+        if self.font_size.is_some() && update.font_size.is_some() {
+            old.font_size = Some(None);
+        }
+        if self.font_family.is_some() && update.font_family.is_some() {
+            old.font_family = Some(None);
+        }
+        if self.text_rotation.is_some() && update.text_rotation.is_some() {
+            old.text_rotation = Some(None);
+        }
         if old.is_default() {
             None
         } else {
@@ -188,6 +213,9 @@ impl Format {
             date_time: Some(self.date_time.clone()),
             underline: Some(self.underline),
             strike_through: Some(self.strike_through),
+            font_size: Some(self.font_size),
+            font_family: Some(self.font_family.clone()),
+            text_rotation: Some(self.text_rotation),
         }
     }
 }
@@ -234,6 +262,15 @@ impl Display for Format {
         if let Some(strike_through) = self.strike_through {
             s.push_str(&format!("strike_through: {:?}, ", strike_through));
         }
+        if let Some(font_size) = self.font_size {
+            s.push_str(&format!("font_size: {:?}, ", font_size));
+        }
+        if let Some(font_family) = &self.font_family {
+            s.push_str(&format!("font_family: {:?}, ", font_family));
+        }
+        if let Some(text_rotation) = self.text_rotation {
+            s.push_str(&format!("text_rotation: {:?}, ", text_rotation));
+        }
         write!(f, "{}", s)
     }
 }
@@ -256,6 +293,9 @@ impl From<&Format> for FormatUpdate {
             date_time: format.date_time.clone().map(Some),
             underline: format.underline.map(Some),
             strike_through: format.strike_through.map(Some),
+            font_size: format.font_size.map(Some),
+            font_family: format.font_family.clone().map(Some),
+            text_rotation: format.text_rotation.map(Some),
         }
     }
 }
@@ -278,6 +318,9 @@ impl From<Format> for FormatUpdate {
             date_time: format.date_time.clone().map(Some),
             underline: format.underline.map(Some),
             strike_through: format.strike_through.map(Some),
+            font_size: format.font_size.map(Some),
+            font_family: format.font_family.clone().map(Some),
+            text_rotation: format.text_rotation.map(Some),
         }
     }
 }
@@ -320,6 +363,9 @@ mod test {
             date_time: Some("%H".to_string()),
             underline: Some(true),
             strike_through: Some(true),
+            font_size: Some(12),
+            font_family: Some("Arial".to_string()),
+            text_rotation: Some(45),
         };
 
         format.clear();
@@ -364,6 +410,9 @@ mod test {
             date_time: Some("%H".to_string()),
             underline: Some(true),
             strike_through: Some(true),
+            font_size: Some(12),
+            font_family: Some("Arial".to_string()),
+            text_rotation: Some(45),
         };
 
         let update = FormatUpdate {
@@ -387,6 +436,9 @@ mod test {
             date_time: Some(Some("%M".to_string())),
             underline: Some(Some(true)),
             strike_through: Some(Some(true)),
+            font_size: Some(Some(12)),
+            font_family: Some(Some("Arial".to_string())),
+            text_rotation: Some(Some(45)),
         };
 
         let clear_update = format
@@ -409,6 +461,9 @@ mod test {
                 date_time: Some(None),
                 underline: Some(None),
                 strike_through: Some(None),
+                font_size: Some(None),
+                font_family: Some(None),
+                text_rotation: Some(None),
             }
         );
     }
@@ -438,6 +493,9 @@ mod test {
             date_time: Some(Some("%H".to_string())),
             underline: Some(Some(true)),
             strike_through: Some(Some(true)),
+            font_size: Some(Some(12)),
+            font_family: Some(Some("Arial".to_string())),
+            text_rotation: Some(Some(45)),
         };
 
         let old = format.apply_update(&update);
@@ -498,6 +556,9 @@ mod test {
             date_time: Some("%H".to_string()),
             underline: Some(true),
             strike_through: Some(true),
+            font_size: Some(12),
+            font_family: Some("Arial".to_string()),
+            text_rotation: Some(45),
         };
 
         let update: FormatUpdate = (&format).into();
@@ -554,6 +615,9 @@ mod test {
             date_time: Some("%H".to_string()),
             underline: Some(true),
             strike_through: Some(true),
+            font_size: Some(12),
+            font_family: Some("Arial".to_string()),
+            text_rotation: Some(45),
         };
 
         let update: FormatUpdate = format.into();
@@ -607,6 +671,9 @@ mod test {
                 date_time: Some(None),
                 underline: Some(None),
                 strike_through: Some(None),
+                font_size: Some(None),
+                font_family: Some(None),
+                text_rotation: Some(None),
             }
         );
     }
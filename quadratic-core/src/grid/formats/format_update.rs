@@ -92,6 +92,24 @@ pub struct FormatUpdate {
         with = "::serde_with::rust::double_option"
     )]
     pub strike_through: Option<Option<bool>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "::serde_with::rust::double_option"
+    )]
+    pub font_size: Option<Option<i16>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "::serde_with::rust::double_option"
+    )]
+    pub font_family: Option<Option<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "::serde_with::rust::double_option"
+    )]
+    pub text_rotation: Option<Option<i16>>,
 }
 
 impl FormatUpdate {
@@ -113,6 +131,9 @@ impl FormatUpdate {
             date_time: Some(None),
             underline: Some(None),
             strike_through: Some(None),
+            font_size: Some(None),
+            font_family: Some(None),
+            text_rotation: Some(None),
         }
     }
 
@@ -131,6 +152,9 @@ impl FormatUpdate {
             && self.date_time.is_none()
             && self.underline.is_none()
             && self.strike_through.is_none()
+            && self.font_size.is_none()
+            && self.font_family.is_none()
+            && self.text_rotation.is_none()
     }
 
     /// Whether we need to send a client html update.
@@ -152,6 +176,9 @@ impl FormatUpdate {
             || self.date_time.is_some()
             || self.underline.is_some()
             || self.strike_through.is_some()
+            || self.font_size.is_some()
+            || self.font_family.is_some()
+            || self.text_rotation.is_some()
     }
 
     pub fn fill_changed(&self) -> bool {
@@ -165,6 +192,9 @@ impl FormatUpdate {
             || self.bold.is_some()
             || self.italic.is_some()
             || self.date_time.is_some()
+            || self.font_size.is_some()
+            || self.font_family.is_some()
+            || self.text_rotation.is_some()
     }
 
     pub fn combine(&self, other: &FormatUpdate) -> FormatUpdate {
@@ -183,6 +213,9 @@ impl FormatUpdate {
             date_time: self.date_time.clone().or(other.date_time.clone()),
             underline: self.underline.or(other.underline),
             strike_through: self.strike_through.or(other.strike_through),
+            font_size: self.font_size.or(other.font_size),
+            font_family: self.font_family.clone().or(other.font_family.clone()),
+            text_rotation: self.text_rotation.or(other.text_rotation),
         }
     }
 
@@ -231,6 +264,15 @@ impl FormatUpdate {
         if self.strike_through.is_some() {
             clear.strike_through = Some(None);
         }
+        if self.font_size.is_some() {
+            clear.font_size = Some(None);
+        }
+        if self.font_family.is_some() {
+            clear.font_family = Some(None);
+        }
+        if self.text_rotation.is_some() {
+            clear.text_rotation = Some(None);
+        }
         clear
     }
 }
@@ -253,6 +295,9 @@ impl From<&FormatUpdate> for Format {
             date_time: update.date_time.clone().unwrap_or(None),
             underline: update.underline.unwrap_or(None),
             strike_through: update.strike_through.unwrap_or(None),
+            font_size: update.font_size.unwrap_or(None),
+            font_family: update.font_family.clone().unwrap_or(None),
+            text_rotation: update.text_rotation.unwrap_or(None),
         }
     }
 }
@@ -294,6 +339,9 @@ mod tests {
                 date_time: Some(None),
                 underline: Some(None),
                 strike_through: Some(None),
+                font_size: Some(None),
+                font_family: Some(None),
+                text_rotation: Some(None),
             }
         );
     }
@@ -383,6 +431,9 @@ mod tests {
 
         let format = FormatUpdate {
             strike_through: Some(None),
+            font_size: Some(None),
+            font_family: Some(None),
+            text_rotation: Some(None),
             ..Default::default()
         };
         assert!(format.render_cells_changed());
@@ -450,6 +501,9 @@ mod tests {
             date_time: Some(Some("%H".to_string())),
             underline: Some(Some(true)),
             strike_through: Some(Some(true)),
+            font_size: Some(Some(12)),
+            font_family: Some(Some("Arial".to_string())),
+            text_rotation: Some(Some(45)),
         };
 
         let format2 = FormatUpdate {
@@ -473,6 +527,9 @@ mod tests {
             date_time: Some(Some("%M".to_string())),
             underline: Some(Some(false)),
             strike_through: Some(Some(false)),
+            font_size: Some(Some(14)),
+            font_family: Some(Some("Courier".to_string())),
+            text_rotation: Some(Some(-45)),
         };
 
         let combined = format1.combine(&format2);
@@ -531,6 +588,9 @@ mod tests {
             date_time: Some(Some("%H".to_string())),
             underline: Some(Some(true)),
             strike_through: Some(Some(true)),
+            font_size: Some(Some(12)),
+            font_family: Some(Some("Arial".to_string())),
+            text_rotation: Some(Some(45)),
         };
 
         let cleared = format.clear_update();
@@ -574,6 +634,9 @@ mod tests {
             date_time: Some(Some("%H".to_string())),
             underline: Some(Some(true)),
             strike_through: Some(Some(true)),
+            font_size: Some(Some(12)),
+            font_family: Some(Some("Arial".to_string())),
+            text_rotation: Some(Some(45)),
         };
 
         let format: Format = (&update).into();
@@ -13,6 +13,10 @@ use super::FormatUpdate;
 
 pub type SheetFormatUpdatesType<T> = Option<Contiguous2D<Option<ClearOption<T>>>>;
 
+/// Text rotation is limited to -MAX_TEXT_ROTATION to MAX_TEXT_ROTATION
+/// degrees.
+pub const MAX_TEXT_ROTATION: i16 = 90;
+
 #[derive(Deserialize, Serialize, Default, Debug, Clone, Eq, PartialEq)]
 pub struct SheetFormatUpdates {
     pub align: SheetFormatUpdatesType<CellAlign>,
@@ -29,6 +33,9 @@ pub struct SheetFormatUpdates {
     pub date_time: SheetFormatUpdatesType<String>,
     pub underline: SheetFormatUpdatesType<bool>,
     pub strike_through: SheetFormatUpdatesType<bool>,
+    pub font_size: SheetFormatUpdatesType<i16>,
+    pub font_family: SheetFormatUpdatesType<String>,
+    pub text_rotation: SheetFormatUpdatesType<i16>,
 }
 
 impl SheetFormatUpdates {
@@ -64,6 +71,9 @@ impl SheetFormatUpdates {
             date_time: Self::apply_selection(selection, update.date_time),
             underline: Self::apply_selection(selection, update.underline),
             strike_through: Self::apply_selection(selection, update.strike_through),
+            font_size: Self::apply_selection(selection, update.font_size),
+            font_family: Self::apply_selection(selection, update.font_family),
+            text_rotation: Self::apply_selection(selection, update.text_rotation),
         }
     }
 
@@ -74,6 +84,42 @@ impl SheetFormatUpdates {
         item.as_ref().is_some_and(|item| item.intersects(rect))
     }
 
+    fn item_finite_rects<T>(item: &SheetFormatUpdatesType<T>, rects: &mut Vec<Rect>)
+    where
+        T: Clone + Debug + PartialEq,
+    {
+        if let Some(item) = item {
+            rects.extend(
+                item.to_rects()
+                    .filter_map(|(x1, y1, x2, y2, _)| Some(Rect::new(x1, y1, x2?, y2?))),
+            );
+        }
+    }
+
+    /// Returns the finite rects in the format update. Column, row, and sheet
+    /// formats are not included.
+    pub fn finite_rects(&self) -> Vec<Rect> {
+        let mut rects = vec![];
+        Self::item_finite_rects(&self.align, &mut rects);
+        Self::item_finite_rects(&self.vertical_align, &mut rects);
+        Self::item_finite_rects(&self.wrap, &mut rects);
+        Self::item_finite_rects(&self.numeric_format, &mut rects);
+        Self::item_finite_rects(&self.numeric_decimals, &mut rects);
+        Self::item_finite_rects(&self.numeric_commas, &mut rects);
+        Self::item_finite_rects(&self.bold, &mut rects);
+        Self::item_finite_rects(&self.italic, &mut rects);
+        Self::item_finite_rects(&self.text_color, &mut rects);
+        Self::item_finite_rects(&self.fill_color, &mut rects);
+        Self::item_finite_rects(&self.render_size, &mut rects);
+        Self::item_finite_rects(&self.date_time, &mut rects);
+        Self::item_finite_rects(&self.underline, &mut rects);
+        Self::item_finite_rects(&self.strike_through, &mut rects);
+        Self::item_finite_rects(&self.font_size, &mut rects);
+        Self::item_finite_rects(&self.font_family, &mut rects);
+        Self::item_finite_rects(&self.text_rotation, &mut rects);
+        rects
+    }
+
     /// Returns whether the format update intersects with the given rect.
     pub fn intersects(&self, rect: Rect) -> bool {
         Self::item_intersects(&self.align, rect)
@@ -90,6 +136,9 @@ impl SheetFormatUpdates {
             || Self::item_intersects(&self.date_time, rect)
             || Self::item_intersects(&self.underline, rect)
             || Self::item_intersects(&self.strike_through, rect)
+            || Self::item_intersects(&self.font_size, rect)
+            || Self::item_intersects(&self.font_family, rect)
+            || Self::item_intersects(&self.text_rotation, rect)
     }
 
     /// Returns whether the format update is empty.
@@ -108,6 +157,24 @@ impl SheetFormatUpdates {
             && self.date_time.is_none()
             && self.underline.is_none()
             && self.strike_through.is_none()
+            && self.font_size.is_none()
+            && self.font_family.is_none()
+            && self.text_rotation.is_none()
+    }
+
+    /// Returns whether all values in the format update are in range (ie,
+    /// text_rotation is between -MAX_TEXT_ROTATION and MAX_TEXT_ROTATION).
+    pub fn is_valid(&self) -> bool {
+        self.text_rotation.as_ref().is_none_or(|text_rotation| {
+            text_rotation
+                .to_rects()
+                .all(|(_, _, _, _, value)| match value {
+                    ClearOption::Some(rotation) => {
+                        (-MAX_TEXT_ROTATION..=MAX_TEXT_ROTATION).contains(&rotation)
+                    }
+                    ClearOption::Clear => true,
+                })
+        })
     }
 
     fn set_format_cell_item<T>(
@@ -138,6 +205,9 @@ impl SheetFormatUpdates {
         Self::set_format_cell_item(pos, &mut self.date_time, update.date_time);
         Self::set_format_cell_item(pos, &mut self.underline, update.underline);
         Self::set_format_cell_item(pos, &mut self.strike_through, update.strike_through);
+        Self::set_format_cell_item(pos, &mut self.font_size, update.font_size);
+        Self::set_format_cell_item(pos, &mut self.font_family, update.font_family);
+        Self::set_format_cell_item(pos, &mut self.text_rotation, update.text_rotation);
     }
 
     fn format_update_item<T>(item: &SheetFormatUpdatesType<T>, pos: Pos) -> Option<Option<T>>
@@ -165,6 +235,9 @@ impl SheetFormatUpdates {
             date_time: Self::format_update_item(&self.date_time, pos),
             underline: Self::format_update_item(&self.underline, pos),
             strike_through: Self::format_update_item(&self.strike_through, pos),
+            font_size: Self::format_update_item(&self.font_size, pos),
+            font_family: Self::format_update_item(&self.font_family, pos),
+            text_rotation: Self::format_update_item(&self.text_rotation, pos),
         }
     }
 
@@ -201,6 +274,9 @@ impl SheetFormatUpdates {
         Self::set_format_rect_item(&mut self.date_time, rect, update.date_time);
         Self::set_format_rect_item(&mut self.underline, rect, update.underline);
         Self::set_format_rect_item(&mut self.strike_through, rect, update.strike_through);
+        Self::set_format_rect_item(&mut self.font_size, rect, update.font_size);
+        Self::set_format_rect_item(&mut self.font_family, rect, update.font_family);
+        Self::set_format_rect_item(&mut self.text_rotation, rect, update.text_rotation);
     }
 
     fn translate_rect_item<T>(item: &mut SheetFormatUpdatesType<T>, x: i64, y: i64)
@@ -226,6 +302,9 @@ impl SheetFormatUpdates {
         Self::translate_rect_item(&mut self.date_time, x, y);
         Self::translate_rect_item(&mut self.underline, x, y);
         Self::translate_rect_item(&mut self.strike_through, x, y);
+        Self::translate_rect_item(&mut self.font_size, x, y);
+        Self::translate_rect_item(&mut self.font_family, x, y);
+        Self::translate_rect_item(&mut self.text_rotation, x, y);
     }
 }
 
@@ -234,6 +313,30 @@ impl SheetFormatUpdates {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_valid() {
+        let mut s = SheetFormatUpdates::default();
+        assert!(s.is_valid());
+
+        s.text_rotation = Contiguous2D::new_from_opt_selection(
+            &A1Selection::test_a1("A1:B2"),
+            Some(ClearOption::Some(-90)),
+        );
+        assert!(s.is_valid());
+
+        s.text_rotation = Contiguous2D::new_from_opt_selection(
+            &A1Selection::test_a1("A1:B2"),
+            Some(ClearOption::Some(180)),
+        );
+        assert!(!s.is_valid());
+
+        s.text_rotation = Contiguous2D::new_from_opt_selection(
+            &A1Selection::test_a1("A1:B2"),
+            Some(ClearOption::Clear),
+        );
+        assert!(s.is_valid());
+    }
+
     #[test]
     fn test_is_default() {
         let mut s = SheetFormatUpdates::default();
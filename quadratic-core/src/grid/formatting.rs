@@ -1,12 +1,14 @@
 // todo: maybe delete this file?
 
 use std::fmt;
+use std::sync::Arc;
 
 #[cfg(test)]
 use proptest::prelude::*;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
 
+use crate::number_format::NumberFormat;
 use crate::RunLengthEncoding;
 
 use super::formats::Format;
@@ -250,9 +252,23 @@ impl CellWrap {
 pub struct NumericFormat {
     #[serde(rename = "type")]
     pub kind: NumericFormatKind,
+
+    /// Currency symbol, or the Excel-style format code for
+    /// NumericFormatKind::Custom.
     pub symbol: Option<String>,
 }
 
+impl NumericFormat {
+    /// Returns the parsed format code for NumericFormatKind::Custom (or None
+    /// if the format code is invalid).
+    pub fn number_format(&self) -> Option<Arc<NumberFormat>> {
+        if self.kind != NumericFormatKind::Custom {
+            return None;
+        }
+        NumberFormat::parse_cached(self.symbol.as_deref()?)
+    }
+}
+
 #[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, ts_rs::TS)]
 /// Measures DOM element size in pixels.
 pub struct RenderSize {
@@ -299,6 +315,9 @@ pub enum NumericFormatKind {
     Currency, // { symbol: String }, // TODO: would be nice if this were just a single char (and it could be)
     Percentage,
     Exponential,
+
+    /// Excel-style format code (see [`crate::number_format`]).
+    Custom,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, ts_rs::TS)]
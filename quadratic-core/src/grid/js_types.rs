@@ -8,8 +8,8 @@ use super::cells_accessed::JsCellsAccessed;
 use super::formats::Format;
 use super::formatting::{CellAlign, CellVerticalAlign, CellWrap};
 use super::sheet::validations::validation::ValidationStyle;
-use super::{CodeCellLanguage, NumericFormat};
-use crate::Pos;
+use super::{CodeCellLanguage, CodeCellRefresh, NumericFormat};
+use crate::{Pos, Rect};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
 pub enum JsRenderCellSpecial {
@@ -105,6 +105,28 @@ pub struct JsRenderCell {
     pub underline: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub strike_through: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_rotation: Option<i16>,
+
+    /// Merged region, set only for the top-left cell of a merged region.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merge: Option<Rect>,
+
+    /// Data bar from a conditional format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_bar: Option<JsRenderDataBar>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+pub struct JsRenderDataBar {
+    pub color: String,
+
+    /// length of the bar as a percent of the cell's width
+    pub percent: u8,
 }
 
 #[cfg(test)]
@@ -176,6 +198,10 @@ pub struct CellFormatSummary {
 
     pub underline: Option<bool>,
     pub strike_through: Option<bool>,
+
+    pub font_size: Option<i16>,
+    pub font_family: Option<String>,
+    pub text_rotation: Option<i16>,
 }
 
 #[derive(Serialize, PartialEq, Debug, TS)]
@@ -196,6 +222,7 @@ pub struct JsCodeCell {
     pub spill_error: Option<Vec<Pos>>,
     pub return_info: Option<JsReturnInfo>,
     pub cells_accessed: Option<Vec<JsCellsAccessed>>,
+    pub refresh: Option<CodeCellRefresh>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
@@ -29,6 +29,7 @@ mod code_cell;
 mod code_run;
 mod column;
 pub mod contiguous;
+pub mod diff;
 pub mod file;
 pub mod formats;
 pub mod formatting;
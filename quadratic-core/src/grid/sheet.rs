@@ -3,7 +3,12 @@ use std::str::FromStr;
 
 use bigdecimal::{BigDecimal, RoundingMode};
 use borders::Borders;
+use comments::Comments;
+use conditional_formats::ConditionalFormats;
 use indexmap::IndexMap;
+use merge_cells::MergeCells;
+use outline::SheetOutline;
+use protections::Protections;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use validations::Validations;
@@ -25,8 +30,13 @@ pub mod cell_values;
 pub mod clipboard;
 pub mod code;
 pub mod col_row;
+pub mod comments;
+pub mod conditional_formats;
 pub mod formats;
 pub mod jump_cursor;
+pub mod merge_cells;
+pub mod outline;
+pub mod protections;
 pub mod rendering;
 pub mod rendering_date_time;
 pub mod row_resize;
@@ -73,6 +83,40 @@ pub struct Sheet {
     pub(super) rows_resize: ResizeMap,
 
     pub borders: Borders,
+
+    /// Hidden columns/rows and column/row groups.
+    #[serde(default)]
+    pub outline: SheetOutline,
+
+    /// Merged regions of cells.
+    #[serde(default)]
+    pub merge_cells: MergeCells,
+
+    #[serde(default)]
+    pub conditional_formats: ConditionalFormats,
+
+    /// Comment threads anchored to cells.
+    #[serde(default)]
+    pub comments: Comments,
+
+    /// Protected ranges and who may edit them.
+    #[serde(default)]
+    pub protections: Protections,
+
+    /// Counts changes to code_runs, see [`Sheet::code_runs_changed`].
+    #[serde(skip)]
+    pub(super) code_runs_generation: CodeRunsGeneration,
+}
+
+/// Number of changes to a sheet's code runs. It's bookkeeping rather than part
+/// of the sheet, so sheets with different generations are still equal.
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct CodeRunsGeneration(u64);
+
+impl PartialEq for CodeRunsGeneration {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
 }
 impl Sheet {
     /// Constructs a new empty sheet.
@@ -91,6 +135,12 @@ impl Sheet {
             validations: Validations::default(),
             rows_resize: ResizeMap::default(),
             borders: Borders::default(),
+            outline: SheetOutline::default(),
+            merge_cells: MergeCells::default(),
+            conditional_formats: ConditionalFormats::default(),
+            comments: Comments::default(),
+            protections: Protections::default(),
+            code_runs_generation: CodeRunsGeneration::default(),
         }
     }
 
@@ -168,6 +218,7 @@ impl Sheet {
 
         // remove code_cells where the rect overlaps the anchor cell
         self.code_runs.retain(|pos, _| !rect.contains(*pos));
+        self.code_runs_changed();
 
         old_cell_values_array
     }
@@ -317,6 +368,9 @@ impl Sheet {
             cell_type,
             underline: format.underline,
             strike_through: format.strike_through,
+            font_size: format.font_size,
+            font_family: format.font_family,
+            text_rotation: format.text_rotation,
         }
     }
 
@@ -361,6 +415,7 @@ impl Sheet {
     pub fn clear(&mut self) {
         self.columns.clear();
         self.code_runs.clear();
+        self.code_runs_changed();
         self.recalculate_bounds();
     }
 
@@ -424,16 +479,26 @@ impl Sheet {
         })
     }
 
+    /// Returns true if the cell's formatting changes the height of its row
+    /// (wrap, font size, or text rotation).
+    fn has_row_height_format(&self, pos: Pos) -> bool {
+        self.formats.wrap.get(pos) == Some(CellWrap::Wrap)
+            || self.formats.font_size.get(pos).is_some()
+            || self
+                .formats
+                .text_rotation
+                .get(pos)
+                .is_some_and(|rotation| rotation != 0)
+    }
+
+    /// Returns the rows in a column that need to be auto resized because of
+    /// their formatting (see `has_row_height_format`).
     pub fn get_rows_with_wrap_in_column(&self, x: i64) -> Vec<i64> {
         let mut rows = vec![];
         if let Some((start, end)) = self.column_bounds(x, true) {
             for y in start..=end {
                 if self.cell_value(Pos { x, y }).is_some()
-                    && self
-                        .formats
-                        .wrap
-                        .get(Pos { x, y })
-                        .is_some_and(|wrap| wrap == CellWrap::Wrap)
+                    && self.has_row_height_format(Pos { x, y })
                 {
                     rows.push(y);
                 }
@@ -442,16 +507,14 @@ impl Sheet {
         rows
     }
 
+    /// Returns the rows in a rect that need to be auto resized because of
+    /// their formatting (see `has_row_height_format`).
     pub fn get_rows_with_wrap_in_rect(&self, rect: &Rect, include_blanks: bool) -> Vec<i64> {
         let mut rows = vec![];
         for y in rect.y_range() {
             for x in rect.x_range() {
                 if (include_blanks || self.cell_value((x, y).into()).is_some())
-                    && self
-                        .formats
-                        .wrap
-                        .get((x, y).into())
-                        .is_some_and(|wrap| wrap == CellWrap::Wrap)
+                    && self.has_row_height_format((x, y).into())
                 {
                     rows.push(y);
                     break;
@@ -299,7 +299,7 @@ mod tests {
     use crate::{
         grid::{
             js_types::{JsCellValuePosAIContext, JsCodeCell, JsReturnInfo},
-            CodeCellLanguage, CodeCellValue, CodeRun, CodeRunResult,
+            CodeCellLanguage, CodeCellRefresh, CodeCellValue, CodeRun, CodeRunResult,
         },
         A1Selection, Array, CellRefRange, CellValue, Pos, Rect, RunError, RunErrorMsg, SheetRect,
         Value,
@@ -413,6 +413,8 @@ mod tests {
             line_number: None,
             output_type: None,
             spill_error: false,
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_cell_value(
             Pos { x: 1, y: 1 },
@@ -437,6 +439,8 @@ mod tests {
             line_number: None,
             output_type: None,
             spill_error: false,
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_cell_value(
             Pos { x: 9, y: 31 },
@@ -461,6 +465,8 @@ mod tests {
             line_number: None,
             output_type: None,
             spill_error: true,
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_cell_value(
             Pos { x: 19, y: 15 },
@@ -493,6 +499,7 @@ mod tests {
                     output_type: None,
                 }),
                 cells_accessed: Some(Default::default()),
+                refresh: Some(CodeCellRefresh::Manual),
             },
             JsCodeCell {
                 x: 9,
@@ -510,6 +517,7 @@ mod tests {
                     output_type: None,
                 }),
                 cells_accessed: Some(Default::default()),
+                refresh: Some(CodeCellRefresh::Manual),
             },
         ];
 
@@ -1,9 +1,12 @@
 use crate::{
+    controller::host::Host,
     grid::{
-        sheet::borders::{JsBorderHorizontal, JsBorderVertical},
+        sheet::{
+            borders::{JsBorderHorizontal, JsBorderVertical},
+            merge_cells::MergeCells,
+        },
         SheetId,
     },
-    wasm_bindings::js::jsBordersSheet,
 };
 
 use super::*;
@@ -160,17 +163,19 @@ impl Borders {
         })
     }
 
-    /// Sends the borders for the sheet to the client.
-    pub fn send_sheet_borders(&self, sheet_id: SheetId) {
+    /// Sends the borders for the sheet to the client. Borders inside merged
+    /// regions are not sent.
+    pub fn send_sheet_borders(&self, host: &dyn Host, sheet_id: SheetId, merge_cells: &MergeCells) {
         match self.borders_in_sheet() {
-            Some(b) => {
+            Some(mut b) => {
+                merge_cells.clip_borders(&mut b);
                 if let Ok(borders) = serde_json::to_string(&b) {
-                    jsBordersSheet(sheet_id.to_string(), borders);
+                    host.borders_sheet(sheet_id.to_string(), borders);
                 } else {
                     dbgjs!("Unable to serialize borders in send_sheet_borders");
                 }
             }
-            None => jsBordersSheet(sheet_id.to_string(), String::new()),
+            None => host.borders_sheet(sheet_id.to_string(), String::new()),
         }
     }
 }
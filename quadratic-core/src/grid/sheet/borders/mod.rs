@@ -141,6 +141,19 @@ impl BordersUpdates {
                 .is_some_and(|bottom| bottom.intersects(rect))
     }
 
+    /// Returns the finite rects in the borders update. Column, row, and sheet
+    /// borders are not included.
+    pub fn finite_rects(&self) -> Vec<Rect> {
+        [&self.left, &self.right, &self.top, &self.bottom]
+            .into_iter()
+            .flatten()
+            .flat_map(|item| {
+                item.to_rects()
+                    .filter_map(|(x1, y1, x2, y2, _)| Some(Rect::new(x1, y1, x2?, y2?)))
+            })
+            .collect()
+    }
+
     pub fn translate_in_place(&mut self, x: i64, y: i64) {
         if let Some(left) = self.left.as_mut() {
             left.translate_in_place(x, y);
@@ -1,8 +1,9 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{anyhow, Result};
 
 use crate::{
+    arrow::cell_values_to_arrow_ipc, column_name,
     controller::execution::run_code::get_cells::JsGetCellResponse, Array, CellValue, Pos, Rect,
 };
 
@@ -58,6 +59,27 @@ impl Sheet {
         response
     }
 
+    /// Returns the displayed values of `rect` as an Arrow IPC stream with
+    /// typed values, one column per sheet column (see [`crate::arrow`]).
+    pub fn get_cells_arrow(
+        &self,
+        rect: Option<Rect>,
+        metadata: HashMap<String, String>,
+    ) -> Result<Vec<u8>> {
+        let columns = rect.map_or(vec![], |rect| {
+            rect.x_range()
+                .map(|x| {
+                    let values = rect
+                        .y_range()
+                        .map(|y| self.display_value(Pos { x, y }).unwrap_or(CellValue::Blank))
+                        .collect();
+                    (column_name(x), values)
+                })
+                .collect()
+        });
+        cell_values_to_arrow_ipc(&columns, metadata)
+    }
+
     // todo: the following two functions are probably in the wrong place
 
     /// In a given rect, collect all cell values into an array.
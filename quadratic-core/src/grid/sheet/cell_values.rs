@@ -3,7 +3,7 @@ use indexmap::IndexMap;
 use crate::{
     cell_values::CellValues,
     controller::{
-        active_transactions::pending_transaction::PendingTransaction,
+        active_transactions::pending_transaction::PendingTransaction, host::Host,
         operations::operation::Operation,
     },
     CellValue, Pos,
@@ -12,7 +12,8 @@ use crate::{
 use super::Sheet;
 
 impl Sheet {
-    /// Replace cell_values with CellValues.
+    /// Replace cell_values with CellValues. New validation warnings are sent
+    /// to `host`, if provided.
     ///
     /// Returns the old CellValues.
     pub fn merge_cell_values(
@@ -20,7 +21,7 @@ impl Sheet {
         transaction: &mut PendingTransaction,
         pos: Pos,
         cell_values: &CellValues,
-        send: bool,
+        host: Option<&dyn Host>,
     ) -> CellValues {
         let mut old = CellValues::new(cell_values.w, cell_values.h);
 
@@ -92,13 +93,13 @@ impl Sheet {
             });
 
             // send the warnings if necessary
-            if send {
+            if let Some(host) = host {
                 let validations = validation_warnings
                     .iter()
                     .map(|(pos, validation_id)| (pos.x, pos.y, *validation_id, false))
                     .collect::<Vec<_>>();
                 if let Ok(validations) = serde_json::to_string(&validations) {
-                    crate::wasm_bindings::js::jsValidationWarning(self.id.to_string(), validations);
+                    host.validation_warning(self.id.to_string(), validations);
                 }
             }
         }
@@ -118,6 +119,15 @@ impl Sheet {
                 let numeric_commas = self.formats.numeric_commas.get(pos);
                 Some(value.to_number_display(numeric_format, numeric_decimals, numeric_commas))
             }
+            CellValue::Text(ref text) => {
+                let formatted = self
+                    .formats
+                    .numeric_format
+                    .get(pos)
+                    .and_then(|numeric_format| numeric_format.number_format())
+                    .and_then(|number_format| number_format.format_text(text));
+                Some(formatted.map_or_else(|| value.to_display(), |formatted| formatted.text))
+            }
             _ => Some(value.to_display()),
         }
     }
@@ -128,6 +138,7 @@ mod test {
     use std::str::FromStr;
 
     use crate::{
+        controller::host::JsHost,
         grid::{
             sheet::validations::{validation::Validation, validation_rules::ValidationRule},
             NumericFormat,
@@ -153,7 +164,7 @@ mod test {
 
         let mut transaction = PendingTransaction::default();
         let old =
-            sheet.merge_cell_values(&mut transaction, Pos { x: -1, y: -2 }, &cell_values, false);
+            sheet.merge_cell_values(&mut transaction, Pos { x: -1, y: -2 }, &cell_values, None);
         assert_eq!(old.w, 2);
         assert_eq!(old.h, 2);
 
@@ -205,6 +216,18 @@ mod test {
 
         // format for to_rendered
         assert_eq!(sheet.rendered_value(pos).unwrap(), "$123.46".to_string());
+
+        // format code
+        let custom = Some(NumericFormat {
+            kind: crate::grid::NumericFormatKind::Custom,
+            symbol: Some("#,##0.0;(#,##0.0);-;\"[\"@\"]\"".to_string()),
+        });
+        sheet.formats.numeric_format.set(pos, custom.clone());
+        assert_eq!(sheet.rendered_value(pos).unwrap(), "123.5".to_string());
+        sheet.set_cell_value(pos, CellValue::Number(BigDecimal::from(-1234)));
+        assert_eq!(sheet.rendered_value(pos).unwrap(), "(1,234.0)".to_string());
+        sheet.set_cell_value(pos, CellValue::Text("text".to_string()));
+        assert_eq!(sheet.rendered_value(pos).unwrap(), "[text]".to_string());
     }
 
     #[test]
@@ -227,7 +250,12 @@ mod test {
             CellValue::Logical(true),
         ]]);
         let mut transaction = PendingTransaction::default();
-        sheet.merge_cell_values(&mut transaction, Pos { x: 1, y: 1 }, &cell_values, true);
+        sheet.merge_cell_values(
+            &mut transaction,
+            Pos { x: 1, y: 1 },
+            &cell_values,
+            Some(&JsHost),
+        );
 
         assert_eq!(
             sheet.cell_value(Pos { x: 3, y: 1 }),
@@ -23,16 +23,26 @@ impl Sheet {
             clipboard_origin.y = bounds.min.y;
             sheet_bounds = Some(bounds);
 
-            for y in bounds.y_range() {
-                if y != bounds.min.y {
+            // hidden columns and rows are not copied
+            let columns = bounds
+                .x_range()
+                .filter(|&x| !self.is_column_hidden(x))
+                .collect::<Vec<_>>();
+            let rows = bounds
+                .y_range()
+                .filter(|&y| !self.is_row_hidden(y))
+                .collect::<Vec<_>>();
+
+            for (row_index, &y) in rows.iter().enumerate() {
+                if row_index != 0 {
                     plain_text.push('\n');
                     html_body.push_str("</tr>");
                 }
 
                 html_body.push_str("<tr>");
 
-                for x in bounds.x_range() {
-                    if x != bounds.min.x {
+                for (column_index, &x) in columns.iter().enumerate() {
+                    if column_index != 0 {
                         plain_text.push('\t');
                         html_body.push_str("</td>");
                     }
@@ -90,6 +100,8 @@ impl Sheet {
                     let cell_wrap = summary.wrap;
                     let underline = summary.underline.unwrap_or(false);
                     let strike_through = summary.strike_through.unwrap_or(false);
+                    let font_size = summary.font_size;
+                    let font_family = summary.font_family;
 
                     let cell_border = self.borders.get_style_cell(pos);
 
@@ -97,6 +109,8 @@ impl Sheet {
                         || italic
                         || underline
                         || strike_through
+                        || font_size.is_some()
+                        || font_family.is_some()
                         || text_color.is_some()
                         || fill_color.is_some()
                         || cell_align.is_some()
@@ -119,6 +133,12 @@ impl Sheet {
                         } else if underline && strike_through {
                             style.push_str("text-decoration:underline line-through;");
                         }
+                        if let Some(font_size) = font_size {
+                            style.push_str(format!("font-size:{}pt;", font_size).as_str());
+                        }
+                        if let Some(font_family) = font_family {
+                            style.push_str(format!("font-family:{};", font_family).as_str());
+                        }
                         if let Some(text_color) = text_color {
                             if let Ok(text_color) = Rgba::from_css_str(text_color.as_str()) {
                                 style.push_str(
@@ -265,6 +285,19 @@ impl Sheet {
 
         let validations = self.validations.to_clipboard(selection, &clipboard_origin);
 
+        // merged regions that are entirely within the selection
+        let merge_cells = sheet_bounds
+            .map(|bounds| {
+                self.merge_cells
+                    .in_rect(bounds)
+                    .into_iter()
+                    .filter(|merge| {
+                        selection.contains_pos(merge.min) && selection.contains_pos(merge.max)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|merge_cells| !merge_cells.is_empty());
+
         let clipboard = Clipboard {
             cells,
             formats,
@@ -275,6 +308,7 @@ impl Sheet {
             origin: clipboard_origin,
             selection: selection.clone(),
             validations,
+            merge_cells,
         };
 
         html_body.push_str("</td></tr></tbody></table>");
@@ -361,4 +395,49 @@ mod tests {
         assert_eq!(border.left.unwrap().line, CellBorderLine::default());
         assert_eq!(border.right.unwrap().line, CellBorderLine::default());
     }
+
+    #[test]
+    #[parallel]
+    fn copy_to_clipboard_hidden() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+
+        let sheet = gc.sheet_mut(sheet_id);
+        sheet.test_set_values(1, 1, 3, 2, vec!["1", "2", "3", "4", "5", "6"]);
+        gc.hide_columns(sheet_id, vec![2], None);
+        gc.hide_rows(sheet_id, vec![1], None);
+
+        let sheet = gc.sheet(sheet_id);
+        let JsClipboard { plain_text, .. } = sheet
+            .copy_to_clipboard(&A1Selection::test_a1("A1:C2"))
+            .unwrap();
+        assert_eq!(plain_text, "4\t6");
+    }
+
+    #[test]
+    #[parallel]
+    fn clipboard_merge_cells() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.merge_cells(&A1Selection::test_a1("A1:B2"), None);
+
+        let sheet = gc.sheet(sheet_id);
+        let JsClipboard { html, .. } = sheet
+            .copy_to_clipboard(&A1Selection::test_a1("A1:C3"))
+            .unwrap();
+
+        gc.paste_from_clipboard(
+            &A1Selection::test_a1("D4"),
+            None,
+            Some(html),
+            PasteSpecial::None,
+            None,
+        );
+
+        let sheet = gc.sheet(sheet_id);
+        assert_eq!(
+            sheet.merge_cells.get(pos![E5]),
+            Some(Rect::test_a1("D4:E5"))
+        );
+    }
 }
@@ -2,7 +2,7 @@ use std::ops::Range;
 
 use super::Sheet;
 use crate::{
-    formulas::replace_internal_cell_references,
+    formulas::{parse_formula, replace_internal_cell_references},
     grid::{
         js_types::{JsCodeCell, JsReturnInfo},
         CodeCellLanguage, CodeRun, RenderSize,
@@ -15,6 +15,7 @@ impl Sheet {
     ///
     /// Returns the old value if it was set.
     pub fn set_code_run(&mut self, pos: Pos, code_run: Option<CodeRun>) -> Option<CodeRun> {
+        self.code_runs_changed();
         if let Some(code_run) = code_run {
             self.code_runs.insert_sorted(pos, code_run).1
         } else {
@@ -22,6 +23,34 @@ impl Sheet {
         }
     }
 
+    /// Records a change to code_runs. Anything that inserts, removes or moves
+    /// a code run must call this so the dependency index notices.
+    pub(crate) fn code_runs_changed(&mut self) {
+        self.code_runs_generation.0 += 1;
+    }
+
+    /// Number of changes to code_runs so far.
+    pub(crate) fn code_runs_generation(&self) -> u64 {
+        self.code_runs_generation.0
+    }
+
+    /// Marks the formula code runs that call volatile functions (eg, `NOW()`).
+    /// Volatility follows from the formula, so it's derived when a sheet is
+    /// loaded rather than saved in the file.
+    pub(crate) fn update_volatile_formulas(&mut self) {
+        let columns = &self.columns;
+        self.code_runs.iter_mut().for_each(|(pos, code_run)| {
+            code_run.volatile = match columns.get(&pos.x).and_then(|c| c.values.get(&pos.y)) {
+                Some(CellValue::Code(code_cell))
+                    if code_cell.language == CodeCellLanguage::Formula =>
+                {
+                    parse_formula(&code_cell.code, *pos).is_ok_and(|parsed| parsed.is_volatile())
+                }
+                _ => false,
+            };
+        });
+    }
+
     /// Returns a CodeCell at a Pos
     pub fn code_run(&self, pos: Pos) -> Option<&CodeRun> {
         self.code_runs.get(&pos)
@@ -183,6 +212,7 @@ impl Sheet {
                             output_type: code_run.output_type.clone(),
                         }),
                         cells_accessed: Some(code_run.cells_accessed.clone().into()),
+                        refresh: Some(code_run.refresh),
                     })
                 } else {
                     Some(JsCodeCell {
@@ -196,6 +226,7 @@ impl Sheet {
                         spill_error: None,
                         return_info: None,
                         cells_accessed: None,
+                        refresh: None,
                     })
                 }
             }
@@ -210,8 +241,8 @@ mod test {
     use crate::{
         controller::GridController,
         grid::{
-            js_types::JsRenderCellSpecial, CodeCellLanguage, CodeCellValue, CodeRunResult,
-            RenderSize,
+            js_types::JsRenderCellSpecial, CodeCellLanguage, CodeCellRefresh, CodeCellValue,
+            CodeRunResult, RenderSize,
         },
         A1Selection, Array, SheetPos, Value,
     };
@@ -265,6 +296,8 @@ mod test {
             line_number: None,
             output_type: None,
             spill_error: false,
+            refresh: Default::default(),
+            volatile: false,
         };
         let old = sheet.set_code_run(Pos { x: 0, y: 0 }, Some(code_run.clone()));
         assert_eq!(old, None);
@@ -290,6 +323,8 @@ mod test {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(Pos { x: 0, y: 0 }, Some(code_run.clone()));
         assert_eq!(
@@ -324,6 +359,8 @@ mod test {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(Pos { x: 0, y: 0 }, Some(code_run.clone()));
         assert_eq!(
@@ -338,7 +375,8 @@ mod test {
                 evaluation_result: Some("{\"size\":{\"w\":3,\"h\":1},\"values\":[{\"type\":\"text\",\"value\":\"1\"},{\"type\":\"text\",\"value\":\"2\"},{\"type\":\"text\",\"value\":\"3\"}]}".to_string()),
                 spill_error: None,
                 return_info: Some(JsReturnInfo { line_number: None, output_type: None }),
-                cells_accessed: Some(Default::default())
+                cells_accessed: Some(Default::default()),
+                refresh: Some(CodeCellRefresh::Manual),
             })
         );
         assert_eq!(
@@ -353,7 +391,8 @@ mod test {
                 evaluation_result: Some("{\"size\":{\"w\":3,\"h\":1},\"values\":[{\"type\":\"text\",\"value\":\"1\"},{\"type\":\"text\",\"value\":\"2\"},{\"type\":\"text\",\"value\":\"3\"}]}".to_string()),
                 spill_error: None,
                 return_info: Some(JsReturnInfo { line_number: None, output_type: None }),
-                cells_accessed: Some(Default::default())
+                cells_accessed: Some(Default::default()),
+                refresh: Some(CodeCellRefresh::Manual),
             })
         );
         assert_eq!(sheet.edit_code_value(Pos { x: 2, y: 2 }), None);
@@ -415,6 +454,8 @@ mod test {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(Pos { x: 0, y: 0 }, Some(code_run.clone()));
         sheet.set_code_run(Pos { x: 1, y: 1 }, Some(code_run.clone()));
@@ -446,6 +487,8 @@ mod test {
             output_type: None,
             spill_error: false,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(Pos { x: 0, y: 0 }, Some(code_run.clone()));
         sheet.set_code_run(Pos { x: 1, y: 1 }, Some(code_run.clone()));
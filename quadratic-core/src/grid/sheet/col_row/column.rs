@@ -126,6 +126,11 @@ impl Sheet {
         }
 
         self.delete_column_offset(transaction, column);
+        self.outline_delete(transaction, true, column);
+        self.merge_cells_delete(transaction, true, column);
+        self.conditional_formats_delete(transaction, true, column);
+        self.comments_delete(transaction, true, column);
+        self.protections_delete(transaction, true, column);
 
         // mark hashes of existing columns dirty
         transaction.add_dirty_hashes_from_sheet_columns(self, column, None);
@@ -140,6 +145,8 @@ impl Sheet {
 
         self.columns.remove(&column);
 
+        self.code_runs_changed();
+
         // remove the column's code runs from the sheet
         self.code_runs.retain(|pos, code_run| {
             if pos.x == column {
@@ -245,6 +252,8 @@ impl Sheet {
             }
         }
 
+        self.code_runs_changed();
+
         // update the indices of all code_runs impacted by the insertion
         let mut code_runs_to_move = Vec::new();
         for (pos, _) in self.code_runs.iter() {
@@ -296,6 +305,11 @@ impl Sheet {
                 transaction.offsets_modified(self.id, Some(*index), None, Some(*size));
             });
         }
+        self.outline_insert(transaction, true, column);
+        self.merge_cells_insert(transaction, true, column);
+        self.conditional_formats_insert(transaction, true, column);
+        self.comments_insert(transaction, true, column);
+        self.protections_insert(transaction, true, column);
 
         // create undo operations for the inserted column
         if transaction.is_user_undo_redo() {
@@ -155,6 +155,11 @@ impl Sheet {
         }
 
         self.delete_row_offset(transaction, row);
+        self.outline_delete(transaction, false, row);
+        self.merge_cells_delete(transaction, false, row);
+        self.conditional_formats_delete(transaction, false, row);
+        self.comments_delete(transaction, false, row);
+        self.protections_delete(transaction, false, row);
 
         // mark hashes of existing rows dirty
         transaction.add_dirty_hashes_from_sheet_rows(self, row, None);
@@ -170,6 +175,8 @@ impl Sheet {
         // update all cells that were impacted by the deletion
         self.delete_and_shift_values(row);
 
+        self.code_runs_changed();
+
         // remove the row's code runs from the sheet
         self.code_runs.retain(|pos, code_run| {
             if pos.y == row {
@@ -279,6 +286,8 @@ impl Sheet {
         self.borders.insert_row(row, copy_formats);
         transaction.sheet_borders.insert(self.id);
 
+        self.code_runs_changed();
+
         // update the indices of all code_runs impacted by the insertion
         let mut code_runs_to_move = Vec::new();
         for (pos, _) in self.code_runs.iter() {
@@ -322,6 +331,11 @@ impl Sheet {
                 transaction.offsets_modified(self.id, None, Some(*index), Some(*size));
             });
         }
+        self.outline_insert(transaction, false, row);
+        self.merge_cells_insert(transaction, false, row);
+        self.conditional_formats_insert(transaction, false, row);
+        self.comments_insert(transaction, false, row);
+        self.protections_insert(transaction, false, row);
 
         // create undo operations for the inserted column
         if transaction.is_user_undo_redo() {
@@ -0,0 +1,436 @@
+//! Comment threads for a Sheet.
+//!
+//! A thread is anchored to a cell and moves with it when columns and rows are
+//! inserted or deleted. Threads are removed when their cell is deleted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::grid::SheetId;
+use crate::Pos;
+
+use super::Sheet;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct Comment {
+    pub id: Uuid,
+
+    /// Email (or name) of the user who wrote the comment.
+    pub author: String,
+    pub text: String,
+
+    #[ts(type = "string")]
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Comment {
+    pub fn new(author: String, text: String) -> Self {
+        Comment {
+            id: Uuid::new_v4(),
+            author,
+            text,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct CommentThread {
+    pub id: Uuid,
+    pub pos: Pos,
+
+    /// The comment that started the thread.
+    pub comment: Comment,
+    pub replies: Vec<Comment>,
+    pub resolved: bool,
+}
+
+impl CommentThread {
+    pub fn new(pos: Pos, comment: Comment) -> Self {
+        CommentThread {
+            id: Uuid::new_v4(),
+            pos,
+            comment,
+            replies: vec![],
+            resolved: false,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Comments {
+    threads: Vec<CommentThread>,
+}
+
+impl Comments {
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CommentThread> {
+        self.threads.iter()
+    }
+
+    /// Gets a thread based on its id.
+    pub fn thread(&self, thread_id: Uuid) -> Option<&CommentThread> {
+        self.threads.iter().find(|thread| thread.id == thread_id)
+    }
+
+    /// Returns the threads anchored to a cell.
+    pub fn at(&self, pos: Pos) -> Vec<&CommentThread> {
+        self.threads
+            .iter()
+            .filter(|thread| thread.pos == pos)
+            .collect()
+    }
+
+    /// Updates or adds a thread. Returns the reverse operations.
+    pub fn set(&mut self, sheet_id: SheetId, thread: CommentThread) -> Vec<Operation> {
+        if let Some(existing) = self.threads.iter_mut().find(|t| t.id == thread.id) {
+            let reverse = vec![Operation::SetCommentThread {
+                sheet_id,
+                thread: existing.clone(),
+            }];
+            *existing = thread;
+            return reverse;
+        }
+        let reverse = vec![Operation::RemoveCommentThread {
+            sheet_id,
+            thread_id: thread.id,
+        }];
+        self.threads.push(thread);
+        reverse
+    }
+
+    /// Removes a thread. Returns the reverse operations.
+    pub fn remove(&mut self, sheet_id: SheetId, thread_id: Uuid) -> Vec<Operation> {
+        let mut reverse = vec![];
+        self.threads.retain(|thread| {
+            if thread.id == thread_id {
+                reverse.push(Operation::SetCommentThread {
+                    sheet_id,
+                    thread: thread.clone(),
+                });
+                false
+            } else {
+                true
+            }
+        });
+        reverse
+    }
+
+    /// Adds a reply to a thread, ordered by its timestamp. Returns the reverse
+    /// operations (empty if the thread does not exist).
+    pub fn add_reply(
+        &mut self,
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        reply: Comment,
+    ) -> Vec<Operation> {
+        let Some(thread) = self.threads.iter_mut().find(|t| t.id == thread_id) else {
+            return vec![];
+        };
+        let reply_id = reply.id;
+        thread.replies.retain(|r| r.id != reply_id);
+        let index = thread
+            .replies
+            .partition_point(|r| r.timestamp <= reply.timestamp);
+        thread.replies.insert(index, reply);
+        vec![Operation::RemoveCommentReply {
+            sheet_id,
+            thread_id,
+            reply_id,
+        }]
+    }
+
+    /// Removes a reply from a thread. Returns the reverse operations.
+    pub fn remove_reply(
+        &mut self,
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        reply_id: Uuid,
+    ) -> Vec<Operation> {
+        let Some(thread) = self.threads.iter_mut().find(|t| t.id == thread_id) else {
+            return vec![];
+        };
+        let Some(index) = thread.replies.iter().position(|r| r.id == reply_id) else {
+            return vec![];
+        };
+        let reply = thread.replies.remove(index);
+        vec![Operation::AddCommentReply {
+            sheet_id,
+            thread_id,
+            reply,
+        }]
+    }
+
+    /// Resolves (or reopens) a thread. Returns the reverse operations.
+    pub fn set_resolved(
+        &mut self,
+        sheet_id: SheetId,
+        thread_id: Uuid,
+        resolved: bool,
+    ) -> Vec<Operation> {
+        let Some(thread) = self.threads.iter_mut().find(|t| t.id == thread_id) else {
+            return vec![];
+        };
+        let reverse = vec![Operation::SetCommentThreadResolved {
+            sheet_id,
+            thread_id,
+            resolved: thread.resolved,
+        }];
+        thread.resolved = resolved;
+        reverse
+    }
+
+    /// Moves threads for an inserted column (or row if `columns` is false).
+    pub fn insert(&mut self, columns: bool, index: i64) -> bool {
+        let mut changed = false;
+        self.threads.iter_mut().for_each(|thread| {
+            let coord = if columns {
+                &mut thread.pos.x
+            } else {
+                &mut thread.pos.y
+            };
+            if *coord >= index {
+                *coord += 1;
+                changed = true;
+            }
+        });
+        changed
+    }
+
+    /// Moves threads for a deleted column (or row if `columns` is false).
+    /// Returns the threads in the deleted column, which are removed.
+    pub fn delete(&mut self, columns: bool, index: i64) -> Vec<CommentThread> {
+        let mut removed = vec![];
+        self.threads.retain_mut(|thread| {
+            let coord = if columns {
+                &mut thread.pos.x
+            } else {
+                &mut thread.pos.y
+            };
+            if *coord == index {
+                removed.push(thread.clone());
+                return false;
+            }
+            if *coord > index {
+                *coord -= 1;
+            }
+            true
+        });
+        removed
+    }
+
+    /// Stringifies the threads to send to the client.
+    pub fn to_string(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&self.threads)
+    }
+}
+
+impl FromIterator<CommentThread> for Comments {
+    fn from_iter<I: IntoIterator<Item = CommentThread>>(threads: I) -> Self {
+        Comments {
+            threads: threads.into_iter().collect(),
+        }
+    }
+}
+
+impl Sheet {
+    /// Moves comment threads for an inserted column (or row if `columns` is
+    /// false).
+    pub(crate) fn comments_insert(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        if self.comments.insert(columns, index) {
+            transaction.sheet_info.insert(self.id);
+        }
+    }
+
+    /// Moves comment threads for a deleted column (or row if `columns` is
+    /// false). Adds the reverse operations to restore the threads that were
+    /// removed.
+    pub(crate) fn comments_delete(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        if self.comments.is_empty() {
+            return;
+        }
+        let removed = self.comments.delete(columns, index);
+        if transaction.is_user_undo_redo() {
+            transaction
+                .reverse_operations
+                .extend(
+                    removed
+                        .into_iter()
+                        .map(|thread| Operation::SetCommentThread {
+                            sheet_id: self.id,
+                            thread,
+                        }),
+                );
+        }
+        transaction.sheet_info.insert(self.id);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    fn thread(pos: Pos) -> CommentThread {
+        CommentThread::new(pos, Comment::new("user@test.com".into(), "hello".into()))
+    }
+
+    #[test]
+    fn set_remove() {
+        let sheet_id = SheetId::TEST;
+        let mut comments = Comments::default();
+        let mut thread = thread(pos![B2]);
+
+        let reverse = comments.set(sheet_id, thread.clone());
+        assert_eq!(
+            reverse,
+            vec![Operation::RemoveCommentThread {
+                sheet_id,
+                thread_id: thread.id
+            }]
+        );
+        assert_eq!(comments.at(pos![B2]), vec![&thread]);
+
+        let original = thread.clone();
+        thread.resolved = true;
+        let reverse = comments.set(sheet_id, thread.clone());
+        assert_eq!(
+            reverse,
+            vec![Operation::SetCommentThread {
+                sheet_id,
+                thread: original
+            }]
+        );
+        assert!(comments.thread(thread.id).unwrap().resolved);
+
+        let reverse = comments.remove(sheet_id, thread.id);
+        assert_eq!(
+            reverse,
+            vec![Operation::SetCommentThread {
+                sheet_id,
+                thread: thread.clone()
+            }]
+        );
+        assert!(comments.is_empty());
+        assert!(comments.remove(sheet_id, thread.id).is_empty());
+    }
+
+    #[test]
+    fn replies_and_resolved() {
+        let sheet_id = SheetId::TEST;
+        let mut comments = Comments::default();
+        let thread = thread(pos![A1]);
+        comments.set(sheet_id, thread.clone());
+
+        let first = Comment::new("a@test.com".into(), "first".into());
+        let second = Comment::new("b@test.com".into(), "second".into());
+
+        // replies are ordered by timestamp regardless of arrival order
+        comments.add_reply(sheet_id, thread.id, second.clone());
+        let reverse = comments.add_reply(sheet_id, thread.id, first.clone());
+        assert_eq!(
+            reverse,
+            vec![Operation::RemoveCommentReply {
+                sheet_id,
+                thread_id: thread.id,
+                reply_id: first.id
+            }]
+        );
+        assert_eq!(
+            comments.thread(thread.id).unwrap().replies,
+            vec![first.clone(), second.clone()]
+        );
+
+        let reverse = comments.remove_reply(sheet_id, thread.id, first.id);
+        assert_eq!(
+            reverse,
+            vec![Operation::AddCommentReply {
+                sheet_id,
+                thread_id: thread.id,
+                reply: first
+            }]
+        );
+        assert_eq!(comments.thread(thread.id).unwrap().replies, vec![second]);
+
+        let reverse = comments.set_resolved(sheet_id, thread.id, true);
+        assert_eq!(
+            reverse,
+            vec![Operation::SetCommentThreadResolved {
+                sheet_id,
+                thread_id: thread.id,
+                resolved: false
+            }]
+        );
+        assert!(comments.thread(thread.id).unwrap().resolved);
+
+        // missing threads are ignored
+        let missing = Uuid::new_v4();
+        assert!(comments.set_resolved(sheet_id, missing, true).is_empty());
+        assert!(comments
+            .add_reply(sheet_id, missing, Comment::new("a".into(), "b".into()))
+            .is_empty());
+    }
+
+    #[test]
+    fn insert_delete() {
+        let mut comments = Comments::default();
+        let b2 = thread(pos![B2]);
+        let d4 = thread(pos![D4]);
+        comments.set(SheetId::TEST, b2.clone());
+        comments.set(SheetId::TEST, d4.clone());
+
+        assert!(comments.insert(true, 3));
+        assert_eq!(comments.thread(b2.id).unwrap().pos, pos![B2]);
+        assert_eq!(comments.thread(d4.id).unwrap().pos, pos![E4]);
+
+        assert!(!comments.insert(false, 5));
+        assert!(comments.insert(false, 2));
+        assert_eq!(comments.thread(b2.id).unwrap().pos, pos![B3]);
+        assert_eq!(comments.thread(d4.id).unwrap().pos, pos![E5]);
+
+        // deleting the anchor cell's column removes the thread
+        let removed = comments.delete(true, 2);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].pos, pos![B3]);
+        assert!(comments.thread(b2.id).is_none());
+        assert_eq!(comments.thread(d4.id).unwrap().pos, pos![D5]);
+
+        assert!(comments.delete(false, 6).is_empty());
+    }
+
+    #[test]
+    fn sheet_insert_delete() {
+        let mut sheet = Sheet::test();
+        let thread = thread(pos![B2]);
+        sheet.comments.set(sheet.id, thread.clone());
+
+        let mut transaction = PendingTransaction::default();
+        sheet.comments_delete(&mut transaction, false, 2);
+        assert!(sheet.comments.is_empty());
+        assert_eq!(
+            transaction.reverse_operations,
+            vec![Operation::SetCommentThread {
+                sheet_id: sheet.id,
+                thread
+            }]
+        );
+        assert!(transaction.sheet_info.contains(&sheet.id));
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::{
+    grid::{formats::Format, js_types::JsRenderDataBar},
+    A1Selection,
+};
+
+use super::conditional_format_rule::ConditionalFormatRule;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct ConditionalFormat {
+    pub id: Uuid,
+    pub selection: A1Selection,
+    pub rule: ConditionalFormatRule,
+
+    /// Format applied to matching cells. Only bold, italic, text_color,
+    /// fill_color, underline, and strike_through are used. This is ignored
+    /// for ColorScale and DataBar rules.
+    pub format: Format,
+}
+
+impl ConditionalFormat {
+    /// Whether the conditional format changes the fill of a cell.
+    pub fn has_fill(&self) -> bool {
+        matches!(self.rule, ConditionalFormatRule::ColorScale(_))
+            || (!matches!(self.rule, ConditionalFormatRule::DataBar(_))
+                && self.format.fill_color.is_some())
+    }
+}
+
+/// The result of applying conditional formats to a cell.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ConditionalFormatResult {
+    pub format: Format,
+    pub data_bar: Option<JsRenderDataBar>,
+}
+
+impl ConditionalFormatResult {
+    /// Applies a conditional format's format over the existing result.
+    pub fn apply(&mut self, format: &Format) {
+        if format.bold.is_some() {
+            self.format.bold = format.bold;
+        }
+        if format.italic.is_some() {
+            self.format.italic = format.italic;
+        }
+        if format.text_color.is_some() {
+            self.format.text_color.clone_from(&format.text_color);
+        }
+        if format.fill_color.is_some() {
+            self.format.fill_color.clone_from(&format.fill_color);
+        }
+        if format.underline.is_some() {
+            self.format.underline = format.underline;
+        }
+        if format.strike_through.is_some() {
+            self.format.strike_through = format.strike_through;
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use bigdecimal::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{color::Rgba, CellValue};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub enum NumberComparison {
+    GreaterThan(f64),
+    GreaterThanOrEqual(f64),
+    LessThan(f64),
+    LessThanOrEqual(f64),
+    Equal(f64),
+    NotEqual(f64),
+
+    // these are inclusive, eg, Between(1, 5) matches 1 and 5
+    Between(f64, f64),
+    NotBetween(f64, f64),
+}
+
+impl NumberComparison {
+    pub fn compare(&self, number: f64) -> bool {
+        match *self {
+            NumberComparison::GreaterThan(value) => number > value,
+            NumberComparison::GreaterThanOrEqual(value) => number >= value,
+            NumberComparison::LessThan(value) => number < value,
+            NumberComparison::LessThanOrEqual(value) => number <= value,
+            NumberComparison::Equal(value) => number == value,
+            NumberComparison::NotEqual(value) => number != value,
+            NumberComparison::Between(min, max) => number >= min && number <= max,
+            NumberComparison::NotBetween(min, max) => number < min || number > max,
+        }
+    }
+}
+
+/// Colors the cell's fill based on where its value falls between the lowest
+/// and highest values in the selection.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct ColorScale {
+    pub min_color: Rgba,
+
+    /// Color of the median value (if not set, then colors are interpolated
+    /// between min_color and max_color).
+    pub mid_color: Option<Rgba>,
+
+    pub max_color: Rgba,
+}
+
+/// Draws a bar in the cell whose length is proportional to its value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct DataBar {
+    pub color: Rgba,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub enum ConditionalFormatRule {
+    Number(NumberComparison),
+    TextContains {
+        text: String,
+        case_sensitive: bool,
+    },
+
+    /// The top (or bottom) `rank` values in the selection. If `percent`, then
+    /// `rank` is the percent of values.
+    TopBottom {
+        top: bool,
+        rank: u32,
+        percent: bool,
+    },
+    AboveAverage {
+        below: bool,
+    },
+
+    /// Values that appear more than once in the selection (or only once if
+    /// `unique`). Text is compared case-insensitively.
+    Duplicates {
+        unique: bool,
+    },
+
+    /// Formula that is evaluated for each cell. Relative references are
+    /// relative to the top-left cell of the selection.
+    Formula(String),
+
+    ColorScale(ColorScale),
+    DataBar(DataBar),
+}
+
+impl ConditionalFormatRule {
+    /// Whether the result for a cell depends on the other values in the
+    /// selection (and not just the cell's own value).
+    pub fn is_range_rule(&self) -> bool {
+        matches!(
+            self,
+            ConditionalFormatRule::TopBottom { .. }
+                | ConditionalFormatRule::AboveAverage { .. }
+                | ConditionalFormatRule::Duplicates { .. }
+                | ConditionalFormatRule::ColorScale(_)
+                | ConditionalFormatRule::DataBar(_)
+        )
+    }
+
+    /// Whether the rule matches a cell value (used for all rules except
+    /// Formula, ColorScale, and DataBar).
+    pub fn matches(&self, value: Option<&CellValue>, range: &RangeValues) -> bool {
+        let Some(value) = value.filter(|value| !value.is_blank_or_empty_string()) else {
+            return false;
+        };
+        match self {
+            ConditionalFormatRule::Number(comparison) => {
+                number(value).is_some_and(|number| comparison.compare(number))
+            }
+            ConditionalFormatRule::TextContains {
+                text,
+                case_sensitive,
+            } => {
+                let display = value.to_display();
+                if *case_sensitive {
+                    display.contains(text.as_str())
+                } else {
+                    display.to_lowercase().contains(&text.to_lowercase())
+                }
+            }
+            ConditionalFormatRule::TopBottom { top, rank, percent } => {
+                let (Some(number), Some(threshold)) =
+                    (number(value), range.rank_threshold(*top, *rank, *percent))
+                else {
+                    return false;
+                };
+                if *top {
+                    number >= threshold
+                } else {
+                    number <= threshold
+                }
+            }
+            ConditionalFormatRule::AboveAverage { below } => {
+                let (Some(number), Some(average)) = (number(value), range.average()) else {
+                    return false;
+                };
+                if *below {
+                    number < average
+                } else {
+                    number > average
+                }
+            }
+            ConditionalFormatRule::Duplicates { unique } => {
+                let count = range.count(value);
+                if *unique {
+                    count == 1
+                } else {
+                    count > 1
+                }
+            }
+            ConditionalFormatRule::Formula(_)
+            | ConditionalFormatRule::ColorScale(_)
+            | ConditionalFormatRule::DataBar(_) => false,
+        }
+    }
+}
+
+impl ColorScale {
+    /// Returns the interpolated color for a value.
+    pub fn color(&self, value: Option<&CellValue>, range: &RangeValues) -> Option<Rgba> {
+        let number = number(value?)?;
+        let (min, max) = (*range.numbers.first()?, *range.numbers.last()?);
+        match self.mid_color {
+            Some(mid_color) => {
+                let mid = range.median()?;
+                if number <= mid {
+                    Some(interpolate(
+                        self.min_color,
+                        mid_color,
+                        fraction(number, min, mid),
+                    ))
+                } else {
+                    Some(interpolate(
+                        mid_color,
+                        self.max_color,
+                        fraction(number, mid, max),
+                    ))
+                }
+            }
+            None => Some(interpolate(
+                self.min_color,
+                self.max_color,
+                fraction(number, min, max),
+            )),
+        }
+    }
+}
+
+impl DataBar {
+    /// Returns the length of the bar as a percent of the cell's width. Bars
+    /// start at 0 (or the lowest value in the selection, if it's negative).
+    pub fn percent(&self, value: Option<&CellValue>, range: &RangeValues) -> Option<u8> {
+        let number = number(value?)?;
+        let min = range.numbers.first()?.min(0.0);
+        let max = *range.numbers.last()?;
+        if max <= min {
+            return Some(100);
+        }
+        Some((fraction(number, min, max) * 100.0).round() as u8)
+    }
+}
+
+/// Values in a conditional format's selection that are needed to evaluate
+/// range rules.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RangeValues {
+    /// sorted numeric values
+    numbers: Vec<f64>,
+
+    /// number of times each (lowercase) display value appears
+    counts: HashMap<String, usize>,
+}
+
+impl RangeValues {
+    pub fn new<'a>(values: impl IntoIterator<Item = &'a CellValue>) -> Self {
+        let mut range = RangeValues::default();
+        for value in values {
+            if value.is_blank_or_empty_string() {
+                continue;
+            }
+            if let Some(number) = number(value) {
+                range.numbers.push(number);
+            }
+            *range
+                .counts
+                .entry(value.to_display().to_lowercase())
+                .or_default() += 1;
+        }
+        range.numbers.sort_by(f64::total_cmp);
+        range
+    }
+
+    fn count(&self, value: &CellValue) -> usize {
+        self.counts
+            .get(&value.to_display().to_lowercase())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn average(&self) -> Option<f64> {
+        if self.numbers.is_empty() {
+            None
+        } else {
+            Some(self.numbers.iter().sum::<f64>() / self.numbers.len() as f64)
+        }
+    }
+
+    fn median(&self) -> Option<f64> {
+        let len = self.numbers.len();
+        if len == 0 {
+            None
+        } else if len % 2 == 0 {
+            Some((self.numbers[len / 2 - 1] + self.numbers[len / 2]) / 2.0)
+        } else {
+            Some(self.numbers[len / 2])
+        }
+    }
+
+    /// Returns the lowest value in the top `rank` (or the highest value in the
+    /// bottom `rank`).
+    fn rank_threshold(&self, top: bool, rank: u32, percent: bool) -> Option<f64> {
+        let len = self.numbers.len();
+        let count = if percent {
+            ((len as f64 * rank as f64 / 100.0).floor() as usize).max(1)
+        } else {
+            rank as usize
+        }
+        .min(len);
+        if count == 0 {
+            return None;
+        }
+        if top {
+            self.numbers.get(len - count).copied()
+        } else {
+            self.numbers.get(count - 1).copied()
+        }
+    }
+}
+
+fn number(value: &CellValue) -> Option<f64> {
+    match value {
+        CellValue::Number(number) => number.to_f64(),
+        _ => None,
+    }
+}
+
+/// Returns where the value falls between min and max (clamped to 0..=1).
+fn fraction(value: f64, min: f64, max: f64) -> f64 {
+    if max <= min {
+        0.0
+    } else {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    }
+}
+
+fn interpolate(from: Rgba, to: Rgba, fraction: f64) -> Rgba {
+    let channel =
+        |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * fraction).round() as u8;
+    Rgba::new(
+        channel(from.red, to.red),
+        channel(from.green, to.green),
+        channel(from.blue, to.blue),
+        channel(from.alpha, to.alpha),
+    )
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    fn numbers(values: &[i64]) -> Vec<CellValue> {
+        values
+            .iter()
+            .map(|value| CellValue::Number((*value).into()))
+            .collect()
+    }
+
+    #[test]
+    fn number_comparison() {
+        assert!(NumberComparison::GreaterThan(1.0).compare(2.0));
+        assert!(!NumberComparison::GreaterThan(1.0).compare(1.0));
+        assert!(NumberComparison::Between(1.0, 5.0).compare(5.0));
+        assert!(NumberComparison::NotBetween(1.0, 5.0).compare(6.0));
+    }
+
+    #[test]
+    fn matches_value() {
+        let range = RangeValues::default();
+        let rule = ConditionalFormatRule::Number(NumberComparison::LessThan(10.0));
+        assert!(rule.matches(Some(&CellValue::Number(5.into())), &range));
+        assert!(!rule.matches(Some(&CellValue::Text("5".into())), &range));
+        assert!(!rule.matches(None, &range));
+
+        let rule = ConditionalFormatRule::TextContains {
+            text: "ell".into(),
+            case_sensitive: false,
+        };
+        assert!(rule.matches(Some(&CellValue::Text("HELLO".into())), &range));
+        let rule = ConditionalFormatRule::TextContains {
+            text: "ell".into(),
+            case_sensitive: true,
+        };
+        assert!(!rule.matches(Some(&CellValue::Text("HELLO".into())), &range));
+    }
+
+    #[test]
+    fn matches_range() {
+        let values = numbers(&[1, 2, 3, 4, 5, 5]);
+        let range = RangeValues::new(values.iter());
+
+        let top = ConditionalFormatRule::TopBottom {
+            top: true,
+            rank: 2,
+            percent: false,
+        };
+        assert!(top.matches(Some(&values[4]), &range));
+        assert!(!top.matches(Some(&values[3]), &range));
+
+        let bottom = ConditionalFormatRule::TopBottom {
+            top: false,
+            rank: 50,
+            percent: true,
+        };
+        assert!(bottom.matches(Some(&values[2]), &range));
+        assert!(!bottom.matches(Some(&values[3]), &range));
+
+        let above = ConditionalFormatRule::AboveAverage { below: false };
+        assert!(above.matches(Some(&values[3]), &range));
+        assert!(!above.matches(Some(&values[2]), &range));
+
+        let duplicates = ConditionalFormatRule::Duplicates { unique: false };
+        assert!(duplicates.matches(Some(&values[4]), &range));
+        assert!(!duplicates.matches(Some(&values[0]), &range));
+        let unique = ConditionalFormatRule::Duplicates { unique: true };
+        assert!(unique.matches(Some(&values[0]), &range));
+    }
+
+    #[test]
+    fn color_scale() {
+        let values = numbers(&[0, 5, 10]);
+        let range = RangeValues::new(values.iter());
+        let black = Rgba::new(0, 0, 0, 255);
+        let white = Rgba::new(255, 255, 255, 255);
+        let red = Rgba::new(255, 0, 0, 255);
+
+        let scale = ColorScale {
+            min_color: black,
+            mid_color: None,
+            max_color: white,
+        };
+        assert_eq!(scale.color(Some(&values[0]), &range), Some(black));
+        assert_eq!(
+            scale.color(Some(&values[1]), &range),
+            Some(Rgba::new(128, 128, 128, 255))
+        );
+        assert_eq!(scale.color(Some(&values[2]), &range), Some(white));
+        assert_eq!(scale.color(None, &range), None);
+
+        let scale = ColorScale {
+            min_color: black,
+            mid_color: Some(red),
+            max_color: white,
+        };
+        assert_eq!(scale.color(Some(&values[1]), &range), Some(red));
+    }
+
+    #[test]
+    fn data_bar() {
+        let values = numbers(&[-5, 0, 15]);
+        let range = RangeValues::new(values.iter());
+        let bar = DataBar {
+            color: Rgba::default(),
+        };
+        assert_eq!(bar.percent(Some(&values[0]), &range), Some(0));
+        assert_eq!(bar.percent(Some(&values[1]), &range), Some(25));
+        assert_eq!(bar.percent(Some(&values[2]), &range), Some(100));
+    }
+}
@@ -0,0 +1,481 @@
+//! Conditional formatting for a Sheet.
+//!
+//! Conditional formats are evaluated when the sheet is rendered. Formula rules
+//! need access to the Grid, so their results are calculated by the
+//! GridController and cached here.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use conditional_format::{ConditionalFormat, ConditionalFormatResult};
+use conditional_format_rule::{ConditionalFormatRule, RangeValues};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    controller::{
+        active_transactions::pending_transaction::PendingTransaction,
+        operations::operation::Operation,
+    },
+    grid::{
+        js_types::{JsRenderDataBar, JsRenderFill},
+        CellsAccessed,
+    },
+    A1Selection, Pos, Rect,
+};
+
+use super::Sheet;
+
+pub mod conditional_format;
+pub mod conditional_format_rule;
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+pub struct ConditionalFormats {
+    #[serde(default)]
+    pub conditional_formats: Vec<ConditionalFormat>,
+
+    /// Cells that match each Formula rule (calculated by the GridController).
+    #[serde(skip)]
+    pub(crate) formula_matches: HashMap<Uuid, HashSet<Pos>>,
+
+    /// Cells referenced by each Formula rule when its matches were last
+    /// calculated. A rule without an entry needs to be recalculated.
+    #[serde(skip)]
+    pub(crate) formula_cells_accessed: HashMap<Uuid, CellsAccessed>,
+
+    /// Values in the selection of each range rule (calculated by the
+    /// GridController). Without an entry, the values are read when the rule
+    /// is applied.
+    #[serde(skip)]
+    pub(crate) range_values: HashMap<Uuid, RangeValues>,
+}
+
+// the cached formula results are not part of the sheet's state
+impl PartialEq for ConditionalFormats {
+    fn eq(&self, other: &Self) -> bool {
+        self.conditional_formats == other.conditional_formats
+    }
+}
+
+impl ConditionalFormats {
+    pub fn is_empty(&self) -> bool {
+        self.conditional_formats.is_empty()
+    }
+
+    /// Marks the cached results of a conditional format as out of date.
+    pub(crate) fn invalidate(&mut self, conditional_format_id: Uuid) {
+        self.formula_cells_accessed.remove(&conditional_format_id);
+        self.range_values.remove(&conditional_format_id);
+    }
+
+    /// Updates or adds a new conditional format to the sheet. Returns the
+    /// reverse operations.
+    pub fn set(&mut self, conditional_format: ConditionalFormat) -> Vec<Operation> {
+        self.invalidate(conditional_format.id);
+        for cf in self.conditional_formats.iter_mut() {
+            if cf.id == conditional_format.id {
+                let reverse = vec![Operation::SetConditionalFormat {
+                    conditional_format: cf.clone(),
+                }];
+                *cf = conditional_format;
+                return reverse;
+            }
+        }
+        let reverse = vec![Operation::RemoveConditionalFormat {
+            sheet_id: conditional_format.selection.sheet_id,
+            conditional_format_id: conditional_format.id,
+        }];
+        self.conditional_formats.push(conditional_format);
+        reverse
+    }
+
+    /// Removes a conditional format. Returns the reverse operations.
+    pub fn remove(&mut self, conditional_format_id: Uuid) -> Vec<Operation> {
+        let mut reverse = vec![];
+        self.conditional_formats.retain(|cf| {
+            if cf.id == conditional_format_id {
+                reverse.push(Operation::SetConditionalFormat {
+                    conditional_format: cf.clone(),
+                });
+                false
+            } else {
+                true
+            }
+        });
+        self.formula_matches.remove(&conditional_format_id);
+        self.invalidate(conditional_format_id);
+        reverse
+    }
+
+    /// Gets a conditional format based on its id.
+    pub fn conditional_format(&self, conditional_format_id: Uuid) -> Option<&ConditionalFormat> {
+        self.conditional_formats
+            .iter()
+            .find(|cf| cf.id == conditional_format_id)
+    }
+
+    /// Stringifies the conditional formats to send to the client.
+    pub fn to_string(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&self.conditional_formats)
+    }
+
+    /// Reads the values in a range rule's selection.
+    pub(crate) fn calculate_range_values(sheet: &Sheet, cf: &ConditionalFormat) -> RangeValues {
+        let values = sheet
+            .selection_to_rects(&cf.selection)
+            .iter()
+            .flat_map(|rect| rect.iter())
+            .filter_map(|pos| sheet.display_value(pos))
+            .collect::<Vec<_>>();
+        RangeValues::new(values.iter())
+    }
+
+    /// Applies a conditional format to the cells in its selection (limited to
+    /// `rect`, if provided).
+    fn apply(
+        &self,
+        sheet: &Sheet,
+        cf: &ConditionalFormat,
+        rect: Option<Rect>,
+        results: &mut HashMap<Pos, ConditionalFormatResult>,
+    ) {
+        let rects = sheet.selection_to_rects(&cf.selection);
+        let range = match self.range_values.get(&cf.id) {
+            Some(range) => Cow::Borrowed(range),
+            None if cf.rule.is_range_rule() => Cow::Owned(Self::calculate_range_values(sheet, cf)),
+            None => Cow::Owned(RangeValues::default()),
+        };
+        let formula_matches = self.formula_matches.get(&cf.id);
+
+        for selection_rect in rects {
+            let selection_rect = match rect {
+                Some(rect) => match selection_rect.intersection(&rect) {
+                    Some(intersection) => intersection,
+                    None => continue,
+                },
+                None => selection_rect,
+            };
+            for pos in selection_rect.iter() {
+                match &cf.rule {
+                    ConditionalFormatRule::Formula(_) => {
+                        if formula_matches.is_some_and(|matches| matches.contains(&pos)) {
+                            results.entry(pos).or_default().apply(&cf.format);
+                        }
+                    }
+                    ConditionalFormatRule::ColorScale(color_scale) => {
+                        let value = sheet.display_value(pos);
+                        if let Some(color) = color_scale.color(value.as_ref(), &range) {
+                            results.entry(pos).or_default().format.fill_color =
+                                Some(color.as_rgb_hex());
+                        }
+                    }
+                    ConditionalFormatRule::DataBar(data_bar) => {
+                        let value = sheet.display_value(pos);
+                        if let Some(percent) = data_bar.percent(value.as_ref(), &range) {
+                            results.entry(pos).or_default().data_bar = Some(JsRenderDataBar {
+                                color: data_bar.color.as_rgb_hex(),
+                                percent,
+                            });
+                        }
+                    }
+                    rule => {
+                        let value = sheet.display_value(pos);
+                        if rule.matches(value.as_ref(), &range) {
+                            results.entry(pos).or_default().apply(&cf.format);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Evaluates the conditional formats for the cells in a rect. Later
+    /// conditional formats overwrite earlier ones.
+    pub fn evaluate(&self, sheet: &Sheet, rect: Rect) -> HashMap<Pos, ConditionalFormatResult> {
+        let mut results = HashMap::new();
+        self.conditional_formats
+            .iter()
+            .filter(|cf| {
+                sheet
+                    .selection_to_rects(&cf.selection)
+                    .iter()
+                    .any(|r| r.intersects(rect))
+            })
+            .for_each(|cf| self.apply(sheet, cf, Some(rect), &mut results));
+        results
+    }
+
+    /// Returns the fills created by conditional formats.
+    pub fn render_fills(&self, sheet: &Sheet) -> Vec<JsRenderFill> {
+        let mut results = HashMap::new();
+        self.conditional_formats
+            .iter()
+            .filter(|cf| cf.has_fill())
+            .for_each(|cf| self.apply(sheet, cf, None, &mut results));
+
+        let mut fills = results
+            .into_iter()
+            .filter_map(|(pos, result)| {
+                Some(JsRenderFill {
+                    x: pos.x,
+                    y: pos.y,
+                    w: 1,
+                    h: 1,
+                    color: result.format.fill_color?,
+                })
+            })
+            .collect::<Vec<_>>();
+        fills.sort_by_key(|fill| (fill.y, fill.x));
+        fills
+    }
+}
+
+impl Sheet {
+    /// Updates conditional format selections for an inserted column (or row if
+    /// `columns` is false).
+    pub(crate) fn conditional_formats_insert(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        let mut changed_selections = vec![];
+        let mut changed_ids = vec![];
+        self.conditional_formats
+            .conditional_formats
+            .iter_mut()
+            .for_each(|cf| {
+                let original_selection = cf.selection.clone();
+                let changed = if columns {
+                    cf.selection.inserted_column(index)
+                } else {
+                    cf.selection.inserted_row(index)
+                };
+                if changed {
+                    changed_selections.push(original_selection);
+                    changed_selections.push(cf.selection.clone());
+                    changed_ids.push(cf.id);
+                }
+            });
+        changed_ids
+            .into_iter()
+            .for_each(|id| self.conditional_formats.invalidate(id));
+        self.conditional_formats_changed(transaction, changed_selections);
+    }
+
+    /// Updates conditional format selections for a deleted column (or row if
+    /// `columns` is false). Adds the reverse operations to restore the
+    /// conditional formats that were changed.
+    pub(crate) fn conditional_formats_delete(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        let mut changed_selections = vec![];
+        let mut reverse_operations = vec![];
+        let mut changed_ids = vec![];
+        let mut removed = vec![];
+        self.conditional_formats
+            .conditional_formats
+            .retain_mut(|cf| {
+                let original_selection = cf.selection.clone();
+                let changed = if columns {
+                    cf.selection.removed_column(index)
+                } else {
+                    cf.selection.removed_row(index)
+                };
+                if !changed {
+                    return true;
+                }
+                changed_selections.push(original_selection.clone());
+                changed_selections.push(cf.selection.clone());
+                changed_ids.push(cf.id);
+                reverse_operations.push(Operation::SetConditionalFormat {
+                    conditional_format: ConditionalFormat {
+                        selection: original_selection,
+                        ..cf.clone()
+                    },
+                });
+                if cf.selection.ranges.is_empty() {
+                    removed.push(cf.id);
+                    false
+                } else {
+                    true
+                }
+            });
+        removed.iter().for_each(|id| {
+            self.conditional_formats.formula_matches.remove(id);
+        });
+        changed_ids
+            .into_iter()
+            .for_each(|id| self.conditional_formats.invalidate(id));
+        if transaction.is_user_undo_redo() {
+            transaction.reverse_operations.extend(reverse_operations);
+        }
+        self.conditional_formats_changed(transaction, changed_selections);
+    }
+
+    fn conditional_formats_changed(
+        &self,
+        transaction: &mut PendingTransaction,
+        changed_selections: Vec<A1Selection>,
+    ) {
+        if changed_selections.is_empty() {
+            return;
+        }
+        transaction.sheet_info.insert(self.id);
+        transaction.fill_cells.insert(self.id);
+        transaction.add_dirty_hashes_from_selections(self, changed_selections);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use conditional_format_rule::{ColorScale, NumberComparison};
+
+    use super::*;
+    use crate::{color::Rgba, grid::formats::Format, CellValue};
+
+    fn conditional_format(selection: &str, rule: ConditionalFormatRule) -> ConditionalFormat {
+        ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1(selection),
+            rule,
+            format: Format {
+                bold: Some(true),
+                fill_color: Some("red".to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn set_remove() {
+        let mut conditional_formats = ConditionalFormats::default();
+        let cf = conditional_format(
+            "A1:B2",
+            ConditionalFormatRule::AboveAverage { below: false },
+        );
+        let reverse = conditional_formats.set(cf.clone());
+        assert_eq!(
+            reverse,
+            vec![Operation::RemoveConditionalFormat {
+                sheet_id: cf.selection.sheet_id,
+                conditional_format_id: cf.id
+            }]
+        );
+
+        let mut replace = cf.clone();
+        replace.selection = A1Selection::test_a1("C3");
+        let reverse = conditional_formats.set(replace.clone());
+        assert_eq!(
+            reverse,
+            vec![Operation::SetConditionalFormat {
+                conditional_format: cf.clone()
+            }]
+        );
+        assert_eq!(
+            conditional_formats.conditional_format(cf.id),
+            Some(&replace)
+        );
+
+        let reverse = conditional_formats.remove(cf.id);
+        assert_eq!(
+            reverse,
+            vec![Operation::SetConditionalFormat {
+                conditional_format: replace
+            }]
+        );
+        assert!(conditional_formats.is_empty());
+    }
+
+    #[test]
+    fn evaluate() {
+        let mut sheet = Sheet::test();
+        sheet.set_cell_value(pos![A1], CellValue::Number(1.into()));
+        sheet.set_cell_value(pos![A2], CellValue::Number(10.into()));
+        sheet.set_cell_value(pos![A3], CellValue::Text("text".into()));
+        sheet.conditional_formats.set(conditional_format(
+            "A1:A3",
+            ConditionalFormatRule::Number(NumberComparison::GreaterThan(5.0)),
+        ));
+
+        let results = sheet
+            .conditional_formats
+            .evaluate(&sheet, Rect::test_a1("A1:B5"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[&pos![A2]].format.bold, Some(true));
+
+        // outside the rect
+        assert!(sheet
+            .conditional_formats
+            .evaluate(&sheet, Rect::test_a1("B1:B5"))
+            .is_empty());
+
+        let fills = sheet.conditional_formats.render_fills(&sheet);
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].x, fills[0].y), (1, 2));
+        assert_eq!(fills[0].color, "red");
+    }
+
+    #[test]
+    fn evaluate_color_scale() {
+        let mut sheet = Sheet::test();
+        sheet.set_cell_value(pos![A1], CellValue::Number(0.into()));
+        sheet.set_cell_value(pos![A2], CellValue::Number(10.into()));
+        sheet.conditional_formats.set(conditional_format(
+            "A",
+            ConditionalFormatRule::ColorScale(ColorScale {
+                min_color: Rgba::new(0, 0, 0, 255),
+                mid_color: None,
+                max_color: Rgba::new(255, 255, 255, 255),
+            }),
+        ));
+        let fills = sheet.conditional_formats.render_fills(&sheet);
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].color, "#000000");
+        assert_eq!(fills[1].color, "#ffffff");
+    }
+
+    #[test]
+    fn evaluate_formula_matches() {
+        let mut sheet = Sheet::test();
+        let cf = conditional_format("A1:A2", ConditionalFormatRule::Formula("A1>0".into()));
+        sheet.conditional_formats.set(cf.clone());
+        sheet
+            .conditional_formats
+            .formula_matches
+            .insert(cf.id, HashSet::from([pos![A2]]));
+
+        let results = sheet
+            .conditional_formats
+            .evaluate(&sheet, Rect::test_a1("A1:A2"));
+        assert_eq!(results.len(), 1);
+        assert!(results.contains_key(&pos![A2]));
+    }
+
+    #[test]
+    fn insert_delete_column() {
+        let mut sheet = Sheet::test();
+        let cf = conditional_format("B1:C3", ConditionalFormatRule::Duplicates { unique: false });
+        sheet.conditional_formats.set(cf.clone());
+
+        let mut transaction = PendingTransaction::default();
+        sheet.conditional_formats_insert(&mut transaction, true, 1);
+        assert_eq!(
+            sheet.conditional_formats.conditional_formats[0].selection,
+            A1Selection::test_a1("C1:D3")
+        );
+
+        sheet.conditional_formats_delete(&mut transaction, true, 1);
+        sheet.conditional_formats_delete(&mut transaction, true, 1);
+        assert_eq!(
+            sheet.conditional_formats.conditional_formats[0].selection,
+            A1Selection::test_a1("A1:B3")
+        );
+        assert_eq!(transaction.reverse_operations.len(), 2);
+        assert!(transaction.sheet_info.contains(&sheet.id));
+    }
+}
@@ -169,6 +169,42 @@ impl Sheet {
                 });
         }
 
+        // font size and text rotation change the height of any row with content
+        if let Some(font_size) = formats.font_size.as_ref() {
+            font_size
+                .to_rects_with_bounds(sheet_bounds, columns_bounds, rows_bounds, true)
+                .for_each(|(x1, y1, x2, y2, _)| {
+                    dirty_hashes.extend(Rect::new(x1, y1, x2, y2).to_hashes());
+                    for y in y1..=y2 {
+                        if self.row_bounds(y, true).is_some() {
+                            resize_rows.insert(y);
+                        }
+                    }
+                });
+        }
+        if let Some(font_family) = formats.font_family.as_ref() {
+            font_family
+                .to_rects_with_bounds(sheet_bounds, columns_bounds, rows_bounds, true)
+                .for_each(|(x1, y1, x2, y2, _)| {
+                    let rect = Rect::new(x1, y1, x2, y2);
+                    let rows = self.get_rows_with_wrap_in_rect(&rect, false);
+                    resize_rows.extend(rows);
+                    dirty_hashes.extend(rect.to_hashes());
+                });
+        }
+        if let Some(text_rotation) = formats.text_rotation.as_ref() {
+            text_rotation
+                .to_rects_with_bounds(sheet_bounds, columns_bounds, rows_bounds, true)
+                .for_each(|(x1, y1, x2, y2, _)| {
+                    dirty_hashes.extend(Rect::new(x1, y1, x2, y2).to_hashes());
+                    for y in y1..=y2 {
+                        if self.row_bounds(y, true).is_some() {
+                            resize_rows.insert(y);
+                        }
+                    }
+                });
+        }
+
         (dirty_hashes, resize_rows, html_cells_changed, fills_changed)
     }
 
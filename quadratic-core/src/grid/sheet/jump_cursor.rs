@@ -222,11 +222,61 @@ impl Sheet {
 
     /// Returns the Pos after a jump (ctrl/cmd + arrow key)
     pub fn jump_cursor(&self, current: Pos, direction: JumpDirection) -> Pos {
-        match direction {
-            JumpDirection::Up => self.jump_up(current),
-            JumpDirection::Down => self.jump_down(current),
-            JumpDirection::Left => self.jump_left(current),
-            JumpDirection::Right => self.jump_right(current),
+        // when in a merged region, jump from its edge in the direction of the jump
+        let start = self
+            .merge_cells
+            .get(current)
+            .map_or(current, |merge| match direction {
+                JumpDirection::Up => Pos {
+                    x: current.x,
+                    y: merge.min.y,
+                },
+                JumpDirection::Down => Pos {
+                    x: current.x,
+                    y: merge.max.y,
+                },
+                JumpDirection::Left => Pos {
+                    x: merge.min.x,
+                    y: current.y,
+                },
+                JumpDirection::Right => Pos {
+                    x: merge.max.x,
+                    y: current.y,
+                },
+            });
+        let pos = match direction {
+            JumpDirection::Up => self.jump_up(start),
+            JumpDirection::Down => self.jump_down(start),
+            JumpDirection::Left => self.jump_left(start),
+            JumpDirection::Right => self.jump_right(start),
+        };
+        let pos = self.skip_hidden(current, pos, direction);
+
+        // the cursor is always placed at the top-left of a merged region
+        self.merge_cells.get(pos).map_or(pos, |merge| merge.min)
+    }
+
+    /// Moves the position past any hidden columns/rows in the direction of
+    /// the jump. Returns `current` if there are no visible cells left.
+    fn skip_hidden(&self, current: Pos, mut pos: Pos, direction: JumpDirection) -> Pos {
+        loop {
+            // jump over a whole hidden range at a time; moving along a hidden
+            // column (or row) never reaches a visible cell
+            if let Some((start, end)) = self.offsets.hidden_column_range(pos.x) {
+                match direction {
+                    JumpDirection::Left if start > 1 => pos.x = start - 1,
+                    JumpDirection::Right if end < i64::MAX => pos.x = end + 1,
+                    _ => return current,
+                }
+            } else if let Some((start, end)) = self.offsets.hidden_row_range(pos.y) {
+                match direction {
+                    JumpDirection::Up if start > 1 => pos.y = start - 1,
+                    JumpDirection::Down if end < i64::MAX => pos.y = end + 1,
+                    _ => return current,
+                }
+            } else {
+                return pos;
+            }
         }
     }
 }
@@ -235,7 +285,7 @@ impl Sheet {
 #[serial_test::parallel]
 mod tests {
 
-    use crate::CellValue;
+    use crate::{CellValue, Rect};
 
     use super::*;
 
@@ -487,4 +537,48 @@ mod tests {
         sheet.set_cell_value(Pos { x: 3, y: 1 }, CellValue::Number(1.into()));
         assert_eq!(sheet.jump_left(Pos { x: 3, y: 1 }), Pos { x: 1, y: 1 });
     }
+
+    #[test]
+    fn test_jump_hidden() {
+        let mut sheet = Sheet::test();
+        sheet.set_cell_value(Pos { x: 1, y: 5 }, CellValue::Number(1.into()));
+        sheet.outline.rows.set_hidden(5, true);
+        sheet.outline.rows.set_hidden(1, true);
+        sheet.update_hidden_offsets();
+
+        assert_eq!(
+            sheet.jump_cursor(Pos { x: 1, y: 2 }, JumpDirection::Down),
+            Pos { x: 1, y: 6 }
+        );
+        assert_eq!(
+            sheet.jump_cursor(Pos { x: 1, y: 3 }, JumpDirection::Up),
+            Pos { x: 1, y: 3 }
+        );
+
+        // a collapsed group to the end of the sheet
+        sheet.outline.rows.add_group(10, i64::MAX);
+        sheet.outline.rows.set_collapsed(10, true);
+        sheet.update_hidden_offsets();
+        assert_eq!(
+            sheet.jump_cursor(Pos { x: 1, y: 9 }, JumpDirection::Down),
+            Pos { x: 1, y: 9 }
+        );
+    }
+
+    #[test]
+    fn test_jump_merged() {
+        let mut sheet = Sheet::test();
+        sheet.set_cell_value(Pos { x: 1, y: 1 }, CellValue::Number(1.into()));
+        sheet.set_cell_value(Pos { x: 5, y: 1 }, CellValue::Number(2.into()));
+        sheet.merge_cells.merge(Rect::test_a1("A1:C2"));
+
+        assert_eq!(
+            sheet.jump_cursor(Pos { x: 1, y: 1 }, JumpDirection::Right),
+            Pos { x: 5, y: 1 }
+        );
+        assert_eq!(
+            sheet.jump_cursor(Pos { x: 5, y: 1 }, JumpDirection::Left),
+            Pos { x: 1, y: 1 }
+        );
+    }
 }
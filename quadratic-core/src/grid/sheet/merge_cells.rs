@@ -0,0 +1,419 @@
+//! Merged cells for a Sheet.
+//!
+//! A merged region is drawn as a single cell. The top-left cell of the region
+//! (the anchor) holds the value and formatting; the remaining cells are
+//! covered by the anchor. Merged regions never overlap.
+
+use serde::{Deserialize, Serialize};
+
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::grid::sheet::borders::{JsBorderHorizontal, JsBorderVertical, JsBordersSheet};
+use crate::{Pos, Rect};
+
+use super::Sheet;
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MergeCells {
+    merges: Vec<Rect>,
+}
+
+impl MergeCells {
+    pub fn is_empty(&self) -> bool {
+        self.merges.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Rect> {
+        self.merges.iter()
+    }
+
+    /// Returns the merged region that contains the position.
+    pub fn get(&self, pos: Pos) -> Option<Rect> {
+        self.merges.iter().find(|rect| rect.contains(pos)).copied()
+    }
+
+    /// Returns whether the position is covered by a merged region but is not
+    /// its anchor (ie, the cell is not drawn).
+    pub fn is_covered(&self, pos: Pos) -> bool {
+        self.get(pos).is_some_and(|rect| rect.min != pos)
+    }
+
+    /// Returns all merged regions that intersect the rect.
+    pub fn in_rect(&self, rect: Rect) -> Vec<Rect> {
+        self.merges
+            .iter()
+            .filter(|merge| merge.intersects(rect))
+            .copied()
+            .collect()
+    }
+
+    /// Expands a rect so it fully contains every merged region it touches.
+    pub fn expand_rect(&self, mut rect: Rect) -> Rect {
+        loop {
+            let expanded = self
+                .merges
+                .iter()
+                .filter(|merge| merge.intersects(rect))
+                .fold(rect, |rect, merge| rect.union(merge));
+            if expanded == rect {
+                return rect;
+            }
+            rect = expanded;
+        }
+    }
+
+    /// Merges a rect. Any existing merged regions that intersect the rect are
+    /// removed. Returns the removed regions.
+    pub fn merge(&mut self, rect: Rect) -> Vec<Rect> {
+        let removed = self.unmerge(rect);
+        if rect.len() > 1 {
+            self.merges.push(rect);
+        }
+        removed
+    }
+
+    /// Removes all merged regions that intersect the rect. Returns the
+    /// removed regions.
+    pub fn unmerge(&mut self, rect: Rect) -> Vec<Rect> {
+        let (removed, merges) = self
+            .merges
+            .drain(..)
+            .partition(|merge| merge.intersects(rect));
+        self.merges = merges;
+        removed
+    }
+
+    /// Adjusts merged regions for an inserted column. Regions that contain
+    /// the column grow. Returns the regions (after the insertion) that grew.
+    pub fn insert_column(&mut self, column: i64) -> Vec<Rect> {
+        let mut changed = vec![];
+        self.merges.iter_mut().for_each(|merge| {
+            if merge.min.x >= column {
+                merge.translate(1, 0);
+            } else if merge.max.x >= column {
+                merge.max.x += 1;
+                changed.push(*merge);
+            }
+        });
+        changed
+    }
+
+    /// Adjusts merged regions for an inserted row. Regions that contain the
+    /// row grow. Returns the regions (after the insertion) that grew.
+    pub fn insert_row(&mut self, row: i64) -> Vec<Rect> {
+        let mut changed = vec![];
+        self.merges.iter_mut().for_each(|merge| {
+            if merge.min.y >= row {
+                merge.translate(0, 1);
+            } else if merge.max.y >= row {
+                merge.max.y += 1;
+                changed.push(*merge);
+            }
+        });
+        changed
+    }
+
+    /// Adjusts merged regions for a deleted column. Returns the regions (as
+    /// they were before the deletion) that were changed.
+    pub fn delete_column(&mut self, column: i64) -> Vec<Rect> {
+        let mut changed = vec![];
+        self.merges.retain_mut(|merge| {
+            if merge.min.x > column {
+                merge.translate(-1, 0);
+            } else if merge.max.x >= column {
+                changed.push(*merge);
+                merge.max.x -= 1;
+            }
+            merge.max.x >= merge.min.x && merge.len() > 1
+        });
+        changed
+    }
+
+    /// Adjusts merged regions for a deleted row. Returns the regions (as they
+    /// were before the deletion) that were changed.
+    pub fn delete_row(&mut self, row: i64) -> Vec<Rect> {
+        let mut changed = vec![];
+        self.merges.retain_mut(|merge| {
+            if merge.min.y > row {
+                merge.translate(0, -1);
+            } else if merge.max.y >= row {
+                changed.push(*merge);
+                merge.max.y -= 1;
+            }
+            merge.max.y >= merge.min.y && merge.len() > 1
+        });
+        changed
+    }
+
+    /// Removes the parts of border lines that are inside merged regions.
+    pub fn clip_borders(&self, borders: &mut JsBordersSheet) {
+        if self.merges.is_empty() {
+            return;
+        }
+        if let Some(horizontal) = borders.horizontal.as_mut() {
+            *horizontal = std::mem::take(horizontal)
+                .into_iter()
+                .flat_map(|line| {
+                    if line.unbounded {
+                        return vec![line];
+                    }
+                    let interior = self
+                        .merges
+                        .iter()
+                        .filter(|merge| line.y > merge.min.y && line.y <= merge.max.y)
+                        .map(|merge| (merge.min.x, merge.max.x))
+                        .collect::<Vec<_>>();
+                    clip_segment(line.x, line.width, &interior)
+                        .into_iter()
+                        .map(|(x, width)| JsBorderHorizontal { x, width, ..line })
+                        .collect()
+                })
+                .collect();
+        }
+        if let Some(vertical) = borders.vertical.as_mut() {
+            *vertical = std::mem::take(vertical)
+                .into_iter()
+                .flat_map(|line| {
+                    if line.unbounded {
+                        return vec![line];
+                    }
+                    let interior = self
+                        .merges
+                        .iter()
+                        .filter(|merge| line.x > merge.min.x && line.x <= merge.max.x)
+                        .map(|merge| (merge.min.y, merge.max.y))
+                        .collect::<Vec<_>>();
+                    clip_segment(line.y, line.height, &interior)
+                        .into_iter()
+                        .map(|(y, height)| JsBorderVertical { y, height, ..line })
+                        .collect()
+                })
+                .collect();
+        }
+    }
+}
+
+/// Removes the (inclusive) ranges in `remove` from a segment that starts at
+/// `start` with an optional length (None is unbounded). Returns the remaining
+/// segments as (start, length).
+fn clip_segment(start: i64, len: Option<i64>, remove: &[(i64, i64)]) -> Vec<(i64, Option<i64>)> {
+    let mut segments = vec![(start, len.map(|len| start + len - 1))];
+    for &(remove_start, remove_end) in remove {
+        segments = segments
+            .into_iter()
+            .flat_map(|(start, end)| {
+                let mut split = vec![];
+                if end.is_some_and(|end| end < remove_start) || start > remove_end {
+                    split.push((start, end));
+                    return split;
+                }
+                if start < remove_start {
+                    split.push((start, Some(remove_start - 1)));
+                }
+                if end.is_none_or(|end| end > remove_end) {
+                    split.push((remove_end + 1, end));
+                }
+                split
+            })
+            .collect();
+    }
+    segments
+        .into_iter()
+        .map(|(start, end)| (start, end.map(|end| end - start + 1)))
+        .collect()
+}
+
+impl Sheet {
+    /// Shifts merged regions for an inserted column (or row if `columns` is
+    /// false).
+    pub(crate) fn merge_cells_insert(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        if self.merge_cells.is_empty() {
+            return;
+        }
+        let changed = if columns {
+            self.merge_cells.insert_column(index)
+        } else {
+            self.merge_cells.insert_row(index)
+        };
+        self.merge_cells_changed(transaction, &changed);
+    }
+
+    /// Shifts merged regions for a deleted column (or row if `columns` is
+    /// false). Adds the reverse operations to restore the regions that were
+    /// changed.
+    pub(crate) fn merge_cells_delete(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        if self.merge_cells.is_empty() {
+            return;
+        }
+        let changed = if columns {
+            self.merge_cells.delete_column(index)
+        } else {
+            self.merge_cells.delete_row(index)
+        };
+        if transaction.is_user_undo_redo() {
+            transaction
+                .reverse_operations
+                .extend(changed.iter().map(|rect| Operation::MergeCells {
+                    sheet_rect: rect.to_sheet_rect(self.id),
+                }));
+        }
+        self.merge_cells_changed(transaction, &changed);
+    }
+
+    /// Sends the merged regions to the client and redraws the regions whose
+    /// size changed (the shifted regions are redrawn with their columns/rows).
+    fn merge_cells_changed(&self, transaction: &mut PendingTransaction, changed: &[Rect]) {
+        transaction.sheet_info.insert(self.id);
+        transaction.sheet_borders.insert(self.id);
+        if transaction.is_server() {
+            return;
+        }
+        changed.iter().for_each(|rect| {
+            transaction.add_dirty_hashes_from_sheet_rect(rect.to_sheet_rect(self.id));
+        });
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+    use crate::color::Rgba;
+    use crate::grid::sheet::borders::CellBorderLine;
+
+    #[test]
+    fn merge_unmerge() {
+        let mut merge_cells = MergeCells::default();
+        assert!(merge_cells.merge(Rect::test_a1("A1:C1")).is_empty());
+        assert_eq!(merge_cells.get(pos![B1]), Some(Rect::test_a1("A1:C1")));
+        assert!(merge_cells.is_covered(pos![B1]));
+        assert!(!merge_cells.is_covered(pos![A1]));
+        assert_eq!(merge_cells.get(pos![A2]), None);
+
+        // a single cell is not merged
+        assert!(merge_cells.merge(Rect::test_a1("E5")).is_empty());
+        assert_eq!(merge_cells.iter().count(), 1);
+
+        // merging over an existing region replaces it
+        assert_eq!(
+            merge_cells.merge(Rect::test_a1("C1:D2")),
+            vec![Rect::test_a1("A1:C1")]
+        );
+        assert_eq!(merge_cells.get(pos![A1]), None);
+
+        assert_eq!(
+            merge_cells.unmerge(Rect::test_a1("D2")),
+            vec![Rect::test_a1("C1:D2")]
+        );
+        assert!(merge_cells.is_empty());
+    }
+
+    #[test]
+    fn expand_rect() {
+        let mut merge_cells = MergeCells::default();
+        merge_cells.merge(Rect::test_a1("B1:C2"));
+        merge_cells.merge(Rect::test_a1("C3:E3"));
+        assert_eq!(
+            merge_cells.expand_rect(Rect::test_a1("A2")),
+            Rect::test_a1("A2")
+        );
+        assert_eq!(
+            merge_cells.expand_rect(Rect::test_a1("B2:B3")),
+            Rect::test_a1("B1:E3")
+        );
+    }
+
+    #[test]
+    fn insert_delete() {
+        let mut merge_cells = MergeCells::default();
+        merge_cells.merge(Rect::test_a1("B2:C3"));
+        merge_cells.merge(Rect::test_a1("E1:E2"));
+
+        assert_eq!(merge_cells.insert_column(3), vec![Rect::test_a1("B2:D3")]);
+        assert_eq!(merge_cells.get(pos![B2]), Some(Rect::test_a1("B2:D3")));
+        assert_eq!(merge_cells.get(pos![F1]), Some(Rect::test_a1("F1:F2")));
+
+        assert_eq!(merge_cells.delete_column(6), vec![Rect::test_a1("F1:F2")]);
+        assert_eq!(merge_cells.iter().count(), 1);
+
+        assert!(merge_cells.insert_row(1).is_empty());
+        assert_eq!(merge_cells.get(pos![B3]), Some(Rect::test_a1("B3:D4")));
+        assert_eq!(merge_cells.delete_row(3), vec![Rect::test_a1("B3:D4")]);
+        assert_eq!(merge_cells.get(pos![B3]), Some(Rect::test_a1("B3:D3")));
+    }
+
+    #[test]
+    fn insert_column_dirty_hashes() {
+        let mut sheet = Sheet::test();
+        sheet.merge_cells.merge(Rect::new(1, 1, 20, 2));
+
+        let mut transaction = PendingTransaction::default();
+        sheet.merge_cells_insert(&mut transaction, true, 2);
+        let dirty_hashes = transaction.dirty_hashes.get(&sheet.id).unwrap();
+        let mut pos = Pos { x: 1, y: 1 };
+        pos.to_quadrant();
+        assert!(dirty_hashes.contains(&pos));
+        let mut pos = Pos { x: 21, y: 1 };
+        pos.to_quadrant();
+        assert!(dirty_hashes.contains(&pos));
+    }
+
+    #[test]
+    fn clip_segment() {
+        assert_eq!(super::clip_segment(1, Some(5), &[]), vec![(1, Some(5))]);
+        assert_eq!(
+            super::clip_segment(1, Some(5), &[(2, 3)]),
+            vec![(1, Some(1)), (4, Some(2))]
+        );
+        assert_eq!(super::clip_segment(1, Some(5), &[(1, 5)]), vec![]);
+        assert_eq!(
+            super::clip_segment(1, None, &[(3, 4)]),
+            vec![(1, Some(2)), (5, None)]
+        );
+    }
+
+    #[test]
+    fn clip_borders() {
+        let mut merge_cells = MergeCells::default();
+        merge_cells.merge(Rect::test_a1("B2:C3"));
+
+        let color = Rgba::default();
+        let mut borders = JsBordersSheet {
+            horizontal: Some(vec![JsBorderHorizontal {
+                color,
+                line: CellBorderLine::default(),
+                x: 1,
+                y: 3,
+                width: Some(4),
+                unbounded: false,
+            }]),
+            vertical: Some(vec![JsBorderVertical {
+                color,
+                line: CellBorderLine::default(),
+                x: 2,
+                y: 1,
+                height: Some(4),
+                unbounded: false,
+            }]),
+        };
+        merge_cells.clip_borders(&mut borders);
+
+        let horizontal = borders.horizontal.unwrap();
+        assert_eq!(horizontal.len(), 2);
+        assert_eq!((horizontal[0].x, horizontal[0].width), (1, Some(1)));
+        assert_eq!((horizontal[1].x, horizontal[1].width), (4, Some(1)));
+
+        // the left edge of the merge is not clipped
+        assert_eq!(borders.vertical.unwrap().len(), 1);
+    }
+}
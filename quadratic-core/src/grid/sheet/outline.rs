@@ -0,0 +1,551 @@
+//! Hidden columns/rows and column/row grouping (outlines) for a Sheet.
+//!
+//! A column or row is hidden if it was explicitly hidden by the user or if it
+//! is inside a collapsed group. Groups may be nested; the level of a group is
+//! the number of groups that contain it (including itself).
+
+use std::collections::BTreeSet;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+
+use super::Sheet;
+use crate::Rect;
+
+/// Maximum number of nested groups (matches Excel).
+pub const MAX_OUTLINE_LEVEL: usize = 7;
+
+/// Sorted, non-overlapping inclusive ranges of hidden columns or rows.
+pub type HiddenRanges = Vec<(i64, i64)>;
+
+/// A group of columns or rows (inclusive range) that may be collapsed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutlineGroup {
+    pub start: i64,
+    pub end: i64,
+    pub collapsed: bool,
+}
+
+impl OutlineGroup {
+    pub fn new(start: i64, end: i64) -> Self {
+        OutlineGroup {
+            start: start.min(end),
+            end: start.max(end),
+            collapsed: false,
+        }
+    }
+
+    pub fn contains(&self, index: i64) -> bool {
+        index >= self.start && index <= self.end
+    }
+
+    /// Returns whether this group fully contains another range.
+    pub fn contains_range(&self, start: i64, end: i64) -> bool {
+        self.start <= start && self.end >= end
+    }
+}
+
+/// Hidden state and groups for one direction (columns or rows).
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OutlineAxis {
+    /// Explicitly hidden columns/rows.
+    #[serde(default)]
+    pub hidden: BTreeSet<i64>,
+
+    /// Groups (sorted by start, then by size descending).
+    #[serde(default)]
+    pub groups: Vec<OutlineGroup>,
+}
+
+impl OutlineAxis {
+    pub fn is_empty(&self) -> bool {
+        self.hidden.is_empty() && self.groups.is_empty()
+    }
+
+    /// Returns whether a column/row is hidden, either explicitly or because it
+    /// is inside a collapsed group.
+    pub fn is_hidden(&self, index: i64) -> bool {
+        self.hidden.contains(&index)
+            || self
+                .groups
+                .iter()
+                .any(|group| group.collapsed && group.contains(index))
+    }
+
+    /// Returns all hidden columns/rows (explicit and collapsed) as sorted,
+    /// non-overlapping inclusive ranges.
+    pub fn all_hidden(&self) -> HiddenRanges {
+        let ranges = self
+            .hidden
+            .iter()
+            .map(|&index| (index, index))
+            .chain(
+                self.groups
+                    .iter()
+                    .filter(|group| group.collapsed)
+                    .map(|group| (group.start, group.end)),
+            )
+            .sorted();
+        let mut hidden: HiddenRanges = vec![];
+        for (start, end) in ranges {
+            match hidden.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => hidden.push((start, end)),
+            }
+        }
+        hidden
+    }
+
+    /// Sets whether a column/row is explicitly hidden. Returns the old value.
+    pub fn set_hidden(&mut self, index: i64, hidden: bool) -> bool {
+        if hidden {
+            !self.hidden.insert(index)
+        } else {
+            self.hidden.remove(&index)
+        }
+    }
+
+    /// Returns the outline level of a column/row (0 if not in a group).
+    pub fn level(&self, index: i64) -> usize {
+        self.groups
+            .iter()
+            .filter(|group| group.contains(index))
+            .count()
+    }
+
+    /// Returns the level of a group (1 for an outer-most group).
+    pub fn group_level(&self, group: &OutlineGroup) -> usize {
+        self.groups
+            .iter()
+            .filter(|g| g.contains_range(group.start, group.end))
+            .count()
+    }
+
+    /// Returns the deepest level of any group.
+    pub fn max_level(&self) -> usize {
+        Self::max_nesting(&self.groups)
+    }
+
+    /// Returns the maximum number of groups that overlap at any index. This
+    /// sweeps over the group endpoints, so it does not depend on the size of
+    /// the groups.
+    fn max_nesting(groups: &[OutlineGroup]) -> usize {
+        let mut level = 0_i64;
+        groups
+            .iter()
+            .flat_map(|group| [(group.start, 1), (group.end.saturating_add(1), -1)])
+            // ends sort before starts at the same index
+            .sorted()
+            .map(|(_, delta)| {
+                level += delta;
+                level.max(0) as usize
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn sort(&mut self) {
+        self.groups
+            .sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+    }
+
+    /// Adds a group. Returns false if the group already exists or would
+    /// exceed [`MAX_OUTLINE_LEVEL`].
+    pub fn add_group(&mut self, start: i64, end: i64) -> bool {
+        let group = OutlineGroup::new(start, end);
+        if self
+            .groups
+            .iter()
+            .any(|g| g.start == group.start && g.end == group.end)
+        {
+            return false;
+        }
+        self.groups.push(group);
+        if Self::max_nesting(&self.groups) > MAX_OUTLINE_LEVEL {
+            self.groups.pop();
+            return false;
+        }
+        self.sort();
+        true
+    }
+
+    /// Removes one level of grouping from a range: the deepest groups that
+    /// are entirely within the range are removed. Returns false if nothing
+    /// was removed.
+    pub fn remove_group(&mut self, start: i64, end: i64) -> bool {
+        let (start, end) = (start.min(end), start.max(end));
+        let Some(level) = self
+            .groups
+            .iter()
+            .filter(|group| group.start >= start && group.end <= end)
+            .map(|group| self.group_level(group))
+            .max()
+        else {
+            return false;
+        };
+        let remove = self
+            .groups
+            .iter()
+            .filter(|group| {
+                group.start >= start && group.end <= end && self.group_level(group) == level
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        self.groups.retain(|group| !remove.contains(group));
+        true
+    }
+
+    /// Collapses or expands the inner-most group that contains a column/row.
+    /// Returns false if no group contains the index.
+    pub fn set_collapsed(&mut self, index: i64, collapsed: bool) -> bool {
+        let Some(group) = self
+            .groups
+            .iter_mut()
+            .filter(|group| group.contains(index))
+            .min_by_key(|group| group.end - group.start)
+        else {
+            return false;
+        };
+        group.collapsed = collapsed;
+        true
+    }
+
+    /// Shows groups up to the given level (similar to Excel's outline level
+    /// buttons): groups at `level` or deeper are collapsed and all others are
+    /// expanded.
+    pub fn show_level(&mut self, level: usize) {
+        let levels = self
+            .groups
+            .iter()
+            .map(|group| self.group_level(group))
+            .collect::<Vec<_>>();
+        self.groups
+            .iter_mut()
+            .zip(levels)
+            .for_each(|(group, group_level)| group.collapsed = group_level >= level);
+    }
+
+    /// Adjusts hidden columns/rows and groups for an inserted column/row.
+    /// Groups that contain the inserted index grow.
+    pub fn insert(&mut self, index: i64) {
+        self.hidden = self
+            .hidden
+            .iter()
+            .map(|&i| if i >= index { i + 1 } else { i })
+            .collect();
+        self.groups.iter_mut().for_each(|group| {
+            if group.start >= index {
+                group.start += 1;
+                group.end += 1;
+            } else if group.end >= index {
+                group.end += 1;
+            }
+        });
+    }
+
+    /// Adjusts hidden columns/rows and groups for a deleted column/row.
+    /// Groups that contain the deleted index shrink (and are removed if
+    /// empty).
+    pub fn delete(&mut self, index: i64) {
+        self.hidden = self
+            .hidden
+            .iter()
+            .filter(|&&i| i != index)
+            .map(|&i| if i > index { i - 1 } else { i })
+            .collect();
+        self.groups.iter_mut().for_each(|group| {
+            if group.start > index {
+                group.start -= 1;
+                group.end -= 1;
+            } else if group.end >= index {
+                group.end -= 1;
+            }
+        });
+        self.groups.retain(|group| group.end >= group.start);
+        self.groups
+            .dedup_by(|a, b| a.start == b.start && a.end == b.end);
+    }
+}
+
+/// Hidden columns/rows and groups for a Sheet.
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SheetOutline {
+    #[serde(default)]
+    pub columns: OutlineAxis,
+
+    #[serde(default)]
+    pub rows: OutlineAxis,
+}
+
+impl Sheet {
+    /// Returns whether a column is hidden (explicitly or by a collapsed group).
+    pub fn is_column_hidden(&self, column: i64) -> bool {
+        self.offsets.is_column_hidden(column)
+    }
+
+    /// Returns whether a row is hidden (explicitly or by a collapsed group).
+    pub fn is_row_hidden(&self, row: i64) -> bool {
+        self.offsets.is_row_hidden(row)
+    }
+
+    /// Returns whether the cell is in a hidden column or row.
+    pub fn is_pos_hidden(&self, pos: crate::Pos) -> bool {
+        self.is_column_hidden(pos.x) || self.is_row_hidden(pos.y)
+    }
+
+    /// Copies the outline's hidden state into the offsets so hidden columns
+    /// and rows have a size of 0. Returns the (column ranges, row ranges)
+    /// whose hidden state changed.
+    pub fn update_hidden_offsets(&mut self) -> (HiddenRanges, HiddenRanges) {
+        let columns = self
+            .offsets
+            .set_hidden_columns(self.outline.columns.all_hidden());
+        let rows = self.offsets.set_hidden_rows(self.outline.rows.all_hidden());
+        (columns, rows)
+    }
+
+    /// Updates the offsets after a change to the outline and adds the
+    /// necessary client updates to the transaction.
+    pub(crate) fn outline_changed(&mut self, transaction: &mut PendingTransaction) {
+        let (columns, rows) = self.update_hidden_offsets();
+        transaction.sheet_info.insert(self.id);
+        if transaction.is_server() {
+            return;
+        }
+        // the client receives the hidden ranges with the offsets in the sheet
+        // info, so only the affected hashes (within the sheet's bounds) need
+        // to be redrawn
+        let Some(bounds) = Option::<Rect>::from(self.bounds(false)) else {
+            return;
+        };
+        for (start, end) in columns {
+            let (start, end) = (start.max(bounds.min.x), end.min(bounds.max.x));
+            if start <= end {
+                transaction.add_dirty_hashes_from_sheet_columns(self, start, Some(end));
+            }
+        }
+        for (start, end) in rows {
+            let (start, end) = (start.max(bounds.min.y), end.min(bounds.max.y));
+            if start <= end {
+                transaction.add_dirty_hashes_from_sheet_rows(self, start, Some(end));
+            }
+        }
+    }
+
+    /// Shifts the outline for an inserted column (or row if `columns` is
+    /// false).
+    pub(crate) fn outline_insert(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        let axis = if columns {
+            &mut self.outline.columns
+        } else {
+            &mut self.outline.rows
+        };
+        if axis.is_empty() {
+            return;
+        }
+        axis.insert(index);
+        self.outline_changed(transaction);
+    }
+
+    /// Shifts the outline for a deleted column (or row if `columns` is
+    /// false). Adds the reverse operations to restore the deleted index's
+    /// hidden state and any groups it changed.
+    pub(crate) fn outline_delete(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        let sheet_id = self.id;
+        let axis = if columns {
+            &mut self.outline.columns
+        } else {
+            &mut self.outline.rows
+        };
+        if axis.is_empty() {
+            return;
+        }
+        let was_hidden = axis.hidden.contains(&index);
+        let old_groups = axis.groups.clone();
+        axis.delete(index);
+
+        if transaction.is_user_undo_redo() {
+            if axis.groups != old_groups {
+                transaction.reverse_operations.push(if columns {
+                    Operation::SetColumnGroups {
+                        sheet_id,
+                        groups: old_groups,
+                    }
+                } else {
+                    Operation::SetRowGroups {
+                        sheet_id,
+                        groups: old_groups,
+                    }
+                });
+            }
+            if was_hidden {
+                transaction.reverse_operations.push(if columns {
+                    Operation::SetColumnsHidden {
+                        sheet_id,
+                        columns: vec![index],
+                        hidden: true,
+                    }
+                } else {
+                    Operation::SetRowsHidden {
+                        sheet_id,
+                        rows: vec![index],
+                        hidden: true,
+                    }
+                });
+            }
+        }
+        self.outline_changed(transaction);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden() {
+        let mut axis = OutlineAxis::default();
+        assert!(!axis.set_hidden(2, true));
+        assert!(axis.set_hidden(2, true));
+        assert!(axis.is_hidden(2));
+        assert!(!axis.is_hidden(3));
+        assert!(axis.set_hidden(2, false));
+        assert!(!axis.is_hidden(2));
+    }
+
+    #[test]
+    fn groups_levels() {
+        let mut axis = OutlineAxis::default();
+        assert!(axis.add_group(2, 10));
+        assert!(axis.add_group(3, 5));
+        assert!(!axis.add_group(5, 3));
+        assert_eq!(axis.level(1), 0);
+        assert_eq!(axis.level(2), 1);
+        assert_eq!(axis.level(4), 2);
+        assert_eq!(axis.max_level(), 2);
+        assert_eq!(axis.groups[0], OutlineGroup::new(2, 10));
+
+        axis.show_level(2);
+        assert!(!axis.is_hidden(2));
+        assert!(axis.is_hidden(3));
+        assert_eq!(axis.all_hidden(), vec![(3, 5)]);
+
+        axis.show_level(1);
+        assert!(axis.is_hidden(10));
+
+        axis.show_level(3);
+        assert!(axis.all_hidden().is_empty());
+
+        assert!(axis.set_collapsed(4, true));
+        assert_eq!(axis.all_hidden(), vec![(3, 5)]);
+        assert!(!axis.set_collapsed(20, true));
+
+        assert!(axis.remove_group(1, 20));
+        assert_eq!(axis.groups, vec![OutlineGroup::new(2, 10)]);
+        assert!(!axis.remove_group(11, 20));
+    }
+
+    #[test]
+    fn max_level() {
+        let mut axis = OutlineAxis::default();
+        for i in 0..MAX_OUTLINE_LEVEL as i64 {
+            assert!(axis.add_group(1 + i, 20 - i));
+        }
+        assert!(!axis.add_group(10, 11));
+        assert!(axis.add_group(21, 30));
+    }
+
+    #[test]
+    fn large_groups() {
+        let mut axis = OutlineAxis::default();
+        for i in 0..MAX_OUTLINE_LEVEL as i64 {
+            assert!(axis.add_group(1 + i, i64::MAX - i));
+        }
+        assert!(!axis.add_group(100, 1_000_000_000));
+        assert_eq!(axis.max_level(), MAX_OUTLINE_LEVEL);
+
+        axis.set_hidden(2, true);
+        axis.set_hidden(4, true);
+        assert_eq!(axis.all_hidden(), vec![(2, 2), (4, 4)]);
+        axis.show_level(MAX_OUTLINE_LEVEL);
+        assert_eq!(axis.all_hidden(), vec![(2, 2), (4, 4), (7, i64::MAX - 6)]);
+        axis.show_level(1);
+        assert_eq!(axis.all_hidden(), vec![(1, i64::MAX)]);
+    }
+
+    #[test]
+    fn insert_delete() {
+        let mut axis = OutlineAxis::default();
+        axis.set_hidden(5, true);
+        axis.add_group(2, 4);
+        axis.add_group(10, 10);
+
+        axis.insert(3);
+        assert_eq!(axis.hidden, BTreeSet::from([6]));
+        assert_eq!(
+            axis.groups,
+            vec![OutlineGroup::new(2, 5), OutlineGroup::new(11, 11)]
+        );
+
+        axis.delete(11);
+        assert_eq!(axis.groups, vec![OutlineGroup::new(2, 5)]);
+
+        axis.delete(6);
+        assert!(axis.hidden.is_empty());
+
+        axis.delete(1);
+        assert_eq!(axis.groups, vec![OutlineGroup::new(1, 4)]);
+    }
+
+    #[test]
+    fn sheet_hidden_offsets() {
+        let mut sheet = Sheet::test();
+        sheet.outline.columns.set_hidden(2, true);
+        sheet.outline.rows.add_group(3, 4);
+        sheet.outline.rows.set_collapsed(3, true);
+        assert_eq!(sheet.update_hidden_offsets(), (vec![(2, 2)], vec![(3, 4)]));
+        assert!(sheet.is_column_hidden(2));
+        assert!(sheet.is_row_hidden(4));
+        assert!(sheet.is_pos_hidden(crate::Pos { x: 1, y: 3 }));
+        assert_eq!(sheet.offsets.column_width(2), 0.0);
+        assert_eq!(sheet.update_hidden_offsets(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn delete_column_undo() {
+        let mut gc = crate::controller::GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.hide_columns(sheet_id, vec![3, 6], None);
+        gc.group_columns(sheet_id, 3, 3, None);
+
+        gc.delete_columns(sheet_id, vec![3], None);
+        let sheet = gc.sheet(sheet_id);
+        assert!(!sheet.is_column_hidden(3));
+        assert!(sheet.is_column_hidden(5));
+        assert!(sheet.outline.columns.groups.is_empty());
+
+        gc.insert_column(sheet_id, 1, false, None);
+        assert!(gc.sheet(sheet_id).is_column_hidden(6));
+
+        gc.undo(None);
+        gc.undo(None);
+        let sheet = gc.sheet(sheet_id);
+        assert!(sheet.is_column_hidden(3));
+        assert!(sheet.is_column_hidden(6));
+        assert_eq!(sheet.outline.columns.groups, vec![OutlineGroup::new(3, 3)]);
+    }
+}
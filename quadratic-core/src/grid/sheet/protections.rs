@@ -0,0 +1,376 @@
+//! Protected ranges for a Sheet.
+//!
+//! A protected range lists the users and roles that may edit it; everyone
+//! else is refused changes that touch it. A range without a rect protects the
+//! whole sheet. Ranges move with their cells when columns and rows are
+//! inserted or deleted.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::controller::active_transactions::pending_transaction::PendingTransaction;
+use crate::controller::operations::operation::Operation;
+use crate::grid::SheetId;
+use crate::Rect;
+
+use super::Sheet;
+
+/// Roles that can be allowed to edit a protected range, in addition to the
+/// listed users.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, TS)]
+pub enum ProtectionRole {
+    /// Users who own the file or manage it in its team (ie, those who can
+    /// delete it).
+    Owner,
+}
+
+/// The user that is making changes, used to check protected ranges.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+pub struct ProtectionEditor {
+    pub user_id: String,
+    pub roles: Vec<ProtectionRole>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+pub struct ProtectedRange {
+    pub id: Uuid,
+
+    /// The protected cells. None protects the whole sheet.
+    pub rect: Option<Rect>,
+
+    /// Ids of the users who may edit the range.
+    pub users: Vec<String>,
+
+    /// Roles whose users may edit the range.
+    pub roles: Vec<ProtectionRole>,
+}
+
+impl ProtectedRange {
+    pub fn new(rect: Option<Rect>, users: Vec<String>, roles: Vec<ProtectionRole>) -> Self {
+        ProtectedRange {
+            id: Uuid::new_v4(),
+            rect,
+            users,
+            roles,
+        }
+    }
+
+    /// Whether the editor may change the range's cells (and the range).
+    pub fn can_edit(&self, editor: &ProtectionEditor) -> bool {
+        self.users.contains(&editor.user_id)
+            || editor.roles.iter().any(|role| self.roles.contains(role))
+    }
+
+    /// Whether a change to `rect` touches the range. Unbounded edges of
+    /// `rect` use `UNBOUNDED`.
+    pub fn intersects(&self, rect: Rect) -> bool {
+        self.rect.is_none_or(|range| range.intersects(rect))
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Protections {
+    ranges: Vec<ProtectedRange>,
+}
+
+impl Protections {
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ProtectedRange> {
+        self.ranges.iter()
+    }
+
+    /// Gets a range based on its id.
+    pub fn get(&self, range_id: Uuid) -> Option<&ProtectedRange> {
+        self.ranges.iter().find(|range| range.id == range_id)
+    }
+
+    /// Updates or adds a range. Returns the reverse operations.
+    pub fn set(&mut self, sheet_id: SheetId, range: ProtectedRange) -> Vec<Operation> {
+        if let Some(existing) = self.ranges.iter_mut().find(|r| r.id == range.id) {
+            let reverse = vec![Operation::SetProtectedRange {
+                sheet_id,
+                range: existing.clone(),
+            }];
+            *existing = range;
+            return reverse;
+        }
+        let reverse = vec![Operation::RemoveProtectedRange {
+            sheet_id,
+            range_id: range.id,
+        }];
+        self.ranges.push(range);
+        reverse
+    }
+
+    /// Removes a range. Returns the reverse operations.
+    pub fn remove(&mut self, sheet_id: SheetId, range_id: Uuid) -> Vec<Operation> {
+        let mut reverse = vec![];
+        self.ranges.retain(|range| {
+            if range.id == range_id {
+                reverse.push(Operation::SetProtectedRange {
+                    sheet_id,
+                    range: range.clone(),
+                });
+                false
+            } else {
+                true
+            }
+        });
+        reverse
+    }
+
+    /// Moves ranges for an inserted column (or row if `columns` is false).
+    /// Ranges that contain the column grow.
+    pub fn insert(&mut self, columns: bool, index: i64) -> bool {
+        let mut changed = false;
+        self.ranges
+            .iter_mut()
+            .filter_map(|range| range.rect.as_mut())
+            .for_each(|rect| {
+                let (min, max) = if columns {
+                    (&mut rect.min.x, &mut rect.max.x)
+                } else {
+                    (&mut rect.min.y, &mut rect.max.y)
+                };
+                if *min >= index {
+                    *min += 1;
+                    *max += 1;
+                    changed = true;
+                } else if *max >= index {
+                    *max += 1;
+                    changed = true;
+                }
+            });
+        changed
+    }
+
+    /// Moves ranges for a deleted column (or row if `columns` is false).
+    /// Returns the ranges (as they were before the deletion) that were
+    /// changed. Ranges that only covered the deleted column are removed.
+    pub fn delete(&mut self, columns: bool, index: i64) -> Vec<ProtectedRange> {
+        let mut changed = vec![];
+        self.ranges.retain_mut(|range| {
+            let original = range.clone();
+            let Some(rect) = range.rect.as_mut() else {
+                return true;
+            };
+            let (min, max) = if columns {
+                (&mut rect.min.x, &mut rect.max.x)
+            } else {
+                (&mut rect.min.y, &mut rect.max.y)
+            };
+            if *min > index {
+                *min -= 1;
+                *max -= 1;
+            } else if *max >= index {
+                *max -= 1;
+                changed.push(original);
+                return *max >= *min;
+            }
+            true
+        });
+        changed
+    }
+
+    /// Stringifies the ranges to send to the client.
+    pub fn to_string(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&self.ranges)
+    }
+}
+
+impl FromIterator<ProtectedRange> for Protections {
+    fn from_iter<I: IntoIterator<Item = ProtectedRange>>(ranges: I) -> Self {
+        Protections {
+            ranges: ranges.into_iter().collect(),
+        }
+    }
+}
+
+impl Sheet {
+    /// Moves protected ranges for an inserted column (or row if `columns` is
+    /// false).
+    pub(crate) fn protections_insert(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        if self.protections.insert(columns, index) {
+            transaction.sheet_info.insert(self.id);
+        }
+    }
+
+    /// Moves protected ranges for a deleted column (or row if `columns` is
+    /// false). Adds the reverse operations to restore the ranges that were
+    /// changed.
+    pub(crate) fn protections_delete(
+        &mut self,
+        transaction: &mut PendingTransaction,
+        columns: bool,
+        index: i64,
+    ) {
+        if self.protections.is_empty() {
+            return;
+        }
+        let changed = self.protections.delete(columns, index);
+        if changed.is_empty() {
+            return;
+        }
+        if transaction.is_user_undo_redo() {
+            transaction
+                .reverse_operations
+                .extend(
+                    changed
+                        .into_iter()
+                        .map(|range| Operation::SetProtectedRange {
+                            sheet_id: self.id,
+                            range,
+                        }),
+                );
+        }
+        transaction.sheet_info.insert(self.id);
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    fn editor(user_id: &str, roles: Vec<ProtectionRole>) -> ProtectionEditor {
+        ProtectionEditor {
+            user_id: user_id.into(),
+            roles,
+        }
+    }
+
+    #[test]
+    fn can_edit_intersects() {
+        let range = ProtectedRange::new(
+            Some(Rect::test_a1("B2:C3")),
+            vec!["alice".into()],
+            vec![ProtectionRole::Owner],
+        );
+
+        let alice = editor("alice", vec![]);
+        let bob = editor("bob", vec![]);
+        let owner = editor("carol", vec![ProtectionRole::Owner]);
+        assert!(range.can_edit(&alice));
+        assert!(!range.can_edit(&bob));
+        assert!(range.can_edit(&owner));
+
+        assert!(range.intersects(Rect::test_a1("C3:D4")));
+        assert!(!range.intersects(Rect::test_a1("D1:D10")));
+
+        // a range without a rect protects the whole sheet
+        let sheet_range = ProtectedRange::new(None, vec![], vec![]);
+        assert!(sheet_range.intersects(Rect::test_a1("Z100")));
+    }
+
+    #[test]
+    fn set_remove() {
+        let sheet_id = SheetId::TEST;
+        let mut protections = Protections::default();
+        let mut range = ProtectedRange::new(Some(Rect::test_a1("A1:B2")), vec![], vec![]);
+
+        let reverse = protections.set(sheet_id, range.clone());
+        assert_eq!(
+            reverse,
+            vec![Operation::RemoveProtectedRange {
+                sheet_id,
+                range_id: range.id
+            }]
+        );
+
+        let original = range.clone();
+        range.users.push("alice".into());
+        let reverse = protections.set(sheet_id, range.clone());
+        assert_eq!(
+            reverse,
+            vec![Operation::SetProtectedRange {
+                sheet_id,
+                range: original
+            }]
+        );
+        assert_eq!(protections.get(range.id), Some(&range));
+
+        let reverse = protections.remove(sheet_id, range.id);
+        assert_eq!(
+            reverse,
+            vec![Operation::SetProtectedRange {
+                sheet_id,
+                range: range.clone()
+            }]
+        );
+        assert!(protections.is_empty());
+        assert!(protections.remove(sheet_id, range.id).is_empty());
+    }
+
+    #[test]
+    fn insert_delete() {
+        let range = ProtectedRange::new(Some(Rect::test_a1("B2:C3")), vec![], vec![]);
+        let column = ProtectedRange::new(Some(Rect::test_a1("E1:E5")), vec![], vec![]);
+        let sheet = ProtectedRange::new(None, vec![], vec![]);
+        let mut protections = Protections::from_iter([range.clone(), column, sheet]);
+
+        // inserting inside a range grows it
+        assert!(protections.insert(true, 3));
+        let rects = protections.iter().map(|r| r.rect).collect::<Vec<_>>();
+        assert_eq!(
+            rects,
+            vec![
+                Some(Rect::test_a1("B2:D3")),
+                Some(Rect::test_a1("F1:F5")),
+                None
+            ]
+        );
+
+        // inserting after all ranges changes nothing
+        assert!(!protections.insert(false, 10));
+
+        // deleting the only column of a range removes it
+        let changed = protections.delete(true, 6);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].rect, Some(Rect::test_a1("F1:F5")));
+        assert_eq!(protections.iter().count(), 2);
+
+        let changed = protections.delete(true, 2);
+        assert_eq!(changed[0].rect, Some(Rect::test_a1("B2:D3")));
+        assert_eq!(
+            protections.get(range.id).unwrap().rect,
+            Some(Rect::test_a1("B2:C3"))
+        );
+    }
+
+    #[test]
+    fn sheet_insert_delete() {
+        let mut sheet = Sheet::test();
+        let range = ProtectedRange::new(Some(Rect::test_a1("B2:C3")), vec![], vec![]);
+        sheet.protections.set(sheet.id, range.clone());
+
+        let mut transaction = PendingTransaction::default();
+        sheet.protections_delete(&mut transaction, false, 2);
+        assert_eq!(
+            transaction.reverse_operations,
+            vec![Operation::SetProtectedRange {
+                sheet_id: sheet.id,
+                range: range.clone()
+            }]
+        );
+        assert_eq!(
+            sheet.protections.get(range.id).unwrap().rect,
+            Some(Rect::test_a1("B2:C2"))
+        );
+
+        // inserting at the range's first row moves it
+        sheet.protections_insert(&mut transaction, false, 2);
+        assert_eq!(
+            sheet.protections.get(range.id).unwrap().rect,
+            Some(Rect::test_a1("B3:C3"))
+        );
+    }
+}
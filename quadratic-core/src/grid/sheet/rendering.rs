@@ -1,6 +1,7 @@
 use code_run::CodeRunResult;
 
 use super::Sheet;
+use crate::controller::host::Host;
 use crate::grid::js_types::{
     JsHtmlOutput, JsNumber, JsRenderCell, JsRenderCellSpecial, JsRenderCodeCell,
     JsRenderCodeCellState, JsRenderFill, JsSheetFill, JsValidationWarning,
@@ -78,14 +79,33 @@ impl Sheet {
 
         let mut format = self.formats.try_format(Pos { x, y }).unwrap_or_default();
         let mut number: Option<JsNumber> = None;
+        let number_format = format
+            .numeric_format
+            .as_ref()
+            .and_then(|numeric_format| numeric_format.number_format());
         let value = match &value {
-            CellValue::Number(_) => {
-                // get numeric_format and numeric_decimal to turn number into a string
+            CellValue::Number(n) => {
                 // if align is not set, set it to right only for numbers
                 format.align = format.align.or(Some(CellAlign::Right));
-                number = Some((&format).into());
-                value.to_display()
+
+                // format codes are formatted here; otherwise the client uses
+                // numeric_format and numeric_decimal to turn number into a string
+                if let Some(number_format) = number_format {
+                    let formatted = number_format.format_number(n);
+                    format.text_color = formatted.color.or(format.text_color);
+                    formatted.text
+                } else {
+                    number = Some((&format).into());
+                    value.to_display()
+                }
             }
+            CellValue::Text(text) => match number_format.and_then(|f| f.format_text(text)) {
+                Some(formatted) => {
+                    format.text_color = formatted.color.or(format.text_color);
+                    formatted.text
+                }
+                None => value.to_display(),
+            },
             CellValue::Date(_) | CellValue::DateTime(_) | CellValue::Time(_) => {
                 Self::value_date_time(value, format.date_time)
             }
@@ -106,6 +126,11 @@ impl Sheet {
             number,
             underline: format.underline,
             strike_through: format.strike_through,
+            font_size: format.font_size,
+            font_family: format.font_family,
+            text_rotation: format.text_rotation,
+            merge: None,
+            data_bar: None,
         }
     }
 
@@ -241,6 +266,51 @@ impl Sheet {
                         });
                 }
             });
+
+        // conditional formats override the cell's format
+        if !self.conditional_formats.is_empty() {
+            let results = self.conditional_formats.evaluate(self, rect);
+            render_cells.iter_mut().for_each(|cell| {
+                if let Some(result) = results.get(&Pos {
+                    x: cell.x,
+                    y: cell.y,
+                }) {
+                    let format = &result.format;
+                    cell.bold = format.bold.or(cell.bold);
+                    cell.italic = format.italic.or(cell.italic);
+                    cell.text_color = format.text_color.clone().or(cell.text_color.take());
+                    cell.underline = format.underline.or(cell.underline);
+                    cell.strike_through = format.strike_through.or(cell.strike_through);
+                    cell.data_bar.clone_from(&result.data_bar);
+                }
+            });
+        }
+
+        // hidden columns and rows are not rendered
+        if !self.outline.columns.is_empty() || !self.outline.rows.is_empty() {
+            render_cells.retain(|cell| {
+                !self.is_pos_hidden(Pos {
+                    x: cell.x,
+                    y: cell.y,
+                })
+            });
+        }
+
+        // merged regions are rendered as a single cell at their top-left
+        if !self.merge_cells.is_empty() {
+            render_cells.retain(|cell| {
+                !self.merge_cells.is_covered(Pos {
+                    x: cell.x,
+                    y: cell.y,
+                })
+            });
+            render_cells.iter_mut().for_each(|cell| {
+                cell.merge = self.merge_cells.get(Pos {
+                    x: cell.x,
+                    y: cell.y,
+                });
+            });
+        }
         render_cells
     }
 
@@ -292,7 +362,8 @@ impl Sheet {
 
     /// Returns all data for rendering cell fill color.
     pub fn get_all_render_fills(&self) -> Vec<JsRenderFill> {
-        self.formats
+        let mut fills = self
+            .formats
             .fill_color
             .to_rects()
             .filter_map(|(x0, y0, x1, y1, color)| {
@@ -308,7 +379,11 @@ impl Sheet {
                     None
                 }
             })
-            .collect()
+            .collect::<Vec<_>>();
+
+        // conditional formats are drawn over the cell fills
+        fills.extend(self.conditional_formats.render_fills(self));
+        fills
     }
 
     /// Returns all fills for the rows, columns, and sheet. This does not return
@@ -426,8 +501,8 @@ impl Sheet {
     /// inside CodeRuns. We may open this up in the future to allow images to be
     /// placed directly on the grid without a CodeRun. In that case, we'll need
     /// to search the columns for images as well.
-    pub fn send_all_images(&self) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_all_images(&self, host: &dyn Host) {
+        if !host.renders() {
             return;
         }
 
@@ -438,7 +513,7 @@ impl Sheet {
                 } else {
                     (None, None)
                 };
-                crate::wasm_bindings::js::jsSendImage(
+                host.send_image(
                     self.id.to_string(),
                     pos.x as i32,
                     pos.y as i32,
@@ -451,15 +526,15 @@ impl Sheet {
     }
 
     /// Sends all validations for this sheet to the client.
-    pub fn send_all_validations(&self) {
+    pub fn send_all_validations(&self, host: &dyn Host) {
         if let Ok(validations) = self.validations.to_string() {
-            crate::wasm_bindings::js::jsSheetValidations(self.id.to_string(), validations);
+            host.sheet_validations(self.id.to_string(), validations);
         }
     }
 
     // Sends an update to a code cell. Sends a message regardless of whether the
     // code cell is still present.
-    pub fn send_code_cell(&self, pos: Pos) {
+    pub fn send_code_cell(&self, host: &dyn Host, pos: Pos) {
         if let (Some(code_cell), Some(render_code_cell)) =
             (self.edit_code_value(pos), self.get_render_code_cell(pos))
         {
@@ -467,7 +542,7 @@ impl Sheet {
                 serde_json::to_string(&code_cell),
                 serde_json::to_string(&render_code_cell),
             ) {
-                crate::wasm_bindings::js::jsUpdateCodeCell(
+                host.update_code_cell(
                     self.id.to_string(),
                     pos.x,
                     pos.y,
@@ -476,29 +551,23 @@ impl Sheet {
                 );
             }
         } else {
-            crate::wasm_bindings::js::jsUpdateCodeCell(
-                self.id.to_string(),
-                pos.x,
-                pos.y,
-                None,
-                None,
-            );
+            host.update_code_cell(self.id.to_string(), pos.x, pos.y, None, None);
         }
     }
 
     /// Sends validation warnings to the client.
-    pub fn send_validation_warnings(&self, warnings: Vec<JsValidationWarning>) {
+    pub fn send_validation_warnings(&self, host: &dyn Host, warnings: Vec<JsValidationWarning>) {
         if warnings.is_empty() {
             return;
         }
 
         if let Ok(warnings) = serde_json::to_string(&warnings) {
-            crate::wasm_bindings::js::jsValidationWarning(self.id.to_string(), warnings);
+            host.validation_warning(self.id.to_string(), warnings);
         }
     }
 
     /// Sends all validation warnings for this sheet to the client.
-    pub fn send_all_validation_warnings(&self) {
+    pub fn send_all_validation_warnings(&self, host: &dyn Host) {
         let warnings = self
             .validations
             .warnings
@@ -514,11 +583,17 @@ impl Sheet {
             })
             .collect::<Vec<_>>();
 
-        self.send_validation_warnings(warnings);
+        self.send_validation_warnings(host, warnings);
     }
 
     /// Sends validation warnings for a hashed region to the client.
-    pub fn send_validation_warnings_from_hash(&self, hash_x: i64, hash_y: i64, rect: Rect) {
+    pub fn send_validation_warnings_from_hash(
+        &self,
+        host: &dyn Host,
+        hash_x: i64,
+        hash_y: i64,
+        rect: Rect,
+    ) {
         let warnings = self
             .validations
             .warnings
@@ -539,21 +614,16 @@ impl Sheet {
             .collect::<Vec<_>>();
 
         if let Ok(warnings) = serde_json::to_string(&warnings) {
-            crate::wasm_bindings::js::jsRenderValidationWarnings(
-                self.id.to_string(),
-                hash_x,
-                hash_y,
-                warnings,
-            );
+            host.render_validation_warnings(self.id.to_string(), hash_x, hash_y, warnings);
         }
     }
 
     /// Sends validation warnings as a response from the request from the
     /// client. Note, the client always requests hash-sized rects.
-    pub fn send_validation_warnings_rect(&self, rect: Rect) {
+    pub fn send_validation_warnings_rect(&self, host: &dyn Host, rect: Rect) {
         let hash_x = rect.min.x / CELL_SHEET_WIDTH as i64;
         let hash_y = rect.min.y / CELL_SHEET_HEIGHT as i64;
-        self.send_validation_warnings_from_hash(hash_x, hash_y, rect);
+        self.send_validation_warnings_from_hash(host, hash_x, hash_y, rect);
     }
 }
 
@@ -566,7 +636,7 @@ mod tests {
     use uuid::Uuid;
 
     use crate::{
-        controller::{transaction_types::JsCodeResult, GridController},
+        controller::{host::JsHost, transaction_types::JsCodeResult, GridController},
         grid::{
             js_types::{
                 JsHtmlOutput, JsNumber, JsRenderCell, JsRenderCellSpecial, JsRenderCodeCell,
@@ -576,7 +646,8 @@ mod tests {
                 validation::{Validation, ValidationStyle},
                 validation_rules::{validation_logical::ValidationLogical, ValidationRule},
             },
-            CellVerticalAlign, CellWrap, CodeCellValue, RenderSize,
+            CellVerticalAlign, CellWrap, CodeCellValue, NumericFormat, NumericFormatKind,
+            RenderSize,
         },
         wasm_bindings::js::{clear_js_calls, expect_js_call, expect_js_call_count, hash_test},
         A1Selection, CellValue, Pos, Rect, RunError, RunErrorMsg, SheetPos, Value,
@@ -621,6 +692,8 @@ mod tests {
                 line_number: None,
                 output_type: None,
                 last_modified: Utc::now(),
+                refresh: Default::default(),
+                volatile: false,
             }),
         );
         assert!(sheet.has_render_cells(rect));
@@ -837,6 +910,8 @@ mod tests {
             spill_error: false,
             line_number: None,
             output_type: None,
+            refresh: Default::default(),
+            volatile: false,
         };
 
         // render rect is larger than code rect
@@ -886,6 +961,8 @@ mod tests {
             spill_error: false,
             line_number: None,
             output_type: None,
+            refresh: Default::default(),
+            volatile: false,
         };
         let code_cells = sheet.get_code_cells(
             &code_cell,
@@ -1006,6 +1083,8 @@ mod tests {
             spill_error: false,
             line_number: None,
             output_type: None,
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(pos, Some(run));
         sheet.set_cell_value(pos, code);
@@ -1033,7 +1112,7 @@ mod tests {
 
         // ensure nothing is sent when no images are in the sheet
         let sheet = gc.sheet(sheet_id);
-        sheet.send_all_images();
+        sheet.send_all_images(&JsHost);
         expect_js_call_count("jsSendImage", 0, false);
 
         // add an image to a code run and then send it to the client
@@ -1052,10 +1131,12 @@ mod tests {
             spill_error: false,
             line_number: None,
             output_type: None,
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(pos, Some(run));
         sheet.set_cell_value(pos, code);
-        sheet.send_all_images();
+        sheet.send_all_images(&JsHost);
         expect_js_call(
             "jsSendImage",
             format!(
@@ -1201,7 +1282,7 @@ mod tests {
             message: Default::default(),
             error: Default::default(),
         });
-        sheet.send_all_validations();
+        sheet.send_all_validations(&JsHost);
         let validations = serde_json::to_string(&sheet.validations.validations).unwrap();
         expect_js_call(
             "jsSheetValidations",
@@ -1229,7 +1310,7 @@ mod tests {
             .validations
             .warnings
             .insert((0, 0).into(), validation_id);
-        sheet.send_all_validation_warnings();
+        sheet.send_all_validation_warnings(&JsHost);
         let warnings = serde_json::to_string(&vec![JsValidationWarning {
             x: 0,
             y: 0,
@@ -1243,4 +1324,113 @@ mod tests {
             true,
         );
     }
+
+    #[test]
+    #[parallel]
+    fn render_cells_hidden() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value((1, 1, sheet_id).into(), "a".to_string(), None);
+        gc.set_cell_value((1, 2, sheet_id).into(), "b".to_string(), None);
+        gc.hide_rows(sheet_id, vec![2], None);
+
+        let sheet = gc.sheet(sheet_id);
+        let render = sheet.get_render_cells(Rect::new(1, 1, 10, 10));
+        assert_eq!(render.len(), 1);
+        assert_eq!(render[0].y, 1);
+    }
+
+    #[test]
+    #[parallel]
+    fn render_cells_merged() {
+        let mut gc = GridController::test();
+        let sheet_id = gc.sheet_ids()[0];
+        gc.set_cell_value((1, 1, sheet_id).into(), "a".to_string(), None);
+        gc.merge_cells(&A1Selection::test_a1("A1:B2"), None);
+        gc.sheet_mut(sheet_id)
+            .set_cell_value(pos![B2], CellValue::Text("covered".to_string()));
+
+        let sheet = gc.sheet(sheet_id);
+        let render = sheet.get_render_cells(Rect::new(1, 1, 10, 10));
+        assert_eq!(render.len(), 1);
+        assert_eq!(render[0].value, "a");
+        assert_eq!(render[0].merge, Some(Rect::test_a1("A1:B2")));
+    }
+
+    #[test]
+    #[parallel]
+    fn render_cells_conditional_format() {
+        use crate::color::Rgba;
+        use crate::grid::formats::Format;
+        use crate::grid::js_types::JsRenderDataBar;
+        use crate::grid::sheet::conditional_formats::{
+            conditional_format::ConditionalFormat,
+            conditional_format_rule::{ConditionalFormatRule, DataBar, NumberComparison},
+        };
+
+        let mut sheet = Sheet::test();
+        sheet.set_cell_value(pos![A1], CellValue::Number(1.into()));
+        sheet.set_cell_value(pos![A2], CellValue::Number(3.into()));
+        sheet.conditional_formats.set(ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1("A"),
+            rule: ConditionalFormatRule::Number(NumberComparison::GreaterThan(2.0)),
+            format: Format {
+                bold: Some(true),
+                text_color: Some("red".to_string()),
+                ..Default::default()
+            },
+        });
+        sheet.conditional_formats.set(ConditionalFormat {
+            id: Uuid::new_v4(),
+            selection: A1Selection::test_a1("A1:A2"),
+            rule: ConditionalFormatRule::DataBar(DataBar {
+                color: Rgba::new(0, 0, 255, 255),
+            }),
+            format: Format::default(),
+        });
+
+        let render = sheet.get_render_cells(Rect::test_a1("A1:A2"));
+        assert_eq!(render[0].bold, None);
+        assert_eq!(render[1].bold, Some(true));
+        assert_eq!(render[1].text_color, Some("red".to_string()));
+        assert_eq!(
+            render[0].data_bar,
+            Some(JsRenderDataBar {
+                color: "#0000ff".to_string(),
+                percent: 33
+            })
+        );
+        assert_eq!(
+            render[1].data_bar.as_ref().map(|data_bar| data_bar.percent),
+            Some(100)
+        );
+    }
+
+    #[test]
+    #[parallel]
+    fn render_cells_number_format_code() {
+        let mut sheet = Sheet::test();
+        sheet.set_cell_value(pos![A1], CellValue::Number(1234.into()));
+        sheet.set_cell_value(pos![A2], CellValue::Number((-5).into()));
+        sheet.formats.numeric_format.set_rect(
+            1,
+            1,
+            Some(1),
+            Some(2),
+            Some(NumericFormat {
+                kind: NumericFormatKind::Custom,
+                symbol: Some("#,##0.00;[Red]-0.00".to_string()),
+            }),
+        );
+
+        // format codes are rendered in core
+        let render = sheet.get_render_cells(Rect::test_a1("A1:A2"));
+        assert_eq!(render[0].value, "1,234.00");
+        assert_eq!(render[0].number, None);
+        assert_eq!(render[0].text_color, None);
+        assert_eq!(render[0].align, Some(CellAlign::Right));
+        assert_eq!(render[1].value, "-5.00");
+        assert_eq!(render[1].text_color, Some("#ff0000".to_string()));
+    }
 }
@@ -512,6 +512,8 @@ mod test {
             line_number: None,
             output_type: None,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(Pos { x: 1, y: 2 }, Some(code_run));
 
@@ -554,6 +556,8 @@ mod test {
             line_number: None,
             output_type: None,
             last_modified: Utc::now(),
+            refresh: Default::default(),
+            volatile: false,
         };
         sheet.set_code_run(Pos { x: 1, y: 2 }, Some(code_run));
 
@@ -1,5 +1,6 @@
 use super::Sheet;
 use crate::{
+    controller::host::Host,
     grid::GridBounds,
     renderer_constants::{CELL_SHEET_HEIGHT, CELL_SHEET_WIDTH},
     Pos, Rect,
@@ -8,8 +9,8 @@ use std::collections::HashSet;
 
 impl Sheet {
     /// Sends the modified cell sheets to the render web worker
-    pub fn send_render_cells(&self, positions: &HashSet<Pos>) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_render_cells(&self, host: &dyn Host, positions: &HashSet<Pos>) {
+        if !host.renders() {
             return;
         }
 
@@ -24,23 +25,23 @@ impl Sheet {
         });
 
         // send the modified cells to the render web worker
-        self.send_render_cells_in_hashes(modified);
+        self.send_render_cells_in_hashes(host, modified);
     }
 
     /// Sends the modified cells in hash to the render web worker
-    pub fn send_render_cells_in_hashes(&self, hashes: HashSet<Pos>) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_render_cells_in_hashes(&self, host: &dyn Host, hashes: HashSet<Pos>) {
+        if !host.renders() {
             return;
         }
 
         hashes.into_iter().for_each(|hash| {
-            self.send_render_cells_in_hash(hash);
+            self.send_render_cells_in_hash(host, hash);
         });
     }
 
     /// Sends the modified cells in hash to the render web worker
-    pub fn send_render_cells_in_hash(&self, hash: Pos) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_render_cells_in_hash(&self, host: &dyn Host, hash: Pos) {
+        if !host.renders() {
             return;
         }
 
@@ -52,19 +53,14 @@ impl Sheet {
         );
         let render_cells = self.get_render_cells(rect);
         if let Ok(cells) = serde_json::to_string(&render_cells) {
-            crate::wasm_bindings::js::jsRenderCellSheets(
-                self.id.to_string(),
-                hash.x,
-                hash.y,
-                cells,
-            );
+            host.render_cells(self.id.to_string(), hash.x, hash.y, cells);
         }
-        self.send_validation_warnings_from_hash(hash.x, hash.y, rect);
+        self.send_validation_warnings_from_hash(host, hash.x, hash.y, rect);
     }
 
     /// Sends all render cells to the render web worker
-    pub fn send_all_render_cells(&self) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_all_render_cells(&self, host: &dyn Host) {
+        if !host.renders() {
             return;
         }
 
@@ -87,7 +83,7 @@ impl Sheet {
                         let render_cells = self.get_render_cells(rect);
                         if !render_cells.is_empty() {
                             if let Ok(cells) = serde_json::to_string(&render_cells) {
-                                crate::wasm_bindings::js::jsRenderCellSheets(
+                                host.render_cells(
                                     self.id.to_string(),
                                     quadrant.0,
                                     quadrant.1,
@@ -102,8 +98,8 @@ impl Sheet {
     }
 
     /// Sends render cells to the render web worker for the specified columns.
-    pub fn send_column_render_cells(&self, columns: Vec<i64>) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_column_render_cells(&self, host: &dyn Host, columns: Vec<i64>) {
+        if !host.renders() {
             return;
         }
 
@@ -132,20 +128,15 @@ impl Sheet {
             let render_cells = self.get_render_cells(rect);
             if !render_cells.is_empty() {
                 if let Ok(cells) = serde_json::to_string(&render_cells) {
-                    crate::wasm_bindings::js::jsRenderCellSheets(
-                        self.id.to_string(),
-                        pos.x,
-                        pos.y,
-                        cells,
-                    );
+                    host.render_cells(self.id.to_string(), pos.x, pos.y, cells);
                 }
             }
         });
     }
 
     /// Sends render cells to the render web worker for the specified rows.
-    pub fn send_row_render_cells(&self, rows: Vec<i64>) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_row_render_cells(&self, host: &dyn Host, rows: Vec<i64>) {
+        if !host.renders() {
             return;
         }
 
@@ -173,27 +164,22 @@ impl Sheet {
             let render_cells = self.get_render_cells(rect);
             if !render_cells.is_empty() {
                 if let Ok(cells) = serde_json::to_string(&render_cells) {
-                    crate::wasm_bindings::js::jsRenderCellSheets(
-                        self.id.to_string(),
-                        pos.x,
-                        pos.y,
-                        cells,
-                    );
+                    host.render_cells(self.id.to_string(), pos.x, pos.y, cells);
                 }
             }
         });
     }
 
     /// Sends html output to the client within a sheetRect
-    pub fn send_html_output(&self, positions: &HashSet<Pos>) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_html_output(&self, host: &dyn Host, positions: &HashSet<Pos>) {
+        if !host.renders() {
             return;
         }
 
         positions.iter().for_each(|pos| {
             if let Some(html_output) = self.get_single_html_output(*pos) {
                 if let Ok(html) = serde_json::to_string(&html_output) {
-                    crate::wasm_bindings::js::jsUpdateHtml(html);
+                    host.update_html(html);
                 }
             }
         });
@@ -201,28 +187,28 @@ impl Sheet {
 
     /// Sends all sheet fills to the client, ie, fills for columns, rows, and
     /// the entire sheet.
-    pub fn send_sheet_fills(&self) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn send_sheet_fills(&self, host: &dyn Host) {
+        if !host.renders() {
             return;
         }
 
         let fills = self.get_all_sheet_fills();
         if let Ok(fills) = serde_json::to_string(&fills) {
-            crate::wasm_bindings::js::jsSheetMetaFills(self.id.to_string(), fills);
+            host.sheet_meta_fills(self.id.to_string(), fills);
         }
     }
 
     /// Sends all column-based fills in the sheet.
-    pub fn resend_fills(&self) {
-        if !cfg!(target_family = "wasm") && !cfg!(test) {
+    pub fn resend_fills(&self, host: &dyn Host) {
+        if !host.renders() {
             return;
         }
 
         let fills = self.get_all_render_fills();
         if let Ok(fills) = serde_json::to_string(&fills) {
-            crate::wasm_bindings::js::jsSheetFills(self.id.to_string(), fills);
+            host.sheet_fills(self.id.to_string(), fills);
         }
-        self.send_sheet_fills();
+        self.send_sheet_fills(host);
     }
 }
 
@@ -231,6 +217,7 @@ mod test {
     use super::*;
     use crate::{
         clear_option::ClearOption,
+        controller::host::JsHost,
         grid::{formats::SheetFormatUpdates, Contiguous2D},
         wasm_bindings::js::{clear_js_calls, expect_js_call, hash_test},
         A1Selection, CellValue,
@@ -258,7 +245,7 @@ mod test {
         sheet.recalculate_bounds();
         let mut positions = HashSet::new();
         positions.insert(Pos { x: 1, y: 2 });
-        sheet.send_render_cells(&positions);
+        sheet.send_render_cells(&JsHost, &positions);
         expect_render_cell_sheet(&sheet, 0, 0, true);
     }
 
@@ -275,7 +262,7 @@ mod test {
             vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"],
         );
         sheet.recalculate_bounds();
-        sheet.send_all_render_cells();
+        sheet.send_all_render_cells(&JsHost);
         expect_render_cell_sheet(&sheet, 0, 0, false);
         expect_render_cell_sheet(&sheet, 1, 0, true);
     }
@@ -293,10 +280,13 @@ mod test {
             vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"],
         );
         sheet.recalculate_bounds();
-        sheet.send_column_render_cells(vec![CELL_SHEET_WIDTH as i64 - 1]);
+        sheet.send_column_render_cells(&JsHost, vec![CELL_SHEET_WIDTH as i64 - 1]);
         expect_render_cell_sheet(&sheet, 0, 0, true);
 
-        sheet.send_column_render_cells(vec![CELL_SHEET_WIDTH as i64 - 1, CELL_SHEET_WIDTH as i64]);
+        sheet.send_column_render_cells(
+            &JsHost,
+            vec![CELL_SHEET_WIDTH as i64 - 1, CELL_SHEET_WIDTH as i64],
+        );
         expect_render_cell_sheet(&sheet, 0, 0, false);
         expect_render_cell_sheet(&sheet, 1, 0, true);
     }
@@ -314,10 +304,13 @@ mod test {
             vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"],
         );
         sheet.recalculate_bounds();
-        sheet.send_row_render_cells(vec![CELL_SHEET_HEIGHT as i64 - 1]);
+        sheet.send_row_render_cells(&JsHost, vec![CELL_SHEET_HEIGHT as i64 - 1]);
         expect_render_cell_sheet(&sheet, 0, 0, true);
 
-        sheet.send_row_render_cells(vec![CELL_SHEET_HEIGHT as i64 - 1, CELL_SHEET_HEIGHT as i64]);
+        sheet.send_row_render_cells(
+            &JsHost,
+            vec![CELL_SHEET_HEIGHT as i64 - 1, CELL_SHEET_HEIGHT as i64],
+        );
         expect_render_cell_sheet(&sheet, 0, 0, false);
         expect_render_cell_sheet(&sheet, 0, 1, true);
     }
@@ -334,7 +327,7 @@ mod test {
             ),
             ..Default::default()
         });
-        sheet.send_sheet_fills();
+        sheet.send_sheet_fills(&JsHost);
         let fills = sheet.get_all_sheet_fills();
         expect_js_call(
             "jsSheetMetaFills",
@@ -65,6 +65,8 @@ impl Sheet {
                 output_type: None,
                 spill_error: false,
                 last_modified: chrono::Utc::now(),
+                refresh: Default::default(),
+                volatile: false,
             }),
         );
     }
@@ -132,6 +134,8 @@ impl Sheet {
                 output_type: None,
                 spill_error: false,
                 last_modified: Utc::now(),
+                refresh: Default::default(),
+                volatile: false,
             }),
         );
         self.recalculate_bounds();
@@ -181,6 +185,8 @@ impl Sheet {
                 output_type: None,
                 spill_error: false,
                 last_modified: Utc::now(),
+                refresh: Default::default(),
+                volatile: false,
             }),
         );
     }
@@ -40,4 +40,7 @@ pub struct SheetFormatting {
     pub date_time: SheetFormattingType<String>,
     pub underline: SheetFormattingType<bool>,
     pub strike_through: SheetFormattingType<bool>,
+    pub font_size: SheetFormattingType<i16>,
+    pub font_family: SheetFormattingType<String>,
+    pub text_rotation: SheetFormattingType<i16>,
 }
@@ -25,6 +25,9 @@ impl SheetFormatting {
         self.date_time.insert_column(column, copy_formats);
         self.underline.insert_column(column, copy_formats);
         self.strike_through.insert_column(column, copy_formats);
+        self.font_size.insert_column(column, copy_formats);
+        self.font_family.insert_column(column, copy_formats);
+        self.text_rotation.insert_column(column, copy_formats);
     }
 
     pub fn insert_row(&mut self, row: i64, copy_formats: CopyFormats) {
@@ -42,6 +45,9 @@ impl SheetFormatting {
         self.date_time.insert_row(row, copy_formats);
         self.underline.insert_row(row, copy_formats);
         self.strike_through.insert_row(row, copy_formats);
+        self.font_size.insert_row(row, copy_formats);
+        self.font_family.insert_row(row, copy_formats);
+        self.text_rotation.insert_row(row, copy_formats);
     }
 
     fn remove_column_item<T>(
@@ -71,6 +77,9 @@ impl SheetFormatting {
             date_time: Self::remove_column_item(&mut self.date_time, column),
             underline: Self::remove_column_item(&mut self.underline, column),
             strike_through: Self::remove_column_item(&mut self.strike_through, column),
+            font_size: Self::remove_column_item(&mut self.font_size, column),
+            font_family: Self::remove_column_item(&mut self.font_family, column),
+            text_rotation: Self::remove_column_item(&mut self.text_rotation, column),
         }
     }
 
@@ -98,6 +107,9 @@ impl SheetFormatting {
             date_time: Self::copy_column_item(&self.date_time, column),
             underline: Self::copy_column_item(&self.underline, column),
             strike_through: Self::copy_column_item(&self.strike_through, column),
+            font_size: Self::copy_column_item(&self.font_size, column),
+            font_family: Self::copy_column_item(&self.font_family, column),
+            text_rotation: Self::copy_column_item(&self.text_rotation, column),
         };
 
         if updates.is_default() {
@@ -131,6 +143,9 @@ impl SheetFormatting {
             date_time: Self::remove_row_item(&mut self.date_time, row),
             underline: Self::remove_row_item(&mut self.underline, row),
             strike_through: Self::remove_row_item(&mut self.strike_through, row),
+            font_size: Self::remove_row_item(&mut self.font_size, row),
+            font_family: Self::remove_row_item(&mut self.font_family, row),
+            text_rotation: Self::remove_row_item(&mut self.text_rotation, row),
         }
     }
 
@@ -158,6 +173,9 @@ impl SheetFormatting {
             date_time: Self::copy_row_item(&self.date_time, row),
             underline: Self::copy_row_item(&self.underline, row),
             strike_through: Self::copy_row_item(&self.strike_through, row),
+            font_size: Self::copy_row_item(&self.font_size, row),
+            font_family: Self::copy_row_item(&self.font_family, row),
+            text_rotation: Self::copy_row_item(&self.text_rotation, row),
         };
 
         if updates.is_default() {
@@ -104,6 +104,42 @@ impl SheetOffsets {
         self.row_heights.get_size(y)
     }
 
+    /// Returns whether a column is hidden.
+    pub fn is_column_hidden(&self, x: i64) -> bool {
+        self.column_widths.is_hidden(x)
+    }
+
+    /// Returns whether a row is hidden.
+    pub fn is_row_hidden(&self, y: i64) -> bool {
+        self.row_heights.is_hidden(y)
+    }
+
+    /// Returns the hidden range of columns that contains `x`, if any.
+    pub fn hidden_column_range(&self, x: i64) -> Option<(i64, i64)> {
+        self.column_widths.hidden_range(x)
+    }
+
+    /// Returns the hidden range of rows that contains `y`, if any.
+    pub fn hidden_row_range(&self, y: i64) -> Option<(i64, i64)> {
+        self.row_heights.hidden_range(y)
+    }
+
+    /// Replaces the hidden column ranges. Returns the ranges whose hidden
+    /// state changed.
+    pub fn set_hidden_columns(&mut self, hidden: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        let changed = self.column_widths.set_hidden(hidden);
+        self.calculate_thumbnail();
+        changed
+    }
+
+    /// Replaces the hidden row ranges. Returns the ranges whose hidden state
+    /// changed.
+    pub fn set_hidden_rows(&mut self, hidden: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        let changed = self.row_heights.set_hidden(hidden);
+        self.calculate_thumbnail();
+        changed
+    }
+
     /// gets the column index from an x-coordinate on the screen
     pub fn column_from_x(&mut self, x: f64) -> (i64, f64) {
         self.column_widths.find_offset(x)
@@ -229,11 +265,25 @@ impl SheetOffsets {
         let mut y = 0;
         let mut width = 0.0;
         let mut height = 0.0;
+        // hidden ranges are skipped as a whole since they may extend to the
+        // end of the sheet
         while width < THUMBNAIL_WIDTH {
+            if let Some((_, end)) = self.hidden_column_range(x) {
+                if end == i64::MAX {
+                    break;
+                }
+                x = end + 1;
+            }
             width += self.column_width(x);
             x += 1;
         }
         while height < THUMBNAIL_HEIGHT {
+            if let Some((_, end)) = self.hidden_row_range(y) {
+                if end == i64::MAX {
+                    break;
+                }
+                y = end + 1;
+            }
             height += self.row_height(y);
             y += 1;
         }
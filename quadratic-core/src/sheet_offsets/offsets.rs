@@ -24,6 +24,16 @@ pub struct Offsets {
     default: f64,
     #[serde(with = "crate::util::btreemap_serde")]
     sizes: BTreeMap<i64, f64>,
+
+    /// Ranges of columns/rows that are hidden, as start => end (inclusive).
+    /// These have a size of 0 but keep their stored size so they can be
+    /// unhidden.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        with = "crate::util::btreemap_serde"
+    )]
+    hidden: BTreeMap<i64, i64>,
 }
 impl Offsets {
     /// Constructs an empty `Offsets` structure.
@@ -31,6 +41,7 @@ impl Offsets {
         Offsets {
             default,
             sizes: BTreeMap::new(),
+            hidden: BTreeMap::new(),
         }
     }
 
@@ -39,6 +50,7 @@ impl Offsets {
         Offsets {
             default,
             sizes: iter.into_iter().collect(),
+            hidden: BTreeMap::new(),
         }
     }
 
@@ -62,11 +74,107 @@ impl Offsets {
         }
     }
 
-    /// Returns the width/height of a column/row.
+    /// Returns the width/height of a column/row. Hidden columns/rows have a
+    /// size of 0.
     pub fn get_size(&self, index: i64) -> f64 {
+        if self.is_hidden(index) {
+            0.0
+        } else {
+            self.get_stored_size(index)
+        }
+    }
+
+    /// Returns the width/height of a column/row, ignoring whether it is
+    /// hidden.
+    pub fn get_stored_size(&self, index: i64) -> f64 {
         *self.sizes.get(&index).unwrap_or(&self.default)
     }
 
+    /// Returns whether a column/row is hidden.
+    pub fn is_hidden(&self, index: i64) -> bool {
+        self.hidden_range(index).is_some()
+    }
+
+    /// Replaces the hidden columns/rows with a list of sorted, non-overlapping
+    /// inclusive ranges.
+    ///
+    /// Returns the ranges whose hidden state changed.
+    pub fn set_hidden(&mut self, hidden: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+        let hidden = hidden.into_iter().collect::<BTreeMap<_, _>>();
+
+        // Sweep over the boundaries of both sets of ranges, collecting the
+        // spans where exactly one of them is hidden. (i128 so a range can end
+        // at i64::MAX.)
+        let boundaries = self
+            .hidden
+            .iter()
+            .chain(&hidden)
+            .flat_map(|(&start, &end)| [start as i128, end as i128 + 1])
+            .sorted()
+            .dedup()
+            .collect::<Vec<_>>();
+        let mut changed: Vec<(i64, i64)> = vec![];
+        for (&start, &next) in boundaries.iter().tuple_windows() {
+            let (start, end) = (start as i64, (next - 1) as i64);
+            let was_hidden = Self::ranges_contain(&self.hidden, start);
+            let is_hidden = Self::ranges_contain(&hidden, start);
+            if was_hidden != is_hidden {
+                match changed.last_mut() {
+                    Some(last) if last.1 + 1 == start => last.1 = end,
+                    _ => changed.push((start, end)),
+                }
+            }
+        }
+        self.hidden = hidden;
+        changed
+    }
+
+    /// Returns the hidden range (inclusive) that contains `index`, if any.
+    pub fn hidden_range(&self, index: i64) -> Option<(i64, i64)> {
+        self.hidden
+            .range(..=index)
+            .next_back()
+            .filter(|(_, &end)| end >= index)
+            .map(|(&start, &end)| (start, end))
+    }
+
+    /// Returns the first column/row at or after `index` that is not hidden
+    /// (or i64::MAX if all of them are hidden).
+    fn next_visible(&self, mut index: i64) -> i64 {
+        while let Some((_, end)) = self.hidden_range(index) {
+            if end == i64::MAX {
+                return end;
+            }
+            index = end + 1;
+        }
+        index
+    }
+
+    fn ranges_contain(ranges: &BTreeMap<i64, i64>, index: i64) -> bool {
+        ranges
+            .range(..=index)
+            .next_back()
+            .is_some_and(|(_, &end)| end >= index)
+    }
+
+    /// Returns the sum of the stored sizes of hidden columns/rows before
+    /// `index`.
+    fn hidden_size_before(&self, index: i64) -> f64 {
+        self.hidden
+            .range(..index)
+            .filter(|(_, &end)| end >= 1)
+            .map(|(&start, &end)| {
+                let (start, end) = (start.max(1), end.min(index - 1));
+                self.default * (end - start + 1) as f64
+                    + self
+                        .sizes
+                        .range(start..=end)
+                        .map(|(_, v)| v - self.default)
+                        .sum::<f64>()
+            })
+            .sum()
+    }
+
     /// Sets the width/height of a column/row.
     pub fn set_size(&mut self, index: i64, value: f64) -> f64 {
         if value == self.default {
@@ -89,7 +197,8 @@ impl Offsets {
                 .sizes
                 .range(1..index_range.start)
                 .map(|(_, v)| v - self.default)
-                .sum::<f64>();
+                .sum::<f64>()
+            - self.hidden_size_before(index_range.start);
         index_range.map(move |index| {
             let ret = current_position;
             current_position += self.get_size(index);
@@ -101,11 +210,12 @@ impl Offsets {
     /// up the search.
     pub fn find_offset(&self, pixel: f64) -> (i64, f64) {
         let mut current_sum = 0.0;
-        let mut current_index = 1;
+        let mut current_index = self.next_visible(1);
         let mut current_size = self.get_size(current_index);
-        while current_sum + current_size <= pixel {
+        // stop if the rest of the columns/rows are hidden
+        while current_sum + current_size <= pixel && current_index < i64::MAX {
             current_sum += current_size;
-            current_index += 1;
+            current_index = self.next_visible(current_index + 1);
             current_size = self.get_size(current_index);
         }
 
@@ -324,4 +434,30 @@ mod tests {
         assert_eq!(offsets.get_size(4), 10.0);
         assert_eq!(offsets.get_size(5), 50.0);
     }
+
+    #[test]
+    fn test_hidden() {
+        let mut offsets = Offsets::new(10.0);
+        offsets.set_size(2, 20.0);
+
+        let changed = offsets.set_hidden(vec![(2, 3)]);
+        assert_eq!(changed, vec![(2, 3)]);
+        assert!(offsets.is_hidden(2));
+        assert_eq!(offsets.get_size(2), 0.0);
+        assert_eq!(offsets.get_stored_size(2), 20.0);
+        assert_eq!(
+            offsets.iter_offsets(1..6).collect::<Vec<_>>(),
+            vec![0.0, 10.0, 10.0, 10.0, 20.0]
+        );
+        assert_eq!(offsets.iter_offsets(5..6).collect::<Vec<_>>(), vec![20.0]);
+        assert_eq!(offsets.find_offset(15.0), (4, 10.0));
+
+        offsets.set_hidden(vec![(2, 3), (5, i64::MAX)]);
+        assert!(offsets.is_hidden(1_000_000));
+        assert_eq!(offsets.find_offset(100.0), (i64::MAX, 20.0));
+
+        let changed = offsets.set_hidden(vec![(3, 3)]);
+        assert_eq!(changed, vec![(2, 2), (5, i64::MAX)]);
+        assert_eq!(offsets.get_size(2), 20.0);
+    }
 }
@@ -7,6 +7,7 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use smallvec::{smallvec, SmallVec};
 
+use super::arrow::{arrow_ipc_to_cell_values, ARROW_HEADERS_METADATA};
 use super::{ArraySize, Axis, CellValue, Spanned, Value};
 use crate::{
     controller::operations::operation::Operation, grid::Sheet, CodeResult, Pos, RunError,
@@ -299,11 +300,18 @@ impl Array {
     pub fn common_len<'a>(
         axis: Axis,
         arrays: impl IntoIterator<Item = Spanned<&'a Array>>,
+    ) -> CodeResult<NonZeroU32> {
+        Self::common_len_of_sizes(axis, arrays.into_iter().map(|a| a.map(|a| a.size())))
+    }
+    /// Returns the unique length that fits all arrays of `sizes` along `axis`.
+    pub fn common_len_of_sizes(
+        axis: Axis,
+        sizes: impl IntoIterator<Item = Spanned<ArraySize>>,
     ) -> CodeResult<NonZeroU32> {
         let mut common_len = 1;
 
-        for array in arrays {
-            let new_array_len = array.inner.size()[axis].get();
+        for array in sizes {
+            let new_array_len = array.inner[axis].get();
             match (common_len, new_array_len) {
                 (a, b) if a == b => continue,
                 (_, 1) => continue,
@@ -374,6 +382,37 @@ impl Array {
 
         (Some(Array { size, values }), ops)
     }
+
+    /// Reads typed output of a code cell from an Arrow IPC stream. The column
+    /// names are the first row when the stream's `headers` metadata is "true".
+    /// Returns `None` for an empty table.
+    pub fn from_arrow_ipc(bytes: &[u8]) -> Result<Option<Array>> {
+        let (columns, metadata) = arrow_ipc_to_cell_values(bytes)?;
+        let headers = metadata
+            .get(ARROW_HEADERS_METADATA)
+            .is_some_and(|headers| headers == "true");
+        let height = columns.first().map_or(0, |(_, values)| values.len()) + usize::from(headers);
+        let Some(size) = ArraySize::new(columns.len() as u32, height as u32) else {
+            return Ok(None);
+        };
+
+        let mut columns = columns
+            .into_iter()
+            .map(|(name, values)| {
+                headers
+                    .then_some(CellValue::Text(name))
+                    .into_iter()
+                    .chain(values)
+            })
+            .collect::<Vec<_>>();
+        let mut values = SmallVec::with_capacity(size.len());
+        for _ in 0..height {
+            for column in &mut columns {
+                values.push(column.next().unwrap_or(CellValue::Blank));
+            }
+        }
+        Ok(Some(Array { size, values }))
+    }
 }
 
 impl Spanned<Array> {
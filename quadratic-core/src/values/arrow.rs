@@ -1,18 +1,33 @@
+use std::collections::HashMap;
+use std::io::Cursor;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use arrow_array::{
     cast::AsArray,
-    types::{Date32Type, Date64Type},
-    Array, ArrayRef,
+    make_array,
+    timezone::Tz,
+    types::{
+        ArrowTimestampType, Date32Type, Date64Type, Decimal128Type, DurationMicrosecondType,
+        DurationMillisecondType, DurationNanosecondType, DurationSecondType, Float16Type,
+        Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, IntervalDayTimeType,
+        IntervalMonthDayNanoType, IntervalYearMonthType, Time32MillisecondType, Time32SecondType,
+        Time64MicrosecondType, Time64NanosecondType, TimestampMicrosecondType,
+        TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType, UInt16Type,
+        UInt32Type, UInt64Type, UInt8Type,
+    },
+    Array, ArrayRef, BooleanArray, Date32Array, Decimal128Array, IntervalMonthDayNanoArray,
+    NullArray, PrimitiveArray, RecordBatch, StringArray, Time64NanosecondArray,
+    TimestampMicrosecondArray,
 };
-use arrow_buffer::ArrowNativeType;
+use arrow_buffer::{ArrowNativeType, Buffer};
 use arrow_data::ArrayData;
-use arrow_schema::{DataType, TimeUnit};
-use bigdecimal::BigDecimal;
-use chrono::{NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use arrow_ipc::{reader::StreamReader, writer::StreamWriter};
+use arrow_schema::{DataType, Field, IntervalUnit, Schema, TimeUnit, UnionFields, UnionMode};
+use bigdecimal::{num_bigint::BigInt, BigDecimal, RoundingMode, ToPrimitive};
+use chrono::{NaiveDate, NaiveTime, TimeDelta, TimeZone, Timelike, Utc};
 
-use crate::{cell_values::CellValues, CellValue};
+use crate::{cell_values::CellValues, CellValue, Duration, RunError, RunErrorMsg};
 
 use super::time::map_local_result;
 
@@ -247,3 +262,600 @@ fn arrow_timestamp_to_cell_value(
 
     Ok(values)
 }
+
+// Typed transfer of cell values to and from code cells.
+//
+// Each column of cells is one Arrow column. A column whose values all have the
+// same type uses the matching Arrow type, with nulls for blank cells.
+// Otherwise it's a dense union with one child per type, named after
+// `CellValue::type_name`. Errors, HTML and images are always in a union so
+// they can be told apart from text; errors are their `RunError` as JSON.
+// Numbers are Decimal128 so they keep their digits.
+
+/// Schema metadata key that is "true" when the column names are the first
+/// row of cells.
+pub const ARROW_HEADERS_METADATA: &str = "headers";
+
+/// Columns of cells with their names.
+pub type ArrowColumns = Vec<(String, Vec<CellValue>)>;
+
+/// Digits in a Decimal128.
+const DECIMAL_PRECISION: u8 = 38;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Types of cells in a typed transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ArrowKind {
+    Blank,
+    Number,
+    Text,
+    Logical,
+    Date,
+    Time,
+    DateTime,
+    Duration,
+    Error,
+    Html,
+    Image,
+}
+
+impl ArrowKind {
+    fn of(value: &CellValue) -> Self {
+        match value {
+            CellValue::Blank | CellValue::Code(_) => ArrowKind::Blank,
+            CellValue::Number(_) => ArrowKind::Number,
+            CellValue::Text(_) => ArrowKind::Text,
+            CellValue::Logical(_) => ArrowKind::Logical,
+            CellValue::Date(_) => ArrowKind::Date,
+            CellValue::Time(_) => ArrowKind::Time,
+            CellValue::DateTime(_) | CellValue::Instant(_) => ArrowKind::DateTime,
+            CellValue::Duration(_) => ArrowKind::Duration,
+            CellValue::Error(_) => ArrowKind::Error,
+            CellValue::Html(_) => ArrowKind::Html,
+            CellValue::Image(_) => ArrowKind::Image,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ArrowKind::Blank => "blank",
+            ArrowKind::Number => "number",
+            ArrowKind::Text => "text",
+            ArrowKind::Logical => "logical",
+            ArrowKind::Date => "date",
+            ArrowKind::Time => "time",
+            ArrowKind::DateTime => "date time",
+            ArrowKind::Duration => "duration",
+            ArrowKind::Error => "error",
+            ArrowKind::Html => "html",
+            ArrowKind::Image => "image",
+        }
+    }
+
+    /// Whether a column of this type can be told apart from other types
+    /// without a union.
+    fn is_plain(self) -> bool {
+        !matches!(self, ArrowKind::Error | ArrowKind::Html | ArrowKind::Image)
+    }
+
+    /// Returns an array of the values of this type. Other values are null.
+    fn array<'a>(self, values: impl Iterator<Item = &'a CellValue>) -> Result<ArrayRef> {
+        Ok(match self {
+            ArrowKind::Blank => Arc::new(NullArray::new(values.count())),
+            ArrowKind::Number => decimal_array(values)?,
+            ArrowKind::Text | ArrowKind::Error | ArrowKind::Html | ArrowKind::Image => {
+                Arc::new(StringArray::from_iter(values.map(|v| match v {
+                    CellValue::Text(s) | CellValue::Html(s) | CellValue::Image(s) => {
+                        Some(s.clone())
+                    }
+                    CellValue::Error(e) => serde_json::to_string(e).ok(),
+                    _ => None,
+                })))
+            }
+            ArrowKind::Logical => Arc::new(BooleanArray::from_iter(values.map(|v| match v {
+                CellValue::Logical(b) => Some(*b),
+                _ => None,
+            }))),
+            ArrowKind::Date => Arc::new(Date32Array::from_iter(values.map(|v| match v {
+                CellValue::Date(d) => Some(Date32Type::from_naive_date(*d)),
+                _ => None,
+            }))),
+            ArrowKind::Time => {
+                Arc::new(Time64NanosecondArray::from_iter(values.map(|v| match v {
+                    CellValue::Time(t) => Some(
+                        t.num_seconds_from_midnight() as i64 * 1_000_000_000
+                            + t.nanosecond() as i64,
+                    ),
+                    _ => None,
+                })))
+            }
+            ArrowKind::DateTime => Arc::new(TimestampMicrosecondArray::from_iter(values.map(
+                |v| match v {
+                    CellValue::DateTime(dt) => Some(dt.and_utc().timestamp_micros()),
+                    CellValue::Instant(i) => Some((i.seconds * 1e6).round() as i64),
+                    _ => None,
+                },
+            ))),
+            ArrowKind::Duration => Arc::new(IntervalMonthDayNanoArray::from_iter(values.map(
+                |v| match v {
+                    CellValue::Duration(d) => {
+                        // whole days keep long durations in range of the
+                        // nanoseconds
+                        let days = (d.seconds / SECONDS_PER_DAY).trunc();
+                        let nanoseconds = ((d.seconds - days * SECONDS_PER_DAY) * 1e9).round();
+                        Some(IntervalMonthDayNanoType::make_value(
+                            d.months,
+                            days as i32,
+                            nanoseconds as i64,
+                        ))
+                    }
+                    _ => None,
+                },
+            ))),
+        })
+    }
+}
+
+/// Returns a Decimal128 array of the numbers. Its scale is the most decimal
+/// places of any number that still leaves room for the largest number's
+/// integer digits, so only digits that don't fit are rounded.
+fn decimal_array<'a>(values: impl Iterator<Item = &'a CellValue>) -> Result<ArrayRef> {
+    let numbers = values
+        .map(|v| match v {
+            CellValue::Number(n) => Some(n.normalized()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let (mut integer_digits, mut scale) = (1, 0);
+    for n in numbers.iter().flatten() {
+        let (_, exponent) = n.as_bigint_and_exponent();
+        integer_digits = integer_digits.max(n.digits() as i64 - exponent);
+        scale = scale.max(exponent);
+    }
+    let scale = scale
+        .min(DECIMAL_PRECISION as i64 - integer_digits)
+        .max(i8::MIN as i64);
+    let array = Decimal128Array::from_iter(numbers.into_iter().map(|n| {
+        n.and_then(|n| {
+            n.with_scale_round(scale, RoundingMode::HalfEven)
+                .as_bigint_and_exponent()
+                .0
+                .to_i128()
+        })
+    }));
+    Ok(Arc::new(array.with_precision_and_scale(
+        DECIMAL_PRECISION,
+        scale as i8,
+    )?))
+}
+
+/// Converts a column of cells to an Arrow array.
+pub fn cell_values_to_arrow(values: &[CellValue]) -> Result<ArrayRef> {
+    let kinds = values.iter().map(ArrowKind::of).collect::<Vec<_>>();
+    let mut present = kinds.clone();
+    present.sort();
+    present.dedup();
+    match present.as_slice() {
+        [] => return ArrowKind::Blank.array(values.iter()),
+        [kind] | [ArrowKind::Blank, kind] if kind.is_plain() => return kind.array(values.iter()),
+        _ => (),
+    }
+
+    let mut type_ids = Vec::with_capacity(values.len());
+    let mut offsets = Vec::with_capacity(values.len());
+    let mut lengths = vec![0i32; present.len()];
+    for kind in &kinds {
+        let type_id = present.iter().position(|k| k == kind).unwrap_or_default();
+        type_ids.push(type_id as i8);
+        offsets.push(lengths[type_id]);
+        lengths[type_id] += 1;
+    }
+    let children = present
+        .iter()
+        .map(|&kind| kind.array(values.iter().filter(|v| ArrowKind::of(v) == kind)))
+        .collect::<Result<Vec<_>>>()?;
+    let fields = UnionFields::new(
+        0..present.len() as i8,
+        present
+            .iter()
+            .zip(&children)
+            .map(|(kind, child)| Field::new(kind.name(), child.data_type().clone(), true)),
+    );
+    let data = ArrayData::builder(DataType::Union(fields, UnionMode::Dense))
+        .len(values.len())
+        .add_buffer(Buffer::from_vec(type_ids))
+        .add_buffer(Buffer::from_vec(offsets))
+        .child_data(children.iter().map(|child| child.to_data()).collect())
+        .build()?;
+    Ok(make_array(data))
+}
+
+/// Converts each value of a primitive array with `f`. Nulls are blank.
+fn primitive_cell_values<T: arrow_array::ArrowPrimitiveType>(
+    array: &dyn Array,
+    f: impl Fn(T::Native) -> CellValue,
+) -> Vec<CellValue> {
+    array
+        .as_primitive::<T>()
+        .iter()
+        .map(|v| v.map_or(CellValue::Blank, &f))
+        .collect()
+}
+
+/// Converts each value of a date or time array with `f`. Nulls are blank.
+fn temporal_cell_values<T: arrow_array::ArrowPrimitiveType>(
+    array: &dyn Array,
+    f: impl Fn(&PrimitiveArray<T>, usize) -> Option<CellValue>,
+) -> Vec<CellValue> {
+    let array = array.as_primitive::<T>();
+    (0..array.len())
+        .map(|i| {
+            if array.is_null(i) {
+                return CellValue::Blank;
+            }
+            f(array, i).unwrap_or(CellValue::Blank)
+        })
+        .collect()
+}
+
+/// Converts timestamps to date times. Cells don't have a time zone, so a
+/// time stamp with one is converted to UTC, which keeps the moment it refers
+/// to; one without is the date time as written.
+fn timestamp_cell_values<T: ArrowTimestampType>(
+    array: &dyn Array,
+    tz: Option<&str>,
+) -> Result<Vec<CellValue>> {
+    let tz = tz.map(|tz| tz.parse::<Tz>()).transpose()?;
+    Ok(temporal_cell_values::<T>(array, |array, i| match tz {
+        Some(tz) => array
+            .value_as_datetime_with_tz(i, tz)
+            .map(|dt| CellValue::DateTime(dt.naive_utc())),
+        None => array.value_as_datetime(i).map(CellValue::DateTime),
+    }))
+}
+
+fn float_cell_value(value: impl ToString) -> CellValue {
+    // the shortest representation that round-trips, so no precision is lost
+    CellValue::unpack_str_float(&value.to_string(), CellValue::Blank)
+}
+
+fn duration_cell_value(months: i32, seconds: f64) -> CellValue {
+    CellValue::Duration(Duration { months, seconds })
+}
+
+/// Converts an Arrow array to cells. Nulls are blank; time stamps with a time
+/// zone are in UTC.
+pub fn arrow_to_cell_values(array: &dyn Array) -> Result<Vec<CellValue>> {
+    let number = |v: i64| CellValue::Number(v.into());
+    Ok(match array.data_type() {
+        DataType::Null => vec![CellValue::Blank; array.len()],
+        DataType::Boolean => array
+            .as_boolean()
+            .iter()
+            .map(|v| v.map_or(CellValue::Blank, CellValue::Logical))
+            .collect(),
+        DataType::Int8 => primitive_cell_values::<Int8Type>(array, |v| number(v.into())),
+        DataType::Int16 => primitive_cell_values::<Int16Type>(array, |v| number(v.into())),
+        DataType::Int32 => primitive_cell_values::<Int32Type>(array, |v| number(v.into())),
+        DataType::Int64 => primitive_cell_values::<Int64Type>(array, number),
+        DataType::UInt8 => primitive_cell_values::<UInt8Type>(array, |v| number(v.into())),
+        DataType::UInt16 => primitive_cell_values::<UInt16Type>(array, |v| number(v.into())),
+        DataType::UInt32 => primitive_cell_values::<UInt32Type>(array, |v| number(v.into())),
+        DataType::UInt64 => {
+            primitive_cell_values::<UInt64Type>(array, |v| CellValue::Number(v.into()))
+        }
+        DataType::Float16 => primitive_cell_values::<Float16Type>(array, float_cell_value),
+        DataType::Float32 => primitive_cell_values::<Float32Type>(array, float_cell_value),
+        DataType::Float64 => primitive_cell_values::<Float64Type>(array, float_cell_value),
+        DataType::Decimal128(_, scale) => primitive_cell_values::<Decimal128Type>(array, |v| {
+            CellValue::Number(BigDecimal::new(BigInt::from(v), *scale as i64))
+        }),
+        DataType::Utf8 => array
+            .as_string::<i32>()
+            .iter()
+            .map(|v| v.map_or(CellValue::Blank, |s| CellValue::Text(s.to_string())))
+            .collect(),
+        DataType::LargeUtf8 => array
+            .as_string::<i64>()
+            .iter()
+            .map(|v| v.map_or(CellValue::Blank, |s| CellValue::Text(s.to_string())))
+            .collect(),
+        DataType::Date32 => temporal_cell_values::<Date32Type>(array, |array, i| {
+            array.value_as_date(i).map(CellValue::Date)
+        }),
+        DataType::Date64 => temporal_cell_values::<Date64Type>(array, |array, i| {
+            array.value_as_date(i).map(CellValue::Date)
+        }),
+        DataType::Time32(TimeUnit::Second) => {
+            temporal_cell_values::<Time32SecondType>(array, |array, i| {
+                array.value_as_time(i).map(CellValue::Time)
+            })
+        }
+        DataType::Time32(_) => temporal_cell_values::<Time32MillisecondType>(array, |array, i| {
+            array.value_as_time(i).map(CellValue::Time)
+        }),
+        DataType::Time64(TimeUnit::Microsecond) => {
+            temporal_cell_values::<Time64MicrosecondType>(array, |array, i| {
+                array.value_as_time(i).map(CellValue::Time)
+            })
+        }
+        DataType::Time64(_) => temporal_cell_values::<Time64NanosecondType>(array, |array, i| {
+            array.value_as_time(i).map(CellValue::Time)
+        }),
+        DataType::Timestamp(unit, tz) => match unit {
+            TimeUnit::Second => timestamp_cell_values::<TimestampSecondType>(array, tz.as_deref())?,
+            TimeUnit::Millisecond => {
+                timestamp_cell_values::<TimestampMillisecondType>(array, tz.as_deref())?
+            }
+            TimeUnit::Microsecond => {
+                timestamp_cell_values::<TimestampMicrosecondType>(array, tz.as_deref())?
+            }
+            TimeUnit::Nanosecond => {
+                timestamp_cell_values::<TimestampNanosecondType>(array, tz.as_deref())?
+            }
+        },
+        DataType::Duration(unit) => match unit {
+            TimeUnit::Second => primitive_cell_values::<DurationSecondType>(array, |v| {
+                duration_cell_value(0, v as f64)
+            }),
+            TimeUnit::Millisecond => primitive_cell_values::<DurationMillisecondType>(array, |v| {
+                duration_cell_value(0, v as f64 / 1e3)
+            }),
+            TimeUnit::Microsecond => primitive_cell_values::<DurationMicrosecondType>(array, |v| {
+                duration_cell_value(0, v as f64 / 1e6)
+            }),
+            TimeUnit::Nanosecond => primitive_cell_values::<DurationNanosecondType>(array, |v| {
+                duration_cell_value(0, v as f64 / 1e9)
+            }),
+        },
+        DataType::Interval(IntervalUnit::YearMonth) => {
+            primitive_cell_values::<IntervalYearMonthType>(array, |v| duration_cell_value(v, 0.0))
+        }
+        DataType::Interval(IntervalUnit::DayTime) => {
+            primitive_cell_values::<IntervalDayTimeType>(array, |v| {
+                let (days, milliseconds) = IntervalDayTimeType::to_parts(v);
+                duration_cell_value(0, days as f64 * SECONDS_PER_DAY + milliseconds as f64 / 1e3)
+            })
+        }
+        DataType::Interval(IntervalUnit::MonthDayNano) => {
+            primitive_cell_values::<IntervalMonthDayNanoType>(array, |v| {
+                let (months, days, nanoseconds) = IntervalMonthDayNanoType::to_parts(v);
+                duration_cell_value(
+                    months,
+                    days as f64 * SECONDS_PER_DAY + nanoseconds as f64 / 1e9,
+                )
+            })
+        }
+        DataType::Union(fields, _) => {
+            let union = array.as_union();
+            let mut children = HashMap::new();
+            for (type_id, field) in fields.iter() {
+                let values = arrow_to_cell_values(union.child(type_id).as_ref())?;
+                let values = values
+                    .into_iter()
+                    .map(|value| match (field.name().as_str(), value) {
+                        ("error", CellValue::Text(msg)) => CellValue::Error(Box::new(
+                            serde_json::from_str::<RunError>(&msg).unwrap_or_else(|_| {
+                                RunError::from(RunErrorMsg::CodeRunError(msg.into()))
+                            }),
+                        )),
+                        ("html", CellValue::Text(s)) => CellValue::Html(s),
+                        ("image", CellValue::Text(s)) => CellValue::Image(s),
+                        (_, value) => value,
+                    });
+                children.insert(type_id, values.collect::<Vec<_>>());
+            }
+            (0..union.len())
+                .map(|i| {
+                    children
+                        .get(&union.type_id(i))
+                        .and_then(|values| values.get(union.value_offset(i)))
+                        .cloned()
+                        .unwrap_or(CellValue::Blank)
+                })
+                .collect()
+        }
+        DataType::Dictionary(_, _) => {
+            let dictionary = array.as_any_dictionary();
+            let values = arrow_to_cell_values(dictionary.values().as_ref())?;
+            dictionary
+                .normalized_keys()
+                .into_iter()
+                .enumerate()
+                .map(|(i, key)| {
+                    if array.is_null(i) {
+                        return CellValue::Blank;
+                    }
+                    values.get(key).cloned().unwrap_or(CellValue::Blank)
+                })
+                .collect()
+        }
+        data_type => bail!("Unsupported Arrow type: {data_type}"),
+    })
+}
+
+/// Writes columns of cells as an Arrow IPC stream.
+pub fn cell_values_to_arrow_ipc(
+    columns: &[(String, Vec<CellValue>)],
+    metadata: HashMap<String, String>,
+) -> Result<Vec<u8>> {
+    let mut fields = vec![];
+    let mut arrays = vec![];
+    for (name, values) in columns {
+        let array = cell_values_to_arrow(values)?;
+        fields.push(Field::new(name, array.data_type().clone(), true));
+        arrays.push(array);
+    }
+    let schema = Arc::new(Schema::new_with_metadata(fields, metadata));
+    let batch = if arrays.is_empty() {
+        RecordBatch::new_empty(schema.clone())
+    } else {
+        RecordBatch::try_new(schema.clone(), arrays)?
+    };
+
+    let mut bytes = vec![];
+    let mut writer = StreamWriter::try_new(&mut bytes, &schema)?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    drop(writer);
+    Ok(bytes)
+}
+
+/// Reads an Arrow IPC stream as columns of cells. Also returns the schema's
+/// metadata.
+pub fn arrow_ipc_to_cell_values(bytes: &[u8]) -> Result<(ArrowColumns, HashMap<String, String>)> {
+    let reader = StreamReader::try_new(Cursor::new(bytes), None)?;
+    let schema = reader.schema();
+    let mut columns = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().to_string(), vec![]))
+        .collect::<ArrowColumns>();
+    for batch in reader {
+        for ((_, values), array) in columns.iter_mut().zip(batch?.columns()) {
+            values.extend(arrow_to_cell_values(array.as_ref())?);
+        }
+    }
+    Ok((columns, schema.metadata().clone()))
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use std::str::FromStr;
+
+    use arrow_array::TimestampSecondArray;
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    #[test]
+    fn test_arrow_ipc_round_trip() {
+        let date_time = NaiveDateTime::from_str("2024-03-10T08:30:15.25").unwrap();
+        let mixed = vec![
+            CellValue::Text("header".into()),
+            CellValue::Number(BigDecimal::from_str("0.30000000000000004").unwrap()),
+            CellValue::Logical(false),
+            CellValue::Date(date_time.date()),
+            CellValue::Time(date_time.time()),
+            CellValue::DateTime(date_time),
+            CellValue::Duration(Duration {
+                months: 14,
+                seconds: 90061.5,
+            }),
+            CellValue::Error(Box::new(RunError::from(RunErrorMsg::CodeRunError(
+                "oops".into(),
+            )))),
+            CellValue::Html("<div></div>".into()),
+            CellValue::Blank,
+        ];
+        let mut numbers = vec![CellValue::from(1.5), CellValue::Blank, CellValue::from(-2)];
+        numbers.resize(mixed.len(), CellValue::Blank);
+        let columns = vec![
+            ("mixed".to_string(), mixed.clone()),
+            ("numbers".to_string(), numbers.clone()),
+            ("empty".to_string(), vec![CellValue::Blank; 10]),
+        ];
+        let metadata = HashMap::from([("x".to_string(), "1".to_string())]);
+
+        let bytes = cell_values_to_arrow_ipc(&columns, metadata.clone()).unwrap();
+        let (read, read_metadata) = arrow_ipc_to_cell_values(&bytes).unwrap();
+        assert_eq!(read_metadata, metadata);
+        assert_eq!(read[0], ("mixed".to_string(), mixed));
+        assert_eq!(read[1], ("numbers".to_string(), numbers));
+        assert_eq!(read[2].1, vec![CellValue::Blank; 10]);
+    }
+
+    #[test]
+    fn test_cell_values_to_arrow_types() {
+        // columns of a single type are typed
+        let array = cell_values_to_arrow(&[CellValue::from(1), CellValue::Blank]).unwrap();
+        assert_eq!(array.data_type(), &DataType::Decimal128(38, 0));
+        assert_eq!(array.null_count(), 1);
+
+        let array = cell_values_to_arrow(&[CellValue::Logical(true)]).unwrap();
+        assert_eq!(array.data_type(), &DataType::Boolean);
+
+        // errors are never plain text
+        let array = cell_values_to_arrow(&[CellValue::Error(Box::new(RunError::from(
+            RunErrorMsg::DivideByZero,
+        )))])
+        .unwrap();
+        assert!(matches!(array.data_type(), DataType::Union(..)));
+
+        // mixed columns are unions
+        let array =
+            cell_values_to_arrow(&[CellValue::Text("a".into()), CellValue::from(1)]).unwrap();
+        assert!(matches!(array.data_type(), DataType::Union(..)));
+    }
+
+    #[test]
+    fn test_arrow_to_cell_values_time_zone() {
+        let array = TimestampSecondArray::from(vec![Some(18_000), None]).with_timezone("+05:00");
+        let values = arrow_to_cell_values(&array).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                CellValue::DateTime(NaiveDateTime::from_str("1970-01-01T05:00:00").unwrap()),
+                CellValue::Blank
+            ]
+        );
+
+        // the same moment in another zone is the same date time
+        let array = TimestampSecondArray::from(vec![Some(18_000)]).with_timezone("-08:00");
+        assert_eq!(arrow_to_cell_values(&array).unwrap(), values[..1]);
+    }
+
+    #[test]
+    fn test_arrow_numbers() {
+        let numbers = [
+            "123456789012345678901234567890.5",
+            "0.30000000000000004",
+            "-0.000001",
+            "1e20",
+        ]
+        .map(|n| CellValue::Number(BigDecimal::from_str(n).unwrap()))
+        .to_vec();
+        let array = cell_values_to_arrow(&numbers).unwrap();
+        assert_eq!(array.data_type(), &DataType::Decimal128(38, 8));
+        let values = arrow_to_cell_values(&array).unwrap();
+        assert_eq!(values[0], numbers[0]);
+        assert_eq!(values[2], numbers[2]);
+        assert_eq!(values[3], numbers[3]);
+
+        // decimal places that don't fit are rounded
+        assert_eq!(
+            values[1],
+            CellValue::Number(BigDecimal::from_str("0.3").unwrap())
+        );
+
+        // so are integer digits that don't fit
+        let large = ["1e40", "12345678901234567890123456789012345678901"]
+            .map(|n| CellValue::Number(BigDecimal::from_str(n).unwrap()));
+        let array = cell_values_to_arrow(&large).unwrap();
+        assert_eq!(array.data_type(), &DataType::Decimal128(38, -3));
+        assert_eq!(
+            arrow_to_cell_values(&array).unwrap(),
+            ["1e40", "12345678901234567890123456789012345679000"]
+                .map(|n| CellValue::Number(BigDecimal::from_str(n).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_arrow_errors_and_durations() {
+        let values = vec![
+            CellValue::Error(Box::new(RunError::from(RunErrorMsg::DivideByZero))),
+            CellValue::Duration(Duration {
+                months: 0,
+                seconds: 0.3,
+            }),
+            CellValue::Duration(Duration {
+                months: -1,
+                seconds: -1e12 - 0.25,
+            }),
+        ];
+        let array = cell_values_to_arrow(&values).unwrap();
+        assert_eq!(arrow_to_cell_values(&array).unwrap(), values);
+    }
+}
@@ -15,7 +15,7 @@ use crate::{
 };
 
 // todo: fill this out
-const CURRENCY_SYMBOLS: &str = "$€£¥";
+const CURRENCY_SYMBOLS: &str = "$€£¥₹₩₽₺₪₫₱₦฿₴";
 const PERCENTAGE_SYMBOL: char = '%';
 
 /// Non-array value in the formula language.
@@ -185,6 +185,9 @@ impl CellValue {
         match self {
             CellValue::Number(n) => {
                 let numeric_format = numeric_format.unwrap_or_default();
+                if let Some(number_format) = numeric_format.number_format() {
+                    return number_format.format_number(n).text;
+                }
                 let use_commas = numeric_commas.is_some_and(|c| c)
                     || (numeric_commas.is_none()
                         && numeric_format.kind == NumericFormatKind::Currency);
@@ -250,6 +253,9 @@ impl CellValue {
                     }
                     NumericFormatKind::Number => number,
                     NumericFormatKind::Exponential => number,
+
+                    // only reached if the format code is invalid
+                    NumericFormatKind::Custom => number,
                 }
             }
             _ => String::new(),
@@ -1045,6 +1051,34 @@ mod test {
         );
     }
 
+    #[test]
+    #[parallel]
+    fn to_number_display_format_code() {
+        let cv = CellValue::Number(BigDecimal::from_str("-1234.567").unwrap());
+        let custom = |code: &str| {
+            Some(NumericFormat {
+                kind: NumericFormatKind::Custom,
+                symbol: Some(code.to_string()),
+            })
+        };
+        assert_eq!(
+            cv.to_number_display(custom("#,##0.00;(#,##0.00)"), None, None),
+            "(1,234.57)"
+        );
+
+        // decimals and commas are part of the format code
+        assert_eq!(
+            cv.to_number_display(custom("0"), Some(3), Some(true)),
+            "-1235"
+        );
+
+        // invalid format codes fall back to the number
+        assert_eq!(
+            cv.to_number_display(custom("\"abc"), None, None),
+            "-1234.567"
+        );
+    }
+
     #[test]
     #[parallel]
     fn to_number_display_scientific() {
@@ -1127,6 +1161,12 @@ mod test {
 
         let value = String::from("$123.123abc");
         assert_eq!(CellValue::unpack_currency(&value), None);
+
+        let value = String::from("₹1,234");
+        assert_eq!(
+            CellValue::unpack_currency(&value),
+            Some((String::from("₹"), BigDecimal::from(1234)))
+        );
     }
 
     #[test]
@@ -0,0 +1,921 @@
+//! Excel-compatible number format codes, eg, `#,##0.00;[Red](#,##0.00)`.
+//!
+//! A format code has up to four sections separated by `;`. The sections are
+//! used for positive numbers, negative numbers, zero, and text. With one
+//! section, it is used for all numbers. With two sections, the first is used
+//! for positive numbers and zero. Sections may instead use conditions, eg,
+//! `[>=100]0;0.00`.
+//!
+//! Supported codes:
+//! - digit placeholders: `0` (shows zeros), `#` (hides zeros), and `?` (pads
+//!   with spaces)
+//! - `.` decimal point, `%` percent, and `E+`/`E-` scientific notation
+//! - `,` between digit placeholders groups thousands; after them it scales
+//!   the number by 1,000 for each comma
+//! - fractions, eg, `# ?/?`, `# ??/??`, or `# ?/8`
+//! - literal text: `"text"`, `\x`, and `[$€-407]` currency symbols
+//! - `_x` adds a space (the width of x is approximated with one space) and
+//!   `*x` (repeat x to fill the cell) is ignored
+//! - colors: `[Red]`, `[Blue]`, ..., and `[Color1]` to `[Color8]`
+//! - `@` the text value, and `General`
+//!
+//! Date and time codes are not supported (dates use the date_time format).
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Result};
+use bigdecimal::{BigDecimal, RoundingMode, Signed, ToPrimitive, Zero};
+use lazy_static::lazy_static;
+
+/// Maximum number of `%` in a section.
+const MAX_PERCENTS: usize = 4;
+
+/// Maximum number of scaling commas (each divides by 1,000) in a section.
+const MAX_SCALE: usize = 6;
+
+/// Maximum number of digits in a fraction's denominator.
+const MAX_DENOMINATOR_DIGITS: usize = 15;
+
+/// Number of parsed format codes kept by [`NumberFormat::parse_cached`].
+const CACHE_SIZE: usize = 1000;
+
+lazy_static! {
+    static ref CACHE: Mutex<HashMap<String, Option<Arc<NumberFormat>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Excel's built-in format codes for the non-date numFmtIds.
+pub fn builtin_number_format_code(id: u32) -> Option<&'static str> {
+    Some(match id {
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        5 => "$#,##0_);($#,##0)",
+        6 => "$#,##0_);[Red]($#,##0)",
+        7 => "$#,##0.00_);($#,##0.00)",
+        8 => "$#,##0.00_);[Red]($#,##0.00)",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        12 => "# ?/?",
+        13 => "# ??/??",
+        37 => "#,##0 ;(#,##0)",
+        38 => "#,##0 ;[Red](#,##0)",
+        39 => "#,##0.00;(#,##0.00)",
+        40 => "#,##0.00;[Red](#,##0.00)",
+        41 => r#"_(* #,##0_);_(* \(#,##0\);_(* "-"_);_(@_)"#,
+        42 => r#"_("$"* #,##0_);_("$"* \(#,##0\);_("$"* "-"_);_(@_)"#,
+        43 => r#"_(* #,##0.00_);_(* \(#,##0.00\);_(* "-"??_);_(@_)"#,
+        44 => r#"_("$"* #,##0.00_);_("$"* \(#,##0.00\);_("$"* "-"??_);_(@_)"#,
+        48 => "##0.0E+0",
+        49 => "@",
+        _ => return None,
+    })
+}
+
+/// A value formatted by a NumberFormat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedValue {
+    pub text: String,
+
+    /// Text color from the format code (as a hex string).
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+
+    /// `0`, `#`, or `?`
+    Digit(char),
+    Decimal,
+    Comma,
+    Percent,
+    Exponent {
+        plus: bool,
+    },
+    Slash,
+    Text,
+    General,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Condition {
+    LessThan(f64),
+    LessThanOrEqual(f64),
+    GreaterThan(f64),
+    GreaterThanOrEqual(f64),
+    Equal(f64),
+    NotEqual(f64),
+}
+
+impl Condition {
+    fn parse(s: &str) -> Option<Self> {
+        let (condition, value): (fn(f64) -> Condition, &str) =
+            if let Some(value) = s.strip_prefix("<=") {
+                (Condition::LessThanOrEqual, value)
+            } else if let Some(value) = s.strip_prefix(">=") {
+                (Condition::GreaterThanOrEqual, value)
+            } else if let Some(value) = s.strip_prefix("<>") {
+                (Condition::NotEqual, value)
+            } else if let Some(value) = s.strip_prefix('<') {
+                (Condition::LessThan, value)
+            } else if let Some(value) = s.strip_prefix('>') {
+                (Condition::GreaterThan, value)
+            } else if let Some(value) = s.strip_prefix('=') {
+                (Condition::Equal, value)
+            } else {
+                return None;
+            };
+        value.trim().parse().ok().map(condition)
+    }
+
+    fn matches(self, number: f64) -> bool {
+        match self {
+            Condition::LessThan(value) => number < value,
+            Condition::LessThanOrEqual(value) => number <= value,
+            Condition::GreaterThan(value) => number > value,
+            Condition::GreaterThanOrEqual(value) => number >= value,
+            Condition::Equal(value) => number == value,
+            Condition::NotEqual(value) => number != value,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Section {
+    color: Option<String>,
+    condition: Option<Condition>,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberFormat {
+    sections: Vec<Section>,
+}
+
+impl FromStr for NumberFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(code: &str) -> Result<Self> {
+        let mut sections = vec![Section::default()];
+        let mut chars = code.chars().peekable();
+        while let Some(c) = chars.next() {
+            let section = sections.last_mut().expect("sections is never empty");
+            let token = match c {
+                ';' => {
+                    if sections.len() == 4 {
+                        bail!("Format code has more than four sections");
+                    }
+                    sections.push(Section::default());
+                    continue;
+                }
+                '"' => {
+                    let mut literal = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => literal.push(c),
+                            None => bail!("Unterminated quote in format code"),
+                        }
+                    }
+                    Token::Literal(literal)
+                }
+                '\\' => Token::Literal(chars.next().map(String::from).unwrap_or_default()),
+                '_' => {
+                    chars.next();
+                    Token::Literal(" ".to_string())
+                }
+                '*' => {
+                    chars.next();
+                    continue;
+                }
+                '[' => {
+                    let mut bracket = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => bracket.push(c),
+                            None => bail!("Unterminated bracket in format code"),
+                        }
+                    }
+                    if let Some(currency) = bracket.strip_prefix('$') {
+                        let symbol = currency.split('-').next().unwrap_or_default();
+                        Token::Literal(symbol.to_string())
+                    } else if let Some(color) = color(&bracket) {
+                        section.color = Some(color.to_string());
+                        continue;
+                    } else if let Some(condition) = Condition::parse(&bracket) {
+                        section.condition = Some(condition);
+                        continue;
+                    } else {
+                        return Err(anyhow!("Unsupported format code [{bracket}]"));
+                    }
+                }
+                '0' | '#' | '?' => Token::Digit(c),
+                '.' if !section.tokens.contains(&Token::Decimal) => Token::Decimal,
+                ',' => Token::Comma,
+                '%' => Token::Percent,
+                '/' => Token::Slash,
+                '@' => Token::Text,
+                'E' | 'e' if matches!(chars.peek(), Some('+') | Some('-')) => Token::Exponent {
+                    plus: chars.next() == Some('+'),
+                },
+                'G' | 'g' => {
+                    let rest = chars.clone().take(6).collect::<String>();
+                    if rest.eq_ignore_ascii_case("eneral") {
+                        chars.nth(5);
+                        Token::General
+                    } else {
+                        Token::Literal(c.to_string())
+                    }
+                }
+                c => Token::Literal(c.to_string()),
+            };
+            section.tokens.push(token);
+        }
+        for section in &sections {
+            if section
+                .tokens
+                .iter()
+                .filter(|t| **t == Token::Percent)
+                .count()
+                > MAX_PERCENTS
+            {
+                bail!("Format code has more than {MAX_PERCENTS} percent signs");
+            }
+            if section.commas().1 > MAX_SCALE {
+                bail!("Format code has more than {MAX_SCALE} scaling commas");
+            }
+        }
+        Ok(NumberFormat { sections })
+    }
+}
+
+impl NumberFormat {
+    /// Parses a format code, reusing the result of earlier calls with the
+    /// same code. Returns None if the format code is invalid.
+    pub fn parse_cached(code: &str) -> Option<Arc<NumberFormat>> {
+        let Ok(mut cache) = CACHE.lock() else {
+            return NumberFormat::from_str(code).ok().map(Arc::new);
+        };
+        if let Some(number_format) = cache.get(code) {
+            return number_format.clone();
+        }
+        if cache.len() >= CACHE_SIZE {
+            cache.clear();
+        }
+        let number_format = NumberFormat::from_str(code).ok().map(Arc::new);
+        cache.insert(code.to_string(), number_format.clone());
+        number_format
+    }
+
+    /// Whether a format code formats dates or times (which are not supported
+    /// by NumberFormat).
+    pub fn is_date_time(code: &str) -> bool {
+        let mut in_quote = false;
+        let mut in_bracket = false;
+        let mut escaped = false;
+        for c in code.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' | '_' | '*' if !in_quote => escaped = true,
+                '"' => in_quote = !in_quote,
+                '[' if !in_quote => in_bracket = true,
+                ']' if !in_quote => in_bracket = false,
+                'y' | 'Y' | 'm' | 'M' | 'd' | 'D' | 'h' | 'H' | 's' | 'S'
+                    if !in_quote && !in_bracket =>
+                {
+                    return true;
+                }
+                _ => (),
+            }
+        }
+        false
+    }
+
+    /// Returns the section used for a number, and whether a minus sign needs
+    /// to be added to the result.
+    fn number_section(&self, number: &BigDecimal) -> Option<(&Section, bool)> {
+        let negative = number.is_negative();
+        let numeric = &self.sections[..self.sections.len().min(3)];
+        if numeric.iter().any(|section| section.condition.is_some()) {
+            let value = number.to_f64().unwrap_or_default();
+            return numeric
+                .iter()
+                .enumerate()
+                .find(|(_, section)| section.condition.is_none_or(|c| c.matches(value)))
+                .map(|(i, section)| (section, negative && i == 0));
+        }
+        match numeric.len() {
+            1 => Some((&numeric[0], negative)),
+            2 if negative => Some((&numeric[1], false)),
+            2 => Some((&numeric[0], false)),
+            _ if negative => Some((&numeric[1], false)),
+            _ if number.is_zero() => Some((&numeric[2], false)),
+            _ => Some((&numeric[0], false)),
+        }
+    }
+
+    /// Formats a number.
+    pub fn format_number(&self, number: &BigDecimal) -> FormattedValue {
+        let Some((section, minus)) = self.number_section(number) else {
+            return FormattedValue {
+                text: number.to_string(),
+                color: None,
+            };
+        };
+        let mut text = section.format_number(&number.abs());
+        if minus {
+            text.insert(0, '-');
+        }
+        FormattedValue {
+            text,
+            color: section.color.clone(),
+        }
+    }
+
+    /// Formats text. Returns None if the format code does not have a text
+    /// section.
+    pub fn format_text(&self, text: &str) -> Option<FormattedValue> {
+        let section = match self.sections.len() {
+            4 => &self.sections[3],
+            1 if self.sections[0].tokens.contains(&Token::Text) => &self.sections[0],
+            _ => return None,
+        };
+        let text = section
+            .tokens
+            .iter()
+            .map(|token| match token {
+                Token::Literal(literal) => literal.as_str(),
+                Token::Text => text,
+                _ => "",
+            })
+            .collect();
+        Some(FormattedValue {
+            text,
+            color: section.color.clone(),
+        })
+    }
+}
+
+impl Section {
+    /// Index of the exponent (or the end of the tokens).
+    fn mantissa_end(&self) -> usize {
+        self.tokens
+            .iter()
+            .position(|t| matches!(t, Token::Exponent { .. }))
+            .unwrap_or(self.tokens.len())
+    }
+
+    /// Returns whether the number is grouped by thousands, the number of
+    /// thousands to scale by, and which commas are shown as literals.
+    ///
+    /// Commas between digit placeholders group thousands; commas after the
+    /// last digit placeholder scale the number.
+    fn commas(&self) -> (bool, usize, Vec<bool>) {
+        let mantissa_end = self.mantissa_end();
+        let integer_end = self
+            .tokens
+            .iter()
+            .position(|t| *t == Token::Decimal)
+            .unwrap_or(mantissa_end);
+        let is_digit = |i: usize| matches!(self.tokens[i], Token::Digit(_));
+
+        let mut grouping = false;
+        let mut scale = 0;
+        let mut commas = vec![false; self.tokens.len()];
+        for (i, token) in self.tokens.iter().enumerate().take(mantissa_end) {
+            if *token == Token::Comma {
+                let before = (0..i).any(is_digit);
+                let after = (i + 1..mantissa_end).any(is_digit);
+                if before && after && i < integer_end {
+                    grouping = true;
+                } else if before && !after {
+                    scale += 1;
+                } else {
+                    commas[i] = true;
+                }
+            }
+        }
+        (grouping, scale, commas)
+    }
+
+    fn format_number(&self, number: &BigDecimal) -> String {
+        if self.tokens.contains(&Token::Slash) {
+            return self.format_fraction(number);
+        }
+
+        let general = number.normalized().to_string();
+        let mantissa_end = self.mantissa_end();
+        let integer_end = self
+            .tokens
+            .iter()
+            .position(|t| *t == Token::Decimal)
+            .unwrap_or(mantissa_end);
+        let is_digit = |i: usize| matches!(self.tokens[i], Token::Digit(_));
+
+        let (grouping, scale, commas) = self.commas();
+        let percents = self.tokens.iter().filter(|t| **t == Token::Percent).count();
+        let mut number = shift(number, 2 * percents as i64 - 3 * scale as i64);
+
+        let integer_places = (0..integer_end)
+            .filter(|&i| is_digit(i))
+            .collect::<Vec<_>>();
+        let decimal_places = (integer_end..mantissa_end)
+            .filter(|&i| is_digit(i))
+            .collect::<Vec<_>>();
+        let decimals = decimal_places.len() as i64;
+
+        let mut exponent = 0;
+        if mantissa_end < self.tokens.len() && !number.is_zero() {
+            let width = integer_places.len().max(1) as i64;
+            let engineering = width > 1
+                && integer_places
+                    .iter()
+                    .any(|&i| self.tokens[i] == Token::Digit('#'));
+            let step = if engineering { width } else { 1 };
+            let magnitude = number.to_f64().unwrap_or_default().log10().floor() as i64;
+            exponent = if engineering {
+                magnitude.div_euclid(width) * width
+            } else {
+                magnitude - (width - 1)
+            };
+            // rounding may add a digit, eg, 9.99 => 10.0
+            let mut mantissa =
+                shift(&number, -exponent).with_scale_round(decimals, RoundingMode::HalfUp);
+            if mantissa >= shift(&BigDecimal::from(1), width) {
+                exponent += step;
+                mantissa =
+                    shift(&number, -exponent).with_scale_round(decimals, RoundingMode::HalfUp);
+            }
+            number = mantissa;
+        }
+
+        let (integer, fraction) = split_digits(
+            &number.with_scale_round(decimals, RoundingMode::HalfUp),
+            decimals,
+        );
+
+        // fill integer placeholders from the right; the leftmost gets any
+        // remaining digits
+        let mut integer_text = vec![String::new(); self.tokens.len()];
+        let mut remaining = integer.as_str();
+        for (n, &i) in integer_places.iter().enumerate().rev() {
+            let Token::Digit(placeholder) = self.tokens[i] else {
+                continue;
+            };
+            integer_text[i] = if remaining.is_empty() {
+                match placeholder {
+                    '0' => "0".to_string(),
+                    '?' => " ".to_string(),
+                    _ => String::new(),
+                }
+            } else if n == 0 {
+                std::mem::take(&mut remaining).to_string()
+            } else {
+                let (rest, digit) = remaining.split_at(remaining.len() - 1);
+                remaining = rest;
+                digit.to_string()
+            };
+        }
+        if grouping {
+            if let Some(&first) = integer_places.first() {
+                let joined = integer_places
+                    .iter()
+                    .map(|&i| std::mem::take(&mut integer_text[i]))
+                    .collect::<String>();
+                let digits = joined.trim_start();
+                let padding = joined.len() - digits.len();
+                integer_text[first] = format!("{}{}", " ".repeat(padding), add_commas(digits));
+            }
+        }
+
+        // decimal placeholders drop trailing zeros for # and ?
+        let mut decimal_text = vec![String::new(); self.tokens.len()];
+        let mut trailing = true;
+        let fraction = fraction.chars().collect::<Vec<_>>();
+        for (&i, &digit) in decimal_places.iter().zip(fraction.iter()).rev() {
+            let Token::Digit(placeholder) = self.tokens[i] else {
+                continue;
+            };
+            decimal_text[i] = match placeholder {
+                '#' if trailing && digit == '0' => String::new(),
+                '?' if trailing && digit == '0' => " ".to_string(),
+                _ => {
+                    trailing = false;
+                    digit.to_string()
+                }
+            };
+        }
+
+        let mut text = String::new();
+        for (i, token) in self.tokens.iter().enumerate() {
+            match token {
+                Token::Literal(literal) => text.push_str(literal),
+                Token::Digit(_) if i < integer_end => text.push_str(&integer_text[i]),
+                Token::Digit(_) if i < mantissa_end => text.push_str(&decimal_text[i]),
+                Token::Digit(_) => (),
+                Token::Decimal => {
+                    if integer_places.is_empty() {
+                        text.push_str(&integer);
+                    }
+                    text.push('.');
+                }
+                Token::Comma if commas[i] => text.push(','),
+                Token::Comma => (),
+                Token::Percent => text.push('%'),
+                Token::Exponent { plus } => {
+                    let width = self.tokens[i + 1..]
+                        .iter()
+                        .filter(|t| **t == Token::Digit('0'))
+                        .count();
+                    text.push('E');
+                    if exponent < 0 {
+                        text.push('-');
+                    } else if *plus {
+                        text.push('+');
+                    }
+                    text.push_str(&format!("{:0width$}", exponent.abs()));
+                }
+                Token::Slash => text.push('/'),
+                Token::Text | Token::General => text.push_str(&general),
+            }
+        }
+        text
+    }
+
+    fn format_fraction(&self, number: &BigDecimal) -> String {
+        let slash = self
+            .tokens
+            .iter()
+            .position(|t| *t == Token::Slash)
+            .unwrap_or_default();
+        let mut numerator_start = slash;
+        while numerator_start > 0 && matches!(self.tokens[numerator_start - 1], Token::Digit(_)) {
+            numerator_start -= 1;
+        }
+        let has_integer = self.tokens[..numerator_start]
+            .iter()
+            .any(|t| matches!(t, Token::Digit(_)));
+
+        // the denominator is either placeholders or a fixed number
+        let mut denominator_end = slash + 1;
+        let mut fixed_denominator = String::new();
+        while let Some(token) = self.tokens.get(denominator_end) {
+            match token {
+                Token::Digit(_) if fixed_denominator.is_empty() => (),
+                Token::Literal(l) if l.chars().all(|c| c.is_ascii_digit()) => {
+                    fixed_denominator.push_str(l);
+                }
+                _ => break,
+            }
+            denominator_end += 1;
+        }
+        let denominator_places = self.tokens[slash + 1..denominator_end]
+            .iter()
+            .filter(|t| matches!(t, Token::Digit(_)))
+            .collect::<Vec<_>>();
+
+        let value = number.to_f64().unwrap_or_default();
+        let mut whole = if has_integer { value.trunc() } else { 0.0 };
+        let fraction = value - whole;
+        let (mut numerator, denominator) = match fixed_denominator.parse::<u64>() {
+            Ok(denominator) if denominator > 0 => {
+                ((fraction * denominator as f64).round() as u64, denominator)
+            }
+            _ => best_fraction(
+                fraction,
+                10u64.pow(denominator_places.len().clamp(1, MAX_DENOMINATOR_DIGITS) as u32) - 1,
+            ),
+        };
+        if has_integer && numerator == denominator {
+            whole += 1.0;
+            numerator = 0;
+        }
+
+        let integer = if whole == 0.0 {
+            String::new()
+        } else {
+            format!("{whole}")
+        };
+        // ? pads with spaces and 0 pads with zeros
+        let pad = |places: &[&Token]| {
+            if places.contains(&&Token::Digit('?')) {
+                Some(' ')
+            } else if places.contains(&&Token::Digit('0')) {
+                Some('0')
+            } else {
+                None
+            }
+        };
+        let numerator_places = self.tokens[numerator_start..slash]
+            .iter()
+            .collect::<Vec<_>>();
+
+        let mut text = String::new();
+        let mut integer_written = false;
+        for (i, token) in self.tokens.iter().enumerate() {
+            if (numerator_start..denominator_end).contains(&i) {
+                if numerator == 0 && has_integer {
+                    continue;
+                }
+                if i == numerator_start {
+                    let numerator = numerator.to_string();
+                    let width = numerator_places.len().saturating_sub(numerator.len());
+                    if let Some(pad) = pad(&numerator_places) {
+                        text.extend(std::iter::repeat_n(pad, width));
+                    }
+                    text.push_str(&numerator);
+                    text.push('/');
+                } else if i == slash + 1 {
+                    let denominator = denominator.to_string();
+                    text.push_str(&denominator);
+                    let width = denominator_places.len().saturating_sub(denominator.len());
+                    if pad(&denominator_places) == Some(' ') {
+                        text.extend(std::iter::repeat_n(' ', width));
+                    }
+                }
+                continue;
+            }
+            match token {
+                Token::Literal(literal) => text.push_str(literal),
+                // the integer is written at the first integer placeholder
+                Token::Digit(placeholder) if i < numerator_start && !integer_written => {
+                    integer_written = true;
+                    if integer.is_empty() && (numerator == 0 || *placeholder == '0') {
+                        text.push('0');
+                    } else {
+                        text.push_str(&integer);
+                    }
+                }
+                Token::Percent => text.push('%'),
+                Token::Text | Token::General => text.push_str(&number.normalized().to_string()),
+                _ => (),
+            }
+        }
+        if numerator == 0 && has_integer {
+            text.trim_end().to_string()
+        } else if numerator == 0 && !has_integer {
+            "0".to_string()
+        } else {
+            text
+        }
+    }
+}
+
+/// Returns the Excel color for a `[Color]` code.
+fn color(name: &str) -> Option<&'static str> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "black" | "color1" => "#000000",
+        "white" | "color2" => "#ffffff",
+        "red" | "color3" => "#ff0000",
+        "green" | "color4" => "#00ff00",
+        "blue" | "color5" => "#0000ff",
+        "yellow" | "color6" => "#ffff00",
+        "magenta" | "color7" => "#ff00ff",
+        "cyan" | "color8" => "#00ffff",
+        _ => return None,
+    })
+}
+
+/// Multiplies a number by 10^exponent.
+fn shift(number: &BigDecimal, exponent: i64) -> BigDecimal {
+    let (digits, scale) = number.as_bigint_and_exponent();
+    BigDecimal::new(digits, scale - exponent)
+}
+
+/// Splits a non-negative number into its integer digits (without leading
+/// zeros) and `decimals` fractional digits.
+fn split_digits(number: &BigDecimal, decimals: i64) -> (String, String) {
+    let (digits, _) = number.with_scale(decimals).as_bigint_and_exponent();
+    let digits = digits.to_string();
+    let decimals = decimals as usize;
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    (
+        integer.trim_start_matches('0').to_string(),
+        fraction.to_string(),
+    )
+}
+
+/// Adds thousands separators to a string of digits.
+fn add_commas(digits: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Returns the closest fraction to a non-negative value with a denominator no
+/// larger than max_denominator.
+///
+/// This walks the value's continued fraction: the best approximation is
+/// either the last convergent within max_denominator or the semiconvergent
+/// between it and the previous convergent.
+fn best_fraction(value: f64, max_denominator: u64) -> (u64, u64) {
+    let max_denominator = max_denominator.max(1);
+    let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+    let mut x = value;
+    loop {
+        let a = x.floor() as u64;
+        let next = a
+            .checked_mul(p1)
+            .and_then(|p| p.checked_add(p0))
+            .zip(a.checked_mul(q1).and_then(|q| q.checked_add(q0)));
+        let Some((p2, q2)) = next.filter(|&(_, q2)| q2 <= max_denominator) else {
+            break;
+        };
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+        let remainder = x - a as f64;
+        if remainder <= f64::EPSILON || p1 as f64 / q1 as f64 == value {
+            break;
+        }
+        x = 1.0 / remainder;
+    }
+
+    let t = (max_denominator - q0) / q1;
+    let semiconvergent = (p0.saturating_add(t.saturating_mul(p1)), q0 + t * q1);
+    let error = |(p, q): (u64, u64)| (value - p as f64 / q as f64).abs();
+    if t > 0 && error(semiconvergent) < error((p1, q1)) - f64::EPSILON {
+        semiconvergent
+    } else {
+        (p1, q1)
+    }
+}
+
+#[cfg(test)]
+#[serial_test::parallel]
+mod tests {
+    use super::*;
+
+    fn format(code: &str, number: &str) -> String {
+        NumberFormat::from_str(code)
+            .unwrap()
+            .format_number(&BigDecimal::from_str(number).unwrap())
+            .text
+    }
+
+    #[test]
+    fn digit_placeholders() {
+        assert_eq!(format("0", "1234.5"), "1235");
+        assert_eq!(format("0.00", "1234.5"), "1234.50");
+        assert_eq!(format("#,##0.00", "1234567.891"), "1,234,567.89");
+        assert_eq!(format("#,##0", "12"), "12");
+        assert_eq!(format("00000", "42"), "00042");
+        assert_eq!(format("#.##", "0.5"), ".5");
+        assert_eq!(format("0.0#", "1.5"), "1.5");
+        assert_eq!(format("0.??", "1.5"), "1.5 ");
+        assert_eq!(format("???.0", "1.5"), "  1.5");
+        assert_eq!(format("(000) 000-0000", "5551234567"), "(555) 123-4567");
+        assert_eq!(format("0", "-3"), "-3");
+    }
+
+    #[test]
+    fn scaling_and_percent() {
+        assert_eq!(format("#,##0,", "1234567"), "1,235");
+        assert_eq!(format("0.0,,\"M\"", "12345678"), "12.3M");
+        assert_eq!(format("0%", "0.256"), "26%");
+        assert_eq!(format("0.00%", "0.256"), "25.60%");
+    }
+
+    #[test]
+    fn scientific() {
+        assert_eq!(format("0.00E+00", "12345"), "1.23E+04");
+        assert_eq!(format("0.00E+00", "0.00012345"), "1.23E-04");
+        assert_eq!(format("0.0E-0", "12345"), "1.2E4");
+        assert_eq!(format("##0.0E+0", "12345"), "12.3E+3");
+        assert_eq!(format("0.0E+0", "99999"), "1.0E+5");
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!(format("# ?/?", "1.5"), "1 1/2");
+        assert_eq!(format("# ??/??", "3.14159"), "3 14/99");
+        assert_eq!(format("# ?/8", "0.375"), " 3/8");
+        assert_eq!(format("?/?", "0.75"), "3/4");
+        assert_eq!(format("# ?/?", "2"), "2");
+    }
+
+    #[test]
+    fn long_fractions() {
+        assert_eq!(format("# ????????/????????", "0.1").trim(), "1/10");
+        assert_eq!(format("0/000", "3.14159265358979"), "355/113");
+        assert_eq!(format("0/0000000", "3.14159265358979"), "5419351/1725033");
+        let code = format!("# {}/{}", "?".repeat(30), "?".repeat(30));
+        assert_eq!(format(&code, "0.5").trim(), "1/2");
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(format("0%%%%", "1"), "100000000%%%%");
+        assert!(NumberFormat::from_str("0%%%%%").is_err());
+        assert_eq!(format("0,,,,,,", "1000000000000000000"), "1");
+        assert!(NumberFormat::from_str("0,,,,,,,").is_err());
+        assert!(NumberFormat::from_str("#,##0,,,,,,").is_ok());
+    }
+
+    #[test]
+    fn parse_cached() {
+        let first = NumberFormat::parse_cached("0.00").unwrap();
+        let second = NumberFormat::parse_cached("0.00").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(NumberFormat::parse_cached("[Unknown]0").is_none());
+    }
+
+    #[test]
+    fn sections() {
+        let code = "#,##0.00;[Red](#,##0.00);\"zero\";\"text: \"@";
+        let format = NumberFormat::from_str(code).unwrap();
+        assert_eq!(
+            format.format_number(&BigDecimal::from(1000)),
+            FormattedValue {
+                text: "1,000.00".into(),
+                color: None
+            }
+        );
+        assert_eq!(
+            format.format_number(&BigDecimal::from(-1000)),
+            FormattedValue {
+                text: "(1,000.00)".into(),
+                color: Some("#ff0000".into())
+            }
+        );
+        assert_eq!(format.format_number(&BigDecimal::zero()).text, "zero");
+        assert_eq!(format.format_text("abc").unwrap().text, "text: abc");
+
+        // two sections: negative numbers use the second without a minus sign
+        assert_eq!(self::format("0;0-", "-5"), "5-");
+
+        // empty sections hide the value
+        assert_eq!(self::format("0;-0;", "0"), "");
+
+        // numbers do not use a text section
+        assert!(NumberFormat::from_str("0")
+            .unwrap()
+            .format_text("a")
+            .is_none());
+        assert_eq!(
+            NumberFormat::from_str("@")
+                .unwrap()
+                .format_text("a")
+                .unwrap()
+                .text,
+            "a"
+        );
+    }
+
+    #[test]
+    fn conditions() {
+        let code = "[Blue][>=100]0;[<0]\"neg\";0.00";
+        assert_eq!(format(code, "150"), "150");
+        assert_eq!(format(code, "-5"), "neg");
+        assert_eq!(format(code, "5"), "5.00");
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(format("\"$\"#,##0.00", "1234"), "$1,234.00");
+        assert_eq!(format("[$€-407] #,##0.00", "1234"), "€ 1,234.00");
+        assert_eq!(format("0\\k", "5"), "5k");
+        assert_eq!(format("General", "1234.5"), "1234.5");
+        assert_eq!(format("\"Total: \"General", "12"), "Total: 12");
+
+        // accounting alignment
+        assert_eq!(
+            format(builtin_number_format_code(44).unwrap(), "1234.5"),
+            " $1,234.50 "
+        );
+        assert_eq!(
+            format(builtin_number_format_code(44).unwrap(), "-1234.5"),
+            " $(1,234.50)"
+        );
+        assert_eq!(
+            format(builtin_number_format_code(44).unwrap(), "0"),
+            " $-   "
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(NumberFormat::from_str("\"abc").is_err());
+        assert!(NumberFormat::from_str("[Red").is_err());
+        assert!(NumberFormat::from_str("0;0;0;0;0").is_err());
+    }
+
+    #[test]
+    fn date_time() {
+        assert!(NumberFormat::is_date_time("yyyy-mm-dd"));
+        assert!(NumberFormat::is_date_time("[h]:mm"));
+        assert!(!NumberFormat::is_date_time("#,##0.00"));
+        assert!(!NumberFormat::is_date_time("\"days\" 0"));
+        assert!(!NumberFormat::is_date_time("[Red]General"));
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::controller::transaction_types::JsCodeResult;
 
 #[wasm_bindgen]
 impl GridController {
@@ -12,6 +13,18 @@ impl GridController {
         }
     }
 
+    /// Called after an external calculation is complete, with its output as
+    /// an Arrow IPC stream instead of the result's `output_array`.
+    #[wasm_bindgen(js_name = "calculationCompleteArrow")]
+    pub fn js_calculation_complete_arrow(&mut self, result: String, output: Vec<u8>) {
+        if let Ok(mut result) = serde_json::from_str::<JsCodeResult>(&result) {
+            result.output_arrow = Some(output);
+            let _ = self.calculation_complete(result);
+        } else {
+            dbgjs!("calculationCompleteArrow: Failed to parse calculation result");
+        }
+    }
+
     #[wasm_bindgen(js_name = "calculationGetCellsA1")]
     pub fn js_calculation_get_cells_a1(
         &mut self,
@@ -31,6 +44,18 @@ impl GridController {
         }
     }
 
+    /// Returns the cells as an Arrow IPC stream with typed values.
+    #[wasm_bindgen(js_name = "calculationGetCellsA1Arrow")]
+    pub fn js_calculation_get_cells_a1_arrow(
+        &mut self,
+        transaction_id: String,
+        a1: String,
+        line_number: Option<u32>,
+    ) -> Result<Vec<u8>, JsValue> {
+        self.calculation_get_cells_a1_arrow(transaction_id, a1, line_number)
+            .map_err(|_| JsValue::UNDEFINED)
+    }
+
     /// Returns the code cell (which is a combination of CellValue::Code and CodeRun).
     /// If the cell is part of a code run, it returns the code run that caused the output.
     ///
@@ -90,6 +115,41 @@ impl GridController {
         }
     }
 
+    /// Returns the code cells that should run again, as a JSON
+    /// `Vec<SheetPos>`. `opened` is true when the file was just opened.
+    #[wasm_bindgen(js_name = "getCodeCellsDueForRefresh")]
+    pub fn js_get_code_cells_due_for_refresh(&self, opened: bool) -> Result<String, JsValue> {
+        let code_cells = self.code_cells_due_for_refresh(chrono::Utc::now(), opened);
+        serde_json::to_string(&code_cells).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Reruns code cells returned by getCodeCellsDueForRefresh.
+    #[wasm_bindgen(js_name = "refreshCodeCells")]
+    pub fn js_refresh_code_cells(&mut self, code_cells: String, cursor: Option<String>) {
+        if let Ok(code_cells) = serde_json::from_str::<Vec<SheetPos>>(&code_cells) {
+            self.refresh_code_cells(&code_cells, cursor);
+        }
+    }
+
+    /// Sets when a code cell runs again without a change to the cells it
+    /// reads.
+    #[wasm_bindgen(js_name = "setCodeCellRefresh")]
+    pub fn js_set_code_cell_refresh(
+        &mut self,
+        sheet_id: String,
+        pos: String,
+        refresh: String,
+        cursor: Option<String>,
+    ) {
+        if let (Ok(pos), Ok(sheet_id), Ok(refresh)) = (
+            serde_json::from_str::<Pos>(&pos),
+            SheetId::from_str(&sheet_id),
+            serde_json::from_str::<CodeCellRefresh>(&refresh),
+        ) {
+            self.set_code_cell_refresh(pos.to_sheet_pos(sheet_id), refresh, cursor);
+        }
+    }
+
     #[wasm_bindgen(js_name = "connectionComplete")]
     pub fn js_connection_complete(
         &mut self,
@@ -42,4 +42,146 @@ impl GridController {
             self.insert_row(sheet_id, row, after, cursor);
         }
     }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "hideColumns")]
+    pub fn js_hide_columns(&mut self, sheet_id: &str, columns: String, cursor: Option<String>) {
+        if let (Ok(sheet_id), Ok(columns)) =
+            (SheetId::from_str(sheet_id), serde_json::from_str(&columns))
+        {
+            self.hide_columns(sheet_id, columns, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "unhideColumns")]
+    pub fn js_unhide_columns(&mut self, sheet_id: &str, columns: String, cursor: Option<String>) {
+        if let (Ok(sheet_id), Ok(columns)) =
+            (SheetId::from_str(sheet_id), serde_json::from_str(&columns))
+        {
+            self.unhide_columns(sheet_id, columns, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "hideRows")]
+    pub fn js_hide_rows(&mut self, sheet_id: &str, rows: String, cursor: Option<String>) {
+        if let (Ok(sheet_id), Ok(rows)) = (SheetId::from_str(sheet_id), serde_json::from_str(&rows))
+        {
+            self.hide_rows(sheet_id, rows, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "unhideRows")]
+    pub fn js_unhide_rows(&mut self, sheet_id: &str, rows: String, cursor: Option<String>) {
+        if let (Ok(sheet_id), Ok(rows)) = (SheetId::from_str(sheet_id), serde_json::from_str(&rows))
+        {
+            self.unhide_rows(sheet_id, rows, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "groupColumns")]
+    pub fn js_group_columns(
+        &mut self,
+        sheet_id: &str,
+        start: i64,
+        end: i64,
+        cursor: Option<String>,
+    ) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.group_columns(sheet_id, start, end, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "ungroupColumns")]
+    pub fn js_ungroup_columns(
+        &mut self,
+        sheet_id: &str,
+        start: i64,
+        end: i64,
+        cursor: Option<String>,
+    ) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.ungroup_columns(sheet_id, start, end, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "setColumnGroupCollapsed")]
+    pub fn js_set_column_group_collapsed(
+        &mut self,
+        sheet_id: &str,
+        column: i64,
+        collapsed: bool,
+        cursor: Option<String>,
+    ) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.set_column_group_collapsed(sheet_id, column, collapsed, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "showColumnOutlineLevel")]
+    pub fn js_show_column_outline_level(
+        &mut self,
+        sheet_id: &str,
+        level: usize,
+        cursor: Option<String>,
+    ) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.show_column_outline_level(sheet_id, level, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "groupRows")]
+    pub fn js_group_rows(&mut self, sheet_id: &str, start: i64, end: i64, cursor: Option<String>) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.group_rows(sheet_id, start, end, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "ungroupRows")]
+    pub fn js_ungroup_rows(
+        &mut self,
+        sheet_id: &str,
+        start: i64,
+        end: i64,
+        cursor: Option<String>,
+    ) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.ungroup_rows(sheet_id, start, end, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "setRowGroupCollapsed")]
+    pub fn js_set_row_group_collapsed(
+        &mut self,
+        sheet_id: &str,
+        row: i64,
+        collapsed: bool,
+        cursor: Option<String>,
+    ) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.set_row_group_collapsed(sheet_id, row, collapsed, cursor);
+        }
+    }
+
+    #[allow(non_snake_case)]
+    #[wasm_bindgen(js_name = "showRowOutlineLevel")]
+    pub fn js_show_row_outline_level(
+        &mut self,
+        sheet_id: &str,
+        level: usize,
+        cursor: Option<String>,
+    ) {
+        if let Ok(sheet_id) = SheetId::from_str(sheet_id) {
+            self.show_row_outline_level(sheet_id, level, cursor);
+        }
+    }
 }
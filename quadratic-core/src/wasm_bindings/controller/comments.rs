@@ -0,0 +1,99 @@
+//! WASM functions for comment threads
+
+use uuid::Uuid;
+
+use super::*;
+
+fn parse_ids(sheet_id: &str, thread_id: &str) -> Result<(SheetId, Uuid), String> {
+    let sheet_id = SheetId::from_str(sheet_id).map_err(|_| "Invalid sheet id".to_string())?;
+    let thread_id = Uuid::from_str(thread_id).map_err(|_| "Invalid thread id".to_string())?;
+    Ok((sheet_id, thread_id))
+}
+
+#[wasm_bindgen]
+impl GridController {
+    /// Returns the comment threads for a sheet
+    #[wasm_bindgen(js_name = "getCommentThreads")]
+    pub fn js_comment_threads(&self, sheet_id: String) -> Result<JsValue, JsValue> {
+        if let Ok(sheet_id) = SheetId::from_str(&sheet_id) {
+            Ok(serde_wasm_bindgen::to_value(
+                &self.comment_threads(sheet_id),
+            )?)
+        } else {
+            Err(JsValue::from_str("Invalid sheet id"))
+        }
+    }
+
+    /// Starts a comment thread on a cell. Returns the id of the thread.
+    #[wasm_bindgen(js_name = "addCommentThread")]
+    pub fn js_add_comment_thread(
+        &mut self,
+        sheet_pos: String,
+        author: String,
+        text: String,
+        cursor: Option<String>,
+    ) -> Result<String, String> {
+        let sheet_pos =
+            serde_json::from_str(&sheet_pos).map_err(|_| "Invalid sheet pos".to_string())?;
+        Ok(self
+            .add_comment_thread(sheet_pos, author, text, cursor)
+            .to_string())
+    }
+
+    /// Replies to a comment thread
+    #[wasm_bindgen(js_name = "replyToCommentThread")]
+    pub fn js_reply_to_comment_thread(
+        &mut self,
+        sheet_id: String,
+        thread_id: String,
+        author: String,
+        text: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let (sheet_id, thread_id) = parse_ids(&sheet_id, &thread_id)?;
+        self.reply_to_comment_thread(sheet_id, thread_id, author, text, cursor);
+        Ok(())
+    }
+
+    /// Resolves (or reopens) a comment thread
+    #[wasm_bindgen(js_name = "resolveCommentThread")]
+    pub fn js_resolve_comment_thread(
+        &mut self,
+        sheet_id: String,
+        thread_id: String,
+        resolved: bool,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let (sheet_id, thread_id) = parse_ids(&sheet_id, &thread_id)?;
+        self.resolve_comment_thread(sheet_id, thread_id, resolved, cursor);
+        Ok(())
+    }
+
+    /// Deletes a reply from a comment thread
+    #[wasm_bindgen(js_name = "removeCommentReply")]
+    pub fn js_remove_comment_reply(
+        &mut self,
+        sheet_id: String,
+        thread_id: String,
+        reply_id: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let (sheet_id, thread_id) = parse_ids(&sheet_id, &thread_id)?;
+        let reply_id = Uuid::from_str(&reply_id).map_err(|_| "Invalid reply id".to_string())?;
+        self.remove_comment_reply(sheet_id, thread_id, reply_id, cursor);
+        Ok(())
+    }
+
+    /// Deletes a comment thread
+    #[wasm_bindgen(js_name = "removeCommentThread")]
+    pub fn js_remove_comment_thread(
+        &mut self,
+        sheet_id: String,
+        thread_id: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let (sheet_id, thread_id) = parse_ids(&sheet_id, &thread_id)?;
+        self.remove_comment_thread(sheet_id, thread_id, cursor);
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+//! WASM functions for Conditional Formats
+
+use sheet::conditional_formats::conditional_format::ConditionalFormat;
+use uuid::Uuid;
+
+use super::*;
+
+#[wasm_bindgen]
+impl GridController {
+    /// Returns a list of ConditionalFormats for a sheet
+    #[wasm_bindgen(js_name = "getConditionalFormats")]
+    pub fn js_conditional_formats(&self, sheet_id: String) -> Result<JsValue, JsValue> {
+        if let Ok(sheet_id) = SheetId::from_str(&sheet_id) {
+            Ok(serde_wasm_bindgen::to_value(
+                &self.conditional_formats(sheet_id),
+            )?)
+        } else {
+            Err(JsValue::from_str("Invalid sheet id"))
+        }
+    }
+
+    /// Creates or updates a conditional format
+    #[wasm_bindgen(js_name = "updateConditionalFormat")]
+    pub fn js_update_conditional_format(
+        &mut self,
+        conditional_format: String, // ConditionalFormat
+        cursor: Option<String>,
+    ) {
+        let conditional_format =
+            match serde_json::from_str::<ConditionalFormat>(&conditional_format) {
+                Ok(conditional_format) => conditional_format,
+                Err(e) => {
+                    dbgjs!(format!(
+                        "Error parsing conditional format: {}",
+                        e.to_string()
+                    ));
+                    return;
+                }
+            };
+        self.update_conditional_format(conditional_format, cursor);
+    }
+
+    /// Removes a conditional format
+    #[wasm_bindgen(js_name = "removeConditionalFormat")]
+    pub fn js_remove_conditional_format(
+        &mut self,
+        sheet_id: String,
+        conditional_format_id: String,
+        cursor: Option<String>,
+    ) {
+        if let (Ok(sheet_id), Ok(conditional_format_id)) = (
+            SheetId::from_str(&sheet_id),
+            Uuid::from_str(&conditional_format_id),
+        ) {
+            self.remove_conditional_format(sheet_id, conditional_format_id, cursor);
+        }
+    }
+}
@@ -3,6 +3,8 @@ use std::str::FromStr;
 use wasm_bindgen::prelude::*;
 
 use crate::controller::GridController;
+use crate::grid::formats::MAX_TEXT_ROTATION;
+use crate::number_format::NumberFormat;
 use crate::A1Selection;
 use crate::Pos;
 
@@ -126,6 +128,22 @@ impl GridController {
         Ok(())
     }
 
+    /// Sets cells numeric_format to an Excel-style format code (eg,
+    /// `#,##0.00;[Red](#,##0.00)`)
+    #[wasm_bindgen(js_name = "setNumberFormatCode")]
+    pub fn js_set_number_format_code(
+        &mut self,
+        selection: String,
+        code: String,
+        cursor: Option<String>,
+    ) -> Result<(), JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        NumberFormat::from_str(&code).map_err(|e| e.to_string())?;
+        self.set_numeric_format(&selection, NumericFormatKind::Custom, Some(code), cursor)?;
+        Ok(())
+    }
+
     /// Sets cells numeric_commas
     #[wasm_bindgen(js_name = "setCommas")]
     pub fn js_set_commas(
@@ -280,6 +298,60 @@ impl GridController {
         Ok(())
     }
 
+    /// Sets cell font size (in points) given as an optional [`i16`].
+    #[wasm_bindgen(js_name = "setFontSize")]
+    pub fn js_set_font_size(
+        &mut self,
+        selection: String,
+        font_size: Option<i16>,
+        cursor: Option<String>,
+    ) -> Result<(), JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        if font_size.is_some_and(|font_size| font_size <= 0) {
+            return Err("Font size must be positive".into());
+        }
+        self.set_font_size(&selection, font_size, cursor)?;
+        Ok(())
+    }
+
+    /// Sets cell font family given as an optional [`String`].
+    #[wasm_bindgen(js_name = "setFontFamily")]
+    pub fn js_set_font_family(
+        &mut self,
+        selection: String,
+        font_family: Option<String>,
+        cursor: Option<String>,
+    ) -> Result<(), JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        self.set_font_family(&selection, font_family, cursor)?;
+        Ok(())
+    }
+
+    /// Sets cell text rotation (in degrees, -90 to 90) given as an optional
+    /// [`i16`].
+    #[wasm_bindgen(js_name = "setTextRotation")]
+    pub fn js_set_text_rotation(
+        &mut self,
+        selection: String,
+        text_rotation: Option<i16>,
+        cursor: Option<String>,
+    ) -> Result<(), JsValue> {
+        let selection = serde_json::from_str::<A1Selection>(&selection)
+            .map_err(|_| "Unable to parse A1Selection")?;
+        if text_rotation.is_some_and(|text_rotation| {
+            !(-MAX_TEXT_ROTATION..=MAX_TEXT_ROTATION).contains(&text_rotation)
+        }) {
+            return Err(format!(
+                "Text rotation must be between -{MAX_TEXT_ROTATION} and {MAX_TEXT_ROTATION} degrees"
+            )
+            .into());
+        }
+        self.set_text_rotation(&selection, text_rotation, cursor)?;
+        Ok(())
+    }
+
     /// Returns a [`TransactionSummary`].
     #[wasm_bindgen(js_name = "clearFormatting")]
     pub fn js_clear_formatting(
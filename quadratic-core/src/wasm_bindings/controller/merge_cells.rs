@@ -0,0 +1,30 @@
+use super::*;
+
+#[wasm_bindgen]
+impl GridController {
+    /// Merges each finite range in the selection into a single cell.
+    #[wasm_bindgen(js_name = "mergeCells")]
+    pub fn js_merge_cells(
+        &mut self,
+        selection: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let selection =
+            serde_json::from_str(&selection).map_err(|_| "Invalid selection".to_string())?;
+        self.merge_cells(&selection, cursor);
+        Ok(())
+    }
+
+    /// Unmerges all merged regions that intersect the selection.
+    #[wasm_bindgen(js_name = "unmergeCells")]
+    pub fn js_unmerge_cells(
+        &mut self,
+        selection: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let selection =
+            serde_json::from_str(&selection).map_err(|_| "Invalid selection".to_string())?;
+        self.unmerge_cells(&selection, cursor);
+        Ok(())
+    }
+}
@@ -11,9 +11,13 @@ pub mod cells;
 pub mod clipboard;
 pub mod code;
 pub mod col_row;
+pub mod comments;
+pub mod conditional_formats;
 pub mod export;
 pub mod formatting;
 pub mod import;
+pub mod merge_cells;
+pub mod protections;
 pub mod render;
 pub mod search;
 pub mod sheet_info;
@@ -22,6 +26,7 @@ pub mod sheets;
 pub mod summarize;
 pub mod transactions;
 pub mod validation;
+pub mod what_if;
 pub mod worker;
 
 #[wasm_bindgen]
@@ -54,11 +59,11 @@ impl GridController {
                         })
                         .collect::<Vec<SheetInfo>>();
                     if let Ok(sheets_info) = serde_json::to_string(&sheets_info) {
-                        crate::wasm_bindings::js::jsSheetInfo(sheets_info);
+                        grid.host().sheet_info(sheets_info);
                     }
                     if !html.is_empty() {
                         if let Ok(html) = serde_json::to_string(&html) {
-                            crate::wasm_bindings::js::jsHtmlOutput(html);
+                            grid.host().html_output(html);
                         }
                     }
                     grid.sheet_ids().iter().for_each(|sheet_id| {
@@ -67,24 +72,25 @@ impl GridController {
                             let code = sheet.get_all_render_code_cells();
                             if !code.is_empty() {
                                 if let Ok(code) = serde_json::to_string(&code) {
-                                    crate::wasm_bindings::js::jsSheetCodeCell(
-                                        sheet_id.to_string(),
-                                        code,
-                                    );
+                                    grid.host().sheet_code_cells(sheet_id.to_string(), code);
                                 }
                             }
 
                             // sends all images to the client
-                            sheet.send_all_images();
+                            sheet.send_all_images(grid.host());
 
                             // sends all validations to the client
-                            sheet.send_all_validations();
+                            sheet.send_all_validations(grid.host());
 
                             // sends all validation warnings to the client
-                            sheet.send_all_validation_warnings();
+                            sheet.send_all_validation_warnings(grid.host());
 
                             // sends all borders to the client
-                            sheet.borders.send_sheet_borders(*sheet_id);
+                            sheet.borders.send_sheet_borders(
+                                grid.host(),
+                                *sheet_id,
+                                &sheet.merge_cells,
+                            );
                         }
                     });
                 }
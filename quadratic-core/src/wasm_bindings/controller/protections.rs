@@ -0,0 +1,65 @@
+//! WASM functions for protected ranges
+
+use uuid::Uuid;
+
+use crate::grid::sheet::protections::{ProtectedRange, ProtectionEditor, ProtectionRole};
+
+use super::*;
+
+#[wasm_bindgen]
+impl GridController {
+    /// Returns the protected ranges for a sheet
+    #[wasm_bindgen(js_name = "getProtectedRanges")]
+    pub fn js_protected_ranges(&self, sheet_id: String) -> Result<JsValue, JsValue> {
+        if let Ok(sheet_id) = SheetId::from_str(&sheet_id) {
+            Ok(serde_wasm_bindgen::to_value(
+                &self.protected_ranges(sheet_id),
+            )?)
+        } else {
+            Err(JsValue::from_str("Invalid sheet id"))
+        }
+    }
+
+    /// Creates or updates a protected range
+    #[wasm_bindgen(js_name = "setProtectedRange")]
+    pub fn js_set_protected_range(
+        &mut self,
+        sheet_id: String,
+        range: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let sheet_id = SheetId::from_str(&sheet_id).map_err(|_| "Invalid sheet id".to_string())?;
+        let range = serde_json::from_str::<ProtectedRange>(&range)
+            .map_err(|_| "Invalid protected range".to_string())?;
+        self.set_protected_range(sheet_id, range, cursor);
+        Ok(())
+    }
+
+    /// Deletes a protected range
+    #[wasm_bindgen(js_name = "removeProtectedRange")]
+    pub fn js_remove_protected_range(
+        &mut self,
+        sheet_id: String,
+        range_id: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let sheet_id = SheetId::from_str(&sheet_id).map_err(|_| "Invalid sheet id".to_string())?;
+        let range_id = Uuid::from_str(&range_id).map_err(|_| "Invalid range id".to_string())?;
+        self.remove_protected_range(sheet_id, range_id, cursor);
+        Ok(())
+    }
+
+    /// Sets the user making local changes, which is checked against
+    /// protected ranges. `roles` is a JSON array of ProtectionRole.
+    #[wasm_bindgen(js_name = "setProtectionEditor")]
+    pub fn js_set_protection_editor(
+        &mut self,
+        user_id: String,
+        roles: String,
+    ) -> Result<(), String> {
+        let roles = serde_json::from_str::<Vec<ProtectionRole>>(&roles)
+            .map_err(|_| "Invalid protection roles".to_string())?;
+        self.set_protection_editor(Some(ProtectionEditor { user_id, roles }));
+        Ok(())
+    }
+}
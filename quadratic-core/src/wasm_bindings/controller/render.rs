@@ -12,7 +12,7 @@ impl GridController {
         let Some(sheet) = self.try_sheet_from_string_id(sheet_id) else {
             return Result::Err("Sheet not found".into());
         };
-        sheet.send_validation_warnings_rect(rect);
+        sheet.send_validation_warnings_rect(self.host(), rect);
         let output = sheet.get_render_cells(rect);
         Ok(serde_wasm_bindgen::to_value(&output).map_err(|e| e.to_string())?)
     }
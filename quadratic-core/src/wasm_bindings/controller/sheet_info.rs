@@ -10,6 +10,11 @@ pub struct SheetInfo {
     pub order: String,
     pub color: Option<String>,
     pub offsets: String,
+    pub outline: String,
+    pub merge_cells: String,
+    pub conditional_formats: String,
+    pub comments: String,
+    pub protections: String,
     pub bounds: GridBounds,
     pub bounds_without_formatting: GridBounds,
 }
@@ -17,12 +22,25 @@ pub struct SheetInfo {
 impl From<&Sheet> for SheetInfo {
     fn from(sheet: &Sheet) -> Self {
         let offsets = serde_json::to_string(&sheet.offsets).unwrap_or("".to_string());
+        let outline = serde_json::to_string(&sheet.outline).unwrap_or("".to_string());
+        let merge_cells = serde_json::to_string(&sheet.merge_cells).unwrap_or("".to_string());
+        let conditional_formats = sheet
+            .conditional_formats
+            .to_string()
+            .unwrap_or("".to_string());
+        let comments = sheet.comments.to_string().unwrap_or("".to_string());
+        let protections = sheet.protections.to_string().unwrap_or("".to_string());
         Self {
             sheet_id: sheet.id.to_string(),
             name: sheet.name.clone(),
             order: sheet.order.clone(),
             color: sheet.color.clone(),
             offsets,
+            outline,
+            merge_cells,
+            conditional_formats,
+            comments,
+            protections,
             bounds: sheet.bounds(false),
             bounds_without_formatting: sheet.bounds(true),
         }
@@ -60,6 +60,40 @@ impl GridController {
         }
     }
 
+    /// Replaces the grid with a checkpoint file and applies the transactions
+    /// after it.
+    #[wasm_bindgen(js_name = "receiveCatchUp")]
+    pub fn js_receive_catch_up(
+        &mut self,
+        file: Vec<u8>,
+        checkpoint_sequence_num: u32,
+        transactions: JsValue,
+    ) -> Result<(), JsValue> {
+        let grid = file::import(file)
+            .map_err(|e| JsValue::from_str(&format!("Failed to import checkpoint: {e}")))?;
+        match serde_wasm_bindgen::from_value::<Vec<TransactionServer>>(transactions) {
+            Ok(transactions) => {
+                self.received_catch_up(grid, checkpoint_sequence_num as u64, transactions);
+                Ok(())
+            }
+            Err(e) => Err(JsValue::from_str(&format!(
+                "Invalid transactions received in receiveCatchUp: {e}"
+            ))),
+        }
+    }
+
+    /// Rolls back a transaction that the server rejected.
+    #[wasm_bindgen(js_name = "receiveRejectedTransaction")]
+    pub fn js_receive_rejected_transaction(
+        &mut self,
+        transaction_id: String,
+    ) -> Result<(), JsValue> {
+        let transaction_id = Uuid::parse_str(&transaction_id)
+            .map_err(|e| JsValue::from_str(&format!("Invalid transaction id: {}", e)))?;
+        self.received_rejected_transaction(transaction_id);
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = "applyOfflineUnsavedTransaction")]
     pub fn js_apply_offline_unsaved_transaction(
         &mut self,
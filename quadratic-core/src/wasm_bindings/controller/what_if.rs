@@ -0,0 +1,55 @@
+//! WASM functions for Goal Seek and data tables
+
+use crate::controller::operations::what_if::DataTableInput;
+
+use super::*;
+
+#[wasm_bindgen]
+impl GridController {
+    /// Changes the input cell so the formula cell equals `target`. Returns a
+    /// GoalSeekResult.
+    #[wasm_bindgen(js_name = "goalSeek")]
+    pub fn js_goal_seek(
+        &mut self,
+        formula: String,
+        target: f64,
+        input: String,
+        cursor: Option<String>,
+    ) -> Result<String, String> {
+        let formula = serde_json::from_str::<SheetPos>(&formula)
+            .map_err(|_| "Invalid formula cell".to_string())?;
+        let input = serde_json::from_str::<SheetPos>(&input)
+            .map_err(|_| "Invalid input cell".to_string())?;
+        let result = self
+            .goal_seek(formula, target, input, cursor)
+            .map_err(|e| e.to_string())?;
+        serde_json::to_string(&result).map_err(|e| e.to_string())
+    }
+
+    /// Writes a data table at `output`. `column_input` and `row_input` are
+    /// optional DataTableInputs.
+    #[wasm_bindgen(js_name = "dataTable")]
+    pub fn js_data_table(
+        &mut self,
+        formula: String,
+        column_input: Option<String>,
+        row_input: Option<String>,
+        output: String,
+        cursor: Option<String>,
+    ) -> Result<(), String> {
+        let formula = serde_json::from_str::<SheetPos>(&formula)
+            .map_err(|_| "Invalid formula cell".to_string())?;
+        let output = serde_json::from_str::<SheetPos>(&output)
+            .map_err(|_| "Invalid output cell".to_string())?;
+        let parse_input = |input: Option<String>| {
+            input
+                .map(|input| serde_json::from_str::<DataTableInput>(&input))
+                .transpose()
+                .map_err(|_| "Invalid data table input".to_string())
+        };
+        let column_input = parse_input(column_input)?;
+        let row_input = parse_input(row_input)?;
+        self.data_table(formula, column_input, row_input, output, cursor)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -90,6 +90,7 @@ extern "C" {
     pub fn jsTransactionStart(transaction_id: String, name: String);
     pub fn addUnsentTransaction(transaction_id: String, transaction: String, operations: u32);
     pub fn jsSendTransaction(transaction_id: String, transaction: Vec<u8>);
+    pub fn jsSendComment(transaction_id: String, transaction: Vec<u8>);
 
     pub fn jsTransactionProgress(transaction_id: String, remaining_operations: i32);
 
@@ -529,6 +530,15 @@ pub fn jsSendTransaction(transaction_id: String, _transaction: Vec<u8>) {
     ));
 }
 
+#[cfg(test)]
+#[allow(non_snake_case)]
+pub fn jsSendComment(transaction_id: String, _transaction: Vec<u8>) {
+    TEST_ARRAY.lock().unwrap().push(TestFunction::new(
+        "jsSendComment",
+        transaction_id.to_string(),
+    ));
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 pub fn jsTransactionProgress(transaction_id: String, remaining_operations: i32) {
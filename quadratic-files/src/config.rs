@@ -27,6 +27,11 @@ pub(crate) struct Config {
     pub(crate) files_per_check: i64,
     pub(crate) truncate_file_check_s: i64,
     pub(crate) truncate_transaction_age_days: i64,
+    pub(crate) retention_check_s: i64,
+    pub(crate) retention_keep_last: u64,
+    pub(crate) retention_keep_days: u64,
+    pub(crate) retention_keep_weeks: u64,
+    pub(crate) recalculate_formulas: bool,
     pub(crate) environment: Environment,
 
     pub(crate) pubsub_host: String,
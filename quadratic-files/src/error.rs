@@ -32,6 +32,9 @@ pub enum FilesError {
     #[error("Unable to export file {0}: {1}")]
     ExportFile(String, String),
 
+    #[error("File permissions error: {0}")]
+    FilePermissions(String),
+
     #[error("Unable to import file {0}: {1}")]
     ImportFile(String, String),
 
@@ -78,6 +81,7 @@ impl IntoResponse for FilesError {
     fn into_response(self) -> Response {
         let (status, error) = match &self {
             FilesError::Authentication(error) => (StatusCode::UNAUTHORIZED, clean_errors(error)),
+            FilesError::FilePermissions(error) => (StatusCode::FORBIDDEN, clean_errors(error)),
             FilesError::InternalServer(error) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, clean_errors(error))
             }
@@ -22,6 +22,7 @@ use quadratic_rust_shared::{
 
 use crate::{
     error::{FilesError, Result},
+    history::add_checkpoint_to_history,
     state::{settings::Settings, State},
     truncate::{add_processed_transaction, processed_transaction_key},
 };
@@ -38,9 +39,33 @@ pub(crate) fn export_file(key: &str, grid: Grid) -> Result<Vec<u8>> {
     export(grid).map_err(|e| FilesError::ExportFile(key.into(), e.to_string()))
 }
 
-/// Apply a vec of operations to the grid
-pub(crate) fn apply_transaction(grid: &mut GridController, operations: Vec<Operation>) {
-    grid.server_apply_transaction(operations, None)
+/// Apply a vec of operations to the grid.  When `recalculate` is set, the
+/// formulas that depend on the changed cells are recalculated, so that stale
+/// results from clients that disconnected mid-calculation are not stored.
+pub(crate) fn apply_transaction(
+    grid: &mut GridController,
+    operations: Vec<Operation>,
+    recalculate: bool,
+) {
+    if !recalculate {
+        grid.server_apply_transaction(operations, None);
+        return;
+    }
+
+    let stale = grid.server_apply_transaction_and_recalculate(operations, None);
+
+    // other code cells need a client to run them
+    if !stale.is_empty() {
+        tracing::trace!(
+            "{} code cell(s) could not be recalculated: {}",
+            stale.len(),
+            stale
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
 }
 
 /// Exports a .grid file
@@ -69,6 +94,7 @@ pub(crate) async fn process_transactions(
     checkpoint_sequence_num: u64,
     final_sequence_num: u64,
     operations: Vec<Operation>,
+    recalculate: bool,
 ) -> Result<u64> {
     let mut grid = get_and_load_object(
         storage,
@@ -78,7 +104,7 @@ pub(crate) async fn process_transactions(
     .await?;
     let key = key(file_id, final_sequence_num);
 
-    apply_transaction(&mut grid, operations);
+    apply_transaction(&mut grid, operations, recalculate);
     let body = export_file(&key, grid.into_grid())?;
 
     storage.write(&key, &body.into()).await?;
@@ -99,6 +125,7 @@ pub(crate) async fn process_queue_for_room(
         storage,
         quadratic_api_uri,
         m2m_auth_token,
+        recalculate_formulas,
         ..
     } = &state.settings;
 
@@ -168,9 +195,28 @@ pub(crate) async fn process_queue_for_room(
         checkpoint_sequence_num,
         last_sequence_num,
         operations,
+        *recalculate_formulas,
     )
     .await?;
 
+    // record the checkpoint in the file's version history, the checkpoint is
+    // already written so a failure here only hides it from the history
+    if let Err(error) = add_checkpoint_to_history(
+        storage,
+        *file_id,
+        last_sequence_num,
+        &key(*file_id, last_sequence_num),
+    )
+    .await
+    {
+        tracing::warn!(
+            "Error recording checkpoint {last_sequence_num} for file {file_id} in history: {error}"
+        );
+    }
+
+    // the new checkpoint may push older ones out of the retention policy
+    state.retention_files.lock().await.insert(*file_id);
+
     // convert keys to &str requires 2 iterations
     let keys = sequence_numbers
         .iter()
@@ -250,7 +296,7 @@ pub(crate) async fn process(state: &Arc<State>, active_channels: &str) -> Result
     Ok(())
 }
 
-fn decompress_and_deserialize<T: DeserializeOwned>(data: Vec<u8>) -> Result<T> {
+pub(crate) fn decompress_and_deserialize<T: DeserializeOwned>(data: Vec<u8>) -> Result<T> {
     Transaction::decompress_and_deserialize::<T>(&data)
         .map_err(|e| FilesError::Serialization(e.to_string()))
 }
@@ -258,7 +304,7 @@ fn decompress_and_deserialize<T: DeserializeOwned>(data: Vec<u8>) -> Result<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use quadratic_core::{CellValue, Pos, SheetPos};
+    use quadratic_core::{grid::CodeCellLanguage, CellValue, Pos, SheetPos};
 
     #[test]
     fn loads_a_file_and_applies_a_transaction_and_exports_the_file() {
@@ -292,7 +338,7 @@ mod tests {
         );
 
         // apply a transaction to the file
-        apply_transaction(&mut gc, transaction.operations);
+        apply_transaction(&mut gc, transaction.operations, false);
         let sheet = gc.grid().try_sheet(sheet_id).unwrap();
 
         assert_eq!(
@@ -304,6 +350,42 @@ mod tests {
         assert!(grid.is_ok());
     }
 
+    #[test]
+    fn recalculates_formulas_when_applying_a_transaction() {
+        let mut client = GridController::test();
+        let sheet_id = client.sheet_ids()[0];
+        let sheet_pos = |x, y| SheetPos { x, y, sheet_id };
+        client.set_cell_value(sheet_pos(1, 1), "1".into(), None);
+        client.set_code_cell(
+            sheet_pos(2, 1),
+            CodeCellLanguage::Formula,
+            "A1 + 1".into(),
+            None,
+        );
+
+        // a transaction that changed A1 without its recalculated formula
+        let operations = vec![Operation::SetCellValues {
+            sheet_pos: sheet_pos(1, 1),
+            values: CellValue::Number(5.into()).into(),
+        }];
+
+        let mut stale = GridController::from_grid(client.grid().clone(), 0);
+        apply_transaction(&mut stale, operations.clone(), false);
+        let sheet = stale.grid().try_sheet(sheet_id).unwrap();
+        assert_eq!(
+            sheet.display_value(Pos { x: 2, y: 1 }),
+            Some(CellValue::Number(2.into()))
+        );
+
+        let mut recalculated = GridController::from_grid(client.grid().clone(), 0);
+        apply_transaction(&mut recalculated, operations, true);
+        let sheet = recalculated.grid().try_sheet(sheet_id).unwrap();
+        assert_eq!(
+            sheet.display_value(Pos { x: 2, y: 1 }),
+            Some(CellValue::Number(6.into()))
+        );
+    }
+
     #[tokio::test]
     async fn processes_a_file() {
         // let state = new_arc_state().await;
@@ -0,0 +1,420 @@
+//! Version History
+//!
+//! Every processed checkpoint is recorded in its own history object in
+//! storage, so recording and removing checkpoints never rewrite each other's
+//! entries.  A file can be materialized at any sequence number by loading the
+//! nearest earlier checkpoint and replaying the transactions after it that are
+//! still in the pubsub channel.  Two versions can be diffed, and restoring
+//! produces a new transaction, so the history itself is never rewritten.
+
+use axum::{extract::Path, response::IntoResponse, Extension, Json};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use quadratic_core::{
+    controller::{
+        operations::operation::Operation,
+        transaction::{Transaction, TransactionServer},
+        GridController,
+    },
+    grid::{diff::GridDiff, file::CURRENT_VERSION},
+};
+use quadratic_rust_shared::{
+    pubsub::PubSub as PubSubTrait,
+    quadratic_api::get_file_checkpoint,
+    storage::{Storage, StorageContainer},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    error::{FilesError, Result},
+    file::{apply_transaction, decompress_and_deserialize, export_file, get_and_load_object},
+    permissions::{validate_user_can_edit_file, validate_user_can_view_file},
+    state::State,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct Checkpoint {
+    pub(crate) sequence_num: u64,
+    pub(crate) key: String,
+    pub(crate) version: String,
+    pub(crate) created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RestoreResponse {
+    sequence_num: u64,
+    operations: String,
+}
+
+pub(crate) fn history_prefix(file_id: Uuid) -> String {
+    format!("{file_id}-history.")
+}
+
+/// The sequence number is zero padded so keys sort in sequence order
+pub(crate) fn history_key(file_id: Uuid, sequence_num: u64) -> String {
+    format!("{}{sequence_num:020}.json", history_prefix(file_id))
+}
+
+/// Read the checkpoints recorded for a file, sorted by sequence number
+pub(crate) async fn read_history(
+    storage: &StorageContainer,
+    file_id: Uuid,
+) -> Result<Vec<Checkpoint>> {
+    let prefix = history_prefix(file_id);
+    let keys = storage.list(&prefix).await?;
+    let mut history = Vec::with_capacity(keys.len());
+
+    for key in keys {
+        let body = match storage.read(&key).await.map_err(FilesError::from) {
+            Ok(body) => body,
+            // removed since it was listed
+            Err(FilesError::NotFound(_)) => continue,
+            Err(error) => return Err(error),
+        };
+        history.push(serde_json::from_slice::<Checkpoint>(&body)?);
+    }
+
+    history.sort_by_key(|checkpoint| checkpoint.sequence_num);
+
+    Ok(history)
+}
+
+/// Record a newly written checkpoint in the file's history
+pub(crate) async fn add_checkpoint_to_history(
+    storage: &StorageContainer,
+    file_id: Uuid,
+    sequence_num: u64,
+    key: &str,
+) -> Result<()> {
+    let checkpoint = Checkpoint {
+        sequence_num,
+        key: key.to_owned(),
+        version: CURRENT_VERSION.into(),
+        created_at: Utc::now(),
+    };
+    let body = serde_json::to_vec(&checkpoint)?;
+    storage
+        .write(&history_key(file_id, sequence_num), &body.into())
+        .await?;
+
+    Ok(())
+}
+
+/// Remove deleted checkpoints from the file's history.  Only the objects of
+/// the deleted checkpoints are touched, so a checkpoint recorded at the same
+/// time is never lost.
+pub(crate) async fn remove_from_history(
+    storage: &StorageContainer,
+    file_id: Uuid,
+    sequence_nums: &[u64],
+) -> Result<()> {
+    let keys = sequence_nums
+        .iter()
+        .map(|sequence_num| history_key(file_id, *sequence_num))
+        .collect::<Vec<_>>();
+
+    // only checkpoints that were recorded have a history object
+    for key in storage.list(&history_prefix(file_id)).await? {
+        if keys.contains(&key) {
+            storage.delete(&key).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// List the checkpoints stored for a file.  The checkpoint known to
+/// quadratic-api is included in case it predates the history object.
+pub(crate) async fn list_checkpoints(state: &Arc<State>, file_id: Uuid) -> Result<Vec<Checkpoint>> {
+    let settings = &state.settings;
+    let mut history = read_history(&settings.storage, file_id).await?;
+
+    if let Ok(last_checkpoint) = get_file_checkpoint(
+        &settings.quadratic_api_uri,
+        &settings.m2m_auth_token,
+        &file_id,
+    )
+    .await
+    {
+        let sequence_num = last_checkpoint.sequence_number;
+
+        if !history.iter().any(|c| c.sequence_num == sequence_num) {
+            history.push(Checkpoint {
+                sequence_num,
+                key: last_checkpoint.s3_key,
+                version: last_checkpoint.version,
+                created_at: Utc::now(),
+            });
+            history.sort_by_key(|checkpoint| checkpoint.sequence_num);
+        }
+    }
+
+    Ok(history)
+}
+
+/// Load the grid as it was at `sequence_num`
+pub(crate) async fn get_grid_at_sequence_num(
+    state: &Arc<State>,
+    file_id: Uuid,
+    sequence_num: u64,
+) -> Result<GridController> {
+    let checkpoint = list_checkpoints(state, file_id)
+        .await?
+        .into_iter()
+        .rfind(|checkpoint| checkpoint.sequence_num <= sequence_num)
+        .ok_or_else(|| {
+            FilesError::NotFound(format!(
+                "No checkpoint for file {file_id} at or before sequence number {sequence_num}"
+            ))
+        })?;
+
+    let mut grid = get_and_load_object(
+        &state.settings.storage,
+        &checkpoint.key,
+        checkpoint.sequence_num,
+    )
+    .await?;
+
+    if checkpoint.sequence_num == sequence_num {
+        return Ok(grid);
+    }
+
+    let transactions = state
+        .pubsub
+        .lock()
+        .await
+        .connection
+        .get_messages_from(
+            &file_id.to_string(),
+            &(checkpoint.sequence_num + 1).to_string(),
+            false,
+        )
+        .await?
+        .into_iter()
+        .map(|(_, message)| decompress_and_deserialize::<TransactionServer>(message))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .take_while(|transaction| transaction.sequence_num <= sequence_num)
+        .collect::<Vec<_>>();
+
+    // transactions older than the truncation age are no longer in pubsub
+    let expected = sequence_num - checkpoint.sequence_num;
+    if transactions.len() as u64 != expected {
+        return Err(FilesError::NotFound(format!(
+            "Transactions {} - {sequence_num} for file {file_id} are no longer available",
+            checkpoint.sequence_num + 1
+        )));
+    }
+
+    let operations = transactions
+        .into_iter()
+        .map(|transaction| decompress_and_deserialize::<Vec<Operation>>(transaction.operations))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    apply_transaction(&mut grid, operations, state.settings.recalculate_formulas);
+
+    Ok(grid)
+}
+
+/// Load the latest state of the file, including unprocessed transactions.
+/// Returns the sequence number of the state along with the grid.
+pub(crate) async fn get_latest_grid(
+    state: &Arc<State>,
+    file_id: Uuid,
+) -> Result<(u64, GridController)> {
+    let last_sequence_num = match state
+        .pubsub
+        .lock()
+        .await
+        .connection
+        .last_message(&file_id.to_string(), false)
+        .await
+    {
+        Ok((_, message)) => decompress_and_deserialize::<TransactionServer>(message)?.sequence_num,
+        Err(_) => 0,
+    };
+
+    let last_checkpoint = list_checkpoints(state, file_id)
+        .await?
+        .last()
+        .map_or(0, |checkpoint| checkpoint.sequence_num);
+
+    let sequence_num = last_sequence_num.max(last_checkpoint);
+    let grid = get_grid_at_sequence_num(state, file_id, sequence_num).await?;
+
+    Ok((sequence_num, grid))
+}
+
+/// List the checkpoints of a file
+pub(crate) async fn get_history(
+    Path(file_id): Path<Uuid>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<Json<Vec<Checkpoint>>> {
+    tracing::trace!("Get history for file {file_id}");
+
+    validate_user_can_view_file(&state, file_id, bearer.token()).await?;
+
+    Ok(Json(list_checkpoints(&state, file_id).await?))
+}
+
+/// Get the .grid file as it was at a sequence number
+pub(crate) async fn get_history_file(
+    Path((file_id, sequence_num)): Path<(Uuid, u64)>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<impl IntoResponse> {
+    tracing::trace!("Get file {file_id} at sequence number {sequence_num}");
+
+    validate_user_can_view_file(&state, file_id, bearer.token()).await?;
+
+    let grid = get_grid_at_sequence_num(&state, file_id, sequence_num).await?;
+    let key = &format!("{file_id}-{sequence_num}");
+    let body = export_file(key, grid.into_grid())?;
+
+    Ok(body.into_response())
+}
+
+/// Get the changes to a file between two sequence numbers
+pub(crate) async fn get_history_diff(
+    Path((file_id, from, to)): Path<(Uuid, u64, u64)>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<Json<GridDiff>> {
+    tracing::trace!("Diff file {file_id} from sequence number {from} to {to}");
+
+    validate_user_can_view_file(&state, file_id, bearer.token()).await?;
+
+    let old = get_grid_at_sequence_num(&state, file_id, from).await?;
+    let new = get_grid_at_sequence_num(&state, file_id, to).await?;
+
+    Ok(Json(GridDiff::new(old.grid(), new.grid())))
+}
+
+/// Get a transaction that restores the file to a sequence number.  The
+/// operations are compressed and base64 encoded, the same as transactions
+/// sent to quadratic-multiplayer.
+pub(crate) async fn get_history_restore(
+    Path((file_id, sequence_num)): Path<(Uuid, u64)>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<Json<RestoreResponse>> {
+    tracing::trace!("Restore file {file_id} to sequence number {sequence_num}");
+
+    validate_user_can_edit_file(&state, file_id, bearer.token()).await?;
+
+    let past = get_grid_at_sequence_num(&state, file_id, sequence_num).await?;
+    let (_, current) = get_latest_grid(&state, file_id).await?;
+    let operations = current.restore_grid_operations(past.grid());
+    let compressed = Transaction::serialize_and_compress(&operations)
+        .map_err(|e| FilesError::Serialization(e.to_string()))?;
+
+    Ok(Json(RestoreResponse {
+        sequence_num,
+        operations: STANDARD.encode(compressed),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permissions::MockFilePermissions;
+    use crate::test_util::new_state_with_file_permissions;
+    use axum::http::StatusCode;
+    use quadratic_rust_shared::quadratic_api::FilePermRole;
+    use quadratic_rust_shared::storage::file_system::{FileSystem, FileSystemConfig};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn records_checkpoints_in_history() {
+        let dir = tempdir().unwrap();
+        let storage = StorageContainer::FileSystem(FileSystem::new(FileSystemConfig {
+            path: dir.path().to_str().unwrap().to_string(),
+            encryption_keys: vec![],
+        }));
+        let file_id = Uuid::new_v4();
+
+        assert_eq!(read_history(&storage, file_id).await.unwrap(), vec![]);
+
+        add_checkpoint_to_history(&storage, file_id, 5, "key-5")
+            .await
+            .unwrap();
+        add_checkpoint_to_history(&storage, file_id, 2, "key-2")
+            .await
+            .unwrap();
+
+        let history = read_history(&storage, file_id).await.unwrap();
+        let sequence_nums = history.iter().map(|c| c.sequence_num).collect::<Vec<_>>();
+        assert_eq!(sequence_nums, vec![2, 5]);
+        assert_eq!(history[0].key, "key-2");
+        assert_eq!(history[0].version, CURRENT_VERSION);
+
+        // recording the same checkpoint again replaces it
+        add_checkpoint_to_history(&storage, file_id, 5, "key-5b")
+            .await
+            .unwrap();
+        remove_from_history(&storage, file_id, &[2, 7])
+            .await
+            .unwrap();
+
+        let history = read_history(&storage, file_id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].sequence_num, 5);
+        assert_eq!(history[0].key, "key-5b");
+    }
+
+    #[tokio::test]
+    async fn denies_history_to_users_without_permission() {
+        let file_permissions = Arc::new(MockFilePermissions::default());
+        let state = Extension(Arc::new(
+            new_state_with_file_permissions(file_permissions.clone()).await,
+        ));
+        let file_id = Uuid::new_v4();
+        let bearer = || TypedHeader(Authorization::bearer("token").unwrap());
+        let forbidden = |error: FilesError| {
+            assert!(matches!(error, FilesError::FilePermissions(_)));
+            assert_eq!(error.into_response().status(), StatusCode::FORBIDDEN);
+        };
+
+        // users who can't view the file can't read its history
+        file_permissions.set(file_id, vec![]);
+        forbidden(
+            get_history(Path(file_id), bearer(), state.clone())
+                .await
+                .unwrap_err(),
+        );
+        forbidden(
+            get_history_file(Path((file_id, 1)), bearer(), state.clone())
+                .await
+                .err()
+                .unwrap(),
+        );
+        forbidden(
+            get_history_diff(Path((file_id, 1, 2)), bearer(), state.clone())
+                .await
+                .unwrap_err(),
+        );
+        forbidden(
+            get_history_restore(Path((file_id, 1)), bearer(), state.clone())
+                .await
+                .unwrap_err(),
+        );
+
+        // viewers can't restore the file
+        file_permissions.set(file_id, vec![FilePermRole::FileView]);
+        forbidden(
+            get_history_restore(Path((file_id, 1)), bearer(), state.clone())
+                .await
+                .unwrap_err(),
+        );
+    }
+}
@@ -7,6 +7,10 @@ mod auth;
 mod config;
 mod error;
 mod file;
+mod history;
+mod permissions;
+mod protections;
+mod retention;
 mod server;
 mod state;
 mod storage;
@@ -0,0 +1,128 @@
+//! File Permissions
+//!
+//! The history and protections of a file are only served to users who can
+//! view it, and restoring a file requires being able to edit it.  A user's
+//! permissions are read from quadratic-api with their JWT.  State holds a
+//! `FilePermissions` so tests can replace quadratic-api with
+//! `MockFilePermissions`.
+
+use futures::future::BoxFuture;
+use quadratic_rust_shared::quadratic_api::{can_edit, can_view, get_file_perms, FilePermRole};
+use quadratic_rust_shared::SharedError;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+use crate::error::{FilesError, Result};
+use crate::state::State;
+
+pub(crate) trait FilePermissions: Debug + Send + Sync {
+    /// Get the permissions of the user making the request for a file
+    fn get<'a>(&'a self, file_id: Uuid, jwt: &'a str) -> BoxFuture<'a, Result<Vec<FilePermRole>>>;
+}
+
+/// Reads permissions from quadratic-api
+#[derive(Debug)]
+pub(crate) struct ApiFilePermissions {
+    pub(crate) quadratic_api_uri: String,
+}
+
+impl FilePermissions for ApiFilePermissions {
+    fn get<'a>(&'a self, file_id: Uuid, jwt: &'a str) -> BoxFuture<'a, Result<Vec<FilePermRole>>> {
+        Box::pin(async move {
+            match get_file_perms(&self.quadratic_api_uri, jwt.to_owned(), file_id).await {
+                Ok((permissions, _)) => Ok(permissions),
+                // the api forbids users without access to the file
+                Err(SharedError::QuadraticApi(error)) if error == "Forbidden" => Ok(vec![]),
+                Err(error) => Err(error.into()),
+            }
+        })
+    }
+}
+
+/// Permissions set by tests.  Users can view and edit other files.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct MockFilePermissions {
+    files: std::sync::Mutex<std::collections::HashMap<Uuid, Vec<FilePermRole>>>,
+}
+
+#[cfg(test)]
+impl MockFilePermissions {
+    pub(crate) fn set(&self, file_id: Uuid, permissions: Vec<FilePermRole>) {
+        self.files.lock().unwrap().insert(file_id, permissions);
+    }
+}
+
+#[cfg(test)]
+impl FilePermissions for MockFilePermissions {
+    fn get<'a>(&'a self, file_id: Uuid, _jwt: &'a str) -> BoxFuture<'a, Result<Vec<FilePermRole>>> {
+        let permissions = self
+            .files
+            .lock()
+            .unwrap()
+            .get(&file_id)
+            .cloned()
+            .unwrap_or_else(|| vec![FilePermRole::FileView, FilePermRole::FileEdit]);
+        Box::pin(async move { Ok(permissions) })
+    }
+}
+
+pub(crate) fn validate_can_view_file(roles: &[FilePermRole]) -> Result<()> {
+    if !(can_view(roles) || can_edit(roles)) {
+        return Err(FilesError::FilePermissions(
+            "You do not have permission to access this file".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_can_edit_file(roles: &[FilePermRole]) -> Result<()> {
+    if !can_edit(roles) {
+        return Err(FilesError::FilePermissions(
+            "You do not have permission to edit this file".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that the user making a request can view the file
+pub(crate) async fn validate_user_can_view_file(
+    state: &State,
+    file_id: Uuid,
+    jwt: &str,
+) -> Result<()> {
+    validate_can_view_file(&state.file_permissions.get(file_id, jwt).await?)
+}
+
+/// Check that the user making a request can edit the file
+pub(crate) async fn validate_user_can_edit_file(
+    state: &State,
+    file_id: Uuid,
+    jwt: &str,
+) -> Result<()> {
+    validate_can_edit_file(&state.file_permissions.get(file_id, jwt).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_can_view_file() {
+        assert!(validate_can_view_file(&[FilePermRole::FileView]).is_ok());
+        assert!(validate_can_view_file(&[FilePermRole::FileEdit]).is_ok());
+
+        let result = validate_can_view_file(&[FilePermRole::FileDelete]);
+        assert!(matches!(result, Err(FilesError::FilePermissions(_))));
+    }
+
+    #[test]
+    fn validates_can_edit_file() {
+        assert!(validate_can_edit_file(&[FilePermRole::FileEdit]).is_ok());
+
+        let result = validate_can_edit_file(&[FilePermRole::FileView]);
+        assert!(matches!(result, Err(FilesError::FilePermissions(_))));
+    }
+}
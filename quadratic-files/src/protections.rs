@@ -0,0 +1,80 @@
+//! Protected Ranges
+//!
+//! quadratic-multiplayer does not load files, so it gets the protected ranges
+//! and comment authors of a file from here before checking transactions
+//! against them.  The sequence number lets it apply the transactions that came
+//! after.
+
+use axum::{extract::Path, Extension, Json};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use quadratic_core::{
+    controller::operations::{
+        comments::{comment_authors, CommentAuthors},
+        protection::sheet_protections,
+    },
+    grid::{sheet::protections::Protections, SheetId},
+};
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    error::Result, history::get_latest_grid, permissions::validate_user_can_view_file, state::State,
+};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ProtectionsResponse {
+    pub(crate) sequence_num: u64,
+    pub(crate) protections: Vec<(SheetId, Protections)>,
+    pub(crate) comment_authors: CommentAuthors,
+}
+
+/// Get the protected ranges and comment authors of the latest state of a file
+pub(crate) async fn get_protections(
+    Path(file_id): Path<Uuid>,
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Extension<Arc<State>>,
+) -> Result<Json<ProtectionsResponse>> {
+    tracing::trace!("Get protected ranges for file {file_id}");
+
+    validate_user_can_view_file(&state, file_id, bearer.token()).await?;
+
+    let (sequence_num, grid) = get_latest_grid(&state, file_id).await?;
+
+    Ok(Json(ProtectionsResponse {
+        sequence_num,
+        protections: sheet_protections(grid.grid()).into_iter().collect(),
+        comment_authors: comment_authors(grid.grid()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::FilesError;
+    use crate::permissions::MockFilePermissions;
+    use crate::test_util::new_state_with_file_permissions;
+    use quadratic_rust_shared::quadratic_api::FilePermRole;
+
+    #[tokio::test]
+    async fn denies_protections_to_users_without_permission() {
+        let file_permissions = Arc::new(MockFilePermissions::default());
+        let state = Extension(Arc::new(
+            new_state_with_file_permissions(file_permissions.clone()).await,
+        ));
+        let file_id = Uuid::new_v4();
+        file_permissions.set(file_id, vec![FilePermRole::FileDelete]);
+
+        let error = get_protections(
+            Path(file_id),
+            TypedHeader(Authorization::bearer("token").unwrap()),
+            state,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, FilesError::FilePermissions(_)));
+    }
+}
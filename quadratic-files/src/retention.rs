@@ -0,0 +1,419 @@
+//! Checkpoint Retention
+//!
+//! Every batch of processed transactions writes a new checkpoint to storage.
+//! The retention job deletes old checkpoints, keeping the most recent ones
+//! along with the last checkpoint of each recent day and week.  The
+//! checkpoint known to quadratic-api is always kept, as it's where the next
+//! batch of transactions is applied.
+//!
+//! Only files that wrote a checkpoint are checked, by listing their own keys.
+//! Each checkpoint has its own history object (see history.rs), so deleting
+//! checkpoints never races with recording a new one.
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use quadratic_rust_shared::{
+    quadratic_api::get_file_checkpoint,
+    storage::{Storage, StorageContainer},
+};
+use std::{cmp::Reverse, collections::HashSet, sync::Arc};
+use uuid::Uuid;
+
+use crate::{config::Config, error::Result, history::remove_from_history, state::State};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RetentionPolicy {
+    /// Number of most recent checkpoints to keep
+    pub(crate) keep_last: usize,
+
+    /// Number of days to keep the last checkpoint of each day
+    pub(crate) keep_days: u64,
+
+    /// Number of weeks to keep the last checkpoint of each week
+    pub(crate) keep_weeks: u64,
+}
+
+impl RetentionPolicy {
+    pub(crate) fn new(config: &Config) -> Self {
+        RetentionPolicy {
+            keep_last: config.retention_keep_last as usize,
+            keep_days: config.retention_keep_days,
+            keep_weeks: config.retention_keep_weeks,
+        }
+    }
+}
+
+/// Parse a checkpoint key (`{file_id}-{sequence_num}.grid`) into its file id
+/// and sequence number.  Returns None for other keys.
+pub(crate) fn parse_checkpoint_key(key: &str) -> Option<(Uuid, u64)> {
+    let (file_id, sequence_num) = key.strip_suffix(".grid")?.rsplit_once('-')?;
+
+    Some((Uuid::parse_str(file_id).ok()?, sequence_num.parse().ok()?))
+}
+
+/// Monday of the date's week
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday() as u64)
+}
+
+/// Select the sequence numbers of the checkpoints to keep, given when each
+/// checkpoint was written.
+pub(crate) fn checkpoints_to_keep(
+    checkpoints: &[(u64, DateTime<Utc>)],
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> HashSet<u64> {
+    let mut checkpoints = checkpoints.to_vec();
+    checkpoints.sort_by_key(|(sequence_num, _)| Reverse(*sequence_num));
+
+    // the latest checkpoint is always kept
+    let mut keep = checkpoints
+        .iter()
+        .take(policy.keep_last.max(1))
+        .map(|(sequence_num, _)| *sequence_num)
+        .collect::<HashSet<_>>();
+
+    let today = now.date_naive();
+    let this_week = week_start(today);
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+
+    // checkpoints are newest first, so the first one seen in a day or week is
+    // its last
+    for (sequence_num, modified) in checkpoints {
+        let day = modified.date_naive();
+        let week = week_start(day);
+
+        if ((today - day).num_days() as u64) < policy.keep_days && days.insert(day) {
+            keep.insert(sequence_num);
+        }
+
+        if ((this_week - week).num_weeks() as u64) < policy.keep_weeks && weeks.insert(week) {
+            keep.insert(sequence_num);
+        }
+    }
+
+    keep
+}
+
+/// Delete a file's checkpoints that are not retained by the policy.
+/// `current_sequence_num` is the checkpoint known to quadratic-api.  Returns
+/// the sequence numbers of the deleted checkpoints.
+pub(crate) async fn delete_old_checkpoints(
+    storage: &StorageContainer,
+    file_id: Uuid,
+    mut checkpoints: Vec<(u64, String)>,
+    current_sequence_num: u64,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Result<Vec<u64>> {
+    if checkpoints.len() <= policy.keep_last {
+        return Ok(vec![]);
+    }
+
+    checkpoints.sort_by_key(|(sequence_num, _)| *sequence_num);
+    let mut modified = Vec::with_capacity(checkpoints.len());
+
+    for (sequence_num, key) in checkpoints.iter() {
+        modified.push((*sequence_num, storage.head(key).await?.modified));
+    }
+
+    let mut keep = checkpoints_to_keep(&modified, policy, now);
+    keep.insert(current_sequence_num);
+
+    let (keys, deleted): (Vec<_>, Vec<_>) = checkpoints
+        .into_iter()
+        .filter(|(sequence_num, _)| !keep.contains(sequence_num))
+        .map(|(sequence_num, key)| (key, sequence_num))
+        .unzip();
+
+    if deleted.is_empty() {
+        return Ok(deleted);
+    }
+
+    // remove the checkpoints from the history first so that they're never
+    // listed without their objects
+    remove_from_history(storage, file_id, &deleted).await?;
+
+    for key in keys {
+        storage.delete(&key).await?;
+    }
+
+    Ok(deleted)
+}
+
+/// Whether any of the remaining checkpoints are only kept by the daily or
+/// weekly windows, so they may be deleted by a later run.
+fn has_aging_checkpoints(
+    mut remaining: Vec<u64>,
+    current_sequence_num: u64,
+    policy: &RetentionPolicy,
+) -> bool {
+    remaining.sort_by_key(|sequence_num| Reverse(*sequence_num));
+    remaining
+        .into_iter()
+        .skip(policy.keep_last.max(1))
+        .any(|sequence_num| sequence_num != current_sequence_num)
+}
+
+/// Apply the retention policy to the files in `state.retention_files`.
+///
+/// Files are added when a checkpoint is written, and stay until none of their
+/// checkpoints can age out of the policy, so the bucket is never listed as a
+/// whole.  Files that were already trimmed before a restart are picked up
+/// again with their next checkpoint.
+pub(crate) async fn apply_retention_policy(
+    state: &Arc<State>,
+    policy: &RetentionPolicy,
+) -> Result<()> {
+    let settings = &state.settings;
+
+    // take the files so that checkpoints written during this run add them again
+    let files = std::mem::take(&mut *state.retention_files.lock().await);
+    let mut retry = HashSet::new();
+
+    for file_id in files {
+        let keys = match settings.storage.list(&format!("{file_id}-")).await {
+            Ok(keys) => keys,
+            Err(error) => {
+                tracing::warn!("Skipping retention for file {file_id}: {error}");
+                retry.insert(file_id);
+                continue;
+            }
+        };
+
+        let checkpoints = keys
+            .into_iter()
+            .filter_map(|key| {
+                let (id, sequence_num) = parse_checkpoint_key(&key)?;
+                (id == file_id).then_some((sequence_num, key))
+            })
+            .collect::<Vec<_>>();
+
+        if checkpoints.len() <= policy.keep_last {
+            continue;
+        }
+
+        // without the current checkpoint, nothing can safely be deleted
+        let current_sequence_num = match get_file_checkpoint(
+            &settings.quadratic_api_uri,
+            &settings.m2m_auth_token,
+            &file_id,
+        )
+        .await
+        {
+            Ok(last_checkpoint) => last_checkpoint.sequence_number,
+            Err(error) => {
+                tracing::warn!("Skipping retention for file {file_id}: {error}");
+                retry.insert(file_id);
+                continue;
+            }
+        };
+
+        let sequence_nums = checkpoints
+            .iter()
+            .map(|(sequence_num, _)| *sequence_num)
+            .collect::<Vec<_>>();
+
+        match delete_old_checkpoints(
+            &settings.storage,
+            file_id,
+            checkpoints,
+            current_sequence_num,
+            policy,
+            Utc::now(),
+        )
+        .await
+        {
+            Ok(deleted) => {
+                if !deleted.is_empty() {
+                    tracing::info!("Deleted {} checkpoint(s) for file {file_id}", deleted.len());
+                }
+
+                let remaining = sequence_nums
+                    .into_iter()
+                    .filter(|sequence_num| !deleted.contains(sequence_num))
+                    .collect();
+
+                if has_aging_checkpoints(remaining, current_sequence_num, policy) {
+                    retry.insert(file_id);
+                }
+            }
+            Err(error) => {
+                tracing::error!("Error applying retention to file {file_id}: {error}");
+                retry.insert(file_id);
+            }
+        }
+    }
+
+    state.retention_files.lock().await.extend(retry);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::key;
+    use crate::history::{add_checkpoint_to_history, history_key, read_history};
+    use crate::test_util::new_state;
+    use chrono::TimeZone;
+    use quadratic_rust_shared::storage::file_system::{FileSystem, FileSystemConfig};
+    use tempfile::tempdir;
+
+    fn policy(keep_last: usize, keep_days: u64, keep_weeks: u64) -> RetentionPolicy {
+        RetentionPolicy {
+            keep_last,
+            keep_days,
+            keep_weeks,
+        }
+    }
+
+    fn sorted(keep: HashSet<u64>) -> Vec<u64> {
+        let mut keep = keep.into_iter().collect::<Vec<_>>();
+        keep.sort();
+        keep
+    }
+
+    #[test]
+    fn parses_checkpoint_keys() {
+        let file_id = Uuid::new_v4();
+
+        assert_eq!(parse_checkpoint_key(&key(file_id, 12)), Some((file_id, 12)));
+        assert_eq!(parse_checkpoint_key(&history_key(file_id, 12)), None);
+        assert_eq!(parse_checkpoint_key(&format!("{file_id}-x.grid")), None);
+        assert_eq!(parse_checkpoint_key("thumbnail-1.grid"), None);
+    }
+
+    #[test]
+    fn keeps_last_daily_and_weekly_checkpoints() {
+        // Wednesday
+        let now = Utc.with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap();
+        let at = |day: u32, hour: u32| Utc.with_ymd_and_hms(2024, 5, day, hour, 0, 0).unwrap();
+        let checkpoints = vec![
+            (1, at(1, 9)),   // two weeks before last week
+            (2, at(6, 9)),   // last week
+            (3, at(8, 9)),   // last week, but a later day
+            (4, at(13, 9)),  // Monday
+            (5, at(13, 10)), // Monday, later
+            (6, at(14, 9)),  // yesterday
+            (7, at(15, 9)),  // today
+            (8, at(15, 10)), // today, later
+        ];
+
+        // only the latest checkpoint is kept without days or weeks
+        let keep = checkpoints_to_keep(&checkpoints, &policy(0, 0, 0), now);
+        assert_eq!(sorted(keep), vec![8]);
+
+        let keep = checkpoints_to_keep(&checkpoints, &policy(2, 0, 0), now);
+        assert_eq!(sorted(keep), vec![7, 8]);
+
+        // the last checkpoint of today, yesterday and Monday
+        let keep = checkpoints_to_keep(&checkpoints, &policy(1, 3, 0), now);
+        assert_eq!(sorted(keep), vec![5, 6, 8]);
+
+        // the last checkpoint of this week and last week
+        let keep = checkpoints_to_keep(&checkpoints, &policy(1, 0, 2), now);
+        assert_eq!(sorted(keep), vec![3, 8]);
+
+        let keep = checkpoints_to_keep(&checkpoints, &policy(1, 1, 4), now);
+        assert_eq!(sorted(keep), vec![1, 3, 8]);
+    }
+
+    #[tokio::test]
+    async fn deletes_old_checkpoints() {
+        let dir = tempdir().unwrap();
+        let storage = StorageContainer::FileSystem(FileSystem::new(FileSystemConfig {
+            path: dir.path().to_str().unwrap().to_string(),
+            encryption_keys: vec![],
+        }));
+        let file_id = Uuid::new_v4();
+
+        for sequence_num in 1..=5 {
+            let key = key(file_id, sequence_num);
+            storage.write(&key, &"grid".into()).await.unwrap();
+            add_checkpoint_to_history(&storage, file_id, sequence_num, &key)
+                .await
+                .unwrap();
+        }
+
+        let checkpoints = storage
+            .list(&format!("{file_id}-"))
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|key| Some((parse_checkpoint_key(&key)?.1, key)))
+            .collect::<Vec<_>>();
+        assert_eq!(checkpoints.len(), 5);
+
+        // all checkpoints were written today, so only the last 2 and the
+        // current checkpoint are kept
+        let deleted = delete_old_checkpoints(
+            &storage,
+            file_id,
+            checkpoints,
+            1,
+            &policy(2, 7, 4),
+            Utc::now(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(deleted, vec![2, 3]);
+
+        let keys = storage.list(&format!("{file_id}-")).await.unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                key(file_id, 1),
+                key(file_id, 4),
+                key(file_id, 5),
+                history_key(file_id, 1),
+                history_key(file_id, 4),
+                history_key(file_id, 5),
+            ]
+        );
+
+        let history = read_history(&storage, file_id).await.unwrap();
+        let sequence_nums = history.iter().map(|c| c.sequence_num).collect::<Vec<_>>();
+        assert_eq!(sequence_nums, vec![1, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn keeps_files_when_listing_fails() {
+        let dir = tempdir().unwrap();
+        let mut state = new_state().await;
+
+        // listing a missing directory fails
+        state.settings.storage = StorageContainer::FileSystem(FileSystem::new(FileSystemConfig {
+            path: dir.path().join("missing").to_str().unwrap().to_string(),
+            encryption_keys: vec![],
+        }));
+        let state = Arc::new(state);
+        let file_ids = HashSet::from([Uuid::new_v4(), Uuid::new_v4()]);
+        state
+            .retention_files
+            .lock()
+            .await
+            .extend(file_ids.iter().copied());
+
+        apply_retention_policy(&state, &policy(2, 7, 4))
+            .await
+            .unwrap();
+
+        // every file is retried by the next run
+        assert_eq!(*state.retention_files.lock().await, file_ids);
+    }
+
+    #[test]
+    fn tracks_files_with_aging_checkpoints() {
+        let policy = policy(2, 7, 4);
+
+        // only the last 2 checkpoints remain
+        assert!(!has_aging_checkpoints(vec![4, 5], 5, &policy));
+
+        // the current checkpoint is kept regardless of age
+        assert!(!has_aging_checkpoints(vec![1, 4, 5], 1, &policy));
+
+        // checkpoint 3 is only kept by the daily or weekly windows
+        assert!(has_aging_checkpoints(vec![1, 3, 4, 5], 1, &policy));
+    }
+}
@@ -15,6 +15,9 @@ use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use crate::history::{get_history, get_history_diff, get_history_file, get_history_restore};
+use crate::protections::get_protections;
+use crate::retention::{apply_retention_policy, RetentionPolicy};
 use crate::storage::{get_presigned_storage, get_storage};
 use crate::truncate::truncate_processed_transactions;
 use crate::{
@@ -61,6 +64,24 @@ pub(crate) fn app(state: Arc<State>) -> Router {
                 .post(upload_storage),
         )
         //
+        // list the checkpoints of a file
+        .route("/history/:file_id", get(get_history))
+        //
+        // get a file as it was at a sequence number
+        .route("/history/:file_id/:sequence_num", get(get_history_file))
+        //
+        // get the changes to a file between two sequence numbers
+        .route("/history/:file_id/diff/:from/:to", get(get_history_diff))
+        //
+        // get a transaction that restores a file to a sequence number
+        .route(
+            "/history/:file_id/:sequence_num/restore",
+            get(get_history_restore),
+        )
+        //
+        // get the protected ranges of a file
+        .route("/protections/:file_id", get(get_protections))
+        //
         // auth middleware
         .route_layer(auth)
         //
@@ -154,6 +175,24 @@ pub(crate) async fn serve() -> Result<()> {
         }
     });
 
+    // in a separate thread, delete old checkpoints
+    tokio::spawn({
+        let state = Arc::clone(&state);
+        let policy = RetentionPolicy::new(&config);
+
+        async move {
+            let mut interval = time::interval(Duration::from_secs(config.retention_check_s as u64));
+
+            loop {
+                interval.tick().await;
+
+                if let Err(error) = apply_retention_policy(&state, &policy).await {
+                    tracing::error!("Error applying retention policy: {error}");
+                }
+            }
+        }
+    });
+
     // in a separate thread, log stats
     tokio::spawn({
         let state = Arc::clone(&state);
@@ -10,10 +10,14 @@ pub mod stats;
 use jsonwebtoken::jwk::JwkSet;
 use quadratic_rust_shared::pubsub::redis_streams::RedisStreamsConfig;
 use quadratic_rust_shared::pubsub::Config as PubSubConfig;
+use std::collections::HashSet;
+use std::sync::Arc;
 use tokio::sync::Mutex;
+use uuid::Uuid;
 
 use crate::config::Config;
 use crate::error::Result;
+use crate::permissions::{ApiFilePermissions, FilePermissions};
 use crate::state::settings::Settings;
 
 use self::pubsub::PubSub;
@@ -24,10 +28,27 @@ pub(crate) struct State {
     pub(crate) pubsub: Mutex<PubSub>,
     pub(crate) settings: Settings,
     pub(crate) stats: Mutex<Stats>,
+    pub(crate) file_permissions: Arc<dyn FilePermissions>,
+
+    /// Files that may have checkpoints to delete, see retention.rs
+    pub(crate) retention_files: Mutex<HashSet<Uuid>>,
 }
 
 impl State {
     pub(crate) async fn new(config: &Config, jwks: Option<JwkSet>) -> Result<Self> {
+        let file_permissions = Arc::new(ApiFilePermissions {
+            quadratic_api_uri: config.quadratic_api_uri.to_owned(),
+        });
+
+        Self::new_with_file_permissions(config, jwks, file_permissions).await
+    }
+
+    /// Create the state with the service that file permissions are read from
+    pub(crate) async fn new_with_file_permissions(
+        config: &Config,
+        jwks: Option<JwkSet>,
+        file_permissions: Arc<dyn FilePermissions>,
+    ) -> Result<Self> {
         let pubsub_config = PubSubConfig::RedisStreams(RedisStreamsConfig {
             host: config.pubsub_host.to_owned(),
             port: config.pubsub_port.to_owned(),
@@ -39,6 +60,8 @@ impl State {
             pubsub: Mutex::new(PubSub::new(pubsub_config).await?),
             settings: Settings::new(config, jwks).await,
             stats: Mutex::new(Stats::new()),
+            file_permissions,
+            retention_files: Mutex::new(HashSet::new()),
         })
     }
 }
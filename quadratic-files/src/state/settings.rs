@@ -14,6 +14,7 @@ pub(crate) struct Settings {
     pub(crate) m2m_auth_token: String,
     pub(crate) storage: StorageContainer,
     pub(crate) pubsub_processed_transactions_channel: String,
+    pub(crate) recalculate_formulas: bool,
 }
 
 impl Settings {
@@ -58,6 +59,7 @@ impl Settings {
             pubsub_processed_transactions_channel: config
                 .pubsub_processed_transactions_channel
                 .to_owned(),
+            recalculate_formulas: config.recalculate_formulas,
         }
     }
 }
@@ -8,11 +8,21 @@
 use std::sync::Arc;
 
 use crate::config::config;
+use crate::permissions::MockFilePermissions;
 use crate::state::State;
 
 pub(crate) async fn new_state() -> State {
+    new_state_with_file_permissions(Arc::new(MockFilePermissions::default())).await
+}
+
+/// Create new global state that reads file permissions from a mock
+pub(crate) async fn new_state_with_file_permissions(
+    file_permissions: Arc<MockFilePermissions>,
+) -> State {
     let config = config().unwrap();
-    State::new(&config, None).await.unwrap()
+    State::new_with_file_permissions(&config, None, file_permissions)
+        .await
+        .unwrap()
 }
 
 pub(crate) async fn new_arc_state() -> Arc<State> {
@@ -0,0 +1,176 @@
+# Converts cells to and from the Arrow IPC streams that core uses to pass
+# typed values to code cells (see quadratic-core/src/values/arrow.rs). A column
+# of one type uses that Arrow type; mixed columns are dense unions whose
+# children are named after the cell type.
+#
+# This module needs pyarrow; it's only imported when pyarrow is available.
+
+import math
+from datetime import date, datetime, time, timedelta
+from decimal import Decimal
+
+import numpy as np
+import pandas as pd
+import pyarrow as pa
+from dateutil.relativedelta import relativedelta
+
+
+def _to_python(field_name: str, value):
+    if value is None:
+        return None
+    if field_name == "error":
+        return "[error]"
+    if field_name in ("html", "image"):
+        return ""
+    if isinstance(value, Decimal):
+        # whole numbers are exact; others are floats, like other Python numbers
+        if value == value.to_integral_value():
+            return int(value)
+        return float(value)
+    if isinstance(value, pa.MonthDayNano):
+        return relativedelta(
+            months=value.months,
+            days=value.days,
+            microseconds=value.nanoseconds / 1000,
+        )
+    return value
+
+
+def _column_values(column: pa.ChunkedArray) -> list:
+    values = []
+    for chunk in column.chunks:
+        if pa.types.is_union(chunk.type):
+            type_codes = chunk.type.type_codes
+            for i in range(len(chunk)):
+                child = type_codes.index(chunk.type_codes[i].as_py())
+                offset = chunk.offsets[i].as_py()
+                value = chunk.field(child)[offset].as_py()
+                values.append(_to_python(chunk.type.field(child).name, value))
+        else:
+            values.extend(_to_python("", value) for value in chunk.to_pylist())
+    return values
+
+
+def cells_from_arrow(data) -> tuple[list[list], dict[str, str]]:
+    """
+    Reads the cells returned by core's calculationGetCellsA1Arrow.
+
+    Returns the columns of values and the schema's metadata.
+    """
+    table = pa.ipc.open_stream(pa.py_buffer(bytes(data))).read_all()
+    metadata = {
+        key.decode(): value.decode()
+        for key, value in (table.schema.metadata or {}).items()
+    }
+    columns = [_column_values(table.column(i)) for i in range(table.num_columns)]
+    return columns, metadata
+
+
+def _kind(value) -> str:
+    if value is None or value is pd.NaT:
+        return "blank"
+    if isinstance(value, (bool, np.bool_)):
+        return "logical"
+    if isinstance(value, (int, float, Decimal, np.number)):
+        if isinstance(value, (float, np.floating)) and math.isnan(value):
+            return "blank"
+        return "number"
+    if isinstance(value, (datetime, pd.Timestamp, np.datetime64)):
+        return "date time"
+    if isinstance(value, date):
+        return "date"
+    if isinstance(value, time):
+        return "time"
+    if isinstance(value, (timedelta, relativedelta, np.timedelta64)):
+        return "duration"
+    return "text"
+
+
+def _month_day_nano(value) -> tuple[int, int, int]:
+    if isinstance(value, relativedelta):
+        seconds = (value.hours * 60 + value.minutes) * 60 + value.seconds
+        return (
+            int(value.years * 12 + value.months),
+            int(value.days),
+            int(seconds * 1_000_000_000 + value.microseconds * 1000),
+        )
+    value = pd.Timedelta(value)
+    days = value.days
+    return (0, days, value.value - days * 86_400 * 1_000_000_000)
+
+
+def _text_array(values: list) -> pa.Array:
+    return pa.array([None if v is None else str(v) for v in values], pa.string())
+
+
+def _typed_array(kind: str, values: list) -> pa.Array:
+    if kind == "blank":
+        return pa.nulls(len(values))
+    if kind == "text":
+        return _text_array(values)
+    try:
+        if kind == "duration":
+            return pa.array(
+                [None if v is None else _month_day_nano(v) for v in values],
+                pa.month_day_nano_interval(),
+            )
+        return pa.array(values, from_pandas=True)
+    except (pa.ArrowInvalid, pa.ArrowTypeError, OverflowError):
+        # e.g. numbers that don't fit one Arrow type, or mixed time zones
+        if kind == "number":
+            return pa.array(
+                [None if v is None else float(v) for v in values], pa.float64()
+            )
+        return _text_array(values)
+
+
+def _column_to_arrow(values: list) -> pa.Array:
+    kinds = [_kind(value) for value in values]
+    values = [None if kind == "blank" else value for kind, value in zip(kinds, values)]
+    present = sorted(set(kinds) - {"blank"})
+    if len(present) <= 1:
+        return _typed_array(present[0] if present else "blank", values)
+
+    children_kinds = sorted(set(kinds))
+    children = [
+        _typed_array(kind, [v for k, v in zip(kinds, values) if k == kind])
+        for kind in children_kinds
+    ]
+    type_ids = [children_kinds.index(kind) for kind in kinds]
+    lengths = [0] * len(children_kinds)
+    offsets = []
+    for type_id in type_ids:
+        offsets.append(lengths[type_id])
+        lengths[type_id] += 1
+    return pa.UnionArray.from_dense(
+        pa.array(type_ids, pa.int8()),
+        pa.array(offsets, pa.int32()),
+        children,
+        children_kinds,
+    )
+
+
+def cells_to_arrow(rows: list, headers: list | None = None) -> bytes:
+    """
+    Writes rows of values (or a list of values, which is one column) as an
+    Arrow IPC stream for core's calculationCompleteArrow. `headers` are the
+    column names, if any.
+    """
+    if len(rows) > 0 and not isinstance(rows[0], list):
+        rows = [[value] for value in rows]
+    width = max([len(row) for row in rows] + [len(headers or [])])
+    columns = [
+        _column_to_arrow([row[x] if x < len(row) else None for row in rows])
+        for x in range(width)
+    ]
+    names = [
+        str(headers[x]) if headers is not None and x < len(headers) else str(x)
+        for x in range(width)
+    ]
+    metadata = {"headers": "true" if headers is not None else "false"}
+    table = pa.table(columns, names=names, metadata=metadata)
+
+    sink = pa.BufferOutputStream()
+    with pa.ipc.new_stream(sink, table.schema) as writer:
+        writer.write_table(table)
+    return sink.getvalue().to_pybytes()
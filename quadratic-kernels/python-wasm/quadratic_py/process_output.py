@@ -2,6 +2,12 @@ import pandas as pd
 
 from .utils import to_quadratic_type
 
+# typed output as Arrow, when pyarrow is available
+try:
+    from .arrow import cells_to_arrow
+except ImportError:
+    cells_to_arrow = None
+
 
 def isListEmpty(inList):
     if isinstance(inList, list):
@@ -14,6 +20,7 @@ def process_output_value(output_value):
     array_output = None
     output_type = type(output_value).__name__
     output_size = None
+    headers = None
 
     # TODO(ddimaria): figure out if we need to covert back to a list for array_output
     # We should have a single output
@@ -38,9 +45,8 @@ def process_output_value(output_value):
         # If output_value columns is not the default (RangeIndex)
         if type(output_value.columns) != pd.core.indexes.range.RangeIndex:
             # Return Column names and values
-            array_output = [
-                output_value.columns.tolist()
-            ] + output_value.values.tolist()
+            headers = output_value.columns.tolist()
+            array_output = [headers] + output_value.values.tolist()
 
         else:
             # convert nan to None, return PD values list
@@ -89,6 +95,13 @@ def process_output_value(output_value):
                             array_output[row][col]
                         )
 
+    arrow_output = None
+    if cells_to_arrow is not None and array_output is not None:
+        if headers is not None:
+            arrow_output = cells_to_arrow(array_output[1:], headers)
+        else:
+            arrow_output = cells_to_arrow(array_output)
+
     # removes output_value if there's an array or None
     if array_output is not None or output_value is None:
         output_value = None
@@ -98,6 +111,7 @@ def process_output_value(output_value):
     return {
         "typed_array_output": typed_array_output,
         "array_output": array_output,
+        "arrow_output": arrow_output,
         "output_value": output_value,
         "output_type": output_type,
         "output_size": output_size,
@@ -6,6 +6,14 @@ from pandas import DataFrame, Series
 
 from ..utils import result_to_value, stack_line_number, to_python_type_df
 
+# typed cells as Arrow, when pyarrow is available
+try:
+    import getCellsA1Arrow
+
+    from ..arrow import cells_from_arrow
+except ImportError:
+    getCellsA1Arrow = None
+
 results = None
 
 # Code in this file is used to generate typeshed stubs for Pyright (Python LSP)
@@ -143,6 +151,9 @@ class q:
         Typical usage example:
             c = q.cells("A1:B5")
         """
+        if getCellsA1Arrow is not None:
+            return self._cells_arrow(a1, first_row_header)
+
         result = getCellsA1(a1, int(stack_line_number()))
 
         if result.w == 1 and result.h == 1:
@@ -170,6 +181,25 @@ class q:
 
         return df
 
+    def _cells_arrow(self, a1: str, first_row_header: bool):
+        data = getCellsA1Arrow(a1, int(stack_line_number()))
+        if data is None:
+            raise Exception(f"Unable to get cells {a1}")
+        columns, metadata = cells_from_arrow(data.to_py())
+
+        if metadata.get("w") == "1" and metadata.get("h") == "1":
+            return columns[0][0]
+
+        df = DataFrame({x: column for x, column in enumerate(columns)})
+
+        # Move the first row to the header
+        if first_row_header:
+            df.rename(columns=df.iloc[0], inplace=True)
+            df.drop(df.index[0], inplace=True)
+            df.reset_index(drop=True, inplace=True)
+
+        return df
+
     def pos(self) -> tuple[int, int]:
         """
         A relative reference to the current cell in the grid.
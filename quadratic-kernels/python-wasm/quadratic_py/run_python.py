@@ -73,6 +73,7 @@ async def run_python(code: str, pos: Tuple[int, int]):
         output_type = output["output_type"]
         output_size = output["output_size"]
         typed_array_output = output["typed_array_output"]
+        arrow_output = output["arrow_output"]
 
         # Plotly HTML
         if plotly_html is not None and plotly_html.result is not None:
@@ -92,6 +93,7 @@ async def run_python(code: str, pos: Tuple[int, int]):
         return {
             "output": output_value,
             "array_output": typed_array_output,
+            "arrow_output": arrow_output,
             "output_type": output_type,
             "output_size": output_size,
             "std_out": sout.getvalue(),
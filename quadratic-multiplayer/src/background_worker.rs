@@ -5,8 +5,7 @@ use uuid::Uuid;
 
 use crate::{
     error::Result,
-    get_room,
-    message::{broadcast, response::MessageResponse},
+    message::{broadcast, broadcast_local, response::MessageResponse},
     state::State,
 };
 
@@ -79,7 +78,8 @@ pub(crate) fn start(
 async fn broadcast_sequence_num(state: Arc<State>, file_id: &Uuid) -> Result<JoinHandle<()>> {
     let sequence_num = state.get_sequence_num(file_id).await?;
 
-    Ok(broadcast(
+    // every instance sends the sequence number to its own users
+    Ok(broadcast_local(
         vec![],
         file_id.to_owned(),
         Arc::clone(&state),
@@ -104,12 +104,14 @@ async fn remove_stale_users_in_room(
         return Ok(None);
     }
 
-    if num_remaining == 0 {
+    // users connected to other instances may still be in the room
+    let users = state.users_in_room(file_id).await?;
+
+    if users.is_empty() {
         tracing::trace!("No users remaining in room {file_id}",);
         return Ok(None);
     }
 
-    let users = get_room!(state, file_id)?.users.to_owned();
     let message = MessageResponse::from((users, &state.settings.min_version));
 
     Ok(Some(broadcast(
@@ -0,0 +1,233 @@
+//! Cluster
+//!
+//! Several instances of quadratic-multiplayer can serve the same file.  Every
+//! broadcast is also published on the file's redis pubsub channel, and each
+//! instance forwards the messages published by the other instances to the
+//! users in the room that are connected to it.  Instances only subscribe to
+//! the channels of the rooms that have users connected to them.
+
+use futures::stream::StreamExt;
+use quadratic_rust_shared::pubsub::{
+    redis::{RedisConfig, RedisConnection},
+    Config as PubSubConfig, PubSub as PubSubTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::{task::JoinHandle, time};
+use uuid::Uuid;
+
+use crate::{
+    error::{MpError, Result},
+    message::{broadcast_local, response::MessageResponse},
+    state::{pubsub::cluster_channel, State},
+};
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct ClusterMessage {
+    pub(crate) instance_id: Uuid,
+    pub(crate) file_id: Uuid,
+    pub(crate) exclude: Vec<Uuid>,
+    pub(crate) message: MessageResponse,
+}
+
+/// Publish a broadcast to the other instances
+pub(crate) async fn publish(
+    state: &Arc<State>,
+    exclude: &[Uuid],
+    file_id: Uuid,
+    message: &MessageResponse,
+) -> Result<()> {
+    let cluster_message = ClusterMessage {
+        instance_id: state.instance_id,
+        file_id,
+        exclude: exclude.to_vec(),
+        message: message.to_owned(),
+    };
+
+    state
+        .publish_cluster(&file_id, &serde_json::to_vec(&cluster_message)?)
+        .await
+}
+
+/// In a separate thread, forward messages published by other instances to
+/// the users connected to this instance.  The subscription is recreated if
+/// the connection to redis is lost.
+#[tracing::instrument(level = "trace")]
+pub(crate) fn start(state: Arc<State>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            if let Err(error) = listen(Arc::clone(&state)).await {
+                tracing::error!("Error listening for messages from other instances: {error}");
+            }
+
+            time::sleep(RECONNECT_INTERVAL).await;
+        }
+    })
+}
+
+async fn listen(state: Arc<State>) -> Result<()> {
+    let config = redis_config(&state.pubsub.lock().await.config)?;
+    let mut connection = RedisConnection::new(config).await?;
+    let mut subscribed = HashSet::new();
+
+    tracing::info!("Listening for messages from other instances");
+
+    loop {
+        update_subscriptions(&state, &mut connection, &mut subscribed).await?;
+
+        // the message stream borrows the connection, so it is recreated after
+        // the subscriptions change
+        let message = {
+            let mut messages = connection.pubsub.on_message();
+
+            tokio::select! {
+                message = messages.next() => message,
+                _ = state.rooms_changed.notified() => continue,
+            }
+        };
+
+        let Some(message) = message else {
+            return Err(MpError::PubSub(
+                "Subscription to other instances closed".into(),
+            ));
+        };
+
+        let forwarded = message
+            .get_payload::<Vec<u8>>()
+            .map_err(|e| MpError::PubSub(e.to_string()))
+            .and_then(|payload| forward(Arc::clone(&state), &payload));
+
+        if let Err(error) = forwarded {
+            tracing::warn!("Error forwarding message from another instance: {error}");
+        }
+    }
+}
+
+/// Subscribe to the channels of the rooms on this instance, and unsubscribe
+/// from the channels of rooms that were removed.
+async fn update_subscriptions(
+    state: &State,
+    connection: &mut RedisConnection,
+    subscribed: &mut HashSet<Uuid>,
+) -> Result<()> {
+    let rooms = state
+        .rooms
+        .lock()
+        .await
+        .iter()
+        .map(|room| *room.key())
+        .collect::<HashSet<_>>();
+
+    for file_id in rooms.difference(subscribed) {
+        connection.subscribe(&cluster_channel(file_id), "").await?;
+    }
+
+    for file_id in subscribed.difference(&rooms) {
+        connection
+            .pubsub
+            .unsubscribe(cluster_channel(file_id))
+            .await
+            .map_err(|e| MpError::PubSub(e.to_string()))?;
+    }
+
+    *subscribed = rooms;
+
+    Ok(())
+}
+
+/// Send a message published by another instance to the users in the room.
+/// Messages published by this instance were already sent.
+fn forward(state: Arc<State>, payload: &[u8]) -> Result<Option<JoinHandle<()>>> {
+    let ClusterMessage {
+        instance_id,
+        file_id,
+        exclude,
+        message,
+    } = serde_json::from_slice(payload)?;
+
+    if instance_id == state.instance_id {
+        return Ok(None);
+    }
+
+    Ok(Some(broadcast_local(exclude, file_id, state, message)))
+}
+
+/// The cluster channel is a plain redis pubsub channel on the same server as
+/// the transaction streams
+fn redis_config(config: &PubSubConfig) -> Result<PubSubConfig> {
+    match config {
+        PubSubConfig::RedisStreams(config) => Ok(PubSubConfig::Redis(RedisConfig {
+            host: config.host.to_owned(),
+            port: config.port.to_owned(),
+            password: config.password.to_owned(),
+            active_channels: config.active_channels.to_owned(),
+        })),
+        _ => Err(MpError::PubSub(
+            "Config type must be RedisStreamsConfig".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::broadcast;
+    use crate::test_util::{integration_test_receive, new_arc_state, setup};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn forwards_messages_from_other_instances() {
+        let (socket, state, _, file_id, _, _) = setup().await;
+        let other_state = new_arc_state().await;
+        let handle = start(Arc::clone(&state));
+
+        // wait for the subscription
+        time::sleep(Duration::from_millis(500)).await;
+
+        // the other instance has no users in the room, so the message can
+        // only reach them through redis
+        let message = MessageResponse::CurrentTransaction { sequence_num: 99 };
+        broadcast(vec![], file_id, other_state, message.clone())
+            .await
+            .unwrap();
+
+        time::timeout(Duration::from_secs(5), async {
+            while integration_test_receive(&socket, 1).await != Some(message.clone()) {}
+        })
+        .await
+        .unwrap();
+
+        // messages published by this instance are not sent again
+        let payload = serde_json::to_vec(&ClusterMessage {
+            instance_id: state.instance_id,
+            file_id,
+            exclude: vec![],
+            message,
+        })
+        .unwrap();
+        assert!(forward(Arc::clone(&state), &payload).unwrap().is_none());
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn subscribes_to_rooms_on_this_instance() {
+        let (_, state, _, file_id, _, _) = setup().await;
+        let config = redis_config(&state.pubsub.lock().await.config).unwrap();
+        let mut connection = RedisConnection::new(config).await.unwrap();
+        let mut subscribed = HashSet::new();
+
+        update_subscriptions(&state, &mut connection, &mut subscribed)
+            .await
+            .unwrap();
+        assert_eq!(subscribed, HashSet::from([file_id]));
+
+        state.remove_room(file_id).await;
+        update_subscriptions(&state, &mut connection, &mut subscribed)
+            .await
+            .unwrap();
+        assert!(subscribed.is_empty());
+    }
+}
@@ -19,6 +19,11 @@ pub(crate) struct Config {
     pub(crate) heartbeat_timeout_s: i64,
     pub(crate) environment: Environment,
 
+    pub(crate) max_transaction_bytes: usize,
+    pub(crate) max_decompressed_transaction_bytes: usize,
+    pub(crate) max_transaction_operations: usize,
+    pub(crate) max_rect_cells: u64,
+
     pub(crate) pubsub_host: String,
     pub(crate) pubsub_port: String,
     pub(crate) pubsub_password: String,
@@ -26,6 +31,7 @@ pub(crate) struct Config {
 
     pub(crate) auth0_jwks_uri: String,
     pub(crate) quadratic_api_uri: String,
+    pub(crate) quadratic_files_uri: String,
     pub(crate) m2m_auth_token: String,
 }
 
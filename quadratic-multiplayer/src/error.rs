@@ -5,6 +5,9 @@
 //! Convert third party crate errors to application errors.
 //! Convert errors to responses.
 
+use quadratic_core::controller::operations::comments::CommentError;
+use quadratic_core::controller::operations::protection::ProtectionError;
+use quadratic_core::controller::operations::validation::OperationValidationError;
 use quadratic_rust_shared::{aws::error::Aws as AwsError, SharedError};
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumString};
@@ -60,12 +63,18 @@ pub(crate) enum MpError {
     #[error("Internal server error: {0}")]
     InternalServer(String),
 
+    #[error("Invalid operations: {0}")]
+    InvalidOperations(String),
+
     #[error("Error reading MinVersion file: {0}")]
     MinVersion(String),
 
     #[error("Requested {0} transactions but only found {1}")]
     MissingTransactions(String, String),
 
+    #[error("Protected range: {0}")]
+    ProtectedRange(String),
+
     #[error("PubSub error: {0}")]
     PubSub(String),
 
@@ -93,6 +102,9 @@ pub(crate) enum MpError {
     #[error("Transaction queue error: {0}")]
     TransactionQueue(String),
 
+    #[error("Transaction is {0} bytes, which exceeds the limit of {1} bytes")]
+    TransactionTooLarge(usize, usize),
+
     #[error("unknown error: {0}")]
     Unknown(String),
 
@@ -103,6 +115,19 @@ pub(crate) enum MpError {
     UserNotFound(Uuid, Uuid),
 }
 
+impl MpError {
+    /// Whether the error rejects a transaction's operations.  The client rolls
+    /// back rejected transactions instead of sending them again.
+    pub(crate) fn rejects_transaction(&self) -> bool {
+        matches!(
+            self,
+            MpError::InvalidOperations(_)
+                | MpError::ProtectedRange(_)
+                | MpError::TransactionTooLarge(..)
+        )
+    }
+}
+
 impl From<SharedError> for MpError {
     fn from(error: SharedError) -> Self {
         match error {
@@ -122,6 +147,24 @@ impl From<serde_json::Error> for MpError {
     }
 }
 
+impl From<OperationValidationError> for MpError {
+    fn from(error: OperationValidationError) -> Self {
+        MpError::InvalidOperations(error.to_string())
+    }
+}
+
+impl From<ProtectionError> for MpError {
+    fn from(error: ProtectionError) -> Self {
+        MpError::ProtectedRange(error.to_string())
+    }
+}
+
+impl From<CommentError> for MpError {
+    fn from(error: CommentError) -> Self {
+        MpError::FilePermissions(error.to_string())
+    }
+}
+
 impl From<uuid::Error> for MpError {
     fn from(error: uuid::Error) -> Self {
         MpError::Unknown(error.to_string())
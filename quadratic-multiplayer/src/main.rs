@@ -4,6 +4,7 @@
 //! tracking for a shared file.
 
 mod background_worker;
+mod cluster;
 mod config;
 mod error;
 mod message;
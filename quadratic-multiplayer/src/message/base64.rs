@@ -0,0 +1,25 @@
+//! Base64 Operations
+//!
+//! Operations are kept as bytes, and only encoded as base64 strings in JSON
+//! messages.  Use with `#[serde(with = "crate::message::base64")]`.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+pub(crate) fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&STANDARD.encode(bytes))
+}
+
+pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let encoded = String::deserialize(deserializer)?;
+
+    STANDARD
+        .decode(encoded)
+        .map_err(|e| D::Error::custom(format!("Could not decode base64 operations: {e}")))
+}
@@ -0,0 +1,317 @@
+//! Binary Frames
+//!
+//! Clients that negotiate protocol version 2 or later in `EnterRoom` send and
+//! receive transactions in binary websocket frames.  A frame is a bincode
+//! serialized message (see `quadratic_core::compression`) that carries
+//! operations as raw bytes instead of base64.  The operations are already
+//! compressed, so frames are not compressed again.  Every other message, and
+//! every message for clients on protocol version 1, is JSON.
+//!
+//! NOTE: the layout needs to be kept in sync with multiplayerBinary.ts
+
+use axum::extract::ws::Message;
+use quadratic_core::compression::{deserialize, serialize, SerializationFormat};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{MpError, Result};
+use crate::message::handle::rejected_transaction;
+use crate::message::request::MessageRequest;
+use crate::message::response::{MessageResponse, Transaction};
+
+/// Protocol version of clients that only speak JSON
+pub(crate) const JSON_PROTOCOL_VERSION: u32 = 1;
+
+/// First protocol version that sends transactions in binary frames
+pub(crate) const BINARY_PROTOCOL_VERSION: u32 = 2;
+
+static SERIALIZATION_FORMAT: SerializationFormat = SerializationFormat::Bincode;
+
+/// Size of a request frame without its operations: the variant index, then
+/// three length prefixed uuids and the length of the operations
+const REQUEST_HEADER_BYTES: usize = 4 + 3 * (8 + 16) + 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) enum BinaryRequest {
+    Transaction {
+        id: Uuid,
+        session_id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+    },
+    Comment {
+        id: Uuid,
+        session_id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct BinaryTransaction {
+    pub(crate) id: Uuid,
+    pub(crate) file_id: Uuid,
+    pub(crate) sequence_num: u64,
+    pub(crate) operations: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) enum BinaryResponse {
+    Transaction(BinaryTransaction),
+    Comment(BinaryTransaction),
+    Transactions {
+        transactions: Vec<BinaryTransaction>,
+    },
+    CatchUp {
+        file_id: Uuid,
+        checkpoint_sequence_num: u64,
+        transactions: Vec<BinaryTransaction>,
+    },
+}
+
+impl From<BinaryRequest> for MessageRequest {
+    fn from(request: BinaryRequest) -> Self {
+        match request {
+            BinaryRequest::Transaction {
+                id,
+                session_id,
+                file_id,
+                operations,
+            } => MessageRequest::Transaction {
+                id,
+                session_id,
+                file_id,
+                operations,
+            },
+            BinaryRequest::Comment {
+                id,
+                session_id,
+                file_id,
+                operations,
+            } => MessageRequest::Comment {
+                id,
+                session_id,
+                file_id,
+                operations,
+            },
+        }
+    }
+}
+
+impl From<&Transaction> for BinaryTransaction {
+    fn from(transaction: &Transaction) -> Self {
+        BinaryTransaction {
+            id: transaction.id,
+            file_id: transaction.file_id,
+            sequence_num: transaction.sequence_num,
+            operations: transaction.operations.to_owned(),
+        }
+    }
+}
+
+fn binary_transactions(transactions: &[Transaction]) -> Vec<BinaryTransaction> {
+    transactions.iter().map(BinaryTransaction::from).collect()
+}
+
+impl BinaryResponse {
+    /// Convert a response that carries transactions.  Returns None for
+    /// responses that are always sent as JSON.
+    pub(crate) fn from_response(response: &MessageResponse) -> Option<Self> {
+        let binary = match response {
+            MessageResponse::Transaction {
+                id,
+                file_id,
+                sequence_num,
+                operations,
+            } => BinaryResponse::Transaction(BinaryTransaction {
+                id: *id,
+                file_id: *file_id,
+                sequence_num: *sequence_num,
+                operations: operations.to_owned(),
+            }),
+            MessageResponse::Comment {
+                id,
+                file_id,
+                sequence_num,
+                operations,
+            } => BinaryResponse::Comment(BinaryTransaction {
+                id: *id,
+                file_id: *file_id,
+                sequence_num: *sequence_num,
+                operations: operations.to_owned(),
+            }),
+            MessageResponse::Transactions { transactions } => BinaryResponse::Transactions {
+                transactions: binary_transactions(transactions),
+            },
+            MessageResponse::CatchUp {
+                file_id,
+                checkpoint_sequence_num,
+                transactions,
+            } => BinaryResponse::CatchUp {
+                file_id: *file_id,
+                checkpoint_sequence_num: *checkpoint_sequence_num,
+                transactions: binary_transactions(transactions),
+            },
+            _ => return None,
+        };
+
+        Some(binary)
+    }
+}
+
+/// Negotiate the protocol version with a client, which sends the highest
+/// version that it supports
+pub(crate) fn negotiate_protocol_version(client_protocol_version: Option<u32>) -> u32 {
+    client_protocol_version
+        .unwrap_or(JSON_PROTOCOL_VERSION)
+        .clamp(JSON_PROTOCOL_VERSION, BINARY_PROTOCOL_VERSION)
+}
+
+/// Decode a request sent in a binary frame.  Frames are rejected before they
+/// are deserialized if their operations would be larger than
+/// `max_transaction_bytes`.
+pub(crate) fn decode_request(frame: &[u8], max_transaction_bytes: usize) -> Result<MessageRequest> {
+    let max_bytes = max_transaction_bytes.saturating_add(REQUEST_HEADER_BYTES);
+    if frame.len() > max_bytes {
+        return Err(MpError::TransactionTooLarge(frame.len(), max_bytes));
+    }
+
+    let request = deserialize::<BinaryRequest>(&SERIALIZATION_FORMAT, frame)
+        .map_err(|e| MpError::Serialization(e.to_string()))?;
+
+    Ok(request.into())
+}
+
+/// Reject the transaction in a request frame that could not be decoded, so
+/// the client doesn't send it again.  Only the frame's header is read.
+/// Returns None if the header can't be read either.
+pub(crate) fn reject_request(frame: &[u8], error: MpError) -> Option<MessageResponse> {
+    // the variant index, then the transaction id, session id and file id
+    let (variant, id, _session_id, file_id) =
+        deserialize::<(u32, Uuid, Uuid, Uuid)>(&SERIALIZATION_FORMAT, frame).ok()?;
+
+    (variant <= 1).then(|| rejected_transaction(id, file_id, error))
+}
+
+/// Encode a response as a binary frame.  Returns None for responses that are
+/// always sent as JSON.
+pub(crate) fn encode_response(response: &MessageResponse) -> Result<Option<Vec<u8>>> {
+    BinaryResponse::from_response(response)
+        .map(|binary| {
+            serialize(&SERIALIZATION_FORMAT, binary)
+                .map_err(|e| MpError::Serialization(e.to_string()))
+        })
+        .transpose()
+}
+
+/// Create the websocket message for a response, using a binary frame if the
+/// client's protocol version supports it
+pub(crate) fn to_message(response: &MessageResponse, protocol_version: u32) -> Result<Message> {
+    if protocol_version >= BINARY_PROTOCOL_VERSION {
+        if let Some(frame) = encode_response(response)? {
+            return Ok(Message::Binary(frame));
+        }
+    }
+
+    Ok(Message::Text(serde_json::to_string(response)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_protocol_version() {
+        assert_eq!(negotiate_protocol_version(None), JSON_PROTOCOL_VERSION);
+        assert_eq!(negotiate_protocol_version(Some(0)), JSON_PROTOCOL_VERSION);
+        assert_eq!(negotiate_protocol_version(Some(2)), BINARY_PROTOCOL_VERSION);
+        assert_eq!(
+            negotiate_protocol_version(Some(99)),
+            BINARY_PROTOCOL_VERSION
+        );
+    }
+
+    #[test]
+    fn decodes_a_request_frame() {
+        let id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let file_id = Uuid::new_v4();
+        let operations = vec![1u8, 2, 3];
+
+        // the layout written by multiplayerBinary.ts: a u32 variant index,
+        // then each uuid and byte array prefixed by its u64 length
+        let mut frame = 0u32.to_le_bytes().to_vec();
+        for uuid in [id, session_id, file_id] {
+            frame.extend(16u64.to_le_bytes());
+            frame.extend(uuid.as_bytes());
+        }
+        frame.extend(3u64.to_le_bytes());
+        frame.extend(&operations);
+        assert_eq!(frame.len(), REQUEST_HEADER_BYTES + operations.len());
+
+        let request = decode_request(&frame, operations.len()).unwrap();
+        let expected = MessageRequest::Transaction {
+            id,
+            session_id,
+            file_id,
+            operations,
+        };
+        assert_eq!(request, expected);
+
+        // frames with operations over the limit are not deserialized
+        let error = decode_request(&frame, 2).unwrap_err();
+        assert!(matches!(error, MpError::TransactionTooLarge(..)));
+
+        // but their transaction is rejected
+        let response = reject_request(&frame, error.clone()).unwrap();
+        let expected = MessageResponse::TransactionRejected { id, file_id, error };
+        assert_eq!(response, expected);
+        assert_eq!(
+            reject_request(&frame[..20], MpError::Unknown("".into())),
+            None
+        );
+    }
+
+    #[test]
+    fn encodes_responses() {
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            file_id: Uuid::new_v4(),
+            sequence_num: 1,
+            operations: vec![1, 2, 3],
+        };
+        let response = MessageResponse::Transactions {
+            transactions: vec![transaction.clone()],
+        };
+
+        let frame = encode_response(&response).unwrap().unwrap();
+        let decoded = deserialize::<BinaryResponse>(&SERIALIZATION_FORMAT, &frame).unwrap();
+        let expected = BinaryResponse::Transactions {
+            transactions: vec![BinaryTransaction {
+                id: transaction.id,
+                file_id: transaction.file_id,
+                sequence_num: 1,
+                operations: vec![1, 2, 3],
+            }],
+        };
+        assert_eq!(decoded, expected);
+
+        // responses without transactions stay JSON
+        let response = MessageResponse::CurrentTransaction { sequence_num: 1 };
+        assert_eq!(encode_response(&response).unwrap(), None);
+        assert!(matches!(
+            to_message(&response, BINARY_PROTOCOL_VERSION).unwrap(),
+            Message::Text(_)
+        ));
+        assert!(matches!(
+            to_message(
+                &MessageResponse::Transactions {
+                    transactions: vec![]
+                },
+                1
+            )
+            .unwrap(),
+            Message::Text(_)
+        ));
+    }
+}
@@ -6,15 +6,16 @@
 //! to all users in a room.
 
 use axum::extract::ws::{Message, WebSocket};
-use base64::{engine::general_purpose::STANDARD, Engine};
 use futures_util::stream::SplitSink;
+use quadratic_core::controller::operations::operation::Operation;
+use quadratic_core::controller::transaction::Transaction as CoreTransaction;
 use quadratic_rust_shared::quadratic_api::{get_file_perms, FilePermRole};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use crate::error::{ErrorLevel, MpError, Result};
-use crate::get_mut_room;
+use crate::message::frame::negotiate_protocol_version;
 use crate::message::response::Transaction;
 use crate::message::{
     broadcast, request::MessageRequest, response::MessageResponse, send_user_message,
@@ -30,6 +31,75 @@ use crate::state::{
     State,
 };
 
+/// Decode and validate the operations of a transaction, then add them to the
+/// room's transaction queue.  If `comments_only` is true, the operations may
+/// only change comments.  Other operations are checked against the protected
+/// ranges that the user can edit, and comments against their authors.
+/// Returns the transaction's sequence number.
+async fn sequence_operations(
+    state: Arc<State>,
+    id: Uuid,
+    file_id: Uuid,
+    session_id: Uuid,
+    jwt: Option<&str>,
+    operations: &[u8],
+    comments_only: bool,
+) -> Result<u64> {
+    // reject oversized or invalid operations before they are sequenced
+    // so they never reach other clients or the files service
+    let max_transaction_bytes = state.settings.max_transaction_bytes;
+    if operations.len() > max_transaction_bytes {
+        return Err(MpError::TransactionTooLarge(
+            operations.len(),
+            max_transaction_bytes,
+        ));
+    }
+
+    // bound the decompressed size so a small, highly compressed transaction
+    // can't exhaust memory
+    let core_operations = CoreTransaction::decompress_and_deserialize_bounded::<Vec<Operation>>(
+        operations,
+        state.settings.max_decompressed_transaction_bytes,
+    )
+    .map_err(|e| {
+        MpError::InvalidOperations(format!(
+            "Could not deserialize operations in transaction {id}: {:?}",
+            e
+        ))
+    })?;
+
+    if comments_only && !Operation::all_comments(&core_operations) {
+        return Err(MpError::InvalidOperations(format!(
+            "Comment {id} contains operations that do not change comments"
+        )));
+    }
+
+    state
+        .validate_protections(
+            file_id,
+            session_id,
+            jwt.unwrap_or_default(),
+            &core_operations,
+            comments_only,
+        )
+        .await?;
+
+    // validate the operations against the sheets deleted in the file and add
+    // the transaction to the transaction queue with the next sequence_num,
+    // both of which are shared by all instances
+    state
+        .push_validated_pubsub(id, file_id, operations.to_vec(), &core_operations)
+        .await
+}
+
+/// Response to a transaction that the server will never sequence, so the
+/// client rolls it back instead of sending it again.
+pub(crate) fn rejected_transaction(id: Uuid, file_id: Uuid, error: MpError) -> MessageResponse {
+    tracing::warn!("Rejected transaction {id} in file {file_id}: {error}");
+
+    MessageResponse::TransactionRejected { id, file_id, error }
+}
+
 /// Handle incoming messages.  All requests and responses are strictly typed.
 #[tracing::instrument(level = "trace")]
 pub(crate) async fn handle_message(
@@ -55,6 +125,7 @@ pub(crate) async fn handle_message(
             cell_edit,
             viewport,
             follow,
+            protocol_version,
         } => {
             // validate that the user has permission to access the file
             let base_url = &state.settings.quadratic_api_uri;
@@ -109,10 +180,12 @@ pub(crate) async fn handle_message(
                 state: user_state,
                 socket: Some(Arc::clone(&sender)),
                 last_heartbeat: chrono::Utc::now(),
+                protocol_version: negotiate_protocol_version(protocol_version),
 
                 // this will be properly set in the enter_room function
                 index: 0,
             };
+            let protocol_version = user.protocol_version;
 
             // subscribe to the file's pubsub channel
             if let Err(error) = state.subscribe_pubsub(&file_id, GROUP_NAME).await {
@@ -131,6 +204,8 @@ pub(crate) async fn handle_message(
                 MessageResponse::EnterRoom {
                     file_id,
                     sequence_num,
+                    min_version: state.settings.min_version.to_owned(),
+                    protocol_version,
                 },
             )
             .await
@@ -138,8 +213,7 @@ pub(crate) async fn handle_message(
 
             // only broadcast if the user is new to the room
             if is_new {
-                let room = state.get_room(&file_id).await?;
-                let response = MessageResponse::from((room.users, &state.settings.min_version));
+                let response = state.users_in_room_response(&file_id).await?;
 
                 broadcast(vec![], file_id, Arc::clone(&state), response);
             }
@@ -154,13 +228,11 @@ pub(crate) async fn handle_message(
         } => {
             validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
 
-            let is_not_empty = state.leave_room(file_id, &session_id).await?;
-            let room = state.get_room(&file_id).await?;
+            state.leave_room(file_id, &session_id).await?;
 
-            if is_not_empty {
-                let response = MessageResponse::from((room.users, &state.settings.min_version));
-                broadcast(vec![session_id], file_id, Arc::clone(&state), response);
-            }
+            // users connected to other instances may still be in the room
+            let response = state.users_in_room_response(&file_id).await?;
+            broadcast(vec![session_id], file_id, Arc::clone(&state), response);
 
             Ok(None)
         }
@@ -178,25 +250,29 @@ pub(crate) async fn handle_message(
             state.update_user_heartbeat(file_id, &session_id).await?;
 
             tracing::trace!(
-                "Transaction received for room {} from user {}, operations: {:?}",
+                "Transaction received for room {} from user {}, operations: {} bytes",
                 file_id,
                 session_id,
-                &operations
+                operations.len()
             );
 
-            // get and increment the room's sequence_num
-            let room_sequence_num = get_mut_room!(state, file_id)?.increment_sequence_num();
-            let decoded_operations = STANDARD.decode(&operations).map_err(|e| {
-                MpError::Serialization(format!(
-                    "Could not decode base64 encoded operations in transaction {id}: {:?}",
-                    e
-                ))
-            })?;
-
-            // add the transaction to the transaction queue
-            let sequence_num = state
-                .push_pubsub(id, file_id, decoded_operations, room_sequence_num)
-                .await?;
+            let sequence_num = match sequence_operations(
+                Arc::clone(&state),
+                id,
+                file_id,
+                session_id,
+                pre_connection.jwt.as_deref(),
+                &operations,
+                false,
+            )
+            .await
+            {
+                Ok(sequence_num) => sequence_num,
+                Err(error) if error.rejects_transaction() => {
+                    return Ok(Some(rejected_transaction(id, file_id, error)));
+                }
+                Err(error) => return Err(error),
+            };
 
             // broadcast the transaction to all users in the room
             let response = MessageResponse::Transaction {
@@ -211,6 +287,56 @@ pub(crate) async fn handle_message(
             Ok(None)
         }
 
+        // User adds, replies to, resolves, or removes comments
+        MessageRequest::Comment {
+            id,
+            session_id,
+            file_id,
+            operations,
+        } => {
+            validate_user_can_edit_or_view_file(Arc::clone(&state), file_id, session_id).await?;
+
+            // update the heartbeat
+            state.update_user_heartbeat(file_id, &session_id).await?;
+
+            tracing::trace!(
+                "Comment received for room {} from user {}, operations: {} bytes",
+                file_id,
+                session_id,
+                operations.len()
+            );
+
+            let sequence_num = match sequence_operations(
+                Arc::clone(&state),
+                id,
+                file_id,
+                session_id,
+                pre_connection.jwt.as_deref(),
+                &operations,
+                true,
+            )
+            .await
+            {
+                Ok(sequence_num) => sequence_num,
+                Err(error) if error.rejects_transaction() => {
+                    return Ok(Some(rejected_transaction(id, file_id, error)));
+                }
+                Err(error) => return Err(error),
+            };
+
+            // broadcast the comment to all users in the room
+            let response = MessageResponse::Comment {
+                id,
+                file_id,
+                operations,
+                sequence_num,
+            };
+
+            broadcast(vec![], file_id, Arc::clone(&state), response);
+
+            Ok(None)
+        }
+
         // User sends transactions
         MessageRequest::GetTransactions {
             file_id,
@@ -244,9 +370,39 @@ pub(crate) async fn handle_message(
 
             tracing::trace!("got: {}", transactions.len());
 
-            // we don't have the expected number of transactions
-            // send an error to the client so they can reload
+            // we don't have the expected number of transactions, so try to
+            // catch the client up from the latest checkpoint instead
             if transactions.len() < expected_num_transactions as usize {
+                let checkpoint_sequence_num = state.get_checkpoint_sequence_num(&file_id).await?;
+
+                // the checkpoint only helps if it is past the client's state
+                if checkpoint_sequence_num >= min_sequence_num {
+                    let expected_num_transactions =
+                        sequence_num.saturating_sub(checkpoint_sequence_num);
+                    let transactions = state
+                        .get_messages_from_pubsub(&file_id, checkpoint_sequence_num + 1)
+                        .await?
+                        .into_iter()
+                        .map(|transaction| transaction.into())
+                        .collect::<Vec<Transaction>>();
+
+                    tracing::trace!(
+                        "catching up from checkpoint {}, got: {}",
+                        checkpoint_sequence_num,
+                        transactions.len()
+                    );
+
+                    if transactions.len() >= expected_num_transactions as usize {
+                        return Ok(Some(MessageResponse::CatchUp {
+                            file_id,
+                            checkpoint_sequence_num,
+                            transactions,
+                        }));
+                    }
+                }
+
+                // neither redis nor the checkpoint can catch the client up,
+                // send an error to the client so they can reload
                 return Ok(Some(MessageResponse::Error {
                     error: MpError::MissingTransactions(
                         expected_num_transactions.to_string(),
@@ -303,17 +459,22 @@ pub(crate) async fn handle_message(
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use quadratic_core::controller::operations::operation::Operation;
-    use quadratic_core::controller::transaction::Transaction as CoreTransaction;
+    use quadratic_core::cell_values::CellValues;
+    use quadratic_core::grid::sheet::comments::{Comment, CommentThread};
+    use quadratic_core::grid::sheet::protections::ProtectedRange;
     use quadratic_core::grid::SheetId;
+    use quadratic_core::{CellValue, Pos, SheetPos};
     use tokio::net::TcpStream;
     use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
     use uuid::Uuid;
 
     use super::*;
+    use crate::get_mut_room;
+    use crate::message::frame::JSON_PROTOCOL_VERSION;
+    use crate::state::checkpoints::MockCheckpoints;
     use crate::state::settings::MinVersion;
     use crate::state::user::{CellEdit, UserStateUpdate};
-    use crate::test_util::{integration_test_receive, new_user, setup};
+    use crate::test_util::{integration_test_receive, new_user, setup, setup_with_checkpoints};
 
     async fn test_handle(
         socket: Arc<Mutex<WebSocketStream<MaybeTlsStream<TcpStream>>>>,
@@ -401,11 +562,14 @@ pub(crate) mod tests {
             cell_edit: CellEdit::default(),
             viewport: "viewport".into(),
             follow: Some(Uuid::new_v4().to_string()),
+            protocol_version: None,
         };
 
         let response = MessageResponse::EnterRoom {
             file_id,
             sequence_num: 0,
+            min_version: state.settings.min_version.to_owned(),
+            protocol_version: JSON_PROTOCOL_VERSION,
         };
 
         let users_in_room = state.get_room(&file_id).await.unwrap().users;
@@ -469,19 +633,18 @@ pub(crate) mod tests {
             color: Some("red".to_string()),
         }];
         let compressed_ops = CoreTransaction::serialize_and_compress(&operations).unwrap();
-        let encoded_ops = STANDARD.encode(&compressed_ops);
 
         let request = MessageRequest::Transaction {
             id,
             file_id,
             session_id,
-            operations: encoded_ops.clone(),
+            operations: compressed_ops.clone(),
         };
 
         let response = MessageResponse::Transaction {
             id,
             file_id,
-            operations: encoded_ops.clone(),
+            operations: compressed_ops.clone(),
             sequence_num: 1,
         };
 
@@ -505,7 +668,7 @@ pub(crate) mod tests {
         let transaction = Transaction {
             id,
             file_id,
-            operations: encoded_ops,
+            operations: compressed_ops,
             sequence_num: 1,
         };
         let response = MessageResponse::Transactions {
@@ -536,9 +699,7 @@ pub(crate) mod tests {
         };
 
         // increment the sequence_num
-        get_mut_room!(state, file_id)
-            .unwrap()
-            .increment_sequence_num();
+        get_mut_room!(state, file_id).unwrap().sequence_num += 1;
 
         let response = MessageResponse::Error {
             error: MpError::MissingTransactions("1".into(), "0".into()), // requested 1, got 0
@@ -557,4 +718,427 @@ pub(crate) mod tests {
         )
         .await;
     }
+
+    #[tokio::test]
+    async fn handle_catch_up_from_checkpoint() {
+        let checkpoints = Arc::new(MockCheckpoints::default());
+        let (socket, state, _, file_id, user_1, _) =
+            setup_with_checkpoints(checkpoints.clone()).await;
+        let id = Uuid::new_v4();
+        let session_id = user_1.session_id;
+
+        // transaction 1 is only in the checkpoint, not in redis
+        get_mut_room!(state, file_id).unwrap().sequence_num += 1;
+        checkpoints.set(file_id, 1);
+
+        let operations = vec![Operation::SetSheetColor {
+            sheet_id: SheetId::new(),
+            color: Some("red".to_string()),
+        }];
+        let compressed_ops = CoreTransaction::serialize_and_compress(&operations).unwrap();
+
+        let request = MessageRequest::Transaction {
+            id,
+            file_id,
+            session_id,
+            operations: compressed_ops.clone(),
+        };
+        let response = MessageResponse::Transaction {
+            id,
+            file_id,
+            operations: compressed_ops.clone(),
+            sequence_num: 2,
+        };
+
+        test_handle(
+            socket.clone(),
+            state.clone(),
+            file_id,
+            user_1.clone(),
+            request,
+            None,
+            Some(response),
+        )
+        .await;
+
+        let request = MessageRequest::GetTransactions {
+            file_id,
+            session_id,
+            min_sequence_num: 1,
+        };
+        let response = MessageResponse::CatchUp {
+            file_id,
+            checkpoint_sequence_num: 1,
+            transactions: vec![Transaction {
+                id,
+                file_id,
+                operations: compressed_ops,
+                sequence_num: 2,
+            }],
+        };
+
+        test_handle(
+            socket,
+            state,
+            file_id,
+            user_1,
+            request,
+            Some(response),
+            None,
+        )
+        .await;
+    }
+
+    async fn handle_transaction_error(
+        state: Arc<State>,
+        file_id: Uuid,
+        user: &User,
+        operations: Vec<u8>,
+    ) -> MpError {
+        let stream = state
+            ._get_user_in_room(&file_id, &user.session_id)
+            .await
+            .unwrap()
+            .socket
+            .unwrap();
+        let request = MessageRequest::Transaction {
+            id: Uuid::new_v4(),
+            file_id,
+            session_id: user.session_id,
+            operations,
+        };
+
+        handle_message(request, state, stream, PreConnection::new(None))
+            .await
+            .unwrap_err()
+    }
+
+    /// Send a transaction that the server rejects.  Returns the rejection's
+    /// error.
+    async fn handle_rejected_transaction(
+        state: Arc<State>,
+        file_id: Uuid,
+        user: &User,
+        operations: Vec<u8>,
+    ) -> MpError {
+        let stream = state
+            ._get_user_in_room(&file_id, &user.session_id)
+            .await
+            .unwrap()
+            .socket
+            .unwrap();
+        let id = Uuid::new_v4();
+        let request = MessageRequest::Transaction {
+            id,
+            file_id,
+            session_id: user.session_id,
+            operations,
+        };
+
+        match handle_message(request, state, stream, PreConnection::new(None)).await {
+            Ok(Some(MessageResponse::TransactionRejected {
+                id: rejected_id,
+                file_id: rejected_file_id,
+                error,
+            })) => {
+                assert_eq!((rejected_id, rejected_file_id), (id, file_id));
+                error
+            }
+            other => panic!("expected a rejected transaction but got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn handle_invalid_transactions() {
+        let (_, state, _, file_id, user_1, _) = setup().await;
+
+        // undecodable operations
+        let operations = b"not operations".to_vec();
+        let error = handle_rejected_transaction(state.clone(), file_id, &user_1, operations).await;
+        assert!(matches!(error, MpError::InvalidOperations(_)));
+
+        // oversized rect
+        let sheet_id = SheetId::new();
+        let operations = vec![Operation::MergeCells {
+            sheet_rect: quadratic_core::SheetRect::new(1, 1, i64::MAX, i64::MAX, sheet_id),
+        }];
+        let operations = CoreTransaction::serialize_and_compress(&operations).unwrap();
+        let error = handle_rejected_transaction(state.clone(), file_id, &user_1, operations).await;
+        assert!(matches!(error, MpError::InvalidOperations(_)));
+
+        // rejected transactions are not sequenced
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 0);
+
+        // operations on a deleted sheet
+        let operations = vec![Operation::DeleteSheet { sheet_id }];
+        state
+            .push_validated_pubsub(
+                Uuid::new_v4(),
+                file_id,
+                CoreTransaction::serialize_and_compress(&operations).unwrap(),
+                &operations,
+            )
+            .await
+            .unwrap();
+        assert!(state
+            .get_deleted_sheets_pubsub(&file_id)
+            .await
+            .unwrap()
+            .contains(&sheet_id));
+        let operations = vec![Operation::SetSheetColor {
+            sheet_id,
+            color: Some("red".to_string()),
+        }];
+        let operations = CoreTransaction::serialize_and_compress(&operations).unwrap();
+        let error = handle_rejected_transaction(state.clone(), file_id, &user_1, operations).await;
+        assert!(matches!(error, MpError::InvalidOperations(_)));
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn handle_protected_transactions() {
+        let (_, state, _, file_id, user_1, _) = setup().await;
+        let sheet_id = SheetId::new();
+
+        // another user protects the sheet
+        let range = ProtectedRange::new(None, vec!["another user".into()], vec![]);
+        let operations = vec![Operation::SetProtectedRange {
+            sheet_id,
+            range: range.clone(),
+        }];
+        state
+            .push_sequenced_pubsub(
+                Uuid::new_v4(),
+                file_id,
+                CoreTransaction::serialize_and_compress(&operations).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let operations = vec![Operation::SetCellValues {
+            sheet_pos: SheetPos::new(sheet_id, 1, 1),
+            values: CellValues::from(CellValue::Text("hello".into())),
+        }];
+        let operations = CoreTransaction::serialize_and_compress(&operations).unwrap();
+        let error = handle_rejected_transaction(state.clone(), file_id, &user_1, operations).await;
+        assert!(matches!(error, MpError::ProtectedRange(_)));
+
+        // the protected range can't be removed either
+        let operations = vec![Operation::RemoveProtectedRange {
+            sheet_id,
+            range_id: range.id,
+        }];
+        let operations = CoreTransaction::serialize_and_compress(&operations).unwrap();
+        let error = handle_rejected_transaction(state.clone(), file_id, &user_1, operations).await;
+        assert!(matches!(error, MpError::ProtectedRange(_)));
+
+        // rejected transactions are not sequenced
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn handle_comments_from_viewers() {
+        let (socket, state, _, file_id, user_1, _) = setup().await;
+        let session_id = user_1.session_id;
+        state
+            .update_user_permissions(file_id, &session_id, vec![FilePermRole::FileView])
+            .await
+            .unwrap();
+
+        let id = Uuid::new_v4();
+        let operations = vec![Operation::SetCommentThreadResolved {
+            sheet_id: SheetId::new(),
+            thread_id: Uuid::new_v4(),
+            resolved: true,
+        }];
+        let compressed_ops = CoreTransaction::serialize_and_compress(&operations).unwrap();
+
+        // viewers cannot send transactions
+        let error =
+            handle_transaction_error(state.clone(), file_id, &user_1, compressed_ops.clone()).await;
+        assert!(matches!(error, MpError::FilePermissions(_)));
+
+        let request = MessageRequest::Comment {
+            id,
+            file_id,
+            session_id,
+            operations: compressed_ops.clone(),
+        };
+        let response = MessageResponse::Comment {
+            id,
+            file_id,
+            operations: compressed_ops,
+            sequence_num: 1,
+        };
+
+        test_handle(
+            socket,
+            state.clone(),
+            file_id,
+            user_1.clone(),
+            request,
+            None,
+            Some(response),
+        )
+        .await;
+
+        // comments cannot contain other operations
+        let operations = vec![Operation::SetSheetColor {
+            sheet_id: SheetId::new(),
+            color: Some("red".to_string()),
+        }];
+        let id = Uuid::new_v4();
+        let request = MessageRequest::Comment {
+            id,
+            file_id,
+            session_id,
+            operations: CoreTransaction::serialize_and_compress(&operations).unwrap(),
+        };
+        let stream = state
+            ._get_user_in_room(&file_id, &session_id)
+            .await
+            .unwrap()
+            .socket
+            .unwrap();
+        let response = handle_message(request, state.clone(), stream, PreConnection::new(None))
+            .await
+            .unwrap();
+        assert!(matches!(
+            response,
+            Some(MessageResponse::TransactionRejected {
+                id: rejected_id,
+                error: MpError::InvalidOperations(_),
+                ..
+            }) if rejected_id == id
+        ));
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 1);
+    }
+
+    async fn handle_comment_error(
+        state: Arc<State>,
+        file_id: Uuid,
+        user: &User,
+        operations: &[Operation],
+    ) -> MpError {
+        let stream = state
+            ._get_user_in_room(&file_id, &user.session_id)
+            .await
+            .unwrap()
+            .socket
+            .unwrap();
+        let request = MessageRequest::Comment {
+            id: Uuid::new_v4(),
+            file_id,
+            session_id: user.session_id,
+            operations: CoreTransaction::serialize_and_compress(&operations).unwrap(),
+        };
+
+        handle_message(request, state, stream, PreConnection::new(None))
+            .await
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn handle_comments_from_other_authors() {
+        let (_, state, _, file_id, user_1, _) = setup().await;
+        let sheet_id = SheetId::new();
+
+        // another user writes a thread with a reply
+        let mut thread = CommentThread::new(
+            Pos { x: 1, y: 1 },
+            Comment::new("another user".into(), "hello".into()),
+        );
+        let reply = Comment::new("another user".into(), "reply".into());
+        thread.replies.push(reply.clone());
+        let operations = vec![Operation::SetCommentThread {
+            sheet_id,
+            thread: thread.clone(),
+        }];
+        state
+            .push_sequenced_pubsub(
+                Uuid::new_v4(),
+                file_id,
+                CoreTransaction::serialize_and_compress(&operations).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // comments can't be written as another user, even by editors
+        let forged = CommentThread::new(
+            Pos { x: 2, y: 2 },
+            Comment::new("another user".into(), "forged".into()),
+        );
+        let operations = [Operation::SetCommentThread {
+            sheet_id,
+            thread: forged,
+        }];
+        let error = handle_comment_error(state.clone(), file_id, &user_1, &operations).await;
+        assert!(matches!(error, MpError::FilePermissions(_)));
+
+        let operations = [Operation::AddCommentReply {
+            sheet_id,
+            thread_id: thread.id,
+            reply: Comment::new("another user".into(), "forged".into()),
+        }];
+        let error = handle_comment_error(state.clone(), file_id, &user_1, &operations).await;
+        assert!(matches!(error, MpError::FilePermissions(_)));
+
+        // users who can't edit the file can't change other users' comments
+        state
+            .update_user_permissions(file_id, &user_1.session_id, vec![FilePermRole::FileView])
+            .await
+            .unwrap();
+
+        let mut resolved = thread.clone();
+        resolved.resolved = true;
+        for operation in [
+            Operation::SetCommentThread {
+                sheet_id,
+                thread: resolved,
+            },
+            Operation::RemoveCommentThread {
+                sheet_id,
+                thread_id: thread.id,
+            },
+            Operation::RemoveCommentReply {
+                sheet_id,
+                thread_id: thread.id,
+                reply_id: reply.id,
+            },
+        ] {
+            let error = handle_comment_error(state.clone(), file_id, &user_1, &[operation]).await;
+            assert!(matches!(error, MpError::FilePermissions(_)));
+        }
+
+        // rejected comments are not sequenced
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 1);
+
+        // but their own comments are accepted
+        let own = CommentThread::new(
+            Pos { x: 3, y: 3 },
+            Comment::new(user_1.comment_author(), "mine".into()),
+        );
+        let operations = [
+            Operation::SetCommentThread {
+                sheet_id,
+                thread: own.clone(),
+            },
+            Operation::RemoveCommentThread {
+                sheet_id,
+                thread_id: own.id,
+            },
+        ];
+        let sequence_num = sequence_operations(
+            state.clone(),
+            Uuid::new_v4(),
+            file_id,
+            user_1.session_id,
+            None,
+            &CoreTransaction::serialize_and_compress(&operations).unwrap(),
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(sequence_num, 2);
+    }
 }
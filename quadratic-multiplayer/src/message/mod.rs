@@ -5,10 +5,14 @@ use std::sync::Arc;
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use crate::cluster;
 use crate::error::MpError;
+use crate::message::frame::{encode_response, to_message, BINARY_PROTOCOL_VERSION};
 use crate::message::response::MessageResponse;
 use crate::state::State;
 
+pub mod base64;
+pub mod frame;
 pub mod handle;
 pub mod request;
 pub mod response;
@@ -48,7 +52,8 @@ pub(crate) struct UserStateUpdate {
     pub viewport: Option<String>,
 }
 
-/// Broadcast a message to all users in a room except the sender.
+/// Broadcast a message to all users in a room except the sender, including
+/// users connected to other instances.
 /// All messages are sent in a separate thread.
 #[tracing::instrument(level = "trace")]
 pub(crate) fn broadcast(
@@ -65,50 +70,94 @@ pub(crate) fn broadcast(
     );
 
     tokio::spawn(async move {
-        if let Ok(room) = state.get_room(&file_id).await {
-            let result = async {
-                let included_users = room
-                    .users
-                    .iter()
-                    .filter(|user| !exclude.contains(&user.session_id));
-
-                if included_users.clone().count() == 0 {
-                    return Ok::<_, MpError>(());
-                }
+        if let Err(e) = cluster::publish(&state, &exclude, file_id, &message).await {
+            tracing::warn!(
+                "Error publishing message to other instances: {:?}",
+                e.to_string()
+            );
+        }
+
+        send_to_room(exclude, file_id, state, message).await;
+    })
+}
+
+/// Broadcast a message to the users in a room that are connected to this
+/// instance, except the sender.
+/// All messages are sent in a separate thread.
+#[tracing::instrument(level = "trace")]
+pub(crate) fn broadcast_local(
+    exclude: Vec<Uuid>,
+    file_id: Uuid,
+    state: Arc<State>,
+    message: MessageResponse,
+) -> JoinHandle<()> {
+    tokio::spawn(send_to_room(exclude, file_id, state, message))
+}
+
+async fn send_to_room(
+    exclude: Vec<Uuid>,
+    file_id: Uuid,
+    state: Arc<State>,
+    message: MessageResponse,
+) {
+    if let Ok(room) = state.get_room(&file_id).await {
+        let result = async {
+            let included_users = room
+                .users
+                .iter()
+                .filter(|user| !exclude.contains(&user.session_id));
+
+            if included_users.clone().count() == 0 {
+                return Ok::<_, MpError>(());
+            }
+
+            let serialized_message = serde_json::to_string(&message)?;
 
-                let serialized_message = serde_json::to_string(&message)?;
-
-                for user in included_users {
-                    if let Some(sender) = &user.socket {
-                        let sent = sender
-                            .lock()
-                            .await
-                            .send(Message::Text(serialized_message.clone()))
-                            .await
-                            .map_err(|e| MpError::SendingMessage(e.to_string()));
-
-                        if let Err(error) = sent {
-                            tracing::warn!(
-                                "Error broadcasting to user {} in room {}: {:?}",
-                                user.session_id,
-                                file_id,
-                                error,
-                            );
-
-                            // the user's socket is stale, so remove them from the room
-                            state.leave_room(file_id, &user.session_id).await?;
+            // only encode a binary frame if a user can receive it
+            let binary_frame = match included_users
+                .clone()
+                .any(|user| user.protocol_version >= BINARY_PROTOCOL_VERSION)
+            {
+                true => encode_response(&message)?,
+                false => None,
+            };
+
+            for user in included_users {
+                if let Some(sender) = &user.socket {
+                    let ws_message = match &binary_frame {
+                        Some(frame) if user.protocol_version >= BINARY_PROTOCOL_VERSION => {
+                            Message::Binary(frame.to_owned())
                         }
+                        _ => Message::Text(serialized_message.clone()),
+                    };
+                    let sent = sender
+                        .lock()
+                        .await
+                        .send(ws_message)
+                        .await
+                        .map_err(|e| MpError::SendingMessage(e.to_string()));
+
+                    if let Err(error) = sent {
+                        tracing::warn!(
+                            "Error broadcasting to user {} in room {}: {:?}",
+                            user.session_id,
+                            file_id,
+                            error,
+                        );
+
+                        // the user's socket is stale, so remove them from the room
+                        state.leave_room(file_id, &user.session_id).await?;
                     }
                 }
+            }
 
-                Ok::<_, MpError>(())
-            };
+            Ok::<_, MpError>(())
+        };
 
-            if let Err(e) = result.await {
-                tracing::warn!("Error broadcasting message: {:?}", e.to_string());
-            }
+        if let Err(e) = result.await {
+            tracing::warn!("Error broadcasting message: {:?}", e.to_string());
         }
-    })
+    }
 }
 
 /// Send a message to a specific user in a room.
@@ -127,7 +176,7 @@ pub(crate) fn send_user_message(
                     sender
                         .lock()
                         .await
-                        .send(Message::Text(serde_json::to_string(&message)?))
+                        .send(to_message(&message, user.protocol_version)?)
                         .await
                         .map_err(|e| MpError::SendingMessage(e.to_string()))?;
                 }
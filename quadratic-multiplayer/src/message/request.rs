@@ -24,6 +24,10 @@ pub(crate) enum MessageRequest {
         cell_edit: CellEdit,
         viewport: String,
         follow: Option<String>,
+        // highest protocol version the client supports, absent for clients
+        // that only speak JSON
+        #[serde(default)]
+        protocol_version: Option<u32>,
     },
     LeaveRoom {
         session_id: Uuid,
@@ -38,7 +42,16 @@ pub(crate) enum MessageRequest {
         id: Uuid,
         session_id: Uuid,
         file_id: Uuid,
-        operations: String,
+        #[serde(with = "crate::message::base64")]
+        operations: Vec<u8>,
+    },
+    /// A transaction that only changes comments.  Viewers can send these.
+    Comment {
+        id: Uuid,
+        session_id: Uuid,
+        file_id: Uuid,
+        #[serde(with = "crate::message::base64")]
+        operations: Vec<u8>,
     },
     GetTransactions {
         file_id: Uuid,
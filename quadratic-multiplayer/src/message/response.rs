@@ -5,8 +5,6 @@
 use crate::error::{ErrorLevel, MpError};
 use crate::state::settings::MinVersion;
 use crate::state::user::{User, UserStateUpdate};
-use base64::{engine::general_purpose::STANDARD, Engine};
-use dashmap::DashMap;
 use quadratic_core::controller::transaction::TransactionServer;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -16,7 +14,8 @@ pub(crate) struct Transaction {
     pub(crate) id: Uuid,
     pub(crate) file_id: Uuid,
     pub(crate) sequence_num: u64,
-    pub(crate) operations: String,
+    #[serde(with = "crate::message::base64")]
+    pub(crate) operations: Vec<u8>,
 }
 
 // NOTE: needs to be kept in sync with multiplayerTypes.ts
@@ -36,18 +35,38 @@ pub(crate) enum MessageResponse {
         id: Uuid,
         file_id: Uuid,
         sequence_num: u64,
-        operations: String,
+        #[serde(with = "crate::message::base64")]
+        operations: Vec<u8>,
+    },
+    Comment {
+        id: Uuid,
+        file_id: Uuid,
+        sequence_num: u64,
+        #[serde(with = "crate::message::base64")]
+        operations: Vec<u8>,
     },
     Transactions {
         transactions: Vec<Transaction>,
     },
+    CatchUp {
+        file_id: Uuid,
+        checkpoint_sequence_num: u64,
+        transactions: Vec<Transaction>,
+    },
     EnterRoom {
         file_id: Uuid,
         sequence_num: u64,
+        min_version: MinVersion,
+        protocol_version: u32,
     },
     CurrentTransaction {
         sequence_num: u64,
     },
+    TransactionRejected {
+        id: Uuid,
+        file_id: Uuid,
+        error: MpError,
+    },
     Error {
         error: MpError,
         error_level: ErrorLevel,
@@ -60,15 +79,15 @@ impl From<TransactionServer> for Transaction {
             id: transaction_server.id,
             file_id: transaction_server.file_id,
             sequence_num: transaction_server.sequence_num,
-            operations: STANDARD.encode(&transaction_server.operations),
+            operations: transaction_server.operations,
         }
     }
 }
 
-impl From<(DashMap<Uuid, User>, &MinVersion)> for MessageResponse {
-    fn from((users, min_version): (DashMap<Uuid, User>, &MinVersion)) -> Self {
+impl From<(Vec<User>, &MinVersion)> for MessageResponse {
+    fn from((users, min_version): (Vec<User>, &MinVersion)) -> Self {
         MessageResponse::UsersInRoom {
-            users: users.into_iter().map(|user| (user.1)).collect(),
+            users,
             min_version: min_version.to_owned(),
         }
     }
@@ -26,11 +26,15 @@ use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{
-    background_worker,
+    background_worker, cluster,
     config::config,
     error::{ErrorLevel, MpError, Result},
     message::{
-        broadcast, handle::handle_message, request::MessageRequest, response::MessageResponse,
+        broadcast,
+        frame::{decode_request, reject_request, to_message},
+        handle::handle_message,
+        request::MessageRequest,
+        response::MessageResponse,
     },
     state::{connection::PreConnection, State},
 };
@@ -94,6 +98,9 @@ pub(crate) async fn serve() -> Result<()> {
         tracing::warn!("JWT authentication is disabled");
     }
 
+    // forward messages from other instances in a separate thread
+    cluster::start(Arc::clone(&state));
+
     // perform various activities in a separate thread
     background_worker::start(
         Arc::clone(&state),
@@ -243,11 +250,10 @@ async fn handle_socket(
                     connection.session_id
                 );
 
-                if let Ok(room) = state.get_room(&file_id).await {
+                // users connected to other instances may still be in the room
+                if let Ok(message) = state.users_in_room_response(&file_id).await {
                     tracing::info!("Broadcasting room {file_id} after connection close");
 
-                    let message = MessageResponse::from((room.users, &state.settings.min_version));
-
                     if let Err(error) = broadcast(
                         vec![connection.session_id],
                         file_id,
@@ -284,27 +290,25 @@ async fn process_message(
 ) -> Result<ControlFlow<Option<MessageResponse>, ()>> {
     match msg {
         Message::Text(text) => {
-            let messsage_request = serde_json::from_str::<MessageRequest>(&text)?;
-            let message_response =
-                handle_message(messsage_request, state, Arc::clone(&sender), pre_connection)
-                    .await?;
-
-            if let Some(message_response) = message_response {
-                let response = Message::Text(serde_json::to_string(&message_response)?);
+            let message_request = serde_json::from_str::<MessageRequest>(&text)?;
+            process_request(message_request, sender, state, pre_connection).await?;
+        }
+        Message::Binary(frame) => {
+            match decode_request(&frame, state.settings.max_transaction_bytes) {
+                Ok(message_request) => {
+                    process_request(message_request, sender, state, pre_connection).await?;
+                }
+                Err(error) => {
+                    // rejections are JSON in every protocol version
+                    let response = reject_request(&frame, error.clone()).ok_or(error)?;
 
-                (*sender.lock().await)
-                    .send(response)
-                    .await
-                    .map_err(|e| MpError::SendingMessage(e.to_string()))?;
+                    (*sender.lock().await)
+                        .send(Message::Text(serde_json::to_string(&response)?))
+                        .await
+                        .map_err(|e| MpError::SendingMessage(e.to_string()))?;
+                }
             }
         }
-        Message::Binary(d) => {
-            tracing::info!(
-                "Binary messages are not yet supported.  {} bytes: {:?}",
-                d.len(),
-                d
-            );
-        }
         Message::Close(c) => {
             if let Some(cf) = c {
                 tracing::info!("Close with code {} and reason `{}`", cf.code, cf.reason);
@@ -321,6 +325,36 @@ async fn process_message(
     Ok(ControlFlow::Continue(()))
 }
 
+/// Handle a request and send the response, if any, in the protocol of the
+/// requesting user.
+async fn process_request(
+    message_request: MessageRequest,
+    sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
+    state: Arc<State>,
+    pre_connection: PreConnection,
+) -> Result<()> {
+    let connection_id = pre_connection.id;
+    let message_response = handle_message(
+        message_request,
+        Arc::clone(&state),
+        Arc::clone(&sender),
+        pre_connection,
+    )
+    .await?;
+
+    if let Some(message_response) = message_response {
+        let protocol_version = state.protocol_version(connection_id).await;
+        let response = to_message(&message_response, protocol_version)?;
+
+        (*sender.lock().await)
+            .send(response)
+            .await
+            .map_err(|e| MpError::SendingMessage(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
 pub(crate) async fn healthcheck() -> impl IntoResponse {
     StatusCode::OK
 }
@@ -329,19 +363,27 @@ pub(crate) async fn healthcheck() -> impl IntoResponse {
 pub(crate) mod tests {
 
     use super::*;
+    use crate::message::frame::{
+        BinaryRequest, BinaryResponse, BinaryTransaction, BINARY_PROTOCOL_VERSION,
+    };
     use crate::state::settings::MinVersion;
-    use crate::state::user::{User, UserStateUpdate};
+    use crate::state::user::{CellEdit, User, UserStateUpdate};
     use crate::test_util::{
-        add_user_via_ws, integration_test_send_and_receive, new_arc_state, setup,
+        add_user_via_ws, integration_test_send_and_receive, integration_test_setup, new_arc_state,
+        new_user, setup,
     };
     use axum::{
         body::Body,
         http::{self, Request},
     };
-    use base64::{engine::general_purpose::STANDARD, Engine};
+    use quadratic_core::cell_values::CellValues;
+    use quadratic_core::compression::{deserialize, serialize, SerializationFormat};
     use quadratic_core::controller::operations::operation::Operation;
     use quadratic_core::controller::transaction::Transaction;
+    use quadratic_core::grid::sheet::protections::ProtectedRange;
     use quadratic_core::grid::SheetId;
+    use quadratic_core::{CellValue, SheetPos};
+    use tokio_tungstenite::tungstenite;
 
     use tower::ServiceExt;
     use uuid::Uuid;
@@ -536,17 +578,16 @@ pub(crate) mod tests {
         }];
         let id = Uuid::new_v4();
         let compressed_ops = Transaction::serialize_and_compress(&operations).unwrap();
-        let encoded_ops = STANDARD.encode(&compressed_ops);
         let request = MessageRequest::Transaction {
             id,
             session_id,
             file_id,
-            operations: encoded_ops.clone(),
+            operations: compressed_ops.clone(),
         };
         let expected = MessageResponse::Transaction {
             id,
             file_id,
-            operations: encoded_ops,
+            operations: compressed_ops,
             sequence_num: 1,
         };
 
@@ -554,4 +595,141 @@ pub(crate) mod tests {
 
         assert_eq!(response, Some(expected));
     }
+
+    #[tokio::test]
+    async fn user_operations_are_rejected() {
+        let (socket, state, _, file_id, user, _) = setup().await;
+        let id = Uuid::new_v4();
+        let request = MessageRequest::Transaction {
+            id,
+            session_id: user.session_id,
+            file_id,
+            operations: b"not operations".to_vec(),
+        };
+
+        // only the sender is told, with the transaction's id
+        let response = integration_test_send_and_receive(&socket, request, true, 1).await;
+        match response {
+            Some(MessageResponse::TransactionRejected {
+                id: rejected_id,
+                file_id: rejected_file_id,
+                error: MpError::InvalidOperations(_),
+            }) => assert_eq!((rejected_id, rejected_file_id), (id, file_id)),
+            other => panic!("expected a rejected transaction but got {other:?}"),
+        }
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn user_protected_operations_are_rejected() {
+        let (socket, state, _, file_id, user, _) = setup().await;
+        let sheet_id = SheetId::new();
+
+        // another user protects the sheet
+        let operations = vec![Operation::SetProtectedRange {
+            sheet_id,
+            range: ProtectedRange::new(None, vec!["another user".into()], vec![]),
+        }];
+        state
+            .push_sequenced_pubsub(
+                Uuid::new_v4(),
+                file_id,
+                Transaction::serialize_and_compress(&operations).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let id = Uuid::new_v4();
+        let operations = vec![Operation::SetCellValues {
+            sheet_pos: SheetPos::new(sheet_id, 1, 1),
+            values: CellValues::from(CellValue::Text("hello".into())),
+        }];
+        let request = MessageRequest::Transaction {
+            id,
+            session_id: user.session_id,
+            file_id,
+            operations: Transaction::serialize_and_compress(&operations).unwrap(),
+        };
+
+        let response = integration_test_send_and_receive(&socket, request, true, 1).await;
+        match response {
+            Some(MessageResponse::TransactionRejected {
+                id: rejected_id,
+                file_id: rejected_file_id,
+                error: MpError::ProtectedRange(_),
+            }) => assert_eq!((rejected_id, rejected_file_id), (id, file_id)),
+            other => panic!("expected a rejected transaction but got {other:?}"),
+        }
+        assert_eq!(state.get_sequence_num(&file_id).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn user_shares_operations_in_binary_frames() {
+        let state = new_arc_state().await;
+        let socket = Arc::new(Mutex::new(integration_test_setup(state.clone()).await));
+        let file_id = Uuid::new_v4();
+        let user = new_user();
+        let session_id = user.session_id;
+        let request = MessageRequest::EnterRoom {
+            session_id,
+            user_id: user.user_id,
+            file_id,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            email: user.email,
+            image: user.image,
+            sheet_id: user.state.sheet_id,
+            selection: String::new(),
+            cell_edit: CellEdit::default(),
+            viewport: "initial viewport".to_string(),
+            follow: None,
+            protocol_version: Some(BINARY_PROTOCOL_VERSION),
+        };
+
+        // EnterRoom and UsersInRoom are sent as JSON
+        integration_test_send_and_receive(&socket, request, true, 2).await;
+
+        let protocol_version = state
+            .get_room(&file_id)
+            .await
+            .unwrap()
+            .get_user(&session_id)
+            .unwrap()
+            .protocol_version;
+        assert_eq!(protocol_version, BINARY_PROTOCOL_VERSION);
+
+        let operations = vec![Operation::SetSheetName {
+            sheet_id: SheetId::new(),
+            name: "test".to_string(),
+        }];
+        let id = Uuid::new_v4();
+        let compressed_ops = Transaction::serialize_and_compress(&operations).unwrap();
+        let request = BinaryRequest::Transaction {
+            id,
+            session_id,
+            file_id,
+            operations: compressed_ops.clone(),
+        };
+        let frame = serialize(&SerializationFormat::Bincode, request).unwrap();
+        socket
+            .lock()
+            .await
+            .send(tungstenite::Message::binary(frame))
+            .await
+            .unwrap();
+
+        let response = match socket.lock().await.next().await {
+            Some(Ok(tungstenite::Message::Binary(frame))) => {
+                deserialize::<BinaryResponse>(&SerializationFormat::Bincode, &frame).unwrap()
+            }
+            other => panic!("expected a binary message but got {other:?}"),
+        };
+        let expected = BinaryResponse::Transaction(BinaryTransaction {
+            id,
+            file_id,
+            sequence_num: 1,
+            operations: compressed_ops,
+        });
+        assert_eq!(response, expected);
+    }
 }
@@ -0,0 +1,60 @@
+//! Checkpoints
+//!
+//! Where the multiplayer server learns which transactions have been written to
+//! a file's checkpoint.  State holds a `Checkpoints` so tests can replace the
+//! quadratic api with `MockCheckpoints`.
+
+use futures::future::BoxFuture;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+use crate::error::Result;
+
+pub(crate) trait Checkpoints: Debug + Send + Sync {
+    /// The sequence number of the last checkpoint written for a file.
+    fn sequence_num(&self, file_id: Uuid) -> BoxFuture<'_, Result<u64>>;
+}
+
+/// Reads checkpoints from the quadratic api.
+#[derive(Debug)]
+pub(crate) struct ApiCheckpoints {
+    pub(crate) quadratic_api_uri: String,
+    pub(crate) m2m_auth_token: String,
+}
+
+impl Checkpoints for ApiCheckpoints {
+    fn sequence_num(&self, file_id: Uuid) -> BoxFuture<'_, Result<u64>> {
+        Box::pin(async move {
+            let checkpoint = quadratic_rust_shared::quadratic_api::get_file_checkpoint(
+                &self.quadratic_api_uri,
+                &self.m2m_auth_token,
+                &file_id,
+            )
+            .await?;
+
+            Ok(checkpoint.sequence_number)
+        })
+    }
+}
+
+/// Checkpoints set by tests.  Files without a checkpoint are at 0.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct MockCheckpoints {
+    sequence_nums: dashmap::DashMap<Uuid, u64>,
+}
+
+#[cfg(test)]
+impl MockCheckpoints {
+    pub(crate) fn set(&self, file_id: Uuid, sequence_num: u64) {
+        self.sequence_nums.insert(file_id, sequence_num);
+    }
+}
+
+#[cfg(test)]
+impl Checkpoints for MockCheckpoints {
+    fn sequence_num(&self, file_id: Uuid) -> BoxFuture<'_, Result<u64>> {
+        let sequence_num = self.sequence_nums.get(&file_id).map_or(0, |s| *s);
+        Box::pin(async move { Ok(sequence_num) })
+    }
+}
@@ -1,6 +1,7 @@
 use uuid::Uuid;
 
 use crate::error::{MpError, Result};
+use crate::message::frame::JSON_PROTOCOL_VERSION;
 use crate::state::State;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -53,6 +54,22 @@ impl State {
         Ok(connection)
     }
 
+    /// Get the protocol version negotiated by the user of a connection.
+    /// Connections that haven't entered a room yet speak JSON.
+    pub(crate) async fn protocol_version(&self, connection_id: Uuid) -> u32 {
+        let protocol_version = async {
+            let connection = self.get_connection(connection_id).await?;
+            let user = self
+                .get_room(&connection.file_id)
+                .await?
+                .get_user(&connection.session_id)?;
+
+            Ok::<_, MpError>(user.protocol_version)
+        };
+
+        protocol_version.await.unwrap_or(JSON_PROTOCOL_VERSION)
+    }
+
     /// Removes a connection from the state.  If the connection is in a room, leave the room.
     #[tracing::instrument(level = "trace")]
     pub(crate) async fn remove_connection(&self, connection: &Connection) -> Result<Option<Uuid>> {
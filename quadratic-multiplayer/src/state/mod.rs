@@ -3,7 +3,10 @@
 //! Store information about the state of the application in a send + sync
 //! struct.  All access and mutations to state should be performed here.
 
+pub mod checkpoints;
 pub mod connection;
+pub mod presence;
+pub mod protections;
 pub mod pubsub;
 pub mod room;
 pub mod settings;
@@ -14,7 +17,8 @@ use jsonwebtoken::jwk::JwkSet;
 use quadratic_rust_shared::pubsub::redis_streams::RedisStreamsConfig;
 use quadratic_rust_shared::pubsub::Config as PubSubConfig;
 use std::collections::HashMap;
-use tokio::sync::Mutex;
+use std::sync::Arc;
+use tokio::sync::{Mutex, Notify};
 use uuid::Uuid;
 
 use crate::config::Config;
@@ -22,19 +26,46 @@ use crate::error::Result;
 use crate::state::room::Room;
 use crate::state::settings::Settings;
 
+use self::checkpoints::{ApiCheckpoints, Checkpoints};
 use self::connection::Connection;
+use self::protections::{FilesProtectionsLoader, ProtectionsLoader};
 use self::pubsub::PubSub;
 
 #[derive(Debug)]
 pub(crate) struct State {
+    // identifies this instance in messages shared with other instances
+    pub(crate) instance_id: Uuid,
     pub(crate) rooms: Mutex<DashMap<Uuid, Room>>,
+    // notified when a room is created or removed, so that the cluster
+    // subscriptions follow the rooms with users on this instance
+    pub(crate) rooms_changed: Notify,
     pub(crate) connections: Mutex<HashMap<Uuid, Connection>>,
     pub(crate) pubsub: Mutex<PubSub>,
     pub(crate) settings: Settings,
+    pub(crate) checkpoints: Arc<dyn Checkpoints>,
+    pub(crate) protections_loader: Arc<dyn ProtectionsLoader>,
 }
 
 impl State {
     pub(crate) async fn new(config: &Config, jwks: Option<JwkSet>) -> Result<Self> {
+        let checkpoints = Arc::new(ApiCheckpoints {
+            quadratic_api_uri: config.quadratic_api_uri.to_owned(),
+            m2m_auth_token: config.m2m_auth_token.to_owned(),
+        });
+        let protections_loader = Arc::new(FilesProtectionsLoader {
+            quadratic_files_uri: config.quadratic_files_uri.to_owned(),
+        });
+
+        Self::new_with_sources(config, jwks, checkpoints, protections_loader).await
+    }
+
+    /// Create the state with the services that files are read from
+    pub(crate) async fn new_with_sources(
+        config: &Config,
+        jwks: Option<JwkSet>,
+        checkpoints: Arc<dyn Checkpoints>,
+        protections_loader: Arc<dyn ProtectionsLoader>,
+    ) -> Result<Self> {
         let pubsub_config = PubSubConfig::RedisStreams(RedisStreamsConfig {
             host: config.pubsub_host.to_owned(),
             port: config.pubsub_port.to_owned(),
@@ -43,10 +74,14 @@ impl State {
         });
 
         Ok(State {
+            instance_id: Uuid::new_v4(),
             rooms: Mutex::new(DashMap::new()),
+            rooms_changed: Notify::new(),
             connections: Mutex::new(HashMap::new()),
             pubsub: Mutex::new(PubSub::new(pubsub_config).await?),
             settings: Settings::new(config, jwks).await,
+            checkpoints,
+            protections_loader,
         })
     }
 }
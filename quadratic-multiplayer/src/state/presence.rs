@@ -0,0 +1,228 @@
+//! Presence
+//!
+//! The users in a room are shared between multiplayer instances through a
+//! redis hash per room, keyed by session id.  Each instance writes the users
+//! connected to it, and reads the hash to list everyone in the room.  Entries
+//! of users whose instance stopped without removing them are dropped once
+//! their heartbeat is stale.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::get_room;
+use crate::message::response::MessageResponse;
+use crate::state::user::User;
+use crate::state::State;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Presence {
+    user: User,
+    last_heartbeat: DateTime<Utc>,
+}
+
+/// Key of the hash of users in a room
+fn presence_key(file_id: &Uuid) -> String {
+    format!("multiplayer:{file_id}:users")
+}
+
+/// Key of the counter used to assign user indices in a room
+fn user_index_key(file_id: &Uuid) -> String {
+    format!("multiplayer:{file_id}:user_index")
+}
+
+fn is_stale(last_heartbeat: &DateTime<Utc>, heartbeat_timeout_s: i64) -> bool {
+    last_heartbeat.timestamp() + heartbeat_timeout_s < Utc::now().timestamp()
+}
+
+impl State {
+    /// Get the next user index in a room.  Indices are unique across instances.
+    pub(crate) async fn next_user_index(&self, file_id: &Uuid) -> Result<usize> {
+        let index = self
+            .pubsub
+            .lock()
+            .await
+            .connection
+            .increment(&user_index_key(file_id))
+            .await?;
+
+        Ok(index.saturating_sub(1) as usize)
+    }
+
+    /// Share a user's presence in a room with the other instances
+    pub(crate) async fn set_presence(&self, file_id: &Uuid, user: &User) -> Result<()> {
+        let presence = Presence {
+            user: user.to_owned(),
+            last_heartbeat: user.last_heartbeat,
+        };
+
+        self.pubsub
+            .lock()
+            .await
+            .connection
+            .hash_set(
+                &presence_key(file_id),
+                &user.session_id.to_string(),
+                &serde_json::to_vec(&presence)?,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Share the presence of a user connected to this instance, reading the
+    /// user from the room.  Users that are no longer in the room are ignored.
+    pub(crate) async fn sync_presence(&self, file_id: Uuid, session_id: &Uuid) -> Result<()> {
+        let user = get_room!(self, file_id)?.get_user(session_id);
+
+        match user {
+            Ok(user) => self.set_presence(&file_id, &user).await,
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Remove a user's presence in a room
+    pub(crate) async fn remove_presence(&self, file_id: &Uuid, session_id: &Uuid) -> Result<()> {
+        self.pubsub
+            .lock()
+            .await
+            .connection
+            .hash_remove(&presence_key(file_id), &session_id.to_string())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove the presence of users with a stale heartbeat, regardless of the
+    /// instance they were connected to.  Returns the number of users removed.
+    pub(crate) async fn remove_stale_presence(
+        &self,
+        file_id: &Uuid,
+        heartbeat_timeout_s: i64,
+    ) -> Result<usize> {
+        let stale = self
+            .presence(file_id)
+            .await?
+            .into_iter()
+            .filter(|presence| is_stale(&presence.last_heartbeat, heartbeat_timeout_s))
+            .collect::<Vec<_>>();
+
+        for presence in stale.iter() {
+            tracing::info!(
+                "Removing stale presence of user {} in room {}",
+                presence.user.session_id,
+                file_id
+            );
+
+            self.remove_presence(file_id, &presence.user.session_id)
+                .await?;
+        }
+
+        Ok(stale.len())
+    }
+
+    /// List the users in a room across all instances, ordered by index.
+    /// Users connected to this instance are read from the room.
+    pub(crate) async fn users_in_room(&self, file_id: &Uuid) -> Result<Vec<User>> {
+        let heartbeat_timeout_s = self.settings.heartbeat_timeout_s;
+        let file_id = file_id.to_owned();
+        let mut users = match get_room!(self, file_id) {
+            Ok(room) => room
+                .users
+                .iter()
+                .map(|user| user.to_owned())
+                .collect::<Vec<_>>(),
+            Err(_) => vec![],
+        };
+
+        for presence in self.presence(&file_id).await? {
+            let is_local = users
+                .iter()
+                .any(|user| user.session_id == presence.user.session_id);
+
+            if !is_local && !is_stale(&presence.last_heartbeat, heartbeat_timeout_s) {
+                let mut user = presence.user;
+                user.last_heartbeat = presence.last_heartbeat;
+                users.push(user);
+            }
+        }
+
+        users.sort_by_key(|user| user.index);
+
+        Ok(users)
+    }
+
+    /// Create a UsersInRoom message for a room
+    pub(crate) async fn users_in_room_response(&self, file_id: &Uuid) -> Result<MessageResponse> {
+        let users = self.users_in_room(file_id).await?;
+
+        Ok(MessageResponse::from((users, &self.settings.min_version)))
+    }
+
+    async fn presence(&self, file_id: &Uuid) -> Result<Vec<Presence>> {
+        let presence = self
+            .pubsub
+            .lock()
+            .await
+            .connection
+            .hash_get_all(&presence_key(file_id))
+            .await?
+            .into_values()
+            .flat_map(|value| serde_json::from_slice::<Presence>(&value))
+            .collect();
+
+        Ok(presence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::connection::PreConnection;
+    use crate::test_util::{new_arc_state, new_user};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn shares_users_between_instances() {
+        let state_1 = new_arc_state().await;
+        let state_2 = new_arc_state().await;
+        let file_id = Uuid::new_v4();
+        let mut user_1 = new_user();
+        let mut user_2 = new_user();
+
+        state_1
+            .enter_room(file_id, &mut user_1, PreConnection::new(None), 0)
+            .await
+            .unwrap();
+        state_2
+            .enter_room(file_id, &mut user_2, PreConnection::new(None), 0)
+            .await
+            .unwrap();
+
+        // indices are unique across instances
+        assert_eq!((user_1.index, user_2.index), (0, 1));
+
+        let expected = vec![user_1.clone(), user_2.clone()];
+        assert_eq!(state_1.users_in_room(&file_id).await.unwrap(), expected);
+        assert_eq!(state_2.users_in_room(&file_id).await.unwrap(), expected);
+
+        state_2
+            .leave_room(file_id, &user_2.session_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            state_1.users_in_room(&file_id).await.unwrap(),
+            vec![user_1.clone()]
+        );
+
+        // user_1's instance stops without removing them
+        state_1.rooms.lock().await.remove(&file_id);
+        assert_eq!(state_2.users_in_room(&file_id).await.unwrap(), vec![user_1]);
+        assert_eq!(
+            state_2.remove_stale_presence(&file_id, -1).await.unwrap(),
+            1
+        );
+        assert_eq!(state_2.users_in_room(&file_id).await.unwrap(), vec![]);
+    }
+}
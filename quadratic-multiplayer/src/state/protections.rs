@@ -0,0 +1,293 @@
+//! Protected Ranges and Comments
+//!
+//! Transactions that change protected ranges are rejected unless the user can
+//! edit them, and comments can only be changed by their authors or users who
+//! can edit the file.  The server does not load files, so a room's protected
+//! ranges and comment authors are loaded from quadratic-files the first time
+//! they are needed and kept up to date by applying the transactions in the
+//! pubsub channel after them.  State holds a `ProtectionsLoader` so tests can
+//! replace quadratic-files with `MockProtectionsLoader`.
+
+use futures::future::BoxFuture;
+use quadratic_core::controller::operations::comments::{
+    apply_comment_operations, validate_comments, CommentAuthors,
+};
+use quadratic_core::controller::operations::operation::Operation;
+use quadratic_core::controller::operations::protection::{
+    apply_protection_operations, validate_protections, SheetProtections,
+};
+use quadratic_core::controller::transaction::Transaction;
+use quadratic_core::grid::sheet::protections::{ProtectionEditor, ProtectionRole};
+use quadratic_core::grid::{sheet::protections::Protections, SheetId};
+use quadratic_rust_shared::quadratic_api::{get_client, FilePermRole};
+use serde::Deserialize;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+use crate::error::{MpError, Result};
+use crate::get_room;
+use crate::state::user::User;
+
+use super::State;
+
+#[derive(Debug, Deserialize)]
+struct ProtectionsResponse {
+    sequence_num: u64,
+    protections: Vec<(SheetId, Protections)>,
+    #[serde(default)]
+    comment_authors: CommentAuthors,
+}
+
+/// Protected ranges and comment authors of a file, and the sequence number
+/// they were read at.
+pub(crate) type LoadedProtections = (u64, SheetProtections, CommentAuthors);
+
+pub(crate) trait ProtectionsLoader: Debug + Send + Sync {
+    /// Load the protected ranges and comment authors of a file.
+    fn load<'a>(&'a self, file_id: Uuid, jwt: &'a str) -> BoxFuture<'a, Result<LoadedProtections>>;
+}
+
+/// Loads protected ranges from quadratic-files.
+#[derive(Debug)]
+pub(crate) struct FilesProtectionsLoader {
+    pub(crate) quadratic_files_uri: String,
+}
+
+impl ProtectionsLoader for FilesProtectionsLoader {
+    fn load<'a>(&'a self, file_id: Uuid, jwt: &'a str) -> BoxFuture<'a, Result<LoadedProtections>> {
+        Box::pin(async move {
+            let url = format!("{}/protections/{file_id}", self.quadratic_files_uri);
+            let response = get_client(&url, jwt)
+                .send()
+                .await?
+                .error_for_status()
+                .map_err(|e| MpError::FileService(e.to_string()))?
+                .json::<ProtectionsResponse>()
+                .await?;
+
+            Ok((
+                response.sequence_num,
+                response.protections.into_iter().collect(),
+                response.comment_authors,
+            ))
+        })
+    }
+}
+
+/// Protected ranges set by tests.  Other files start empty.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct MockProtectionsLoader {
+    files: dashmap::DashMap<Uuid, LoadedProtections>,
+}
+
+#[cfg(test)]
+impl MockProtectionsLoader {
+    pub(crate) fn set(&self, file_id: Uuid, loaded: LoadedProtections) {
+        self.files.insert(file_id, loaded);
+    }
+}
+
+#[cfg(test)]
+impl ProtectionsLoader for MockProtectionsLoader {
+    fn load<'a>(
+        &'a self,
+        file_id: Uuid,
+        _jwt: &'a str,
+    ) -> BoxFuture<'a, Result<LoadedProtections>> {
+        let loaded = self
+            .files
+            .get(&file_id)
+            .map(|loaded| loaded.clone())
+            .unwrap_or_default();
+        Box::pin(async move { Ok(loaded) })
+    }
+}
+
+impl From<&User> for ProtectionEditor {
+    fn from(user: &User) -> Self {
+        // users who can delete a file own or manage it
+        let roles = if user.permissions.contains(&FilePermRole::FileDelete) {
+            vec![ProtectionRole::Owner]
+        } else {
+            vec![]
+        };
+
+        ProtectionEditor {
+            user_id: user.user_id.to_owned(),
+            roles,
+        }
+    }
+}
+
+impl User {
+    /// The author name of the user's comments.
+    pub(crate) fn comment_author(&self) -> String {
+        if self.email.is_empty() {
+            format!("{} {}", self.first_name, self.last_name)
+                .trim()
+                .to_string()
+        } else {
+            self.email.to_owned()
+        }
+    }
+}
+
+impl State {
+    /// Get the protected ranges and comment authors of a room as of its
+    /// latest transaction.
+    pub(crate) async fn get_protections(
+        &self,
+        file_id: Uuid,
+        jwt: &str,
+    ) -> Result<(SheetProtections, CommentAuthors)> {
+        let (sequence_num, cached) = get_room!(self, file_id).map(|room| {
+            (
+                room.protections_sequence_num,
+                room.protections
+                    .clone()
+                    .map(|protections| (protections, room.comment_authors.clone())),
+            )
+        })?;
+
+        let (mut sequence_num, mut protections, mut comment_authors) = match cached {
+            Some((protections, comment_authors)) => (sequence_num, protections, comment_authors),
+            None => self.protections_loader.load(file_id, jwt).await?,
+        };
+
+        // apply the transactions since the protections were read
+        let mut transactions = self
+            .get_messages_from_pubsub(&file_id, sequence_num + 1)
+            .await?;
+        transactions.sort_by_key(|transaction| transaction.sequence_num);
+
+        for transaction in transactions {
+            let operations =
+                Transaction::decompress_and_deserialize::<Vec<Operation>>(&transaction.operations)
+                    .map_err(|e| MpError::Serialization(e.to_string()))?;
+            apply_protection_operations(&operations, &mut protections);
+            apply_comment_operations(&operations, &mut comment_authors);
+            sequence_num = transaction.sequence_num;
+        }
+
+        if let Some(mut room) = self.rooms.lock().await.get_mut(&file_id) {
+            room.protections = Some(protections.clone());
+            room.comment_authors = comment_authors.clone();
+            room.protections_sequence_num = sequence_num;
+        }
+
+        Ok((protections, comment_authors))
+    }
+
+    /// Validates that a user's operations only change the comments and, unless
+    /// `comments_only` is true, the protected ranges that they can edit.
+    /// Comments are allowed in protected ranges.
+    pub(crate) async fn validate_protections(
+        &self,
+        file_id: Uuid,
+        session_id: Uuid,
+        jwt: &str,
+        operations: &[Operation],
+        comments_only: bool,
+    ) -> Result<()> {
+        let user = get_room!(self, file_id)?.get_user(&session_id)?;
+        let (mut protections, mut comment_authors) = self.get_protections(file_id, jwt).await?;
+
+        // the pubsub channel is the source of truth, so the changes are only
+        // kept once the transaction is sequenced
+        if !comments_only {
+            validate_protections(operations, &mut protections, &ProtectionEditor::from(&user))?;
+        }
+        validate_comments(
+            operations,
+            &mut comment_authors,
+            &user.comment_author(),
+            user.permissions.contains(&FilePermRole::FileEdit),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quadratic_core::cell_values::CellValues;
+    use quadratic_core::grid::sheet::protections::ProtectedRange;
+    use quadratic_core::{CellValue, SheetPos};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::state::checkpoints::MockCheckpoints;
+    use crate::state::connection::PreConnection;
+    use crate::test_util::{new_state_with_sources, new_user, setup};
+
+    #[tokio::test]
+    async fn protection_editor_from_user() {
+        let (_, _, _, _, mut user, _) = setup().await;
+        user.permissions = vec![FilePermRole::FileView, FilePermRole::FileEdit];
+        assert_eq!(ProtectionEditor::from(&user).roles, vec![]);
+
+        user.permissions.push(FilePermRole::FileDelete);
+        let editor = ProtectionEditor::from(&user);
+        assert_eq!(editor.user_id, user.user_id);
+        assert_eq!(editor.roles, vec![ProtectionRole::Owner]);
+    }
+
+    #[tokio::test]
+    async fn gets_protections_from_pubsub() {
+        let (_, state, _, file_id, _, _) = setup().await;
+        let sheet_id = SheetId::new();
+        let range = ProtectedRange::new(None, vec![], vec![]);
+        let operations = vec![Operation::SetProtectedRange {
+            sheet_id,
+            range: range.clone(),
+        }];
+        let compressed = Transaction::serialize_and_compress(&operations).unwrap();
+        state
+            .push_sequenced_pubsub(Uuid::new_v4(), file_id, compressed)
+            .await
+            .unwrap();
+
+        let (protections, _) = state.get_protections(file_id, "").await.unwrap();
+        assert_eq!(protections[&sheet_id].get(range.id), Some(&range));
+
+        let room = state.get_room(&file_id).await.unwrap();
+        assert_eq!(room.protections_sequence_num, 1);
+        assert_eq!(room.protections, Some(protections));
+    }
+
+    #[tokio::test]
+    async fn loads_protections_once() {
+        let loader = Arc::new(MockProtectionsLoader::default());
+        let state =
+            new_state_with_sources(Arc::new(MockCheckpoints::default()), loader.clone()).await;
+        let file_id = Uuid::new_v4();
+        let mut user = new_user();
+        user.permissions = vec![FilePermRole::FileView, FilePermRole::FileEdit];
+        state
+            .enter_room(file_id, &mut user, PreConnection::new(None), 0)
+            .await
+            .unwrap();
+
+        // another user protects a sheet in the loaded file
+        let sheet_id = SheetId::new();
+        let range = ProtectedRange::new(None, vec!["another user".into()], vec![]);
+        let protections = SheetProtections::from([(sheet_id, Protections::from_iter([range]))]);
+        loader.set(file_id, (0, protections.clone(), CommentAuthors::new()));
+
+        let operations = [Operation::SetCellValues {
+            sheet_pos: SheetPos::new(sheet_id, 1, 1),
+            values: CellValues::from(CellValue::Text("hello".into())),
+        }];
+        let error = state
+            .validate_protections(file_id, user.session_id, "", &operations, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MpError::ProtectedRange(_)));
+
+        // the cached protections are used after they are loaded
+        loader.set(file_id, Default::default());
+        let (cached, _) = state.get_protections(file_id, "").await.unwrap();
+        assert_eq!(cached, protections);
+    }
+}
@@ -1,15 +1,46 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use quadratic_core::controller::operations::operation::Operation;
+use quadratic_core::controller::operations::validation::{validate_operations, OperationLimits};
 use quadratic_core::controller::transaction::{Transaction, TransactionServer};
+use quadratic_core::grid::SheetId;
 use quadratic_rust_shared::pubsub::{
-    redis_streams::RedisConnection, Config as PubSubConfig, PubSub as PubSubTrait,
+    redis_streams::{RedisConnection, SetUpdate},
+    Config as PubSubConfig, PubSub as PubSubTrait,
 };
 use uuid::Uuid;
 
 use crate::error::{MpError, Result};
+use crate::get_room;
 
 use super::State;
 
 pub static GROUP_NAME: &str = "quadratic-multiplayer-1";
 
+/// Prefix of the channels that instances use to forward broadcasts to users on
+/// other instances
+pub static CLUSTER_CHANNEL: &str = "quadratic-multiplayer-cluster";
+
+/// Channel of the broadcasts for a file, which instances only subscribe to
+/// while they have users in its room
+pub(crate) fn cluster_channel(file_id: &Uuid) -> String {
+    format!("{CLUSTER_CHANNEL}:{file_id}")
+}
+
+/// Key of the sequence number counter shared by all instances for a file.
+/// The file id is a hash tag, so on a Redis Cluster the key is in the same
+/// slot as the file's stream and the other keys of a sequenced publish.
+pub(crate) fn sequence_num_key(file_id: &Uuid) -> String {
+    format!("multiplayer:{{{file_id}}}:sequence_num")
+}
+
+/// Key of the set of sheets deleted (and not restored) in a file, shared by
+/// all instances.  It is only changed together with the sequence number.
+pub(crate) fn deleted_sheets_key(file_id: &Uuid) -> String {
+    format!("multiplayer:{{{file_id}}}:deleted_sheets")
+}
+
 #[derive(Debug)]
 pub(crate) struct PubSub {
     pub(crate) config: PubSubConfig,
@@ -31,39 +62,132 @@ impl PubSub {
         Ok(connection)
     }
 
-    pub(crate) async fn push(
-        &mut self,
+    fn active_channels(&self) -> &str {
+        match self.config {
+            PubSubConfig::RedisStreams(ref config) => config.active_channels.as_str(),
+            _ => "active_channels",
+        }
+    }
+
+    fn serialize_transaction(
         id: Uuid,
         file_id: Uuid,
         operations: Vec<u8>,
         sequence_num: u64,
-    ) -> Result<u64> {
+    ) -> Result<Vec<u8>> {
         let transaction = TransactionServer {
             id,
             file_id,
             operations,
             sequence_num,
         };
-        let transaction_compressed = Transaction::serialize_and_compress(&transaction)
-            .map_err(|e| MpError::Serialization(e.to_string()))?;
 
-        let active_channels = match self.config {
-            PubSubConfig::RedisStreams(ref config) => config.active_channels.as_str(),
-            _ => "active_channels",
-        };
+        Transaction::serialize_and_compress(&transaction)
+            .map_err(|e| MpError::Serialization(e.to_string()))
+    }
+
+    pub(crate) async fn push(
+        &mut self,
+        id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+        sequence_num: u64,
+    ) -> Result<u64> {
+        let transaction_compressed =
+            Self::serialize_transaction(id, file_id, operations, sequence_num)?;
+        let active_channels = self.active_channels().to_owned();
 
         self.connection
             .publish(
                 &file_id.to_string(),
                 &sequence_num.to_string(),
                 &transaction_compressed,
-                Some(active_channels),
+                Some(&active_channels),
             )
             .await?;
 
         Ok(sequence_num)
     }
 
+    /// Get the sheets deleted (and not restored) in a file
+    pub(crate) async fn deleted_sheets(&mut self, file_id: &Uuid) -> Result<HashSet<SheetId>> {
+        self.connection
+            .set_members(&deleted_sheets_key(file_id))
+            .await?
+            .iter()
+            .map(|sheet_id| {
+                SheetId::from_str(sheet_id).map_err(|e| MpError::Serialization(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Push a transaction with the next sequence number of the file.  The
+    /// sequence number is claimed in redis together with the push, so
+    /// instances serving the same file never reuse a sequence number.
+    /// `floor` is the lowest sequence number already used for the file.
+    ///
+    /// `core_operations` are validated against the file's deleted sheets, and
+    /// the sheets they delete or restore are recorded with the push.  If
+    /// another instance claims the sequence number first, the operations are
+    /// validated again against its changes.
+    pub(crate) async fn push_sequenced(
+        &mut self,
+        id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+        floor: u64,
+        core_operations: &[Operation],
+        limits: &OperationLimits,
+    ) -> Result<u64> {
+        let key = sequence_num_key(&file_id);
+        let deleted_key = deleted_sheets_key(&file_id);
+        let active_channels = self.active_channels().to_owned();
+
+        loop {
+            // read the sequence number before the deleted sheets: if the set
+            // changes after this, so does the sequence number, and the push
+            // below fails
+            let sequence_num = self.connection.sequence_num(&key, floor).await? + 1;
+            let deleted_before = self.deleted_sheets(&file_id).await?;
+            let mut deleted_sheets = deleted_before.clone();
+            validate_operations(core_operations, limits, &mut deleted_sheets)?;
+            let set_update = SetUpdate {
+                key: &deleted_key,
+                insert: deleted_sheets
+                    .difference(&deleted_before)
+                    .map(|sheet_id| sheet_id.to_string())
+                    .collect(),
+                remove: deleted_before
+                    .difference(&deleted_sheets)
+                    .map(|sheet_id| sheet_id.to_string())
+                    .collect(),
+            };
+
+            let transaction_compressed =
+                Self::serialize_transaction(id, file_id, operations.clone(), sequence_num)?;
+
+            let published = self
+                .connection
+                .publish_sequenced(
+                    &key,
+                    floor,
+                    &file_id.to_string(),
+                    sequence_num,
+                    &transaction_compressed,
+                    Some(&active_channels),
+                    Some(set_update),
+                )
+                .await?;
+
+            if published {
+                return Ok(sequence_num);
+            }
+
+            // another instance claimed the sequence number, try the next one
+            tracing::trace!("Sequence number {sequence_num} for file {file_id} already claimed");
+        }
+    }
+
     /// Check if the connection is healthy and attempt to reconnect if not
     pub(crate) async fn reconnect_if_unhealthy(&mut self) {
         let is_healthy = self.connection.is_healthy().await;
@@ -112,6 +236,83 @@ impl State {
             .await
     }
 
+    /// Push a transaction to the transaction queue with the file's next
+    /// sequence number.  Returns the sequence number.
+    pub(crate) async fn push_sequenced_pubsub(
+        &self,
+        id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+    ) -> Result<u64> {
+        self.push_validated_pubsub(id, file_id, operations, &[])
+            .await
+    }
+
+    /// Validate a transaction's operations against the sheets deleted in the
+    /// file, then push it to the transaction queue with the file's next
+    /// sequence number.  Returns the sequence number.
+    pub(crate) async fn push_validated_pubsub(
+        &self,
+        id: Uuid,
+        file_id: Uuid,
+        operations: Vec<u8>,
+        core_operations: &[Operation],
+    ) -> Result<u64> {
+        let floor = get_room!(self, file_id)?.sequence_num;
+        let sequence_num = self
+            .pubsub
+            .lock()
+            .await
+            .push_sequenced(
+                id,
+                file_id,
+                operations,
+                floor,
+                core_operations,
+                &self.settings.operation_limits,
+            )
+            .await?;
+
+        if let Some(mut room) = self.rooms.lock().await.get_mut(&file_id) {
+            room.sequence_num = room.sequence_num.max(sequence_num);
+        }
+
+        Ok(sequence_num)
+    }
+
+    /// Get the sheets deleted (and not restored) in a file by any instance
+    pub(crate) async fn get_deleted_sheets_pubsub(
+        &self,
+        file_id: &Uuid,
+    ) -> Result<HashSet<SheetId>> {
+        self.pubsub.lock().await.deleted_sheets(file_id).await
+    }
+
+    /// Get the last sequence number used for a file by any instance
+    pub(crate) async fn get_sequence_num_pubsub(&self, file_id: &Uuid, floor: u64) -> Result<u64> {
+        let sequence_num = self
+            .pubsub
+            .lock()
+            .await
+            .connection
+            .sequence_num(&sequence_num_key(file_id), floor)
+            .await?;
+
+        Ok(sequence_num)
+    }
+
+    /// Send a message to the other multiplayer instances serving a file
+    pub(crate) async fn publish_cluster(&self, file_id: &Uuid, message: &[u8]) -> Result<()> {
+        self.pubsub
+            .lock()
+            .await
+            .connection
+            .broadcast(&cluster_channel(file_id), message)
+            .await?;
+
+        Ok(())
+    }
+
     pub(crate) async fn get_messages_from_pubsub(
         &self,
         file_id: &Uuid,
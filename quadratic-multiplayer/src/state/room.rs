@@ -1,4 +1,6 @@
 use dashmap::DashMap;
+use quadratic_core::controller::operations::comments::CommentAuthors;
+use quadratic_core::controller::operations::protection::SheetProtections;
 use serde::Serialize;
 use uuid::Uuid;
 
@@ -14,7 +16,14 @@ pub(crate) struct Room {
     pub(crate) users: DashMap<Uuid, User>,
     pub(crate) sequence_num: u64,
     pub(crate) checkpoint_sequence_num: u64,
-    pub(crate) user_index: usize,
+
+    // protected ranges and comment authors as of protections_sequence_num,
+    // loaded the first time a transaction is validated against them
+    #[serde(skip)]
+    pub(crate) protections: Option<SheetProtections>,
+    #[serde(skip)]
+    pub(crate) comment_authors: CommentAuthors,
+    pub(crate) protections_sequence_num: u64,
 }
 
 #[cfg(test)]
@@ -33,15 +42,12 @@ impl Room {
             users: DashMap::new(),
             sequence_num,
             checkpoint_sequence_num: sequence_num,
-            user_index: 0,
+            protections: None,
+            comment_authors: CommentAuthors::new(),
+            protections_sequence_num: 0,
         }
     }
 
-    pub fn increment_sequence_num(&mut self) -> u64 {
-        self.sequence_num += 1;
-        self.sequence_num
-    }
-
     pub fn get_user(&self, session_id: &Uuid) -> Result<User> {
         let user = self
             .users
@@ -50,13 +56,6 @@ impl Room {
 
         Ok(user.to_owned())
     }
-
-    /// Gets the next user index and increments the user index to prepare for the next user.
-    pub fn user_index_increment(&mut self) -> usize {
-        let index = self.user_index;
-        self.user_index += 1;
-        index
-    }
 }
 
 impl State {
@@ -68,8 +67,8 @@ impl State {
     }
 
     /// Add a user to a room.  If the room doesn't exist, it is created.  Users
-    /// are only added to a room once (DashMap).  Returns true if the user was
-    /// newly added.
+    /// are only added to a room once (DashMap).  The user's presence is shared
+    /// with other instances.  Returns true if the user was newly added.
     #[tracing::instrument(level = "trace")]
     pub(crate) async fn enter_room(
         &self,
@@ -78,12 +77,15 @@ impl State {
         mut pre_connection: PreConnection,
         sequence_num: u64,
     ) -> Result<bool> {
-        user.index = get_or_create_room!(self, file_id, sequence_num).user_index_increment();
+        get_or_create_room!(self, file_id, sequence_num);
+        user.index = self.next_user_index(&file_id).await?;
         let is_new = get_room!(self, file_id)?
             .users
             .insert(user.session_id.to_owned(), user.to_owned())
             .is_none();
 
+        self.set_presence(&file_id, user).await?;
+
         let connection = Connection::new(
             pre_connection.id,
             user.session_id,
@@ -104,6 +106,7 @@ impl State {
     #[tracing::instrument(level = "trace")]
     pub(crate) async fn leave_room(&self, file_id: Uuid, session_id: &Uuid) -> Result<bool> {
         get_mut_room!(self, file_id)?.users.remove(session_id);
+        self.remove_presence(&file_id, session_id).await?;
         let num_in_room = get_room!(self, file_id)?.users.len();
 
         tracing::info!(
@@ -124,13 +127,28 @@ impl State {
     /// Removes a room.
     pub(crate) async fn remove_room(&self, file_id: Uuid) {
         self.rooms.lock().await.remove(&file_id);
+        self.rooms_changed.notify_one();
 
         tracing::info!("Room {file_id} removed");
     }
 
-    /// Get a room's current sequence number.
+    /// Get a room's current sequence number, which may have been incremented
+    /// by another instance.
     pub(crate) async fn get_sequence_num(&self, file_id: &Uuid) -> Result<u64> {
-        Ok(get_room!(self, file_id)?.sequence_num)
+        let room_sequence_num = get_room!(self, file_id)?.sequence_num;
+
+        self.get_sequence_num_pubsub(file_id, room_sequence_num)
+            .await
+    }
+
+    /// Get the sequence number of the latest checkpoint that quadratic-files
+    /// has written for a room, and record it on the room.
+    pub(crate) async fn get_checkpoint_sequence_num(&self, file_id: &Uuid) -> Result<u64> {
+        let checkpoint_sequence_num = self.checkpoints.sequence_num(*file_id).await?;
+
+        get_mut_room!(self, file_id)?.checkpoint_sequence_num = checkpoint_sequence_num;
+
+        Ok(checkpoint_sequence_num)
     }
 }
 
@@ -164,24 +182,17 @@ macro_rules! get_or_create_room {
         let sequence_num = match get_room!($self, $file_id) {
             Ok(room) => room.sequence_num.max($sequence_num),
             Err(_) => {
-                if cfg!(test) {
-                    0
-                } else {
-                    let url = &$self.settings.quadratic_api_uri;
-                    let jwt = &$self.settings.m2m_auth_token;
-                    let response = quadratic_rust_shared::quadratic_api::get_file_checkpoint(
-                        url, jwt, &$file_id,
-                    )
+                let response = $self
+                    .checkpoints
+                    .sequence_num($file_id)
                     .await?
-                    .sequence_number
                     .max($sequence_num);
-                    tracing::info!(
-                        "Retrieved sequence number {} for room {}",
-                        response,
-                        $file_id
-                    );
-                    response
-                }
+                tracing::info!(
+                    "Retrieved sequence number {} for room {}",
+                    response,
+                    $file_id
+                );
+                response
             }
         };
 
@@ -191,6 +202,7 @@ macro_rules! get_or_create_room {
                 $file_id,
                 $sequence_num
             );
+            $self.rooms_changed.notify_one();
 
             Room::new($file_id, sequence_num)
         })
@@ -227,9 +239,7 @@ mod tests {
         let sequence_num = state.get_sequence_num(&file_id).await.unwrap();
         assert_eq!(sequence_num, 0);
 
-        get_mut_room!(state, file_id)
-            .unwrap()
-            .increment_sequence_num();
+        get_mut_room!(state, file_id).unwrap().sequence_num += 1;
         let sequence_num = state.get_sequence_num(&file_id).await.unwrap();
         assert_eq!(sequence_num, 1);
 
@@ -1,4 +1,5 @@
 use jsonwebtoken::jwk::JwkSet;
+use quadratic_core::controller::operations::validation::OperationLimits;
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
@@ -25,6 +26,10 @@ pub(crate) struct Settings {
     pub(crate) quadratic_api_uri: String,
     pub(crate) m2m_auth_token: String,
     pub(crate) min_version: MinVersion,
+    pub(crate) max_transaction_bytes: usize,
+    pub(crate) max_decompressed_transaction_bytes: usize,
+    pub(crate) heartbeat_timeout_s: i64,
+    pub(crate) operation_limits: OperationLimits,
 }
 
 impl Settings {
@@ -35,6 +40,13 @@ impl Settings {
             quadratic_api_uri: config.quadratic_api_uri.to_owned(),
             m2m_auth_token: config.m2m_auth_token.to_owned(),
             min_version: MinVersion::new().expect("Unable to load min version file"),
+            max_transaction_bytes: config.max_transaction_bytes,
+            max_decompressed_transaction_bytes: config.max_decompressed_transaction_bytes,
+            heartbeat_timeout_s: config.heartbeat_timeout_s,
+            operation_limits: OperationLimits {
+                max_operations: config.max_transaction_operations,
+                max_rect_cells: config.max_rect_cells,
+            },
         }
     }
 }
@@ -30,6 +30,8 @@ pub(crate) struct User {
     pub socket: Option<UserSocket>,
     #[serde(skip)]
     pub last_heartbeat: DateTime<Utc>,
+    #[serde(skip)]
+    pub protocol_version: u32,
 }
 
 impl PartialEq for User {
@@ -99,7 +101,9 @@ impl State {
         Ok(user)
     }
 
-    /// Remove stale users in a room.  Returns the number of users removed in the room, and the number left.
+    /// Remove stale users in a room, including the presence of stale users on
+    /// other instances.  Returns the number of users removed in the room, and
+    /// the number left.
     #[tracing::instrument(level = "trace")]
     pub(crate) async fn remove_stale_users_in_room(
         &self,
@@ -134,7 +138,11 @@ impl State {
             self.connections.lock().await.remove(&user.connection_id);
         }
 
-        Ok((stale_users.len(), num_active_users))
+        let num_stale_presence = self
+            .remove_stale_presence(&file_id, heartbeat_timeout_s)
+            .await?;
+
+        Ok((stale_users.len() + num_stale_presence, num_active_users))
     }
 
     /// Updates a user's heartbeat in a room
@@ -152,7 +160,7 @@ impl State {
                 tracing::trace!("Updating heartbeat for {session_id}");
             });
 
-        Ok(())
+        self.sync_presence(file_id, session_id).await
     }
 
     /// Updates a user's permissions in a room
@@ -168,7 +176,7 @@ impl State {
             .entry(session_id.to_owned())
             .and_modify(|user| user.permissions = permissions);
 
-        Ok(())
+        self.sync_presence(file_id, session_id).await
     }
 
     /// updates a user's state in a room
@@ -218,7 +226,7 @@ impl State {
                 user.last_heartbeat = Utc::now();
             });
 
-        Ok(())
+        self.sync_presence(*file_id, session_id).await
     }
 }
 
@@ -20,9 +20,12 @@ use tokio_tungstenite::{tungstenite, MaybeTlsStream, WebSocketStream};
 use uuid::Uuid;
 
 use crate::config::config;
+use crate::message::frame::JSON_PROTOCOL_VERSION;
 use crate::message::request::MessageRequest;
 use crate::message::response::MessageResponse;
+use crate::state::checkpoints::MockCheckpoints;
 use crate::state::connection::PreConnection;
+use crate::state::protections::MockProtectionsLoader;
 use crate::state::user::{CellEdit, User, UserState};
 use crate::state::State;
 
@@ -44,7 +47,21 @@ pub(crate) async fn setup() -> (
     User,
     User,
 ) {
-    let state = new_arc_state().await;
+    setup_with_checkpoints(Arc::new(MockCheckpoints::default())).await
+}
+
+/// Same as `setup`, with checkpoints read from `checkpoints`.
+pub(crate) async fn setup_with_checkpoints(
+    checkpoints: Arc<MockCheckpoints>,
+) -> (
+    Arc<Mutex<WebSocketStream<MaybeTlsStream<TcpStream>>>>,
+    Arc<State>,
+    Uuid,
+    Uuid,
+    User,
+    User,
+) {
+    let state = Arc::new(new_state_with_checkpoints(checkpoints).await);
     let socket = integration_test_setup(state.clone()).await;
     let socket = Arc::new(Mutex::new(socket));
     let file_id = Uuid::new_v4();
@@ -78,8 +95,24 @@ pub(crate) async fn setup() -> (
 
 /// Create new global state
 pub(crate) async fn new_state() -> State {
+    new_state_with_checkpoints(Arc::new(MockCheckpoints::default())).await
+}
+
+/// Create new global state that reads checkpoints from `checkpoints`
+pub(crate) async fn new_state_with_checkpoints(checkpoints: Arc<MockCheckpoints>) -> State {
+    new_state_with_sources(checkpoints, Arc::new(MockProtectionsLoader::default())).await
+}
+
+/// Create new global state that reads checkpoints and protected ranges from
+/// mocks
+pub(crate) async fn new_state_with_sources(
+    checkpoints: Arc<MockCheckpoints>,
+    protections_loader: Arc<MockProtectionsLoader>,
+) -> State {
     let config = config().unwrap();
-    State::new(&config, None).await.unwrap()
+    State::new_with_sources(&config, None, checkpoints, protections_loader)
+        .await
+        .unwrap()
 }
 
 /// Create new global state wrapped in an Arc
@@ -111,6 +144,7 @@ pub(crate) fn new_user() -> User {
         permissions: vec![FilePermRole::FileView, FilePermRole::FileEdit],
         socket: None,
         last_heartbeat: chrono::Utc::now(),
+        protocol_version: JSON_PROTOCOL_VERSION,
         index: 0,
     }
 }
@@ -136,6 +170,7 @@ pub(crate) async fn add_user_via_ws(
         cell_edit: CellEdit::default(),
         viewport: "initial viewport".to_string(),
         follow: None,
+        protocol_version: None,
     };
 
     // UsersInRoom and EnterRoom are sent to the client when they enter a room
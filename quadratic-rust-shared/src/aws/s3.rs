@@ -1,5 +1,8 @@
 use aws_sdk_s3::{
-    operation::{get_object::GetObjectOutput, put_object::PutObjectOutput},
+    operation::{
+        copy_object::CopyObjectOutput, delete_object::DeleteObjectOutput,
+        get_object::GetObjectOutput, head_object::HeadObjectOutput, put_object::PutObjectOutput,
+    },
     primitives::{ByteStream, SdkBody},
     Client,
 };
@@ -45,6 +48,96 @@ pub async fn upload_object(
         })
 }
 
+pub async fn delete_object(client: &Client, bucket: &str, key: &str) -> Result<DeleteObjectOutput> {
+    client
+        .delete_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error deleting file {key} from bucket {bucket}: {:?}.",
+                error
+            )))
+        })
+}
+
+/// List the keys in a bucket that start with `prefix`, following
+/// continuation tokens until all keys are returned.
+pub async fn list_objects(client: &Client, bucket: &str, prefix: &str) -> Result<Vec<String>> {
+    let mut keys = vec![];
+    let mut continuation_token = None;
+
+    loop {
+        let output = client
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix(prefix)
+            .set_continuation_token(continuation_token)
+            .send()
+            .await
+            .map_err(|error| {
+                SharedError::Aws(AwsError::S3(format!(
+                    "Error listing files with prefix {prefix} in bucket {bucket}: {:?}.",
+                    error
+                )))
+            })?;
+
+        keys.extend(
+            output
+                .contents()
+                .iter()
+                .filter_map(|object| object.key().map(ToOwned::to_owned)),
+        );
+
+        match output.next_continuation_token() {
+            Some(token) if output.is_truncated().unwrap_or(false) => {
+                continuation_token = Some(token.to_owned());
+            }
+            _ => break,
+        }
+    }
+
+    Ok(keys)
+}
+
+pub async fn copy_object(
+    client: &Client,
+    bucket: &str,
+    from: &str,
+    to: &str,
+) -> Result<CopyObjectOutput> {
+    client
+        .copy_object()
+        .copy_source(format!("{bucket}/{from}"))
+        .bucket(bucket)
+        .key(to)
+        .send()
+        .await
+        .map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error copying file {from} to {to} in bucket {bucket}: {:?}.",
+                error
+            )))
+        })
+}
+
+pub async fn head_object(client: &Client, bucket: &str, key: &str) -> Result<HeadObjectOutput> {
+    client
+        .head_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|error| {
+            SharedError::Aws(AwsError::S3(format!(
+                "Error retrieving metadata for file {key} from bucket {bucket}: {:?}.",
+                error
+            )))
+        })
+}
+
 #[cfg(test)]
 pub mod tests {
     // use aws_config::{imds::Client as ImdsClient, provider_config::ProviderConfig};
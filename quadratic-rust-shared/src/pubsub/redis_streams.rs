@@ -1,11 +1,13 @@
 use chrono::prelude::*;
 use futures_util::StreamExt;
+use lazy_static::lazy_static;
 use redis::{
     aio::{AsyncStream, Monitor, MultiplexedConnection, PubSub},
     cmd,
     streams::{StreamId, StreamKey, StreamRangeReply, StreamReadOptions, StreamReadReply},
-    AsyncCommands, Client, Value,
+    AsyncCommands, Client, Script, Value,
 };
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::{
     fmt::{self, Debug},
@@ -39,6 +41,54 @@ impl Debug for RedisConnection {
 // A message consists of a key (String) and a value (Bytes).
 type Message = (String, Vec<u8>);
 
+// Claims ARGV[2] as the next sequence number in KEYS[1] and adds the message
+// to the KEYS[2] stream in one step, so that messages published by different
+// processes are added to the stream in sequence order.  ARGV[1] is a floor for
+// the sequence number, used when the sequence key doesn't exist yet.
+//
+// If there is another key, it is a set that is updated in the same step:
+// ARGV[4] is the number of members to add, followed by the members to add and
+// then the members to remove.
+//
+// On a Redis Cluster all keys must hash to the same slot.  A stream key that
+// is a plain file id hashes the same as other keys with the file id in a hash
+// tag, e.g. `multiplayer:{<file_id>}:sequence_num`.
+const PUBLISH_SEQUENCED_SCRIPT: &str = r"
+local current = math.max(tonumber(redis.call('GET', KEYS[1]) or '0'), tonumber(ARGV[1]))
+
+if tonumber(ARGV[2]) ~= current + 1 then
+    return 0
+end
+
+redis.call('SET', KEYS[1], ARGV[2])
+redis.call('XADD', KEYS[2], ARGV[2], ARGV[2], ARGV[3])
+
+if KEYS[3] then
+    local inserted = tonumber(ARGV[4])
+    for i = 5, 4 + inserted do
+        redis.call('SADD', KEYS[3], ARGV[i])
+    end
+    for i = 5 + inserted, #ARGV do
+        redis.call('SREM', KEYS[3], ARGV[i])
+    end
+end
+
+return 1
+";
+
+lazy_static! {
+    // the script's hash is computed once, and redis caches the script itself
+    static ref PUBLISH_SEQUENCED: Script = Script::new(PUBLISH_SEQUENCED_SCRIPT);
+}
+
+/// Changes to a set that are applied together with a sequenced publish.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetUpdate<'a> {
+    pub key: &'a str,
+    pub insert: Vec<String>,
+    pub remove: Vec<String>,
+}
+
 fn client(config: Config) -> Result<Client> {
     if let Config::RedisStreams(RedisStreamsConfig {
         host,
@@ -326,6 +376,108 @@ impl super::PubSub for RedisConnection {
     }
 }
 
+impl RedisConnection {
+    /// Get the last sequence number claimed in a sequence key, or `floor` if
+    /// it is higher
+    pub async fn sequence_num(&mut self, sequence_key: &str, floor: u64) -> Result<u64> {
+        let sequence_num: Option<u64> = self.multiplex.get(sequence_key).await?;
+
+        Ok(sequence_num.unwrap_or_default().max(floor))
+    }
+
+    /// Atomically claim `sequence_num` in a sequence key and publish the
+    /// message to the channel with it as the key.  Returns false without
+    /// publishing if `sequence_num` isn't the next number in the sequence,
+    /// which happens when another process claimed it first.  `set_update` is
+    /// only applied if the message is published.
+    ///
+    /// The sequence key, channel and set must hash to the same cluster slot.
+    /// The active channels set is shared by all channels, so it is updated
+    /// after the message is published.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn publish_sequenced(
+        &mut self,
+        sequence_key: &str,
+        floor: u64,
+        channel: &str,
+        sequence_num: u64,
+        value: &[u8],
+        active_channel: Option<&str>,
+        set_update: Option<SetUpdate<'_>>,
+    ) -> Result<bool> {
+        let mut invocation = PUBLISH_SEQUENCED.key(sequence_key);
+        invocation
+            .key(channel)
+            .arg(floor)
+            .arg(sequence_num)
+            .arg(value);
+
+        match set_update {
+            Some(set_update) => invocation
+                .key(set_update.key)
+                .arg(set_update.insert.len())
+                .arg(set_update.insert)
+                .arg(set_update.remove),
+            None => invocation.arg(0),
+        };
+
+        let published: u8 = invocation.invoke_async(&mut self.multiplex).await?;
+        if published != 1 {
+            return Ok(false);
+        }
+
+        // add the channel to the active channels set
+        if let Some(active_channel) = active_channel {
+            let score = Utc::now().timestamp_millis();
+            let () = self.multiplex.zadd(active_channel, channel, score).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Increment a counter, returning the new value
+    pub async fn increment(&mut self, key: &str) -> Result<u64> {
+        let value = self.multiplex.incr(key, 1).await?;
+
+        Ok(value)
+    }
+
+    /// Set a field within a hash
+    pub async fn hash_set(&mut self, key: &str, field: &str, value: &[u8]) -> Result<()> {
+        let () = self.multiplex.hset(key, field, value).await?;
+
+        Ok(())
+    }
+
+    /// Remove a field from a hash
+    pub async fn hash_remove(&mut self, key: &str, field: &str) -> Result<()> {
+        let () = self.multiplex.hdel(key, field).await?;
+
+        Ok(())
+    }
+
+    /// Get all fields and values of a hash
+    pub async fn hash_get_all(&mut self, key: &str) -> Result<HashMap<String, Vec<u8>>> {
+        let values = self.multiplex.hgetall(key).await?;
+
+        Ok(values)
+    }
+
+    /// Get all members of a set
+    pub async fn set_members(&mut self, key: &str) -> Result<Vec<String>> {
+        let members = self.multiplex.smembers(key).await?;
+
+        Ok(members)
+    }
+
+    /// Send a message to the subscribers of a (non-stream) pubsub channel
+    pub async fn broadcast(&mut self, channel: &str, value: &[u8]) -> Result<()> {
+        let () = self.multiplex.publish(channel, value).await?;
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::vec;
@@ -531,4 +683,111 @@ pub mod tests {
         let results = connection.active_channels(&active_channels).await.unwrap();
         assert_eq!(results, vec![channels[1].clone()]);
     }
+
+    #[tokio::test]
+    async fn stream_publish_sequenced() {
+        let (config, channel) = setup();
+        let sequence_key = format!("{{{channel}}}:sequence_num");
+        let set_key = format!("{{{channel}}}:set");
+        let active_channels = Uuid::new_v4().to_string();
+
+        let mut connection = RedisConnection::new(config).await.unwrap();
+        assert_eq!(connection.sequence_num(&sequence_key, 2).await.unwrap(), 2);
+
+        // the first number must be above the floor
+        let published = connection
+            .publish_sequenced(&sequence_key, 2, &channel, 2, b"test 2", None, None)
+            .await
+            .unwrap();
+        assert!(!published);
+
+        for (sequence_num, value) in [(3, b"test 3"), (4, b"test 4")] {
+            let published = connection
+                .publish_sequenced(
+                    &sequence_key,
+                    2,
+                    &channel,
+                    sequence_num,
+                    value,
+                    Some(&active_channels),
+                    Some(SetUpdate {
+                        key: &set_key,
+                        insert: vec![format!("{sequence_num}")],
+                        remove: vec!["3".into()],
+                    }),
+                )
+                .await
+                .unwrap();
+            assert!(published);
+        }
+
+        // a number that was already claimed is rejected
+        let published = connection
+            .publish_sequenced(
+                &sequence_key,
+                2,
+                &channel,
+                4,
+                b"test 4 again",
+                None,
+                Some(SetUpdate {
+                    key: &set_key,
+                    insert: vec!["c".into()],
+                    remove: vec![],
+                }),
+            )
+            .await
+            .unwrap();
+        assert!(!published);
+
+        assert_eq!(connection.sequence_num(&sequence_key, 2).await.unwrap(), 4);
+        assert_eq!(
+            connection
+                .get_messages_from(&channel, "0", false)
+                .await
+                .unwrap(),
+            vec![
+                ("3".into(), b"test 3".to_vec()),
+                ("4".into(), b"test 4".to_vec())
+            ]
+        );
+        assert_eq!(
+            connection.active_channels(&active_channels).await.unwrap(),
+            vec![channel]
+        );
+
+        // the set is only updated by published messages
+        assert_eq!(
+            connection.set_members(&set_key).await.unwrap(),
+            vec!["4".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn hash_set_get_remove() {
+        let (config, key) = setup();
+        let mut connection = RedisConnection::new(config).await.unwrap();
+
+        assert_eq!(
+            connection
+                .increment(&format!("{key}:counter"))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            connection
+                .increment(&format!("{key}:counter"))
+                .await
+                .unwrap(),
+            2
+        );
+
+        connection.hash_set(&key, "a", b"value a").await.unwrap();
+        connection.hash_set(&key, "b", b"value b").await.unwrap();
+        connection.hash_remove(&key, "a").await.unwrap();
+
+        let values = connection.hash_get_all(&key).await.unwrap();
+        assert_eq!(values, HashMap::from([("b".into(), b"value b".to_vec())]));
+    }
 }
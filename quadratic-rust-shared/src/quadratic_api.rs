@@ -39,9 +39,9 @@ pub enum FilePermRole {
 #[serde(rename_all = "camelCase")]
 pub struct LastCheckpoint {
     pub sequence_number: u64,
-    version: String,
-    s3_key: String,
-    s3_bucket: String,
+    pub version: String,
+    pub s3_key: String,
+    pub s3_bucket: String,
 }
 
 #[derive(Debug, Deserialize)]
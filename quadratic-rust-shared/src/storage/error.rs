@@ -3,12 +3,24 @@ use thiserror::Error;
 
 #[derive(Error, Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub enum Storage {
+    #[error("Error copying key {0} to {1}: {2}")]
+    Copy(String, String, String),
+
     #[error("Error creating directory {0}: {1}")]
     CreateDirectory(String, String),
 
+    #[error("Error deleting key {0}: {1}")]
+    Delete(String, String),
+
+    #[error("Error reading metadata for key {0}: {1}")]
+    Head(String, String),
+
     #[error("Invalid key: {0}")]
     InvalidKey(String),
 
+    #[error("Error listing keys with prefix {0}: {1}")]
+    List(String, String),
+
     #[error("Error reading key {0}: {1}")]
     Read(String, String),
 
@@ -1,10 +1,11 @@
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
-use tokio::fs::{create_dir_all, File};
+use tokio::fs::{copy, create_dir_all, metadata, read_dir, remove_file, File};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-use super::Storage;
+use super::{Storage, StorageMetadata};
 use crate::error::Result;
 use crate::storage::error::Storage as StorageError;
 use crate::SharedError;
@@ -52,6 +53,99 @@ impl Storage for FileSystem {
         Ok(())
     }
 
+    /// Delete the file from the file system.
+    async fn delete(&self, key: &str) -> Result<()> {
+        let file_path = self.full_path(key, false).await?.0;
+        remove_file(file_path)
+            .await
+            .map_err(|e| Self::delete_error(key, &e))?;
+
+        Ok(())
+    }
+
+    /// List the keys that start with the prefix.  Keys are stored as
+    /// `uuid/file_name`, so only directories that can match are read.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = vec![];
+        let mut dirs = read_dir(&self.config.path)
+            .await
+            .map_err(|e| Self::list_error(prefix, &e))?;
+
+        while let Some(dir) = dirs
+            .next_entry()
+            .await
+            .map_err(|e| Self::list_error(prefix, &e))?
+        {
+            let dir_name = dir.file_name().to_string_lossy().into_owned();
+
+            // the part of the prefix that the file name must match
+            let file_prefix = if dir_name.starts_with(prefix) {
+                ""
+            } else if let Some(file_prefix) = prefix
+                .strip_prefix(&dir_name)
+                .and_then(|rest| rest.strip_prefix('-'))
+            {
+                file_prefix
+            } else {
+                continue;
+            };
+
+            if !dir
+                .file_type()
+                .await
+                .is_ok_and(|file_type| file_type.is_dir())
+            {
+                continue;
+            }
+
+            let mut files = read_dir(dir.path())
+                .await
+                .map_err(|e| Self::list_error(prefix, &e))?;
+
+            while let Some(file) = files
+                .next_entry()
+                .await
+                .map_err(|e| Self::list_error(prefix, &e))?
+            {
+                let file_name = file.file_name().to_string_lossy().into_owned();
+
+                if file_name.starts_with(file_prefix) {
+                    keys.push(format!("{dir_name}-{file_name}"));
+                }
+            }
+        }
+
+        keys.sort();
+
+        Ok(keys)
+    }
+
+    /// Copy a file within the file system.
+    async fn copy(&self, from: &str, to: &str) -> Result<()> {
+        let from_path = self.full_path(from, false).await?.0;
+        let to_path = self.full_path(to, true).await?.0;
+        copy(from_path, to_path)
+            .await
+            .map_err(|e| Self::copy_error(from, to, &e))?;
+
+        Ok(())
+    }
+
+    /// Return the size and last modified time of a file.
+    async fn head(&self, key: &str) -> Result<StorageMetadata> {
+        let file_path = self.full_path(key, false).await?.0;
+        let metadata = metadata(file_path)
+            .await
+            .map_err(|e| Self::head_error(key, &e))?;
+        let modified = metadata.modified().map_err(|e| Self::head_error(key, &e))?;
+
+        Ok(StorageMetadata {
+            key: key.to_owned(),
+            size: metadata.len(),
+            modified: DateTime::<Utc>::from(modified),
+        })
+    }
+
     /// Return the path to the file system.
     fn path(&self) -> &str {
         &self.config.path
@@ -100,7 +194,7 @@ impl FileSystem {
 
 #[cfg(test)]
 mod tests {
-    use tokio::fs::{remove_dir, remove_file};
+    use tokio::fs::{remove_dir, remove_dir_all};
     use uuid::Uuid;
 
     use super::*;
@@ -132,4 +226,40 @@ mod tests {
 
         assert_eq!(data, &read_data);
     }
+
+    #[tokio::test]
+    async fn file_system_delete_list_copy_and_head() {
+        let config = config();
+        let storage = FileSystem { config };
+        let file_id = Uuid::new_v4();
+        let key = |sequence_number: u64| format!("{file_id}-{sequence_number}.grid");
+        let data = &Bytes::from("Hello, world!");
+
+        storage.write(&key(1), data).await.unwrap();
+        storage.write(&key(2), data).await.unwrap();
+        storage.copy(&key(2), &key(3)).await.unwrap();
+        assert_eq!(&storage.read(&key(3)).await.unwrap(), data);
+
+        let metadata = storage.head(&key(3)).await.unwrap();
+        assert_eq!(metadata.key, key(3));
+        assert_eq!(metadata.size, data.len() as u64);
+        assert!(metadata.modified <= Utc::now());
+
+        storage.delete(&key(1)).await.unwrap();
+        assert!(storage.read(&key(1)).await.is_err());
+        assert!(storage.delete(&key(1)).await.is_err());
+
+        let keys = storage.list(&format!("{file_id}-")).await.unwrap();
+        assert_eq!(keys, vec![key(2), key(3)]);
+
+        let keys = storage.list(&format!("{file_id}-3")).await.unwrap();
+        assert_eq!(keys, vec![key(3)]);
+
+        let keys = storage.list(&file_id.to_string()[..8]).await.unwrap();
+        assert_eq!(keys, vec![key(2), key(3)]);
+
+        // cleanup
+        let (_, dir) = storage.full_path(&key(2), false).await.unwrap();
+        remove_dir_all(dir).await.unwrap();
+    }
 }
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use file_system::FileSystemConfig;
 use s3::S3Config;
 
@@ -21,12 +22,24 @@ pub enum StorageContainer {
     FileSystem(file_system::FileSystem),
 }
 
+/// The size and last modified time of a stored object
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageMetadata {
+    pub key: String,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+}
+
 #[async_trait]
 pub trait Storage {
     type Config;
 
     async fn read(&self, key: &str) -> Result<Bytes>;
     async fn write<'a>(&self, key: &'a str, data: &'a Bytes) -> Result<()>;
+    async fn delete(&self, key: &str) -> Result<()>;
+    async fn list(&self, prefix: &str) -> Result<Vec<String>>;
+    async fn copy(&self, from: &str, to: &str) -> Result<()>;
+    async fn head(&self, key: &str) -> Result<StorageMetadata>;
     fn path(&self) -> &str;
     fn config(&self) -> Self::Config;
 
@@ -37,6 +50,22 @@ pub trait Storage {
     fn write_error(key: &str, e: impl ToString) -> SharedError {
         SharedError::Storage(StorageError::Write(key.into(), e.to_string()))
     }
+
+    fn delete_error(key: &str, e: impl ToString) -> SharedError {
+        SharedError::Storage(StorageError::Delete(key.into(), e.to_string()))
+    }
+
+    fn list_error(prefix: &str, e: impl ToString) -> SharedError {
+        SharedError::Storage(StorageError::List(prefix.into(), e.to_string()))
+    }
+
+    fn copy_error(from: &str, to: &str, e: impl ToString) -> SharedError {
+        SharedError::Storage(StorageError::Copy(from.into(), to.into(), e.to_string()))
+    }
+
+    fn head_error(key: &str, e: impl ToString) -> SharedError {
+        SharedError::Storage(StorageError::Head(key.into(), e.to_string()))
+    }
 }
 
 // TODO(ddimaria): this is a temp hack to get around some trait issues, do something better
@@ -58,6 +87,34 @@ impl Storage for StorageContainer {
         }
     }
 
+    async fn delete(&self, key: &str) -> Result<()> {
+        match self {
+            Self::S3(s3) => s3.delete(key).await,
+            Self::FileSystem(fs) => fs.delete(key).await,
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        match self {
+            Self::S3(s3) => s3.list(prefix).await,
+            Self::FileSystem(fs) => fs.list(prefix).await,
+        }
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<()> {
+        match self {
+            Self::S3(s3) => s3.copy(from, to).await,
+            Self::FileSystem(fs) => fs.copy(from, to).await,
+        }
+    }
+
+    async fn head(&self, key: &str) -> Result<StorageMetadata> {
+        match self {
+            Self::S3(s3) => s3.head(key).await,
+            Self::FileSystem(fs) => fs.head(key).await,
+        }
+    }
+
     fn path(&self) -> &str {
         match self {
             Self::S3(s3) => s3.path(),
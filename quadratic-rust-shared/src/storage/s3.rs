@@ -1,10 +1,13 @@
 use async_trait::async_trait;
 use aws_sdk_s3::Client;
 use bytes::Bytes;
+use chrono::DateTime;
 
-use super::Storage;
+use super::{Storage, StorageMetadata};
 use crate::{
-    aws::s3::{download_object, upload_object},
+    aws::s3::{
+        copy_object, delete_object, download_object, head_object, list_objects, upload_object,
+    },
     error::Result,
 };
 
@@ -52,6 +55,57 @@ impl Storage for S3 {
         Ok(())
     }
 
+    /// Delete the object from the S3 bucket.
+    async fn delete(&self, key: &str) -> Result<()> {
+        let S3Config { client, bucket } = &self.config;
+
+        delete_object(client, bucket, key)
+            .await
+            .map_err(|e| Self::delete_error(key, &e))?;
+
+        Ok(())
+    }
+
+    /// List the keys in the S3 bucket that start with the prefix.
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let S3Config { client, bucket } = &self.config;
+
+        list_objects(client, bucket, prefix)
+            .await
+            .map_err(|e| Self::list_error(prefix, &e))
+    }
+
+    /// Copy an object within the S3 bucket.
+    async fn copy(&self, from: &str, to: &str) -> Result<()> {
+        let S3Config { client, bucket } = &self.config;
+
+        copy_object(client, bucket, from, to)
+            .await
+            .map_err(|e| Self::copy_error(from, to, &e))?;
+
+        Ok(())
+    }
+
+    /// Return the size and last modified time of an object in the S3 bucket.
+    async fn head(&self, key: &str) -> Result<StorageMetadata> {
+        let S3Config { client, bucket } = &self.config;
+
+        let output = head_object(client, bucket, key)
+            .await
+            .map_err(|e| Self::head_error(key, &e))?;
+
+        let modified = output
+            .last_modified()
+            .and_then(|modified| DateTime::from_timestamp(modified.secs(), modified.subsec_nanos()))
+            .ok_or_else(|| Self::head_error(key, "missing last modified time"))?;
+
+        Ok(StorageMetadata {
+            key: key.to_owned(),
+            size: output.content_length().unwrap_or_default().max(0) as u64,
+            modified,
+        })
+    }
+
     /// Return the S3 bucket.
     fn path(&self) -> &str {
         &self.config.bucket